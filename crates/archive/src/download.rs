@@ -0,0 +1,421 @@
+//! Downloads an archive, retrying the transient failures of the request and of the transfer
+//!
+//! Archives are downloaded with idempotent GET requests, so a request that fails with a status that
+//! may not happen again (like a 502 from a registry behind a load balancer), or a connection that
+//! drops in the middle of the transfer, is made again after an exponential backoff. A transfer
+//! that is retried starts over: the cache file is truncated and filled again from the start of the
+//! new response, so it never holds a mix of two responses, and the bytes that were already read
+//! are skipped rather than passed on again.
+
+use std::env;
+use std::fs::File;
+use std::io::{self, Read, Seek, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use super::{content_length, ArchiveError};
+use attohttpc::{ProxySettings, ResponseReader, StatusCode};
+use fs_utils::ensure_containing_dir_exists;
+use log::debug;
+
+const VOLTA_FETCH_RETRIES: &str = "VOLTA_FETCH_RETRIES";
+
+/// The number of times a failed download is retried when `VOLTA_FETCH_RETRIES` isn't set
+const DEFAULT_RETRIES: u32 = 2;
+
+/// The delay before the first retry, which doubles with each retry after it
+const BASE_DELAY: Duration = Duration::from_millis(250);
+
+/// How often, and after how long, a failed download is retried
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RetryPolicy {
+    pub(crate) retries: u32,
+    pub(crate) base_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// The policy configured with `VOLTA_FETCH_RETRIES`, ignoring values that aren't a number
+    pub(crate) fn from_env() -> Self {
+        let retries = match env::var(VOLTA_FETCH_RETRIES) {
+            Ok(value) => value.trim().parse().unwrap_or_else(|_| {
+                debug!(
+                    "Ignoring invalid value '{}' for {}",
+                    value, VOLTA_FETCH_RETRIES
+                );
+                DEFAULT_RETRIES
+            }),
+            Err(_) => DEFAULT_RETRIES,
+        };
+
+        RetryPolicy {
+            retries,
+            base_delay: BASE_DELAY,
+        }
+    }
+
+    /// The delay before the given retry, counting from 1
+    fn delay(&self, retry: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+    }
+}
+
+/// Whether a request that failed with a status may succeed when it is made again
+///
+/// Client errors are final, apart from timeouts and rate limiting. A server that doesn't implement
+/// the request won't start to by being asked again, either.
+fn is_transient_status(status: StatusCode) -> bool {
+    match status {
+        StatusCode::REQUEST_TIMEOUT | StatusCode::TOO_MANY_REQUESTS => true,
+        StatusCode::NOT_IMPLEMENTED => false,
+        _ => status.is_server_error(),
+    }
+}
+
+/// Whether an error of the request or the transfer may not happen again
+fn is_transient(error: &ArchiveError) -> bool {
+    match error {
+        ArchiveError::HttpError(status) => is_transient_status(*status),
+        ArchiveError::AttohttpcError(error) => {
+            matches!(error.kind(), attohttpc::ErrorKind::Io(_))
+        }
+        ArchiveError::IoError(error) => is_transient_io(error),
+        _ => false,
+    }
+}
+
+fn is_transient_io(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::UnexpectedEof
+    )
+}
+
+/// An archive that is being downloaded, which copies what it reads to the cache file
+pub(crate) struct Download {
+    url: String,
+    proxy: ProxySettings,
+    policy: RetryPolicy,
+    response: ResponseReader,
+    cache: File,
+    /// The number of bytes that were read, which a retried transfer skips
+    position: u64,
+    /// The length of the archive, from the first response
+    length: u64,
+    attempts: u32,
+}
+
+impl Download {
+    /// Starts to download an archive through the given proxies, retrying the request if it fails
+    /// with a transient error
+    pub(crate) fn start(
+        url: &str,
+        cache_file: &Path,
+        proxy: ProxySettings,
+        policy: RetryPolicy,
+    ) -> Result<Download, ArchiveError> {
+        let mut attempts = 0;
+        let (response, length) = loop {
+            attempts += 1;
+            match request(url, &proxy) {
+                Ok(started) => break started,
+                Err(error) if attempts <= policy.retries && is_transient(&error) => {
+                    wait_to_retry(url, &error, attempts, policy);
+                }
+                Err(error) => return Err(ArchiveError::with_attempts(error, attempts)),
+            }
+        };
+
+        ensure_containing_dir_exists(&cache_file)?;
+        let cache = File::create(cache_file)?;
+
+        Ok(Download {
+            url: url.to_owned(),
+            proxy,
+            policy,
+            response,
+            cache,
+            position: 0,
+            length,
+            attempts,
+        })
+    }
+
+    /// The length of the archive in bytes, from the `Content-Length` header
+    pub(crate) fn len(&self) -> u64 {
+        self.length
+    }
+
+    /// Makes the request again after a failed transfer, truncating the cache file and skipping
+    /// the bytes that were already read
+    fn restart(&mut self) -> Result<(), ArchiveError> {
+        self.cache.set_len(0)?;
+        self.cache.rewind()?;
+
+        let (mut response, length) = request(&self.url, &self.proxy)?;
+        if length != self.length {
+            return Err(ArchiveError::UnexpectedContentLengthError(length));
+        }
+
+        let skipped = io::copy(&mut (&mut response).take(self.position), &mut self.cache)?;
+        if skipped < self.position {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+
+        self.response = response;
+        Ok(())
+    }
+
+    /// Reads from the current response, treating a response that ends early as a failed transfer
+    fn read_response(&mut self, buf: &mut [u8]) -> Result<usize, ArchiveError> {
+        let read = self.response.read(buf)?;
+        if read == 0 && !buf.is_empty() && self.position < self.length {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(read)
+    }
+}
+
+impl Read for Download {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let error = match self.read_response(buf) {
+                Ok(read) => {
+                    self.cache.write_all(&buf[..read])?;
+                    self.position += read as u64;
+                    return Ok(read);
+                }
+                Err(error) => error,
+            };
+
+            if self.attempts > self.policy.retries || !is_transient(&error) {
+                // Leave nothing behind that a later run could mistake for a complete archive
+                let _ = self.cache.set_len(0);
+                let error = ArchiveError::with_attempts(error, self.attempts);
+                return Err(io::Error::new(io::ErrorKind::Other, error));
+            }
+
+            self.attempts += 1;
+            wait_to_retry(&self.url, &error, self.attempts - 1, self.policy);
+            while let Err(error) = self.restart() {
+                if self.attempts > self.policy.retries || !is_transient(&error) {
+                    let _ = self.cache.set_len(0);
+                    let error = ArchiveError::with_attempts(error, self.attempts);
+                    return Err(io::Error::new(io::ErrorKind::Other, error));
+                }
+                self.attempts += 1;
+                wait_to_retry(&self.url, &error, self.attempts - 1, self.policy);
+            }
+        }
+    }
+}
+
+/// Sends the request for an archive, failing on an unsuccessful status
+fn request(url: &str, proxy: &ProxySettings) -> Result<(ResponseReader, u64), ArchiveError> {
+    let (status, headers, response) = attohttpc::get(url)
+        .proxy_settings(proxy.clone())
+        .send()?
+        .split();
+
+    if !status.is_success() {
+        return Err(ArchiveError::HttpError(status));
+    }
+
+    Ok((response, content_length(&headers)?))
+}
+
+fn wait_to_retry(url: &str, error: &ArchiveError, retry: u32, policy: RetryPolicy) {
+    let delay = policy.delay(retry);
+    debug!(
+        "Downloading {} failed ({}), retrying in {}ms ({} of {})",
+        url,
+        error,
+        delay.as_millis(),
+        retry,
+        policy.retries
+    );
+    thread::sleep(delay);
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// A response of the mock server
+    #[derive(Clone, Copy)]
+    enum Reply {
+        Status(u16),
+        /// The whole body
+        Body,
+        /// The headers for the whole body, but only the first half of it
+        Truncated,
+    }
+
+    /// A server that replies to each request in turn, repeating the last reply
+    pub(crate) struct MockServer {
+        pub(crate) url: String,
+        requests: Arc<AtomicUsize>,
+    }
+
+    impl MockServer {
+        /// Serves the whole body at the given file name
+        pub(crate) fn serving(file_name: &str, body: Vec<u8>) -> Self {
+            Self::start_at(file_name, body, vec![Reply::Body])
+        }
+
+        fn start(body: Vec<u8>, replies: Vec<Reply>) -> Self {
+            Self::start_at("archive", body, replies)
+        }
+
+        fn start_at(file_name: &str, body: Vec<u8>, replies: Vec<Reply>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/{}", listener.local_addr().unwrap(), file_name);
+            let requests = Arc::new(AtomicUsize::new(0));
+
+            let counter = Arc::clone(&requests);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(mut stream) = stream else { return };
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).is_ok_and(|read| read > 2) {
+                        line.clear();
+                    }
+
+                    let index = counter.fetch_add(1, Ordering::SeqCst);
+                    let reply = replies[index.min(replies.len() - 1)];
+                    let _ = match reply {
+                        Reply::Status(status) => write!(
+                            stream,
+                            "HTTP/1.1 {} Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                            status
+                        ),
+                        Reply::Body | Reply::Truncated => {
+                            let sent = match reply {
+                                Reply::Truncated => &body[..body.len() / 2],
+                                _ => &body[..],
+                            };
+                            write!(
+                                stream,
+                                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                                body.len()
+                            )
+                            .and_then(|()| stream.write_all(sent))
+                        }
+                    };
+                }
+            });
+
+            MockServer { url, requests }
+        }
+
+        fn requests(&self) -> usize {
+            self.requests.load(Ordering::SeqCst)
+        }
+    }
+
+    /// Proxy settings that connect to the mock server directly, whatever the environment says
+    fn no_proxy() -> ProxySettings {
+        ProxySettings::builder().build()
+    }
+
+    fn policy(retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            retries,
+            base_delay: Duration::from_millis(1),
+        }
+    }
+
+    fn body() -> Vec<u8> {
+        (0..64 * 1024).map(|n| (n % 251) as u8).collect()
+    }
+
+    fn download(server: &MockServer, retries: u32) -> Result<Vec<u8>, String> {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_file = dir.path().join("archive.tar.gz");
+
+        let mut download = Download::start(&server.url, &cache_file, no_proxy(), policy(retries))
+            .map_err(|e| e.to_string())?;
+        let mut read = Vec::new();
+        download.read_to_end(&mut read).map_err(|e| e.to_string())?;
+
+        assert_eq!(std::fs::read(&cache_file).unwrap(), read);
+        Ok(read)
+    }
+
+    #[test]
+    fn retries_transient_statuses() {
+        let server = MockServer::start(
+            body(),
+            vec![Reply::Status(502), Reply::Status(503), Reply::Body],
+        );
+
+        assert_eq!(download(&server, 2).unwrap(), body());
+        assert_eq!(server.requests(), 3);
+    }
+
+    #[test]
+    fn fails_after_exhausting_retries() {
+        let server = MockServer::start(body(), vec![Reply::Status(502)]);
+
+        let error = download(&server, 2).unwrap_err();
+        assert_eq!(error, "HTTP failure (502 Bad Gateway) after 3 attempts");
+        assert_eq!(server.requests(), 3);
+
+        let dir = tempfile::tempdir().unwrap();
+        let error = Download::start(
+            &server.url,
+            &dir.path().join("archive"),
+            no_proxy(),
+            policy(1),
+        )
+        .err()
+        .unwrap();
+        assert_eq!(error.attempts(), 2);
+    }
+
+    #[test]
+    fn does_not_retry_client_errors() {
+        let server = MockServer::start(body(), vec![Reply::Status(404), Reply::Body]);
+
+        let error = download(&server, 2).unwrap_err();
+        assert_eq!(error, "HTTP failure (404 Not Found)");
+        assert_eq!(server.requests(), 1);
+
+        let server = MockServer::start(body(), vec![Reply::Status(429), Reply::Body]);
+        assert_eq!(download(&server, 2).unwrap(), body());
+        assert_eq!(server.requests(), 2);
+    }
+
+    #[test]
+    fn restarts_dropped_transfers() {
+        let server = MockServer::start(body(), vec![Reply::Truncated, Reply::Body]);
+
+        assert_eq!(download(&server, 2).unwrap(), body());
+        assert_eq!(server.requests(), 2);
+    }
+
+    #[test]
+    fn truncates_the_cache_file_after_a_failed_transfer() {
+        let server = MockServer::start(body(), vec![Reply::Truncated]);
+        let dir = tempfile::tempdir().unwrap();
+        let cache_file = dir.path().join("archive.tar.gz");
+
+        let mut download =
+            Download::start(&server.url, &cache_file, no_proxy(), policy(1)).unwrap();
+        let error = download.read_to_end(&mut Vec::new()).unwrap_err();
+
+        assert!(error.to_string().ends_with("after 2 attempts"), "{}", error);
+        assert_eq!(std::fs::metadata(&cache_file).unwrap().len(), 0);
+        assert_eq!(server.requests(), 2);
+    }
+}
@@ -1,15 +1,21 @@
 //! This crate provides types for fetching and unpacking compressed
 //! archives in tarball or zip format.
 use std::fs::File;
+use std::io::Read;
+#[cfg(unix)]
+use std::io::Seek;
 use std::path::Path;
 
 use attohttpc::header::HeaderMap;
+use attohttpc::ProxySettings;
 use headers::{ContentLength, Header, HeaderMapExt};
 use thiserror::Error;
 
+mod download;
 mod tarball;
 mod zip;
 
+use crate::download::{Download, RetryPolicy};
 pub use crate::tarball::Tarball;
 pub use crate::zip::Zip;
 
@@ -33,6 +39,39 @@ pub enum ArchiveError {
 
     #[error("{0}")]
     ZipError(#[from] zip_rs::result::ZipError),
+
+    #[error("{source} after {attempts} attempts")]
+    RetriesExhausted {
+        attempts: u32,
+        source: Box<ArchiveError>,
+    },
+}
+
+impl ArchiveError {
+    /// Records the number of attempts that were made before failing, if there was more than one.
+    fn with_attempts(self, attempts: u32) -> Self {
+        if attempts > 1 {
+            ArchiveError::RetriesExhausted {
+                attempts,
+                source: Box::new(self),
+            }
+        } else {
+            self
+        }
+    }
+
+    /// The number of times the archive was requested before failing.
+    pub fn attempts(&self) -> u32 {
+        match self {
+            ArchiveError::RetriesExhausted { attempts, .. } => *attempts,
+            // Errors of the transfer reach the unpacking as I/O errors
+            ArchiveError::IoError(error) => error
+                .get_ref()
+                .and_then(|inner| inner.downcast_ref::<ArchiveError>())
+                .map_or(1, ArchiveError::attempts),
+            _ => 1,
+        }
+    }
 }
 
 /// Metadata describing whether an archive comes from a local or remote origin.
@@ -55,23 +94,60 @@ pub trait Archive {
     fn origin(&self) -> Origin;
 }
 
+/// Start downloading a remote file to the specified file path, without unpacking it.
+///
+/// The request goes through the given proxies. Returns the length of the file, from
+/// the HTTP `"Content-Length"` header, and a reader that saves the file as it is read.
+/// Once the reader is read to the end, the saved file can be checked before it's
+/// loaded with `load_native`.
+pub fn download(
+    url: &str,
+    cache_file: &Path,
+    proxy: ProxySettings,
+) -> Result<(u64, impl Read), ArchiveError> {
+    let download = Download::start(url, cache_file, proxy, RetryPolicy::from_env())?;
+    Ok((download.len(), download))
+}
+
 cfg_if::cfg_if! {
     if #[cfg(unix)] {
         /// Load an archive in the native OS-preferred format from the specified file.
         ///
         /// On Windows, the preferred format is zip. On Unixes, the preferred format
-        /// is tarball.
-        pub fn load_native(source: File) -> Result<Box<dyn Archive>, ArchiveError> {
-            Tarball::load(source)
+        /// is tarball, but a zip archive (such as one fetched from a mirror that only
+        /// hosts zips) is recognized by its signature.
+        pub fn load_native(mut source: File) -> Result<Box<dyn Archive>, ArchiveError> {
+            let mut signature = [0; 4];
+            let is_zip = source.read_exact(&mut signature).is_ok() && signature == ZIP_SIGNATURE;
+            source.rewind()?;
+
+            if is_zip {
+                Zip::load(source)
+            } else {
+                Tarball::load(source)
+            }
         }
 
         /// Fetch a remote archive in the native OS-preferred format from the specified
         /// URL and store its results at the specified file path.
         ///
         /// On Windows, the preferred format is zip. On Unixes, the preferred format
-        /// is tarball.
+        /// is tarball, but a URL of a `.zip` file is fetched as a zip archive.
         pub fn fetch_native(url: &str, cache_file: &Path) -> Result<Box<dyn Archive>, ArchiveError> {
-            Tarball::fetch(url, cache_file)
+            if is_zip_url(url) {
+                Zip::fetch(url, cache_file)
+            } else {
+                Tarball::fetch(url, cache_file)
+            }
+        }
+
+        /// The signature at the start of a zip archive
+        const ZIP_SIGNATURE: [u8; 4] = *b"PK\x03\x04";
+
+        /// Determines whether a URL names a zip archive, ignoring any query or fragment
+        fn is_zip_url(url: &str) -> bool {
+            let path = url.split(['?', '#']).next().unwrap_or(url);
+            path.len() >= 4 && path[path.len() - 4..].eq_ignore_ascii_case(".zip")
         }
     } else if #[cfg(windows)] {
         /// Load an archive in the native OS-preferred format from the specified file.
@@ -103,3 +179,83 @@ fn content_length(headers: &HeaderMap) -> Result<u64, ArchiveError> {
         .map(|ContentLength(v)| v)
         .ok_or_else(|| ArchiveError::MissingHeaderError(ContentLength::name()))
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::download::tests::MockServer;
+    use crate::zip::tests::{mode, node_zip};
+
+    #[test]
+    fn download_saves_the_file_without_unpacking_it() {
+        let server = MockServer::serving("node-v20.11.0-linux-x64.zip", node_zip());
+        let cache = tempfile::tempdir().unwrap();
+        let cache_file = cache.path().join("node-v20.11.0-linux-x64.zip");
+
+        let proxy = ProxySettings::builder().build();
+        let (len, mut data) = download(&server.url, &cache_file, proxy).unwrap();
+        let mut read = Vec::new();
+        data.read_to_end(&mut read).unwrap();
+        assert_eq!(len, read.len() as u64);
+        assert_eq!(std::fs::read(&cache_file).unwrap(), read);
+
+        let dest = tempfile::tempdir().unwrap();
+        load_native(File::open(&cache_file).unwrap())
+            .unwrap()
+            .unpack(dest.path(), &mut |_, _| ())
+            .unwrap();
+        assert_eq!(mode(dest.path(), "bin/node"), 0o755);
+    }
+
+    #[test]
+    fn download_goes_through_the_proxy() {
+        // The mock server answers any request, so it can stand in for the proxy of a host that
+        // can't be reached directly
+        let proxy_server = MockServer::serving("proxy", node_zip());
+        let proxy_url = proxy_server.url.parse().unwrap();
+        let proxy = ProxySettings::builder().http_proxy(Some(proxy_url)).build();
+        let cache = tempfile::tempdir().unwrap();
+        let cache_file = cache.path().join("volta-2.0.2-linux.zip");
+
+        let (_, mut data) =
+            download("http://volta-release.invalid/volta.zip", &cache_file, proxy).unwrap();
+        let mut read = Vec::new();
+        data.read_to_end(&mut read).unwrap();
+        assert_eq!(read, node_zip());
+    }
+
+    #[test]
+    fn zip_urls() {
+        assert!(is_zip_url(
+            "https://mirror.example/node-v20.11.0-linux-x64.zip"
+        ));
+        assert!(is_zip_url("https://mirror.example/node.ZIP?token=abc"));
+        assert!(!is_zip_url(
+            "https://mirror.example/node-v20.11.0-linux-x64.tar.gz"
+        ));
+        assert!(!is_zip_url("https://mirror.example/download?file=node.zip"));
+    }
+
+    #[test]
+    fn fetch_native_unpacks_a_zip_from_a_zip_only_mirror() {
+        let server = MockServer::serving("node-v20.11.0-linux-x64.zip", node_zip());
+        let cache = tempfile::tempdir().unwrap();
+        let cache_file = cache.path().join("node-v20.11.0-linux-x64.tar.gz");
+        let dest = tempfile::tempdir().unwrap();
+
+        fetch_native(&server.url, &cache_file)
+            .unwrap()
+            .unpack(dest.path(), &mut |_, _| ())
+            .unwrap();
+        assert_eq!(mode(dest.path(), "bin/node"), 0o755);
+        assert_eq!(mode(dest.path(), "README.md"), 0o644);
+
+        // The cached archive is recognized as a zip when it's loaded again
+        let dest = tempfile::tempdir().unwrap();
+        load_native(File::open(&cache_file).unwrap())
+            .unwrap()
+            .unpack(dest.path(), &mut |_, _| ())
+            .unwrap();
+        assert_eq!(mode(dest.path(), "bin/node"), 0o755);
+    }
+}
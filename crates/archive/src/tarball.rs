@@ -2,14 +2,14 @@
 //! tarball in Unix operating systems.
 
 use std::fs::File;
-use std::io::Read;
+use std::io::{self, Read};
 use std::path::Path;
 
-use super::{content_length, Archive, ArchiveError, Origin};
+use super::download::{Download, RetryPolicy};
+use super::{Archive, ArchiveError, Origin};
+use attohttpc::ProxySettings;
 use flate2::read::GzDecoder;
-use fs_utils::ensure_containing_dir_exists;
 use progress_read::ProgressRead;
-use tee::TeeReader;
 
 /// A Node installation tarball.
 pub struct Tarball {
@@ -32,18 +32,18 @@ impl Tarball {
     /// Initiate fetching of a tarball from the given URL, returning a
     /// tarball that can be streamed (and that tees its data to a local
     /// file as it streams).
+    ///
+    /// Failed requests and dropped transfers are retried as configured by
+    /// `VOLTA_FETCH_RETRIES`.
     pub fn fetch(url: &str, cache_file: &Path) -> Result<Box<dyn Archive>, ArchiveError> {
-        let (status, headers, response) = attohttpc::get(url).send()?.split();
-
-        if !status.is_success() {
-            return Err(ArchiveError::HttpError(status));
-        }
-
-        let compressed_size = content_length(&headers)?;
-
-        ensure_containing_dir_exists(&cache_file)?;
-        let file = File::create(cache_file)?;
-        let data = Box::new(TeeReader::new(response, file));
+        let download = Download::start(
+            url,
+            cache_file,
+            ProxySettings::from_env(),
+            RetryPolicy::from_env(),
+        )?;
+        let compressed_size = download.len();
+        let data = Box::new(download);
 
         Ok(Box::new(Tarball {
             compressed_size,
@@ -62,9 +62,13 @@ impl Archive for Tarball {
         dest: &Path,
         progress: &mut dyn FnMut(&(), usize),
     ) -> Result<(), ArchiveError> {
-        let decoded = GzDecoder::new(ProgressRead::new(self.data, (), progress));
+        let mut data = ProgressRead::new(self.data, (), progress);
+        let decoded = GzDecoder::new(&mut data);
         let mut tarball = tar::Archive::new(decoded);
         tarball.unpack(dest)?;
+        // Unpacking stops at the end of the tar entries, so read whatever follows them (padding
+        // and the gzip trailer) to make sure that a downloaded archive is saved in full
+        io::copy(&mut data, &mut io::sink())?;
         Ok(())
     }
     fn origin(&self) -> Origin {
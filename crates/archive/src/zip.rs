@@ -1,16 +1,18 @@
 //! Provides types and functions for fetching and unpacking a Node installation
 //! zip file in Windows operating systems.
 
-use std::fs::File;
-use std::io::Read;
-use std::path::Path;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
 
-use super::{content_length, ArchiveError};
-use fs_utils::ensure_containing_dir_exists;
+use super::download::{Download, RetryPolicy};
+use super::ArchiveError;
+use attohttpc::ProxySettings;
+use fs_utils::long_path;
 use progress_read::ProgressRead;
-use tee::TeeReader;
-use verbatim::PathExt;
-use zip_rs::unstable::stream::ZipStreamReader;
+use zip_rs::read::{read_zipfile_from_stream, ZipFile};
+use zip_rs::result::{ZipError, ZipResult};
 
 use super::Archive;
 use super::Origin;
@@ -35,18 +37,18 @@ impl Zip {
 
     /// Initiate fetching of a Node zip archive from the given URL, returning
     /// a `Remote` data source.
+    ///
+    /// Failed requests and dropped transfers are retried as configured by
+    /// `VOLTA_FETCH_RETRIES`.
     pub fn fetch(url: &str, cache_file: &Path) -> Result<Box<dyn Archive>, ArchiveError> {
-        let (status, headers, response) = attohttpc::get(url).send()?.split();
-
-        if !status.is_success() {
-            return Err(ArchiveError::HttpError(status));
-        }
-
-        let compressed_size = content_length(&headers)?;
-
-        ensure_containing_dir_exists(&cache_file)?;
-        let file = File::create(cache_file)?;
-        let data = Box::new(TeeReader::new(response, file));
+        let download = Download::start(
+            url,
+            cache_file,
+            ProxySettings::from_env(),
+            RetryPolicy::from_env(),
+        )?;
+        let compressed_size = download.len();
+        let data = Box::new(download);
 
         Ok(Box::new(Zip {
             compressed_size,
@@ -65,10 +67,23 @@ impl Archive for Zip {
         dest: &Path,
         progress: &mut dyn FnMut(&(), usize),
     ) -> Result<(), ArchiveError> {
-        // Use a verbatim path to avoid the legacy Windows 260 byte path limit.
-        let dest: &Path = &dest.to_verbatim();
-        let zip = ZipStreamReader::new(ProgressRead::new(self.data, (), progress));
-        zip.extract(dest)?;
+        let mut data = TrailingBytes::new(ProgressRead::new(self.data, (), progress));
+
+        let mut entries = HashMap::new();
+        while let Some(mut file) = read_zipfile_from_stream(&mut data)? {
+            let name = file.name_raw().to_vec();
+            entries.insert(name, unpack_entry(&mut file, dest)?);
+        }
+
+        // Reading the entries stops at the central directory, after having read the start of
+        // its first header. Reading the rest of the file also makes sure that a downloaded
+        // archive is saved in full.
+        let mut central_directory = data.trailing;
+        data.inner.read_to_end(&mut central_directory)?;
+        let modes = recorded_modes(&central_directory);
+        restore_links(&modes, &entries)?;
+        restore_permissions(&modes, &entries)?;
+
         Ok(())
     }
     fn origin(&self) -> Origin {
@@ -76,12 +91,275 @@ impl Archive for Zip {
     }
 }
 
+/// The length of the fixed-size part of a local file header, which is read in full when looking
+/// for the next entry of the archive
+const LOCAL_HEADER_LEN: usize = 30;
+
+/// A reader that keeps the bytes it read last, so that the start of the central directory can be
+/// recovered after the entries before it have been read
+struct TrailingBytes<R> {
+    inner: R,
+    trailing: Vec<u8>,
+}
+
+impl<R> TrailingBytes<R> {
+    fn new(inner: R) -> Self {
+        TrailingBytes {
+            inner,
+            trailing: Vec::with_capacity(LOCAL_HEADER_LEN),
+        }
+    }
+}
+
+impl<R: Read> Read for TrailingBytes<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        let read = &buf[..len];
+
+        if len >= LOCAL_HEADER_LEN {
+            self.trailing.clear();
+            self.trailing
+                .extend_from_slice(&read[len - LOCAL_HEADER_LEN..]);
+        } else {
+            self.trailing.extend_from_slice(read);
+            let excess = self.trailing.len().saturating_sub(LOCAL_HEADER_LEN);
+            self.trailing.drain(..excess);
+        }
+
+        Ok(len)
+    }
+}
+
+/// A file or directory unpacked from the archive
+#[cfg_attr(windows, allow(dead_code))]
+struct Entry {
+    path: PathBuf,
+    /// Whether the entry is a file in a `bin` directory or a script starting with a shebang
+    looks_executable: bool,
+}
+
+fn unpack_entry(file: &mut ZipFile<'_>, dest: &Path) -> ZipResult<Entry> {
+    let path = file
+        .enclosed_name()
+        .map(|name| dest.join(name))
+        .ok_or(ZipError::InvalidArchive("Invalid file path"))?;
+
+    let in_bin_dir = in_bin_dir(&path, dest);
+    // Entries nested deeply enough to pass the legacy Windows path limit need a verbatim path
+    let path = long_path(&path).into_owned();
+
+    if file.is_dir() {
+        fs::create_dir_all(&path)?;
+        return Ok(Entry {
+            path,
+            looks_executable: false,
+        });
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut start = Vec::with_capacity(2);
+    file.by_ref().take(2).read_to_end(&mut start)?;
+
+    let mut output = File::create(&path)?;
+    output.write_all(&start)?;
+    io::copy(file, &mut output)?;
+
+    let looks_executable = start == b"#!" || in_bin_dir;
+    Ok(Entry {
+        path,
+        looks_executable,
+    })
+}
+
+/// Determines whether a file is directly within a `bin` directory of the archive
+fn in_bin_dir(path: &Path, dest: &Path) -> bool {
+    path.strip_prefix(dest)
+        .ok()
+        .and_then(Path::parent)
+        .and_then(Path::file_name)
+        == Some("bin".as_ref())
+}
+
+/// The file type bits of a Unix mode
+const S_IFMT: u32 = 0o170000;
+/// The file type of a symbolic link
+const S_IFLNK: u32 = 0o120000;
+
+fn is_link(mode: u32) -> bool {
+    mode & S_IFMT == S_IFLNK
+}
+
+/// Recreates the symbolic links of the archive
+///
+/// A link is stored as a file containing the path it points to, and only the mode recorded in the
+/// central directory tells it apart from a regular file, so the links can only be recreated once
+/// every entry has been unpacked. Links that would point outside of the archive are refused.
+fn restore_links(modes: &HashMap<&[u8], u32>, entries: &HashMap<Vec<u8>, Entry>) -> io::Result<()> {
+    for (name, entry) in entries {
+        match modes.get(name.as_slice()) {
+            Some(mode) if is_link(*mode) => {}
+            _ => continue,
+        }
+
+        let name = String::from_utf8_lossy(name);
+        let target = link_target(fs::read(&entry.path)?)?;
+        if !stays_in_archive(Path::new(&*name), &target) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Link '{}' points outside of the archive", name),
+            ));
+        }
+
+        fs::remove_file(&entry.path)?;
+        create_link(&target, &entry.path)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn link_target(contents: Vec<u8>) -> io::Result<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    Ok(OsString::from_vec(contents).into())
+}
+
+#[cfg(windows)]
+fn link_target(contents: Vec<u8>) -> io::Result<PathBuf> {
+    let target = String::from_utf8(contents)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid link target"))?;
+    Ok(target.replace('/', "\\").into())
+}
+
+/// Determines whether a link target, relative to the link at the given path in the archive,
+/// resolves to a path within the archive
+fn stays_in_archive(link: &Path, target: &Path) -> bool {
+    use std::path::Component;
+
+    let mut depth = link
+        .parent()
+        .map_or(0, |parent| parent.components().count());
+
+    for component in target.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => depth -= 1,
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+#[cfg(unix)]
+fn create_link(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn create_link(target: &Path, link: &Path) -> io::Result<()> {
+    use std::os::windows::fs::{symlink_dir, symlink_file};
+
+    let resolved = link
+        .parent()
+        .map_or(target.into(), |parent| parent.join(target));
+    if resolved.is_dir() {
+        symlink_dir(target, link)
+    } else {
+        symlink_file(target, link)
+    }
+}
+
+/// Restores the permissions of the unpacked entries
+///
+/// Archives created on Unix record the mode of each entry in the central directory, which is
+/// restored as is. Archives created elsewhere record no mode, or only DOS attributes, which never
+/// mark a file as executable. For those entries, files that look executable are made executable,
+/// so that the tools in the archive can be run.
+#[cfg(unix)]
+fn restore_permissions(
+    modes: &HashMap<&[u8], u32>,
+    entries: &HashMap<Vec<u8>, Entry>,
+) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    for (name, entry) in entries {
+        // Only the permission bits are kept, so an archive can't create setuid, setgid or sticky
+        // files. Links are skipped, as setting their permissions would change their targets.
+        let mode = match modes.get(name.as_slice()) {
+            Some(mode) if is_link(*mode) => continue,
+            Some(mode) if mode & 0o777 != 0 => mode & 0o777,
+            _ if entry.looks_executable => {
+                // Anyone who can read the file can also execute it
+                let mode = fs::metadata(&entry.path)?.permissions().mode();
+                mode | (mode & 0o444) >> 2
+            }
+            _ => continue,
+        };
+
+        fs::set_permissions(&entry.path, fs::Permissions::from_mode(mode))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(windows)]
+fn restore_permissions(
+    _modes: &HashMap<&[u8], u32>,
+    _entries: &HashMap<Vec<u8>, Entry>,
+) -> io::Result<()> {
+    Ok(())
+}
+
+/// Reads the modes recorded for the entries that were created on Unix, by entry name
+fn recorded_modes(central_directory: &[u8]) -> HashMap<&[u8], u32> {
+    const SIGNATURE: &[u8] = b"PK\x01\x02";
+    const HEADER_LEN: usize = 46;
+    const UNIX: u8 = 3;
+
+    let u16_at = |header: &[u8], offset: usize| {
+        usize::from(u16::from_le_bytes([header[offset], header[offset + 1]]))
+    };
+
+    let mut modes = HashMap::new();
+    let mut rest = central_directory;
+
+    while rest.len() >= HEADER_LEN && rest.starts_with(SIGNATURE) {
+        let name_len = u16_at(rest, 28);
+        let len = HEADER_LEN + name_len + u16_at(rest, 30) + u16_at(rest, 32);
+        if rest.len() < len {
+            break;
+        }
+
+        // The upper byte of "version made by" is the system that created the entry, and Unix
+        // systems store the mode in the upper half of the external attributes
+        let system = rest[5];
+        let mode = u32::from_le_bytes([rest[38], rest[39], rest[40], rest[41]]) >> 16;
+        if system == UNIX && mode != 0 {
+            modes.insert(&rest[HEADER_LEN..HEADER_LEN + name_len], mode);
+        }
+
+        rest = &rest[len..];
+    }
+
+    modes
+}
+
 #[cfg(test)]
 pub mod tests {
 
     use crate::zip::Zip;
     use std::fs::File;
     use std::path::PathBuf;
+    #[cfg(unix)]
+    use std::{fs, io::Cursor, io::Write, os::unix::fs::PermissionsExt, path::Path};
+    #[cfg(unix)]
+    use zip_rs::write::{SimpleFileOptions, ZipWriter};
 
     fn fixture_path(fixture_dir: &str) -> PathBuf {
         let mut cargo_manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -99,4 +377,161 @@ pub mod tests {
 
         assert_eq!(zip.compressed_size(), 214);
     }
+
+    /// Creates a zip archive of a Node distro, with the given modes for its files
+    #[cfg(unix)]
+    pub(crate) fn node_zip() -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let files = [
+            ("node-v20.11.0/bin/node", "\x7fELF", 0o755),
+            ("node-v20.11.0/lib/cli.js", "#!/usr/bin/env node\n", 0o644),
+            ("node-v20.11.0/README.md", "# Node.js\n", 0o644),
+        ];
+
+        for (name, contents, mode) in files {
+            let options = SimpleFileOptions::default()
+                .compression_method(zip_rs::CompressionMethod::Stored)
+                .unix_permissions(mode);
+            zip.start_file(name, options).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+
+        zip.finish().unwrap().into_inner()
+    }
+
+    /// Clears the attributes of every entry in the central directory of a zip archive, as in
+    /// archives created by tools that don't record them
+    #[cfg(unix)]
+    fn without_attributes(mut zip: Vec<u8>) -> Vec<u8> {
+        const CENTRAL_DIRECTORY_HEADER: &[u8] = b"PK\x01\x02";
+        const EXTERNAL_ATTRIBUTES: usize = 38;
+
+        let headers: Vec<usize> = zip
+            .windows(CENTRAL_DIRECTORY_HEADER.len())
+            .enumerate()
+            .filter(|(_, window)| *window == CENTRAL_DIRECTORY_HEADER)
+            .map(|(offset, _)| offset)
+            .collect();
+        assert_eq!(headers.len(), 3);
+
+        for offset in headers {
+            let start = offset + EXTERNAL_ATTRIBUTES;
+            zip[start..start + 4].fill(0);
+        }
+        zip
+    }
+
+    #[cfg(unix)]
+    fn unpack(zip: Vec<u8>) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = tempfile::NamedTempFile::new().unwrap();
+        fs::write(archive.path(), zip).unwrap();
+
+        Zip::load(File::open(archive.path()).unwrap())
+            .unwrap()
+            .unpack(dir.path(), &mut |_, _| ())
+            .expect("Failed to unpack zip file");
+        dir
+    }
+
+    #[cfg(unix)]
+    pub(crate) fn mode(dir: &Path, file: &str) -> u32 {
+        let path = dir.join("node-v20.11.0").join(file);
+        fs::metadata(path).unwrap().permissions().mode() & 0o777
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn unpack_restores_recorded_modes() {
+        let dir = unpack(node_zip());
+
+        assert_eq!(mode(dir.path(), "bin/node"), 0o755);
+        // The archive records modes, so a script that isn't executable in it stays that way
+        assert_eq!(mode(dir.path(), "lib/cli.js"), 0o644);
+        assert_eq!(mode(dir.path(), "README.md"), 0o644);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn unpack_drops_special_mode_bits() {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default()
+            .compression_method(zip_rs::CompressionMethod::Stored)
+            .unix_permissions(0o755);
+        zip.start_file("node-v20.11.0/bin/node", options).unwrap();
+        zip.write_all(b"\x7fELF").unwrap();
+        let mut zip = zip.finish().unwrap().into_inner();
+
+        // The writer only records permission bits, so the setuid and setgid bits are added to
+        // the external attributes of the entry directly
+        let header = zip
+            .windows(4)
+            .position(|window| window == b"PK\x01\x02")
+            .unwrap();
+        let attributes = header + 38;
+        let mode = (0o100000 | 0o6755u32) << 16;
+        zip[attributes..attributes + 4].copy_from_slice(&mode.to_le_bytes());
+        let dir = unpack(zip);
+
+        let path = dir.path().join("node-v20.11.0").join("bin").join("node");
+        assert_eq!(
+            fs::metadata(path).unwrap().permissions().mode() & 0o7777,
+            0o755
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn unpack_without_attributes_marks_likely_executables() {
+        let dir = unpack(without_attributes(node_zip()));
+
+        assert_ne!(mode(dir.path(), "bin/node") & 0o111, 0);
+        assert_ne!(mode(dir.path(), "lib/cli.js") & 0o111, 0);
+        assert_eq!(mode(dir.path(), "README.md") & 0o111, 0);
+    }
+
+    /// Creates a zip archive of a Node distro with a link to its npm CLI
+    #[cfg(unix)]
+    fn node_zip_with_link(target: &str) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default()
+            .compression_method(zip_rs::CompressionMethod::Stored)
+            .unix_permissions(0o755);
+        zip.start_file("node-v20.11.0/lib/npm-cli.js", options)
+            .unwrap();
+        zip.write_all(b"#!/usr/bin/env node\n").unwrap();
+        zip.add_symlink("node-v20.11.0/bin/npm", target, options)
+            .unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn unpack_recreates_links() {
+        let dir = unpack(node_zip_with_link("../lib/npm-cli.js"));
+
+        let link = dir.path().join("node-v20.11.0").join("bin").join("npm");
+        assert_eq!(
+            fs::read_link(&link).unwrap(),
+            Path::new("../lib/npm-cli.js")
+        );
+        assert_eq!(fs::read(&link).unwrap(), b"#!/usr/bin/env node\n");
+        assert_eq!(mode(dir.path(), "lib/npm-cli.js"), 0o755);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn unpack_refuses_links_out_of_the_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = tempfile::NamedTempFile::new().unwrap();
+        fs::write(archive.path(), node_zip_with_link("../../../etc/passwd")).unwrap();
+
+        let result = Zip::load(File::open(archive.path()).unwrap())
+            .unwrap()
+            .unpack(dir.path(), &mut |_, _| ());
+
+        assert!(result.is_err());
+        let link = dir.path().join("node-v20.11.0").join("bin").join("npm");
+        assert!(fs::symlink_metadata(link).unwrap().file_type().is_file());
+    }
 }
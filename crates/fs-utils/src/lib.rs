@@ -4,6 +4,10 @@ use std::fs;
 use std::io;
 use std::path::Path;
 
+mod verbatim;
+
+pub use verbatim::{long_path, to_verbatim};
+
 /// This creates the parent directory of the input path, assuming the input path is a file.
 pub fn ensure_containing_dir_exists<P: AsRef<Path>>(path: &P) -> io::Result<()> {
     path.as_ref()
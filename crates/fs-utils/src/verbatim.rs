@@ -0,0 +1,180 @@
+//! Converts Windows paths to verbatim (`\\?\`) paths, which aren't subject to the legacy limit of
+//! 260 characters, so that deeply nested package directories can still be created and renamed.
+//!
+//! Windows doesn't normalize verbatim paths, so the conversion resolves `.` and `..` and uses
+//! `\` as the only separator.
+
+use std::borrow::Cow;
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf, Prefix};
+
+/// The length from which a path is converted by `long_path`
+///
+/// `MAX_PATH` is 260 characters, including the terminating NUL, but directories are limited to
+/// 248 so that an 8.3 file name still fits within them.
+#[cfg(windows)]
+const LEGACY_PATH_LIMIT: usize = 248;
+
+/// Converts a path to a verbatim path if it is too long for the legacy Windows APIs
+///
+/// Shorter paths, and every path on other platforms, are returned as they are.
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStrExt;
+
+        if path.as_os_str().encode_wide().count() >= LEGACY_PATH_LIMIT {
+            return Cow::Owned(to_verbatim(path));
+        }
+    }
+
+    Cow::Borrowed(path)
+}
+
+/// Converts an absolute Windows path to the equivalent verbatim path
+///
+/// Paths that are already verbatim are returned as they are, as are paths that can't be made
+/// verbatim because they aren't absolute (such as `dir\file` or `C:file`), or because they don't
+/// have a Windows prefix (every path on other platforms).
+pub fn to_verbatim(path: &Path) -> PathBuf {
+    let mut components = path.components();
+    let prefix = match components.next() {
+        Some(Component::Prefix(prefix)) => prefix,
+        _ => return path.to_owned(),
+    };
+
+    let mut verbatim = OsString::from(r"\\?\");
+    match prefix.kind() {
+        Prefix::Verbatim(_) | Prefix::VerbatimUNC(..) | Prefix::VerbatimDisk(_) => {
+            return path.to_owned()
+        }
+        Prefix::DeviceNS(device) => verbatim.push(device),
+        Prefix::UNC(server, share) => {
+            verbatim.push(r"UNC\");
+            verbatim.push(server);
+            verbatim.push(r"\");
+            verbatim.push(share);
+        }
+        Prefix::Disk(letter) => verbatim.push(format!("{}:", char::from(letter))),
+    }
+
+    // A drive-relative path, like `C:file`, depends on the current directory of the drive
+    if matches!(prefix.kind(), Prefix::Disk(_)) && !path.has_root() {
+        return path.to_owned();
+    }
+
+    let mut parts = Vec::new();
+    for component in components {
+        match component {
+            Component::Normal(part) => parts.push(part),
+            Component::ParentDir => {
+                parts.pop();
+            }
+            Component::Prefix(_) | Component::RootDir | Component::CurDir => {}
+        }
+    }
+
+    if parts.is_empty() {
+        verbatim.push(r"\");
+    }
+    for part in parts {
+        verbatim.push(r"\");
+        verbatim.push(part);
+    }
+
+    PathBuf::from(verbatim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_paths_are_unchanged() {
+        let path = Path::new("/home/user/.volta/tools/image/packages/@scope/name");
+
+        assert_eq!(to_verbatim(path), path);
+        assert_eq!(
+            long_path(&path.join("a".repeat(300))),
+            path.join("a".repeat(300))
+        );
+    }
+
+    #[cfg(windows)]
+    fn verbatim(path: &str) -> PathBuf {
+        to_verbatim(Path::new(path))
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn converts_disk_paths() {
+        assert_eq!(
+            verbatim(r"C:\Users\me\AppData\Local\Volta"),
+            Path::new(r"\\?\C:\Users\me\AppData\Local\Volta")
+        );
+        assert_eq!(verbatim(r"d:\"), Path::new(r"\\?\d:\"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn normalizes_separators_and_dots() {
+        assert_eq!(
+            verbatim(r"C:\Users\me/./Volta\tools\..\bin"),
+            Path::new(r"\\?\C:\Users\me\Volta\bin")
+        );
+        assert_eq!(verbatim(r"C:\..\.."), Path::new(r"\\?\C:\"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn converts_unc_paths() {
+        assert_eq!(
+            verbatim(r"\\server\share\home\.volta"),
+            Path::new(r"\\?\UNC\server\share\home\.volta")
+        );
+        assert_eq!(
+            verbatim(r"\\server\share"),
+            Path::new(r"\\?\UNC\server\share\")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn converts_device_paths() {
+        assert_eq!(
+            verbatim(r"\\.\Volume{b75e2c83}\volta"),
+            Path::new(r"\\?\Volume{b75e2c83}\volta")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn keeps_verbatim_paths() {
+        for path in [
+            r"\\?\C:\Users\me\..\Volta",
+            r"\\?\UNC\server\share\volta",
+            r"\\?\Volume{b75e2c83}\volta",
+        ] {
+            assert_eq!(verbatim(path), Path::new(path));
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn keeps_relative_paths() {
+        for path in [r"tools\image", r"C:tools\image", r"\tools\image"] {
+            assert_eq!(verbatim(path), Path::new(path));
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn converts_only_long_paths() {
+        let short = Path::new(r"C:\Users\me\AppData\Local\Volta");
+        assert_eq!(long_path(short), short);
+
+        let long = short.join("a".repeat(250));
+        assert!(long_path(&long).starts_with(r"\\?\C:\"));
+    }
+}
@@ -0,0 +1,39 @@
+//! Checks that directories nested beyond the legacy Windows path limit can be created and renamed,
+//! as when installing a package with deeply nested dependencies
+
+#![cfg(windows)]
+
+use std::fs;
+use std::path::PathBuf;
+
+use fs_utils::long_path;
+
+/// Nests `node_modules` directories under `root` until the path is over 260 characters long
+fn nested_modules(root: PathBuf) -> PathBuf {
+    let mut path = root;
+    while path.as_os_str().len() <= 260 {
+        path.push("node_modules");
+        path.push("@scope-with-a-long-name");
+    }
+    path
+}
+
+#[test]
+fn creates_and_renames_long_paths() {
+    let temp = tempfile::tempdir().unwrap();
+    let staging = temp.path().join("staging");
+    let image = temp.path().join("image");
+
+    let nested = nested_modules(staging.clone());
+    assert!(nested.as_os_str().len() > 260);
+    fs::create_dir_all(long_path(&nested)).unwrap();
+    fs::write(long_path(&nested.join("package.json")), "{}").unwrap();
+
+    fs::rename(long_path(&staging), long_path(&image)).unwrap();
+
+    let moved = image.join(nested.strip_prefix(&staging).unwrap());
+    assert_eq!(
+        fs::read_to_string(long_path(&moved.join("package.json"))).unwrap(),
+        "{}"
+    );
+}
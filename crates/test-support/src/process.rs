@@ -164,6 +164,77 @@ impl ProcessBuilder {
         }
     }
 
+    /// Execute the process with its stdout and stderr attached to a pseudo-terminal, as they are
+    /// in an interactive shell, returning everything written to the terminal as `stdout`, or an
+    /// error if non-zero exit status.
+    ///
+    /// The terminal translates each newline into `\r\n`.
+    #[cfg(unix)]
+    pub fn exec_with_pty(&self) -> Result<Output, ProcessError> {
+        use std::fs::File;
+        use std::io::Read;
+        use std::os::unix::io::FromRawFd;
+        use std::process::Stdio;
+        use std::ptr;
+
+        let could_not_execute =
+            || process_error(&format!("could not execute process {}", self), None, None);
+
+        let (mut master, mut slave) = (0, 0);
+        let mut size = libc::winsize {
+            ws_row: 24,
+            ws_col: 80,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        // SAFETY: `openpty` only writes the two descriptors, which are then owned by the files
+        let (master, slave) = unsafe {
+            if libc::openpty(
+                &mut master,
+                &mut slave,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::addr_of_mut!(size),
+            ) != 0
+            {
+                return Err(could_not_execute());
+            }
+            (File::from_raw_fd(master), File::from_raw_fd(slave))
+        };
+
+        let mut command = self.build_command();
+        command
+            .stdin(Stdio::null())
+            .stdout(slave.try_clone().map_err(|_| could_not_execute())?)
+            .stderr(slave);
+        let mut child = command.spawn().map_err(|_| could_not_execute())?;
+        // The command holds the parent's copies of the terminal, which must be closed for reading
+        // to end when the process exits
+        drop(command);
+
+        // Once the process has closed the terminal, reading fails (with EIO on Linux) rather
+        // than reaching the end of the file, so the error is expected
+        let mut stdout = Vec::new();
+        let _ = (&master).read_to_end(&mut stdout);
+        let status = child.wait().map_err(|_| could_not_execute())?;
+
+        let output = Output {
+            status,
+            stdout,
+            stderr: Vec::new(),
+        };
+
+        if output.status.success() {
+            Ok(output)
+        } else {
+            Err(process_error(
+                &format!("process didn't exit successfully: {}", self),
+                Some(output.status),
+                Some(&output),
+            ))
+        }
+    }
+
     /// Converts ProcessBuilder into a `std::process::Command`
     pub fn build_command(&self) -> Command {
         let mut command = Command::new(&self.program);
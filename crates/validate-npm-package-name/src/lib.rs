@@ -1,6 +1,8 @@
 //! A Rust implementation of the validation rules from the core JS package
 //! [`validate-npm-package-name`](https://github.com/npm/validate-npm-package-name/).
 
+use std::fmt;
+
 use once_cell::sync::Lazy;
 use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use regex::Regex;
@@ -67,18 +69,74 @@ const BUILTINS: [&str; 39] = [
     "perf_hooks",
 ];
 
+/// A rule that a package name breaks
+///
+/// The `Display` implementation renders the same message as the JS package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidityIssue {
+    /// The name is empty
+    EmptyName,
+    /// The name starts with a `.`
+    LeadingPeriod,
+    /// The name starts with an `_`
+    LeadingUnderscore,
+    /// The name starts or ends with whitespace
+    LeadingOrTrailingSpaces,
+    /// The name is reserved, like `node_modules`
+    Blacklisted(String),
+    /// The name is the name of a Node core module, like `http`
+    CoreModule(String),
+    /// The name is longer than 214 characters
+    TooLong,
+    /// The name contains capital letters
+    Capitals,
+    /// The name contains one of `~'!()*`
+    SpecialChars,
+    /// The name contains characters that aren't allowed in a URL
+    NonUrlSafe,
+    /// The scope of a scoped name, like `@scope/name`, contains characters that aren't allowed in
+    /// a URL
+    InvalidScope,
+}
+
+impl fmt::Display for ValidityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidityIssue::EmptyName => f.write_str("name length must be greater than zero"),
+            ValidityIssue::LeadingPeriod => f.write_str("name cannot start with a period"),
+            ValidityIssue::LeadingUnderscore => f.write_str("name cannot start with an underscore"),
+            ValidityIssue::LeadingOrTrailingSpaces => {
+                f.write_str("name cannot contain leading or trailing spaces")
+            }
+            ValidityIssue::Blacklisted(name) => write!(f, "{} is a blacklisted name", name),
+            ValidityIssue::CoreModule(name) => write!(f, "{} is a core module name", name),
+            ValidityIssue::TooLong => {
+                f.write_str("name can no longer contain more than 214 characters")
+            }
+            ValidityIssue::Capitals => f.write_str("name can no longer contain capital letters"),
+            ValidityIssue::SpecialChars => {
+                f.write_str(r#"name can no longer contain special characters ("~\'!()*")"#)
+            }
+            // The JS package doesn't distinguish an invalid scope from the rest of the name
+            ValidityIssue::NonUrlSafe | ValidityIssue::InvalidScope => {
+                f.write_str("name can only contain URL-friendly characters")
+            }
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum Validity {
     /// Valid for new and old packages
     Valid,
 
     /// Valid only for old packages
-    ValidForOldPackages { warnings: Vec<String> },
+    ValidForOldPackages { warnings: Vec<ValidityIssue> },
 
     /// Not valid for new or old packages
     Invalid {
-        warnings: Vec<String>,
-        errors: Vec<String>,
+        warnings: Vec<ValidityIssue>,
+        errors: Vec<ValidityIssue>,
     },
 }
 
@@ -97,25 +155,25 @@ pub fn validate(name: &str) -> Validity {
     let mut errors = Vec::new();
 
     if name.is_empty() {
-        errors.push("name length must be greater than zero".into());
+        errors.push(ValidityIssue::EmptyName);
     }
 
     if name.starts_with('.') {
-        errors.push("name cannot start with a period".into());
+        errors.push(ValidityIssue::LeadingPeriod);
     }
 
     if name.starts_with('_') {
-        errors.push("name cannot start with an underscore".into());
+        errors.push(ValidityIssue::LeadingUnderscore);
     }
 
     if name.trim() != name {
-        errors.push("name cannot contain leading or trailing spaces".into());
+        errors.push(ValidityIssue::LeadingOrTrailingSpaces);
     }
 
     // No funny business
     for blacklisted_name in BLACKLIST.iter() {
         if &name.to_lowercase() == blacklisted_name {
-            errors.push(format!("{} is a blacklisted name", blacklisted_name));
+            errors.push(ValidityIssue::Blacklisted(blacklisted_name.to_string()));
         }
     }
 
@@ -123,19 +181,19 @@ pub fn validate(name: &str) -> Validity {
 
     for builtin in BUILTINS.iter() {
         if name.to_lowercase() == *builtin {
-            warnings.push(format!("{} is a core module name", builtin));
+            warnings.push(ValidityIssue::CoreModule(builtin.to_string()));
         }
     }
 
     // really-long-package-names-------------------------------such--length-----many---wow
     // the thisisareallyreallylongpackagenameitshouldpublishdowenowhavealimittothelengthofpackagenames-poch.
     if name.len() > 214 {
-        warnings.push("name can no longer contain more than 214 characters".into());
+        warnings.push(ValidityIssue::TooLong);
     }
 
     // mIxeD CaSe nAMEs
     if name.to_lowercase() != name {
-        warnings.push("name can no longer contain capital letters".into());
+        warnings.push(ValidityIssue::Capitals);
     }
 
     if name
@@ -144,7 +202,7 @@ pub fn validate(name: &str) -> Validity {
         .map(|final_part| SPECIAL_CHARS.is_match(final_part))
         .unwrap_or(false)
     {
-        warnings.push(r#"name can no longer contain special characters ("~\'!()*")"#.into());
+        warnings.push(ValidityIssue::SpecialChars);
     }
 
     if utf8_percent_encode(name, ENCODE_URI_SET).to_string() != name {
@@ -165,15 +223,20 @@ pub fn validate(name: &str) -> Validity {
             if valid_scope_name && valid_package_name {
                 return done(warnings, errors);
             }
+
+            if !valid_scope_name && valid_package_name {
+                errors.push(ValidityIssue::InvalidScope);
+                return done(warnings, errors);
+            }
         }
 
-        errors.push("name can only contain URL-friendly characters".into());
+        errors.push(ValidityIssue::NonUrlSafe);
     }
 
     done(warnings, errors)
 }
 
-fn done(warnings: Vec<String>, errors: Vec<String>) -> Validity {
+fn done(warnings: Vec<ValidityIssue>, errors: Vec<ValidityIssue>) -> Validity {
     match (warnings.len(), errors.len()) {
         (0, 0) => Validity::Valid,
         (_, 0) => Validity::ValidForOldPackages { warnings },
@@ -185,6 +248,24 @@ fn done(warnings: Vec<String>, errors: Vec<String>) -> Validity {
 mod tests {
     use super::*;
 
+    /// The messages of the JS package for the issues with a name
+    fn messages(validity: &Validity) -> (Vec<String>, Vec<String>) {
+        let render = |issues: &[ValidityIssue]| issues.iter().map(ToString::to_string).collect();
+        match validity {
+            Validity::Valid => (vec![], vec![]),
+            Validity::ValidForOldPackages { warnings } => (render(warnings), vec![]),
+            Validity::Invalid { warnings, errors } => (render(warnings), render(errors)),
+        }
+    }
+
+    fn warnings(messages: &[&str]) -> (Vec<String>, Vec<String>) {
+        (messages.iter().map(|m| m.to_string()).collect(), vec![])
+    }
+
+    fn errors(messages: &[&str]) -> (Vec<String>, Vec<String>) {
+        (vec![], messages.iter().map(|m| m.to_string()).collect())
+    }
+
     #[test]
     fn traditional() {
         assert_eq!(validate("some-package"), Validity::Valid);
@@ -195,11 +276,13 @@ mod tests {
         assert_eq!(
             validate("crazy!"),
             Validity::ValidForOldPackages {
-                warnings: vec![
-                    r#"name can no longer contain special characters ("~\'!()*")"#.into()
-                ]
+                warnings: vec![ValidityIssue::SpecialChars]
             }
         );
+        assert_eq!(
+            messages(&validate("crazy!")),
+            warnings(&[r#"name can no longer contain special characters ("~\'!()*")"#])
+        );
     }
 
     #[test]
@@ -208,92 +291,114 @@ mod tests {
         assert_eq!(
             validate("@npm-zors/money!time.js"),
             Validity::ValidForOldPackages {
-                warnings: vec![
-                    r#"name can no longer contain special characters ("~\'!()*")"#.into()
-                ]
+                warnings: vec![ValidityIssue::SpecialChars]
             }
         );
-    }
-
-    #[test]
-    fn invalid() {
         assert_eq!(
-            validate(""),
-            Validity::Invalid {
-                errors: vec!["name length must be greater than zero".into()],
-                warnings: vec![]
-            }
+            messages(&validate("@npm-zors/money!time.js")),
+            warnings(&[r#"name can no longer contain special characters ("~\'!()*")"#])
         );
+    }
 
+    #[test]
+    fn invalid_scope() {
         assert_eq!(
-            validate(".start-with-period"),
+            validate("@my:scope/package"),
             Validity::Invalid {
-                errors: vec!["name cannot start with a period".into()],
+                errors: vec![ValidityIssue::InvalidScope],
                 warnings: vec![]
             }
         );
-
         assert_eq!(
-            validate("_start-with-underscore"),
-            Validity::Invalid {
-                errors: vec!["name cannot start with an underscore".into()],
-                warnings: vec![]
-            }
+            messages(&validate("@my:scope/package")),
+            errors(&["name can only contain URL-friendly characters"])
         );
 
+        // An invalid name in a scope isn't a problem with the scope
         assert_eq!(
-            validate("contain:colons"),
+            validate("@scope/pack:age"),
             Validity::Invalid {
-                errors: vec!["name can only contain URL-friendly characters".into()],
+                errors: vec![ValidityIssue::NonUrlSafe],
                 warnings: vec![]
             }
         );
+    }
 
-        assert_eq!(
-            validate(" leading-space"),
-            Validity::Invalid {
-                errors: vec![
-                    "name cannot contain leading or trailing spaces".into(),
-                    "name can only contain URL-friendly characters".into()
+    #[test]
+    fn invalid() {
+        let cases: [(&str, Vec<ValidityIssue>, &[&str]); 9] = [
+            (
+                "",
+                vec![ValidityIssue::EmptyName],
+                &["name length must be greater than zero"],
+            ),
+            (
+                ".start-with-period",
+                vec![ValidityIssue::LeadingPeriod],
+                &["name cannot start with a period"],
+            ),
+            (
+                "_start-with-underscore",
+                vec![ValidityIssue::LeadingUnderscore],
+                &["name cannot start with an underscore"],
+            ),
+            (
+                "contain:colons",
+                vec![ValidityIssue::NonUrlSafe],
+                &["name can only contain URL-friendly characters"],
+            ),
+            (
+                " leading-space",
+                vec![
+                    ValidityIssue::LeadingOrTrailingSpaces,
+                    ValidityIssue::NonUrlSafe,
                 ],
-                warnings: vec![]
-            }
-        );
-
-        assert_eq!(
-            validate("trailing-space "),
-            Validity::Invalid {
-                errors: vec![
-                    "name cannot contain leading or trailing spaces".into(),
-                    "name can only contain URL-friendly characters".into()
+                &[
+                    "name cannot contain leading or trailing spaces",
+                    "name can only contain URL-friendly characters",
                 ],
-                warnings: vec![]
-            }
-        );
-
-        assert_eq!(
-            validate("s/l/a/s/h/e/s"),
-            Validity::Invalid {
-                errors: vec!["name can only contain URL-friendly characters".into()],
-                warnings: vec![]
-            }
-        );
-
-        assert_eq!(
-            validate("node_modules"),
-            Validity::Invalid {
-                errors: vec!["node_modules is a blacklisted name".into()],
-                warnings: vec![]
-            }
-        );
-
-        assert_eq!(
-            validate("favicon.ico"),
-            Validity::Invalid {
-                errors: vec!["favicon.ico is a blacklisted name".into()],
-                warnings: vec![]
-            }
-        );
+            ),
+            (
+                "trailing-space ",
+                vec![
+                    ValidityIssue::LeadingOrTrailingSpaces,
+                    ValidityIssue::NonUrlSafe,
+                ],
+                &[
+                    "name cannot contain leading or trailing spaces",
+                    "name can only contain URL-friendly characters",
+                ],
+            ),
+            (
+                "s/l/a/s/h/e/s",
+                vec![ValidityIssue::NonUrlSafe],
+                &["name can only contain URL-friendly characters"],
+            ),
+            (
+                "node_modules",
+                vec![ValidityIssue::Blacklisted("node_modules".into())],
+                &["node_modules is a blacklisted name"],
+            ),
+            (
+                "favicon.ico",
+                vec![ValidityIssue::Blacklisted("favicon.ico".into())],
+                &["favicon.ico is a blacklisted name"],
+            ),
+        ];
+
+        for (name, issues, expected) in cases {
+            let validity = validate(name);
+            assert_eq!(
+                validity,
+                Validity::Invalid {
+                    errors: issues,
+                    warnings: vec![]
+                },
+                "{:?}",
+                name
+            );
+            assert_eq!(messages(&validity), errors(expected), "{:?}", name);
+        }
     }
 
     #[test]
@@ -301,9 +406,13 @@ mod tests {
         assert_eq!(
             validate("http"),
             Validity::ValidForOldPackages {
-                warnings: vec!["http is a core module name".into()]
+                warnings: vec![ValidityIssue::CoreModule("http".into())]
             }
         );
+        assert_eq!(
+            messages(&validate("http")),
+            warnings(&["http is a core module name"])
+        );
     }
 
     #[test]
@@ -314,9 +423,13 @@ mod tests {
         assert_eq!(
             validate(one_too_long),
             Validity::ValidForOldPackages {
-                warnings: vec!["name can no longer contain more than 214 characters".into()]
+                warnings: vec![ValidityIssue::TooLong]
             }
         );
+        assert_eq!(
+            messages(&validate(one_too_long)),
+            warnings(&["name can no longer contain more than 214 characters"])
+        );
 
         assert_eq!(validate(short_enough), Validity::Valid);
     }
@@ -326,8 +439,12 @@ mod tests {
         assert_eq!(
             validate("CAPITAL-LETTERS"),
             Validity::ValidForOldPackages {
-                warnings: vec!["name can no longer contain capital letters".into()]
+                warnings: vec![ValidityIssue::Capitals]
             }
         );
+        assert_eq!(
+            messages(&validate("CAPITAL-LETTERS")),
+            warnings(&["name can no longer contain capital letters"])
+        );
     }
 }
@@ -0,0 +1,3 @@
+#!/bin/sh
+# Never reads its input and never exits
+exec sleep 86400
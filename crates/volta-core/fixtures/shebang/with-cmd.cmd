@@ -0,0 +1,2 @@
+@ECHO off
+node "%~dp0\with-cmd.js" %*
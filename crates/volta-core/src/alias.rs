@@ -0,0 +1,319 @@
+//! Provides types for working with version aliases, which give names to the versions of a tool
+//! (as in `volta install node@company-standard`).
+//!
+//! Aliases are defined in the `aliases` section of the settings file, and may be overridden in
+//! the hooks files of a project. They are resolved locally, before any registry is consulted.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::{ErrorKind, Fallible};
+use crate::version::{parse_requirements, parse_version, VersionSpec, VersionTag};
+use log::debug;
+
+/// The `aliases` section of a settings or hooks file, mapping each tool to its aliases and the
+/// versions or ranges they stand for
+pub(crate) type RawAliases = BTreeMap<String, BTreeMap<String, String>>;
+
+/// A name for a version or range of versions of a tool
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alias {
+    /// The tool the alias applies to, e.g. `node`
+    pub tool: String,
+    /// The name of the alias, e.g. `company-standard`
+    pub name: String,
+    /// The version or range the alias stands for, as written in the file
+    pub target: String,
+    /// The file that defines the alias
+    pub source: PathBuf,
+}
+
+impl Alias {
+    /// The version or range the alias stands for
+    fn version_spec(&self) -> Fallible<VersionSpec> {
+        if let Ok(version) = parse_version(&self.target) {
+            Ok(VersionSpec::Exact(version))
+        } else if let Ok(range) = parse_requirements(&self.target) {
+            Ok(VersionSpec::Semver(range))
+        } else {
+            Err(ErrorKind::InvalidVersionAlias {
+                tool: self.tool.clone(),
+                name: self.name.clone(),
+                target: self.target.clone(),
+                file: self.source.clone(),
+            }
+            .into())
+        }
+    }
+}
+
+/// Reads the aliases defined in a file, checking that each one names a version or range
+///
+/// Names that are themselves versions, ranges, or the `latest` and `lts` tags are rejected, since
+/// they would never be looked up as aliases.
+pub(crate) fn from_raw(raw: RawAliases, file: &Path) -> Fallible<Vec<Alias>> {
+    let mut aliases = Vec::new();
+
+    for (tool, names) in raw {
+        for (name, target) in names {
+            let alias = Alias {
+                tool: tool.clone(),
+                name,
+                target,
+                source: file.to_owned(),
+            };
+
+            let is_custom_tag = matches!(
+                alias.name.parse(),
+                Ok(VersionSpec::Tag(VersionTag::Custom(ref tag))) if *tag == alias.name
+            );
+            if !is_custom_tag {
+                return Err(ErrorKind::InvalidVersionAlias {
+                    tool: alias.tool,
+                    name: alias.name,
+                    target: alias.target,
+                    file: alias.source,
+                }
+                .into());
+            }
+
+            alias.version_spec()?;
+            aliases.push(alias);
+        }
+    }
+
+    Ok(aliases)
+}
+
+/// The version aliases that apply in the current directory
+#[derive(Debug, Default)]
+pub struct Aliases {
+    aliases: Vec<Alias>,
+}
+
+impl Aliases {
+    /// Combines the aliases defined in several files
+    ///
+    /// `layers` should be sorted in order of descending precedence: an alias defined for the same
+    /// tool in more than one file is taken from the first one.
+    pub(crate) fn from_layers<I>(layers: I) -> Self
+    where
+        I: IntoIterator<Item = Vec<Alias>>,
+    {
+        let mut aliases: BTreeMap<(String, String), Alias> = BTreeMap::new();
+
+        for alias in layers.into_iter().flatten() {
+            aliases
+                .entry((alias.tool.clone(), alias.name.clone()))
+                .or_insert(alias);
+        }
+
+        Aliases {
+            aliases: aliases.into_values().collect(),
+        }
+    }
+
+    /// All of the aliases, sorted by tool and name
+    pub fn all(&self) -> &[Alias] {
+        &self.aliases
+    }
+
+    /// The aliases defined for a single tool, sorted by name
+    pub fn for_tool<'a>(&'a self, tool: &'a str) -> impl Iterator<Item = &'a Alias> {
+        self.aliases.iter().filter(move |alias| alias.tool == tool)
+    }
+
+    /// Replaces an alias with the version or range it stands for
+    ///
+    /// Tags that aren't aliases of the tool are left as they are, since they may still be tags in
+    /// the registry.
+    pub fn resolve(&self, tool: &str, version: VersionSpec) -> Fallible<VersionSpec> {
+        let name = match version {
+            VersionSpec::Tag(VersionTag::Custom(name)) => name,
+            other => return Ok(other),
+        };
+
+        match self.for_tool(tool).find(|alias| alias.name == name) {
+            Some(alias) => {
+                debug!(
+                    "Resolved alias '{}@{}' to '{}' (defined in '{}')",
+                    tool,
+                    name,
+                    alias.target,
+                    alias.source.display()
+                );
+                alias.version_spec()
+            }
+            None => Ok(VersionSpec::Tag(VersionTag::Custom(name))),
+        }
+    }
+
+    /// The error for a tag that the tool doesn't support, listing the aliases it does have
+    ///
+    /// Returns `None` if no aliases are defined for the tool, so that the tool can report the
+    /// unsupported tag itself.
+    pub(crate) fn unknown_alias_error(&self, tool: &str, name: &str) -> Option<ErrorKind> {
+        let defined: Vec<String> = self
+            .for_tool(tool)
+            .map(|alias| alias.name.clone())
+            .collect();
+
+        if defined.is_empty() {
+            None
+        } else {
+            Some(ErrorKind::UnknownVersionAlias {
+                tool: tool.into(),
+                name: name.into(),
+                defined,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use node_semver::{Range, Version};
+
+    fn raw(entries: &[(&str, &str, &str)]) -> RawAliases {
+        let mut raw = RawAliases::new();
+        for (tool, name, target) in entries {
+            raw.entry(tool.to_string())
+                .or_default()
+                .insert(name.to_string(), target.to_string());
+        }
+        raw
+    }
+
+    fn settings_file() -> PathBuf {
+        PathBuf::from("/home/user/.volta/settings.json")
+    }
+
+    fn project_file() -> PathBuf {
+        PathBuf::from("/home/user/app/.volta/hooks.json")
+    }
+
+    #[test]
+    fn project_aliases_take_precedence() {
+        let settings = from_raw(
+            raw(&[
+                ("node", "company-standard", "20.11.1"),
+                ("node", "legacy", "16"),
+                ("yarn", "company-standard", "1.22.19"),
+            ]),
+            &settings_file(),
+        )
+        .unwrap();
+        let project = from_raw(
+            raw(&[("node", "company-standard", "18.19.0")]),
+            &project_file(),
+        )
+        .unwrap();
+
+        let aliases = Aliases::from_layers([project, settings]);
+        let summary: Vec<(&str, &str, &str, &Path)> = aliases
+            .all()
+            .iter()
+            .map(|alias| {
+                (
+                    alias.tool.as_str(),
+                    alias.name.as_str(),
+                    alias.target.as_str(),
+                    alias.source.as_path(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "node",
+                    "company-standard",
+                    "18.19.0",
+                    project_file().as_path()
+                ),
+                ("node", "legacy", "16", settings_file().as_path()),
+                (
+                    "yarn",
+                    "company-standard",
+                    "1.22.19",
+                    settings_file().as_path()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_aliases_to_versions_and_ranges() {
+        let aliases = Aliases::from_layers([from_raw(
+            raw(&[
+                ("node", "company-standard", "v20.11.1"),
+                ("node", "legacy", "^16.14"),
+            ]),
+            &settings_file(),
+        )
+        .unwrap()]);
+
+        let custom = |tag: &str| VersionSpec::Tag(VersionTag::Custom(tag.into()));
+
+        assert_eq!(
+            aliases.resolve("node", custom("company-standard")).unwrap(),
+            VersionSpec::Exact(Version::from((20, 11, 1)))
+        );
+        assert_eq!(
+            aliases.resolve("node", custom("legacy")).unwrap(),
+            VersionSpec::Semver(Range::parse("^16.14").unwrap())
+        );
+        // Aliases only apply to the tool they are defined for
+        assert_eq!(
+            aliases.resolve("npm", custom("legacy")).unwrap(),
+            custom("legacy")
+        );
+        assert_eq!(
+            aliases.resolve("node", custom("beta")).unwrap(),
+            custom("beta")
+        );
+        assert_eq!(
+            aliases.resolve("node", VersionSpec::None).unwrap(),
+            VersionSpec::None
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_aliases() {
+        assert!(from_raw(
+            raw(&[("node", "broken", "not a version")]),
+            &settings_file()
+        )
+        .is_err());
+        assert!(from_raw(raw(&[("node", "lts", "20.11.1")]), &settings_file()).is_err());
+        assert!(from_raw(raw(&[("node", "20", "20.11.1")]), &settings_file()).is_err());
+        // Aliases of aliases aren't supported
+        assert!(from_raw(
+            raw(&[("node", "std", "company-standard")]),
+            &settings_file()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn lists_defined_aliases_for_unknown_names() {
+        let aliases = Aliases::from_layers([from_raw(
+            raw(&[
+                ("node", "company-standard", "20.11.1"),
+                ("node", "legacy", "16"),
+            ]),
+            &settings_file(),
+        )
+        .unwrap()]);
+
+        let error = aliases
+            .unknown_alias_error("node", "companystandard")
+            .unwrap();
+        assert!(error
+            .to_string()
+            .contains("The aliases defined for node are: company-standard, legacy"));
+        assert!(aliases.unknown_alias_error("yarn", "anything").is_none());
+    }
+}
@@ -0,0 +1,401 @@
+//! Builds and checks manifests of the binaries that Volta places on the local machine
+//!
+//! A manifest lists every executable that Volta manages: the shims in the shim directory, the
+//! contents of the `bin` directory of each Node, npm, pnpm, and Yarn image, and the bin entry
+//! points of each installed package. Each entry records the size and SHA-256 hash of the file,
+//! along with the tool that owns it, so that the binaries can be allow-listed and re-verified.
+//!
+//! Manifests are written as JSON lines, sorted by path. Paths are relative to the Volta home
+//! directory, so that manifests from different machines can be compared.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::num::NonZeroUsize;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::read_dir_eager;
+use crate::inventory::{
+    node_versions, npm_versions, package_configs, pnpm_versions, yarn_versions,
+};
+use crate::layout::volta_home;
+use crate::tool::checksum::sha256_file;
+use log::debug;
+use node_semver::Version;
+use serde::{Deserialize, Serialize};
+
+/// A single binary in a manifest
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ManifestEntry {
+    /// The path of the binary, relative to the Volta home directory
+    pub path: String,
+    /// The size of the binary, in bytes
+    pub size: u64,
+    /// The SHA-256 hash of the binary, as `sha256-<hex>`
+    pub hash: String,
+    /// The tool or package that owns the binary, or `volta` for shims
+    pub tool: String,
+    /// The version of the owning tool, which shims don't have
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+/// The differences between a manifest and the binaries that are currently on the machine
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ManifestDiff {
+    /// Binaries that aren't in the manifest
+    pub added: Vec<ManifestEntry>,
+    /// Binaries in the manifest that no longer exist
+    pub removed: Vec<ManifestEntry>,
+    /// Binaries whose contents differ from the manifest, as (expected, actual)
+    pub changed: Vec<(ManifestEntry, ManifestEntry)>,
+}
+
+impl ManifestDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.added.len() + self.removed.len() + self.changed.len()
+    }
+}
+
+/// A binary that hasn't been hashed yet
+struct Binary {
+    path: PathBuf,
+    tool: String,
+    version: Option<String>,
+}
+
+/// Builds the manifest of the binaries Volta manages, hashing up to `jobs` binaries at a time
+pub fn build(jobs: NonZeroUsize) -> Fallible<Vec<ManifestEntry>> {
+    let home = volta_home()?;
+    let binaries = binaries()?;
+    let hashes = hash_all(&binaries, jobs);
+
+    let mut entries = binaries
+        .into_iter()
+        .zip(hashes)
+        .map(|(binary, hash)| {
+            let (size, hash) = hash.with_context(|| ErrorKind::HashBinaryError {
+                file: binary.path.clone(),
+            })?;
+
+            Ok(ManifestEntry {
+                path: relative_path(&binary.path, home.root()),
+                size,
+                hash,
+                tool: binary.tool,
+                version: binary.version,
+            })
+        })
+        .collect::<Fallible<Vec<_>>>()?;
+
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+/// Formats a manifest as JSON lines, one binary per line
+pub fn to_json_lines(entries: &[ManifestEntry]) -> String {
+    entries
+        .iter()
+        .map(|entry| {
+            // Serializing can't fail: the entries only contain strings and numbers
+            let mut line = serde_json::to_string(entry).expect("manifest entries are serializable");
+            line.push('\n');
+            line
+        })
+        .collect()
+}
+
+/// Reads a manifest written by `to_json_lines`, ignoring blank lines
+pub fn read(file: &Path) -> Fallible<Vec<ManifestEntry>> {
+    let contents =
+        fs::read_to_string(file).with_context(|| ErrorKind::ReadBinaryManifestError {
+            file: file.to_owned(),
+        })?;
+
+    parse(file, &contents)
+}
+
+fn parse(file: &Path, contents: &str) -> Fallible<Vec<ManifestEntry>> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).with_context(|| ErrorKind::ParseBinaryManifestError {
+                file: file.to_owned(),
+                line: index + 1,
+            })
+        })
+        .collect()
+}
+
+/// Compares a manifest with the current binaries, matching them by path
+pub fn diff(expected: Vec<ManifestEntry>, actual: Vec<ManifestEntry>) -> ManifestDiff {
+    let mut expected: BTreeMap<String, ManifestEntry> = expected
+        .into_iter()
+        .map(|entry| (entry.path.clone(), entry))
+        .collect();
+    let mut diff = ManifestDiff::default();
+
+    for entry in actual {
+        match expected.remove(&entry.path) {
+            Some(previous) if previous.hash != entry.hash || previous.size != entry.size => {
+                diff.changed.push((previous, entry));
+            }
+            Some(_) => {}
+            None => diff.added.push(entry),
+        }
+    }
+
+    diff.removed = expected.into_values().collect();
+    diff.added.sort_by(|a, b| a.path.cmp(&b.path));
+    diff.changed.sort_by(|(a, _), (b, _)| a.path.cmp(&b.path));
+    diff
+}
+
+/// Lists the binaries Volta manages, along with the tools that own them
+fn binaries() -> Fallible<Vec<Binary>> {
+    let home = volta_home()?;
+    let mut binaries = Vec::new();
+
+    for path in files_in(home.shim_dir())? {
+        binaries.push(Binary {
+            path,
+            tool: "volta".into(),
+            version: None,
+        });
+    }
+
+    add_images(&mut binaries, "node", node_versions()?, |version| {
+        home.node_image_bin_dir(version)
+    })?;
+    add_images(&mut binaries, "npm", npm_versions()?, |version| {
+        home.npm_image_bin_dir(version)
+    })?;
+    add_images(&mut binaries, "pnpm", pnpm_versions()?, |version| {
+        home.pnpm_image_bin_dir(version)
+    })?;
+    add_images(&mut binaries, "yarn", yarn_versions()?, |version| {
+        home.yarn_image_bin_dir(version)
+    })?;
+
+    for config in package_configs()? {
+        let bin_dir = config
+            .manager
+            .binary_dir(home.package_image_dir(&config.name));
+        for bin in &config.bins {
+            let path = bin_dir.join(bin);
+            if path.is_file() {
+                binaries.push(Binary {
+                    path,
+                    tool: config.name.clone(),
+                    version: Some(config.version.to_string()),
+                });
+            } else {
+                debug!("Skipping missing binary {}", path.display());
+            }
+        }
+    }
+
+    Ok(binaries)
+}
+
+/// Adds the binaries in the `bin` directory of each image of a tool
+fn add_images<F>(
+    binaries: &mut Vec<Binary>,
+    tool: &str,
+    versions: BTreeSet<Version>,
+    bin_dir: F,
+) -> Fallible<()>
+where
+    F: Fn(&str) -> PathBuf,
+{
+    for version in versions {
+        let version = version.to_string();
+        for path in files_in(&bin_dir(&version))? {
+            binaries.push(Binary {
+                path,
+                tool: tool.into(),
+                version: Some(version.clone()),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists the files directly within a directory, following symlinks, if the directory exists
+fn files_in(dir: &Path) -> Fallible<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = read_dir_eager(dir).with_context(|| ErrorKind::ReadDirError {
+        dir: dir.to_owned(),
+    })?;
+
+    Ok(entries
+        .map(|(entry, _)| entry.path())
+        .filter(|path| path.is_file())
+        .collect())
+}
+
+/// Hashes the binaries on up to `jobs` threads, returning the size and hash of each in order
+fn hash_all(binaries: &[Binary], jobs: NonZeroUsize) -> Vec<io::Result<(u64, String)>> {
+    let next = AtomicUsize::new(0);
+    let workers = jobs.get().min(binaries.len()).max(1);
+
+    let mut results: Vec<(usize, io::Result<(u64, String)>)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut hashed = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        match binaries.get(index) {
+                            Some(binary) => hashed.push((index, hash_file(&binary.path))),
+                            None => break hashed,
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("hashing threads don't panic"))
+            .collect()
+    });
+
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Returns the size and SHA-256 hash of a file
+fn hash_file(path: &Path) -> io::Result<(u64, String)> {
+    let size = fs::metadata(path)?.len();
+    let hash = sha256_file(path)?;
+    Ok((size, format!("sha256-{}", hash)))
+}
+
+/// Formats a path relative to the Volta home, with `/` separators on every platform
+fn relative_path(path: &Path, root: &Path) -> String {
+    match path.strip_prefix(root) {
+        Ok(relative) => relative
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(part) => Some(part.to_string_lossy()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("/"),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, hash: &str) -> ManifestEntry {
+        ManifestEntry {
+            path: path.into(),
+            size: 4,
+            hash: hash.into(),
+            tool: "node".into(),
+            version: Some("20.11.0".into()),
+        }
+    }
+
+    #[test]
+    fn json_lines_round_trip() {
+        let entries = vec![
+            entry("tools/image/node/20.11.0/bin/node", "sha256-aa"),
+            ManifestEntry {
+                tool: "volta".into(),
+                version: None,
+                ..entry("bin/node", "sha256-bb")
+            },
+        ];
+        let lines = to_json_lines(&entries);
+
+        assert_eq!(
+            lines.lines().next().unwrap(),
+            r#"{"path":"tools/image/node/20.11.0/bin/node","size":4,"hash":"sha256-aa","tool":"node","version":"20.11.0"}"#
+        );
+        assert_eq!(parse(Path::new("manifest"), &lines).unwrap(), entries);
+    }
+
+    #[test]
+    fn parse_reports_line_number() {
+        let contents = format!("{}\nnot json\n", to_json_lines(&[entry("bin/node", "x")]));
+        let error = parse(Path::new("manifest"), &contents).unwrap_err();
+
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::ParseBinaryManifestError { line: 3, .. }
+        ));
+    }
+
+    #[test]
+    fn diff_categories() {
+        let expected = vec![
+            entry("bin/node", "sha256-aa"),
+            entry("bin/npm", "sha256-bb"),
+            entry("bin/yarn", "sha256-cc"),
+        ];
+        let actual = vec![
+            entry("bin/pnpm", "sha256-dd"),
+            entry("bin/node", "sha256-aa"),
+            entry("bin/npm", "sha256-ee"),
+        ];
+
+        let diff = diff(expected, actual);
+        assert_eq!(diff.added, [entry("bin/pnpm", "sha256-dd")]);
+        assert_eq!(diff.removed, [entry("bin/yarn", "sha256-cc")]);
+        assert_eq!(
+            diff.changed,
+            [(entry("bin/npm", "sha256-bb"), entry("bin/npm", "sha256-ee"))]
+        );
+        assert_eq!(diff.len(), 3);
+    }
+
+    #[test]
+    fn hashes_stream_in_order() {
+        const CHUNK: usize = 64 * 1024;
+        let dir = tempfile::tempdir().unwrap();
+        let binaries: Vec<Binary> = (0..8)
+            .map(|index| {
+                let path = dir.path().join(index.to_string());
+                fs::write(&path, vec![b'x'; index * CHUNK / 3]).unwrap();
+                Binary {
+                    path,
+                    tool: "node".into(),
+                    version: None,
+                }
+            })
+            .collect();
+
+        let serial = hash_all(&binaries, NonZeroUsize::new(1).unwrap());
+        let parallel = hash_all(&binaries, NonZeroUsize::new(4).unwrap());
+        let serial: Vec<_> = serial.into_iter().map(Result::unwrap).collect();
+        let parallel: Vec<_> = parallel.into_iter().map(Result::unwrap).collect();
+
+        assert_eq!(serial, parallel);
+        assert_eq!(
+            serial[0],
+            (
+                0,
+                "sha256-e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".into()
+            )
+        );
+        assert_eq!(serial[3].0, CHUNK as u64);
+    }
+}
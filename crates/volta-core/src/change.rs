@@ -0,0 +1,141 @@
+//! Describes the changes that commands make to the files Volta manages
+//!
+//! Tools that wrap Volta, such as project generators, need to know exactly what a command
+//! changed. The changes are collected in the session as they are made, and `--porcelain` prints
+//! one line for each of them, like `shim created /home/user/.volta/bin/tsc`. That format is
+//! stable: the subjects and actions below are only ever added to.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// The kind of file or directory that was changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subject {
+    /// A shim in the shim directory
+    Shim,
+    /// A configuration file, such as the default platform or the config of a package or bin
+    Config,
+    /// The directory that a tool or package is installed in
+    Image,
+    /// The link to a package in the shared library directory
+    Link,
+    /// An archive or other file in the inventory of fetched tools
+    Inventory,
+    /// A shell profile script
+    Profile,
+}
+
+impl fmt::Display for Subject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Subject::Shim => "shim",
+            Subject::Config => "config",
+            Subject::Image => "image",
+            Subject::Link => "link",
+            Subject::Inventory => "inventory",
+            Subject::Profile => "profile",
+        })
+    }
+}
+
+/// What happened to the file or directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// It didn't exist before
+    Created,
+    /// It existed and was written again in full
+    Replaced,
+    /// It existed and was edited in place
+    Updated,
+    /// It existed and was removed
+    Removed,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Action::Created => "created",
+            Action::Replaced => "replaced",
+            Action::Updated => "updated",
+            Action::Removed => "removed",
+        })
+    }
+}
+
+/// A single change to a file or directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub subject: Subject,
+    pub action: Action,
+    pub path: PathBuf,
+}
+
+impl Change {
+    pub fn new<P: Into<PathBuf>>(subject: Subject, action: Action, path: P) -> Self {
+        Change {
+            subject,
+            action,
+            path: path.into(),
+        }
+    }
+
+    /// A file or directory that was written, given whether it existed beforehand
+    pub fn written<P: Into<PathBuf>>(subject: Subject, path: P, existed: bool) -> Self {
+        let action = if existed {
+            Action::Replaced
+        } else {
+            Action::Created
+        };
+        Change::new(subject, action, path)
+    }
+
+    /// A file or directory that was removed
+    pub fn removed<P: Into<PathBuf>>(subject: Subject, path: P) -> Self {
+        Change::new(subject, Action::Removed, path)
+    }
+}
+
+/// Formats the change as a line of porcelain output, like `config removed <path>`
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.subject,
+            self.action,
+            self.path.display()
+        )
+    }
+}
+
+/// Determines whether a path exists, without following a final symlink
+///
+/// A dangling link is still replaced when it is written over, so it counts as existing.
+pub(crate) fn exists(path: &Path) -> bool {
+    path.symlink_metadata().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_porcelain_lines() {
+        assert_eq!(
+            Change::written(Subject::Shim, "/volta/bin/tsc", false).to_string(),
+            "shim created /volta/bin/tsc"
+        );
+        assert_eq!(
+            Change::written(Subject::Config, "/volta/tools/user/platform.json", true).to_string(),
+            "config replaced /volta/tools/user/platform.json"
+        );
+        assert_eq!(
+            Change::removed(Subject::Link, "/volta/tools/shared/typescript").to_string(),
+            "link removed /volta/tools/shared/typescript"
+        );
+        assert_eq!(
+            Change::new(Subject::Profile, Action::Updated, "/home/user/.bashrc").to_string(),
+            "profile updated /home/user/.bashrc"
+        );
+    }
+}
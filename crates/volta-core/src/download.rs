@@ -0,0 +1,342 @@
+//! Bounds the number of network requests that Volta has in flight at once
+//!
+//! Corporate proxies frequently limit the number of connections a single client may have open,
+//! and will reset connections beyond that limit. To keep the real connection count bounded, every
+//! network request that Volta makes (index lookups, registry metadata, and archive downloads)
+//! first acquires a `DownloadPermit` from a semaphore shared by the whole process.
+//!
+//! The limit defaults to `DEFAULT_MAX_CONCURRENT_DOWNLOADS` and can be changed with the
+//! `network.maxConcurrentDownloads` setting, the `VOLTA_MAX_CONCURRENT_DOWNLOADS` environment
+//! variable, or the `--max-concurrent-downloads` command-line flag, each of which takes
+//! precedence over the ones before it. The settings file is only read once a download starts.
+
+use std::env;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+
+use crate::settings::LazySettings;
+use crate::style::progress_spinner;
+use log::{debug, warn};
+use once_cell::sync::Lazy;
+
+/// The number of concurrent downloads allowed when the user hasn't configured a limit
+pub const DEFAULT_MAX_CONCURRENT_DOWNLOADS: NonZeroUsize = match NonZeroUsize::new(4) {
+    Some(limit) => limit,
+    None => unreachable!(),
+};
+
+const VOLTA_MAX_CONCURRENT_DOWNLOADS: &str = "VOLTA_MAX_CONCURRENT_DOWNLOADS";
+
+static DOWNLOADS: Lazy<Semaphore> = Lazy::new(|| Semaphore::new(configured_limit()));
+
+static NETWORK_USED: AtomicBool = AtomicBool::new(false);
+
+/// Sets the maximum number of downloads that may be in flight at once
+///
+/// A limit of 0 would prevent any downloads from starting, so it is treated as 1.
+pub fn set_max_concurrent_downloads(limit: usize) {
+    DOWNLOADS.set_limit(limit);
+}
+
+/// Returns the maximum number of downloads that may be in flight at once
+pub fn max_concurrent_downloads() -> usize {
+    DOWNLOADS.limit()
+}
+
+/// Determines whether this process has made any network request
+pub fn network_used() -> bool {
+    NETWORK_USED.load(Ordering::Relaxed)
+}
+
+/// The limit from the environment variable if it's set, otherwise from the settings
+fn configured_limit() -> usize {
+    let from_settings = || match LazySettings::init().get() {
+        Ok(settings) => settings.max_concurrent_downloads(),
+        Err(error) => {
+            debug!(
+                "Could not read the settings, using the default download limit: {}",
+                error
+            );
+            DEFAULT_MAX_CONCURRENT_DOWNLOADS
+        }
+    };
+
+    let limit = match env::var(VOLTA_MAX_CONCURRENT_DOWNLOADS) {
+        Ok(value) => match value.trim().parse::<NonZeroUsize>() {
+            Ok(limit) => limit,
+            Err(_) => {
+                let limit = from_settings();
+                warn!(
+                    "Ignoring invalid value '{}' for {}, using the limit of {}",
+                    value, VOLTA_MAX_CONCURRENT_DOWNLOADS, limit
+                );
+                limit
+            }
+        },
+        Err(_) => from_settings(),
+    };
+    limit.get()
+}
+
+/// A counting semaphore guarding the available download slots
+struct Semaphore {
+    state: Mutex<SemaphoreState>,
+    released: Condvar,
+}
+
+struct SemaphoreState {
+    limit: usize,
+    active: usize,
+}
+
+impl Semaphore {
+    fn new(limit: usize) -> Self {
+        Semaphore {
+            state: Mutex::new(SemaphoreState {
+                limit: limit.max(1),
+                active: 0,
+            }),
+            released: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SemaphoreState> {
+        // The state is only ever a pair of counters, so it can't be left inconsistent by a panic
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn limit(&self) -> usize {
+        self.lock().limit
+    }
+
+    fn set_limit(&self, limit: usize) {
+        self.lock().limit = limit.max(1);
+        // Raising the limit may allow queued downloads to start
+        self.released.notify_all();
+    }
+
+    fn try_acquire(&self) -> Option<DownloadPermit<'_>> {
+        let mut state = self.lock();
+        if state.active < state.limit {
+            state.active += 1;
+            Some(DownloadPermit { semaphore: self })
+        } else {
+            None
+        }
+    }
+
+    fn acquire(&self) -> DownloadPermit<'_> {
+        let mut state = self.lock();
+        while state.active >= state.limit {
+            state = self
+                .released
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        state.active += 1;
+        DownloadPermit { semaphore: self }
+    }
+
+    fn release(&self) {
+        let mut state = self.lock();
+        state.active = state.active.saturating_sub(1);
+        self.released.notify_one();
+    }
+}
+
+/// An RAII permit to perform a single download. The slot is released when the permit is dropped.
+///
+/// Since most archives are streamed while they are unpacked, the permit for such a download needs
+/// to be held until the unpacking is complete, not only until the response starts.
+pub struct DownloadPermit<'a> {
+    semaphore: &'a Semaphore,
+}
+
+impl DownloadPermit<'static> {
+    /// Acquires a download slot, blocking until one is available
+    ///
+    /// If every slot is already in use, a spinner is shown with the queued state until a slot
+    /// becomes free.
+    pub fn acquire() -> Self {
+        NETWORK_USED.store(true, Ordering::Relaxed);
+
+        if let Some(permit) = DOWNLOADS.try_acquire() {
+            return permit;
+        }
+
+        let limit = DOWNLOADS.limit();
+        debug!(
+            "All {} download slots are in use, waiting for one to be released",
+            limit
+        );
+        let spinner = progress_spinner(format!(
+            "Queued: waiting for one of {} download slots",
+            limit
+        ));
+        let permit = DOWNLOADS.acquire();
+        spinner.finish_and_clear();
+        permit
+    }
+}
+
+impl Drop for DownloadPermit<'_> {
+    fn drop(&mut self) {
+        self.semaphore.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Semaphore;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{mpsc, Arc};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn zero_limit_is_treated_as_one() {
+        let semaphore = Semaphore::new(0);
+        assert_eq!(semaphore.limit(), 1);
+
+        semaphore.set_limit(0);
+        assert_eq!(semaphore.limit(), 1);
+    }
+
+    #[test]
+    fn try_acquire_respects_limit() {
+        let semaphore = Semaphore::new(2);
+
+        let first = semaphore.try_acquire();
+        let second = semaphore.try_acquire();
+        assert!(first.is_some());
+        assert!(second.is_some());
+        assert!(semaphore.try_acquire().is_none());
+
+        drop(first);
+        assert!(semaphore.try_acquire().is_some());
+    }
+
+    #[test]
+    fn raising_limit_releases_capacity() {
+        let semaphore = Semaphore::new(1);
+        let _held = semaphore.acquire();
+        assert!(semaphore.try_acquire().is_none());
+
+        semaphore.set_limit(2);
+        assert!(semaphore.try_acquire().is_some());
+    }
+
+    #[test]
+    fn acquire_blocks_while_every_permit_is_held() {
+        let semaphore: &'static Semaphore = Box::leak(Box::new(Semaphore::new(2)));
+        let held = [semaphore.acquire(), semaphore.acquire()];
+
+        let (acquired, received) = mpsc::channel();
+        let waiting = thread::spawn(move || {
+            let _permit = semaphore.acquire();
+            acquired.send(()).unwrap();
+        });
+
+        assert!(received.recv_timeout(Duration::from_millis(100)).is_err());
+
+        drop(held);
+        received.recv_timeout(Duration::from_secs(5)).unwrap();
+        waiting.join().unwrap();
+    }
+
+    #[test]
+    fn concurrent_acquires_never_exceed_limit() {
+        let semaphore: &'static Semaphore = Box::leak(Box::new(Semaphore::new(3)));
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..12)
+            .map(|_| {
+                let running = Arc::clone(&running);
+                let peak = Arc::clone(&peak);
+                thread::spawn(move || {
+                    let _permit = semaphore.acquire();
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(10));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+        assert_eq!(running.load(Ordering::SeqCst), 0);
+    }
+
+    /// Answers every request after a delay, recording the most requests it had in flight at once
+    fn serve_slowly(listener: TcpListener, in_flight: Arc<AtomicUsize>, peak: Arc<AtomicUsize>) {
+        for stream in listener.incoming() {
+            let stream = stream.unwrap();
+            let in_flight = Arc::clone(&in_flight);
+            let peak = Arc::clone(&peak);
+            thread::spawn(move || {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                read_request(&stream);
+                thread::sleep(Duration::from_millis(50));
+
+                // The request is finished before the client gets the response, so that it can't
+                // release its permit while the request still counts as in flight
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                respond(stream);
+            });
+        }
+    }
+
+    fn read_request(stream: &TcpStream) {
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap() > 0 && line != "\r\n" {
+            line.clear();
+        }
+    }
+
+    fn respond(mut stream: TcpStream) {
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n[]")
+            .unwrap();
+    }
+
+    #[test]
+    fn downloads_in_flight_never_exceed_limit() {
+        const LIMIT: usize = 2;
+        let semaphore: &'static Semaphore = Box::leak(Box::new(Semaphore::new(LIMIT)));
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/index.json", listener.local_addr().unwrap());
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let server_peak = Arc::clone(&peak);
+        thread::spawn(move || serve_slowly(listener, in_flight, server_peak));
+
+        let handles: Vec<_> = (0..LIMIT * 4)
+            .map(|_| {
+                let url = url.clone();
+                thread::spawn(move || {
+                    let _permit = semaphore.acquire();
+                    attohttpc::get(url).send().unwrap().text().unwrap()
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), "[]");
+        }
+
+        let peak = peak.load(Ordering::SeqCst);
+        assert!(peak >= 1);
+        assert!(peak <= LIMIT, "{} downloads were in flight at once", peak);
+    }
+}
@@ -17,6 +17,18 @@ const PERMISSIONS_CTA: &str = "Please ensure you have correct permissions to the
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 pub enum ErrorKind {
+    /// Thrown when version aliases are requested in a version of the `volta list` JSON output that
+    /// doesn't include them
+    AliasesJsonVersionError {
+        version: u32,
+        required: u32,
+    },
+
+    /// Thrown when `volta fetch --all-lts` is given a tool other than Node
+    AllLtsInvalidTool {
+        tool: String,
+    },
+
     /// Thrown when package tries to install a binary that is already installed.
     BinaryAlreadyInstalled {
         bin_name: String,
@@ -27,6 +39,11 @@ pub enum ErrorKind {
     /// Thrown when executing an external binary fails
     BinaryExecError,
 
+    /// Thrown when a tool binary exists but can't be run because it isn't executable
+    BinaryNotExecutable {
+        binary: PathBuf,
+    },
+
     /// Thrown when a binary could not be found in the local inventory
     BinaryNotFound {
         name: String,
@@ -35,11 +52,21 @@ pub enum ErrorKind {
     /// Thrown when building the virtual environment path fails
     BuildPathError,
 
+    /// Thrown when a project manifest uses "bundled" for a tool other than npm
+    BundledVersionNotSupported {
+        tool: String,
+    },
+
     /// Thrown when unable to launch a command with VOLTA_BYPASS set
     BypassError {
         command: String,
     },
 
+    /// Thrown when a user tries to `volta exec` something other than node/npm/pnpm/yarn.
+    CannotExecPackage {
+        package: String,
+    },
+
     /// Thrown when a user tries to `volta fetch` something other than node/yarn/npm.
     CannotFetchPackage {
         package: String,
@@ -50,6 +77,17 @@ pub enum ErrorKind {
         package: String,
     },
 
+    /// Thrown when a user tries to `volta pin --remove node`, which every other pin depends on
+    CannotRemoveNodePin,
+
+    /// Thrown when a downloaded archive doesn't match its published checksum
+    ChecksumMismatch {
+        tool: String,
+        version: String,
+        expected: String,
+        actual: String,
+    },
+
     /// Thrown when the Completions out-dir is not a directory
     CompletionsOutFileError {
         path: PathBuf,
@@ -60,6 +98,18 @@ pub enum ErrorKind {
         path: PathBuf,
     },
 
+    /// Thrown when a package installed from a local directory could not be copied into its image
+    CopyLocalPackageError {
+        package: String,
+        dir: PathBuf,
+    },
+
+    /// Thrown when an existing Node installation could not be copied into the Volta directory
+    CopyNodeInstallError {
+        version: String,
+        dir: PathBuf,
+    },
+
     CouldNotDetermineTool,
 
     /// Thrown when unable to start the migration executable
@@ -106,6 +156,17 @@ pub enum ErrorKind {
         advice: String,
     },
 
+    /// Thrown when `volta list --diff` is asked for a version of the JSON output without diffs
+    DiffJsonVersionError {
+        version: u32,
+        required: u32,
+    },
+
+    /// Thrown when dynamic completions are requested for a shell that doesn't support them
+    DynamicCompletionsUnsupported {
+        shell: String,
+    },
+
     DownloadToolNetworkError {
         tool: tool::Spec,
         from_url: String,
@@ -127,6 +188,20 @@ pub enum ErrorKind {
         path: PathBuf,
     },
 
+    /// Thrown when some of the tools listed in a `volta fetch --manifest` file could not be fetched
+    FetchManifestError {
+        file: PathBuf,
+        fetched: Vec<String>,
+        skipped: Vec<String>,
+        failed: Vec<(String, String)>,
+        exit_code: ExitCode,
+    },
+
+    /// Thrown when a binary can't be read to compute its hash
+    HashBinaryError {
+        file: PathBuf,
+    },
+
     /// Thrown when a hook command returns a non-zero exit code
     HookCommandFailed {
         command: String,
@@ -143,9 +218,28 @@ pub enum ErrorKind {
         command: String,
     },
 
+    /// Thrown when the URL resolved by a hook couldn't be probed with `volta hook test --probe`
+    HookProbeError {
+        url: String,
+    },
+
+    /// Thrown when a project-local binary's package requires a Node version that doesn't match
+    /// the platform, and the project sets `volta.engineStrict`
+    IncompatibleNodeEngine {
+        package: String,
+        required: String,
+        node: String,
+    },
+
     /// Thrown when determining the name of a newly-installed package fails
     InstalledPackageNameError,
 
+    /// Thrown when a `volta fetch --manifest` file can't be parsed or lists invalid tools
+    InvalidFetchManifest {
+        file: PathBuf,
+        errors: Vec<String>,
+    },
+
     InvalidHookCommand {
         command: String,
     },
@@ -170,25 +264,94 @@ pub enum ErrorKind {
         version: String,
     },
 
+    /// Thrown when the architecture chosen for Node builds isn't a valid architecture name
+    InvalidNodeArch {
+        arch: String,
+    },
+
+    /// Thrown when the name of a platform profile can't be used as a file name
+    InvalidPlatformProfileName {
+        name: String,
+    },
+
+    /// Thrown when the publish hook `mode` isn't one of the supported values
+    InvalidPublishMode {
+        mode: String,
+    },
+
     /// Thrown when a format other than "npm" or "github" is given for yarn.index in the hooks
     InvalidRegistryFormat {
         format: String,
     },
 
+    /// Thrown when a value passed to `volta config set` isn't valid for the setting
+    InvalidSettingValue {
+        key: String,
+        value: String,
+        expected: String,
+    },
+
     /// Thrown when a tool name is invalid per npm's rules.
     InvalidToolName {
         name: String,
         errors: Vec<String>,
+        /// How to fix the first of the errors, if it's known
+        hint: Option<String>,
+    },
+
+    /// Thrown when a version alias in the settings or hooks file doesn't name a version or range
+    InvalidVersionAlias {
+        tool: String,
+        name: String,
+        target: String,
+        file: PathBuf,
+    },
+
+    /// Thrown when the `policies` section of a settings or hooks file has an invalid rule
+    InvalidVersionPolicy {
+        tool: String,
+        reason: String,
+        file: PathBuf,
+    },
+
+    /// Thrown when the JSON output of a command could not be serialized
+    JsonOutputError,
+
+    /// Thrown when a local package spec doesn't point to a tarball or a directory containing a
+    /// package
+    LocalPackageNotFound {
+        path: PathBuf,
     },
 
     /// Thrown when unable to acquire a lock on the Volta directory
     LockAcquireError,
 
+    /// Thrown when a lockfile doesn't identify a package manager version that Volta can pin
+    LockfileNotInferred {
+        lockfile: PathBuf,
+        reason: String,
+    },
+
+    /// Thrown when pinning in a project manifest that could only be read by ignoring comments or
+    /// trailing commas
+    ManifestNotStrictJson {
+        file: PathBuf,
+    },
+
+    /// Thrown when more than one kind of lockfile is found, so the package manager is ambiguous
+    MultipleLockfiles {
+        directory: PathBuf,
+        lockfiles: Vec<String>,
+    },
+
     /// Thrown when pinning or installing npm@bundled and couldn't detect the bundled version
     NoBundledNpm {
         command: String,
     },
 
+    /// Thrown when `npm@bundled-for` is fetched without a Node version
+    NoBundledNpmNodeVersion,
+
     /// Thrown when pnpm is not set at the command-line
     NoCommandLinePnpm,
 
@@ -200,11 +363,58 @@ pub enum ErrorKind {
         tool: String,
     },
 
+    /// Thrown when the Node index has no build of a version for the overridden architecture
+    NodeArchNotAvailable {
+        version: String,
+        arch: String,
+        available: Vec<String>,
+    },
+
+    /// Thrown when the checksum file from the `node.shasums` hook doesn't list the Node archive
+    NodeChecksumNotFound {
+        file: String,
+    },
+
+    /// Thrown when an existing Node installation reports a different version than expected
+    NodeInstallVersionMismatch {
+        dir: PathBuf,
+        expected: String,
+        found: String,
+    },
+
+    /// Thrown when a fetched Node can't be run, because it requires a newer glibc than the system has
+    NodeRequiresNewerGlibc {
+        version: String,
+        loader_error: String,
+        glibc: Option<String>,
+        newest_major: Option<u64>,
+    },
+
+    /// Thrown when the Node release schedule could not be downloaded
+    NodeScheduleFetchError {
+        from_url: String,
+    },
+
+    /// Thrown when the checksum file for a Node distro could not be downloaded
+    NodeShasumsFetchError {
+        from_url: String,
+    },
+
+    /// Thrown when `volta uninstall node` is used without an exact version
+    NodeUninstallVersionRequired {
+        installed: Vec<String>,
+    },
+
     /// Thrown when there is no Node version matching a requested semver specifier.
     NodeVersionNotFound {
         matching: String,
     },
 
+    /// Thrown when a custom tag is used to request a Node version
+    NodeVersionTagNotSupported {
+        tag: String,
+    },
+
     NoHomeEnvironmentVar,
 
     /// Thrown when the install dir could not be determined
@@ -212,6 +422,11 @@ pub enum ErrorKind {
 
     NoLocalDataDir,
 
+    /// Thrown when a package manager should be inferred from a lockfile, but there is none
+    NoLockfile {
+        directory: PathBuf,
+    },
+
     /// Thrown when a user tries to pin a npm, pnpm, or Yarn version before pinning a Node version.
     NoPinnedNodeVersion {
         tool: String,
@@ -235,6 +450,10 @@ pub enum ErrorKind {
         bin_dir: PathBuf,
     },
 
+    /// Thrown when `volta install --manifest` is run outside of a project with a
+    /// `.volta/tools.json` file
+    NoToolsManifest,
+
     /// Thrown when the user tries to pin Node or Yarn versions outside of a package.
     NotInPackage,
 
@@ -244,6 +463,17 @@ pub enum ErrorKind {
     /// Thrown when default pnpm is not set
     NoDefaultPnpm,
 
+    /// Thrown when the Notion directory could not be renamed out of the way
+    NotionBackupError {
+        from: PathBuf,
+        to: PathBuf,
+    },
+
+    /// Thrown when the migration of a Notion directory wasn't confirmed
+    NotionMigrationNotConfirmed {
+        dir: PathBuf,
+    },
+
     /// Thrown when `npm link` is called with a package that isn't available
     NpmLinkMissingPackage {
         package: String,
@@ -263,6 +493,49 @@ pub enum ErrorKind {
         version: String,
     },
 
+    /// Thrown when the nvm directory to import from does not contain any Node versions
+    NvmDirNotFound {
+        dir: PathBuf,
+    },
+
+    /// Thrown when an archive in the `--offline-from` directory doesn't match its checksum file
+    OfflineArchiveChecksumMismatch {
+        file: PathBuf,
+        expected: String,
+        actual: String,
+    },
+
+    /// Thrown when the archive for a tool is missing from the `--offline-from` directory
+    OfflineArchiveNotFound {
+        tool: String,
+        dir: PathBuf,
+        file: String,
+    },
+
+    /// Thrown when an archive in the `--offline-from` directory can't be read
+    OfflineArchiveReadError {
+        file: PathBuf,
+    },
+
+    /// Thrown when a tool is installed with `--offline-from` without an exact version
+    OfflineInstallNeedsVersion {
+        tool: String,
+    },
+
+    /// Thrown when offline mode can't satisfy a version requirement with the versions available
+    /// locally
+    OfflineVersionNotAvailable {
+        tool: String,
+        matching: String,
+        available: Vec<String>,
+    },
+
+    /// Thrown when `volta list --outdated` is asked for a JSON version that doesn't include it
+    OutdatedJsonVersionError {
+        version: u32,
+        required: u32,
+    },
+
     /// Thrown when the command to install a global package is not successful
     PackageInstallFailed {
         package: String,
@@ -304,46 +577,147 @@ pub enum ErrorKind {
     /// Thrown when unable to parse a bin config file
     ParseBinConfigError,
 
+    /// Thrown when a line of a binary manifest can't be parsed
+    ParseBinaryManifestError {
+        file: PathBuf,
+        line: usize,
+    },
+
     /// Thrown when unable to parse a hooks.json file
     ParseHooksError {
         file: PathBuf,
     },
 
+    /// Thrown when the snapshot given to `volta list --diff` isn't the output of `volta list --json`
+    ParseListSnapshotError {
+        file: PathBuf,
+    },
+
     /// Thrown when unable to parse the node index cache
     ParseNodeIndexCacheError,
 
     /// Thrown when unable to parse the node index
     ParseNodeIndexError {
         from_url: String,
+        response_start: String,
     },
 
     /// Thrown when unable to parse the node index cache expiration
     ParseNodeIndexExpiryError,
 
+    /// Thrown when unable to parse the Node release schedule
+    ParseNodeScheduleError {
+        from_url: String,
+    },
+
     /// Thrown when unable to parse the npm manifest file from a node install
     ParseNpmManifestError,
 
     /// Thrown when unable to parse a package configuration
     ParsePackageConfigError,
 
+    /// Thrown when the `volta.lock` file of a project can't be parsed
+    ParsePlatformLockError {
+        file: PathBuf,
+    },
+
     /// Thrown when unable to parse the platform.json file
     ParsePlatformError,
 
+    /// Thrown when a platform profile doesn't match the format of a platform file
+    ParsePlatformProfileError {
+        file: PathBuf,
+        error: String,
+    },
+
+    /// Thrown when the settings file could not be parsed
+    ParseSettingsError {
+        file: PathBuf,
+    },
+
     /// Thrown when unable to parse a tool spec (`<tool>[@<version>]`)
     ParseToolSpecError {
         tool_spec: String,
     },
 
+    /// Thrown when the `.volta/tools.json` file of a project can't be parsed
+    ParseToolsManifestError {
+        file: PathBuf,
+        error: String,
+    },
+
+    /// Thrown when unable to parse the Yarn index cache
+    ParseYarnIndexCacheError,
+
+    /// Thrown when unable to parse the Yarn index cache expiration
+    ParseYarnIndexExpiryError,
+
+    /// Thrown when the `PATH` for a tool is longer than the platform allows
+    PathTooLong {
+        length: usize,
+        limit: usize,
+        added: Vec<PathBuf>,
+    },
+
     /// Thrown when persisting an archive to the inventory fails
     PersistInventoryError {
         tool: String,
     },
 
+    /// Thrown when the files in a project disagree about the version to pin and the choice wasn't
+    /// confirmed
+    PinInferredNotConfirmed {
+        tool: String,
+        requirement: String,
+        found: Vec<String>,
+    },
+
+    /// Thrown when a pin would affect other workspace members and wasn't confirmed
+    PinMembersNotConfirmed {
+        manifest: PathBuf,
+        members: Vec<PathBuf>,
+    },
+
+    /// Thrown when some of the tools in a platform could not be fetched while others could
+    PlatformCheckoutError {
+        present: Vec<String>,
+        fetched: Vec<String>,
+        failed: Vec<(String, String)>,
+        exit_code: ExitCode,
+    },
+
+    /// Thrown when the `volta` settings of a project have changed since its `volta.lock` file was
+    /// written, and the lock file must be up to date (`--frozen`)
+    PlatformLockOutdated {
+        file: PathBuf,
+        manifest: PathBuf,
+    },
+
+    /// Thrown when there is no platform profile with the given name
+    PlatformProfileNotFound {
+        name: String,
+        available: Vec<String>,
+    },
+
+    /// Thrown when the platform file keeps changing while trying to save a change to it
+    PlatformWriteConflict {
+        file: PathBuf,
+    },
+
     /// Thrown when there is no pnpm version matching a requested semver specifier.
     PnpmVersionNotFound {
         matching: String,
     },
 
+    /// Thrown when a version of a tool is resolved that a version policy doesn't allow
+    PolicyViolation {
+        tool: String,
+        version: String,
+        rule: String,
+        file: PathBuf,
+        overridable: bool,
+    },
+
     /// Thrown when executing a project-local binary fails
     ProjectLocalBinaryExecError {
         command: String,
@@ -370,6 +744,11 @@ pub enum ErrorKind {
         file: PathBuf,
     },
 
+    /// Thrown when a binary manifest can't be read
+    ReadBinaryManifestError {
+        file: PathBuf,
+    },
+
     /// Thrown when unable to read the default npm version file
     ReadDefaultNpmError {
         file: PathBuf,
@@ -380,11 +759,26 @@ pub enum ErrorKind {
         dir: PathBuf,
     },
 
+    /// Thrown when a `volta fetch --manifest` file can't be read
+    ReadFetchManifestError {
+        file: PathBuf,
+    },
+
     /// Thrown when there was an error opening a hooks.json file
     ReadHooksError {
         file: PathBuf,
     },
 
+    /// Thrown when the snapshot given to `volta list --diff` couldn't be read
+    ReadListSnapshotError {
+        file: PathBuf,
+    },
+
+    /// Thrown when there was an error reading a lockfile
+    ReadLockfileError {
+        file: PathBuf,
+    },
+
     /// Thrown when there was an error reading the Node Index Cache
     ReadNodeIndexCacheError {
         file: PathBuf,
@@ -395,6 +789,11 @@ pub enum ErrorKind {
         file: PathBuf,
     },
 
+    /// Thrown when the version of an existing Node installation could not be determined
+    ReadNodeInstallVersionError {
+        bin: PathBuf,
+    },
+
     /// Thrown when there was an error reading the npm manifest file
     ReadNpmManifestError,
 
@@ -403,24 +802,103 @@ pub enum ErrorKind {
         file: PathBuf,
     },
 
+    /// Thrown when the `volta.lock` file of a project can't be read
+    ReadPlatformLockError {
+        file: PathBuf,
+    },
+
     /// Thrown when there was an error opening the user platform file
     ReadPlatformError {
         file: PathBuf,
     },
 
+    /// Thrown when a platform profile can't be read
+    ReadPlatformProfileError {
+        file: PathBuf,
+    },
+
+    /// Thrown when the settings file could not be read
+    ReadSettingsError {
+        file: PathBuf,
+    },
+
+    /// Thrown when the `.volta/tools.json` file of a project can't be read
+    ReadToolsManifestError {
+        file: PathBuf,
+    },
+
+    /// Thrown when the local usage counters could not be read
+    ReadUsageStatsError {
+        file: PathBuf,
+    },
+
     /// Thrown when unable to read the user Path environment variable from the registry
     #[cfg(windows)]
     ReadUserPathError,
 
+    /// Thrown when there was an error reading the Yarn index cache
+    ReadYarnIndexCacheError {
+        file: PathBuf,
+    },
+
+    /// Thrown when there was an error reading the Yarn index cache expiration
+    ReadYarnIndexExpiryError {
+        file: PathBuf,
+    },
+
     /// Thrown when the public registry for Node or Yarn could not be downloaded.
     RegistryFetchError {
         tool: String,
         from_url: String,
     },
 
+    /// Thrown when the shared links of some packages could not be repaired
+    RelinkPackagesError {
+        packages: Vec<String>,
+    },
+
     /// Thrown when the shim binary is called directly, not through a symlink
     RunShimDirectly,
 
+    /// Thrown when `volta run --where` is used with a command that Volta handles itself
+    RunWhereUnsupported {
+        command: String,
+    },
+
+    /// Thrown when the checksum file of a Volta release doesn't list its archive
+    SelfUpdateChecksumNotFound {
+        file: String,
+    },
+
+    /// Thrown when the latest Volta release, or one of its files, could not be downloaded
+    SelfUpdateFetchError {
+        from_url: String,
+    },
+
+    /// Thrown when the archive of a Volta release doesn't contain the `volta` executable
+    SelfUpdateInvalidRelease {
+        file: String,
+    },
+
+    /// Thrown when `volta self update` is run in an installation that a package manager owns
+    SelfUpdateManagedInstall {
+        manager: String,
+        dir: PathBuf,
+        update: String,
+    },
+
+    /// Thrown when a Volta binary could not be replaced with the one from the new release
+    SelfUpdateReplaceError {
+        file: PathBuf,
+    },
+
+    /// Thrown when Volta doesn't publish a release for the current operating system and
+    /// architecture
+    SelfUpdateUnsupportedPlatform {
+        os: String,
+        arch: String,
+    },
+
     /// Thrown when there was an error setting a tool to executable
     SetToolExecutable {
         tool: String,
@@ -433,16 +911,41 @@ pub enum ErrorKind {
         dir: PathBuf,
     },
 
+    /// Thrown when two shims differ only by case on a case-insensitive file system
+    ShimCaseCollision {
+        first_bin: String,
+        first_owner: String,
+        second_bin: String,
+        second_owner: String,
+    },
+
     /// Thrown when Volta is unable to create a shim
     ShimCreateError {
         name: String,
     },
 
+    /// Thrown when a shim would shadow one of Volta's own executables
+    ShimNameReserved {
+        name: String,
+    },
+
+    /// Thrown when one or more shims could not be regenerated
+    ShimRegenerationError {
+        failures: Vec<PathBuf>,
+    },
+
     /// Thrown when Volta is unable to remove a shim
     ShimRemoveError {
         name: String,
     },
 
+    /// Thrown when a shim left over from an older install shadows Volta's own executable
+    ShimShadowsVolta {
+        name: String,
+        shim: PathBuf,
+        volta: PathBuf,
+    },
+
     /// Thrown when serializing a bin config to JSON fails
     StringifyBinConfigError,
 
@@ -452,27 +955,76 @@ pub enum ErrorKind {
     /// Thrown when serializing the platform to JSON fails
     StringifyPlatformError,
 
+    /// Thrown when serializing the Yarn index cache to JSON fails
+    StringifyYarnIndexCacheError,
+
+    /// Thrown when some of the packages in the tools manifest could not be installed
+    ToolsManifestInstallError {
+        failed: Vec<String>,
+    },
+
+    /// Thrown when a command needs tools that are not installed, and `install.mode` is `manual`
+    ToolsNotInstalled {
+        tools: Vec<String>,
+    },
+
     /// Thrown when a given feature has not yet been implemented
     Unimplemented {
         feature: String,
     },
 
-    /// Thrown when unpacking an archive (tarball or zip) fails
-    UnpackArchiveError {
-        tool: String,
+    /// Thrown when uninstalling the default version of Node
+    UninstallDefaultNode {
         version: String,
     },
 
-    /// Thrown when a package to upgrade was not found
-    UpgradePackageNotFound {
-        package: String,
-        manager: PackageManager,
+    /// Thrown when uninstalling the version of Node that the current project uses
+    UninstallProjectNode {
+        version: String,
+        manifest: PathBuf,
     },
 
-    /// Thrown when a package to upgrade was installed with a different package manager
-    UpgradePackageWrongManager {
-        package: String,
-        manager: PackageManager,
+    /// Thrown when `volta config` is given a setting that doesn't exist
+    UnknownSetting {
+        key: String,
+        known: Vec<String>,
+    },
+
+    /// Thrown when a tag isn't supported by a tool and isn't one of the aliases defined for it
+    UnknownVersionAlias {
+        tool: String,
+        name: String,
+        defined: Vec<String>,
+    },
+
+    /// Thrown when unpacking an archive (tarball or zip) fails
+    UnpackArchiveError {
+        tool: String,
+        version: String,
+    },
+
+    /// Thrown when a `volta.lock` file has a format version this version of Volta doesn't know
+    UnsupportedPlatformLockVersion {
+        file: PathBuf,
+        version: u64,
+    },
+
+    /// Thrown when a script that Windows can't launch has a `#!` line for an interpreter other than Node
+    UnsupportedScriptInterpreter {
+        file: PathBuf,
+        interpreter: String,
+    },
+
+    /// Thrown when a package to upgrade was not found
+    UpgradePackageNotFound {
+        package: String,
+        manager: PackageManager,
+    },
+
+    /// Thrown when a package to upgrade was installed with a different package manager
+    UpgradePackageWrongManager {
+        package: String,
+        manager: PackageManager,
     },
 
     VersionParseError {
@@ -494,6 +1046,11 @@ pub enum ErrorKind {
         tool: String,
     },
 
+    /// Thrown when `volta list --snapshot-to` couldn't write the snapshot
+    WriteListSnapshotError {
+        file: PathBuf,
+    },
+
     /// Thrown when there was an error writing the node index cache
     WriteNodeIndexCacheError {
         file: PathBuf,
@@ -504,20 +1061,55 @@ pub enum ErrorKind {
         file: PathBuf,
     },
 
+    /// Thrown when there was an error writing the Node release schedule cache
+    WriteNodeScheduleCacheError {
+        file: PathBuf,
+    },
+
     /// Thrown when there was an error writing a package config
     WritePackageConfigError {
         file: PathBuf,
     },
 
+    /// Thrown when the `volta.lock` file of a project can't be written
+    WritePlatformLockError {
+        file: PathBuf,
+    },
+
     /// Thrown when writing the platform.json file fails
     WritePlatformError {
         file: PathBuf,
     },
 
+    /// Thrown when a platform profile can't be written
+    WritePlatformProfileError {
+        file: PathBuf,
+    },
+
+    /// Thrown when a shell profile script can't be rewritten
+    WriteProfileError {
+        file: PathBuf,
+    },
+
+    /// Thrown when the settings file couldn't be written by `volta config set`
+    WriteSettingsError {
+        file: PathBuf,
+    },
+
     /// Thrown when unable to write the user PATH environment variable
     #[cfg(windows)]
     WriteUserPathError,
 
+    /// Thrown when there was an error writing the Yarn index cache
+    WriteYarnIndexCacheError {
+        file: PathBuf,
+    },
+
+    /// Thrown when there was an error writing the Yarn index cache expiration
+    WriteYarnIndexExpiryError {
+        file: PathBuf,
+    },
+
     /// Thrown when a user attempts to install a version of Yarn2
     Yarn2NotSupported,
 
@@ -535,6 +1127,20 @@ pub enum ErrorKind {
 impl fmt::Display for ErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            ErrorKind::AliasesJsonVersionError { version, required } => write!(
+                f,
+                "Could not list version aliases in version {} of the JSON output.
+
+Please use `--json={}` or newer, which includes the output of `volta list --aliases`.",
+                version, required
+            ),
+            ErrorKind::AllLtsInvalidTool { tool } => write!(
+                f,
+                "Could not fetch '{}' with --all-lts: only Node has LTS release lines.
+
+Use `volta fetch node --all-lts` to fetch the newest release of each active LTS line.",
+                tool
+            ),
             ErrorKind::BinaryAlreadyInstalled {
                 bin_name,
                 existing_package,
@@ -552,6 +1158,15 @@ Please remove {} before installing {}",
 
 See `volta help install` and `volta help pin` for info about making tools available."
             ),
+            ErrorKind::BinaryNotExecutable { binary } => write!(
+                f,
+                "Could not execute {}
+The file exists, but it is not marked as executable.
+
+This usually means that the tool's archive was unpacked without its file permissions.
+Make the file executable with `chmod +x`, or remove the tool's image directory so that it is unpacked again.",
+                binary.display()
+            ),
             ErrorKind::BinaryNotFound { name } => write!(
                 f,
                 r#"Could not find executable "{}"
@@ -565,6 +1180,13 @@ Use `volta install` to add a package to your toolchain (see `volta help install`
 
 Please ensure your PATH is valid."
             ),
+            ErrorKind::BundledVersionNotSupported { tool } => write!(
+                f,
+                r#"Could not use "bundled" as the {} version in package.json
+
+Only npm is bundled with Node. Please specify a version for {} or remove it from the "volta" section."#,
+                tool, tool
+            ),
             ErrorKind::BypassError { command } => write!(
                 f,
                 "Could not execute command '{}'
@@ -572,6 +1194,14 @@ Please ensure your PATH is valid."
 VOLTA_BYPASS is enabled, please ensure that the command exists on your system or unset VOLTA_BYPASS",
                 command,
             ),
+            ErrorKind::CannotExecPackage { package } => write!(
+                f,
+                "Only Node, npm, pnpm, and Yarn can be run with `volta exec`.
+
+Use `npx {}` to run a package once, or `volta run` to run an installed package with custom
+versions of Node and the package managers.",
+                package
+            ),
             ErrorKind::CannotFetchPackage { package } => write!(
                 f,
                 "Fetching packages without installing them is not supported.
@@ -586,6 +1216,27 @@ Use `volta install {}` to update the default version.",
 Use `npm install` or `yarn add` to select a version of {} for this project.",
                 package
             ),
+            ErrorKind::CannotRemoveNodePin => write!(
+                f,
+                "Cannot remove the Node pin from a project.
+
+The other tools can only be pinned along with Node. To use a different version of Node, use
+`volta pin node@<version>`."
+            ),
+            ErrorKind::ChecksumMismatch {
+                tool,
+                version,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "The downloaded archive for {} {} does not match its published checksum
+Expected SHA-256: {}
+  Actual SHA-256: {}
+
+Please try again, the download may have been corrupted.",
+                tool, version, expected, actual
+            ),
             ErrorKind::CompletionsOutFileError { path } => write!(
                 f,
                 "Completions file `{}` already exists.
@@ -601,6 +1252,24 @@ Please remove the file or pass `-f` or `--force` to override.",
                 path.display(),
                 PERMISSIONS_CTA
             ),
+            ErrorKind::CopyLocalPackageError { package, dir } => write!(
+                f,
+                "Could not copy {} from {}
+
+{}",
+                package,
+                dir.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::CopyNodeInstallError { version, dir } => write!(
+                f,
+                "Could not copy Node v{} from {}
+
+{}",
+                version,
+                dir.display(),
+                PERMISSIONS_CTA
+            ),
             ErrorKind::CouldNotDetermineTool => write!(
                 f,
                 "Could not determine tool name
@@ -680,6 +1349,20 @@ at {}
             ErrorKind::DeprecatedCommandError { command, advice } => {
                 write!(f, "The subcommand `{}` is deprecated.\n{}", command, advice)
             }
+            ErrorKind::DiffJsonVersionError { version, required } => write!(
+                f,
+                "Could not compare with a snapshot in version {} of the JSON output.
+
+Please use `--json={}` or newer, which includes the output of `volta list --diff`.",
+                version, required
+            ),
+            ErrorKind::DynamicCompletionsUnsupported { shell } => write!(
+                f,
+                "Dynamic completions are not available for {}.
+
+Please use the static completions, or one of bash, zsh, and fish.",
+                shell
+            ),
             ErrorKind::DownloadToolNetworkError { tool, from_url } => write!(
                 f,
                 "Could not download {}
@@ -727,6 +1410,42 @@ Please ensure that the correct command is specified.",
 Please ensure that the file exists and is accessible.",
                 path.display(),
             ),
+            ErrorKind::FetchManifestError {
+                file,
+                fetched,
+                skipped,
+                failed,
+                ..
+            } => {
+                writeln!(f, "Could not fetch every tool listed in {}", file.display())?;
+                writeln!(f)?;
+                if !fetched.is_empty() {
+                    writeln!(f, "Fetched: {}", fetched.join(", "))?;
+                }
+                if !skipped.is_empty() {
+                    writeln!(f, "Already fetched: {}", skipped.join(", "))?;
+                }
+                for (tool, error) in failed {
+                    writeln!(f, "Failed: {}", tool)?;
+                    writeln!(f, "{}", indent(error, "    "))?;
+                }
+                write!(
+                    f,
+                    "
+Tools that are already fetched are skipped, so run the command again to retry only the failed
+tools."
+                )
+            }
+            ErrorKind::HashBinaryError { file } => write!(
+                f,
+                "Could not read binary
+{}
+to compute its hash
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
             ErrorKind::HookCommandFailed { command } => write!(
                 f,
                 "Hook command '{}' indicated a failure.
@@ -753,6 +1472,25 @@ Please include one of 'bin', 'prefix', or 'template'"
 Please ensure that the correct command is specified.",
                 command
             ),
+            ErrorKind::HookProbeError { url } => write!(
+                f,
+                "Could not send a HEAD request to {}
+
+Please verify your internet connection.",
+                url
+            ),
+            ErrorKind::IncompatibleNodeEngine {
+                package,
+                required,
+                node,
+            } => write!(
+                f,
+                "'{}' requires Node {}, which doesn't match Node {} used by this project.
+
+Please pin a Node version that matches, or set `volta.engineStrict` to false in package.json
+to run it anyway.",
+                package, required, node
+            ),
             ErrorKind::InstalledPackageNameError => write!(
                 f,
                 "Could not determine the name of the package that was just installed.
@@ -760,6 +1498,20 @@ Please ensure that the correct command is specified.",
 {}",
                 REPORT_BUG_CTA
             ),
+            ErrorKind::InvalidFetchManifest { file, errors } => {
+                writeln!(f, "Invalid fetch manifest {}", file.display())?;
+                writeln!(f)?;
+                for error in errors {
+                    writeln!(f, "{}", indent(error, "    "))?;
+                }
+                write!(
+                    f,
+                    "
+Please ensure that the manifest lists the tools to fetch under `tools`, like
+    {{ \"tools\": [\"node@20.11.1\", \"yarn@1.22\"] }}
+Nothing was fetched."
+                )
+            }
             ErrorKind::InvalidHookCommand { command } => write!(
                 f,
                 "Invalid hook command: '{}'
@@ -830,6 +1582,27 @@ To {action} the package '{version}', please use an explicit version such as '{ve
                 write!(f, "{}\n\n{}", error, wrapped_cta)
             }
 
+            ErrorKind::InvalidNodeArch { arch } => write!(
+                f,
+                "Invalid Node architecture '{}'
+
+Please use the name of an architecture of the Node builds, such as 'x64' or 'arm64'.",
+                arch
+            ),
+            ErrorKind::InvalidPlatformProfileName { name } => write!(
+                f,
+                "Invalid platform profile name '{}'
+
+Profile names may only contain letters, numbers, '-', '_', and '.', and can't start with '.'.",
+                name
+            ),
+            ErrorKind::InvalidPublishMode { mode } => write!(
+                f,
+                "Unrecognized publish hook mode: '{}'
+
+Please specify either 'detach' or 'wait' for the mode.",
+                mode
+            ),
             ErrorKind::InvalidRegistryFormat { format } => write!(
                 f,
                 "Unrecognized index registry format: '{}'
@@ -838,7 +1611,18 @@ Please specify either 'npm' or 'github' for the format.",
 format
             ),
 
-            ErrorKind::InvalidToolName { name, errors } => {
+            ErrorKind::InvalidSettingValue {
+                key,
+                value,
+                expected,
+            } => write!(
+                f,
+                "Invalid value '{}' for setting '{}'.
+
+Please use {}.",
+                value, key, expected
+            ),
+            ErrorKind::InvalidToolName { name, errors, hint } => {
                 let indentation = "    ";
                 let wrapped = match text_width() {
                     Some(width) => fill(&errors.join("\n"), width - indentation.len()),
@@ -856,13 +1640,88 @@ format
                     f,
                     "Invalid tool name `{}`\n\n{}\n{}",
                     name, call_to_action, formatted_errs
-                )
+                )?;
+
+                match (hint, text_width()) {
+                    (Some(hint), Some(width)) => write!(f, "\n\n{}", fill(hint, width)),
+                    (Some(hint), None) => write!(f, "\n\n{}", hint),
+                    (None, _) => Ok(()),
+                }
             }
             // Note: No CTA as this error is purely informational and shouldn't be exposed to the user
+            ErrorKind::InvalidVersionAlias {
+                tool,
+                name,
+                target,
+                file,
+            } => write!(
+                f,
+                "Invalid version alias `{}@{}` (set to '{}')
+in {}
+
+Aliases must stand for a version or a range of versions, and can't be named after a version, a
+range, or the `latest` and `lts` tags.",
+                tool,
+                name,
+                target,
+                file.display()
+            ),
+            ErrorKind::InvalidVersionPolicy { tool, reason, file } => write!(
+                f,
+                "Invalid version policy for {}
+in {}
+
+{}",
+                tool,
+                file.display(),
+                reason
+            ),
+            ErrorKind::JsonOutputError => write!(
+                f,
+                "Could not write the output as JSON.
+
+A path in the output may not be valid Unicode."
+            ),
+            ErrorKind::LocalPackageNotFound { path } => write!(
+                f,
+                "Could not find a package to install at {}
+
+Please ensure the path is correct and that it is a package tarball or a directory containing a package.json file.",
+                path.display()
+            ),
             ErrorKind::LockAcquireError => write!(
                 f,
                 "Unable to acquire lock on Volta directory"
             ),
+            ErrorKind::LockfileNotInferred { lockfile, reason } => write!(
+                f,
+                "Could not infer a package manager version from {}:
+{}.
+
+Please pin the package manager explicitly, e.g. `volta pin yarn@4`.",
+                lockfile.display(),
+                reason
+            ),
+            ErrorKind::ManifestNotStrictJson { file } => write!(
+                f,
+                "Could not update project manifest
+at {}
+
+The file contains comments or trailing commas, which Volta can read but can't preserve when
+rewriting it. Please remove them, or edit the `volta` settings by hand.",
+                file.display()
+            ),
+            ErrorKind::MultipleLockfiles {
+                directory,
+                lockfiles,
+            } => write!(
+                f,
+                "Could not infer a package manager, as {} has more than one lockfile: {}
+
+Please remove the lockfiles that are no longer used, or pin the package manager explicitly.",
+                directory.display(),
+                lockfiles.join(", ")
+            ),
             ErrorKind::NoBundledNpm { command } => write!(
                 f,
                 "Could not detect bundled npm version.
@@ -870,6 +1729,14 @@ format
 Please ensure you have a Node version selected with `volta {} node` (see `volta help {0}` for more info).",
                 command
             ),
+            ErrorKind::NoBundledNpmNodeVersion => write!(
+                f,
+                "No Node version specified for npm@bundled-for.
+
+Please include the Node version to record the bundled npm for, for example:
+
+    volta fetch npm@bundled-for node@20"
+            ),
             ErrorKind::NoCommandLinePnpm => write!(
                 f,
                 "No pnpm version specified.
@@ -889,6 +1756,107 @@ Use `volta run --yarn` to select a version (see `volta help run` for more info).
 Use `volta install node` to select a default Node first, then install a {0} version.",
                                 tool
             ),
+            ErrorKind::NodeArchNotAvailable {
+                version,
+                arch,
+                available,
+            } => {
+                writeln!(f, "There is no {} build of {}.", arch, version)?;
+                if available.is_empty() {
+                    write!(f, "\nPlease choose another version, or remove the architecture override.")
+                } else {
+                    write!(
+                        f,
+                        "\nIt is available for: {}\n\nPlease choose one of those architectures, or remove the override.",
+                        available.join(", ")
+                    )
+                }
+            }
+            ErrorKind::NodeChecksumNotFound { file } => write!(
+                f,
+                "Could not find the checksum for {} in the checksum file.
+
+Please ensure that the `node.shasums` hook in your hooks.json provides the checksums for Node.",
+                file
+            ),
+            ErrorKind::NodeInstallVersionMismatch {
+                dir,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Expected the Node installation at {} to be v{}, but it reported {}
+
+Please verify that the installation is not corrupted.",
+                dir.display(),
+                expected,
+                found
+            ),
+            ErrorKind::NodeRequiresNewerGlibc {
+                version,
+                loader_error,
+                glibc,
+                newest_major,
+            } => {
+                match glibc {
+                    Some(glibc) => writeln!(
+                        f,
+                        "{} requires a newer version of glibc than this system provides ({}).",
+                        version, glibc
+                    )?,
+                    None => writeln!(
+                        f,
+                        "{} requires a newer version of glibc than this system provides.",
+                        version
+                    )?,
+                }
+                writeln!(f, "{}", indent(loader_error, "    "))?;
+                writeln!(f)?;
+                if let (Some(glibc), Some(major)) = (glibc, newest_major) {
+                    writeln!(
+                        f,
+                        "The newest Node version known to work with glibc {} is Node {}. To use it, run:
+    volta pin node@{}
+",
+                        glibc, major, major
+                    )?;
+                }
+                write!(
+                    f,
+                    "If this check is wrong for your system, it can be skipped by setting
+`node.glibcCheck` to `false` in your Volta settings."
+                )
+            }
+            ErrorKind::NodeScheduleFetchError { from_url } => write!(
+                f,
+                "Could not download the Node release schedule
+from {}
+
+Please verify your internet connection and the `node.schedule` hook in your hooks.json.",
+                from_url
+            ),
+            ErrorKind::NodeShasumsFetchError { from_url } => write!(
+                f,
+                "Could not download the Node checksum file
+from {}
+
+Please verify your internet connection, and the `node.shasums` hook if your hooks.json has one.",
+                from_url
+            ),
+            ErrorKind::NodeUninstallVersionRequired { installed } => {
+                let installed = if installed.is_empty() {
+                    "No versions of Node are installed.".to_string()
+                } else {
+                    format!("Installed versions:\n    {}", installed.join("\n    "))
+                };
+                write!(
+                    f,
+                    "Please specify the version of Node to uninstall, e.g. `volta uninstall node@20.11.1`
+
+{}",
+                    installed
+                )
+            }
             ErrorKind::NodeVersionNotFound { matching } => write!(
                 f,
                 r#"Could not find Node version matching "{}" in the version registry.
@@ -896,6 +1864,13 @@ Use `volta install node` to select a default Node first, then install a {0} vers
 Please verify that the version is correct."#,
                 matching
             ),
+            ErrorKind::NodeVersionTagNotSupported { tag } => write!(
+                f,
+                r#"Could not resolve Node version "{}": Node does not publish custom version tags.
+
+Please use 'lts', 'current', 'latest', an exact version (e.g. 20.11.1), or a version range (e.g. ^20)."#,
+                tag
+            ),
             ErrorKind::NoHomeEnvironmentVar => write!(
                 f,
                 "Could not determine home directory.
@@ -914,6 +1889,14 @@ Please ensure Volta was installed correctly"
 
 Please ensure the directory is available."
             ),
+            ErrorKind::NoLockfile { directory } => write!(
+                f,
+                "Could not find a lockfile in {}
+
+Volta infers the package manager from `yarn.lock` or `pnpm-lock.yaml`. Please install the
+project's dependencies first, or pin the package manager explicitly.",
+                directory.display()
+            ),
             ErrorKind::NoPinnedNodeVersion { tool } => write!(
                 f,
                 "Cannot pin {} because the Node version is not pinned in this project.
@@ -953,6 +1936,13 @@ Tried $PROFILE ({}), ~/.bashrc, ~/.bash_profile, ~/.zshenv ~/.zshrc, ~/.profile,
 Please create one of these and try again; or you can edit your profile manually to add '{}' to your PATH",
                 env_profile, bin_dir.display()
             ),
+            ErrorKind::NoToolsManifest => write!(
+                f,
+                "Could not find a `.volta/tools.json` file in this project.
+
+Create one in the project root that lists the global packages to install, like
+    {{ \"packages\": {{ \"typescript\": \"^5\" }} }}"
+            ),
             ErrorKind::NotInPackage => write!(
                 f,
                 "Not in a node package.
@@ -971,6 +1961,21 @@ Use `volta install pnpm` to select a default version (see `volta help install` f
 
 Use `volta install yarn` to select a default version (see `volta help install` for more info)."
             ),
+            ErrorKind::NotionBackupError { from, to } => write!(
+                f,
+                "Could not move the Notion directory {} to {}
+
+Please ensure you have permission to rename it, or move it yourself.",
+                from.display(),
+                to.display()
+            ),
+            ErrorKind::NotionMigrationNotConfirmed { dir } => write!(
+                f,
+                "Did not migrate the Notion directory {}, as the migration was not confirmed.
+
+To confirm, run the command again with `--yes`.",
+                dir.display()
+            ),
             ErrorKind::NpmLinkMissingPackage { package } => write!(
                 f,
                 "Could not locate the package '{}'
@@ -999,25 +2004,98 @@ Please verify that the version is correct."#,
 This project is configured to use version {} of npm.",
                 version
             ),
-            ErrorKind::PackageInstallFailed { package } => write!(
+            ErrorKind::NvmDirNotFound { dir } => write!(
                 f,
-                "Could not install package '{}'
+                "Could not find any nvm-installed Node versions in {}
 
-Please confirm the package is valid and run with `--verbose` for more diagnostics.",
-                package
+Please ensure the nvm directory is correct, or pass its location with `--nvm-dir`.",
+                dir.display()
             ),
-            ErrorKind::PackageManifestParseError { package } => write!(
+            ErrorKind::OfflineArchiveChecksumMismatch {
+                file,
+                expected,
+                actual,
+            } => write!(
                 f,
-                "Could not parse package.json manifest for {}
+                "The archive {} does not match its checksum file
+Expected SHA-256: {}
+  Actual SHA-256: {}
 
-Please ensure the package includes a valid manifest file.",
-                package
+Please replace the archive, it may have been corrupted while it was copied.",
+                file.display(),
+                expected,
+                actual
             ),
-            ErrorKind::PackageManifestReadError { package } => write!(
+            ErrorKind::OfflineArchiveNotFound { tool, dir, file } => write!(
                 f,
-                "Could not read package.json manifest for {}
+                "Could not find the archive for {} in {}
+Expected a file named: {}
 
-Please ensure the package includes a valid manifest file.",
+Please add the archive to that directory and try again.",
+                tool,
+                dir.display(),
+                file
+            ),
+            ErrorKind::OfflineArchiveReadError { file } => write!(
+                f,
+                "Could not read the archive {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::OfflineInstallNeedsVersion { tool } => write!(
+                f,
+                "Could not install {} from a directory of archives.
+
+Please specify an exact version, like `node@18.16.0`, as other versions can't be resolved without network access.",
+                tool
+            ),
+            ErrorKind::OfflineVersionNotAvailable {
+                tool,
+                matching,
+                available,
+            } => {
+                let available = if available.is_empty() {
+                    "None".to_string()
+                } else {
+                    available.join(", ")
+                };
+                write!(
+                    f,
+                    "Could not find a version of {} matching '{}' without network access.
+Available locally: {}
+
+Please use one of the available versions, or run without `--offline` and `VOLTA_OFFLINE` to download it.",
+                    tool, matching, available
+                )
+            }
+            ErrorKind::OutdatedJsonVersionError { version, required } => write!(
+                f,
+                "Could not list outdated tools in version {} of the JSON output.
+
+Please use `--json={}` or newer, which includes the output of `volta list --outdated`.",
+                version, required
+            ),
+            ErrorKind::PackageInstallFailed { package } => write!(
+                f,
+                "Could not install package '{}'
+
+Please confirm the package is valid and run with `--verbose` for more diagnostics.",
+                package
+            ),
+            ErrorKind::PackageManifestParseError { package } => write!(
+                f,
+                "Could not parse package.json manifest for {}
+
+Please ensure the package includes a valid manifest file.",
+                package
+            ),
+            ErrorKind::PackageManifestReadError { package } => write!(
+                f,
+                "Could not read package.json manifest for {}
+
+Please ensure the package includes a valid manifest file.",
                 package
             ),
             ErrorKind::PackageNotFound { package } => write!(
@@ -1064,6 +2142,15 @@ Please ensure you have correct permissions.",
 {}",
                 REPORT_BUG_CTA
             ),
+            ErrorKind::ParseBinaryManifestError { file, line } => write!(
+                f,
+                "Could not parse line {} of binary manifest
+{}
+
+Please ensure the file was written by `volta verify-binaries`.",
+                line,
+                file.display()
+            ),
             ErrorKind::ParseHooksError { file } => write!(
                 f,
                 "Could not parse hooks configuration file.
@@ -1072,6 +2159,14 @@ from {}
 Please ensure the file is correctly formatted.",
                 file.display()
             ),
+            ErrorKind::ParseListSnapshotError { file } => write!(
+                f,
+                "Could not parse snapshot file
+from {}
+
+Please use a file written by `volta list --snapshot-to`.",
+                file.display()
+            ),
             ErrorKind::ParseNodeIndexCacheError => write!(
                 f,
                 "Could not parse Node index cache file.
@@ -1079,13 +2174,19 @@ Please ensure the file is correctly formatted.",
 {}",
                 REPORT_BUG_CTA
             ),
-            ErrorKind::ParseNodeIndexError { from_url } => write!(
+            ErrorKind::ParseNodeIndexError {
+                from_url,
+                response_start,
+            } => write!(
                 f,
                 "Could not parse Node version index
 from {}
 
-Please verify your internet connection.",
-                from_url
+The response began with:
+{}
+
+Please verify your internet connection, and that no proxy or captive portal is intercepting requests.",
+                from_url, response_start
             ),
             ErrorKind::ParseNodeIndexExpiryError => write!(
                 f,
@@ -1094,6 +2195,14 @@ Please verify your internet connection.",
 {}",
                 REPORT_BUG_CTA
             ),
+            ErrorKind::ParseNodeScheduleError { from_url } => write!(
+                f,
+                "Could not parse the Node release schedule
+from {}
+
+Please verify your internet connection.",
+                from_url
+            ),
             ErrorKind::ParseNpmManifestError => write!(
                 f,
                 "Could not parse package.json file for bundled npm.
@@ -1107,6 +2216,14 @@ Please ensure the version of Node is correct."
 {}",
                 REPORT_BUG_CTA
             ),
+            ErrorKind::ParsePlatformLockError { file } => write!(
+                f,
+                "Could not parse platform lock file
+from {}
+
+Please ensure the file is correctly formatted, or run `volta lock --update` to regenerate it.",
+                file.display()
+            ),
             ErrorKind::ParsePlatformError => write!(
                 f,
                 "Could not parse platform settings file.
@@ -1114,6 +2231,26 @@ Please ensure the version of Node is correct."
 {}",
                 REPORT_BUG_CTA
             ),
+            ErrorKind::ParsePlatformProfileError { file, error } => write!(
+                f,
+                "Could not parse platform profile
+from {}
+
+{}
+
+A profile has the same format as the default platform file, like
+    {{ \"node\": {{ \"runtime\": \"20.11.1\", \"npm\": null }}, \"yarn\": \"1.22.19\" }}",
+                file.display(),
+                error
+            ),
+            ErrorKind::ParseSettingsError { file } => write!(
+                f,
+                "Could not parse settings file.
+from {}
+
+Please ensure the file is correctly formatted.",
+                file.display()
+            ),
             ErrorKind::ParseToolSpecError { tool_spec } => write!(
                 f,
                 "Could not parse tool spec `{}`
@@ -1121,6 +2258,53 @@ Please ensure the version of Node is correct."
 Please supply a spec in the format `<tool name>[@<version>]`.",
                 tool_spec
             ),
+            ErrorKind::ParseToolsManifestError { file, error } => write!(
+                f,
+                "Could not parse the tools manifest
+from {}
+
+{}
+
+The file lists the global packages to install by version, like
+    {{ \"packages\": {{ \"typescript\": \"^5\", \"eslint\": \"8.50.0\" }} }}",
+                file.display(),
+                error
+            ),
+            ErrorKind::ParseYarnIndexCacheError => write!(
+                f,
+                "Could not parse Yarn index cache file.
+
+{}",
+                REPORT_BUG_CTA
+            ),
+            ErrorKind::ParseYarnIndexExpiryError => write!(
+                f,
+                "Could not parse Yarn index cache expiration file.
+
+{}",
+                REPORT_BUG_CTA
+            ),
+            ErrorKind::PathTooLong {
+                length,
+                limit,
+                added,
+            } => {
+                write!(
+                    f,
+                    "Could not create execution environment: PATH would be {} characters long, more than the limit of {}.",
+                    length, limit
+                )?;
+                if !added.is_empty() {
+                    let added: Vec<String> =
+                        added.iter().map(|dir| dir.display().to_string()).collect();
+                    write!(
+                        f,
+                        "\n\nVolta added these directories to PATH:\n    {}",
+                        added.join("\n    ")
+                    )?;
+                }
+                write!(f, "\n\nPlease remove duplicate or unused entries from your PATH.")
+            }
             ErrorKind::PersistInventoryError { tool } => write!(
                 f,
                 "Could not store {} archive in inventory cache
@@ -1128,6 +2312,100 @@ Please supply a spec in the format `<tool name>[@<version>]`.",
 {}",
                 tool, PERMISSIONS_CTA
             ),
+            ErrorKind::PinInferredNotConfirmed {
+                tool,
+                requirement,
+                found,
+            } => {
+                let found = found
+                    .iter()
+                    .map(|found| format!("    {}", found))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                write!(
+                    f,
+                    "Did not pin {}, as the project records different versions of it:
+{}
+
+To pin {}, run the command again with `--yes`. To pin another version, pass it to `volta pin`.",
+                    tool,
+                    found,
+                    tool_version(tool, requirement)
+                )
+            }
+            ErrorKind::PinMembersNotConfirmed { manifest, members } => {
+                let members = members
+                    .iter()
+                    .map(|member| format!("    {}", member.display()))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                write!(
+                    f,
+                    "Did not pin, as the change to {} was not confirmed.
+
+It would also change the platform of these workspace members, which extend it:
+{}
+
+To confirm, run the command again with `--yes`. To skip this check, use `--no-verify-members`.",
+                    manifest.display(),
+                    members
+                )
+            }
+            ErrorKind::PlatformCheckoutError {
+                present,
+                fetched,
+                failed,
+                ..
+            } => {
+                writeln!(f, "Could not fetch every tool in the platform for this command.")?;
+                writeln!(f)?;
+                if !present.is_empty() {
+                    writeln!(f, "Already available: {}", present.join(", "))?;
+                }
+                if !fetched.is_empty() {
+                    writeln!(f, "Fetched: {}", fetched.join(", "))?;
+                }
+                for (tool, error) in failed {
+                    writeln!(f, "Failed: {}", tool)?;
+                    writeln!(f, "{}", indent(error, "    "))?;
+                }
+                write!(
+                    f,
+                    "
+The command was not run. Tools that are available do not need to be fetched again, so run the
+command again to retry only the failed tools."
+                )
+            }
+            ErrorKind::PlatformLockOutdated { file, manifest } => write!(
+                f,
+                "The platform lock file {}
+is out of date with the Volta settings in {}
+
+Use `volta lock --update` to update the lock file.",
+                file.display(),
+                manifest.display()
+            ),
+            ErrorKind::PlatformProfileNotFound { name, available } => {
+                write!(f, "Could not find a platform profile named '{}'.", name)?;
+                if available.is_empty() {
+                    write!(
+                        f,
+                        "
+
+There are no profiles yet. Use `volta profile save <name>` to save the current platform as one."
+                    )
+                } else {
+                    write!(f, "\n\nAvailable profiles: {}", available.join(", "))
+                }
+            }
+            ErrorKind::PlatformWriteConflict { file } => write!(
+                f,
+                "Could not save platform settings to {}
+as it was repeatedly changed by another Volta process.
+
+Please try again.",
+                file.display()
+            ),
             ErrorKind::PnpmVersionNotFound { matching } => write!(
                 f,
                 r#"Could not find pnpm version matching "{}" in the version registry.
@@ -1135,6 +2413,31 @@ Please supply a spec in the format `<tool name>[@<version>]`.",
 Please verify that the version is correct."#,
                 matching
             ),
+            ErrorKind::PolicyViolation {
+                tool,
+                version,
+                rule,
+                file,
+                overridable,
+            } => {
+                let cta = if *overridable {
+                    "Please use a version that the policy allows, or run the command again with `--override-policy` to ignore it."
+                } else {
+                    "Please use a version that the policy allows. This policy can't be overridden."
+                };
+                write!(
+                    f,
+                    "{}@{} is not allowed by the version policy in {}
+Rule: {}
+
+{}",
+                    tool,
+                    version,
+                    file.display(),
+                    rule,
+                    cta
+                )
+            }
             ErrorKind::ProjectLocalBinaryExecError { command } => write!(
                 f,
                 "Could not execute `{}`
@@ -1146,7 +2449,7 @@ Please ensure you have correct permissions to access the file.",
                 f,
                 "Could not locate executable `{}` in your project.
 
-Please ensure that all project dependencies are installed with `npm install` or `yarn install`",
+Please ensure that all project dependencies are installed with `npm install`, `yarn install` or `pnpm install`",
                 command
             ),
             ErrorKind::PublishHookBothUrlAndBin => write!(
@@ -1175,6 +2478,15 @@ at {}
                 "Could not read executable configuration
 from {}
 
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::ReadBinaryManifestError { file } => write!(
+                f,
+                "Could not read binary manifest
+from {}
+
 {}",
                 file.display(),
                 PERMISSIONS_CTA
@@ -1195,11 +2507,37 @@ from {}
 {}",
                 dir.display(), PERMISSIONS_CTA
             ),
+            ErrorKind::ReadFetchManifestError { file } => write!(
+                f,
+                "Could not read fetch manifest
+from {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
             ErrorKind::ReadHooksError { file } => write!(
                 f,
                 "Could not read hooks file
 from {}
 
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::ReadListSnapshotError { file } => write!(
+                f,
+                "Could not read snapshot file
+from {}
+
+Please ensure the file exists and you have access to it.",
+                file.display()
+            ),
+            ErrorKind::ReadLockfileError { file } => write!(
+                f,
+                "Could not read lockfile
+from {}
+
 {}",
                 file.display(),
                 PERMISSIONS_CTA
@@ -1222,6 +2560,13 @@ from {}
                 file.display(),
                 PERMISSIONS_CTA
             ),
+            ErrorKind::ReadNodeInstallVersionError { bin } => write!(
+                f,
+                "Could not determine the version of Node at {}
+
+Please verify that the installation is not corrupted.",
+                bin.display()
+            ),
             ErrorKind::ReadNpmManifestError => write!(
                 f,
                 "Could not read package.json file for bundled npm.
@@ -1233,6 +2578,15 @@ Please ensure the version of Node is correct."
                 "Could not read package configuration file
 from {}
 
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::ReadPlatformLockError { file } => write!(
+                f,
+                "Could not read platform lock file
+from {}
+
 {}",
                 file.display(),
                 PERMISSIONS_CTA
@@ -1242,6 +2596,42 @@ from {}
                 "Could not read default platform file
 from {}
 
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::ReadPlatformProfileError { file } => write!(
+                f,
+                "Could not read platform profile
+from {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::ReadSettingsError { file } => write!(
+                f,
+                "Could not read settings file
+from {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::ReadToolsManifestError { file } => write!(
+                f,
+                "Could not read the tools manifest
+from {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::ReadUsageStatsError { file } => write!(
+                f,
+                "Could not read usage counters
+from {}
+
 {}",
                 file.display(),
                 PERMISSIONS_CTA
@@ -1253,6 +2643,24 @@ from {}
 
 Please ensure you have access to the your environment variables."
             ),
+            ErrorKind::ReadYarnIndexCacheError { file } => write!(
+                f,
+                "Could not read Yarn index cache
+from {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::ReadYarnIndexExpiryError { file } => write!(
+                f,
+                "Could not read Yarn index cache expiration
+from {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
             ErrorKind::RegistryFetchError { tool, from_url } => write!(
                 f,
                 "Could not download {} version registry
@@ -1261,12 +2669,79 @@ from {}
 Please verify your internet connection.",
                 tool, from_url
             ),
+            ErrorKind::RelinkPackagesError { packages } => write!(
+                f,
+                "Could not repair the shared links for: {}
+
+{}",
+                packages.join(", "),
+                PERMISSIONS_CTA
+            ),
             ErrorKind::RunShimDirectly => write!(
                 f,
                 "'volta-shim' should not be called directly.
 
 Please use the existing shims provided by Volta (node, yarn, etc.) to run tools."
             ),
+            ErrorKind::RunWhereUnsupported { command } => write!(
+                f,
+                "Could not determine the executable for `{}`.
+
+Volta handles this command itself (e.g. a global package install) instead of running a single
+executable, so it can't be used with `volta run --where`.",
+                command
+            ),
+            ErrorKind::SelfUpdateChecksumNotFound { file } => write!(
+                f,
+                "Could not find the checksum for {} in the checksum file of the release.
+
+Volta was not updated, please try again later.",
+                file
+            ),
+            ErrorKind::SelfUpdateFetchError { from_url } => write!(
+                f,
+                "Could not download the latest release of Volta
+from {}
+
+Please verify your internet connection.",
+                from_url
+            ),
+            ErrorKind::SelfUpdateInvalidRelease { file } => write!(
+                f,
+                "The release archive {} does not contain the Volta executable.
+
+Volta was not updated, please try again later.",
+                file
+            ),
+            ErrorKind::SelfUpdateManagedInstall {
+                manager,
+                dir,
+                update,
+            } => write!(
+                f,
+                "Volta was installed with {} in {}, so it can't update itself.
+
+Please update it with {} instead.",
+                manager,
+                dir.display(),
+                update
+            ),
+            ErrorKind::SelfUpdateReplaceError { file } => write!(
+                f,
+                "Could not replace {} with the new release.
+The binaries that were already replaced have been restored.
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::SelfUpdateUnsupportedPlatform { os, arch } => write!(
+                f,
+                "Volta does not publish releases for {} on {}, so it can't update itself.
+
+Please build the latest version from source instead.",
+                os, arch
+            ),
             ErrorKind::SetToolExecutable { tool } => write!(
                 f,
                 r#"Could not set "{}" to executable
@@ -1285,6 +2760,19 @@ at {}
                 dir.display(),
                 PERMISSIONS_CTA
             ),
+            ErrorKind::ShimCaseCollision {
+                first_bin,
+                first_owner,
+                second_bin,
+                second_owner,
+            } => write!(
+                f,
+                r#"Could not regenerate shims: "{}" (from {}) and "{}" (from {}) differ only by case.
+
+The shim directory is on a case-insensitive file system, so only one of them can be installed.
+Please uninstall one of the packages and try again."#,
+                first_bin, first_owner, second_bin, second_owner
+            ),
             ErrorKind::ShimCreateError { name } => write!(
                 f,
                 r#"Could not create shim for "{}"
@@ -1292,6 +2780,31 @@ at {}
 {}"#,
                 name, PERMISSIONS_CTA
             ),
+            ErrorKind::ShimNameReserved { name } => write!(
+                f,
+                "Could not create a shim for '{}'
+
+The name is reserved for Volta's own executables, which the shim would shadow.",
+                name
+            ),
+            ErrorKind::ShimRegenerationError { failures } => {
+                let paths = failures
+                    .iter()
+                    .map(|path| format!("    {}", path.display()))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                write!(
+                    f,
+                    "Could not regenerate {} {}:
+{}
+
+The remaining shims were regenerated. Please fix the permissions on the files listed above
+and run the command again.",
+                    failures.len(),
+                    if failures.len() == 1 { "shim" } else { "shims" },
+                    paths
+                )
+            }
             ErrorKind::ShimRemoveError { name } => write!(
                 f,
                 r#"Could not remove shim for "{}"
@@ -1299,6 +2812,17 @@ at {}
 {}"#,
                 name, PERMISSIONS_CTA
             ),
+            ErrorKind::ShimShadowsVolta { name, shim, volta } => write!(
+                f,
+                "The '{}' shim at {} is shadowing Volta's own executable.
+
+It was created by an older version of Volta for a package that provides a '{}' executable.
+Please remove it by running `\"{}\" doctor --fix`, or delete the shim directly.",
+                name,
+                shim.display(),
+                name,
+                volta.display()
+            ),
             ErrorKind::StringifyBinConfigError => write!(
                 f,
                 "Could not serialize executable configuration.
@@ -1320,9 +2844,71 @@ at {}
 {}",
                 REPORT_BUG_CTA
             ),
+            ErrorKind::StringifyYarnIndexCacheError => write!(
+                f,
+                "Could not serialize Yarn index cache.
+
+{}",
+                REPORT_BUG_CTA
+            ),
+            ErrorKind::ToolsManifestInstallError { failed } => write!(
+                f,
+                "Could not install {} from the tools manifest: {}
+
+The other tools were installed, see the errors above for the details.",
+                if failed.len() == 1 { "a tool" } else { "some tools" },
+                failed.join(", ")
+            ),
+            ErrorKind::ToolsNotInstalled { tools } => write!(
+                f,
+                "This command needs tools that are not installed: {}
+
+Automatic installs are disabled (`install.mode` is `manual`). To install them, run:
+    volta fetch {}",
+                tools.join(", "),
+                tools.join(" ")
+            ),
             ErrorKind::Unimplemented { feature } => {
                 write!(f, "{} is not supported yet.", feature)
             }
+            ErrorKind::UninstallDefaultNode { version } => write!(
+                f,
+                "Could not uninstall {} because it is your default Node version.
+
+Use `volta install node@<version>` to set a different default first.",
+                tool_version("node", version)
+            ),
+            ErrorKind::UninstallProjectNode { version, manifest } => write!(
+                f,
+                "Could not uninstall {} because it is used by the current project ({}).
+
+Use `volta pin node@<version>` to pin a different version first, or run the command outside of the project.",
+                tool_version("node", version),
+                manifest.display()
+            ),
+            ErrorKind::UnknownSetting { key, known } => write!(
+                f,
+                "Unknown setting '{}'.
+
+The known settings are: {}",
+                key,
+                known.join(", ")
+            ),
+            ErrorKind::UnknownVersionAlias {
+                tool,
+                name,
+                defined,
+            } => write!(
+                f,
+                "Could not find a version alias named '{}' for {}.
+
+The aliases defined for {} are: {}
+Use `volta list --aliases` to see where they are defined.",
+                name,
+                tool,
+                tool,
+                defined.join(", ")
+            ),
             ErrorKind::UnpackArchiveError { tool, version } => write!(
                 f,
                 "Could not unpack {} v{}
@@ -1330,6 +2916,24 @@ at {}
 Please ensure the correct version is specified.",
                 tool, version
             ),
+            ErrorKind::UnsupportedPlatformLockVersion { file, version } => write!(
+                f,
+                "The platform lock file {}
+has version {}, which is not supported by this version of Volta.
+
+Please upgrade Volta, or run `volta lock --update` to regenerate the lock file.",
+                file.display(),
+                version
+            ),
+            ErrorKind::UnsupportedScriptInterpreter { file, interpreter } => write!(
+                f,
+                "Could not run {}
+It is a script for `{}`, which Volta can't run on Windows.
+
+Only scripts that start with a `#!` line for `node` can be run directly. Please run the script with its interpreter instead.",
+                file.display(),
+                interpreter
+            ),
             ErrorKind::UpgradePackageNotFound { package, manager } => write!(
                 f,
                 r#"Could not locate the package '{}' to upgrade.
@@ -1388,6 +2992,14 @@ to {}
 This is most likely an intermittent failure, please try again.",
                 tool
             ),
+            ErrorKind::WriteListSnapshotError { file } => write!(
+                f,
+                "Could not write snapshot file
+to {}
+
+Please ensure the directory exists and you have permission to write to it.",
+                file.display()
+            ),
             ErrorKind::WriteNodeIndexCacheError { file } => write!(
                 f,
                 "Could not write Node index cache
@@ -1402,6 +3014,15 @@ to {}
                 "Could not write Node index cache expiration
 to {}
 
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::WriteNodeScheduleCacheError { file } => write!(
+                f,
+                "Could not write Node release schedule cache
+to {}
+
 {}",
                 file.display(),
                 PERMISSIONS_CTA
@@ -1411,6 +3032,15 @@ to {}
                 "Could not write package configuration
 to {}
 
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::WritePlatformLockError { file } => write!(
+                f,
+                "Could not write platform lock file
+to {}
+
 {}",
                 file.display(),
                 PERMISSIONS_CTA
@@ -1420,6 +3050,33 @@ to {}
                 "Could not save platform settings
 to {}
 
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::WritePlatformProfileError { file } => write!(
+                f,
+                "Could not write platform profile
+to {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::WriteProfileError { file } => write!(
+                f,
+                "Could not write shell profile script
+to {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::WriteSettingsError { file } => write!(
+                f,
+                "Could not write settings file
+to {}
+
 {}",
                 file.display(),
                 PERMISSIONS_CTA
@@ -1431,6 +3088,24 @@ to {}
 
 Please ensure you have permissions to edit your environment variables."
             ),
+            ErrorKind::WriteYarnIndexCacheError { file } => write!(
+                f,
+                "Could not write Yarn index cache
+to {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::WriteYarnIndexExpiryError { file } => write!(
+                f,
+                "Could not write Yarn index cache expiration
+to {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
             ErrorKind::Yarn2NotSupported => write!(
                 f,
                 "Yarn version 2 is not recommended for use, and not supported by Volta.
@@ -1459,15 +3134,24 @@ Please verify that the version is correct."#,
 impl ErrorKind {
     pub fn exit_code(&self) -> ExitCode {
         match self {
+            ErrorKind::AliasesJsonVersionError { .. } => ExitCode::InvalidArguments,
+            ErrorKind::AllLtsInvalidTool { .. } => ExitCode::InvalidArguments,
             ErrorKind::BinaryAlreadyInstalled { .. } => ExitCode::FileSystemError,
             ErrorKind::BinaryExecError => ExitCode::ExecutionFailure,
+            ErrorKind::BinaryNotExecutable { .. } => ExitCode::ExecutionFailure,
             ErrorKind::BinaryNotFound { .. } => ExitCode::ExecutableNotFound,
             ErrorKind::BuildPathError => ExitCode::EnvironmentError,
+            ErrorKind::BundledVersionNotSupported { .. } => ExitCode::ConfigurationError,
             ErrorKind::BypassError { .. } => ExitCode::ExecutionFailure,
+            ErrorKind::CannotExecPackage { .. } => ExitCode::InvalidArguments,
             ErrorKind::CannotFetchPackage { .. } => ExitCode::InvalidArguments,
             ErrorKind::CannotPinPackage { .. } => ExitCode::InvalidArguments,
+            ErrorKind::CannotRemoveNodePin => ExitCode::InvalidArguments,
+            ErrorKind::ChecksumMismatch { .. } => ExitCode::NetworkError,
             ErrorKind::CompletionsOutFileError { .. } => ExitCode::InvalidArguments,
             ErrorKind::ContainingDirError { .. } => ExitCode::FileSystemError,
+            ErrorKind::CopyLocalPackageError { .. } => ExitCode::FileSystemError,
+            ErrorKind::CopyNodeInstallError { .. } => ExitCode::FileSystemError,
             ErrorKind::CouldNotDetermineTool => ExitCode::UnknownError,
             ErrorKind::CouldNotStartMigration => ExitCode::EnvironmentError,
             ErrorKind::CreateDirError { .. } => ExitCode::FileSystemError,
@@ -1479,43 +3163,81 @@ impl ErrorKind {
             ErrorKind::DeleteDirectoryError { .. } => ExitCode::FileSystemError,
             ErrorKind::DeleteFileError { .. } => ExitCode::FileSystemError,
             ErrorKind::DeprecatedCommandError { .. } => ExitCode::InvalidArguments,
+            ErrorKind::DiffJsonVersionError { .. } => ExitCode::InvalidArguments,
+            ErrorKind::DynamicCompletionsUnsupported { .. } => ExitCode::InvalidArguments,
             ErrorKind::DownloadToolNetworkError { .. } => ExitCode::NetworkError,
             ErrorKind::ExecuteHookError { .. } => ExitCode::ExecutionFailure,
             ErrorKind::ExtensionCycleError { .. } => ExitCode::ConfigurationError,
             ErrorKind::ExtensionPathError { .. } => ExitCode::FileSystemError,
+            ErrorKind::FetchManifestError { exit_code, .. } => *exit_code,
+            ErrorKind::HashBinaryError { .. } => ExitCode::FileSystemError,
             ErrorKind::HookCommandFailed { .. } => ExitCode::ConfigurationError,
             ErrorKind::HookMultipleFieldsSpecified => ExitCode::ConfigurationError,
             ErrorKind::HookNoFieldsSpecified => ExitCode::ConfigurationError,
             ErrorKind::HookPathError { .. } => ExitCode::ConfigurationError,
+            ErrorKind::HookProbeError { .. } => ExitCode::NetworkError,
+            ErrorKind::IncompatibleNodeEngine { .. } => ExitCode::ConfigurationError,
             ErrorKind::InstalledPackageNameError => ExitCode::UnknownError,
+            ErrorKind::InvalidFetchManifest { .. } => ExitCode::ConfigurationError,
             ErrorKind::InvalidHookCommand { .. } => ExitCode::ExecutableNotFound,
             ErrorKind::InvalidHookOutput { .. } => ExitCode::ExecutionFailure,
             ErrorKind::InvalidInvocation { .. } => ExitCode::InvalidArguments,
             ErrorKind::InvalidInvocationOfBareVersion { .. } => ExitCode::InvalidArguments,
+            ErrorKind::InvalidNodeArch { .. } => ExitCode::InvalidArguments,
+            ErrorKind::InvalidPlatformProfileName { .. } => ExitCode::InvalidArguments,
+            ErrorKind::InvalidPublishMode { .. } => ExitCode::ConfigurationError,
             ErrorKind::InvalidRegistryFormat { .. } => ExitCode::ConfigurationError,
+            ErrorKind::InvalidSettingValue { .. } => ExitCode::InvalidArguments,
             ErrorKind::InvalidToolName { .. } => ExitCode::InvalidArguments,
+            ErrorKind::InvalidVersionAlias { .. } => ExitCode::ConfigurationError,
+            ErrorKind::InvalidVersionPolicy { .. } => ExitCode::ConfigurationError,
+            ErrorKind::JsonOutputError => ExitCode::UnknownError,
+            ErrorKind::LocalPackageNotFound { .. } => ExitCode::InvalidArguments,
             ErrorKind::LockAcquireError => ExitCode::FileSystemError,
+            ErrorKind::LockfileNotInferred { .. } => ExitCode::ConfigurationError,
+            ErrorKind::ManifestNotStrictJson { .. } => ExitCode::ConfigurationError,
+            ErrorKind::MultipleLockfiles { .. } => ExitCode::ConfigurationError,
             ErrorKind::NoBundledNpm { .. } => ExitCode::ConfigurationError,
+            ErrorKind::NoBundledNpmNodeVersion => ExitCode::InvalidArguments,
             ErrorKind::NoCommandLinePnpm => ExitCode::ConfigurationError,
             ErrorKind::NoCommandLineYarn => ExitCode::ConfigurationError,
             ErrorKind::NoDefaultNodeVersion { .. } => ExitCode::ConfigurationError,
+            ErrorKind::NodeArchNotAvailable { .. } => ExitCode::NoVersionMatch,
+            ErrorKind::NodeChecksumNotFound { .. } => ExitCode::ConfigurationError,
+            ErrorKind::NodeInstallVersionMismatch { .. } => ExitCode::EnvironmentError,
+            ErrorKind::NodeRequiresNewerGlibc { .. } => ExitCode::ExecutionFailure,
+            ErrorKind::NodeScheduleFetchError { .. } => ExitCode::NetworkError,
+            ErrorKind::NodeShasumsFetchError { .. } => ExitCode::NetworkError,
+            ErrorKind::NodeUninstallVersionRequired { .. } => ExitCode::InvalidArguments,
             ErrorKind::NodeVersionNotFound { .. } => ExitCode::NoVersionMatch,
+            ErrorKind::NodeVersionTagNotSupported { .. } => ExitCode::InvalidArguments,
             ErrorKind::NoHomeEnvironmentVar => ExitCode::EnvironmentError,
             ErrorKind::NoInstallDir => ExitCode::EnvironmentError,
             ErrorKind::NoLocalDataDir => ExitCode::EnvironmentError,
+            ErrorKind::NoLockfile { .. } => ExitCode::ConfigurationError,
             ErrorKind::NoPinnedNodeVersion { .. } => ExitCode::ConfigurationError,
             ErrorKind::NoPlatform => ExitCode::ConfigurationError,
             ErrorKind::NoProjectNodeInManifest => ExitCode::ConfigurationError,
             ErrorKind::NoProjectPnpm => ExitCode::ConfigurationError,
             ErrorKind::NoProjectYarn => ExitCode::ConfigurationError,
             ErrorKind::NoShellProfile { .. } => ExitCode::EnvironmentError,
+            ErrorKind::NoToolsManifest => ExitCode::ConfigurationError,
             ErrorKind::NotInPackage => ExitCode::ConfigurationError,
             ErrorKind::NoDefaultPnpm => ExitCode::ConfigurationError,
             ErrorKind::NoDefaultYarn => ExitCode::ConfigurationError,
+            ErrorKind::NotionBackupError { .. } => ExitCode::FileSystemError,
+            ErrorKind::NotionMigrationNotConfirmed { .. } => ExitCode::InvalidArguments,
             ErrorKind::NpmLinkMissingPackage { .. } => ExitCode::ConfigurationError,
             ErrorKind::NpmLinkWrongManager { .. } => ExitCode::ConfigurationError,
             ErrorKind::NpmVersionNotFound { .. } => ExitCode::NoVersionMatch,
             ErrorKind::NpxNotAvailable { .. } => ExitCode::ExecutableNotFound,
+            ErrorKind::NvmDirNotFound { .. } => ExitCode::EnvironmentError,
+            ErrorKind::OfflineArchiveChecksumMismatch { .. } => ExitCode::FileSystemError,
+            ErrorKind::OfflineArchiveNotFound { .. } => ExitCode::FileSystemError,
+            ErrorKind::OfflineArchiveReadError { .. } => ExitCode::FileSystemError,
+            ErrorKind::OfflineInstallNeedsVersion { .. } => ExitCode::InvalidArguments,
+            ErrorKind::OfflineVersionNotAvailable { .. } => ExitCode::NoVersionMatch,
+            ErrorKind::OutdatedJsonVersionError { .. } => ExitCode::InvalidArguments,
             ErrorKind::PackageInstallFailed { .. } => ExitCode::UnknownError,
             ErrorKind::PackageManifestParseError { .. } => ExitCode::ConfigurationError,
             ErrorKind::PackageManifestReadError { .. } => ExitCode::FileSystemError,
@@ -1525,58 +3247,742 @@ impl ErrorKind {
             ErrorKind::PackageUnpackError => ExitCode::ConfigurationError,
             ErrorKind::PackageWriteError { .. } => ExitCode::FileSystemError,
             ErrorKind::ParseBinConfigError => ExitCode::UnknownError,
+            ErrorKind::ParseBinaryManifestError { .. } => ExitCode::ConfigurationError,
             ErrorKind::ParseHooksError { .. } => ExitCode::ConfigurationError,
+            ErrorKind::ParsePlatformProfileError { .. } => ExitCode::ConfigurationError,
+            ErrorKind::ParseSettingsError { .. } => ExitCode::ConfigurationError,
             ErrorKind::ParseToolSpecError { .. } => ExitCode::InvalidArguments,
+            ErrorKind::ParseListSnapshotError { .. } => ExitCode::InvalidArguments,
             ErrorKind::ParseNodeIndexCacheError => ExitCode::UnknownError,
             ErrorKind::ParseNodeIndexError { .. } => ExitCode::NetworkError,
             ErrorKind::ParseNodeIndexExpiryError => ExitCode::UnknownError,
+            ErrorKind::ParseNodeScheduleError { .. } => ExitCode::NetworkError,
             ErrorKind::ParseNpmManifestError => ExitCode::UnknownError,
             ErrorKind::ParsePackageConfigError => ExitCode::UnknownError,
+            ErrorKind::ParsePlatformLockError { .. } => ExitCode::ConfigurationError,
             ErrorKind::ParsePlatformError => ExitCode::ConfigurationError,
+            ErrorKind::ParseToolsManifestError { .. } => ExitCode::ConfigurationError,
+            ErrorKind::ParseYarnIndexCacheError => ExitCode::UnknownError,
+            ErrorKind::ParseYarnIndexExpiryError => ExitCode::UnknownError,
+            ErrorKind::PathTooLong { .. } => ExitCode::EnvironmentError,
             ErrorKind::PersistInventoryError { .. } => ExitCode::FileSystemError,
+            ErrorKind::PinInferredNotConfirmed { .. } => ExitCode::InvalidArguments,
+            ErrorKind::PinMembersNotConfirmed { .. } => ExitCode::InvalidArguments,
+            ErrorKind::PlatformCheckoutError { exit_code, .. } => *exit_code,
+            ErrorKind::PlatformLockOutdated { .. } => ExitCode::ConfigurationError,
+            ErrorKind::PlatformProfileNotFound { .. } => ExitCode::InvalidArguments,
+            ErrorKind::PlatformWriteConflict { .. } => ExitCode::FileSystemError,
             ErrorKind::PnpmVersionNotFound { .. } => ExitCode::NoVersionMatch,
+            ErrorKind::PolicyViolation { .. } => ExitCode::ConfigurationError,
             ErrorKind::ProjectLocalBinaryExecError { .. } => ExitCode::ExecutionFailure,
             ErrorKind::ProjectLocalBinaryNotFound { .. } => ExitCode::FileSystemError,
             ErrorKind::PublishHookBothUrlAndBin => ExitCode::ConfigurationError,
             ErrorKind::PublishHookNeitherUrlNorBin => ExitCode::ConfigurationError,
             ErrorKind::ReadBinConfigDirError { .. } => ExitCode::FileSystemError,
             ErrorKind::ReadBinConfigError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadBinaryManifestError { .. } => ExitCode::FileSystemError,
             ErrorKind::ReadDefaultNpmError { .. } => ExitCode::FileSystemError,
             ErrorKind::ReadDirError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadFetchManifestError { .. } => ExitCode::FileSystemError,
             ErrorKind::ReadHooksError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadListSnapshotError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadLockfileError { .. } => ExitCode::FileSystemError,
             ErrorKind::ReadNodeIndexCacheError { .. } => ExitCode::FileSystemError,
             ErrorKind::ReadNodeIndexExpiryError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadNodeInstallVersionError { .. } => ExitCode::EnvironmentError,
             ErrorKind::ReadNpmManifestError => ExitCode::UnknownError,
             ErrorKind::ReadPackageConfigError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadPlatformLockError { .. } => ExitCode::FileSystemError,
             ErrorKind::ReadPlatformError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadPlatformProfileError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadSettingsError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadToolsManifestError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadUsageStatsError { .. } => ExitCode::FileSystemError,
             #[cfg(windows)]
             ErrorKind::ReadUserPathError => ExitCode::EnvironmentError,
+            ErrorKind::ReadYarnIndexCacheError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadYarnIndexExpiryError { .. } => ExitCode::FileSystemError,
             ErrorKind::RegistryFetchError { .. } => ExitCode::NetworkError,
+            ErrorKind::RelinkPackagesError { .. } => ExitCode::FileSystemError,
             ErrorKind::RunShimDirectly => ExitCode::InvalidArguments,
+            ErrorKind::RunWhereUnsupported { .. } => ExitCode::InvalidArguments,
             ErrorKind::SetupToolImageError { .. } => ExitCode::FileSystemError,
+            ErrorKind::SelfUpdateChecksumNotFound { .. } => ExitCode::NetworkError,
+            ErrorKind::SelfUpdateFetchError { .. } => ExitCode::NetworkError,
+            ErrorKind::SelfUpdateInvalidRelease { .. } => ExitCode::NetworkError,
+            ErrorKind::SelfUpdateManagedInstall { .. } => ExitCode::EnvironmentError,
+            ErrorKind::SelfUpdateReplaceError { .. } => ExitCode::FileSystemError,
+            ErrorKind::SelfUpdateUnsupportedPlatform { .. } => ExitCode::NotYetImplemented,
             ErrorKind::SetToolExecutable { .. } => ExitCode::FileSystemError,
+            ErrorKind::ShimCaseCollision { .. } => ExitCode::ConfigurationError,
             ErrorKind::ShimCreateError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ShimNameReserved { .. } => ExitCode::ConfigurationError,
+            ErrorKind::ShimRegenerationError { .. } => ExitCode::FileSystemError,
             ErrorKind::ShimRemoveError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ShimShadowsVolta { .. } => ExitCode::ConfigurationError,
             ErrorKind::StringifyBinConfigError => ExitCode::UnknownError,
             ErrorKind::StringifyPackageConfigError => ExitCode::UnknownError,
             ErrorKind::StringifyPlatformError => ExitCode::UnknownError,
+            ErrorKind::StringifyYarnIndexCacheError => ExitCode::UnknownError,
+            ErrorKind::ToolsManifestInstallError { .. } => ExitCode::UnknownError,
+            ErrorKind::ToolsNotInstalled { .. } => ExitCode::ConfigurationError,
             ErrorKind::Unimplemented { .. } => ExitCode::UnknownError,
+            ErrorKind::UninstallDefaultNode { .. } => ExitCode::ConfigurationError,
+            ErrorKind::UninstallProjectNode { .. } => ExitCode::ConfigurationError,
+            ErrorKind::UnknownSetting { .. } => ExitCode::InvalidArguments,
+            ErrorKind::UnknownVersionAlias { .. } => ExitCode::InvalidArguments,
             ErrorKind::UnpackArchiveError { .. } => ExitCode::UnknownError,
+            ErrorKind::UnsupportedPlatformLockVersion { .. } => ExitCode::ConfigurationError,
+            ErrorKind::UnsupportedScriptInterpreter { .. } => ExitCode::ExecutionFailure,
             ErrorKind::UpgradePackageNotFound { .. } => ExitCode::ConfigurationError,
             ErrorKind::UpgradePackageWrongManager { .. } => ExitCode::ConfigurationError,
             ErrorKind::VersionParseError { .. } => ExitCode::NoVersionMatch,
             ErrorKind::WriteBinConfigError { .. } => ExitCode::FileSystemError,
             ErrorKind::WriteDefaultNpmError { .. } => ExitCode::FileSystemError,
             ErrorKind::WriteLauncherError { .. } => ExitCode::FileSystemError,
+            ErrorKind::WriteListSnapshotError { .. } => ExitCode::FileSystemError,
             ErrorKind::WriteNodeIndexCacheError { .. } => ExitCode::FileSystemError,
             ErrorKind::WriteNodeIndexExpiryError { .. } => ExitCode::FileSystemError,
+            ErrorKind::WriteNodeScheduleCacheError { .. } => ExitCode::FileSystemError,
             ErrorKind::WritePackageConfigError { .. } => ExitCode::FileSystemError,
+            ErrorKind::WritePlatformLockError { .. } => ExitCode::FileSystemError,
             ErrorKind::WritePlatformError { .. } => ExitCode::FileSystemError,
+            ErrorKind::WritePlatformProfileError { .. } => ExitCode::FileSystemError,
+            ErrorKind::WriteProfileError { .. } => ExitCode::FileSystemError,
+            ErrorKind::WriteSettingsError { .. } => ExitCode::FileSystemError,
             #[cfg(windows)]
             ErrorKind::WriteUserPathError => ExitCode::EnvironmentError,
+            ErrorKind::WriteYarnIndexCacheError { .. } => ExitCode::FileSystemError,
+            ErrorKind::WriteYarnIndexExpiryError { .. } => ExitCode::FileSystemError,
             ErrorKind::Yarn2NotSupported => ExitCode::NoVersionMatch,
             ErrorKind::YarnLatestFetchError { .. } => ExitCode::NetworkError,
             ErrorKind::YarnVersionNotFound { .. } => ExitCode::NoVersionMatch,
         }
     }
+
+    /// A short name for the error that stays the same across releases, used to link to its
+    /// documentation at `https://volta.sh/errors/<slug>`
+    ///
+    /// Slugs must never be reused or changed, since they are the anchors that the documentation
+    /// and any search for an error rely on.
+    pub fn slug(&self) -> &'static str {
+        match self {
+            ErrorKind::AliasesJsonVersionError { .. } => "aliases-json-version-error",
+            ErrorKind::AllLtsInvalidTool { .. } => "all-lts-invalid-tool",
+            ErrorKind::BinaryAlreadyInstalled { .. } => "binary-already-installed",
+            ErrorKind::BinaryExecError => "binary-exec-error",
+            ErrorKind::BinaryNotExecutable { .. } => "binary-not-executable",
+            ErrorKind::BinaryNotFound { .. } => "binary-not-found",
+            ErrorKind::BuildPathError => "build-path-error",
+            ErrorKind::BundledVersionNotSupported { .. } => "bundled-version-not-supported",
+            ErrorKind::BypassError { .. } => "bypass-error",
+            ErrorKind::CannotExecPackage { .. } => "cannot-exec-package",
+            ErrorKind::CannotFetchPackage { .. } => "cannot-fetch-package",
+            ErrorKind::CannotPinPackage { .. } => "cannot-pin-package",
+            ErrorKind::CannotRemoveNodePin => "cannot-remove-node-pin",
+            ErrorKind::ChecksumMismatch { .. } => "checksum-mismatch",
+            ErrorKind::CompletionsOutFileError { .. } => "completions-out-file-error",
+            ErrorKind::ContainingDirError { .. } => "containing-dir-error",
+            ErrorKind::CopyLocalPackageError { .. } => "copy-local-package-error",
+            ErrorKind::CopyNodeInstallError { .. } => "copy-node-install-error",
+            ErrorKind::CouldNotDetermineTool => "could-not-determine-tool",
+            ErrorKind::CouldNotStartMigration => "could-not-start-migration",
+            ErrorKind::CreateDirError { .. } => "create-dir-error",
+            ErrorKind::CreateLayoutFileError { .. } => "create-layout-file-error",
+            ErrorKind::CreateSharedLinkError { .. } => "create-shared-link-error",
+            ErrorKind::CreateTempDirError { .. } => "create-temp-dir-error",
+            ErrorKind::CreateTempFileError { .. } => "create-temp-file-error",
+            ErrorKind::CurrentDirError => "current-dir-error",
+            ErrorKind::DeleteDirectoryError { .. } => "delete-directory-error",
+            ErrorKind::DeleteFileError { .. } => "delete-file-error",
+            ErrorKind::DeprecatedCommandError { .. } => "deprecated-command-error",
+            ErrorKind::DiffJsonVersionError { .. } => "diff-json-version-error",
+            ErrorKind::DynamicCompletionsUnsupported { .. } => "dynamic-completions-unsupported",
+            ErrorKind::DownloadToolNetworkError { .. } => "download-tool-network-error",
+            ErrorKind::ExecuteHookError { .. } => "execute-hook-error",
+            ErrorKind::ExtensionCycleError { .. } => "extension-cycle-error",
+            ErrorKind::ExtensionPathError { .. } => "extension-path-error",
+            ErrorKind::FetchManifestError { .. } => "fetch-manifest-error",
+            ErrorKind::HashBinaryError { .. } => "hash-binary-error",
+            ErrorKind::HookCommandFailed { .. } => "hook-command-failed",
+            ErrorKind::HookMultipleFieldsSpecified => "hook-multiple-fields-specified",
+            ErrorKind::HookNoFieldsSpecified => "hook-no-fields-specified",
+            ErrorKind::HookPathError { .. } => "hook-path-error",
+            ErrorKind::HookProbeError { .. } => "hook-probe-error",
+            ErrorKind::IncompatibleNodeEngine { .. } => "incompatible-node-engine",
+            ErrorKind::InstalledPackageNameError => "installed-package-name-error",
+            ErrorKind::InvalidFetchManifest { .. } => "invalid-fetch-manifest",
+            ErrorKind::InvalidHookCommand { .. } => "invalid-hook-command",
+            ErrorKind::InvalidHookOutput { .. } => "invalid-hook-output",
+            ErrorKind::InvalidInvocation { .. } => "invalid-invocation",
+            ErrorKind::InvalidInvocationOfBareVersion { .. } => {
+                "invalid-invocation-of-bare-version"
+            }
+            ErrorKind::InvalidNodeArch { .. } => "invalid-node-arch",
+            ErrorKind::InvalidPlatformProfileName { .. } => "invalid-platform-profile-name",
+            ErrorKind::InvalidPublishMode { .. } => "invalid-publish-mode",
+            ErrorKind::InvalidRegistryFormat { .. } => "invalid-registry-format",
+            ErrorKind::InvalidSettingValue { .. } => "invalid-setting-value",
+            ErrorKind::InvalidToolName { .. } => "invalid-tool-name",
+            ErrorKind::InvalidVersionAlias { .. } => "invalid-version-alias",
+            ErrorKind::InvalidVersionPolicy { .. } => "invalid-version-policy",
+            ErrorKind::JsonOutputError => "json-output-error",
+            ErrorKind::LocalPackageNotFound { .. } => "local-package-not-found",
+            ErrorKind::LockAcquireError => "lock-acquire-error",
+            ErrorKind::LockfileNotInferred { .. } => "lockfile-not-inferred",
+            ErrorKind::ManifestNotStrictJson { .. } => "manifest-not-strict-json",
+            ErrorKind::MultipleLockfiles { .. } => "multiple-lockfiles",
+            ErrorKind::NoBundledNpm { .. } => "no-bundled-npm",
+            ErrorKind::NoBundledNpmNodeVersion => "no-bundled-npm-node-version",
+            ErrorKind::NoCommandLinePnpm => "no-command-line-pnpm",
+            ErrorKind::NoCommandLineYarn => "no-command-line-yarn",
+            ErrorKind::NoDefaultNodeVersion { .. } => "no-default-node-version",
+            ErrorKind::NodeArchNotAvailable { .. } => "node-arch-not-available",
+            ErrorKind::NodeChecksumNotFound { .. } => "node-checksum-not-found",
+            ErrorKind::NodeInstallVersionMismatch { .. } => "node-install-version-mismatch",
+            ErrorKind::NodeRequiresNewerGlibc { .. } => "node-requires-newer-glibc",
+            ErrorKind::NodeScheduleFetchError { .. } => "node-schedule-fetch-error",
+            ErrorKind::NodeShasumsFetchError { .. } => "node-shasums-fetch-error",
+            ErrorKind::NodeUninstallVersionRequired { .. } => "node-uninstall-version-required",
+            ErrorKind::NodeVersionNotFound { .. } => "node-version-not-found",
+            ErrorKind::NodeVersionTagNotSupported { .. } => "node-version-tag-not-supported",
+            ErrorKind::NoHomeEnvironmentVar => "no-home-environment-var",
+            ErrorKind::NoInstallDir => "no-install-dir",
+            ErrorKind::NoLocalDataDir => "no-local-data-dir",
+            ErrorKind::NoLockfile { .. } => "no-lockfile",
+            ErrorKind::NoPinnedNodeVersion { .. } => "no-pinned-node-version",
+            ErrorKind::NoPlatform => "no-platform",
+            ErrorKind::NoProjectNodeInManifest => "no-project-node-in-manifest",
+            ErrorKind::NoProjectPnpm => "no-project-pnpm",
+            ErrorKind::NoProjectYarn => "no-project-yarn",
+            ErrorKind::NoShellProfile { .. } => "no-shell-profile",
+            ErrorKind::NoToolsManifest => "no-tools-manifest",
+            ErrorKind::NotInPackage => "not-in-package",
+            ErrorKind::NoDefaultPnpm => "no-default-pnpm",
+            ErrorKind::NoDefaultYarn => "no-default-yarn",
+            ErrorKind::NotionBackupError { .. } => "notion-backup-error",
+            ErrorKind::NotionMigrationNotConfirmed { .. } => "notion-migration-not-confirmed",
+            ErrorKind::NpmLinkMissingPackage { .. } => "npm-link-missing-package",
+            ErrorKind::NpmLinkWrongManager { .. } => "npm-link-wrong-manager",
+            ErrorKind::NpmVersionNotFound { .. } => "npm-version-not-found",
+            ErrorKind::NpxNotAvailable { .. } => "npx-not-available",
+            ErrorKind::NvmDirNotFound { .. } => "nvm-dir-not-found",
+            ErrorKind::OfflineArchiveChecksumMismatch { .. } => "offline-archive-checksum-mismatch",
+            ErrorKind::OfflineArchiveNotFound { .. } => "offline-archive-not-found",
+            ErrorKind::OfflineArchiveReadError { .. } => "offline-archive-read-error",
+            ErrorKind::OfflineInstallNeedsVersion { .. } => "offline-install-needs-version",
+            ErrorKind::OfflineVersionNotAvailable { .. } => "offline-version-not-available",
+            ErrorKind::OutdatedJsonVersionError { .. } => "outdated-json-version-error",
+            ErrorKind::PackageInstallFailed { .. } => "package-install-failed",
+            ErrorKind::PackageManifestParseError { .. } => "package-manifest-parse-error",
+            ErrorKind::PackageManifestReadError { .. } => "package-manifest-read-error",
+            ErrorKind::PackageNotFound { .. } => "package-not-found",
+            ErrorKind::PackageParseError { .. } => "package-parse-error",
+            ErrorKind::PackageReadError { .. } => "package-read-error",
+            ErrorKind::PackageUnpackError => "package-unpack-error",
+            ErrorKind::PackageWriteError { .. } => "package-write-error",
+            ErrorKind::ParseBinConfigError => "parse-bin-config-error",
+            ErrorKind::ParseBinaryManifestError { .. } => "parse-binary-manifest-error",
+            ErrorKind::ParseHooksError { .. } => "parse-hooks-error",
+            ErrorKind::ParsePlatformProfileError { .. } => "parse-platform-profile-error",
+            ErrorKind::ParseSettingsError { .. } => "parse-settings-error",
+            ErrorKind::ParseToolSpecError { .. } => "parse-tool-spec-error",
+            ErrorKind::ParseListSnapshotError { .. } => "parse-list-snapshot-error",
+            ErrorKind::ParseNodeIndexCacheError => "parse-node-index-cache-error",
+            ErrorKind::ParseNodeIndexError { .. } => "parse-node-index-error",
+            ErrorKind::ParseNodeIndexExpiryError => "parse-node-index-expiry-error",
+            ErrorKind::ParseNodeScheduleError { .. } => "parse-node-schedule-error",
+            ErrorKind::ParseNpmManifestError => "parse-npm-manifest-error",
+            ErrorKind::ParsePackageConfigError => "parse-package-config-error",
+            ErrorKind::ParsePlatformLockError { .. } => "parse-platform-lock-error",
+            ErrorKind::ParsePlatformError => "parse-platform-error",
+            ErrorKind::ParseToolsManifestError { .. } => "parse-tools-manifest-error",
+            ErrorKind::ParseYarnIndexCacheError => "parse-yarn-index-cache-error",
+            ErrorKind::ParseYarnIndexExpiryError => "parse-yarn-index-expiry-error",
+            ErrorKind::PathTooLong { .. } => "path-too-long",
+            ErrorKind::PersistInventoryError { .. } => "persist-inventory-error",
+            ErrorKind::PinInferredNotConfirmed { .. } => "pin-inferred-not-confirmed",
+            ErrorKind::PinMembersNotConfirmed { .. } => "pin-members-not-confirmed",
+            ErrorKind::PlatformCheckoutError { .. } => "platform-checkout-error",
+            ErrorKind::PlatformLockOutdated { .. } => "platform-lock-outdated",
+            ErrorKind::PlatformProfileNotFound { .. } => "platform-profile-not-found",
+            ErrorKind::PlatformWriteConflict { .. } => "platform-write-conflict",
+            ErrorKind::PnpmVersionNotFound { .. } => "pnpm-version-not-found",
+            ErrorKind::PolicyViolation { .. } => "policy-violation",
+            ErrorKind::ProjectLocalBinaryExecError { .. } => "project-local-binary-exec-error",
+            ErrorKind::ProjectLocalBinaryNotFound { .. } => "project-local-binary-not-found",
+            ErrorKind::PublishHookBothUrlAndBin => "publish-hook-both-url-and-bin",
+            ErrorKind::PublishHookNeitherUrlNorBin => "publish-hook-neither-url-nor-bin",
+            ErrorKind::ReadBinConfigDirError { .. } => "read-bin-config-dir-error",
+            ErrorKind::ReadBinConfigError { .. } => "read-bin-config-error",
+            ErrorKind::ReadBinaryManifestError { .. } => "read-binary-manifest-error",
+            ErrorKind::ReadDefaultNpmError { .. } => "read-default-npm-error",
+            ErrorKind::ReadDirError { .. } => "read-dir-error",
+            ErrorKind::ReadFetchManifestError { .. } => "read-fetch-manifest-error",
+            ErrorKind::ReadHooksError { .. } => "read-hooks-error",
+            ErrorKind::ReadListSnapshotError { .. } => "read-list-snapshot-error",
+            ErrorKind::ReadLockfileError { .. } => "read-lockfile-error",
+            ErrorKind::ReadNodeIndexCacheError { .. } => "read-node-index-cache-error",
+            ErrorKind::ReadNodeIndexExpiryError { .. } => "read-node-index-expiry-error",
+            ErrorKind::ReadNodeInstallVersionError { .. } => "read-node-install-version-error",
+            ErrorKind::ReadNpmManifestError => "read-npm-manifest-error",
+            ErrorKind::ReadPackageConfigError { .. } => "read-package-config-error",
+            ErrorKind::ReadPlatformLockError { .. } => "read-platform-lock-error",
+            ErrorKind::ReadPlatformError { .. } => "read-platform-error",
+            ErrorKind::ReadPlatformProfileError { .. } => "read-platform-profile-error",
+            ErrorKind::ReadSettingsError { .. } => "read-settings-error",
+            ErrorKind::ReadToolsManifestError { .. } => "read-tools-manifest-error",
+            ErrorKind::ReadUsageStatsError { .. } => "read-usage-stats-error",
+            #[cfg(windows)]
+            ErrorKind::ReadUserPathError => "read-user-path-error",
+            ErrorKind::ReadYarnIndexCacheError { .. } => "read-yarn-index-cache-error",
+            ErrorKind::ReadYarnIndexExpiryError { .. } => "read-yarn-index-expiry-error",
+            ErrorKind::RegistryFetchError { .. } => "registry-fetch-error",
+            ErrorKind::RelinkPackagesError { .. } => "relink-packages-error",
+            ErrorKind::RunShimDirectly => "run-shim-directly",
+            ErrorKind::RunWhereUnsupported { .. } => "run-where-unsupported",
+            ErrorKind::SetupToolImageError { .. } => "setup-tool-image-error",
+            ErrorKind::SelfUpdateChecksumNotFound { .. } => "self-update-checksum-not-found",
+            ErrorKind::SelfUpdateFetchError { .. } => "self-update-fetch-error",
+            ErrorKind::SelfUpdateInvalidRelease { .. } => "self-update-invalid-release",
+            ErrorKind::SelfUpdateManagedInstall { .. } => "self-update-managed-install",
+            ErrorKind::SelfUpdateReplaceError { .. } => "self-update-replace-error",
+            ErrorKind::SelfUpdateUnsupportedPlatform { .. } => "self-update-unsupported-platform",
+            ErrorKind::SetToolExecutable { .. } => "set-tool-executable",
+            ErrorKind::ShimCaseCollision { .. } => "shim-case-collision",
+            ErrorKind::ShimCreateError { .. } => "shim-create-error",
+            ErrorKind::ShimNameReserved { .. } => "shim-name-reserved",
+            ErrorKind::ShimRegenerationError { .. } => "shim-regeneration-error",
+            ErrorKind::ShimRemoveError { .. } => "shim-remove-error",
+            ErrorKind::ShimShadowsVolta { .. } => "shim-shadows-volta",
+            ErrorKind::StringifyBinConfigError => "stringify-bin-config-error",
+            ErrorKind::StringifyPackageConfigError => "stringify-package-config-error",
+            ErrorKind::StringifyPlatformError => "stringify-platform-error",
+            ErrorKind::StringifyYarnIndexCacheError => "stringify-yarn-index-cache-error",
+            ErrorKind::ToolsManifestInstallError { .. } => "tools-manifest-install-error",
+            ErrorKind::ToolsNotInstalled { .. } => "tools-not-installed",
+            ErrorKind::Unimplemented { .. } => "unimplemented",
+            ErrorKind::UninstallDefaultNode { .. } => "uninstall-default-node",
+            ErrorKind::UninstallProjectNode { .. } => "uninstall-project-node",
+            ErrorKind::UnknownSetting { .. } => "unknown-setting",
+            ErrorKind::UnknownVersionAlias { .. } => "unknown-version-alias",
+            ErrorKind::UnpackArchiveError { .. } => "unpack-archive-error",
+            ErrorKind::UnsupportedPlatformLockVersion { .. } => "unsupported-platform-lock-version",
+            ErrorKind::UnsupportedScriptInterpreter { .. } => "unsupported-script-interpreter",
+            ErrorKind::UpgradePackageNotFound { .. } => "upgrade-package-not-found",
+            ErrorKind::UpgradePackageWrongManager { .. } => "upgrade-package-wrong-manager",
+            ErrorKind::VersionParseError { .. } => "version-parse-error",
+            ErrorKind::WriteBinConfigError { .. } => "write-bin-config-error",
+            ErrorKind::WriteDefaultNpmError { .. } => "write-default-npm-error",
+            ErrorKind::WriteLauncherError { .. } => "write-launcher-error",
+            ErrorKind::WriteListSnapshotError { .. } => "write-list-snapshot-error",
+            ErrorKind::WriteNodeIndexCacheError { .. } => "write-node-index-cache-error",
+            ErrorKind::WriteNodeIndexExpiryError { .. } => "write-node-index-expiry-error",
+            ErrorKind::WriteNodeScheduleCacheError { .. } => "write-node-schedule-cache-error",
+            ErrorKind::WritePackageConfigError { .. } => "write-package-config-error",
+            ErrorKind::WritePlatformLockError { .. } => "write-platform-lock-error",
+            ErrorKind::WritePlatformError { .. } => "write-platform-error",
+            ErrorKind::WritePlatformProfileError { .. } => "write-platform-profile-error",
+            ErrorKind::WriteProfileError { .. } => "write-profile-error",
+            ErrorKind::WriteSettingsError { .. } => "write-settings-error",
+            #[cfg(windows)]
+            ErrorKind::WriteUserPathError => "write-user-path-error",
+            ErrorKind::WriteYarnIndexCacheError { .. } => "write-yarn-index-cache-error",
+            ErrorKind::WriteYarnIndexExpiryError { .. } => "write-yarn-index-expiry-error",
+            ErrorKind::Yarn2NotSupported => "yarn2-not-supported",
+            ErrorKind::YarnLatestFetchError { .. } => "yarn-latest-fetch-error",
+            ErrorKind::YarnVersionNotFound { .. } => "yarn-version-not-found",
+        }
+    }
+
+    /// A code for the error that stays the same across releases, such as
+    /// `VOLTA_NO_PINNED_NODE_VERSION`, for tools that need to tell errors apart
+    ///
+    /// It is shown with the error when `VOLTA_ERROR_CODES` is set or with `--verbose`, and is
+    /// included in the error events sent to the event hooks. Like slugs, codes must never be
+    /// reused or changed.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::AliasesJsonVersionError { .. } => "VOLTA_ALIASES_JSON_VERSION_ERROR",
+            ErrorKind::AllLtsInvalidTool { .. } => "VOLTA_ALL_LTS_INVALID_TOOL",
+            ErrorKind::BinaryAlreadyInstalled { .. } => "VOLTA_BINARY_ALREADY_INSTALLED",
+            ErrorKind::BinaryExecError => "VOLTA_BINARY_EXEC_ERROR",
+            ErrorKind::BinaryNotExecutable { .. } => "VOLTA_BINARY_NOT_EXECUTABLE",
+            ErrorKind::BinaryNotFound { .. } => "VOLTA_BINARY_NOT_FOUND",
+            ErrorKind::BuildPathError => "VOLTA_BUILD_PATH_ERROR",
+            ErrorKind::BundledVersionNotSupported { .. } => "VOLTA_BUNDLED_VERSION_NOT_SUPPORTED",
+            ErrorKind::BypassError { .. } => "VOLTA_BYPASS_ERROR",
+            ErrorKind::CannotExecPackage { .. } => "VOLTA_CANNOT_EXEC_PACKAGE",
+            ErrorKind::CannotFetchPackage { .. } => "VOLTA_CANNOT_FETCH_PACKAGE",
+            ErrorKind::CannotPinPackage { .. } => "VOLTA_CANNOT_PIN_PACKAGE",
+            ErrorKind::CannotRemoveNodePin => "VOLTA_CANNOT_REMOVE_NODE_PIN",
+            ErrorKind::ChecksumMismatch { .. } => "VOLTA_CHECKSUM_MISMATCH",
+            ErrorKind::CompletionsOutFileError { .. } => "VOLTA_COMPLETIONS_OUT_FILE_ERROR",
+            ErrorKind::ContainingDirError { .. } => "VOLTA_CONTAINING_DIR_ERROR",
+            ErrorKind::CopyLocalPackageError { .. } => "VOLTA_COPY_LOCAL_PACKAGE_ERROR",
+            ErrorKind::CopyNodeInstallError { .. } => "VOLTA_COPY_NODE_INSTALL_ERROR",
+            ErrorKind::CouldNotDetermineTool => "VOLTA_COULD_NOT_DETERMINE_TOOL",
+            ErrorKind::CouldNotStartMigration => "VOLTA_COULD_NOT_START_MIGRATION",
+            ErrorKind::CreateDirError { .. } => "VOLTA_CREATE_DIR_ERROR",
+            ErrorKind::CreateLayoutFileError { .. } => "VOLTA_CREATE_LAYOUT_FILE_ERROR",
+            ErrorKind::CreateSharedLinkError { .. } => "VOLTA_CREATE_SHARED_LINK_ERROR",
+            ErrorKind::CreateTempDirError { .. } => "VOLTA_CREATE_TEMP_DIR_ERROR",
+            ErrorKind::CreateTempFileError { .. } => "VOLTA_CREATE_TEMP_FILE_ERROR",
+            ErrorKind::CurrentDirError => "VOLTA_CURRENT_DIR_ERROR",
+            ErrorKind::DeleteDirectoryError { .. } => "VOLTA_DELETE_DIRECTORY_ERROR",
+            ErrorKind::DeleteFileError { .. } => "VOLTA_DELETE_FILE_ERROR",
+            ErrorKind::DeprecatedCommandError { .. } => "VOLTA_DEPRECATED_COMMAND_ERROR",
+            ErrorKind::DiffJsonVersionError { .. } => "VOLTA_DIFF_JSON_VERSION_ERROR",
+            ErrorKind::DynamicCompletionsUnsupported { .. } => {
+                "VOLTA_DYNAMIC_COMPLETIONS_UNSUPPORTED"
+            }
+            ErrorKind::DownloadToolNetworkError { .. } => "VOLTA_DOWNLOAD_TOOL_NETWORK_ERROR",
+            ErrorKind::ExecuteHookError { .. } => "VOLTA_EXECUTE_HOOK_ERROR",
+            ErrorKind::ExtensionCycleError { .. } => "VOLTA_EXTENSION_CYCLE_ERROR",
+            ErrorKind::ExtensionPathError { .. } => "VOLTA_EXTENSION_PATH_ERROR",
+            ErrorKind::FetchManifestError { .. } => "VOLTA_FETCH_MANIFEST_ERROR",
+            ErrorKind::HashBinaryError { .. } => "VOLTA_HASH_BINARY_ERROR",
+            ErrorKind::HookCommandFailed { .. } => "VOLTA_HOOK_COMMAND_FAILED",
+            ErrorKind::HookMultipleFieldsSpecified => "VOLTA_HOOK_MULTIPLE_FIELDS_SPECIFIED",
+            ErrorKind::HookNoFieldsSpecified => "VOLTA_HOOK_NO_FIELDS_SPECIFIED",
+            ErrorKind::HookPathError { .. } => "VOLTA_HOOK_PATH_ERROR",
+            ErrorKind::HookProbeError { .. } => "VOLTA_HOOK_PROBE_ERROR",
+            ErrorKind::IncompatibleNodeEngine { .. } => "VOLTA_INCOMPATIBLE_NODE_ENGINE",
+            ErrorKind::InstalledPackageNameError => "VOLTA_INSTALLED_PACKAGE_NAME_ERROR",
+            ErrorKind::InvalidFetchManifest { .. } => "VOLTA_INVALID_FETCH_MANIFEST",
+            ErrorKind::InvalidHookCommand { .. } => "VOLTA_INVALID_HOOK_COMMAND",
+            ErrorKind::InvalidHookOutput { .. } => "VOLTA_INVALID_HOOK_OUTPUT",
+            ErrorKind::InvalidInvocation { .. } => "VOLTA_INVALID_INVOCATION",
+            ErrorKind::InvalidInvocationOfBareVersion { .. } => {
+                "VOLTA_INVALID_INVOCATION_OF_BARE_VERSION"
+            }
+            ErrorKind::InvalidNodeArch { .. } => "VOLTA_INVALID_NODE_ARCH",
+            ErrorKind::InvalidPlatformProfileName { .. } => "VOLTA_INVALID_PLATFORM_PROFILE_NAME",
+            ErrorKind::InvalidPublishMode { .. } => "VOLTA_INVALID_PUBLISH_MODE",
+            ErrorKind::InvalidRegistryFormat { .. } => "VOLTA_INVALID_REGISTRY_FORMAT",
+            ErrorKind::InvalidSettingValue { .. } => "VOLTA_INVALID_SETTING_VALUE",
+            ErrorKind::InvalidToolName { .. } => "VOLTA_INVALID_TOOL_NAME",
+            ErrorKind::InvalidVersionAlias { .. } => "VOLTA_INVALID_VERSION_ALIAS",
+            ErrorKind::InvalidVersionPolicy { .. } => "VOLTA_INVALID_VERSION_POLICY",
+            ErrorKind::JsonOutputError => "VOLTA_JSON_OUTPUT_ERROR",
+            ErrorKind::LocalPackageNotFound { .. } => "VOLTA_LOCAL_PACKAGE_NOT_FOUND",
+            ErrorKind::LockAcquireError => "VOLTA_LOCK_ACQUIRE_ERROR",
+            ErrorKind::LockfileNotInferred { .. } => "VOLTA_LOCKFILE_NOT_INFERRED",
+            ErrorKind::ManifestNotStrictJson { .. } => "VOLTA_MANIFEST_NOT_STRICT_JSON",
+            ErrorKind::MultipleLockfiles { .. } => "VOLTA_MULTIPLE_LOCKFILES",
+            ErrorKind::NoBundledNpm { .. } => "VOLTA_NO_BUNDLED_NPM",
+            ErrorKind::NoBundledNpmNodeVersion => "VOLTA_NO_BUNDLED_NPM_NODE_VERSION",
+            ErrorKind::NoCommandLinePnpm => "VOLTA_NO_COMMAND_LINE_PNPM",
+            ErrorKind::NoCommandLineYarn => "VOLTA_NO_COMMAND_LINE_YARN",
+            ErrorKind::NoDefaultNodeVersion { .. } => "VOLTA_NO_DEFAULT_NODE_VERSION",
+            ErrorKind::NodeArchNotAvailable { .. } => "VOLTA_NODE_ARCH_NOT_AVAILABLE",
+            ErrorKind::NodeChecksumNotFound { .. } => "VOLTA_NODE_CHECKSUM_NOT_FOUND",
+            ErrorKind::NodeInstallVersionMismatch { .. } => "VOLTA_NODE_INSTALL_VERSION_MISMATCH",
+            ErrorKind::NodeRequiresNewerGlibc { .. } => "VOLTA_NODE_REQUIRES_NEWER_GLIBC",
+            ErrorKind::NodeScheduleFetchError { .. } => "VOLTA_NODE_SCHEDULE_FETCH_ERROR",
+            ErrorKind::NodeShasumsFetchError { .. } => "VOLTA_NODE_SHASUMS_FETCH_ERROR",
+            ErrorKind::NodeUninstallVersionRequired { .. } => {
+                "VOLTA_NODE_UNINSTALL_VERSION_REQUIRED"
+            }
+            ErrorKind::NodeVersionNotFound { .. } => "VOLTA_NODE_VERSION_NOT_FOUND",
+            ErrorKind::NodeVersionTagNotSupported { .. } => "VOLTA_NODE_VERSION_TAG_NOT_SUPPORTED",
+            ErrorKind::NoHomeEnvironmentVar => "VOLTA_NO_HOME_ENVIRONMENT_VAR",
+            ErrorKind::NoInstallDir => "VOLTA_NO_INSTALL_DIR",
+            ErrorKind::NoLocalDataDir => "VOLTA_NO_LOCAL_DATA_DIR",
+            ErrorKind::NoLockfile { .. } => "VOLTA_NO_LOCKFILE",
+            ErrorKind::NoPinnedNodeVersion { .. } => "VOLTA_NO_PINNED_NODE_VERSION",
+            ErrorKind::NoPlatform => "VOLTA_NO_PLATFORM",
+            ErrorKind::NoProjectNodeInManifest => "VOLTA_NO_PROJECT_NODE_IN_MANIFEST",
+            ErrorKind::NoProjectPnpm => "VOLTA_NO_PROJECT_PNPM",
+            ErrorKind::NoProjectYarn => "VOLTA_NO_PROJECT_YARN",
+            ErrorKind::NoShellProfile { .. } => "VOLTA_NO_SHELL_PROFILE",
+            ErrorKind::NoToolsManifest => "VOLTA_NO_TOOLS_MANIFEST",
+            ErrorKind::NotInPackage => "VOLTA_NOT_IN_PACKAGE",
+            ErrorKind::NoDefaultPnpm => "VOLTA_NO_DEFAULT_PNPM",
+            ErrorKind::NoDefaultYarn => "VOLTA_NO_DEFAULT_YARN",
+            ErrorKind::NotionBackupError { .. } => "VOLTA_NOTION_BACKUP_ERROR",
+            ErrorKind::NotionMigrationNotConfirmed { .. } => "VOLTA_NOTION_MIGRATION_NOT_CONFIRMED",
+            ErrorKind::NpmLinkMissingPackage { .. } => "VOLTA_NPM_LINK_MISSING_PACKAGE",
+            ErrorKind::NpmLinkWrongManager { .. } => "VOLTA_NPM_LINK_WRONG_MANAGER",
+            ErrorKind::NpmVersionNotFound { .. } => "VOLTA_NPM_VERSION_NOT_FOUND",
+            ErrorKind::NpxNotAvailable { .. } => "VOLTA_NPX_NOT_AVAILABLE",
+            ErrorKind::NvmDirNotFound { .. } => "VOLTA_NVM_DIR_NOT_FOUND",
+            ErrorKind::OfflineArchiveChecksumMismatch { .. } => {
+                "VOLTA_OFFLINE_ARCHIVE_CHECKSUM_MISMATCH"
+            }
+            ErrorKind::OfflineArchiveNotFound { .. } => "VOLTA_OFFLINE_ARCHIVE_NOT_FOUND",
+            ErrorKind::OfflineArchiveReadError { .. } => "VOLTA_OFFLINE_ARCHIVE_READ_ERROR",
+            ErrorKind::OfflineInstallNeedsVersion { .. } => "VOLTA_OFFLINE_INSTALL_NEEDS_VERSION",
+            ErrorKind::OfflineVersionNotAvailable { .. } => "VOLTA_OFFLINE_VERSION_NOT_AVAILABLE",
+            ErrorKind::OutdatedJsonVersionError { .. } => "VOLTA_OUTDATED_JSON_VERSION_ERROR",
+            ErrorKind::PackageInstallFailed { .. } => "VOLTA_PACKAGE_INSTALL_FAILED",
+            ErrorKind::PackageManifestParseError { .. } => "VOLTA_PACKAGE_MANIFEST_PARSE_ERROR",
+            ErrorKind::PackageManifestReadError { .. } => "VOLTA_PACKAGE_MANIFEST_READ_ERROR",
+            ErrorKind::PackageNotFound { .. } => "VOLTA_PACKAGE_NOT_FOUND",
+            ErrorKind::PackageParseError { .. } => "VOLTA_PACKAGE_PARSE_ERROR",
+            ErrorKind::PackageReadError { .. } => "VOLTA_PACKAGE_READ_ERROR",
+            ErrorKind::PackageUnpackError => "VOLTA_PACKAGE_UNPACK_ERROR",
+            ErrorKind::PackageWriteError { .. } => "VOLTA_PACKAGE_WRITE_ERROR",
+            ErrorKind::ParseBinConfigError => "VOLTA_PARSE_BIN_CONFIG_ERROR",
+            ErrorKind::ParseBinaryManifestError { .. } => "VOLTA_PARSE_BINARY_MANIFEST_ERROR",
+            ErrorKind::ParseHooksError { .. } => "VOLTA_PARSE_HOOKS_ERROR",
+            ErrorKind::ParsePlatformProfileError { .. } => "VOLTA_PARSE_PLATFORM_PROFILE_ERROR",
+            ErrorKind::ParseSettingsError { .. } => "VOLTA_PARSE_SETTINGS_ERROR",
+            ErrorKind::ParseToolSpecError { .. } => "VOLTA_PARSE_TOOL_SPEC_ERROR",
+            ErrorKind::ParseListSnapshotError { .. } => "VOLTA_PARSE_LIST_SNAPSHOT_ERROR",
+            ErrorKind::ParseNodeIndexCacheError => "VOLTA_PARSE_NODE_INDEX_CACHE_ERROR",
+            ErrorKind::ParseNodeIndexError { .. } => "VOLTA_PARSE_NODE_INDEX_ERROR",
+            ErrorKind::ParseNodeIndexExpiryError => "VOLTA_PARSE_NODE_INDEX_EXPIRY_ERROR",
+            ErrorKind::ParseNodeScheduleError { .. } => "VOLTA_PARSE_NODE_SCHEDULE_ERROR",
+            ErrorKind::ParseNpmManifestError => "VOLTA_PARSE_NPM_MANIFEST_ERROR",
+            ErrorKind::ParsePackageConfigError => "VOLTA_PARSE_PACKAGE_CONFIG_ERROR",
+            ErrorKind::ParsePlatformLockError { .. } => "VOLTA_PARSE_PLATFORM_LOCK_ERROR",
+            ErrorKind::ParsePlatformError => "VOLTA_PARSE_PLATFORM_ERROR",
+            ErrorKind::ParseToolsManifestError { .. } => "VOLTA_PARSE_TOOLS_MANIFEST_ERROR",
+            ErrorKind::ParseYarnIndexCacheError => "VOLTA_PARSE_YARN_INDEX_CACHE_ERROR",
+            ErrorKind::ParseYarnIndexExpiryError => "VOLTA_PARSE_YARN_INDEX_EXPIRY_ERROR",
+            ErrorKind::PathTooLong { .. } => "VOLTA_PATH_TOO_LONG",
+            ErrorKind::PersistInventoryError { .. } => "VOLTA_PERSIST_INVENTORY_ERROR",
+            ErrorKind::PinInferredNotConfirmed { .. } => "VOLTA_PIN_INFERRED_NOT_CONFIRMED",
+            ErrorKind::PinMembersNotConfirmed { .. } => "VOLTA_PIN_MEMBERS_NOT_CONFIRMED",
+            ErrorKind::PlatformCheckoutError { .. } => "VOLTA_PLATFORM_CHECKOUT_ERROR",
+            ErrorKind::PlatformLockOutdated { .. } => "VOLTA_PLATFORM_LOCK_OUTDATED",
+            ErrorKind::PlatformProfileNotFound { .. } => "VOLTA_PLATFORM_PROFILE_NOT_FOUND",
+            ErrorKind::PlatformWriteConflict { .. } => "VOLTA_PLATFORM_WRITE_CONFLICT",
+            ErrorKind::PnpmVersionNotFound { .. } => "VOLTA_PNPM_VERSION_NOT_FOUND",
+            ErrorKind::PolicyViolation { .. } => "VOLTA_POLICY_VIOLATION",
+            ErrorKind::ProjectLocalBinaryExecError { .. } => {
+                "VOLTA_PROJECT_LOCAL_BINARY_EXEC_ERROR"
+            }
+            ErrorKind::ProjectLocalBinaryNotFound { .. } => "VOLTA_PROJECT_LOCAL_BINARY_NOT_FOUND",
+            ErrorKind::PublishHookBothUrlAndBin => "VOLTA_PUBLISH_HOOK_BOTH_URL_AND_BIN",
+            ErrorKind::PublishHookNeitherUrlNorBin => "VOLTA_PUBLISH_HOOK_NEITHER_URL_NOR_BIN",
+            ErrorKind::ReadBinConfigDirError { .. } => "VOLTA_READ_BIN_CONFIG_DIR_ERROR",
+            ErrorKind::ReadBinConfigError { .. } => "VOLTA_READ_BIN_CONFIG_ERROR",
+            ErrorKind::ReadBinaryManifestError { .. } => "VOLTA_READ_BINARY_MANIFEST_ERROR",
+            ErrorKind::ReadDefaultNpmError { .. } => "VOLTA_READ_DEFAULT_NPM_ERROR",
+            ErrorKind::ReadDirError { .. } => "VOLTA_READ_DIR_ERROR",
+            ErrorKind::ReadFetchManifestError { .. } => "VOLTA_READ_FETCH_MANIFEST_ERROR",
+            ErrorKind::ReadHooksError { .. } => "VOLTA_READ_HOOKS_ERROR",
+            ErrorKind::ReadListSnapshotError { .. } => "VOLTA_READ_LIST_SNAPSHOT_ERROR",
+            ErrorKind::ReadLockfileError { .. } => "VOLTA_READ_LOCKFILE_ERROR",
+            ErrorKind::ReadNodeIndexCacheError { .. } => "VOLTA_READ_NODE_INDEX_CACHE_ERROR",
+            ErrorKind::ReadNodeIndexExpiryError { .. } => "VOLTA_READ_NODE_INDEX_EXPIRY_ERROR",
+            ErrorKind::ReadNodeInstallVersionError { .. } => {
+                "VOLTA_READ_NODE_INSTALL_VERSION_ERROR"
+            }
+            ErrorKind::ReadNpmManifestError => "VOLTA_READ_NPM_MANIFEST_ERROR",
+            ErrorKind::ReadPackageConfigError { .. } => "VOLTA_READ_PACKAGE_CONFIG_ERROR",
+            ErrorKind::ReadPlatformLockError { .. } => "VOLTA_READ_PLATFORM_LOCK_ERROR",
+            ErrorKind::ReadPlatformError { .. } => "VOLTA_READ_PLATFORM_ERROR",
+            ErrorKind::ReadPlatformProfileError { .. } => "VOLTA_READ_PLATFORM_PROFILE_ERROR",
+            ErrorKind::ReadSettingsError { .. } => "VOLTA_READ_SETTINGS_ERROR",
+            ErrorKind::ReadToolsManifestError { .. } => "VOLTA_READ_TOOLS_MANIFEST_ERROR",
+            ErrorKind::ReadUsageStatsError { .. } => "VOLTA_READ_USAGE_STATS_ERROR",
+            #[cfg(windows)]
+            ErrorKind::ReadUserPathError => "VOLTA_READ_USER_PATH_ERROR",
+            ErrorKind::ReadYarnIndexCacheError { .. } => "VOLTA_READ_YARN_INDEX_CACHE_ERROR",
+            ErrorKind::ReadYarnIndexExpiryError { .. } => "VOLTA_READ_YARN_INDEX_EXPIRY_ERROR",
+            ErrorKind::RegistryFetchError { .. } => "VOLTA_REGISTRY_FETCH_ERROR",
+            ErrorKind::RelinkPackagesError { .. } => "VOLTA_RELINK_PACKAGES_ERROR",
+            ErrorKind::RunShimDirectly => "VOLTA_RUN_SHIM_DIRECTLY",
+            ErrorKind::RunWhereUnsupported { .. } => "VOLTA_RUN_WHERE_UNSUPPORTED",
+            ErrorKind::SetupToolImageError { .. } => "VOLTA_SETUP_TOOL_IMAGE_ERROR",
+            ErrorKind::SelfUpdateChecksumNotFound { .. } => "VOLTA_SELF_UPDATE_CHECKSUM_NOT_FOUND",
+            ErrorKind::SelfUpdateFetchError { .. } => "VOLTA_SELF_UPDATE_FETCH_ERROR",
+            ErrorKind::SelfUpdateInvalidRelease { .. } => "VOLTA_SELF_UPDATE_INVALID_RELEASE",
+            ErrorKind::SelfUpdateManagedInstall { .. } => "VOLTA_SELF_UPDATE_MANAGED_INSTALL",
+            ErrorKind::SelfUpdateReplaceError { .. } => "VOLTA_SELF_UPDATE_REPLACE_ERROR",
+            ErrorKind::SelfUpdateUnsupportedPlatform { .. } => {
+                "VOLTA_SELF_UPDATE_UNSUPPORTED_PLATFORM"
+            }
+            ErrorKind::SetToolExecutable { .. } => "VOLTA_SET_TOOL_EXECUTABLE",
+            ErrorKind::ShimCaseCollision { .. } => "VOLTA_SHIM_CASE_COLLISION",
+            ErrorKind::ShimCreateError { .. } => "VOLTA_SHIM_CREATE_ERROR",
+            ErrorKind::ShimNameReserved { .. } => "VOLTA_SHIM_NAME_RESERVED",
+            ErrorKind::ShimRegenerationError { .. } => "VOLTA_SHIM_REGENERATION_ERROR",
+            ErrorKind::ShimRemoveError { .. } => "VOLTA_SHIM_REMOVE_ERROR",
+            ErrorKind::ShimShadowsVolta { .. } => "VOLTA_SHIM_SHADOWS_VOLTA",
+            ErrorKind::StringifyBinConfigError => "VOLTA_STRINGIFY_BIN_CONFIG_ERROR",
+            ErrorKind::StringifyPackageConfigError => "VOLTA_STRINGIFY_PACKAGE_CONFIG_ERROR",
+            ErrorKind::StringifyPlatformError => "VOLTA_STRINGIFY_PLATFORM_ERROR",
+            ErrorKind::StringifyYarnIndexCacheError => "VOLTA_STRINGIFY_YARN_INDEX_CACHE_ERROR",
+            ErrorKind::ToolsManifestInstallError { .. } => "VOLTA_TOOLS_MANIFEST_INSTALL_ERROR",
+            ErrorKind::ToolsNotInstalled { .. } => "VOLTA_TOOLS_NOT_INSTALLED",
+            ErrorKind::Unimplemented { .. } => "VOLTA_UNIMPLEMENTED",
+            ErrorKind::UninstallDefaultNode { .. } => "VOLTA_UNINSTALL_DEFAULT_NODE",
+            ErrorKind::UninstallProjectNode { .. } => "VOLTA_UNINSTALL_PROJECT_NODE",
+            ErrorKind::UnknownSetting { .. } => "VOLTA_UNKNOWN_SETTING",
+            ErrorKind::UnknownVersionAlias { .. } => "VOLTA_UNKNOWN_VERSION_ALIAS",
+            ErrorKind::UnpackArchiveError { .. } => "VOLTA_UNPACK_ARCHIVE_ERROR",
+            ErrorKind::UnsupportedPlatformLockVersion { .. } => {
+                "VOLTA_UNSUPPORTED_PLATFORM_LOCK_VERSION"
+            }
+            ErrorKind::UnsupportedScriptInterpreter { .. } => {
+                "VOLTA_UNSUPPORTED_SCRIPT_INTERPRETER"
+            }
+            ErrorKind::UpgradePackageNotFound { .. } => "VOLTA_UPGRADE_PACKAGE_NOT_FOUND",
+            ErrorKind::UpgradePackageWrongManager { .. } => "VOLTA_UPGRADE_PACKAGE_WRONG_MANAGER",
+            ErrorKind::VersionParseError { .. } => "VOLTA_VERSION_PARSE_ERROR",
+            ErrorKind::WriteBinConfigError { .. } => "VOLTA_WRITE_BIN_CONFIG_ERROR",
+            ErrorKind::WriteDefaultNpmError { .. } => "VOLTA_WRITE_DEFAULT_NPM_ERROR",
+            ErrorKind::WriteLauncherError { .. } => "VOLTA_WRITE_LAUNCHER_ERROR",
+            ErrorKind::WriteListSnapshotError { .. } => "VOLTA_WRITE_LIST_SNAPSHOT_ERROR",
+            ErrorKind::WriteNodeIndexCacheError { .. } => "VOLTA_WRITE_NODE_INDEX_CACHE_ERROR",
+            ErrorKind::WriteNodeIndexExpiryError { .. } => "VOLTA_WRITE_NODE_INDEX_EXPIRY_ERROR",
+            ErrorKind::WriteNodeScheduleCacheError { .. } => {
+                "VOLTA_WRITE_NODE_SCHEDULE_CACHE_ERROR"
+            }
+            ErrorKind::WritePackageConfigError { .. } => "VOLTA_WRITE_PACKAGE_CONFIG_ERROR",
+            ErrorKind::WritePlatformLockError { .. } => "VOLTA_WRITE_PLATFORM_LOCK_ERROR",
+            ErrorKind::WritePlatformError { .. } => "VOLTA_WRITE_PLATFORM_ERROR",
+            ErrorKind::WritePlatformProfileError { .. } => "VOLTA_WRITE_PLATFORM_PROFILE_ERROR",
+            ErrorKind::WriteProfileError { .. } => "VOLTA_WRITE_PROFILE_ERROR",
+            ErrorKind::WriteSettingsError { .. } => "VOLTA_WRITE_SETTINGS_ERROR",
+            #[cfg(windows)]
+            ErrorKind::WriteUserPathError => "VOLTA_WRITE_USER_PATH_ERROR",
+            ErrorKind::WriteYarnIndexCacheError { .. } => "VOLTA_WRITE_YARN_INDEX_CACHE_ERROR",
+            ErrorKind::WriteYarnIndexExpiryError { .. } => "VOLTA_WRITE_YARN_INDEX_EXPIRY_ERROR",
+            ErrorKind::Yarn2NotSupported => "VOLTA_YARN2_NOT_SUPPORTED",
+            ErrorKind::YarnLatestFetchError { .. } => "VOLTA_YARN_LATEST_FETCH_ERROR",
+            ErrorKind::YarnVersionNotFound { .. } => "VOLTA_YARN_VERSION_NOT_FOUND",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    /// The source of this file, so that the slug table can be checked against the declaration of
+    /// every kind, including those that are only compiled on other platforms
+    const SOURCE: &str = include_str!("kind.rs");
+
+    fn section<'a>(start: &str, end: &str) -> &'a str {
+        let from = SOURCE.find(start).unwrap() + start.len();
+        let to = from + SOURCE[from..].find(end).unwrap();
+        &SOURCE[from..to]
+    }
+
+    #[test]
+    fn every_kind_has_a_unique_slug() {
+        let declared: Vec<&str> = section("pub enum ErrorKind {\n", "\n}\n")
+            .lines()
+            .filter_map(|line| line.strip_prefix("    "))
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_uppercase()))
+            .map(|line| line.trim_end_matches([' ', '{', ',']))
+            .collect();
+
+        let mut slugged = BTreeSet::new();
+        let mut slugs = BTreeSet::new();
+        // Long arms are wrapped onto several lines, so each arm runs up to the next `ErrorKind::`
+        for arm in section("pub fn slug(&self) -> &'static str {\n", "\n    }\n")
+            .split("ErrorKind::")
+            .skip(1)
+        {
+            let kind = arm.split(' ').next().unwrap();
+            let slug = arm.split('"').nth(1).unwrap();
+
+            assert!(slugged.insert(kind), "{} has more than one slug", kind);
+            assert!(slugs.insert(slug), "the slug '{}' is used twice", slug);
+            assert!(
+                slug.split('-').all(|word| !word.is_empty()
+                    && word
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())),
+                "the slug '{}' of {} is not lowercase words separated by dashes",
+                slug,
+                kind
+            );
+        }
+
+        let missing: Vec<&&str> = declared
+            .iter()
+            .filter(|kind| !slugged.contains(*kind))
+            .collect();
+        assert!(missing.is_empty(), "no slug for {:?}", missing);
+        assert_eq!(slugged.len(), declared.len());
+    }
+
+    #[test]
+    fn every_code_is_unique_and_matches_the_slug() {
+        let codes: Vec<(&str, &str)> =
+            section("pub fn code(&self) -> &'static str {\n", "\n    }\n")
+                .split("ErrorKind::")
+                .skip(1)
+                .map(|arm| {
+                    (
+                        arm.split(' ').next().unwrap(),
+                        arm.split('"').nth(1).unwrap(),
+                    )
+                })
+                .collect();
+        let slugs: Vec<(&str, &str)> =
+            section("pub fn slug(&self) -> &'static str {\n", "\n    }\n")
+                .split("ErrorKind::")
+                .skip(1)
+                .map(|arm| {
+                    (
+                        arm.split(' ').next().unwrap(),
+                        arm.split('"').nth(1).unwrap(),
+                    )
+                })
+                .collect();
+
+        assert_eq!(codes.len(), slugs.len());
+        let mut unique = BTreeSet::new();
+        for ((kind, code), (slugged, slug)) in codes.iter().zip(&slugs) {
+            assert_eq!(
+                kind, slugged,
+                "codes and slugs are listed in the same order"
+            );
+            assert!(unique.insert(code), "the code '{}' is used twice", code);
+            assert_eq!(
+                *code,
+                format!("VOLTA_{}", slug.to_ascii_uppercase().replace('-', "_")),
+                "the code of {} doesn't match its slug",
+                kind
+            );
+        }
+    }
+
+    #[test]
+    fn codes_of_common_errors() {
+        use super::ErrorKind;
+        use std::path::PathBuf;
+
+        let kinds = [
+            (
+                ErrorKind::NoPinnedNodeVersion {
+                    tool: "Yarn".into(),
+                },
+                "VOLTA_NO_PINNED_NODE_VERSION",
+            ),
+            (
+                ErrorKind::NodeVersionNotFound {
+                    matching: "^99".into(),
+                },
+                "VOLTA_NODE_VERSION_NOT_FOUND",
+            ),
+            (ErrorKind::NotInPackage, "VOLTA_NOT_IN_PACKAGE"),
+            (ErrorKind::NoPlatform, "VOLTA_NO_PLATFORM"),
+            (
+                ErrorKind::PackageParseError {
+                    file: PathBuf::from("package.json"),
+                },
+                "VOLTA_PACKAGE_PARSE_ERROR",
+            ),
+            (
+                ErrorKind::ShimCreateError {
+                    name: "node".into(),
+                },
+                "VOLTA_SHIM_CREATE_ERROR",
+            ),
+            (ErrorKind::Yarn2NotSupported, "VOLTA_YARN2_NOT_SUPPORTED"),
+        ];
+
+        for (kind, code) in kinds {
+            assert_eq!(kind.code(), code);
+        }
+    }
 }
@@ -10,6 +10,9 @@ pub use reporter::report_error;
 
 pub type Fallible<T> = Result<T, VoltaError>;
 
+/// The page that documents each error, by its slug
+const ERROR_DOCS_URL: &str = "https://volta.sh/errors/";
+
 /// Error type for Volta
 #[derive(Debug)]
 pub struct VoltaError {
@@ -41,6 +44,11 @@ impl VoltaError {
         }
     }
 
+    /// The URL of the documentation for this kind of error
+    pub fn docs_url(&self) -> String {
+        format!("{}{}", ERROR_DOCS_URL, self.inner.kind.slug())
+    }
+
     /// Get a reference to the ErrorKind for this error
     pub fn kind(&self) -> &ErrorKind {
         &self.inner.kind
@@ -88,6 +96,7 @@ where
 
 /// Exit codes supported by Volta Errors
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
 pub enum ExitCode {
     /// No error occurred.
     Success = 0,
@@ -116,6 +125,9 @@ pub enum ExitCode {
     /// The command or feature is not yet implemented.
     NotYetImplemented = 9,
 
+    /// The toolchain differs from the snapshot it was compared with by `volta list --diff`.
+    DifferencesFound = 10,
+
     /// The requested executable could not be run.
     ExecutionFailure = 126,
 
@@ -1,4 +1,4 @@
-use std::env::args_os;
+use std::env::{self, args_os};
 use std::error::Error;
 use std::fs::File;
 use std::io::Write;
@@ -6,17 +6,31 @@ use std::path::PathBuf;
 
 use super::VoltaError;
 use crate::layout::volta_home;
+use crate::log::is_quiet;
 use crate::style::format_error_cause;
 use chrono::Local;
 use ci_info::is_ci;
 use console::strip_ansi_codes;
 use fs_utils::ensure_containing_dir_exists;
-use log::{debug, error};
+use log::{debug, error, LevelFilter};
+
+const VOLTA_ERROR_CODES: &str = "VOLTA_ERROR_CODES";
 
 /// Report an error, both to the console and to error logs
+///
+/// The error's code is shown in brackets before the message with `--verbose` or when
+/// `VOLTA_ERROR_CODES` is set (to anything other than `0`), for tools that wrap Volta.
 pub fn report_error(volta_version: &str, err: &VoltaError) {
-    let message = err.to_string();
-    error!("{}", message);
+    let message = if show_error_codes() {
+        format!("[{}] {}", err.kind().code(), err)
+    } else {
+        err.to_string()
+    };
+    if is_quiet() {
+        error!("{}", message);
+    } else {
+        error!("{}\n\nLearn more: {}", message.trim_end(), err.docs_url());
+    }
 
     if let Some(details) = compose_error_details(err) {
         if is_ci() {
@@ -43,6 +57,18 @@ pub fn report_error(volta_version: &str, err: &VoltaError) {
     }
 }
 
+/// Determines whether error codes are shown, which they are with `--verbose` or `VOLTA_ERROR_CODES`
+fn show_error_codes() -> bool {
+    if log::max_level() >= LevelFilter::Debug {
+        return true;
+    }
+
+    match env::var_os(VOLTA_ERROR_CODES) {
+        Some(value) => !value.is_empty() && value != "0",
+        None => false,
+    }
+}
+
 /// Write an error log with all details about the error
 fn write_error_log(
     volta_version: &str,
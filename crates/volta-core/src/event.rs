@@ -1,10 +1,11 @@
 //! Events for the sessions in executables and shims and everything
 
 use std::env;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
 
+use crate::download::network_used;
 use crate::error::{ExitCode, VoltaError};
 use crate::hook::Publish;
 use crate::monitor::send_events;
@@ -33,17 +34,39 @@ pub enum EventKind {
     Start,
     End {
         exit_code: i32,
+        /// The milliseconds since the activity started
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        duration_ms: Option<u64>,
     },
     Error {
         exit_code: i32,
         error: String,
+        /// The stable code of the error, such as `VOLTA_NO_PINNED_NODE_VERSION`
+        #[serde(default)]
+        code: String,
+        /// The stable slug of the error, such as `no-pinned-node-version`, which its
+        /// documentation at `https://volta.sh/errors/<slug>` is found by
+        #[serde(default)]
+        slug: String,
         env: ErrorEnv,
     },
     ToolEnd {
         exit_code: i32,
+        /// The milliseconds since the activity started
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        duration_ms: Option<u64>,
     },
     Args {
         argv: String,
+        /// The activity started within the top-level one, such as `install` for `volta install`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        subcommand: Option<String>,
+        /// The number of arguments, not counting the executable
+        #[serde(default)]
+        arg_count: usize,
+        /// Whether any network request was made during the session
+        #[serde(default)]
+        fetched: bool,
     },
 }
 
@@ -93,33 +116,59 @@ fn get_error_env() -> ErrorEnv {
 
 pub struct EventLog {
     events: Vec<Event>,
+    /// When each activity started, in order, for measuring how long it took
+    starts: Vec<(ActivityKind, Instant)>,
+    /// The subcommand that the session runs, included with the arguments
+    subcommand: Option<ActivityKind>,
 }
 
 impl EventLog {
     /// Constructs a new 'EventLog'
     pub fn init() -> Self {
-        EventLog { events: Vec::new() }
+        EventLog {
+            events: Vec::new(),
+            starts: Vec::new(),
+            subcommand: None,
+        }
     }
 
     pub fn add_event_start(&mut self, activity_kind: ActivityKind) {
+        self.starts.push((activity_kind, Instant::now()));
         self.add_event(EventKind::Start, activity_kind)
     }
+    /// Records the start of the subcommand that the session runs, such as `install` for `volta
+    /// install` or `npm` for the npm shim
+    ///
+    /// Only the first one is kept, so a command that runs another is still reported as itself.
+    pub fn add_event_subcommand_start(&mut self, activity_kind: ActivityKind) {
+        self.subcommand.get_or_insert(activity_kind);
+        self.add_event_start(activity_kind)
+    }
     pub fn add_event_end(&mut self, activity_kind: ActivityKind, exit_code: ExitCode) {
         self.add_event(
             EventKind::End {
                 exit_code: exit_code as i32,
+                duration_ms: self.duration_ms(activity_kind),
             },
             activity_kind,
         )
     }
     pub fn add_event_tool_end(&mut self, activity_kind: ActivityKind, exit_code: i32) {
-        self.add_event(EventKind::ToolEnd { exit_code }, activity_kind)
+        self.add_event(
+            EventKind::ToolEnd {
+                exit_code,
+                duration_ms: self.duration_ms(activity_kind),
+            },
+            activity_kind,
+        )
     }
     pub fn add_event_error(&mut self, activity_kind: ActivityKind, error: &VoltaError) {
         self.add_event(
             EventKind::Error {
                 exit_code: error.exit_code() as i32,
                 error: error.to_string(),
+                code: error.kind().code().to_owned(),
+                slug: error.kind().slug().to_owned(),
                 env: get_error_env(),
             },
             activity_kind,
@@ -135,7 +184,28 @@ impl EventLog {
                 result.push_str(&arg.to_string_lossy());
                 result
             });
-        self.add_event(EventKind::Args { argv }, ActivityKind::Args)
+        // Only the number of the other arguments is included, since they may contain paths
+        let arg_count = env::args_os().count().saturating_sub(1);
+        let subcommand = self.subcommand.map(|kind| kind.to_string());
+
+        self.add_event(
+            EventKind::Args {
+                argv,
+                subcommand,
+                arg_count,
+                fetched: network_used(),
+            },
+            ActivityKind::Args,
+        )
+    }
+
+    /// The milliseconds since the most recent start of the activity, if it was started
+    fn duration_ms(&self, activity_kind: ActivityKind) -> Option<u64> {
+        self.starts
+            .iter()
+            .rev()
+            .find(|(kind, _)| *kind == activity_kind)
+            .map(|(_, start)| start.elapsed().as_millis() as u64)
     }
 
     fn add_event(&mut self, event_kind: EventKind, activity_kind: ActivityKind) {
@@ -147,8 +217,8 @@ impl EventLog {
         match plugin {
             // Note: This call to unimplemented is left in, as it's not a Fallible operation that can use ErrorKind::Unimplemented
             Some(Publish::Url(_)) => unimplemented!(),
-            Some(Publish::Bin(command)) => {
-                send_events(command, &self.events);
+            Some(Publish::Bin { command, mode }) => {
+                send_events(command, *mode, &self.events);
             }
             None => {}
         }
@@ -158,7 +228,7 @@ impl EventLog {
 #[cfg(test)]
 pub mod tests {
 
-    use super::{EventKind, EventLog};
+    use super::{Event, EventKind, EventLog};
     use crate::error::{ErrorKind, ExitCode};
     use crate::session::ActivityKind;
     use regex::Regex;
@@ -176,14 +246,23 @@ pub mod tests {
         event_log.add_event_end(ActivityKind::Pin, ExitCode::NetworkError);
         assert_eq!(event_log.events.len(), 2);
         assert_eq!(event_log.events[1].name, "pin");
-        assert_eq!(event_log.events[1].event, EventKind::End { exit_code: 5 });
+        assert_eq!(
+            event_log.events[1].event,
+            EventKind::End {
+                exit_code: 5,
+                duration_ms: None
+            }
+        );
 
         event_log.add_event_tool_end(ActivityKind::Version, 12);
         assert_eq!(event_log.events.len(), 3);
         assert_eq!(event_log.events[2].name, "version");
         assert_eq!(
             event_log.events[2].event,
-            EventKind::ToolEnd { exit_code: 12 }
+            EventKind::ToolEnd {
+                exit_code: 12,
+                duration_ms: None
+            }
         );
 
         let error = ErrorKind::BinaryExecError.into();
@@ -191,14 +270,26 @@ pub mod tests {
         assert_eq!(event_log.events.len(), 4);
         assert_eq!(event_log.events[3].name, "install");
         // not checking the error because it has too much machine-specific info
+        match &event_log.events[3].event {
+            EventKind::Error { code, slug, .. } => {
+                assert_eq!(code, "VOLTA_BINARY_EXEC_ERROR");
+                assert_eq!(slug, "binary-exec-error");
+            }
+            event => panic!("Expected EventKind::Error, Got: {:?}", event),
+        }
 
         event_log.add_event_args();
         assert_eq!(event_log.events.len(), 5);
         assert_eq!(event_log.events[4].name, "args");
         match event_log.events[4].event {
-            EventKind::Args { ref argv } => {
+            EventKind::Args {
+                ref argv,
+                ref subcommand,
+                ..
+            } => {
                 let re = Regex::new("volta_core").unwrap();
                 assert!(re.is_match(argv));
+                assert_eq!(subcommand.as_deref(), None);
             }
             _ => {
                 panic!(
@@ -208,4 +299,110 @@ pub mod tests {
             }
         }
     }
+
+    #[test]
+    fn records_durations_and_subcommand() {
+        let mut event_log = EventLog::init();
+
+        event_log.add_event_start(ActivityKind::Volta);
+        event_log.add_event_subcommand_start(ActivityKind::Install);
+        // A command run by another one isn't the subcommand
+        event_log.add_event_subcommand_start(ActivityKind::Fetch);
+
+        event_log.add_event_end(ActivityKind::Install, ExitCode::Success);
+        assert!(matches!(
+            event_log.events[3].event,
+            EventKind::End {
+                exit_code: 0,
+                duration_ms: Some(_)
+            }
+        ));
+
+        // An activity that never started has no duration
+        event_log.add_event_tool_end(ActivityKind::Pin, 1);
+        assert_eq!(
+            event_log.events[4].event,
+            EventKind::ToolEnd {
+                exit_code: 1,
+                duration_ms: None
+            }
+        );
+
+        event_log.add_event_args();
+        match &event_log.events[5].event {
+            EventKind::Args { subcommand, .. } => {
+                assert_eq!(subcommand.as_deref(), Some("install"))
+            }
+            event => panic!("Expected EventKind::Args, Got: {:?}", event),
+        }
+    }
+
+    #[test]
+    fn reads_enriched_payload() {
+        let payload = r#"[
+  { "timestamp": 1700000000000, "name": "volta", "event": "start" },
+  { "timestamp": 1700000000003, "name": "install", "event": "start" },
+  {
+    "timestamp": 1700000001250,
+    "name": "volta",
+    "event": { "end": { "exit_code": 0, "duration_ms": 1250 } }
+  },
+  {
+    "timestamp": 1700000001251,
+    "name": "args",
+    "event": {
+      "args": {
+        "argv": "volta install node@20",
+        "subcommand": "install",
+        "arg_count": 2,
+        "fetched": true
+      }
+    }
+  }
+]"#;
+        let events: Vec<Event> = serde_json::from_str(payload).unwrap();
+
+        assert_eq!(
+            events[2].event,
+            EventKind::End {
+                exit_code: 0,
+                duration_ms: Some(1250)
+            }
+        );
+        assert_eq!(
+            events[3].event,
+            EventKind::Args {
+                argv: "volta install node@20".into(),
+                subcommand: Some("install".into()),
+                arg_count: 2,
+                fetched: true
+            }
+        );
+    }
+
+    #[test]
+    fn reads_payload_without_new_fields() {
+        let payload = r#"[
+  { "timestamp": 1700000001250, "name": "volta", "event": { "end": { "exit_code": 0 } } },
+  { "timestamp": 1700000001251, "name": "args", "event": { "args": { "argv": "volta list" } } }
+]"#;
+        let events: Vec<Event> = serde_json::from_str(payload).unwrap();
+
+        assert_eq!(
+            events[0].event,
+            EventKind::End {
+                exit_code: 0,
+                duration_ms: None
+            }
+        );
+        assert_eq!(
+            events[1].event,
+            EventKind::Args {
+                argv: "volta list".into(),
+                subcommand: None,
+                arg_count: 0,
+                fetched: false
+            }
+        );
+    }
 }
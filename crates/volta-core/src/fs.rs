@@ -4,13 +4,15 @@ use std::fs::{self, create_dir_all, read_dir, DirEntry, File, Metadata};
 use std::io;
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::error::{Context, ErrorKind, Fallible};
 use crate::layout::volta_home;
+use log::debug;
 use retry::delay::Fibonacci;
 use retry::{retry, OperationResult};
 use tempfile::{tempdir_in, NamedTempFile, TempDir};
+use walkdir::WalkDir;
 
 /// Opens a file, creating it if it doesn't exist
 pub fn touch(path: &Path) -> io::Result<File> {
@@ -23,6 +25,28 @@ pub fn touch(path: &Path) -> io::Result<File> {
     File::open(path)
 }
 
+/// Notes that a configuration file is being read, so that tests can check how often that happens
+///
+/// Each read is logged at debug level, which the acceptance tests count. Unit tests count the
+/// reads directly, separately for each test thread.
+pub(crate) fn record_open(description: &str, path: &Path) {
+    debug!("Reading {} '{}'", description, path.display());
+    #[cfg(test)]
+    OPENS.with(|opens| *opens.borrow_mut().entry(path.to_owned()).or_default() += 1);
+}
+
+#[cfg(test)]
+thread_local! {
+    static OPENS: std::cell::RefCell<std::collections::HashMap<PathBuf, usize>> =
+        Default::default();
+}
+
+/// Returns how many times the current test thread has opened the file, see [`record_open`]
+#[cfg(test)]
+pub(crate) fn open_count(path: &Path) -> usize {
+    OPENS.with(|opens| opens.borrow().get(path).copied().unwrap_or(0))
+}
+
 /// Removes the target directory, if it exists. If the directory doesn't exist, that is treated as
 /// success.
 pub fn remove_dir_if_exists<P: AsRef<Path>>(path: P) -> Fallible<()> {
@@ -144,6 +168,57 @@ where
     return std::os::unix::fs::symlink(src, dest);
 }
 
+/// Builds the relative path from the `from_dir` directory to `to`, if they share a root
+#[cfg_attr(windows, allow(dead_code))]
+pub(crate) fn relative_path(from_dir: &Path, to: &Path) -> Option<PathBuf> {
+    let from: Vec<_> = from_dir.components().collect();
+    let to: Vec<_> = to.components().collect();
+
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return None;
+    }
+
+    let mut path = PathBuf::new();
+    for _ in common..from.len() {
+        path.push("..");
+    }
+    path.extend(&to[common..]);
+
+    Some(path)
+}
+
+/// Recursively copies the contents of the `src` directory into `dest`, recreating any symlinks
+/// rather than following them. The `dest` directory is created if it doesn't exist.
+pub fn copy_dir_all(src: &Path, dest: &Path) -> io::Result<()> {
+    create_dir_all(dest)?;
+
+    for entry in WalkDir::new(src).min_depth(1) {
+        let entry = entry?;
+        let relative = entry
+            .path()
+            .strip_prefix(src)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        let target = dest.join(relative);
+        let file_type = entry.file_type();
+
+        if file_type.is_symlink() {
+            let link = fs::read_link(entry.path())?;
+            if entry.path().is_dir() {
+                symlink_dir(link, target)?;
+            } else {
+                symlink_file(link, target)?;
+            }
+        } else if file_type.is_dir() {
+            create_dir_all(target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Ensure that a given file has 'executable' permissions, otherwise we won't be able to call it
 #[cfg(unix)]
 pub fn set_executable(bin: &Path) -> io::Result<()> {
@@ -1,13 +1,13 @@
 //! Provides types for working with Volta hooks.
 
-use std::borrow::Cow;
 use std::fs::File;
-use std::iter::once;
 use std::marker::PhantomData;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use crate::alias::Alias;
 use crate::error::{Context, ErrorKind, Fallible};
 use crate::layout::volta_home;
+use crate::policy::Policy;
 use crate::project::Project;
 use crate::tool::{Node, Npm, Pnpm, Tool};
 use log::debug;
@@ -23,7 +23,30 @@ pub enum Publish {
     Url(String),
 
     /// Reports an event by forking a process and sending the event by IPC.
-    Bin(String),
+    Bin { command: String, mode: PublishMode },
+}
+
+/// Whether Volta waits for the process started by a `Publish::Bin` hook
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum PublishMode {
+    /// Sends the events and lets the process finish on its own (the default)
+    #[default]
+    Detach,
+    /// Sends the events and waits a bounded amount of time for the process to exit
+    Wait,
+}
+
+impl PublishMode {
+    pub(crate) fn from_str(raw_mode: &str) -> Fallible<PublishMode> {
+        match raw_mode {
+            "detach" => Ok(PublishMode::Detach),
+            "wait" => Ok(PublishMode::Wait),
+            other => Err(ErrorKind::InvalidPublishMode {
+                mode: String::from(other),
+            }
+            .into()),
+        }
+    }
 }
 
 /// Lazily loaded Volta hook configuration
@@ -52,7 +75,10 @@ pub struct HookConfig {
     npm: Option<ToolHooks<Npm>>,
     pnpm: Option<ToolHooks<Pnpm>>,
     yarn: Option<YarnHooks>,
+    package: Option<PackageHooks>,
     events: Option<EventHooks>,
+    aliases: Option<AliasHooks>,
+    policies: Option<PolicyHooks>,
 }
 
 /// Volta hooks for an individual tool
@@ -63,6 +89,10 @@ pub struct ToolHooks<T: Tool> {
     pub latest: Option<tool::MetadataHook>,
     /// The hook for resolving the Tool Index URL
     pub index: Option<tool::MetadataHook>,
+    /// The hook for resolving the checksum file for a distro (only used for Node)
+    pub shasums: Option<tool::ShasumsHook>,
+    /// The hook for resolving the release schedule URL (only used for Node)
+    pub schedule: Option<tool::MetadataHook>,
 
     phantom: PhantomData<T>,
 }
@@ -77,6 +107,24 @@ pub struct YarnHooks {
     pub index: Option<tool::YarnIndexHook>,
 }
 
+/// Volta hooks for installing global packages
+pub struct PackageHooks {
+    /// The registry that packages are installed from, instead of the one configured for npm
+    pub registry: Option<String>,
+}
+
+/// Version aliases defined in hooks files, which override the ones in the settings file
+pub struct AliasHooks {
+    /// The aliases, in order of descending precedence
+    pub aliases: Vec<Alias>,
+}
+
+/// Version policies defined in hooks files, which apply along with the ones in the settings file
+pub struct PolicyHooks {
+    /// The policies, in order of descending precedence
+    pub policies: Vec<Policy>,
+}
+
 impl<T: Tool> ToolHooks<T> {
     /// Extends this ToolHooks with another, giving precendence to the current instance
     fn merge(self, other: Self) -> Self {
@@ -84,6 +132,8 @@ impl<T: Tool> ToolHooks<T> {
             distro: self.distro.or(other.distro),
             latest: self.latest.or(other.latest),
             index: self.index.or(other.index),
+            shasums: self.shasums.or(other.shasums),
+            schedule: self.schedule.or(other.schedule),
             phantom: PhantomData,
         }
     }
@@ -100,6 +150,31 @@ impl YarnHooks {
     }
 }
 
+impl PackageHooks {
+    /// Extends this PackageHooks with another, giving precedence to the current instance
+    fn merge(self, other: Self) -> Self {
+        Self {
+            registry: self.registry.or(other.registry),
+        }
+    }
+}
+
+impl AliasHooks {
+    /// Extends this AliasHooks with another, giving precedence to the current instance
+    fn merge(mut self, other: Self) -> Self {
+        self.aliases.extend(other.aliases);
+        self
+    }
+}
+
+impl PolicyHooks {
+    /// Extends this PolicyHooks with another, keeping the policies of both
+    fn merge(mut self, other: Self) -> Self {
+        self.policies.extend(other.policies);
+        self
+    }
+}
+
 macro_rules! merge_hooks {
     ($self:ident, $other:ident, $field:ident) => {
         match ($self.$field, $other.$field) {
@@ -127,13 +202,44 @@ impl HookConfig {
         self.yarn.as_ref()
     }
 
+    pub fn package(&self) -> Option<&PackageHooks> {
+        self.package.as_ref()
+    }
+
     pub fn events(&self) -> Option<&EventHooks> {
         self.events.as_ref()
     }
 
+    pub fn aliases(&self) -> Option<&AliasHooks> {
+        self.aliases.as_ref()
+    }
+
+    pub fn policies(&self) -> Option<&PolicyHooks> {
+        self.policies.as_ref()
+    }
+
     /// Returns the current hooks, which are a merge between the user hooks and
     /// the project hooks (if any).
     fn current(project: Option<&Project>) -> Fallible<Self> {
+        Self::from_paths(Self::hook_files(project)?)
+    }
+
+    /// Loads each of the hooks files that apply separately, in order of descending precedence
+    ///
+    /// The current hooks take each hook from the first of these files that defines it, so this
+    /// shows which file each hook comes from.
+    pub fn layers(project: Option<&Project>) -> Fallible<Vec<(PathBuf, Self)>> {
+        let mut layers = Vec::new();
+        for hooks_file in Self::hook_files(project)? {
+            if let Some(hooks) = Self::from_file(&hooks_file)? {
+                layers.push((hooks_file, hooks));
+            }
+        }
+        Ok(layers)
+    }
+
+    /// Lists the hooks files that may apply, in order of descending precedence
+    fn hook_files(project: Option<&Project>) -> Fallible<Vec<PathBuf>> {
         let default_hooks_file = volta_home()?.default_hooks_file();
 
         // Since `from_paths` expects the paths to be sorted in descending precedence order, we
@@ -141,17 +247,18 @@ impl HookConfig {
         // precedence order)
         // See the per-project configuration RFC for more details on the configuration precedence:
         // https://github.com/volta-cli/rfcs/blob/main/text/0033-per-project-config.md#configuration-precedence
-        let paths = project
+        let mut paths: Vec<PathBuf> = project
             .into_iter()
             .flat_map(Project::workspace_roots)
             .map(|root| {
                 let mut path = root.join(".volta");
                 path.push("hooks.json");
-                Cow::Owned(path)
+                path
             })
-            .chain(once(Cow::Borrowed(default_hooks_file)));
+            .collect();
+        paths.push(default_hooks_file.to_owned());
 
-        Self::from_paths(paths)
+        Ok(paths)
     }
 
     /// Returns the merged hooks loaded from an iterator of potential hook files
@@ -189,7 +296,10 @@ impl HookConfig {
                         npm: None,
                         pnpm: None,
                         yarn: None,
+                        package: None,
                         events: None,
+                        aliases: None,
+                        policies: None,
                     }
                 })
             })
@@ -209,10 +319,7 @@ impl HookConfig {
                 file: file_path.to_path_buf(),
             })?;
 
-        // Invariant: Since we successfully loaded it, we know we have a valid file path
-        let hooks_path = file_path.parent().expect("File paths always have a parent");
-
-        raw.into_hook_config(hooks_path).map(Some)
+        raw.into_hook_config(file_path).map(Some)
     }
 
     /// Merges this HookConfig with another, giving precedence to the current instance
@@ -222,7 +329,10 @@ impl HookConfig {
             npm: merge_hooks!(self, other, npm),
             pnpm: merge_hooks!(self, other, pnpm),
             yarn: merge_hooks!(self, other, yarn),
+            package: merge_hooks!(self, other, package),
             events: merge_hooks!(self, other, events),
+            aliases: merge_hooks!(self, other, aliases),
+            policies: merge_hooks!(self, other, policies),
         }
     }
 }
@@ -235,7 +345,7 @@ pub enum RegistryFormat {
 }
 
 impl RegistryFormat {
-    pub fn from_str(raw_format: &str) -> Fallible<RegistryFormat> {
+    pub(crate) fn from_str(raw_format: &str) -> Fallible<RegistryFormat> {
         match raw_format {
             "npm" => Ok(RegistryFormat::Npm),
             "github" => Ok(RegistryFormat::Github),
@@ -265,8 +375,9 @@ impl EventHooks {
 #[cfg(test)]
 pub mod tests {
 
-    use super::{tool, HookConfig, Publish, RegistryFormat};
-    use std::path::PathBuf;
+    use super::{tool, HookConfig, Publish, PublishMode, RegistryFormat};
+    use crate::alias::Aliases;
+    use std::path::{Path, PathBuf};
 
     fn fixture_path(fixture_dir: &str) -> PathBuf {
         let mut cargo_manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -287,12 +398,28 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_from_str_event_bin_wait() {
+        let fixture_dir = fixture_path("hooks");
+        let bin_file = fixture_dir.join("event_bin_wait.json");
+        let hooks = HookConfig::from_file(&bin_file).unwrap().unwrap();
+
+        assert_eq!(
+            hooks.events.unwrap().publish,
+            Some(Publish::Bin {
+                command: "/events/bin".to_string(),
+                mode: PublishMode::Wait,
+            })
+        );
+    }
+
     #[test]
     fn test_from_str_bins() {
         let fixture_dir = fixture_path("hooks");
         let bin_file = fixture_dir.join("bins.json");
         let hooks = HookConfig::from_file(&bin_file).unwrap().unwrap();
         let node = hooks.node.unwrap();
+        let npm = hooks.npm.unwrap();
         let pnpm = hooks.pnpm.unwrap();
         let yarn = hooks.yarn.unwrap();
 
@@ -317,6 +444,42 @@ pub mod tests {
                 base_path: fixture_dir.clone(),
             })
         );
+        assert_eq!(
+            node.shasums,
+            Some(tool::ShasumsHook::Bin {
+                bin: "/some/bin/for/node/shasums".to_string(),
+                base_path: fixture_dir.clone(),
+            })
+        );
+        assert_eq!(
+            node.schedule,
+            Some(tool::MetadataHook::Bin {
+                bin: "/some/bin/for/node/schedule".to_string(),
+                base_path: fixture_dir.clone(),
+            })
+        );
+        // npm
+        assert_eq!(
+            npm.distro,
+            Some(tool::DistroHook::Bin {
+                bin: "/bin/to/npm/distro".to_string(),
+                base_path: fixture_dir.clone(),
+            })
+        );
+        assert_eq!(
+            npm.latest,
+            Some(tool::MetadataHook::Bin {
+                bin: "/bin/to/npm/latest".to_string(),
+                base_path: fixture_dir.clone(),
+            })
+        );
+        assert_eq!(
+            npm.index,
+            Some(tool::MetadataHook::Bin {
+                bin: "/bin/to/npm/index".to_string(),
+                base_path: fixture_dir.clone(),
+            })
+        );
         // pnpm
         assert_eq!(
             pnpm.distro,
@@ -366,7 +529,10 @@ pub mod tests {
         );
         assert_eq!(
             hooks.events.unwrap().publish,
-            Some(Publish::Bin("/events/bin".to_string()))
+            Some(Publish::Bin {
+                command: "/events/bin".to_string(),
+                mode: PublishMode::Detach,
+            })
         );
     }
 
@@ -376,6 +542,7 @@ pub mod tests {
         let prefix_file = fixture_dir.join("prefixes.json");
         let hooks = HookConfig::from_file(&prefix_file).unwrap().unwrap();
         let node = hooks.node.unwrap();
+        let npm = hooks.npm.unwrap();
         let pnpm = hooks.pnpm.unwrap();
         let yarn = hooks.yarn.unwrap();
 
@@ -397,6 +564,25 @@ pub mod tests {
                 "http://localhost/node/index/".to_string()
             ))
         );
+        // npm
+        assert_eq!(
+            npm.distro,
+            Some(tool::DistroHook::Prefix(
+                "http://localhost/npm/distro/".to_string()
+            ))
+        );
+        assert_eq!(
+            npm.latest,
+            Some(tool::MetadataHook::Prefix(
+                "http://localhost/npm/latest/".to_string()
+            ))
+        );
+        assert_eq!(
+            npm.index,
+            Some(tool::MetadataHook::Prefix(
+                "http://localhost/npm/index/".to_string()
+            ))
+        );
         // pnpm
         assert_eq!(
             pnpm.distro,
@@ -444,6 +630,7 @@ pub mod tests {
         let template_file = fixture_dir.join("templates.json");
         let hooks = HookConfig::from_file(&template_file).unwrap().unwrap();
         let node = hooks.node.unwrap();
+        let npm = hooks.npm.unwrap();
         let pnpm = hooks.pnpm.unwrap();
         let yarn = hooks.yarn.unwrap();
         assert_eq!(
@@ -464,6 +651,37 @@ pub mod tests {
                 "http://localhost/node/index/{{version}}/".to_string()
             ))
         );
+        assert_eq!(
+            node.shasums,
+            Some(tool::ShasumsHook::Template(
+                "http://localhost/node/shasums/{{version}}/".to_string()
+            ))
+        );
+        assert_eq!(
+            node.schedule,
+            Some(tool::MetadataHook::Template(
+                "http://localhost/node/schedule/{{filename}}".to_string()
+            ))
+        );
+        // npm
+        assert_eq!(
+            npm.distro,
+            Some(tool::DistroHook::Template(
+                "http://localhost/npm/distro/{{version}}/".to_string()
+            ))
+        );
+        assert_eq!(
+            npm.latest,
+            Some(tool::MetadataHook::Template(
+                "http://localhost/npm/latest/{{version}}/".to_string()
+            ))
+        );
+        assert_eq!(
+            npm.index,
+            Some(tool::MetadataHook::Template(
+                "http://localhost/npm/index/{{version}}/".to_string()
+            ))
+        );
         // pnpm
         assert_eq!(
             pnpm.distro,
@@ -505,6 +723,10 @@ pub mod tests {
                 )
             })
         );
+        assert_eq!(
+            hooks.package.unwrap().registry,
+            Some("http://localhost/registry/".to_string())
+        );
     }
 
     #[test]
@@ -661,9 +883,19 @@ pub mod tests {
                 )
             })
         );
+        assert_eq!(
+            merged_hooks
+                .package
+                .expect("No package config found")
+                .registry,
+            Some("http://localhost/project/registry/".to_string())
+        );
         assert_eq!(
             merged_hooks.events.expect("No events config found").publish,
-            Some(Publish::Bin("/events/bin".to_string()))
+            Some(Publish::Bin {
+                command: "/events/bin".to_string(),
+                mode: PublishMode::Detach,
+            })
         );
     }
 
@@ -743,7 +975,40 @@ pub mod tests {
         );
         assert_eq!(
             merged_hooks.events.expect("No events config found").publish,
-            Some(Publish::Bin("/events/bin".to_string()))
+            Some(Publish::Bin {
+                command: "/events/bin".to_string(),
+                mode: PublishMode::Detach,
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_aliases() {
+        let project_hooks_file = fixture_path("hooks/project/.volta/hooks.json");
+        let default_hooks_file = fixture_path("hooks/templates.json");
+
+        let merged_hooks =
+            HookConfig::from_paths([project_hooks_file.clone(), default_hooks_file.clone()])
+                .unwrap();
+        let aliases =
+            Aliases::from_layers([merged_hooks.aliases.expect("No aliases found").aliases]);
+        let summary: Vec<(&str, &str, &Path)> = aliases
+            .for_tool("node")
+            .map(|alias| {
+                (
+                    alias.name.as_str(),
+                    alias.target.as_str(),
+                    alias.source.as_path(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("company-standard", "18.19.0", project_hooks_file.as_path()),
+                ("legacy", "^16", default_hooks_file.as_path()),
+            ]
         );
     }
 }
@@ -2,8 +2,10 @@ use std::marker::PhantomData;
 use std::path::Path;
 
 use super::tool;
-use super::RegistryFormat;
+use super::{PublishMode, RegistryFormat};
+use crate::alias::{self, RawAliases};
 use crate::error::{ErrorKind, Fallible, VoltaError};
+use crate::policy::{self, RawPolicies};
 use crate::tool::{Node, Npm, Pnpm, Tool};
 use serde::{Deserialize, Serialize};
 
@@ -26,6 +28,7 @@ pub struct RawIndexHook {
 pub struct RawPublishHook {
     url: Option<String>,
     bin: Option<String>,
+    mode: Option<String>,
 }
 
 impl RawResolveHook {
@@ -71,6 +74,17 @@ impl RawResolveHook {
         )
     }
 
+    pub fn into_shasums_hook(self, base_dir: &Path) -> Fallible<tool::ShasumsHook> {
+        self.into_hook(
+            tool::ShasumsHook::Prefix,
+            tool::ShasumsHook::Template,
+            |bin| tool::ShasumsHook::Bin {
+                bin,
+                base_path: base_dir.to_owned(),
+            },
+        )
+    }
+
     pub fn into_metadata_hook(self, base_dir: &Path) -> Fallible<tool::MetadataHook> {
         self.into_hook(
             tool::MetadataHook::Prefix,
@@ -110,14 +124,23 @@ impl TryFrom<RawPublishHook> for super::Publish {
             RawPublishHook {
                 url: Some(url),
                 bin: None,
+                ..
             } => Ok(super::Publish::Url(url)),
             RawPublishHook {
                 url: None,
                 bin: Some(bin),
-            } => Ok(super::Publish::Bin(bin)),
+                mode,
+            } => {
+                let mode = match mode {
+                    Some(mode) => PublishMode::from_str(&mode)?,
+                    None => PublishMode::default(),
+                };
+                Ok(super::Publish::Bin { command: bin, mode })
+            }
             RawPublishHook {
                 url: None,
                 bin: None,
+                ..
             } => Err(ErrorKind::PublishHookNeitherUrlNorBin.into()),
             _ => Err(ErrorKind::PublishHookBothUrlAndBin.into()),
         }
@@ -130,7 +153,24 @@ pub struct RawHookConfig {
     pub npm: Option<RawToolHooks<Npm>>,
     pub pnpm: Option<RawToolHooks<Pnpm>>,
     pub yarn: Option<RawYarnHooks>,
+    pub package: Option<RawPackageHooks>,
     pub events: Option<RawEventHooks>,
+    pub aliases: Option<RawAliases>,
+    pub policies: Option<RawPolicies>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "package")]
+pub struct RawPackageHooks {
+    pub registry: Option<String>,
+}
+
+impl From<RawPackageHooks> for super::PackageHooks {
+    fn from(raw: RawPackageHooks) -> super::PackageHooks {
+        super::PackageHooks {
+            registry: raw.registry,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -155,6 +195,8 @@ pub struct RawToolHooks<T: Tool> {
     pub distro: Option<RawResolveHook>,
     pub latest: Option<RawResolveHook>,
     pub index: Option<RawResolveHook>,
+    pub shasums: Option<RawResolveHook>,
+    pub schedule: Option<RawResolveHook>,
 
     #[serde(skip)]
     phantom: PhantomData<T>,
@@ -169,18 +211,37 @@ pub struct RawYarnHooks {
 }
 
 impl RawHookConfig {
-    pub fn into_hook_config(self, base_dir: &Path) -> Fallible<super::HookConfig> {
+    pub fn into_hook_config(self, hooks_file: &Path) -> Fallible<super::HookConfig> {
+        // Invariant: Since we successfully loaded it, we know we have a valid file path
+        let base_dir = hooks_file
+            .parent()
+            .expect("File paths always have a parent");
+
         let node = self.node.map(|n| n.into_tool_hooks(base_dir)).transpose()?;
         let npm = self.npm.map(|n| n.into_tool_hooks(base_dir)).transpose()?;
         let pnpm = self.pnpm.map(|p| p.into_tool_hooks(base_dir)).transpose()?;
         let yarn = self.yarn.map(|y| y.into_yarn_hooks(base_dir)).transpose()?;
+        let package = self.package.map(Into::into);
         let events = self.events.map(|e| e.try_into()).transpose()?;
+        let aliases = self
+            .aliases
+            .map(|a| alias::from_raw(a, hooks_file))
+            .transpose()?
+            .map(|aliases| super::AliasHooks { aliases });
+        let policies = self
+            .policies
+            .map(|p| policy::from_raw(p, hooks_file))
+            .transpose()?
+            .map(|policies| super::PolicyHooks { policies });
         Ok(super::HookConfig {
             node,
             npm,
             pnpm,
             yarn,
+            package,
             events,
+            aliases,
+            policies,
         })
     }
 }
@@ -199,11 +260,21 @@ impl<T: Tool> RawToolHooks<T> {
             .index
             .map(|d| d.into_metadata_hook(base_dir))
             .transpose()?;
+        let shasums = self
+            .shasums
+            .map(|d| d.into_shasums_hook(base_dir))
+            .transpose()?;
+        let schedule = self
+            .schedule
+            .map(|d| d.into_metadata_hook(base_dir))
+            .transpose()?;
 
         Ok(super::ToolHooks {
             distro,
             latest,
             index,
+            shasums,
+            schedule,
             phantom: PhantomData,
         })
     }
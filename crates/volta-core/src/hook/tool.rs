@@ -5,6 +5,7 @@ use std::path::{Path, PathBuf};
 use std::process::Stdio;
 
 use crate::command::create_command;
+use crate::download::DownloadPermit;
 use crate::error::{Context, ErrorKind, Fallible};
 use crate::hook::RegistryFormat;
 use crate::tool::{NODE_DISTRO_ARCH, NODE_DISTRO_OS};
@@ -98,6 +99,45 @@ impl MetadataHook {
     }
 }
 
+/// A hook for resolving the checksum file for a Node distro
+#[derive(PartialEq, Eq, Debug)]
+pub enum ShasumsHook {
+    Prefix(String),
+    Template(String),
+    Bin { bin: String, base_path: PathBuf },
+}
+
+/// The checksum file resolved by a `ShasumsHook`
+#[derive(PartialEq, Eq, Debug)]
+pub enum Shasums {
+    /// The URL to download the checksum file from
+    Url(String),
+    /// The content of the checksum file itself
+    Content(String),
+}
+
+impl ShasumsHook {
+    /// Performs resolution of the checksum file based on the given version and default file name
+    ///
+    /// Prefix and template hooks resolve to the URL of the file, while the output of a bin hook
+    /// is the content of the file, so that it can come from an attestation service.
+    pub fn resolve(&self, version: &Version, filename: &str) -> Fallible<Shasums> {
+        match &self {
+            ShasumsHook::Prefix(prefix) => Ok(Shasums::Url(format!("{}{}", prefix, filename))),
+            ShasumsHook::Template(template) => Ok(Shasums::Url(
+                template
+                    .replace(ARCH_TEMPLATE, NODE_DISTRO_ARCH)
+                    .replace(OS_TEMPLATE, NODE_DISTRO_OS)
+                    .replace(FILENAME_TEMPLATE, filename)
+                    .replace(VERSION_TEMPLATE, &version.to_string()),
+            )),
+            ShasumsHook::Bin { bin, base_path } => {
+                execute_binary(bin, base_path, Some(version.to_string())).map(Shasums::Content)
+            }
+        }
+    }
+}
+
 /// A hook for resolving the URL for the Yarn index
 #[derive(PartialEq, Eq, Debug)]
 pub struct YarnIndexHook {
@@ -119,6 +159,20 @@ impl YarnIndexHook {
     }
 }
 
+/// Sends a HEAD request to a URL resolved by a hook, returning the status code of the response
+///
+/// Only the headers are requested, so nothing is downloaded.
+pub fn probe(url: &str) -> Fallible<u16> {
+    let _permit = DownloadPermit::acquire();
+    debug!("Probing hook URL {}", url);
+
+    let response = attohttpc::head(url)
+        .send()
+        .with_context(|| ErrorKind::HookProbeError { url: url.into() })?;
+
+    Ok(response.status().as_u16())
+}
+
 /// Execute a shell command and return the trimmed stdout from that command
 fn execute_binary(bin: &str, base_path: &Path, extra_arg: Option<String>) -> Fallible<String> {
     let mut trimmed = bin.trim().to_string();
@@ -178,7 +232,7 @@ fn execute_binary(bin: &str, base_path: &Path, extra_arg: Option<String>) -> Fal
 
 #[cfg(test)]
 pub mod tests {
-    use super::{calculate_extension, DistroHook, MetadataHook};
+    use super::{calculate_extension, DistroHook, MetadataHook, Shasums, ShasumsHook};
     use crate::tool::{NODE_DISTRO_ARCH, NODE_DISTRO_OS};
     use node_semver::Version;
 
@@ -254,6 +308,36 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_shasums_prefix_resolve() {
+        let hook = ShasumsHook::Prefix("http://localhost/node/shasums/".to_string());
+        let version = Version::parse("1.0.0").unwrap();
+
+        assert_eq!(
+            hook.resolve(&version, "SHASUMS256.txt")
+                .expect("Could not resolve URL"),
+            Shasums::Url("http://localhost/node/shasums/SHASUMS256.txt".to_string())
+        );
+    }
+
+    #[test]
+    fn test_shasums_template_resolve() {
+        let hook = ShasumsHook::Template(
+            "http://localhost/node/{{os}}/{{arch}}/v{{version}}/{{filename}}".to_string(),
+        );
+        let version = Version::parse("1.0.0").unwrap();
+        let expected = format!(
+            "http://localhost/node/{}/{}/v1.0.0/SHASUMS256.txt",
+            NODE_DISTRO_OS, NODE_DISTRO_ARCH
+        );
+
+        assert_eq!(
+            hook.resolve(&version, "SHASUMS256.txt")
+                .expect("Could not resolve URL"),
+            Shasums::Url(expected)
+        );
+    }
+
     #[test]
     fn test_calculate_extension() {
         // Handles .tar.* files
@@ -0,0 +1,264 @@
+//! Provides support for importing Node versions from other version managers
+//!
+//! Currently, only nvm is supported. Importing never modifies the source directory: versions are
+//! either fetched normally or copied into Volta's image layout.
+
+use std::collections::BTreeSet;
+use std::env;
+use std::fmt::{self, Display};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::read_dir_eager;
+use crate::inventory::node_available;
+use crate::session::Session;
+use crate::style::{note_prefix, success_prefix, tool_version};
+use crate::sync::VoltaLock;
+use crate::tool::{node, Node, Tool};
+use crate::version::{parse_version, VersionSpec, VersionTag};
+use log::{debug, info, warn};
+use node_semver::Version;
+
+const NVM_DIR: &str = "NVM_DIR";
+
+/// The maximum number of nvm aliases that will be followed when resolving the default alias
+const MAX_ALIAS_DEPTH: usize = 10;
+
+/// How Node versions found in the nvm directory are brought into Volta
+#[derive(Clone, Copy)]
+pub enum NvmImportMode {
+    /// Fetch each version normally, as `volta fetch` would
+    Fetch,
+    /// Copy the existing installations into Volta's image layout
+    LinkExisting,
+}
+
+/// Determines the default location of the nvm directory, using `NVM_DIR` if it is set
+pub fn default_nvm_dir() -> Fallible<PathBuf> {
+    match env::var_os(NVM_DIR) {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => dirs::home_dir()
+            .map(|home| home.join(".nvm"))
+            .ok_or_else(|| ErrorKind::NoHomeEnvironmentVar.into()),
+    }
+}
+
+/// Imports the Node versions installed by nvm, along with the nvm default alias
+pub fn import_nvm(nvm_dir: &Path, mode: NvmImportMode, session: &mut Session) -> Fallible<()> {
+    let versions_dir = nvm_dir.join("versions").join("node");
+    if !versions_dir.is_dir() {
+        return Err(ErrorKind::NvmDirNotFound {
+            dir: nvm_dir.to_owned(),
+        }
+        .into());
+    }
+
+    // Acquire a lock on the Volta directory, if possible, to prevent concurrent changes
+    let _lock = VoltaLock::acquire();
+
+    let mut imported = 0;
+    let mut skipped = 0;
+    let mut installed = BTreeSet::new();
+
+    for (name, dir) in nvm_version_dirs(&versions_dir)? {
+        let Ok(version) = parse_version(&name) else {
+            info!("Skipped '{}': {}", name, SkipReason::NotAVersion);
+            skipped += 1;
+            continue;
+        };
+        installed.insert(version.clone());
+
+        match import_version(&version, &dir, mode, session) {
+            Ok(()) => {
+                info!("Imported {}", tool_version("node", &version));
+                imported += 1;
+            }
+            Err(reason) => {
+                info!("Skipped {}: {}", tool_version("node", &version), reason);
+                skipped += 1;
+            }
+        }
+    }
+
+    import_default(nvm_dir, &installed, session)?;
+
+    info!(
+        "{} imported {} Node {} from nvm ({} skipped)",
+        success_prefix(),
+        imported,
+        if imported == 1 { "version" } else { "versions" },
+        skipped
+    );
+
+    Ok(())
+}
+
+/// The reason a directory in the nvm versions directory was not imported
+enum SkipReason {
+    NotAVersion,
+    AlreadyAvailable,
+    Failed(String),
+}
+
+impl Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SkipReason::NotAVersion => f.write_str("not a Node version"),
+            SkipReason::AlreadyAvailable => f.write_str("already available in Volta"),
+            SkipReason::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+fn import_version(
+    version: &Version,
+    dir: &Path,
+    mode: NvmImportMode,
+    session: &mut Session,
+) -> Result<(), SkipReason> {
+    if node_available(version).unwrap_or(false) {
+        return Err(SkipReason::AlreadyAvailable);
+    }
+
+    let result = match mode {
+        NvmImportMode::Fetch => Node::new(version.clone()).ensure_fetched(session),
+        NvmImportMode::LinkExisting => node::import_existing(version, dir),
+    };
+
+    result.map(|_| ()).map_err(|error| {
+        // Only the summary line of the error is useful in the import report
+        let message = error.to_string();
+        SkipReason::Failed(message.lines().next().unwrap_or_default().to_owned())
+    })
+}
+
+/// Lists the directories in the nvm versions directory, sorted by name
+fn nvm_version_dirs(versions_dir: &Path) -> Fallible<Vec<(String, PathBuf)>> {
+    let mut dirs: Vec<_> = read_dir_eager(versions_dir)
+        .with_context(|| ErrorKind::ReadDirError {
+            dir: versions_dir.to_owned(),
+        })?
+        .filter(|(_, metadata)| metadata.is_dir())
+        .filter_map(|(entry, _)| {
+            let name = entry.file_name().into_string().ok()?;
+            Some((name, entry.path()))
+        })
+        .collect();
+
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Sets the Volta default Node version from the nvm `default` alias, if there is one
+fn import_default(
+    nvm_dir: &Path,
+    installed: &BTreeSet<Version>,
+    session: &mut Session,
+) -> Fallible<()> {
+    let Some(target) = resolve_alias(nvm_dir, "default") else {
+        debug!("No nvm default alias found");
+        return Ok(());
+    };
+
+    let version = match default_spec(&target) {
+        Some(VersionSpec::Exact(version)) => version,
+        Some(VersionSpec::Semver(range)) => {
+            // nvm resolves partial versions to the newest matching installed version
+            match installed.iter().rev().find(|v| range.satisfies(v)) {
+                Some(version) => version.clone(),
+                None => node::resolve(VersionSpec::Semver(range), session)?,
+            }
+        }
+        Some(VersionSpec::Tag(VersionTag::Latest)) => match installed.iter().next_back() {
+            Some(version) => version.clone(),
+            None => node::resolve(VersionSpec::Tag(VersionTag::Latest), session)?,
+        },
+        Some(spec) => node::resolve(spec, session)?,
+        None => {
+            warn!(
+                "Could not import the nvm default alias '{}', as it is not supported by Volta",
+                target
+            );
+            info!(
+                "{} to set a default Node version, run `volta install node@<version>`",
+                note_prefix()
+            );
+            return Ok(());
+        }
+    };
+
+    Box::new(Node::new(version)).install(session)
+}
+
+/// Follows a chain of nvm aliases, returning the final target
+fn resolve_alias(nvm_dir: &Path, name: &str) -> Option<String> {
+    let alias_dir = nvm_dir.join("alias");
+    let mut target = read_alias(&alias_dir, name)?;
+
+    for _ in 0..MAX_ALIAS_DEPTH {
+        match read_alias(&alias_dir, &target) {
+            Some(next) => target = next,
+            None => break,
+        }
+    }
+
+    Some(target)
+}
+
+fn read_alias(alias_dir: &Path, name: &str) -> Option<String> {
+    // Alias names such as `lts/*` are stored as nested files, so make sure the name can't escape
+    // the alias directory
+    if name.is_empty() || name.split('/').any(|part| part == "..") {
+        return None;
+    }
+
+    let contents = read_to_string(alias_dir.join(name)).ok()?;
+    let target = contents.trim();
+    if target.is_empty() {
+        None
+    } else {
+        Some(target.to_owned())
+    }
+}
+
+/// Converts the target of an nvm alias into a Volta version specifier
+///
+/// Returns `None` for targets that Volta can't resolve, such as `system` or LTS codenames
+fn default_spec(target: &str) -> Option<VersionSpec> {
+    match target {
+        "lts/*" => Some(VersionSpec::Tag(VersionTag::Lts)),
+        "node" | "stable" => Some(VersionSpec::Tag(VersionTag::Latest)),
+        "system" | "iojs" => None,
+        _ if target.starts_with("lts/") => None,
+        _ => match target.parse() {
+            Ok(spec @ (VersionSpec::Exact(_) | VersionSpec::Semver(_))) => Some(spec),
+            _ => None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_spec_handles_nvm_aliases() {
+        assert_eq!(
+            default_spec("lts/*"),
+            Some(VersionSpec::Tag(VersionTag::Lts))
+        );
+        assert_eq!(
+            default_spec("node"),
+            Some(VersionSpec::Tag(VersionTag::Latest))
+        );
+        assert_eq!(
+            default_spec("v18.12.1"),
+            Some(VersionSpec::Exact(Version::parse("18.12.1").unwrap()))
+        );
+        assert!(matches!(default_spec("18"), Some(VersionSpec::Semver(_))));
+        assert_eq!(default_spec("system"), None);
+        assert_eq!(default_spec("lts/hydrogen"), None);
+        assert_eq!(default_spec("my-alias"), None);
+    }
+}
@@ -1,31 +1,112 @@
 //! Provides types for working with Volta's _inventory_, the local repository
 //! of available tool versions.
+//!
+//! The inventory can be read without a `Session`, either from the Volta home directory of the
+//! current process with the functions of this module, or from any Volta home directory with an
+//! [`Inventory`]. Reading it never fetches tools or changes any files, and every listing is sorted
+//! and free of duplicates.
+//!
+//! ```no_run
+//! use volta_core::inventory::Inventory;
+//!
+//! # fn main() -> volta_core::error::Fallible<()> {
+//! let inventory = Inventory::new("/home/user/.volta");
+//! for version in inventory.node_versions()? {
+//!     println!("node@{}", version);
+//! }
+//! for package in inventory.package_configs()? {
+//!     println!("{}@{}", package.name, package.version);
+//! }
+//! # Ok(())
+//! # }
+//! ```
 
 use std::collections::BTreeSet;
 use std::ffi::OsStr;
-use std::path::Path;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
 
 use crate::error::{Context, ErrorKind, Fallible};
-use crate::fs::read_dir_eager;
+use crate::fs::{ok_if_not_found, read_dir_eager};
 use crate::layout::volta_home;
-use crate::tool::PackageConfig;
+use crate::tool::{Node, PackageConfig};
 use crate::version::parse_version;
 use log::debug;
 use node_semver::Version;
+use volta_layout::v4::VoltaHome;
 use walkdir::WalkDir;
 
-/// Checks if a given Node version image is available on the local machine
+/// The tools and packages installed in a Volta home directory
+///
+/// Unlike the functions of this module, an `Inventory` doesn't depend on the `VOLTA_HOME` of the
+/// current process, so it can read the installation of any user.
+pub struct Inventory {
+    home: VoltaHome,
+}
+
+impl Inventory {
+    /// Reads the inventory of the Volta home directory at the given path
+    pub fn new<P: Into<PathBuf>>(volta_home: P) -> Self {
+        Inventory {
+            home: VoltaHome::new(volta_home.into()),
+        }
+    }
+
+    /// Reads the inventory of the Volta home directory of the current process
+    pub fn current() -> Fallible<Self> {
+        volta_home().map(|home| Inventory::new(home.root()))
+    }
+
+    /// The fetched Node versions, in ascending order
+    ///
+    /// Images built for an architecture other than the native one aren't included.
+    pub fn node_versions(&self) -> Fallible<BTreeSet<Version>> {
+        read_versions(self.home.node_image_root_dir())
+    }
+
+    /// The npm version bundled with a fetched Node version, if it was recorded
+    pub fn node_bundled_npm(&self, version: &Version) -> Option<Version> {
+        let file = self.home.node_npm_version_file(&version.to_string());
+        read_to_string(file)
+            .ok()
+            .and_then(|npm| parse_version(npm).ok())
+    }
+
+    /// The fetched npm versions, in ascending order
+    pub fn npm_versions(&self) -> Fallible<BTreeSet<Version>> {
+        read_versions(self.home.npm_image_root_dir())
+    }
+
+    /// The fetched pnpm versions, in ascending order
+    pub fn pnpm_versions(&self) -> Fallible<BTreeSet<Version>> {
+        read_versions(self.home.pnpm_image_root_dir())
+    }
+
+    /// The fetched Yarn versions, in ascending order
+    pub fn yarn_versions(&self) -> Fallible<BTreeSet<Version>> {
+        read_versions(self.home.yarn_image_root_dir())
+    }
+
+    /// The configs of the installed packages, ordered by package name and then version
+    pub fn package_configs(&self) -> Fallible<BTreeSet<PackageConfig>> {
+        read_package_configs(self.home.default_package_dir())
+    }
+}
+
+/// Checks if a given Node version image is available on the local machine, for the overridden
+/// architecture if there is one
 pub fn node_available(version: &Version) -> Fallible<bool> {
-    volta_home().map(|home| {
-        home.node_image_root_dir()
-            .join(version.to_string())
-            .exists()
-    })
+    Node::image_dir(version).map(|image| image.exists())
 }
 
 /// Collects a set of all Node versions fetched on the local machine
 pub fn node_versions() -> Fallible<BTreeSet<Version>> {
-    volta_home().and_then(|home| read_versions(home.node_image_root_dir()))
+    Inventory::current()?.node_versions()
+}
+
+/// Reads the bundled npm version recorded for a given Node version, if there is one
+pub fn node_bundled_npm(version: &Version) -> Fallible<Option<Version>> {
+    Ok(Inventory::current()?.node_bundled_npm(version))
 }
 
 /// Checks if a given npm version image is available on the local machine
@@ -35,7 +116,7 @@ pub fn npm_available(version: &Version) -> Fallible<bool> {
 
 /// Collects a set of all npm versions fetched on the local machine
 pub fn npm_versions() -> Fallible<BTreeSet<Version>> {
-    volta_home().and_then(|home| read_versions(home.npm_image_root_dir()))
+    Inventory::current()?.npm_versions()
 }
 
 /// Checks if a given pnpm version image is available on the local machine
@@ -45,7 +126,7 @@ pub fn pnpm_available(version: &Version) -> Fallible<bool> {
 
 /// Collects a set of all pnpm versions fetched on the local machine
 pub fn pnpm_versions() -> Fallible<BTreeSet<Version>> {
-    volta_home().and_then(|home| read_versions(home.pnpm_image_root_dir()))
+    Inventory::current()?.pnpm_versions()
 }
 
 /// Checks if a given Yarn version image is available on the local machine
@@ -55,13 +136,16 @@ pub fn yarn_available(version: &Version) -> Fallible<bool> {
 
 /// Collects a set of all Yarn versions fetched on the local machine
 pub fn yarn_versions() -> Fallible<BTreeSet<Version>> {
-    volta_home().and_then(|home| read_versions(home.yarn_image_root_dir()))
+    Inventory::current()?.yarn_versions()
 }
 
 /// Collects a set of all Package Configs on the local machine
 pub fn package_configs() -> Fallible<BTreeSet<PackageConfig>> {
-    let package_dir = volta_home()?.default_package_dir();
+    Inventory::current()?.package_configs()
+}
 
+/// Reads all of the package configs in a directory, including those of scoped packages
+fn read_package_configs(package_dir: &Path) -> Fallible<BTreeSet<PackageConfig>> {
     WalkDir::new(package_dir)
         .max_depth(2)
         .into_iter()
@@ -93,13 +177,105 @@ pub fn package_configs() -> Fallible<BTreeSet<PackageConfig>> {
 
 /// Reads the contents of a directory and returns the set of all versions found
 /// in the directory's listing by parsing the directory names as semantic versions
+///
+/// A directory that doesn't exist yet has no versions.
 fn read_versions(dir: &Path) -> Fallible<BTreeSet<Version>> {
-    let contents = read_dir_eager(dir).with_context(|| ErrorKind::ReadDirError {
-        dir: dir.to_owned(),
-    })?;
+    let contents = match read_dir_eager(dir) {
+        Ok(contents) => contents,
+        Err(error) => {
+            return ok_if_not_found(error).with_context(|| ErrorKind::ReadDirError {
+                dir: dir.to_owned(),
+            })
+        }
+    };
 
     Ok(contents
         .filter(|(_, metadata)| metadata.is_dir())
         .filter_map(|(entry, _)| parse_version(entry.file_name().to_string_lossy()).ok())
         .collect())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_inventory() -> Inventory {
+        let mut home = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        home.push("fixtures");
+        home.push("volta-home");
+        Inventory::new(home)
+    }
+
+    fn versions(versions: &[&str]) -> BTreeSet<Version> {
+        versions.iter().map(|v| v.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn node_versions_are_sorted_and_deduplicated() {
+        // `v18.19.0` names the same version as `18.19.0`, while the `arm64` images, the file
+        // `99.0.0` and anything that isn't a version are left out
+        assert_eq!(
+            fixture_inventory().node_versions().unwrap(),
+            versions(&["18.19.0", "20.11.1"])
+        );
+    }
+
+    #[test]
+    fn node_bundled_npm() {
+        let inventory = fixture_inventory();
+        assert_eq!(
+            inventory.node_bundled_npm(&"20.11.1".parse().unwrap()),
+            Some("10.2.4".parse().unwrap())
+        );
+        assert_eq!(
+            inventory.node_bundled_npm(&"18.19.0".parse().unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn package_manager_versions() {
+        let inventory = fixture_inventory();
+        assert_eq!(inventory.npm_versions().unwrap(), versions(&["10.5.0"]));
+        assert_eq!(
+            inventory.yarn_versions().unwrap(),
+            versions(&["1.22.19", "4.1.0"])
+        );
+    }
+
+    #[test]
+    fn missing_image_directory_has_no_versions() {
+        assert!(fixture_inventory().pnpm_versions().unwrap().is_empty());
+        assert!(Inventory::new("/does/not/exist")
+            .node_versions()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn package_configs_are_sorted_by_name() {
+        let names: Vec<_> = fixture_inventory()
+            .package_configs()
+            .unwrap()
+            .into_iter()
+            .map(|config| (config.name, config.version.to_string()))
+            .collect();
+
+        assert_eq!(
+            names,
+            [
+                ("@vue/cli".to_string(), "5.0.8".to_string()),
+                ("cowsay".to_string(), "1.4.0".to_string()),
+                ("typescript".to_string(), "5.3.3".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_package_directory_has_no_packages() {
+        assert!(Inventory::new("/does/not/exist")
+            .package_configs()
+            .unwrap()
+            .is_empty());
+    }
+}
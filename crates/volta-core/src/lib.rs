@@ -1,24 +1,35 @@
 //! The main implementation crate for the core of Volta.
 
+pub mod alias;
+pub mod binary_manifest;
+pub mod change;
 mod command;
+pub mod download;
 pub mod error;
 pub mod event;
 pub mod fs;
-mod hook;
+pub mod hook;
+pub mod import;
 pub mod inventory;
 pub mod layout;
 pub mod log;
 pub mod monitor;
+pub mod notion;
 pub mod platform;
+pub mod policy;
+pub mod profile;
 pub mod project;
 pub mod run;
+pub mod self_update;
 pub mod session;
+pub mod settings;
 pub mod shim;
 pub mod signal;
 pub mod style;
 pub mod sync;
 pub mod tool;
 pub mod toolchain;
+pub mod usage;
 pub mod version;
 
 const VOLTA_FEATURE_PNPM: &str = "VOLTA_FEATURE_PNPM";
@@ -4,8 +4,10 @@ use log::{trace, Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
 use std::env;
 use std::fmt::Display;
 use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
 use textwrap::{fill, Options, WordSplitter};
 
+use crate::settings::LazySettings;
 use crate::style::text_width;
 
 const ERROR_PREFIX: &str = "error:";
@@ -24,6 +26,9 @@ const ALLOWED_PREFIXES: [&str; 5] = [
 ];
 const WRAP_INDENT: &str = "    ";
 
+static QUIET: AtomicBool = AtomicBool::new(false);
+static PORCELAIN: AtomicBool = AtomicBool::new(false);
+
 /// Represents the context from which the logger was created
 pub enum LogContext {
     /// Log messages from the `volta` executable
@@ -66,7 +71,8 @@ impl Log for Logger {
             match record.level() {
                 Level::Error => self.log_error(record.args()),
                 Level::Warn => self.log_warning(record.args()),
-                // all info-level messages go to stdout
+                // all info-level messages go to stdout, unless it is reserved for porcelain output
+                Level::Info if is_porcelain() => eprintln!("{}", record.args()),
                 Level::Info => println!("{}", record.args()),
                 // all debug- and trace-level messages go to stderr
                 Level::Debug => eprintln!("[verbose] {}", record.args()),
@@ -78,12 +84,28 @@ impl Log for Logger {
     fn flush(&self) {}
 }
 
+/// Determines whether the user asked for as little output as possible, with `--quiet`
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Moves info-level messages to stderr, so that stdout only holds the lines of `--porcelain`
+pub fn set_porcelain() {
+    PORCELAIN.store(true, Ordering::Relaxed);
+}
+
+/// Determines whether stdout is reserved for the lines of `--porcelain`
+pub fn is_porcelain() -> bool {
+    PORCELAIN.load(Ordering::Relaxed)
+}
+
 impl Logger {
     /// Initialize the global logger with a Logger instance
     /// Will use the requested level of Verbosity
     /// If set to Default, will use the environment to determine the level of verbosity
     pub fn init(context: LogContext, verbosity: LogVerbosity) -> Result<(), SetLoggerError> {
         let logger = Logger::new(context, verbosity);
+        QUIET.store(matches!(verbosity, LogVerbosity::Quiet), Ordering::Relaxed);
         log::set_max_level(logger.level);
         log::set_boxed_logger(Box::new(logger))?;
         Ok(())
@@ -155,6 +177,7 @@ where
 
 /// Determines the correct logging level based on the environment
 /// If VOLTA_LOGLEVEL is set to a valid level, we use that
+/// If not, we use the `log.level` setting, if it names a level
 /// If not, we check the current stdout to determine whether it is a TTY or not
 ///     If it is a TTY, we use Info
 ///     If it is NOT a TTY, we use Error as we don't want to show warnings when running as a script
@@ -162,6 +185,14 @@ fn level_from_env() -> LevelFilter {
     env::var(VOLTA_LOGLEVEL)
         .ok()
         .and_then(|level| level.to_uppercase().parse().ok())
+        .or_else(|| {
+            // The logger isn't set up yet, so a settings file that can't be read is reported
+            // later, by whatever uses the settings next
+            LazySettings::init()
+                .get()
+                .ok()
+                .and_then(|settings| settings.log_level().filter())
+        })
         .unwrap_or_else(|| {
             if std::io::stdout().is_terminal() {
                 trace!("using fallback log level (info)");
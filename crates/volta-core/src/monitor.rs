@@ -1,27 +1,40 @@
 use std::env;
 use std::io::Write;
 use std::path::PathBuf;
-use std::process::{Child, Stdio};
+use std::process::{Child, ChildStdin, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use log::debug;
 use tempfile::NamedTempFile;
 
 use crate::command::create_command;
 use crate::event::Event;
+use crate::hook::PublishMode;
+
+/// How long to wait for the spawned process to accept the events on its stdin
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long to wait for the spawned process to exit, when the hook is in `wait` mode
+const WAIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often to check whether the spawned process has exited, when waiting for it
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
 
 /// Send event to the spawned command process
 // if hook command is not configured, this is not called
-pub fn send_events(command: &str, events: &[Event]) {
+pub fn send_events(command: &str, mode: PublishMode, events: &[Event]) {
     match serde_json::to_string_pretty(&events) {
         Ok(events_json) => {
             let tempfile_path = env::var_os("VOLTA_WRITE_EVENTS_FILE")
                 .and_then(|_| write_events_file(events_json.clone()));
-            if let Some(ref mut child_process) = spawn_process(command, tempfile_path) {
-                if let Some(ref mut p_stdin) = child_process.stdin.as_mut() {
-                    if let Err(error) = writeln!(p_stdin, "{}", events_json) {
-                        debug!("Could not write events to executable stdin: {:?}", error);
-                    }
-                }
+            if let Some(child_process) = spawn_process(command, tempfile_path) {
+                let wait_timeout = match mode {
+                    PublishMode::Detach => None,
+                    PublishMode::Wait => Some(WAIT_TIMEOUT),
+                };
+                deliver(child_process, events_json, WRITE_TIMEOUT, wait_timeout);
             }
         }
         Err(error) => {
@@ -30,6 +43,107 @@ pub fn send_events(command: &str, events: &[Event]) {
     }
 }
 
+/// Writes the events to the stdin of the spawned process, then either waits for it to exit (for
+/// at most `wait_timeout`) or detaches from it
+///
+/// Neither step can block for longer than its timeout, so a process that never reads its input or
+/// never exits can't hang Volta.
+fn deliver(
+    mut child: Child,
+    events_json: String,
+    write_timeout: Duration,
+    wait_timeout: Option<Duration>,
+) {
+    if let Some(stdin) = child.stdin.take() {
+        write_with_timeout(stdin, events_json, write_timeout);
+    }
+
+    match wait_timeout {
+        Some(timeout) => wait_with_timeout(child, timeout),
+        None => detach(child),
+    }
+}
+
+fn write_with_timeout(mut stdin: ChildStdin, events_json: String, timeout: Duration) {
+    let (sender, receiver) = mpsc::channel();
+
+    // Writing happens on a separate thread, since a write to a full pipe blocks until the process
+    // reads from it. The pipe is closed when the thread finishes, so the process sees the end of
+    // its input.
+    let spawned = thread::Builder::new()
+        .name("volta-events-writer".into())
+        .spawn(move || {
+            let _ = sender.send(writeln!(stdin, "{}", events_json));
+        });
+
+    if let Err(error) = spawned {
+        debug!(
+            "Could not start writing events to executable stdin: {:?}",
+            error
+        );
+        return;
+    }
+
+    match receiver.recv_timeout(timeout) {
+        Ok(Ok(())) => {}
+        Ok(Err(error)) => debug!("Could not write events to executable stdin: {:?}", error),
+        Err(_) => debug!(
+            "Timed out after {:?} writing events to executable stdin",
+            timeout
+        ),
+    }
+}
+
+fn wait_with_timeout(mut child: Child, timeout: Duration) {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                debug!("Events executable exited with {}", status);
+                return;
+            }
+            Ok(None) if Instant::now() < deadline => thread::sleep(POLL_INTERVAL),
+            Ok(None) => {
+                debug!(
+                    "Events executable did not exit within {:?}, stopping it",
+                    timeout
+                );
+                if let Err(error) = child.kill() {
+                    debug!("Could not stop events executable: {:?}", error);
+                }
+                // Reap the process, so it doesn't linger as a zombie
+                let _ = child.wait();
+                return;
+            }
+            Err(error) => {
+                debug!("Could not wait for events executable: {:?}", error);
+                return;
+            }
+        }
+    }
+}
+
+/// Lets the spawned process finish on its own
+///
+/// If it exits while Volta is still running, a background thread reaps it so it doesn't linger as a
+/// zombie. If Volta exits first, the process is re-parented and reaped by the system instead.
+fn detach(mut child: Child) {
+    if let Ok(Some(_)) = child.try_wait() {
+        return;
+    }
+
+    let spawned = thread::Builder::new()
+        .name("volta-events-reaper".into())
+        .spawn(move || {
+            let _ = child.wait();
+        });
+
+    if let Err(error) = spawned {
+        debug!("Could not detach from events executable: {:?}", error);
+    }
+}
+
 // Write the events JSON to a file in the temporary directory
 fn write_events_file(events_json: String) -> Option<PathBuf> {
     match NamedTempFile::new() {
@@ -65,15 +179,16 @@ fn spawn_process(command: &str, tempfile_path: Option<PathBuf>) -> Option<Child>
     command.split(' ').take(1).next().and_then(|executable| {
         let mut child = create_command(executable);
         child.args(command.split(' ').skip(1));
-        child.stdin(Stdio::piped());
+        // Only stdin is connected, so the output of the spawned process can't interleave with
+        // Volta's own output, or keep our pipes open after Volta exits
+        child
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
         if let Some(events_file) = tempfile_path {
             child.env("EVENTS_FILE", events_file);
         }
 
-        #[cfg(not(debug_assertions))]
-        // Hide stdout and stderr of spawned process in release mode
-        child.stdout(Stdio::null()).stderr(Stdio::null());
-
         match child.spawn() {
             Err(err) => {
                 debug!("Unable to run executable command: '{}'\n{}", command, err);
@@ -83,3 +198,86 @@ fn spawn_process(command: &str, tempfile_path: Option<PathBuf>) -> Option<Child>
         }
     })
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn fixture_script(name: &str) -> String {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("fixtures");
+        path.push("monitor");
+        path.push(name);
+        path.to_string_lossy().into_owned()
+    }
+
+    fn spawn_fixture(name: &str) -> Child {
+        spawn_process(&fixture_script(name), None).expect("fixture script should start")
+    }
+
+    fn stop(pid: u32) {
+        let _ = Command::new("kill").arg(pid.to_string()).status();
+    }
+
+    #[test]
+    fn detaching_does_not_wait_for_hanging_process() {
+        let child = spawn_fixture("sleep-forever.sh");
+        let pid = child.id();
+
+        let start = Instant::now();
+        deliver(child, "[]".into(), Duration::from_secs(5), None);
+        let elapsed = start.elapsed();
+        stop(pid);
+
+        assert!(elapsed < Duration::from_secs(1), "took {:?}", elapsed);
+    }
+
+    #[test]
+    fn waiting_is_bounded_for_hanging_process() {
+        let child = spawn_fixture("sleep-forever.sh");
+
+        let start = Instant::now();
+        deliver(
+            child,
+            "[]".into(),
+            Duration::from_secs(5),
+            Some(Duration::from_millis(200)),
+        );
+        let elapsed = start.elapsed();
+
+        assert!(elapsed >= Duration::from_millis(200), "took {:?}", elapsed);
+        assert!(elapsed < Duration::from_secs(2), "took {:?}", elapsed);
+    }
+
+    #[test]
+    fn writing_is_bounded_when_process_never_reads() {
+        let child = spawn_fixture("sleep-forever.sh");
+        let pid = child.id();
+        // Larger than a pipe buffer, so the write can't complete without the process reading it
+        let events_json = "x".repeat(4 * 1024 * 1024);
+
+        let start = Instant::now();
+        deliver(child, events_json, Duration::from_millis(200), None);
+        let elapsed = start.elapsed();
+        stop(pid);
+
+        assert!(elapsed < Duration::from_secs(2), "took {:?}", elapsed);
+    }
+
+    #[test]
+    fn waiting_returns_once_process_exits() {
+        let child = spawn_fixture("exit-immediately.sh");
+
+        let start = Instant::now();
+        deliver(
+            child,
+            "[]".into(),
+            Duration::from_secs(5),
+            Some(Duration::from_secs(10)),
+        );
+        let elapsed = start.elapsed();
+
+        assert!(elapsed < Duration::from_secs(5), "took {:?}", elapsed);
+    }
+}
@@ -0,0 +1,300 @@
+//! Detects what is left of Notion, the name that Volta had before it was renamed
+//!
+//! Notion kept its tools and shims in `~/.notion` (or in `NOTION_HOME`), and its setup put the
+//! shim directory on the `PATH`. Those shims still run the old Notion binaries, so a Notion home
+//! left alongside the Volta home means that a tool is sometimes run by one and sometimes by the
+//! other. Nothing here modifies the Notion home: [`LegacyNotion::back_up`] only renames it, so that
+//! it can be restored by renaming it back.
+
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::layout::volta_home;
+use crate::platform::PlatformSpec;
+use crate::toolchain::serial::Platform;
+use log::{debug, warn};
+
+const NOTION_HOME: &str = "NOTION_HOME";
+
+/// How often the commands that change the toolchain check for a Notion home
+const CHECK_INTERVAL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// The file in the cache directory whose modification time records the last check
+const CHECK_STAMP: &str = "notion-check";
+
+/// A Notion home, or entries in the `PATH` that point into one
+#[derive(Debug)]
+pub struct LegacyNotion {
+    /// The location of the Notion home
+    pub root: PathBuf,
+    /// Whether the Notion home still exists
+    pub exists: bool,
+    /// The entries of the `PATH` that are inside the Notion home, usually its shim directory
+    pub path_entries: Vec<PathBuf>,
+}
+
+/// The state of a Notion home that can be brought over to Volta
+#[derive(Default)]
+pub struct Salvage {
+    /// The default platform
+    pub platform: Option<PlatformSpec>,
+    /// The names of the packages that were installed, which have to be installed again
+    pub packages: Vec<String>,
+}
+
+impl LegacyNotion {
+    /// Reads the default platform and the installed packages of the Notion home
+    ///
+    /// Notion used the same format as Volta for both, but anything that can't be read is skipped,
+    /// since none of it is needed to migrate.
+    pub fn salvage(&self) -> Salvage {
+        let user_dir = self.root.join("tools").join("user");
+
+        let platform = match fs::read_to_string(user_dir.join("platform.json")) {
+            Ok(contents) => match Platform::try_from(contents) {
+                Ok(platform) => platform.into(),
+                Err(error) => {
+                    debug!("Could not parse the Notion default platform: {}", error);
+                    None
+                }
+            },
+            Err(_) => None,
+        };
+
+        let mut packages: Vec<String> = fs::read_dir(user_dir.join("packages"))
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| package_name(&path))
+            .collect();
+        packages.sort();
+        packages.dedup();
+
+        Salvage { platform, packages }
+    }
+
+    /// Renames the Notion home to `.notion.bak` next to it, or to `.notion.bak.1` and so on if
+    /// that is taken, returning the new location
+    pub fn back_up(&self) -> Fallible<PathBuf> {
+        let backup = self.backup_dir();
+        fs::rename(&self.root, &backup).with_context(|| ErrorKind::NotionBackupError {
+            from: self.root.clone(),
+            to: backup.clone(),
+        })?;
+        Ok(backup)
+    }
+
+    /// The location that [`back_up`](Self::back_up) renames the Notion home to
+    pub fn backup_dir(&self) -> PathBuf {
+        let name = format!(
+            "{}.bak",
+            self.root.file_name().unwrap_or_default().to_string_lossy()
+        );
+
+        let mut backup = self.root.with_file_name(&name);
+        let mut suffix = 0;
+        while backup.exists() {
+            suffix += 1;
+            backup = self.root.with_file_name(format!("{}.{}", name, suffix));
+        }
+        backup
+    }
+
+    /// Describes what was found, for warnings
+    pub fn describe(&self) -> String {
+        let entries = self
+            .path_entries
+            .iter()
+            .map(|entry| entry.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        match (self.exists, self.path_entries.is_empty()) {
+            (true, true) => format!(
+                "Found {}, the directory of Notion (the previous name of Volta)",
+                self.root.display()
+            ),
+            (true, false) => format!(
+                "Found {}, the directory of Notion (the previous name of Volta), and its shims are on your PATH: {}",
+                self.root.display(),
+                entries
+            ),
+            (false, _) => format!(
+                "Your PATH includes the shims of Notion (the previous name of Volta): {}",
+                entries
+            ),
+        }
+    }
+}
+
+/// Reads the name of a package from its Notion config file, falling back to the file name
+fn package_name(config: &Path) -> Option<String> {
+    let from_config = fs::read_to_string(config)
+        .ok()
+        .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+        .and_then(|json| json.get("name")?.as_str().map(str::to_owned));
+
+    from_config.or_else(|| Some(config.file_stem()?.to_string_lossy().into_owned()))
+}
+
+/// The location of the Notion home, from `NOTION_HOME` or else `~/.notion`
+fn notion_root() -> Fallible<PathBuf> {
+    match env::var_os(NOTION_HOME).filter(|home| !home.is_empty()) {
+        Some(home) => Ok(PathBuf::from(home)),
+        None => dirs::home_dir()
+            .map(|home| home.join(".notion"))
+            .ok_or_else(|| ErrorKind::NoHomeEnvironmentVar.into()),
+    }
+}
+
+/// The entries of a `PATH` that are inside the Notion home
+fn entries_within(path: &OsStr, root: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = Vec::new();
+    for entry in env::split_paths(path) {
+        if entry.starts_with(root) && !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    }
+    entries
+}
+
+/// Looks for a Notion home and for entries in the `PATH` that point into one
+pub fn detect() -> Fallible<Option<LegacyNotion>> {
+    let root = notion_root()?;
+    let path_entries = entries_within(&env::var_os("PATH").unwrap_or_default(), &root);
+    let exists = root.is_dir();
+
+    if exists || !path_entries.is_empty() {
+        Ok(Some(LegacyNotion {
+            root,
+            exists,
+            path_entries,
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Warns about a Notion home, checking at most once per week
+///
+/// This runs before the commands that change the toolchain, so it never fails: any problem is only
+/// logged.
+pub fn warn_weekly() {
+    let stamp = match volta_home() {
+        Ok(home) => home.cache_dir().join(CHECK_STAMP),
+        Err(_) => return,
+    };
+
+    let checked_recently = fs::metadata(&stamp)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|elapsed| elapsed < CHECK_INTERVAL);
+    if checked_recently {
+        return;
+    }
+
+    match detect() {
+        Ok(Some(legacy)) => warn!(
+            "{}\n\nRun `volta setup --migrate-notion` to import its settings and move it out of the way.",
+            legacy.describe()
+        ),
+        Ok(None) => {}
+        Err(error) => debug!("Could not check for a Notion directory: {}", error),
+    }
+
+    if let Err(error) = stamp
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| fs::write(&stamp, ""))
+    {
+        debug!(
+            "Could not record the check for a Notion directory in '{}': {}",
+            stamp.display(),
+            error
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy(root: &Path) -> LegacyNotion {
+        LegacyNotion {
+            root: root.to_owned(),
+            exists: root.is_dir(),
+            path_entries: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn finds_path_entries_in_the_notion_home() {
+        let root = env::temp_dir().join("home").join(".notion");
+        let path = env::join_paths([
+            root.join("bin"),
+            env::temp_dir().join("home").join(".volta").join("bin"),
+            root.join("bin"),
+            env::temp_dir().join("home").join(".notion-tools"),
+        ])
+        .unwrap();
+
+        assert_eq!(entries_within(&path, &root), vec![root.join("bin")]);
+    }
+
+    #[test]
+    fn salvages_platform_and_packages() {
+        let home = tempfile::tempdir().unwrap();
+        let root = home.path().join(".notion");
+        let user_dir = root.join("tools").join("user");
+        fs::create_dir_all(user_dir.join("packages")).unwrap();
+        fs::write(
+            user_dir.join("platform.json"),
+            r#"{"node":{"runtime":"10.15.0","npm":"6.4.1"},"yarn":"1.12.3"}"#,
+        )
+        .unwrap();
+        fs::write(
+            user_dir.join("packages").join("cowsay.json"),
+            r#"{"name":"cowsay","version":"1.4.0"}"#,
+        )
+        .unwrap();
+        fs::write(
+            user_dir.join("packages").join("typescript.json"),
+            "not json",
+        )
+        .unwrap();
+
+        let salvage = legacy(&root).salvage();
+        let platform = salvage.platform.unwrap();
+        assert_eq!(platform.node.to_string(), "10.15.0");
+        assert_eq!(platform.npm.unwrap().to_string(), "6.4.1");
+        assert_eq!(platform.yarn.unwrap().to_string(), "1.12.3");
+        assert_eq!(salvage.packages, vec!["cowsay", "typescript"]);
+
+        // A Notion home without any state has nothing to salvage
+        let empty = legacy(home.path()).salvage();
+        assert!(empty.platform.is_none());
+        assert!(empty.packages.is_empty());
+    }
+
+    #[test]
+    fn backs_up_without_overwriting() {
+        let home = tempfile::tempdir().unwrap();
+        let root = home.path().join(".notion");
+        fs::create_dir_all(home.path().join(".notion.bak")).unwrap();
+        fs::create_dir_all(root.join("bin")).unwrap();
+
+        let backup = legacy(&root).back_up().unwrap();
+
+        assert_eq!(backup, home.path().join(".notion.bak.1"));
+        assert!(backup.join("bin").is_dir());
+        assert!(!root.exists());
+        assert!(home.path().join(".notion.bak").is_dir());
+    }
+}
@@ -0,0 +1,345 @@
+use std::fmt;
+use std::mem;
+
+use crate::error::{ErrorKind, Fallible, VoltaError};
+use crate::style::tool_version;
+use log::warn;
+use node_semver::Version;
+
+/// A tool in the platform, other than Node, that an `Image` can be checked out without
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OptionalTool {
+    Npm,
+    Pnpm,
+    Yarn,
+}
+
+impl OptionalTool {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OptionalTool::Npm => "npm",
+            OptionalTool::Pnpm => "pnpm",
+            OptionalTool::Yarn => "yarn",
+        }
+    }
+}
+
+impl fmt::Display for OptionalTool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The outcomes of making each tool in a `Platform` available during a checkout
+#[derive(Default)]
+pub(super) struct CheckoutReport {
+    entries: Vec<(String, Outcome)>,
+    /// Whether missing tools are reported instead of fetched (`install.mode` is `manual`)
+    manual: bool,
+}
+
+enum Outcome {
+    AlreadyPresent,
+    Fetched,
+    Failed(VoltaError),
+    Missing,
+    Skipped,
+}
+
+impl CheckoutReport {
+    /// Constructs a report that doesn't fetch missing tools, only recording them as missing
+    pub(super) fn manual() -> Self {
+        CheckoutReport {
+            entries: Vec::new(),
+            manual: true,
+        }
+    }
+
+    /// Makes a single tool available, recording whether it was already present, was fetched, or
+    /// could not be fetched
+    ///
+    /// Returns `true` if the tool is available afterwards. A tool that fails to fetch but is
+    /// allowed to be missing is skipped with a warning, rather than failing the checkout. For a
+    /// manual report, a tool that isn't already present is not fetched at all.
+    pub(super) fn check<A, F>(
+        &mut self,
+        name: &str,
+        version: &Version,
+        allow_missing: bool,
+        available: A,
+        fetch: F,
+    ) -> bool
+    where
+        A: FnOnce(&Version) -> Fallible<bool>,
+        F: FnOnce() -> Fallible<()>,
+    {
+        let already_present = available(version).unwrap_or(false);
+
+        let outcome = if self.manual && !already_present {
+            if allow_missing {
+                warn!(
+                    "Continuing without {}, as it is not installed and automatic installs are disabled",
+                    tool_version(name, version)
+                );
+                Outcome::Skipped
+            } else {
+                Outcome::Missing
+            }
+        } else {
+            self.fetch_outcome(name, version, already_present, allow_missing, fetch)
+        };
+
+        let is_available = matches!(outcome, Outcome::AlreadyPresent | Outcome::Fetched);
+        self.entries.push((tool_version(name, version), outcome));
+        is_available
+    }
+
+    fn fetch_outcome<F>(
+        &self,
+        name: &str,
+        version: &Version,
+        already_present: bool,
+        allow_missing: bool,
+        fetch: F,
+    ) -> Outcome
+    where
+        F: FnOnce() -> Fallible<()>,
+    {
+        match fetch() {
+            Ok(()) if already_present => Outcome::AlreadyPresent,
+            Ok(()) => Outcome::Fetched,
+            Err(error) if allow_missing => {
+                warn!(
+                    "Continuing without {}, as it could not be fetched: {}",
+                    tool_version(name, version),
+                    summary(&error)
+                );
+                Outcome::Skipped
+            }
+            Err(error) => Outcome::Failed(error),
+        }
+    }
+
+    /// Ends the checkout with the report so far if a tool failed to fetch, so that the tools after
+    /// it aren't fetched for a command that won't run
+    pub(super) fn stop_on_failure(&mut self) -> Fallible<()> {
+        let failed = self
+            .entries
+            .iter()
+            .any(|(_, outcome)| matches!(outcome, Outcome::Failed(_)));
+
+        if failed {
+            mem::take(self).finish()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Converts the report into the result of the checkout
+    ///
+    /// Missing tools are reported together, along with the command that installs them. When the
+    /// only tool involved fails, its error is returned unchanged. Otherwise, the error describes
+    /// which tools are usable, so that it's clear what state the checkout was left in.
+    pub(super) fn finish(self) -> Fallible<()> {
+        let mut present = Vec::new();
+        let mut fetched = Vec::new();
+        let mut failures = Vec::new();
+        let mut missing = Vec::new();
+
+        for (tool, outcome) in self.entries {
+            match outcome {
+                Outcome::AlreadyPresent => present.push(tool),
+                Outcome::Fetched => fetched.push(tool),
+                Outcome::Failed(error) => failures.push((tool, error)),
+                Outcome::Missing => missing.push(tool),
+                Outcome::Skipped => {}
+            }
+        }
+
+        if !missing.is_empty() {
+            return Err(ErrorKind::ToolsNotInstalled { tools: missing }.into());
+        }
+
+        if failures.is_empty() {
+            return Ok(());
+        }
+
+        if present.is_empty() && fetched.is_empty() && failures.len() == 1 {
+            return Err(failures.remove(0).1);
+        }
+
+        let failed = failures
+            .iter()
+            .map(|(tool, error)| (tool.clone(), error.to_string()))
+            .collect();
+        let (_, error) = failures.remove(0);
+        let kind = ErrorKind::PlatformCheckoutError {
+            present,
+            fetched,
+            failed,
+            exit_code: error.exit_code(),
+        };
+
+        Err(VoltaError::from_source(error, kind))
+    }
+}
+
+/// The first line of an error message, which is enough to identify the failure in a warning
+fn summary(error: &VoltaError) -> String {
+    let message = error.to_string();
+    message.lines().next().unwrap_or_default().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ExitCode;
+
+    fn version(v: &str) -> Version {
+        Version::parse(v).unwrap()
+    }
+
+    fn yarn_failure() -> Fallible<()> {
+        Err(ErrorKind::DownloadToolNetworkError {
+            tool: crate::tool::Spec::Yarn(crate::version::VersionSpec::default()),
+            from_url: "https://example.com/yarn.tgz".into(),
+        }
+        .into())
+    }
+
+    #[test]
+    fn finish_succeeds_when_all_tools_are_available() {
+        let mut report = CheckoutReport::default();
+        assert!(report.check("node", &version("18.0.0"), false, |_| Ok(true), || Ok(())));
+        assert!(report.check("yarn", &version("1.22.0"), false, |_| Ok(false), || Ok(())));
+
+        assert!(report.finish().is_ok());
+    }
+
+    #[test]
+    fn single_failure_is_returned_unchanged() {
+        let mut report = CheckoutReport::default();
+        assert!(!report.check(
+            "yarn",
+            &version("1.22.0"),
+            false,
+            |_| Ok(false),
+            yarn_failure
+        ));
+
+        let error = report.finish().unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::DownloadToolNetworkError { .. }
+        ));
+    }
+
+    #[test]
+    fn partial_failure_lists_each_outcome() {
+        let mut report = CheckoutReport::default();
+        report.check("node", &version("18.0.0"), false, |_| Ok(true), || Ok(()));
+        report.check("npm", &version("9.0.0"), false, |_| Ok(false), || Ok(()));
+        report.check(
+            "yarn",
+            &version("1.22.0"),
+            false,
+            |_| Ok(false),
+            yarn_failure,
+        );
+
+        let error = report.finish().unwrap_err();
+        match error.kind() {
+            ErrorKind::PlatformCheckoutError {
+                present,
+                fetched,
+                failed,
+                exit_code,
+            } => {
+                assert_eq!(present, &["node@18.0.0"]);
+                assert_eq!(fetched, &["npm@9.0.0"]);
+                assert_eq!(failed.len(), 1);
+                assert_eq!(failed[0].0, "yarn@1.22.0");
+                assert!(failed[0].1.contains("Could not download yarn"));
+                assert_eq!(*exit_code, ExitCode::NetworkError);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn stops_after_the_first_failure() {
+        let mut report = CheckoutReport::default();
+        report.check("node", &version("18.0.0"), false, |_| Ok(true), || Ok(()));
+        assert!(report.stop_on_failure().is_ok());
+
+        report.check("npm", &version("9.0.0"), false, |_| Ok(false), yarn_failure);
+        let error = report.stop_on_failure().unwrap_err();
+        match error.kind() {
+            ErrorKind::PlatformCheckoutError {
+                present, failed, ..
+            } => {
+                assert_eq!(present, &["node@18.0.0"]);
+                assert_eq!(failed[0].0, "npm@9.0.0");
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn allowed_missing_tools_are_skipped() {
+        let mut report = CheckoutReport::default();
+        report.check("node", &version("18.0.0"), false, |_| Ok(true), || Ok(()));
+        assert!(!report.check(
+            "yarn",
+            &version("1.22.0"),
+            true,
+            |_| Ok(false),
+            yarn_failure
+        ));
+
+        assert!(report.finish().is_ok());
+    }
+
+    #[test]
+    fn manual_reports_every_missing_tool_without_fetching() {
+        let mut report = CheckoutReport::manual();
+        assert!(report.check("node", &version("18.0.0"), false, |_| Ok(true), || Ok(())));
+        assert!(!report.check(
+            "npm",
+            &version("9.0.0"),
+            false,
+            |_| Ok(false),
+            || panic!("npm should not be fetched")
+        ));
+        assert!(!report.check(
+            "yarn",
+            &version("1.22.0"),
+            false,
+            |_| Ok(false),
+            || panic!("yarn should not be fetched")
+        ));
+
+        let error = report.finish().unwrap_err();
+        match error.kind() {
+            ErrorKind::ToolsNotInstalled { tools } => {
+                assert_eq!(tools, &["npm@9.0.0", "yarn@1.22.0"]);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn manual_skips_missing_tools_that_are_allowed_to_be_missing() {
+        let mut report = CheckoutReport::manual();
+        report.check("node", &version("18.0.0"), false, |_| Ok(true), || Ok(()));
+        assert!(!report.check(
+            "yarn",
+            &version("1.22.0"),
+            true,
+            |_| Ok(false),
+            || panic!("yarn should not be fetched")
+        ));
+
+        assert!(report.finish().is_ok());
+    }
+}
@@ -1,10 +1,14 @@
-use std::ffi::OsString;
+use std::env;
+use std::ffi::{OsStr, OsString};
 use std::path::PathBuf;
 
-use super::{build_path_error, Sourced};
+use super::path::join_path;
+use super::{build_path_error, Sourced, System};
 use crate::error::{Context, Fallible};
-use crate::layout::volta_home;
-use crate::tool::load_default_npm_version;
+use crate::inventory::{node_available, npm_available, pnpm_available, yarn_available};
+use crate::layout::{env_paths, volta_home};
+use crate::style::tool_version;
+use crate::tool::{load_default_npm_version, Node};
 use node_semver::Version;
 
 /// A platform image.
@@ -19,6 +23,14 @@ pub struct Image {
     pub yarn: Option<Sourced<Version>>,
 }
 
+/// An executable of the platform whose tool hasn't been fetched yet
+pub struct Unfetched {
+    /// The tool and version that provides the executable, e.g. `node@20.11.1`
+    pub tool: String,
+    /// The path that the executable will have once the tool is fetched
+    pub path: PathBuf,
+}
+
 impl Image {
     fn bins(&self) -> Fallible<Vec<PathBuf>> {
         let home = volta_home()?;
@@ -40,22 +52,92 @@ impl Image {
         }
 
         // Add Node path to the bins last, so that any custom version of npm will be earlier in the PATH
-        let node_str = self.node.value.to_string();
-        bins.push(home.node_image_bin_dir(&node_str));
+        bins.push(Node::image_bin_dir(&self.node.value)?);
         Ok(bins)
     }
 
     /// Produces a modified version of the current `PATH` environment variable that
     /// will find toolchain executables (Node, npm, pnpm, Yarn) in the installation directories
     /// for the given versions instead of in the Volta shim directory.
+    ///
+    /// If the shim directory isn't on the `PATH`, as in a cron job that runs Volta by its absolute
+    /// path, it is added at the end so that the tool can still run installed packages. Repeated
+    /// entries are dropped.
     pub fn path(&self) -> Fallible<OsString> {
         let old_path = envoy::path().unwrap_or_else(|| envoy::Var::from(""));
+        let bins = self.bins()?;
+        let missing: Vec<PathBuf> = env_paths()?
+            .into_iter()
+            .filter(|dir| !old_path.split().any(|entry| entry == *dir))
+            .collect();
+        let added: Vec<PathBuf> = bins.iter().chain(&missing).cloned().collect();
+
+        join_path(old_path.split().prefix(bins).suffix(missing), &added)
+    }
 
-        old_path
-            .split()
-            .prefix(self.bins()?)
-            .join()
-            .with_context(build_path_error)
+    /// Produces a `PATH` that runs the image's tools directly, for environments that don't go
+    /// through the shims, such as cron jobs and systemd units
+    ///
+    /// The tools come first, then the shim directory (so that installed packages are found), and
+    /// then the rest of the current `PATH`.
+    pub fn pinned_path(&self) -> Fallible<OsString> {
+        let system = System::path()?;
+        let mut paths = self.bins()?;
+        paths.extend(env_paths()?);
+        paths.extend(env::split_paths(&system));
+
+        env::join_paths(paths).with_context(build_path_error)
+    }
+
+    /// Checks whether the tool that provides one of the platform's executables has been fetched,
+    /// returning where the executable will be if it hasn't
+    ///
+    /// This only looks at the local inventory, so it can be used on an image that was never checked
+    /// out. Executables that don't belong to one of the image's tools return `None`.
+    pub fn unfetched(&self, binary: &OsStr) -> Fallible<Option<Unfetched>> {
+        let home = volta_home()?;
+        let (name, version, available, bin_dir) = match (binary.to_str(), &self.npm) {
+            (Some("node"), _) | (Some("npm" | "npx"), None) => (
+                "node",
+                &self.node.value,
+                node_available(&self.node.value)?,
+                Node::image_bin_dir(&self.node.value)?,
+            ),
+            (Some("npm" | "npx"), Some(npm)) => (
+                "npm",
+                &npm.value,
+                npm_available(&npm.value)?,
+                home.npm_image_bin_dir(&npm.value.to_string()),
+            ),
+            (Some("pnpm" | "pnpx"), _) => match &self.pnpm {
+                Some(pnpm) => (
+                    "pnpm",
+                    &pnpm.value,
+                    pnpm_available(&pnpm.value)?,
+                    home.pnpm_image_bin_dir(&pnpm.value.to_string()),
+                ),
+                None => return Ok(None),
+            },
+            (Some("yarn" | "yarnpkg"), _) => match &self.yarn {
+                Some(yarn) => (
+                    "yarn",
+                    &yarn.value,
+                    yarn_available(&yarn.value)?,
+                    home.yarn_image_bin_dir(&yarn.value.to_string()),
+                ),
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+
+        if available {
+            return Ok(None);
+        }
+
+        Ok(Some(Unfetched {
+            tool: tool_version(name, version),
+            path: bin_dir.join(executable_file(binary)),
+        }))
     }
 
     /// Determines the sourced version of npm that will be available, resolving the version bundled with Node, if needed
@@ -69,3 +151,17 @@ impl Image {
         }
     }
 }
+
+/// The file name of a platform executable, which on Windows is a batch script for everything but
+/// Node itself
+#[cfg(unix)]
+fn executable_file(binary: &OsStr) -> OsString {
+    binary.to_owned()
+}
+
+#[cfg(windows)]
+fn executable_file(binary: &OsStr) -> OsString {
+    let mut file = binary.to_owned();
+    file.push(if binary == "node" { ".exe" } else { ".cmd" });
+    file
+}
@@ -1,20 +1,25 @@
-use std::env;
 use std::fmt;
 
 use crate::error::{ErrorKind, Fallible};
+use crate::inventory::{node_available, npm_available, pnpm_available, yarn_available};
 use crate::session::Session;
+use crate::style::tool_version;
 use crate::tool::{Node, Npm, Pnpm, Yarn};
-use crate::VOLTA_FEATURE_PNPM;
+use log::debug;
 use node_semver::Version;
 
+mod checkout;
 mod image;
+mod path;
 mod system;
 // Note: The tests get their own module because we need them to run as a single unit to prevent
 // clobbering environment variable changes
 #[cfg(test)]
 mod tests;
 
-pub use image::Image;
+use checkout::CheckoutReport;
+pub use checkout::OptionalTool;
+pub use image::{Image, Unfetched};
 pub use system::System;
 
 /// The source with which a version is associated
@@ -271,38 +276,132 @@ impl Platform {
         }
     }
 
+    /// Converts a `Platform` into an `Image` without fetching any of its tools
+    ///
+    /// The tools may not be available yet, so this is only suitable for looking up where they are,
+    /// e.g. with [`Image::unfetched`]. Use [`Platform::checkout`] to run them.
+    pub fn into_image(self) -> Image {
+        Image {
+            node: self.node,
+            npm: self.npm,
+            pnpm: self.pnpm,
+            yarn: self.yarn,
+        }
+    }
+
     /// Check out a `Platform` into a fully-realized `Image`
     ///
-    /// This will ensure that all necessary tools are fetched and available for execution
+    /// This will ensure that all necessary tools are fetched and available for execution, stopping
+    /// at the first tool that fails to fetch. When automatic installs are disabled, missing tools
+    /// are an error instead of being fetched.
     pub fn checkout(self, session: &mut Session) -> Fallible<Image> {
-        Node::new(self.node.value.clone()).ensure_fetched(session)?;
-
-        if let Some(Sourced { value: version, .. }) = &self.npm {
-            Npm::new(version.clone()).ensure_fetched(session)?;
-        }
+        let mut report = if session.may_fetch()? {
+            CheckoutReport::default()
+        } else {
+            CheckoutReport::manual()
+        };
+
+        let node = &self.node.value;
+        check_once(
+            &mut report,
+            session,
+            "node",
+            node,
+            false,
+            node_available,
+            |session| Node::new(node.clone()).ensure_fetched(session).map(drop),
+        );
+        report.stop_on_failure()?;
+
+        let npm = self.npm.filter(|Sourced { value: version, .. }| {
+            let allow_missing = session.may_continue_without(OptionalTool::Npm);
+            check_once(
+                &mut report,
+                session,
+                "npm",
+                version,
+                allow_missing,
+                npm_available,
+                |session| Npm::new(version.clone()).ensure_fetched(session),
+            )
+        });
+        report.stop_on_failure()?;
 
         // Only force download of the pnpm version if the pnpm feature flag is set. If it isn't,
         // then we won't be using the `Pnpm` tool to execute (we will be relying on the global
         // package logic), so fetching the Pnpm version would only be redundant work.
-        if env::var_os(VOLTA_FEATURE_PNPM).is_some() {
-            if let Some(Sourced { value: version, .. }) = &self.pnpm {
-                Pnpm::new(version.clone()).ensure_fetched(session)?;
-            }
-        }
-
-        if let Some(Sourced { value: version, .. }) = &self.yarn {
-            Yarn::new(version.clone()).ensure_fetched(session)?;
-        }
+        let pnpm = if session.pnpm_enabled()? {
+            self.pnpm.filter(|Sourced { value: version, .. }| {
+                let allow_missing = session.may_continue_without(OptionalTool::Pnpm);
+                check_once(
+                    &mut report,
+                    session,
+                    "pnpm",
+                    version,
+                    allow_missing,
+                    pnpm_available,
+                    |session| Pnpm::new(version.clone()).ensure_fetched(session),
+                )
+            })
+        } else {
+            self.pnpm
+        };
+        report.stop_on_failure()?;
+
+        let yarn = self.yarn.filter(|Sourced { value: version, .. }| {
+            let allow_missing = session.may_continue_without(OptionalTool::Yarn);
+            check_once(
+                &mut report,
+                session,
+                "yarn",
+                version,
+                allow_missing,
+                yarn_available,
+                |session| Yarn::new(version.clone()).ensure_fetched(session),
+            )
+        });
+
+        report.finish()?;
 
         Ok(Image {
             node: self.node,
-            npm: self.npm,
-            pnpm: self.pnpm,
-            yarn: self.yarn,
+            npm,
+            pnpm,
+            yarn,
         })
     }
 }
 
+/// Checks that a tool version is available, unless an earlier checkout in this invocation already
+/// did, such as for another package of `volta install` that runs on the same platform
+fn check_once<A, F>(
+    report: &mut CheckoutReport,
+    session: &mut Session,
+    name: &'static str,
+    version: &Version,
+    allow_missing: bool,
+    available: A,
+    fetch: F,
+) -> bool
+where
+    A: FnOnce(&Version) -> Fallible<bool>,
+    F: FnOnce(&mut Session) -> Fallible<()>,
+{
+    if session.is_checked_out(name, version) {
+        debug!(
+            "{} was already checked out in this invocation",
+            tool_version(name, version)
+        );
+        return report.check(name, version, allow_missing, |_| Ok(true), || Ok(()));
+    }
+
+    let is_available = report.check(name, version, allow_missing, available, || fetch(session));
+    if is_available {
+        session.record_checkout(name, version);
+    }
+    is_available
+}
+
 fn build_path_error() -> ErrorKind {
     ErrorKind::BuildPathError
 }
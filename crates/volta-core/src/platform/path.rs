@@ -0,0 +1,129 @@
+//! Joins the `PATH` that Volta gives to the tools it runs
+//!
+//! Prefixing the image directories can push a long `PATH` over the length that the OS allows for
+//! an environment variable, which makes starting the tool fail with an error that doesn't mention
+//! `PATH` at all. Repeated entries are dropped first, since they're common in long values, and a
+//! `PATH` that is still too long is reported along with the directories that Volta added.
+
+use std::collections::HashSet;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
+
+use super::build_path_error;
+use crate::error::{Context, ErrorKind, Fallible};
+use log::debug;
+
+/// The longest value that Windows allows for an environment variable, without the trailing NUL
+#[cfg(windows)]
+const MAX_PATH_LENGTH: Option<usize> = Some(32_767 - 1);
+
+/// The longest string that Linux allows in the environment of a new process (`MAX_ARG_STRLEN`),
+/// which holds `PATH=` and a trailing NUL along with the value
+#[cfg(target_os = "linux")]
+const MAX_PATH_LENGTH: Option<usize> = Some(131_072 - "PATH=".len() - 1);
+
+/// Other platforms only limit the size of the whole environment
+#[cfg(not(any(windows, target_os = "linux")))]
+const MAX_PATH_LENGTH: Option<usize> = None;
+
+/// Joins the entries of a `PATH`, checking the result against the platform's limit
+///
+/// `added` holds the directories that Volta added, to point them out if the `PATH` is too long.
+pub(super) fn join_path<I>(entries: I, added: &[PathBuf]) -> Fallible<OsString>
+where
+    I: IntoIterator<Item = PathBuf>,
+{
+    join_path_within(entries, added, MAX_PATH_LENGTH)
+}
+
+fn join_path_within<I>(entries: I, added: &[PathBuf], limit: Option<usize>) -> Fallible<OsString>
+where
+    I: IntoIterator<Item = PathBuf>,
+{
+    let path = env::join_paths(dedup_paths(entries)).with_context(build_path_error)?;
+    let length = path_length(&path);
+    debug!("Built a PATH of {} characters", length);
+
+    match limit {
+        Some(limit) if length > limit => Err(ErrorKind::PathTooLong {
+            length,
+            limit,
+            added: added.to_vec(),
+        }
+        .into()),
+        _ => Ok(path),
+    }
+}
+
+/// Drops the entries that are identical to an earlier one, which never take effect
+fn dedup_paths<I>(entries: I) -> Vec<PathBuf>
+where
+    I: IntoIterator<Item = PathBuf>,
+{
+    let mut seen = HashSet::new();
+    entries
+        .into_iter()
+        .filter(|entry| seen.insert(entry.clone()))
+        .collect()
+}
+
+/// The length of a `PATH` in the units that the platform's limit uses
+#[cfg(windows)]
+fn path_length(path: &OsStr) -> usize {
+    use std::os::windows::ffi::OsStrExt;
+    path.encode_wide().count()
+}
+
+#[cfg(unix)]
+fn path_length(path: &OsStr) -> usize {
+    path.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(paths: &[&str]) -> Vec<PathBuf> {
+        paths.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn dedup_keeps_the_first_of_each_entry() {
+        assert_eq!(
+            dedup_paths(entries(&["/a", "/b", "/a", "/c", "/b", "/a"])),
+            entries(&["/a", "/b", "/c"])
+        );
+        assert_eq!(dedup_paths(entries(&[])), entries(&[]));
+    }
+
+    #[test]
+    fn duplicates_do_not_count_against_the_limit() {
+        let long = "x".repeat(100);
+        let path = join_path_within(vec![PathBuf::from(&long); 50], &[], Some(100)).unwrap();
+        assert_eq!(path, OsString::from(&long));
+    }
+
+    #[test]
+    fn reports_a_path_over_the_limit() {
+        let added = entries(&["/volta/tools/image/node/20.11.1/bin"]);
+        let others = (0..1000).map(|index| PathBuf::from(format!("/opt/tool-{:04}/bin", index)));
+        let all: Vec<PathBuf> = added.iter().cloned().chain(others).collect();
+
+        let error = join_path_within(all.clone(), &added, Some(1024)).unwrap_err();
+        let message = error.to_string();
+        assert!(
+            message.contains("more than the limit of 1024"),
+            "{}",
+            message
+        );
+        assert!(
+            message.contains("/volta/tools/image/node/20.11.1/bin"),
+            "{}",
+            message
+        );
+
+        assert!(join_path_within(all.clone(), &added, Some(1_000_000)).is_ok());
+        assert!(join_path_within(all, &added, None).is_ok());
+    }
+}
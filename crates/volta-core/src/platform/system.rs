@@ -1,7 +1,7 @@
 use std::ffi::OsString;
 
-use super::build_path_error;
-use crate::error::{Context, Fallible};
+use super::path::join_path;
+use crate::error::Fallible;
 use crate::layout::env_paths;
 
 /// A lightweight namespace type representing the system environment, i.e. the environment
@@ -11,7 +11,7 @@ pub struct System;
 impl System {
     /// Produces a modified version of the current `PATH` environment variable that
     /// removes the Volta shims and binaries, to use for running system node and
-    /// executables.
+    /// executables. Repeated entries are dropped.
     pub fn path() -> Fallible<OsString> {
         let old_path = envoy::path().unwrap_or_else(|| envoy::Var::from(""));
         let mut new_path = old_path.split();
@@ -20,6 +20,6 @@ impl System {
             new_path = new_path.remove(remove_path);
         }
 
-        new_path.join().with_context(build_path_error)
+        join_path(new_path, &[])
     }
 }
@@ -12,6 +12,10 @@ use std::path::PathBuf;
 fn test_paths() {
     test_image_path();
     test_system_path();
+    #[cfg(unix)]
+    test_image_path_without_shims();
+    #[cfg(unix)]
+    test_pinned_path();
 }
 
 #[cfg(unix)]
@@ -146,6 +150,54 @@ fn test_image_path() {
     );
 }
 
+#[cfg(unix)]
+fn test_image_path_without_shims() {
+    std::env::set_var("PATH", "/usr/bin:/bin");
+
+    let image = Image {
+        node: Sourced::with_default(Version::parse("1.2.3").unwrap()),
+        npm: None,
+        pnpm: None,
+        yarn: None,
+    };
+    let node_bin = volta_home().unwrap().node_image_bin_dir("1.2.3");
+    let shim_dir = volta_home().unwrap().shim_dir().to_owned();
+
+    assert_eq!(
+        image.path().unwrap().into_string().unwrap(),
+        format!(
+            "{}:/usr/bin:/bin:{}",
+            node_bin.display(),
+            shim_dir.display()
+        )
+    );
+}
+
+#[cfg(unix)]
+fn test_pinned_path() {
+    std::env::set_var("PATH", build_test_path());
+
+    let image = Image {
+        node: Sourced::with_default(Version::parse("1.2.3").unwrap()),
+        npm: None,
+        pnpm: None,
+        yarn: Some(Sourced::with_default(Version::parse("4.5.7").unwrap())),
+    };
+    let node_bin = volta_home().unwrap().node_image_bin_dir("1.2.3");
+    let yarn_bin = volta_home().unwrap().yarn_image_bin_dir("4.5.7");
+    let shim_dir = volta_home().unwrap().shim_dir().to_owned();
+
+    assert_eq!(
+        image.pinned_path().unwrap().into_string().unwrap(),
+        format!(
+            "{}:{}:{}:/usr/bin:/bin",
+            yarn_bin.display(),
+            node_bin.display(),
+            shim_dir.display()
+        )
+    );
+}
+
 fn test_system_path() {
     let path = build_test_path();
     std::env::set_var("PATH", path);
@@ -0,0 +1,319 @@
+//! Provides types for working with version policies, which restrict the versions of a tool that
+//! may be installed or pinned (as in an organization that requires `node@>=18.17`).
+//!
+//! Policies are defined in the `policies` section of the settings file and of the hooks files.
+//! Unlike aliases, every policy applies, so the hooks of a project can add restrictions to the
+//! ones in the user's hooks and settings files but can never lift them.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::{ErrorKind, Fallible};
+use crate::version::{parse_requirements, parse_version};
+use log::warn;
+use node_semver::{Range, Version};
+use serde::{Deserialize, Serialize};
+
+/// The tools that policies may be defined for
+const POLICY_TOOLS: [&str; 4] = ["node", "npm", "pnpm", "yarn"];
+
+/// The `policies` section of a settings or hooks file, mapping each tool to its policy
+pub(crate) type RawPolicies = BTreeMap<String, RawPolicy>;
+
+/// The policy for a single tool, as written in a settings or hooks file
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+pub(crate) struct RawPolicy {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    allowed: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    blocked: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    overridable: Option<bool>,
+}
+
+/// A restriction on the versions of a tool
+#[derive(Clone, Debug)]
+pub struct Policy {
+    /// The tool the policy applies to, e.g. `node`
+    pub tool: String,
+    /// The range that versions must satisfy, as written in the file
+    pub allowed: Option<String>,
+    allowed_range: Option<Range>,
+    /// The versions that may not be used, even if they satisfy the allowed range
+    pub blocked: Vec<Version>,
+    /// Whether the policy can be ignored with `--override-policy`
+    pub overridable: bool,
+    /// The file that defines the policy
+    pub source: PathBuf,
+}
+
+impl Policy {
+    /// The rule of the policy that a version breaks, if any
+    fn violation(&self, version: &Version) -> Option<String> {
+        if self.blocked.contains(version) {
+            return Some(format!("blocked: {}", version));
+        }
+
+        match (&self.allowed, &self.allowed_range) {
+            (Some(allowed), Some(range)) if !range.satisfies(version) => {
+                Some(format!("allowed: {}", allowed))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Reads the policies defined in a file, checking that each rule is a valid range or version
+pub(crate) fn from_raw(raw: RawPolicies, file: &Path) -> Fallible<Vec<Policy>> {
+    let invalid = |tool: &str, reason: String| ErrorKind::InvalidVersionPolicy {
+        tool: tool.into(),
+        reason,
+        file: file.to_owned(),
+    };
+
+    let mut policies = Vec::new();
+    for (tool, policy) in raw {
+        if !POLICY_TOOLS.contains(&tool.as_str()) {
+            return Err(invalid(
+                &tool,
+                format!(
+                    "Policies can only be defined for {}.",
+                    POLICY_TOOLS.join(", ")
+                ),
+            )
+            .into());
+        }
+
+        let allowed_range = policy
+            .allowed
+            .as_ref()
+            .map(|allowed| {
+                parse_requirements(allowed).map_err(|_| {
+                    invalid(
+                        &tool,
+                        format!("`allowed` must be a range of versions, not '{}'.", allowed),
+                    )
+                })
+            })
+            .transpose()?;
+
+        let blocked = policy
+            .blocked
+            .iter()
+            .map(|version| {
+                parse_version(version).map_err(|_| {
+                    invalid(
+                        &tool,
+                        format!("`blocked` must list exact versions, not '{}'.", version),
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        policies.push(Policy {
+            tool,
+            allowed: policy.allowed,
+            allowed_range,
+            blocked,
+            overridable: policy.overridable.unwrap_or(true),
+            source: file.to_owned(),
+        });
+    }
+
+    Ok(policies)
+}
+
+/// The version policies that apply in the current directory
+#[derive(Debug, Default)]
+pub struct Policies {
+    policies: Vec<Policy>,
+}
+
+impl Policies {
+    /// Combines the policies defined in several files
+    ///
+    /// Every policy applies, so the order of `layers` only decides which violation is reported
+    /// when a version breaks more than one of them.
+    pub(crate) fn from_layers<I>(layers: I) -> Self
+    where
+        I: IntoIterator<Item = Vec<Policy>>,
+    {
+        Policies {
+            policies: layers.into_iter().flatten().collect(),
+        }
+    }
+
+    /// All of the policies, in the order of the files that define them
+    pub fn all(&self) -> &[Policy] {
+        &self.policies
+    }
+
+    /// The policies defined for a single tool
+    pub fn for_tool<'a>(&'a self, tool: &'a str) -> impl Iterator<Item = &'a Policy> {
+        self.policies
+            .iter()
+            .filter(move |policy| policy.tool == tool)
+    }
+
+    /// Checks that a version of a tool is allowed by every policy for it
+    ///
+    /// With `override_policy`, the policies that are overridable only produce a warning. A policy
+    /// with `overridable: false` is reported first, since overriding wouldn't get past it.
+    pub(crate) fn check(
+        &self,
+        tool: &str,
+        version: &Version,
+        override_policy: bool,
+    ) -> Fallible<()> {
+        let violations: Vec<(&Policy, String)> = self
+            .for_tool(tool)
+            .filter_map(|policy| policy.violation(version).map(|rule| (policy, rule)))
+            .collect();
+
+        let reported = violations
+            .iter()
+            .find(|(policy, _)| !policy.overridable)
+            .or_else(|| violations.first().filter(|_| !override_policy));
+
+        if let Some((policy, rule)) = reported {
+            return Err(ErrorKind::PolicyViolation {
+                tool: tool.into(),
+                version: version.to_string(),
+                rule: rule.clone(),
+                file: policy.source.clone(),
+                overridable: policy.overridable,
+            }
+            .into());
+        }
+
+        for (policy, rule) in violations {
+            warn!(
+                "Ignoring the version policy in {} for {}@{} ({})",
+                policy.source.display(),
+                tool,
+                version,
+                rule
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str, file: &Path) -> Fallible<Vec<Policy>> {
+        from_raw(serde_json::from_str(json).unwrap(), file)
+    }
+
+    fn hooks_file() -> PathBuf {
+        PathBuf::from("/home/user/.volta/hooks.json")
+    }
+
+    fn project_file() -> PathBuf {
+        PathBuf::from("/home/user/app/.volta/hooks.json")
+    }
+
+    fn version(version: &str) -> Version {
+        version.parse().unwrap()
+    }
+
+    fn violated_rule(policies: &Policies, tool: &str, v: &str) -> String {
+        policies
+            .check(tool, &version(v), false)
+            .unwrap_err()
+            .to_string()
+            .lines()
+            .find_map(|line| line.strip_prefix("Rule: "))
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn evaluates_allowed_ranges_and_blocked_versions() {
+        let policies = Policies::from_layers([parse(
+            r#"{
+                "node": { "allowed": ">=18.17" },
+                "yarn": { "blocked": ["1.22.0", "v3.6.2"] }
+            }"#,
+            &hooks_file(),
+        )
+        .unwrap()]);
+
+        assert!(policies.check("node", &version("18.17.0"), false).is_ok());
+        assert!(policies.check("node", &version("20.11.1"), false).is_ok());
+        assert_eq!(
+            violated_rule(&policies, "node", "18.16.1"),
+            "allowed: >=18.17"
+        );
+
+        assert!(policies.check("yarn", &version("1.22.19"), false).is_ok());
+        assert_eq!(violated_rule(&policies, "yarn", "3.6.2"), "blocked: 3.6.2");
+
+        // Tools without a policy are unrestricted
+        assert!(policies.check("npm", &version("1.0.0"), false).is_ok());
+    }
+
+    #[test]
+    fn project_policies_only_tighten() {
+        let org = parse(
+            r#"{ "node": { "allowed": ">=18.17", "overridable": false } }"#,
+            &hooks_file(),
+        )
+        .unwrap();
+        let project = parse(
+            r#"{ "node": { "allowed": ">=16", "blocked": ["20.0.0"], "overridable": true } }"#,
+            &project_file(),
+        )
+        .unwrap();
+        let policies = Policies::from_layers([project, org]);
+
+        // The wider range of the project doesn't loosen the policy from the hooks file
+        let error = policies
+            .check("node", &version("16.20.2"), true)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(&hooks_file().display().to_string()));
+        assert!(error.contains("This policy can't be overridden."));
+
+        // But the project can block versions that the hooks file allows
+        let error = policies
+            .check("node", &version("20.0.0"), false)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(&project_file().display().to_string()));
+        assert!(error.contains("Rule: blocked: 20.0.0"));
+        assert!(policies.check("node", &version("20.1.0"), false).is_ok());
+    }
+
+    #[test]
+    fn overrides_are_gated_by_the_policy() {
+        let overridable = Policies::from_layers([parse(
+            r#"{ "npm": { "blocked": ["10.4.0"] } }"#,
+            &hooks_file(),
+        )
+        .unwrap()]);
+        let error = overridable
+            .check("npm", &version("10.4.0"), false)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("--override-policy"));
+        assert!(overridable.check("npm", &version("10.4.0"), true).is_ok());
+
+        let strict = Policies::from_layers([parse(
+            r#"{ "npm": { "blocked": ["10.4.0"], "overridable": false } }"#,
+            &hooks_file(),
+        )
+        .unwrap()]);
+        assert!(strict.check("npm", &version("10.4.0"), true).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_policies() {
+        assert!(parse(r#"{ "node": { "allowed": "not a range" } }"#, &hooks_file()).is_err());
+        assert!(parse(r#"{ "yarn": { "blocked": ["^1"] } }"#, &hooks_file()).is_err());
+        assert!(parse(r#"{ "cowsay": { "allowed": ">=1" } }"#, &hooks_file()).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+//! Provides named platform profiles, which are platforms saved under a name in the Volta home
+//! (as in `legacy` for Node 14 with Yarn 1), so that `volta run --profile <name>` can use them.
+//!
+//! Each profile is a file in the `profiles` directory with the same format as the default
+//! platform file, so a platform file can be copied there to turn it into a profile.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::{read_file, remove_file_if_exists};
+use crate::layout::volta_home;
+use crate::platform::PlatformSpec;
+use crate::toolchain::serial::Platform;
+use log::debug;
+use tempfile::NamedTempFile;
+
+/// Checks that a profile name can be used as a file name in the profiles directory
+fn check_name(name: &str) -> Fallible<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ErrorKind::InvalidPlatformProfileName { name: name.into() }.into())
+    }
+}
+
+/// Lists the names of the saved profiles, in alphabetical order
+pub fn list() -> Fallible<Vec<String>> {
+    let dir = volta_home()?.profiles_dir();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(error).with_context(|| ErrorKind::ReadDirError {
+                dir: dir.to_owned(),
+            })
+        }
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension()? != "json" {
+                return None;
+            }
+            let name = path.file_stem()?.to_str()?.to_owned();
+            check_name(&name).ok().map(|()| name)
+        })
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Reads the platform saved in a profile
+pub fn load(name: &str) -> Fallible<PlatformSpec> {
+    check_name(name)?;
+    let file = volta_home()?.profile_file(name);
+
+    let contents = read_file(&file)
+        .with_context(|| ErrorKind::ReadPlatformProfileError { file: file.clone() })?;
+    let Some(contents) = contents else {
+        return Err(ErrorKind::PlatformProfileNotFound {
+            name: name.into(),
+            available: list()?,
+        }
+        .into());
+    };
+
+    debug!("Using platform profile '{}' from {}", name, file.display());
+    parse(&contents, &file)
+}
+
+fn parse(contents: &str, file: &Path) -> Fallible<PlatformSpec> {
+    let invalid = |error: String| ErrorKind::ParsePlatformProfileError {
+        file: file.to_owned(),
+        error,
+    };
+
+    let platform: Platform =
+        serde_json::from_str(contents).map_err(|error| invalid(error.to_string()))?;
+    Option::<PlatformSpec>::from(platform)
+        .ok_or_else(|| invalid("The profile doesn't set `node.runtime`.".into()).into())
+}
+
+/// Saves a platform as a profile, replacing any profile with the same name
+///
+/// Returns whether a profile was replaced.
+pub fn save(name: &str, platform: &PlatformSpec) -> Fallible<bool> {
+    check_name(name)?;
+    let file = volta_home()?.profile_file(name);
+    let replaced = file.exists();
+
+    let src = Platform::of(platform).into_json()?;
+    write_profile(&file, &src)
+        .with_context(|| ErrorKind::WritePlatformProfileError { file: file.clone() })?;
+
+    Ok(replaced)
+}
+
+/// Writes a profile atomically, so that a concurrent `volta run` never reads a partial file
+fn write_profile(file: &Path, src: &str) -> io::Result<()> {
+    let dir = file.parent().unwrap_or_else(|| Path::new("."));
+    fs::create_dir_all(dir)?;
+    let mut staging = NamedTempFile::new_in(dir)?;
+    staging.write_all(src.as_bytes())?;
+    staging.persist(file).map_err(|error| error.error)?;
+    Ok(())
+}
+
+/// Deletes a profile, returning the file that held it
+pub fn delete(name: &str) -> Fallible<PathBuf> {
+    check_name(name)?;
+    let file = volta_home()?.profile_file(name);
+
+    if !file.exists() {
+        return Err(ErrorKind::PlatformProfileNotFound {
+            name: name.into(),
+            available: list()?,
+        }
+        .into());
+    }
+
+    remove_file_if_exists(&file)?;
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file() -> PathBuf {
+        PathBuf::from("/home/user/.volta/profiles/legacy.json")
+    }
+
+    #[test]
+    fn checks_names() {
+        for name in ["legacy", "node-20", "team_a.v2"] {
+            assert!(check_name(name).is_ok(), "{}", name);
+        }
+        for name in ["", ".hidden", "../escape", "a/b", "with space"] {
+            assert!(check_name(name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn parses_platform_files() {
+        let platform = parse(
+            r#"{"node":{"runtime":"14.21.3","npm":null},"yarn":"1.22.19"}"#,
+            &file(),
+        )
+        .unwrap();
+
+        assert_eq!(platform.node.to_string(), "14.21.3");
+        assert_eq!(platform.npm, None);
+        assert_eq!(platform.pnpm, None);
+        assert_eq!(platform.yarn.unwrap().to_string(), "1.22.19");
+    }
+
+    #[test]
+    fn reports_where_a_profile_is_malformed() {
+        let error = parse(r#"{"node":{"runtime":14}}"#, &file())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("legacy.json"), "{}", error);
+        assert!(error.contains("invalid type: integer `14`"), "{}", error);
+
+        let error = parse(r#"{"yarn":"1.22.19"}"#, &file())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("doesn't set `node.runtime`"), "{}", error);
+    }
+}
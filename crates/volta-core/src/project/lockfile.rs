@@ -0,0 +1,184 @@
+//! Infers a compatible package manager version from the lockfile in a project
+//!
+//! Yarn and pnpm record the version of their lockfile format, which changes with their major
+//! releases, so the lockfile identifies which versions of the package manager can use it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::tool::package::PackageManager;
+use crate::tool::Spec;
+
+/// The lockfiles that Volta recognizes, and the package manager that writes each of them
+const LOCKFILES: [(&str, PackageManager); 3] = [
+    ("yarn.lock", PackageManager::Yarn),
+    ("pnpm-lock.yaml", PackageManager::Pnpm),
+    ("package-lock.json", PackageManager::Npm),
+];
+
+/// The versions of Yarn that write each lockfile version
+///
+/// Yarn 1 doesn't record a version in `__metadata`, so its lockfiles are recorded as version `1`.
+pub(super) const YARN_LOCKFILE_VERSIONS: [(&str, &str); 5] = [
+    ("1", "^1"),
+    ("4", "^2"),
+    ("5", ">=3.0.0 <3.2.0"),
+    ("6", ">=3.2.0 <4.0.0"),
+    ("8", "^4"),
+];
+
+/// The versions of pnpm that write each lockfile version
+pub(super) const PNPM_LOCKFILE_VERSIONS: [(&str, &str); 4] =
+    [("5.3", "^6"), ("5.4", "^7"), ("6.0", "^8"), ("9.0", ">=9")];
+
+/// A range of package manager versions that can use a project's lockfile
+#[derive(Debug, PartialEq, Eq)]
+pub struct InferredPackageManager {
+    pub manager: PackageManager,
+    /// The version range that writes the lockfile, as a semver requirement
+    pub range: &'static str,
+    pub lockfile: PathBuf,
+    pub lockfile_version: String,
+}
+
+impl InferredPackageManager {
+    /// The spec to resolve and pin, which selects the newest version in the range
+    pub fn spec(&self) -> Fallible<Spec> {
+        let version = self.range.parse()?;
+        Ok(match self.manager {
+            PackageManager::Npm => Spec::Npm(version),
+            PackageManager::Pnpm => Spec::Pnpm(version),
+            PackageManager::Yarn => Spec::Yarn(version),
+        })
+    }
+}
+
+/// Infers the package manager version range from the single lockfile in the given directory
+pub(super) fn infer_package_manager(directory: &Path) -> Fallible<InferredPackageManager> {
+    let found: Vec<_> = LOCKFILES
+        .iter()
+        .filter(|(name, _)| directory.join(name).is_file())
+        .collect();
+
+    let (name, manager) = match found.as_slice() {
+        [] => {
+            return Err(ErrorKind::NoLockfile {
+                directory: directory.to_owned(),
+            }
+            .into())
+        }
+        [lockfile] => **lockfile,
+        _ => {
+            return Err(ErrorKind::MultipleLockfiles {
+                directory: directory.to_owned(),
+                lockfiles: found.iter().map(|(name, _)| (*name).to_owned()).collect(),
+            }
+            .into())
+        }
+    };
+
+    let lockfile = directory.join(name);
+    let not_inferred = |reason: String| ErrorKind::LockfileNotInferred {
+        lockfile: lockfile.clone(),
+        reason,
+    };
+
+    let (lockfile_version, table) = match manager {
+        PackageManager::Npm => {
+            return Err(not_inferred(
+                "npm is bundled with Node, so its version follows the pinned Node version".into(),
+            )
+            .into())
+        }
+        PackageManager::Pnpm => (
+            parse_pnpm_lockfile_version(&read_lockfile(&lockfile)?),
+            &PNPM_LOCKFILE_VERSIONS[..],
+        ),
+        PackageManager::Yarn => (
+            parse_yarn_lockfile_version(&read_lockfile(&lockfile)?),
+            &YARN_LOCKFILE_VERSIONS[..],
+        ),
+    };
+
+    let lockfile_version = lockfile_version
+        .ok_or_else(|| not_inferred("it does not record a lockfile version".into()))?;
+    let range = compatible_range(table, &lockfile_version).ok_or_else(|| {
+        not_inferred(format!(
+            "Volta doesn't know which {} versions write lockfile version {}",
+            manager_name(manager),
+            lockfile_version
+        ))
+    })?;
+
+    Ok(InferredPackageManager {
+        manager,
+        range,
+        lockfile,
+        lockfile_version,
+    })
+}
+
+fn read_lockfile(lockfile: &Path) -> Fallible<String> {
+    fs::read_to_string(lockfile).with_context(|| ErrorKind::ReadLockfileError {
+        file: lockfile.to_owned(),
+    })
+}
+
+/// Looks up the version range for a lockfile version in a table
+pub(super) fn compatible_range(
+    table: &[(&str, &'static str)],
+    lockfile_version: &str,
+) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(version, _)| *version == lockfile_version)
+        .map(|(_, range)| *range)
+}
+
+/// Parses the lockfile version from a `yarn.lock`
+///
+/// Yarn 2 and later record it under `__metadata`, while Yarn 1 only marks the file in a comment.
+pub(super) fn parse_yarn_lockfile_version(contents: &str) -> Option<String> {
+    if contents
+        .lines()
+        .take_while(|line| line.is_empty() || line.starts_with('#'))
+        .any(|line| line.trim() == "# yarn lockfile v1")
+    {
+        return Some("1".into());
+    }
+
+    let mut lines = contents.lines().skip_while(|line| *line != "__metadata:");
+    lines.next()?;
+    lines
+        .take_while(|line| line.starts_with(' '))
+        .find_map(|line| line.trim().strip_prefix("version:"))
+        .map(|version| unquote(version).to_owned())
+}
+
+/// Parses the top-level `lockfileVersion` from a `pnpm-lock.yaml`
+pub(super) fn parse_pnpm_lockfile_version(contents: &str) -> Option<String> {
+    let version = contents
+        .lines()
+        .find_map(|line| line.strip_prefix("lockfileVersion:"))
+        .map(unquote)?;
+
+    // The version may be written as a whole number, e.g. `9` for `9.0`
+    Some(if version.contains('.') {
+        version.to_owned()
+    } else {
+        format!("{}.0", version)
+    })
+}
+
+fn manager_name(manager: PackageManager) -> &'static str {
+    match manager {
+        PackageManager::Npm => "npm",
+        PackageManager::Pnpm => "pnpm",
+        PackageManager::Yarn => "Yarn",
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim().trim_matches(|c| c == '\'' || c == '"')
+}
@@ -1,26 +1,51 @@
 //! Provides the `Project` type, which represents a Node project tree in
 //! the filesystem.
 
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::ffi::OsStr;
+#[cfg(unix)]
+use std::fs;
 use std::iter::once;
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
 
 use node_semver::Version;
+use once_cell::sync::Lazy;
 use once_cell::unsync::OnceCell;
 
 use crate::error::{Context, ErrorKind, Fallible, VoltaError};
 use crate::layout::volta_home;
 use crate::platform::PlatformSpec;
+use crate::tool::node::set_arch_override;
 use crate::tool::BinConfig;
 use chain_map::ChainMap;
+use dunce::canonicalize;
 use indexmap::IndexSet;
+use log::{debug, warn};
+use walkdir::{DirEntry, WalkDir};
 
+mod lockfile;
+mod platform_lock;
 mod serial;
 #[cfg(test)]
 mod tests;
+mod tools_manifest;
+mod version_files;
+mod writer;
 
-use serial::{update_manifest, Manifest, ManifestKey};
+pub use lockfile::InferredPackageManager;
+use platform_lock::PlatformLock;
+pub use platform_lock::{set_frozen, PlatformLockStatus};
+use serial::{Manifest, ManifestKey, BUNDLED_NPM};
+pub use tools_manifest::ToolsManifest;
+pub use version_files::{FoundVersion, InferredTool};
+use writer::update_manifest;
+
+const VOLTA_PROJECT_CEILING: &str = "VOLTA_PROJECT_CEILING";
+
+/// The directory in `node_modules` where pnpm keeps the packages that it links into the project
+const PNPM_VIRTUAL_STORE: &str = ".pnpm";
 
 /// A lazily loaded Project
 pub struct LazyProject {
@@ -52,13 +77,33 @@ pub struct Project {
     workspace_manifests: IndexSet<PathBuf>,
     dependencies: ChainMap<String, String>,
     platform: Option<PlatformSpec>,
+    bundled_npm: bool,
+    /// The `volta.engineStrict` setting, if any manifest in the `extends` chain has one
+    engine_strict: Option<bool>,
+    /// The `volta.arch` setting, if any manifest in the `extends` chain has one
+    node_arch: Option<String>,
+    /// The platform recorded in the `volta.lock` file, which takes precedence over the manifest
+    ///
+    /// It is only read the first time the platform is needed.
+    platform_lock: OnceCell<Option<PlatformLock>>,
+    /// Pins that haven't been written to the manifest file yet, in the order they were made
+    unsaved_pins: Vec<(ManifestKey, Option<String>)>,
+    /// Whether any tool was pinned by this process, in which case the pinned platform is used
+    /// instead of the one in the lock file
+    pinned: bool,
 }
 
 impl Project {
     /// Creates an optional Project instance from the current directory
+    ///
+    /// The project's `volta.arch`, if any, applies to the Node builds for the rest of the process.
     fn for_current_dir() -> Fallible<Option<Self>> {
         let current_dir = env::current_dir().with_context(|| ErrorKind::CurrentDirError)?;
-        Self::for_dir(current_dir)
+        let project = Self::for_dir(current_dir)?;
+        if let Some(arch) = project.as_ref().and_then(Project::node_arch) {
+            set_arch_override(arch)?;
+        }
+        Ok(project)
     }
 
     /// Creates an optional Project instance from the specified directory
@@ -81,6 +126,8 @@ impl Project {
         let mut workspace_manifests = IndexSet::new();
         let mut platform = manifest.platform;
         let mut extends = manifest.extends;
+        let mut engine_strict = manifest.engine_strict;
+        let mut node_arch = manifest.arch;
 
         // Iterate the `volta.extends` chain, parsing each file in turn
         while let Some(path) = extends {
@@ -99,6 +146,8 @@ impl Project {
             let manifest = Manifest::from_file(&path)?;
             workspace_manifests.insert(path);
             dependencies.extend(manifest.dependency_maps);
+            engine_strict = engine_strict.or(manifest.engine_strict);
+            node_arch = node_arch.or(manifest.arch);
 
             platform = match (platform, manifest.platform) {
                 (Some(base), Some(ext)) => Some(base.merge(ext)),
@@ -109,6 +158,7 @@ impl Project {
             extends = manifest.extends;
         }
 
+        let bundled_npm = platform.as_ref().map_or(false, |p| p.bundled_npm);
         let platform = platform.map(TryInto::try_into).transpose()?;
 
         Ok(Project {
@@ -116,6 +166,12 @@ impl Project {
             workspace_manifests,
             dependencies,
             platform,
+            bundled_npm,
+            engine_strict,
+            node_arch,
+            platform_lock: OnceCell::new(),
+            unsaved_pins: Vec::new(),
+            pinned: false,
         })
     }
 
@@ -124,6 +180,25 @@ impl Project {
         &self.manifest_file
     }
 
+    /// Infers a package manager version range from the lockfile next to the project manifest
+    pub fn infer_package_manager(&self) -> Fallible<InferredPackageManager> {
+        let root = self
+            .manifest_file
+            .parent()
+            .expect("File paths always have a parent");
+        lockfile::infer_package_manager(root)
+    }
+
+    /// Infers the Node and npm version requirements from the version files and `engines` next to
+    /// the project manifest
+    pub fn infer_tools(&self) -> Vec<InferredTool> {
+        let root = self
+            .manifest_file
+            .parent()
+            .expect("File paths always have a parent");
+        version_files::infer_tools(root)
+    }
+
     /// Returns an iterator of paths to all of the workspace roots
     pub fn workspace_roots(&self) -> impl Iterator<Item = &Path> {
         // Invariant: self.manifest_file and self.extensions will only contain paths to files that we successfully loaded
@@ -132,9 +207,151 @@ impl Project {
             .map(|file| file.parent().expect("File paths always have a parent"))
     }
 
+    /// Finds the `.volta/tools.json` file of the nearest workspace root that has one
+    pub fn tools_manifest(&self) -> Option<PathBuf> {
+        ToolsManifest::find(self.workspace_roots())
+    }
+
+    /// Finds the workspace members whose `volta.extends` chain includes this project's manifest
+    ///
+    /// Pinning a tool in this manifest changes the platform of every one of these members. Only
+    /// the subtree of the project root is searched: `node_modules` and hidden directories are
+    /// skipped, and symlinks are not followed. Returns the member directories, sorted.
+    pub fn extending_members(&self) -> Vec<PathBuf> {
+        let root_dir = self
+            .manifest_file
+            .parent()
+            .expect("File paths always have a parent");
+        // The `extends` paths are canonicalized when they are parsed, so compare against the
+        // canonical path of this manifest
+        let root_manifest =
+            canonicalize(&self.manifest_file).unwrap_or_else(|_| self.manifest_file.clone());
+
+        let mut extends_cache = HashMap::new();
+        let mut members: Vec<PathBuf> = WalkDir::new(root_dir)
+            .min_depth(1)
+            .into_iter()
+            .filter_entry(|entry| !is_skipped_member_dir(entry))
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_file() && entry.file_name() == "package.json")
+            .filter(|entry| {
+                canonicalize(entry.path()).is_ok_and(|manifest| {
+                    manifest != root_manifest
+                        && extends_chain_reaches(manifest, &root_manifest, &mut extends_cache)
+                })
+            })
+            .filter_map(|entry| entry.path().parent().map(Path::to_owned))
+            .collect();
+
+        members.sort();
+        members
+    }
+
     /// Returns a reference to the Project's `PlatformSpec`, if available
+    ///
+    /// The platform recorded in the project's `volta.lock` file is preferred over the one in the
+    /// manifest, if the project has a lock file.
     pub fn platform(&self) -> Option<&PlatformSpec> {
-        self.platform.as_ref()
+        match self.locked_platform() {
+            Some(lock) => Some(&lock.platform),
+            None => self.platform.as_ref(),
+        }
+    }
+
+    /// Returns true if the project explicitly pins the npm bundled with Node (`"npm": "bundled"`)
+    ///
+    /// The platform treats this the same as having no npm pin.
+    pub fn pins_bundled_npm(&self) -> bool {
+        match self.locked_platform() {
+            Some(lock) => lock.bundled_npm,
+            None => self.bundled_npm,
+        }
+    }
+
+    /// Returns the platform recorded in the `volta.lock` file, unless a tool was pinned since
+    /// the project was loaded
+    ///
+    /// A lock file that can't be read is reported with a warning, and the manifest is used
+    /// instead, so that `volta lock --update` can still replace it.
+    fn locked_platform(&self) -> Option<&PlatformLock> {
+        if self.pinned {
+            return None;
+        }
+
+        self.platform_lock
+            .get_or_init(|| {
+                PlatformLock::from_file(&self.platform_lock_file()).unwrap_or_else(|error| {
+                    warn!(
+                        "{}\nUsing the Volta settings in {} instead.",
+                        error,
+                        self.manifest_file.display()
+                    );
+                    None
+                })
+            })
+            .as_ref()
+    }
+
+    /// Returns the project's `volta.engineStrict` setting, if it has one
+    ///
+    /// When it is `true`, project-local binaries whose package requires a different Node version
+    /// than the platform's aren't run. When it isn't set, they are run with a warning.
+    pub fn engine_strict(&self) -> Option<bool> {
+        self.engine_strict
+    }
+
+    /// Returns the architecture of the Node builds that the project uses, if it overrides it with
+    /// `volta.arch`
+    pub fn node_arch(&self) -> Option<&str> {
+        self.node_arch.as_deref()
+    }
+
+    /// Returns the path to the project's `volta.lock` file, whether or not it exists
+    pub fn platform_lock_file(&self) -> PathBuf {
+        platform_lock::lock_file_for(&self.manifest_file)
+    }
+
+    /// Determines whether the project's `volta.lock` file matches the `volta` settings in its
+    /// manifest, including any pins that haven't been saved yet
+    pub fn platform_lock_status(&self) -> Fallible<PlatformLockStatus> {
+        let lock = self
+            .platform_lock
+            .get_or_try_init(|| PlatformLock::from_file(&self.platform_lock_file()))?;
+
+        Ok(match lock {
+            None => PlatformLockStatus::Missing,
+            Some(lock) if lock.hash == self.settings_hash() => PlatformLockStatus::UpToDate,
+            Some(_) => PlatformLockStatus::Outdated,
+        })
+    }
+
+    /// Checks that the project's `volta.lock` file, if it has one, matches the `volta` settings
+    /// in its manifest
+    ///
+    /// An outdated lock file is reported with a warning, or is an error with `--frozen`.
+    pub fn check_platform_lock(&self) -> Fallible<()> {
+        match self.platform_lock_status()? {
+            PlatformLockStatus::Outdated => platform_lock::report_outdated(&self.manifest_file),
+            PlatformLockStatus::Missing | PlatformLockStatus::UpToDate => Ok(()),
+        }
+    }
+
+    /// Writes the platform from the `volta` settings in the manifest to the `volta.lock` file
+    pub fn write_platform_lock(&mut self) -> Fallible<()> {
+        let platform = self
+            .platform
+            .clone()
+            .ok_or(ErrorKind::NoProjectNodeInManifest)?;
+        let lock = PlatformLock::new(platform, self.bundled_npm, self.settings_hash());
+
+        lock.write(&self.platform_lock_file())?;
+        self.platform_lock = OnceCell::with_value(Some(lock));
+
+        Ok(())
+    }
+
+    fn settings_hash(&self) -> String {
+        platform_lock::settings_hash(self.platform.as_ref(), self.bundled_npm)
     }
 
     /// Returns true if the project dependency map contains the specified dependency
@@ -156,17 +373,24 @@ impl Project {
     }
 
     /// Searches the project roots to find the path to a project-local binary file
+    ///
+    /// Besides `node_modules/.bin`, this looks in the `.bin` directory of pnpm's virtual store,
+    /// which holds the binaries of the dependencies that pnpm hoists out of sight of the project,
+    /// such as those of workspace members. A link in `node_modules/.bin` that no longer resolves
+    /// falls through to the virtual store as well.
     pub fn find_bin<P: AsRef<Path>>(&self, bin_name: P) -> Option<PathBuf> {
+        let bin_name = bin_name.as_ref();
         self.workspace_roots().find_map(|root| {
-            let mut bin_path = root.join("node_modules");
-            bin_path.push(".bin");
-            bin_path.push(&bin_name);
-
+            let node_modules = root.join("node_modules");
+            let bin_path = node_modules.join(".bin").join(bin_name);
             if bin_path.is_file() {
-                Some(bin_path)
-            } else {
-                None
+                return Some(bin_path);
             }
+
+            let mut store_path = node_modules;
+            store_path.extend([PNPM_VIRTUAL_STORE, "node_modules", ".bin"]);
+            store_path.push(bin_name);
+            store_path.is_file().then_some(store_path)
         })
     }
 
@@ -182,9 +406,26 @@ impl Project {
             })
     }
 
-    /// Pins the Node version in this project's manifest file
+    /// Projects managed by pnpm, whose binaries can't be found directly, need to use pnpm exec.
+    // (project pins pnpm and its dependencies are installed in a virtual store in 'node_modules')
+    //
+    // A 'pnpm-lock.yaml' alone isn't enough: without installed dependencies, pnpm exec looks the
+    // command up on the PATH, which finds the shim again.
+    pub fn needs_pnpm_exec(&self) -> bool {
+        self.platform()
+            .is_some_and(|platform| platform.pnpm.is_some())
+            && self
+                .workspace_roots()
+                .any(|x| x.join("node_modules").join(PNPM_VIRTUAL_STORE).is_dir())
+    }
+
+    /// Pins the Node version in this project
+    ///
+    /// The manifest file isn't changed until [`Project::save_pins`] is called.
     pub fn pin_node(&mut self, version: Version) -> Fallible<()> {
-        update_manifest(&self.manifest_file, ManifestKey::Node, Some(&version))?;
+        self.pinned = true;
+        self.unsaved_pins
+            .push((ManifestKey::Node, Some(version.to_string())));
 
         if let Some(platform) = self.platform.as_mut() {
             platform.node = version;
@@ -200,11 +441,18 @@ impl Project {
         Ok(())
     }
 
-    /// Pins the npm version in this project's manifest file
+    /// Pins the npm version in this project
+    ///
+    /// A version of `None` pins the npm bundled with Node, which is written as `"bundled"`
     pub fn pin_npm(&mut self, version: Option<Version>) -> Fallible<()> {
         if let Some(platform) = self.platform.as_mut() {
-            update_manifest(&self.manifest_file, ManifestKey::Npm, version.as_ref())?;
+            let value = version
+                .as_ref()
+                .map_or_else(|| BUNDLED_NPM.to_owned(), Version::to_string);
+            self.unsaved_pins.push((ManifestKey::Npm, Some(value)));
+            self.pinned = true;
 
+            self.bundled_npm = version.is_none();
             platform.npm = version;
 
             Ok(())
@@ -213,10 +461,27 @@ impl Project {
         }
     }
 
-    /// Pins the pnpm version in this project's manifest file
+    /// Removes the npm pin from this project, so that it uses the npm bundled with Node again
+    pub fn unpin_npm(&mut self) -> Fallible<()> {
+        if let Some(platform) = self.platform.as_mut() {
+            self.unsaved_pins.push((ManifestKey::Npm, None));
+            self.pinned = true;
+
+            self.bundled_npm = false;
+            platform.npm = None;
+
+            Ok(())
+        } else {
+            Err(ErrorKind::NoPinnedNodeVersion { tool: "npm".into() }.into())
+        }
+    }
+
+    /// Pins the pnpm version in this project
     pub fn pin_pnpm(&mut self, version: Option<Version>) -> Fallible<()> {
         if let Some(platform) = self.platform.as_mut() {
-            update_manifest(&self.manifest_file, ManifestKey::Pnpm, version.as_ref())?;
+            self.unsaved_pins
+                .push((ManifestKey::Pnpm, version.as_ref().map(Version::to_string)));
+            self.pinned = true;
 
             platform.pnpm = version;
 
@@ -229,10 +494,12 @@ impl Project {
         }
     }
 
-    /// Pins the Yarn version in this project's manifest file
+    /// Pins the Yarn version in this project
     pub fn pin_yarn(&mut self, version: Option<Version>) -> Fallible<()> {
         if let Some(platform) = self.platform.as_mut() {
-            update_manifest(&self.manifest_file, ManifestKey::Yarn, version.as_ref())?;
+            self.unsaved_pins
+                .push((ManifestKey::Yarn, version.as_ref().map(Version::to_string)));
+            self.pinned = true;
 
             platform.yarn = version;
 
@@ -244,6 +511,69 @@ impl Project {
             .into())
         }
     }
+
+    /// Writes the pins made since the last save to this project's manifest file
+    ///
+    /// All of them are written at once, so pinning several tools either updates the manifest
+    /// with every one of them or, if any pin fails before saving, leaves it untouched.
+    pub fn save_pins(&mut self) -> Fallible<()> {
+        if self.unsaved_pins.is_empty() {
+            return Ok(());
+        }
+
+        update_manifest(&self.manifest_file, &self.unsaved_pins)?;
+        self.unsaved_pins.clear();
+
+        Ok(())
+    }
+}
+
+/// Determines whether the search for workspace members should skip a directory
+fn is_skipped_member_dir(entry: &DirEntry) -> bool {
+    entry.file_type().is_dir()
+        && (is_node_modules(entry.path())
+            || entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.starts_with('.')))
+}
+
+/// Follows the `volta.extends` chain starting at `manifest`, returning true if it reaches `target`
+///
+/// The parsed `extends` value of each manifest is cached, since members of a workspace usually
+/// share most of their chain. Manifests that can't be parsed end the chain.
+fn extends_chain_reaches(
+    manifest: PathBuf,
+    target: &Path,
+    cache: &mut HashMap<PathBuf, Option<PathBuf>>,
+) -> bool {
+    let mut seen = HashSet::new();
+    let mut current = manifest;
+
+    loop {
+        let next = cache
+            .entry(current.clone())
+            .or_insert_with(|| match Manifest::from_file(&current) {
+                Ok(parsed) => parsed.extends,
+                Err(error) => {
+                    debug!(
+                        "Could not read workspace member '{}': {}",
+                        current.display(),
+                        error
+                    );
+                    None
+                }
+            })
+            .clone();
+
+        match next {
+            Some(next) if next == target => return true,
+            // A cycle will be reported when the member itself is used, so just stop here
+            Some(next) if !seen.insert(next.clone()) => return false,
+            Some(next) => current = next,
+            None => return false,
+        }
+    }
 }
 
 fn is_node_root(dir: &Path) -> bool {
@@ -263,28 +593,121 @@ fn is_project_root(dir: &Path) -> bool {
 }
 
 /// Starts at `base_dir` and walks up the directory tree until a package.json file is found
-pub(crate) fn find_closest_root(mut dir: PathBuf) -> Option<PathBuf> {
-    while !is_project_root(&dir) {
-        if !dir.pop() {
-            return None;
+///
+/// The walk stops before entering any of the project ceiling directories (see
+/// `project_ceilings`) and before crossing onto a different filesystem volume.
+pub(crate) fn find_closest_root(dir: PathBuf) -> Option<PathBuf> {
+    find_closest_root_within(dir, &project_ceilings())
+}
+
+fn find_closest_root_within(mut dir: PathBuf, ceilings: &[PathBuf]) -> Option<PathBuf> {
+    let mut visited = Vec::new();
+    let mut volume = volume_id(&dir);
+
+    loop {
+        if no_project_cache().contains(&dir) {
+            break;
+        }
+
+        if is_project_root(&dir) {
+            return Some(dir);
+        }
+
+        visited.push(dir.clone());
+
+        let Some(parent) = dir.parent() else {
+            break;
+        };
+
+        if ceilings.iter().any(|ceiling| ceiling == parent) {
+            debug!("Stopping project search at ceiling '{}'", parent.display());
+            break;
+        }
+
+        let parent_volume = volume_id(parent);
+        if volume.is_some() && parent_volume.is_some() && volume != parent_volume {
+            debug!("Stopping project search at volume root '{}'", dir.display());
+            break;
         }
+
+        volume = parent_volume;
+        dir.pop();
     }
 
-    Some(dir)
+    // Remember that none of the visited directories are within a project, so later lookups in
+    // this session can stop as soon as they reach one of them
+    no_project_cache().extend(visited);
+    None
+}
+
+/// Directories that are known to not be within a project
+static NO_PROJECT_DIRS: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+fn no_project_cache() -> MutexGuard<'static, HashSet<PathBuf>> {
+    // The cache only ever holds complete entries, so it can't be left inconsistent by a panic
+    NO_PROJECT_DIRS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Determines the directories that the search for a project root will never enter
+///
+/// These are the parent of the user's home directory, along with any directories listed in the
+/// `VOLTA_PROJECT_CEILING` environment variable (separated the same way as `PATH`). Only absolute
+/// paths are used as ceilings.
+fn project_ceilings() -> Vec<PathBuf> {
+    let home_parent = dirs::home_dir().and_then(|home| home.parent().map(Path::to_owned));
+    let configured = env::var_os(VOLTA_PROJECT_CEILING)
+        .map(|value| env::split_paths(&value).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    home_parent
+        .into_iter()
+        .chain(configured)
+        .filter(|dir| dir.is_absolute())
+        .collect()
+}
+
+/// Identifies the filesystem volume that contains a directory
+///
+/// Returns `None` if the volume can't be determined, in which case the search continues as normal.
+#[cfg(unix)]
+fn volume_id(dir: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    fs::metadata(dir).ok().map(|metadata| metadata.dev())
+}
+
+/// Identifies the filesystem volume that contains a directory
+///
+/// On Windows, each volume has its own path prefix, so walking up the tree can never leave it.
+#[cfg(windows)]
+fn volume_id(_dir: &Path) -> Option<u64> {
+    None
 }
 
 struct PartialPlatform {
     node: Option<Version>,
     npm: Option<Version>,
+    /// Whether npm is explicitly pinned to the version bundled with Node
+    bundled_npm: bool,
     pnpm: Option<Version>,
     yarn: Option<Version>,
 }
 
 impl PartialPlatform {
     fn merge(self, other: PartialPlatform) -> PartialPlatform {
+        // An explicit bundled pin takes precedence over an npm version from the extended manifest
+        let (npm, bundled_npm) = if self.npm.is_some() || self.bundled_npm {
+            (self.npm, self.bundled_npm)
+        } else {
+            (other.npm, other.bundled_npm)
+        };
+
         PartialPlatform {
             node: self.node.or(other.node),
-            npm: self.npm.or(other.npm),
+            npm,
+            bundled_npm,
             pnpm: self.pnpm.or(other.pnpm),
             yarn: self.yarn.or(other.yarn),
         }
@@ -0,0 +1,317 @@
+//! Reads and writes the `volta.lock` file, which records the exact platform of a project so that
+//! it is replayed even if the `volta` settings in `package.json` change
+//!
+//! The lock file also records a hash of the settings it was derived from. The commands that
+//! check it compare that hash with the current settings, and show a warning when they differ.
+//! With `--frozen` (or `VOLTA_FROZEN`), an outdated lock file is an error instead, as with
+//! `npm ci`. Shims use the locked platform without checking it, so they don't hash the settings
+//! on every run.
+
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::BUNDLED_NPM;
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::read_file;
+use crate::platform::PlatformSpec;
+use crate::version::parse_version;
+use log::warn;
+use node_semver::Version;
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+
+/// The name of the lock file, which lives next to the project's `package.json`
+pub(super) const PLATFORM_LOCK_FILE: &str = "volta.lock";
+
+/// The version of the lock file format written by this version of Volta
+const PLATFORM_LOCK_VERSION: u64 = 1;
+
+const VOLTA_FROZEN: &str = "VOLTA_FROZEN";
+
+static FROZEN: AtomicBool = AtomicBool::new(false);
+
+/// Requires the `volta.lock` file of a project to be up to date, as `--frozen` does
+pub fn set_frozen() {
+    FROZEN.store(true, Ordering::Relaxed);
+}
+
+/// Determines whether an outdated lock file is an error, which can be turned on with `--frozen`
+/// or by setting `VOLTA_FROZEN` (to anything other than `0`)
+fn frozen() -> bool {
+    if FROZEN.load(Ordering::Relaxed) {
+        return true;
+    }
+
+    match env::var_os(VOLTA_FROZEN) {
+        Some(value) => !value.is_empty() && value != "0",
+        None => false,
+    }
+}
+
+/// Whether a project's `volta.lock` file matches the `volta` settings in its manifest
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlatformLockStatus {
+    /// The project has no lock file
+    Missing,
+    /// The lock file was written from the current settings
+    UpToDate,
+    /// The settings have changed since the lock file was written
+    Outdated,
+}
+
+/// The platform recorded in a project's `volta.lock` file
+#[cfg_attr(test, derive(Debug))]
+pub(super) struct PlatformLock {
+    pub platform: PlatformSpec,
+    /// Whether npm is locked to the version bundled with Node
+    pub bundled_npm: bool,
+    /// The hash of the `volta` settings the lock file was derived from
+    pub hash: String,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPlatformLock {
+    version: u64,
+    node: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    npm: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pnpm: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    yarn: Option<String>,
+    volta_hash: String,
+}
+
+impl PlatformLock {
+    /// Creates a lock for the given platform, derived from the settings with the given hash
+    pub fn new(platform: PlatformSpec, bundled_npm: bool, hash: String) -> Self {
+        PlatformLock {
+            platform,
+            bundled_npm,
+            hash,
+        }
+    }
+
+    /// Reads the lock file, if it exists
+    pub fn from_file(file: &Path) -> Fallible<Option<Self>> {
+        let contents = read_file(file).with_context(|| ErrorKind::ReadPlatformLockError {
+            file: file.to_owned(),
+        })?;
+
+        contents
+            .map(|contents| Self::parse(file, &contents))
+            .transpose()
+    }
+
+    fn parse(file: &Path, contents: &str) -> Fallible<Self> {
+        let parse_error = || ErrorKind::ParsePlatformLockError {
+            file: file.to_owned(),
+        };
+
+        // The version is checked on its own first, so that a file written by a newer version of
+        // Volta is reported as such, rather than as a file that can't be parsed
+        #[derive(Deserialize)]
+        struct Versioned {
+            version: u64,
+        }
+
+        let Versioned { version } = serde_json::from_str(contents).with_context(parse_error)?;
+        if version != PLATFORM_LOCK_VERSION {
+            return Err(ErrorKind::UnsupportedPlatformLockVersion {
+                file: file.to_owned(),
+                version,
+            }
+            .into());
+        }
+
+        let raw: RawPlatformLock = serde_json::from_str(contents).with_context(parse_error)?;
+        let bundled_npm = raw.npm.as_deref() == Some(BUNDLED_NPM);
+        let npm = match raw.npm {
+            Some(npm) if npm == BUNDLED_NPM => None,
+            npm => npm.map(parse_version).transpose()?,
+        };
+
+        Ok(PlatformLock {
+            platform: PlatformSpec {
+                node: parse_version(raw.node)?,
+                npm,
+                pnpm: raw.pnpm.map(parse_version).transpose()?,
+                yarn: raw.yarn.map(parse_version).transpose()?,
+            },
+            bundled_npm,
+            hash: raw.volta_hash,
+        })
+    }
+
+    /// Writes the lock file as stable, pretty-printed JSON
+    pub fn write(&self, file: &Path) -> Fallible<()> {
+        let write_error = || ErrorKind::WritePlatformLockError {
+            file: file.to_owned(),
+        };
+
+        let npm = match &self.platform.npm {
+            Some(npm) => Some(npm.to_string()),
+            None if self.bundled_npm => Some(BUNDLED_NPM.to_owned()),
+            None => None,
+        };
+        let raw = RawPlatformLock {
+            version: PLATFORM_LOCK_VERSION,
+            node: self.platform.node.to_string(),
+            npm,
+            pnpm: self.platform.pnpm.as_ref().map(Version::to_string),
+            yarn: self.platform.yarn.as_ref().map(Version::to_string),
+            volta_hash: self.hash.clone(),
+        };
+
+        let mut contents = serde_json::to_string_pretty(&raw).with_context(write_error)?;
+        contents.push('\n');
+        fs::write(file, contents).with_context(write_error)
+    }
+}
+
+/// Reports a lock file that is out of date with the settings in the manifest
+///
+/// This is a warning, unless the lock file must be up to date.
+pub(super) fn report_outdated(manifest_file: &Path) -> Fallible<()> {
+    let file = lock_file_for(manifest_file);
+    if frozen() {
+        return Err(ErrorKind::PlatformLockOutdated {
+            file,
+            manifest: manifest_file.to_owned(),
+        }
+        .into());
+    }
+
+    warn!(
+        "The Volta settings in {} have changed since {} was written.
+Run `volta lock --update` to update it.",
+        manifest_file.display(),
+        file.display()
+    );
+    Ok(())
+}
+
+/// The path to the lock file of the project with the given manifest
+pub(super) fn lock_file_for(manifest_file: &Path) -> PathBuf {
+    manifest_file
+        .parent()
+        .expect("File paths always have a parent")
+        .join(PLATFORM_LOCK_FILE)
+}
+
+/// Hashes the `volta` settings of a project, including those from any manifests it extends
+///
+/// The hash is computed from a canonical form of the settings, so that formatting changes to
+/// `package.json`, or changes to other settings, don't make the lock file outdated.
+pub(super) fn settings_hash(platform: Option<&PlatformSpec>, bundled_npm: bool) -> String {
+    let mut canonical = String::new();
+
+    if let Some(platform) = platform {
+        let _ = writeln!(canonical, "node={}", platform.node);
+        match &platform.npm {
+            Some(npm) => {
+                let _ = writeln!(canonical, "npm={}", npm);
+            }
+            None if bundled_npm => {
+                let _ = writeln!(canonical, "npm={}", BUNDLED_NPM);
+            }
+            None => {}
+        }
+        if let Some(pnpm) = &platform.pnpm {
+            let _ = writeln!(canonical, "pnpm={}", pnpm);
+        }
+        if let Some(yarn) = &platform.yarn {
+            let _ = writeln!(canonical, "yarn={}", yarn);
+        }
+    }
+
+    let mut hash = String::from("sha256-");
+    for byte in digest(&SHA256, canonical.as_bytes()).as_ref() {
+        let _ = write!(hash, "{:02x}", byte);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn platform(npm: Option<&str>) -> PlatformSpec {
+        PlatformSpec {
+            node: "20.11.0".parse().unwrap(),
+            npm: npm.map(|npm| npm.parse().unwrap()),
+            pnpm: None,
+            yarn: Some("1.22.19".parse().unwrap()),
+        }
+    }
+
+    #[test]
+    fn writes_stable_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(PLATFORM_LOCK_FILE);
+        let lock = PlatformLock::new(platform(Some("10.2.4")), false, "sha256-abc".into());
+
+        lock.write(&file).unwrap();
+        assert_eq!(
+            fs::read_to_string(&file).unwrap(),
+            r#"{
+  "version": 1,
+  "node": "20.11.0",
+  "npm": "10.2.4",
+  "yarn": "1.22.19",
+  "voltaHash": "sha256-abc"
+}
+"#
+        );
+
+        let read = PlatformLock::from_file(&file).unwrap().unwrap();
+        assert_eq!(read.platform, lock.platform);
+        assert!(!read.bundled_npm);
+        assert_eq!(read.hash, "sha256-abc");
+    }
+
+    #[test]
+    fn bundled_npm_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(PLATFORM_LOCK_FILE);
+
+        PlatformLock::new(platform(None), true, "sha256-abc".into())
+            .write(&file)
+            .unwrap();
+        assert!(fs::read_to_string(&file)
+            .unwrap()
+            .contains(r#""npm": "bundled""#));
+
+        let read = PlatformLock::from_file(&file).unwrap().unwrap();
+        assert!(read.bundled_npm);
+        assert_eq!(read.platform.npm, None);
+    }
+
+    #[test]
+    fn rejects_unknown_version() {
+        let error = PlatformLock::parse(Path::new(PLATFORM_LOCK_FILE), r#"{ "version": 2 }"#)
+            .expect_err("version 2 should not be supported");
+
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::UnsupportedPlatformLockVersion { version: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn hash_depends_only_on_settings() {
+        let npm = settings_hash(Some(&platform(Some("10.2.4"))), false);
+
+        assert_eq!(npm, settings_hash(Some(&platform(Some("10.2.4"))), false));
+        assert_ne!(npm, settings_hash(Some(&platform(Some("10.2.5"))), false));
+        assert_ne!(
+            settings_hash(Some(&platform(None)), true),
+            settings_hash(Some(&platform(None)), false)
+        );
+        assert_ne!(npm, settings_hash(None, false));
+    }
+}
@@ -1,16 +1,19 @@
 use std::collections::HashMap;
 use std::fmt;
-use std::fs::{read_to_string, File};
-use std::io::Write;
+use std::fs::read_to_string;
 use std::path::{Path, PathBuf};
 
+use super::writer::{skip_string, skip_whitespace, strip_bom};
 use super::PartialPlatform;
 use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::record_open;
+use crate::tool::node::parse_arch;
 use crate::version::parse_version;
 use dunce::canonicalize;
+use log::debug;
 use node_semver::Version;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use serde_json::{Map, Value};
 
 pub type DependencyMapIterator = std::iter::Chain<
     std::option::IntoIter<HashMap<String, String>>,
@@ -21,6 +24,8 @@ pub(super) struct Manifest {
     pub dependency_maps: DependencyMapIterator,
     pub platform: Option<PartialPlatform>,
     pub extends: Option<PathBuf>,
+    pub engine_strict: Option<bool>,
+    pub arch: Option<String>,
 }
 
 impl Manifest {
@@ -29,6 +34,16 @@ impl Manifest {
 
         let dependency_maps = raw.dependencies.into_iter().chain(raw.dev_dependencies);
 
+        let engine_strict = raw
+            .volta
+            .as_ref()
+            .and_then(|toolchain| toolchain.engine_strict);
+        let arch = raw
+            .volta
+            .as_ref()
+            .and_then(|toolchain| toolchain.arch.as_deref())
+            .map(parse_arch)
+            .transpose()?;
         let (platform, extends) = match raw.volta {
             Some(toolchain) => {
                 let (partial, extends) = toolchain.parse_split()?;
@@ -53,10 +68,16 @@ impl Manifest {
             dependency_maps,
             platform,
             extends,
+            engine_strict,
+            arch,
         })
     }
 }
 
+/// The value of `volta.npm` that explicitly selects the npm bundled with the pinned Node version
+pub(super) const BUNDLED_NPM: &str = "bundled";
+
+#[cfg_attr(test, derive(Debug))]
 pub(super) enum ManifestKey {
     Node,
     Npm,
@@ -75,67 +96,70 @@ impl fmt::Display for ManifestKey {
     }
 }
 
-/// Updates the `volta` hash in the specified manifest with the given key and value
-///
-/// Will create the `volta` hash if it isn't already present
-///
-/// If the value is `None`, will remove the key from the hash
-pub(super) fn update_manifest(
-    file: &Path,
-    key: ManifestKey,
-    value: Option<&Version>,
-) -> Fallible<()> {
-    let contents = read_to_string(file).with_context(|| ErrorKind::PackageReadError {
-        file: file.to_owned(),
-    })?;
-
-    let mut manifest: serde_json::Value =
-        serde_json::from_str(&contents).with_context(|| ErrorKind::PackageParseError {
-            file: file.to_owned(),
-        })?;
-
-    let root = manifest
-        .as_object_mut()
-        .ok_or_else(|| ErrorKind::PackageParseError {
-            file: file.to_owned(),
-        })?;
+/// Parses JSON that may contain comments and trailing commas, as some tools write them
+pub(super) fn parse_lenient<T: DeserializeOwned>(contents: &str) -> Option<T> {
+    let stripped = remove_trailing_commas(&remove_comments(contents)?)?;
+    serde_json::from_str(&stripped).ok()
+}
 
-    let key = key.to_string();
+/// Removes `//` and `/* */` comments outside of strings, returning `None` if a block comment is
+/// never closed
+fn remove_comments(contents: &str) -> Option<String> {
+    let bytes = contents.as_bytes();
+    let mut output = String::with_capacity(contents.len());
+    let mut copied = 0;
+    let mut pos = 0;
 
-    match (value, root.get_mut("volta").and_then(|v| v.as_object_mut())) {
-        (Some(v), Some(hash)) => {
-            hash.insert(key, Value::String(v.to_string()));
-        }
-        (None, Some(hash)) => {
-            hash.remove(&key);
-        }
-        (Some(v), None) => {
-            let mut map = Map::new();
-            map.insert(key, Value::String(v.to_string()));
-            root.insert("volta".into(), Value::Object(map));
+    while let Some(&byte) = bytes.get(pos) {
+        match (byte, bytes.get(pos + 1)) {
+            (b'"', _) => pos = skip_string(bytes, pos)?,
+            (b'/', Some(b'/')) => {
+                output.push_str(&contents[copied..pos]);
+                // The line ending is kept, so that error positions still match the file
+                pos = contents[pos..]
+                    .find('\n')
+                    .map_or(bytes.len(), |end| pos + end);
+                copied = pos;
+            }
+            (b'/', Some(b'*')) => {
+                output.push_str(&contents[copied..pos]);
+                pos += 2 + contents[pos + 2..].find("*/")? + 2;
+                output.push(' ');
+                copied = pos;
+            }
+            _ => pos += 1,
         }
-        (None, None) => {}
     }
 
-    let indent = detect_indent::detect_indent(&contents);
-    let mut output = File::create(file).with_context(|| ErrorKind::PackageWriteError {
-        file: file.to_owned(),
-    })?;
-    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.indent().as_bytes());
-    let mut ser = serde_json::Serializer::with_formatter(&output, formatter);
-    manifest
-        .serialize(&mut ser)
-        .with_context(|| ErrorKind::PackageWriteError {
-            file: file.to_owned(),
-        })?;
+    output.push_str(&contents[copied..]);
+    Some(output)
+}
 
-    if contents.ends_with('\n') {
-        writeln!(output).with_context(|| ErrorKind::PackageWriteError {
-            file: file.to_owned(),
-        })?;
+/// Removes the commas that directly precede the end of an object or array
+fn remove_trailing_commas(contents: &str) -> Option<String> {
+    let bytes = contents.as_bytes();
+    let mut output = String::with_capacity(contents.len());
+    let mut copied = 0;
+    let mut pos = 0;
+
+    while let Some(&byte) = bytes.get(pos) {
+        match byte {
+            b'"' => pos = skip_string(bytes, pos)?,
+            b',' if matches!(
+                bytes.get(skip_whitespace(bytes, pos + 1)),
+                Some(b'}' | b']')
+            ) =>
+            {
+                output.push_str(&contents[copied..pos]);
+                pos += 1;
+                copied = pos;
+            }
+            _ => pos += 1,
+        }
     }
 
-    Ok(())
+    output.push_str(&contents[copied..]);
+    Some(output)
 }
 
 #[derive(Deserialize)]
@@ -150,13 +174,28 @@ struct RawManifest {
 
 impl RawManifest {
     fn from_file(package: &Path) -> Fallible<Self> {
-        let file = File::open(package).with_context(|| ErrorKind::PackageReadError {
+        record_open("project manifest", package);
+        let contents = read_to_string(package).with_context(|| ErrorKind::PackageReadError {
             file: package.to_owned(),
         })?;
+        let (_, contents) = strip_bom(&contents);
 
-        serde_json::de::from_reader(file).with_context(|| ErrorKind::PackageParseError {
-            file: package.to_owned(),
-        })
+        match serde_json::from_str(contents) {
+            Ok(raw) => Ok(raw),
+            Err(error) => match parse_lenient(contents) {
+                Some(raw) => {
+                    debug!(
+                        "Read project manifest '{}' by ignoring comments and trailing commas: {}",
+                        package.display(),
+                        error
+                    );
+                    Ok(raw)
+                }
+                None => Err(error).with_context(|| ErrorKind::PackageParseError {
+                    file: package.to_owned(),
+                }),
+            },
+        }
     }
 }
 
@@ -172,19 +211,28 @@ struct ToolchainSpec {
     yarn: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     extends: Option<PathBuf>,
+    #[serde(rename = "engineStrict", skip_serializing_if = "Option::is_none")]
+    engine_strict: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    arch: Option<String>,
 }
 
 impl ToolchainSpec {
     /// Moves the tool versions into a `PartialPlatform` and returns that along with the `extends` value
     fn parse_split(self) -> Fallible<(PartialPlatform, Option<PathBuf>)> {
-        let node = self.node.map(parse_version).transpose()?;
-        let npm = self.npm.map(parse_version).transpose()?;
-        let pnpm = self.pnpm.map(parse_version).transpose()?;
-        let yarn = self.yarn.map(parse_version).transpose()?;
+        let node = parse_pinned_version(ManifestKey::Node, self.node)?;
+        let bundled_npm = self.npm.as_deref() == Some(BUNDLED_NPM);
+        let npm = match self.npm {
+            Some(npm) if npm == BUNDLED_NPM => None,
+            npm => npm.map(parse_version).transpose()?,
+        };
+        let pnpm = parse_pinned_version(ManifestKey::Pnpm, self.pnpm)?;
+        let yarn = parse_pinned_version(ManifestKey::Yarn, self.yarn)?;
 
         let platform = PartialPlatform {
             node,
             npm,
+            bundled_npm,
             pnpm,
             yarn,
         };
@@ -192,3 +240,103 @@ impl ToolchainSpec {
         Ok((platform, self.extends))
     }
 }
+
+/// Parses the version pinned for a tool that has no bundled version, rejecting `"bundled"`
+fn parse_pinned_version(key: ManifestKey, value: Option<String>) -> Fallible<Option<Version>> {
+    match value {
+        Some(value) if value == BUNDLED_NPM => Err(ErrorKind::BundledVersionNotSupported {
+            tool: key.to_string(),
+        }
+        .into()),
+        value => value.map(parse_version).transpose(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::writer::update_manifest;
+    use super::*;
+    use serde_json::Value;
+
+    fn parse(volta: &str) -> Fallible<PartialPlatform> {
+        let spec: ToolchainSpec = serde_json::from_str(volta).unwrap();
+        spec.parse_split().map(|(platform, _)| platform)
+    }
+
+    #[test]
+    fn lenient_parse_ignores_comments_and_trailing_commas() {
+        let parsed: Value = parse_lenient(
+            r#"{
+  // comment
+  "a": "http://example.com, ]", /* block */
+  "b": [1, 2,],
+  "c": { "d": "\\" },
+}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            parsed,
+            serde_json::json!({ "a": "http://example.com, ]", "b": [1, 2], "c": { "d": "\\" } })
+        );
+    }
+
+    #[test]
+    fn lenient_parse_rejects_invalid_json() {
+        assert!(parse_lenient::<Value>(r#"{ "a": 1 /* unclosed }"#).is_none());
+        assert!(parse_lenient::<Value>(r#"{ "a": }"#).is_none());
+    }
+
+    #[test]
+    fn bundled_npm_is_parsed_as_no_version() {
+        let platform = parse(r#"{ "node": "18.12.1", "npm": "bundled" }"#).unwrap();
+
+        assert!(platform.bundled_npm);
+        assert_eq!(platform.npm, None);
+    }
+
+    #[test]
+    fn bundled_is_rejected_for_other_tools() {
+        for volta in [
+            r#"{ "node": "bundled" }"#,
+            r#"{ "node": "18.12.1", "pnpm": "bundled" }"#,
+            r#"{ "node": "18.12.1", "yarn": "bundled" }"#,
+        ] {
+            match parse(volta) {
+                Err(error) => assert!(matches!(
+                    error.kind(),
+                    ErrorKind::BundledVersionNotSupported { .. }
+                )),
+                Ok(_) => panic!("Expected an error for {}", volta),
+            }
+        }
+    }
+
+    #[test]
+    fn bundled_npm_round_trips_through_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("package.json");
+        std::fs::write(&file, "{\n  \"name\": \"project\"\n}\n").unwrap();
+
+        update_manifest(
+            &file,
+            &[
+                (ManifestKey::Node, Some("18.12.1".into())),
+                (ManifestKey::Npm, Some(BUNDLED_NPM.into())),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            read_to_string(&file).unwrap(),
+            "{\n  \"name\": \"project\",\n  \"volta\": {\n    \"node\": \"18.12.1\",\n    \"npm\": \"bundled\"\n  }\n}\n"
+        );
+
+        let platform = Manifest::from_file(&file).unwrap().platform.unwrap();
+        assert!(platform.bundled_npm);
+        assert_eq!(platform.npm, None);
+
+        update_manifest(&file, &[(ManifestKey::Npm, None)]).unwrap();
+        let platform = Manifest::from_file(&file).unwrap().platform.unwrap();
+        assert!(!platform.bundled_npm);
+    }
+}
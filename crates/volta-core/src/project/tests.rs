@@ -1,3 +1,4 @@
+use std::fs;
 use std::path::PathBuf;
 
 use super::*;
@@ -40,6 +41,51 @@ mod find_closest_root {
 
         assert_eq!(project_dir, fixture_path(&["basic"]));
     }
+
+    #[test]
+    fn test_find_closest_root_ceiling_above_project() {
+        let base_dir = fixture_path(&["basic", "subdir"]);
+        let ceilings = [fixture_path(&[])];
+        let project_dir = find_closest_root_within(base_dir, &ceilings)
+            .expect("Failed to find project directory");
+
+        assert_eq!(project_dir, fixture_path(&["basic"]));
+    }
+
+    #[test]
+    fn test_find_closest_root_stops_at_ceiling() {
+        let base_dir = fixture_path(&["nested", "subproject", "inner_project", "node_modules"]);
+        let ceilings = [fixture_path(&["nested", "subproject", "inner_project"])];
+
+        assert_eq!(find_closest_root_within(base_dir.clone(), &ceilings), None);
+        assert!(no_project_cache().contains(&base_dir));
+    }
+
+    #[test]
+    fn test_find_closest_root_checks_ceiling_start_dir() {
+        let base_dir = fixture_path(&["basic"]);
+        let ceilings = [base_dir.clone()];
+        let project_dir = find_closest_root_within(base_dir.clone(), &ceilings)
+            .expect("Failed to find project directory");
+
+        assert_eq!(project_dir, base_dir);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_find_closest_root_stops_at_volume_root() {
+        // `/proc` is always mounted as a separate filesystem on Linux
+        let base_dir = PathBuf::from("/proc/self");
+        if volume_id(&base_dir).is_none() {
+            return;
+        }
+
+        assert_eq!(find_closest_root_within(base_dir, &[]), None);
+
+        let cache = no_project_cache();
+        assert!(cache.contains(Path::new("/proc")));
+        assert!(!cache.contains(Path::new("/")));
+    }
 }
 
 mod project {
@@ -94,6 +140,130 @@ mod project {
         assert_eq!(platform.yarn, Some("1.22.4".parse().unwrap()));
     }
 
+    #[test]
+    fn platform_bundled_npm_overrides_extended_npm() {
+        let project_path = fixture_path(&["bundled-npm"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+        let platform = test_project.platform().unwrap();
+
+        assert!(test_project.pins_bundled_npm());
+        assert_eq!(platform.node, "12.14.0".parse().unwrap());
+        assert_eq!(platform.npm, None);
+        assert_eq!(platform.yarn, Some("1.22.4".parse().unwrap()));
+    }
+
+    #[test]
+    fn platform_bundled_npm_matches_no_npm_pin() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundled = dir.path().join("bundled");
+        let unpinned = dir.path().join("unpinned");
+        fs::create_dir_all(&bundled).unwrap();
+        fs::create_dir_all(&unpinned).unwrap();
+        fs::write(
+            bundled.join("package.json"),
+            r#"{ "volta": { "node": "18.12.1", "npm": "bundled", "yarn": "1.22.19" } }"#,
+        )
+        .unwrap();
+        fs::write(
+            unpinned.join("package.json"),
+            r#"{ "volta": { "node": "18.12.1", "yarn": "1.22.19" } }"#,
+        )
+        .unwrap();
+
+        let bundled = Project::for_dir(bundled).unwrap().unwrap();
+        let unpinned = Project::for_dir(unpinned).unwrap().unwrap();
+
+        assert_eq!(bundled.platform(), unpinned.platform());
+        assert!(bundled.pins_bundled_npm());
+        assert!(!unpinned.pins_bundled_npm());
+    }
+
+    #[test]
+    fn pin_npm_bundled_writes_literal() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("package.json");
+        fs::write(
+            &manifest,
+            r#"{ "volta": { "node": "18.12.1", "npm": "9.1.0" } }"#,
+        )
+        .unwrap();
+
+        let mut project = Project::for_dir(dir.path().to_owned()).unwrap().unwrap();
+        project.pin_npm(None).unwrap();
+        project.save_pins().unwrap();
+        assert!(project.pins_bundled_npm());
+
+        let reloaded = Project::for_dir(dir.path().to_owned()).unwrap().unwrap();
+        assert!(reloaded.pins_bundled_npm());
+        assert_eq!(reloaded.platform().unwrap().npm, None);
+        assert!(fs::read_to_string(&manifest)
+            .unwrap()
+            .contains(r#""npm": "bundled""#));
+
+        project.pin_npm(Some("9.2.0".parse().unwrap())).unwrap();
+        project.save_pins().unwrap();
+        let reloaded = Project::for_dir(dir.path().to_owned()).unwrap().unwrap();
+        assert!(!reloaded.pins_bundled_npm());
+        assert_eq!(
+            reloaded.platform().unwrap().npm,
+            Some("9.2.0".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn platform_with_comments_and_trailing_commas() {
+        let project_path = fixture_path(&["lenient"]);
+        let project = Project::for_dir(project_path).unwrap().unwrap();
+        let platform = project.platform().unwrap();
+
+        assert_eq!(platform.node, "18.12.1".parse().unwrap());
+        assert_eq!(platform.yarn, Some("1.22.19".parse().unwrap()));
+        assert!(project.has_direct_dependency("rsvp"));
+    }
+
+    #[test]
+    fn pin_refuses_manifest_with_comments_and_trailing_commas() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("package.json");
+        let original = fs::read_to_string(fixture_path(&["lenient", "package.json"])).unwrap();
+        fs::write(&manifest, &original).unwrap();
+
+        let mut project = Project::for_dir(dir.path().to_owned()).unwrap().unwrap();
+        project.pin_node("20.11.1".parse().unwrap()).unwrap();
+        let error = project.save_pins().unwrap_err();
+        use std::error::Error as _;
+
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::ManifestNotStrictJson { .. }
+        ));
+        assert!(error.source().is_some());
+        assert_eq!(fs::read_to_string(&manifest).unwrap(), original);
+    }
+
+    #[test]
+    fn pins_are_written_together() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("package.json");
+        let original = "{\n  \"name\": \"project\"\n}\n";
+        fs::write(&manifest, original).unwrap();
+
+        let mut project = Project::for_dir(dir.path().to_owned()).unwrap().unwrap();
+        project.pin_node("18.12.1".parse().unwrap()).unwrap();
+        project.pin_yarn(Some("3.6.4".parse().unwrap())).unwrap();
+        assert_eq!(
+            project.platform().unwrap().yarn,
+            Some("3.6.4".parse().unwrap())
+        );
+        assert_eq!(fs::read_to_string(&manifest).unwrap(), original);
+
+        project.save_pins().unwrap();
+        assert_eq!(
+            fs::read_to_string(&manifest).unwrap(),
+            "{\n  \"name\": \"project\",\n  \"volta\": {\n    \"node\": \"18.12.1\",\n    \"yarn\": \"3.6.4\"\n  }\n}\n"
+        );
+    }
+
     #[test]
     fn direct_dependencies_single() {
         let project_path = fixture_path(&["basic"]);
@@ -181,6 +351,74 @@ mod project {
         assert!(test_project.find_bin("ember").is_none());
     }
 
+    #[test]
+    fn find_bin_pnpm_virtual_store() {
+        let project_path = fixture_path(&["pnpm", "virtual-store"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+
+        // eslint is linked into the project, so that link is used
+        assert_eq!(
+            test_project.find_bin("eslint"),
+            Some(fixture_path(&[
+                "pnpm",
+                "virtual-store",
+                "node_modules",
+                ".bin",
+                "eslint"
+            ]))
+        );
+
+        // tsc is only in the virtual store
+        assert_eq!(
+            test_project.find_bin("tsc"),
+            Some(fixture_path(&[
+                "pnpm",
+                "virtual-store",
+                "node_modules",
+                ".pnpm",
+                "node_modules",
+                ".bin",
+                "tsc"
+            ]))
+        );
+
+        assert!(test_project.find_bin("rsvp").is_none());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn find_bin_pnpm_links() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let package_bin =
+            root.join("node_modules/.pnpm/typescript@5.4.5/node_modules/typescript/bin");
+        let store_bin = root.join("node_modules/.pnpm/node_modules/.bin");
+        let project_bin = root.join("node_modules/.bin");
+        for dir in [&package_bin, &store_bin, &project_bin] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(root.join("package.json"), "{}").unwrap();
+        fs::write(package_bin.join("tsc"), "").unwrap();
+        fs::write(store_bin.join("eslint"), "").unwrap();
+        symlink(
+            "../.pnpm/typescript@5.4.5/node_modules/typescript/bin/tsc",
+            project_bin.join("tsc"),
+        )
+        .unwrap();
+        symlink("../.pnpm/eslint@8.57.0/missing", project_bin.join("eslint")).unwrap();
+
+        let test_project = Project::for_dir(root.to_owned()).unwrap().unwrap();
+
+        // A link into the store is used as it is, while one that no longer resolves is skipped
+        assert_eq!(test_project.find_bin("tsc"), Some(project_bin.join("tsc")));
+        assert_eq!(
+            test_project.find_bin("eslint"),
+            Some(store_bin.join("eslint"))
+        );
+    }
+
     #[test]
     fn detects_workspace_cycles() {
         // cycle-1 has a cycle with the original package.json
@@ -249,3 +487,389 @@ mod needs_yarn_run {
         assert!(test_project.needs_yarn_run());
     }
 }
+
+mod needs_pnpm_exec {
+    use super::*;
+
+    #[test]
+    fn project_does_not_need_pnpm_exec() {
+        let project_path = fixture_path(&["basic"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+        assert!(!test_project.needs_pnpm_exec());
+    }
+
+    #[test]
+    fn project_has_pnpm_virtual_store() {
+        let project_path = fixture_path(&["pnpm", "virtual-store"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+        assert!(test_project.needs_pnpm_exec());
+    }
+
+    #[test]
+    fn project_has_only_pnpm_lockfile() {
+        let project_path = fixture_path(&["pnpm", "lockfile-only"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+        assert!(!test_project.needs_pnpm_exec());
+    }
+
+    #[test]
+    fn project_does_not_pin_pnpm() {
+        let project_path = fixture_path(&["pnpm", "unpinned"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+        assert!(!test_project.needs_pnpm_exec());
+    }
+}
+
+mod extending_members {
+    use super::*;
+
+    #[test]
+    fn finds_direct_and_transitive_members() {
+        let project_path = fixture_path(&["nested"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+
+        let expected = vec![
+            fixture_path(&["nested", "subproject"]),
+            fixture_path(&["nested", "subproject", "inner_project"]),
+        ];
+        assert_eq!(test_project.extending_members(), expected);
+    }
+
+    #[test]
+    fn only_searches_below_the_project() {
+        let project_path = fixture_path(&["nested", "subproject"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+
+        let expected = vec![fixture_path(&["nested", "subproject", "inner_project"])];
+        assert_eq!(test_project.extending_members(), expected);
+    }
+
+    #[test]
+    fn leaf_project_has_no_members() {
+        let project_path = fixture_path(&["nested", "subproject", "inner_project"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+
+        assert!(test_project.extending_members().is_empty());
+    }
+
+    #[test]
+    fn project_without_workspace_has_no_members() {
+        let project_path = fixture_path(&["basic"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+
+        assert!(test_project.extending_members().is_empty());
+    }
+}
+
+mod lockfile {
+    use super::*;
+    use crate::project::lockfile::{
+        compatible_range, infer_package_manager, parse_pnpm_lockfile_version,
+        parse_yarn_lockfile_version, PNPM_LOCKFILE_VERSIONS, YARN_LOCKFILE_VERSIONS,
+    };
+    use crate::tool::package::PackageManager;
+    use crate::version::VersionSpec;
+
+    fn read_fixture(fixture_dirs: &[&str]) -> String {
+        fs::read_to_string(fixture_path(fixture_dirs)).unwrap()
+    }
+
+    #[test]
+    fn parses_yarn_lockfiles() {
+        assert_eq!(
+            parse_yarn_lockfile_version(&read_fixture(&["lockfiles", "yarn-classic", "yarn.lock"])),
+            Some("1".into())
+        );
+        assert_eq!(
+            parse_yarn_lockfile_version(&read_fixture(&["lockfiles", "yarn-berry", "yarn.lock"])),
+            Some("8".into())
+        );
+        assert_eq!(parse_yarn_lockfile_version(""), None);
+        assert_eq!(
+            parse_yarn_lockfile_version(
+                "__metadata:\n  cacheKey: 8\n\n\"a@npm:1\":\n  version: 1.0.0\n"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn parses_pnpm_lockfiles() {
+        assert_eq!(
+            parse_pnpm_lockfile_version(&read_fixture(&["lockfiles", "pnpm-v6", "pnpm-lock.yaml"])),
+            Some("6.0".into())
+        );
+        assert_eq!(
+            parse_pnpm_lockfile_version(&read_fixture(&["lockfiles", "pnpm-v9", "pnpm-lock.yaml"])),
+            Some("9.0".into())
+        );
+        assert_eq!(
+            parse_pnpm_lockfile_version("lockfileVersion: 5.4\n"),
+            Some("5.4".into())
+        );
+        assert_eq!(
+            parse_pnpm_lockfile_version("lockfileVersion: 9\n"),
+            Some("9.0".into())
+        );
+        assert_eq!(parse_pnpm_lockfile_version("packages: {}\n"), None);
+    }
+
+    #[test]
+    fn maps_lockfile_versions_to_ranges() {
+        assert_eq!(compatible_range(&YARN_LOCKFILE_VERSIONS, "1"), Some("^1"));
+        assert_eq!(compatible_range(&YARN_LOCKFILE_VERSIONS, "8"), Some("^4"));
+        assert_eq!(compatible_range(&YARN_LOCKFILE_VERSIONS, "7"), None);
+        assert_eq!(compatible_range(&PNPM_LOCKFILE_VERSIONS, "5.4"), Some("^7"));
+        assert_eq!(
+            compatible_range(&PNPM_LOCKFILE_VERSIONS, "9.0"),
+            Some(">=9")
+        );
+        assert_eq!(compatible_range(&PNPM_LOCKFILE_VERSIONS, "5.1"), None);
+    }
+
+    #[test]
+    fn mapping_table_ranges_are_valid() {
+        for (_, range) in YARN_LOCKFILE_VERSIONS.iter().chain(&PNPM_LOCKFILE_VERSIONS) {
+            assert!(
+                matches!(range.parse(), Ok(VersionSpec::Semver(_))),
+                "{} is not a semver range",
+                range
+            );
+        }
+    }
+
+    #[test]
+    fn infers_from_single_lockfile() {
+        let inferred = infer_package_manager(&fixture_path(&["lockfiles", "yarn-berry"])).unwrap();
+        assert_eq!(inferred.manager, PackageManager::Yarn);
+        assert_eq!(inferred.range, "^4");
+        assert_eq!(inferred.lockfile_version, "8");
+
+        let inferred = infer_package_manager(&fixture_path(&["lockfiles", "pnpm-v6"])).unwrap();
+        assert_eq!(inferred.manager, PackageManager::Pnpm);
+        assert_eq!(inferred.range, "^8");
+    }
+
+    #[test]
+    fn fails_without_single_inferable_lockfile() {
+        let error = infer_package_manager(&fixture_path(&["lockfiles", "multiple"])).unwrap_err();
+        match error.kind() {
+            ErrorKind::MultipleLockfiles { lockfiles, .. } => {
+                assert_eq!(lockfiles, &["yarn.lock", "package-lock.json"])
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        let error = infer_package_manager(&fixture_path(&["lockfiles"])).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::NoLockfile { .. }));
+
+        for fixture in ["npm", "pnpm-unknown"] {
+            let error = infer_package_manager(&fixture_path(&["lockfiles", fixture])).unwrap_err();
+            assert!(matches!(
+                error.kind(),
+                ErrorKind::LockfileNotInferred { .. }
+            ));
+        }
+    }
+}
+
+mod version_files {
+    use super::*;
+    use crate::project::version_files::{infer_tools, parse_requirement, parse_version_file};
+    use crate::tool::Spec;
+
+    fn requirements(inferred: &InferredTool) -> Vec<(&str, Option<&str>, &str)> {
+        inferred
+            .found
+            .iter()
+            .map(|found| {
+                (
+                    found.file.file_name().unwrap().to_str().unwrap(),
+                    found.field,
+                    found.requirement.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_version_files() {
+        assert_eq!(parse_version_file("v18.16\n"), Some("18.16".into()));
+        assert_eq!(parse_version_file("20.11.1"), Some("20.11.1".into()));
+        assert_eq!(parse_version_file("v20.11.1\r\n"), Some("20.11.1".into()));
+        assert_eq!(
+            parse_version_file("# comment\n\nlts/*\n"),
+            Some("lts".into())
+        );
+        assert_eq!(parse_version_file("node\n"), Some("latest".into()));
+        assert_eq!(parse_version_file("lts/hydrogen\n"), None);
+        assert_eq!(parse_version_file(""), None);
+    }
+
+    #[test]
+    fn parses_engine_requirements() {
+        assert_eq!(parse_requirement(">=18.17"), Some(">=18.17".into()));
+        assert_eq!(parse_requirement("^18 || ^20"), Some("^18 || ^20".into()));
+        assert_eq!(parse_requirement("v20.0.0"), Some("20.0.0".into()));
+        assert_eq!(parse_requirement("not a range"), None);
+    }
+
+    #[test]
+    fn infers_node_from_nvmrc() {
+        let inferred = infer_tools(&fixture_path(&["version-files", "nvmrc"]));
+
+        assert_eq!(inferred.len(), 1);
+        assert_eq!(inferred[0].tool, "node");
+        assert_eq!(requirements(&inferred[0]), [(".nvmrc", None, "18.16")]);
+        assert!(!inferred[0].is_ambiguous());
+        assert!(matches!(inferred[0].spec().unwrap(), Spec::Node(_)));
+    }
+
+    #[test]
+    fn infers_node_and_npm_from_engines() {
+        let inferred = infer_tools(&fixture_path(&["version-files", "engines"]));
+
+        assert_eq!(inferred.len(), 2);
+        assert_eq!(
+            requirements(&inferred[0]),
+            [
+                ("package.json", Some("engines.node"), ">=18.17"),
+                ("package-lock.json", Some("engines.node"), ">=18.17"),
+            ]
+        );
+        assert!(!inferred[0].is_ambiguous());
+        assert_eq!(inferred[1].tool, "npm");
+        assert_eq!(
+            requirements(&inferred[1]),
+            [
+                ("package.json", Some("engines.npm"), "^10"),
+                ("package-lock.json", Some("engines.npm"), "^10"),
+            ]
+        );
+        assert!(matches!(inferred[1].spec().unwrap(), Spec::Npm(_)));
+    }
+
+    #[test]
+    fn reports_conflicting_versions() {
+        let inferred = infer_tools(&fixture_path(&["version-files", "conflicting"]));
+
+        // The invalid range in `engines` is skipped
+        assert_eq!(inferred.len(), 1);
+        assert_eq!(
+            requirements(&inferred[0]),
+            [(".nvmrc", None, "lts"), (".node-version", None, "20.11.1")]
+        );
+        assert!(inferred[0].is_ambiguous());
+    }
+
+    #[test]
+    fn infers_nothing_without_version_files() {
+        assert!(infer_tools(&fixture_path(&["lockfiles", "yarn-berry"])).is_empty());
+    }
+}
+
+mod platform_lock {
+    use super::*;
+
+    fn project_with_manifest(contents: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("package.json"), contents).unwrap();
+        dir
+    }
+
+    #[test]
+    fn project_without_lock_file_uses_manifest() {
+        let dir = project_with_manifest(r#"{ "volta": { "node": "18.12.1" } }"#);
+        let project = Project::for_dir(dir.path().to_owned()).unwrap().unwrap();
+
+        assert_eq!(
+            project.platform_lock_status().unwrap(),
+            PlatformLockStatus::Missing
+        );
+        assert_eq!(project.platform().unwrap().node, "18.12.1".parse().unwrap());
+    }
+
+    #[test]
+    fn lock_file_is_compared_with_manifest() {
+        let dir = project_with_manifest(r#"{ "volta": { "node": "18.12.1", "npm": "bundled" } }"#);
+        let mut project = Project::for_dir(dir.path().to_owned()).unwrap().unwrap();
+        project.write_platform_lock().unwrap();
+        assert_eq!(
+            project.platform_lock_status().unwrap(),
+            PlatformLockStatus::UpToDate
+        );
+
+        // Formatting changes and other fields don't affect the locked settings
+        fs::write(
+            dir.path().join("package.json"),
+            "{\n  \"name\": \"project\",\n  \"volta\": {\n    \"npm\": \"bundled\",\n    \"node\": \"18.12.1\"\n  }\n}\n",
+        )
+        .unwrap();
+        let reloaded = Project::for_dir(dir.path().to_owned()).unwrap().unwrap();
+        assert_eq!(
+            reloaded.platform_lock_status().unwrap(),
+            PlatformLockStatus::UpToDate
+        );
+
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{ "volta": { "node": "20.11.0", "yarn": "1.22.19" } }"#,
+        )
+        .unwrap();
+        let drifted = Project::for_dir(dir.path().to_owned()).unwrap().unwrap();
+        let platform = drifted.platform().unwrap();
+
+        // The locked platform is still used, but the lock file is reported as outdated
+        assert_eq!(
+            drifted.platform_lock_status().unwrap(),
+            PlatformLockStatus::Outdated
+        );
+        assert_eq!(platform.node, "18.12.1".parse().unwrap());
+        assert_eq!(platform.yarn, None);
+        assert!(drifted.pins_bundled_npm());
+    }
+
+    #[test]
+    fn unreadable_lock_file_falls_back_to_manifest() {
+        let dir = project_with_manifest(r#"{ "volta": { "node": "18.12.1" } }"#);
+        fs::write(dir.path().join("volta.lock"), r#"{ "version": 2 }"#).unwrap();
+
+        let project = Project::for_dir(dir.path().to_owned()).unwrap().unwrap();
+        assert!(Project::for_dir(dir.path().to_owned())
+            .unwrap()
+            .unwrap()
+            .check_platform_lock()
+            .is_err());
+        assert_eq!(project.platform().unwrap().node, "18.12.1".parse().unwrap());
+    }
+
+    #[test]
+    fn pins_make_lock_file_outdated() {
+        let dir = project_with_manifest(r#"{ "volta": { "node": "18.12.1" } }"#);
+        let mut project = Project::for_dir(dir.path().to_owned()).unwrap().unwrap();
+        project.write_platform_lock().unwrap();
+
+        project.pin_node("20.11.0".parse().unwrap()).unwrap();
+        assert_eq!(
+            project.platform_lock_status().unwrap(),
+            PlatformLockStatus::Outdated
+        );
+        assert_eq!(project.platform().unwrap().node, "20.11.0".parse().unwrap());
+
+        project.write_platform_lock().unwrap();
+        assert_eq!(
+            project.platform_lock_status().unwrap(),
+            PlatformLockStatus::UpToDate
+        );
+    }
+
+    #[test]
+    fn lock_file_requires_node() {
+        let dir = project_with_manifest(r#"{ "name": "project" }"#);
+        let mut project = Project::for_dir(dir.path().to_owned()).unwrap().unwrap();
+
+        let error = project.write_platform_lock().unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::NoProjectNodeInManifest));
+        assert!(!project.platform_lock_file().exists());
+    }
+}
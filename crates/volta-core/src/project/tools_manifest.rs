@@ -0,0 +1,130 @@
+//! Provides the `.volta/tools.json` file of a project, which lists the global packages that
+//! everyone working on the project needs, for `volta install --manifest`
+
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Context, ErrorKind, Fallible};
+use serde_json::{Map, Value};
+
+/// The global packages listed in a project's tools manifest
+#[derive(Debug)]
+pub struct ToolsManifest {
+    /// The path to the manifest file
+    pub file: PathBuf,
+    /// The packages with their requested versions, in the order of the file
+    pub packages: Vec<(String, String)>,
+}
+
+impl ToolsManifest {
+    /// Finds the tools manifest of the first of the roots that has one
+    ///
+    /// The roots are given nearest first, so a workspace member can replace the manifest that it
+    /// would otherwise share with the rest of the monorepo.
+    pub(super) fn find<'a, I>(roots: I) -> Option<PathBuf>
+    where
+        I: IntoIterator<Item = &'a Path>,
+    {
+        roots
+            .into_iter()
+            .map(|root| root.join(".volta").join("tools.json"))
+            .find(|file| file.is_file())
+    }
+
+    /// Reads the tools manifest from a file
+    pub fn from_file(file: &Path) -> Fallible<Self> {
+        let contents = read_to_string(file).with_context(|| ErrorKind::ReadToolsManifestError {
+            file: file.to_owned(),
+        })?;
+
+        Self::parse(&contents, file)
+    }
+
+    fn parse(contents: &str, file: &Path) -> Fallible<Self> {
+        let invalid = |error: String| ErrorKind::ParseToolsManifestError {
+            file: file.to_owned(),
+            error,
+        };
+
+        let mut manifest: Map<String, Value> =
+            serde_json::from_str(contents).map_err(|error| invalid(error.to_string()))?;
+
+        let packages = match manifest.remove("packages") {
+            Some(Value::Object(packages)) => packages,
+            Some(_) => return Err(invalid("`packages` must be an object".into()).into()),
+            None => Map::new(),
+        };
+
+        let packages = packages
+            .into_iter()
+            .map(|(name, version)| match version {
+                Value::String(version) => Ok((name, version)),
+                _ => Err(invalid(format!(
+                    "the version of `{}` must be a string, like \"^5\"",
+                    name
+                ))),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(ToolsManifest {
+            file: file.to_owned(),
+            packages,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> Fallible<ToolsManifest> {
+        ToolsManifest::parse(contents, Path::new("tools.json"))
+    }
+
+    #[test]
+    fn packages_are_kept_in_the_order_of_the_file() {
+        let manifest =
+            parse(r#"{ "packages": { "typescript": "^5", "eslint": "8.50.0" } }"#).unwrap();
+
+        assert_eq!(
+            manifest.packages,
+            [
+                ("typescript".to_string(), "^5".to_string()),
+                ("eslint".to_string(), "8.50.0".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_packages_are_an_empty_list() {
+        assert!(parse("{}").unwrap().packages.is_empty());
+    }
+
+    #[test]
+    fn versions_must_be_strings() {
+        assert!(parse(r#"{ "packages": { "typescript": 5 } }"#).is_err());
+        assert!(parse(r#"{ "packages": ["typescript"] }"#).is_err());
+    }
+
+    #[test]
+    fn nearest_root_with_a_manifest_wins() {
+        let monorepo = tempfile::tempdir().unwrap();
+        let member = monorepo.path().join("packages").join("app");
+        let other = monorepo.path().join("packages").join("lib");
+        for dir in [&monorepo.path().join(".volta"), &other.join(".volta")] {
+            std::fs::create_dir_all(dir).unwrap();
+            std::fs::write(dir.join("tools.json"), "{}").unwrap();
+        }
+        std::fs::create_dir_all(&member).unwrap();
+
+        assert_eq!(
+            ToolsManifest::find([member.as_path(), monorepo.path()]),
+            Some(monorepo.path().join(".volta").join("tools.json"))
+        );
+        assert_eq!(
+            ToolsManifest::find([other.as_path(), monorepo.path()]),
+            Some(other.join(".volta").join("tools.json"))
+        );
+        assert_eq!(ToolsManifest::find([member.as_path()]), None);
+    }
+}
@@ -0,0 +1,158 @@
+//! Infers the Node and npm versions of a project from the files in its root
+//!
+//! Projects that don't use Volta yet often record the Node version they expect in an `.nvmrc` or
+//! `.node-version` file, or as a range in the `engines` of their manifest. These are only read to
+//! suggest the versions to pin, so a file that can't be read or parsed is skipped rather than
+//! reported as an error.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::Fallible;
+use crate::tool::Spec;
+use crate::version::{VersionSpec, VersionTag};
+use log::{debug, warn};
+use serde_json::Value;
+
+/// The files that only hold a Node version, in the order that they take precedence
+const NODE_VERSION_FILES: [&str; 2] = [".nvmrc", ".node-version"];
+
+/// A version requirement for a tool that a file in the project root records
+#[derive(Debug, PartialEq, Eq)]
+pub struct FoundVersion {
+    pub file: PathBuf,
+    /// The setting that holds the requirement, or `None` for a file that only holds a version
+    pub field: Option<&'static str>,
+    pub requirement: String,
+}
+
+/// The version requirements found for a single tool, in the order that they take precedence
+#[derive(Debug)]
+pub struct InferredTool {
+    pub tool: &'static str,
+    pub found: Vec<FoundVersion>,
+}
+
+impl InferredTool {
+    /// Whether the files disagree about the version of the tool
+    pub fn is_ambiguous(&self) -> bool {
+        self.found
+            .iter()
+            .any(|found| found.requirement != self.found[0].requirement)
+    }
+
+    /// The spec to resolve and pin, from the requirement that takes precedence
+    pub fn spec(&self) -> Fallible<Spec> {
+        let version = self.found[0].requirement.parse()?;
+        Ok(match self.tool {
+            "npm" => Spec::Npm(version),
+            _ => Spec::Node(version),
+        })
+    }
+}
+
+/// Infers the Node and npm version requirements from the files in the given directory
+///
+/// Node versions are read from `.nvmrc`, `.node-version`, and the `engines` of `package.json` and
+/// of the root package in `package-lock.json`, while npm versions are only read from `engines`,
+/// since the version of npm otherwise follows the version of Node.
+pub(super) fn infer_tools(directory: &Path) -> Vec<InferredTool> {
+    let mut node = Vec::new();
+    for name in NODE_VERSION_FILES {
+        let file = directory.join(name);
+        let Ok(contents) = fs::read_to_string(&file) else {
+            continue;
+        };
+
+        match parse_version_file(&contents) {
+            Some(requirement) => node.push(FoundVersion {
+                file,
+                field: None,
+                requirement,
+            }),
+            None => warn!(
+                "Ignoring {}, as it doesn't hold a version that Volta can pin",
+                file.display()
+            ),
+        }
+    }
+
+    let mut npm = Vec::new();
+    let manifests = [
+        ("package.json", "/engines"),
+        ("package-lock.json", "/packages//engines"),
+    ];
+    for (name, pointer) in manifests {
+        let file = directory.join(name);
+        let Some(engines) = read_json(&file).and_then(|json| json.pointer(pointer).cloned()) else {
+            continue;
+        };
+
+        for (tool, field, found) in [
+            ("node", "engines.node", &mut node),
+            ("npm", "engines.npm", &mut npm),
+        ] {
+            if let Some(requirement) = engines.get(tool).and_then(Value::as_str) {
+                match parse_requirement(requirement) {
+                    Some(requirement) => found.push(FoundVersion {
+                        file: file.clone(),
+                        field: Some(field),
+                        requirement,
+                    }),
+                    None => debug!(
+                        "Ignoring {} in {}, as '{}' is not a version range",
+                        field,
+                        file.display(),
+                        requirement
+                    ),
+                }
+            }
+        }
+    }
+
+    [("node", node), ("npm", npm)]
+        .into_iter()
+        .filter(|(_, found)| !found.is_empty())
+        .map(|(tool, found)| InferredTool { tool, found })
+        .collect()
+}
+
+fn read_json(file: &Path) -> Option<Value> {
+    let contents = fs::read_to_string(file).ok()?;
+    serde_json::from_str(&contents)
+        .map_err(|error| debug!("Could not parse {}: {}", file.display(), error))
+        .ok()
+}
+
+/// Parses the version in an `.nvmrc` or `.node-version` file
+///
+/// Both hold a single line, which may be a version with a `v` prefix, a partial version like
+/// `18.16`, or an alias. Only the `lts/*` and `node` aliases of nvm can be pinned, as the aliases
+/// for LTS lines by name would need the Node index to resolve.
+pub(super) fn parse_version_file(contents: &str) -> Option<String> {
+    let line = contents
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))?;
+
+    match line {
+        "lts/*" => Some("lts".into()),
+        "node" | "stable" => Some("latest".into()),
+        _ => parse_requirement(line),
+    }
+}
+
+/// Normalizes a version requirement, if it is a version, a range, `lts`, or `latest`
+pub(super) fn parse_requirement(requirement: &str) -> Option<String> {
+    let requirement = requirement.trim();
+    match requirement.parse::<VersionSpec>().ok()? {
+        VersionSpec::Tag(VersionTag::Custom(_)) => None,
+        VersionSpec::Exact(version) => Some(version.to_string()),
+        _ => Some(
+            requirement
+                .strip_prefix(['v', 'V'])
+                .unwrap_or(requirement)
+                .to_owned(),
+        ),
+    }
+}
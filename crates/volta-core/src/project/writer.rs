@@ -0,0 +1,407 @@
+//! Writes the pins in a project manifest (`package.json`)
+//!
+//! Every command that changes a manifest goes through `update_manifest`, which guarantees that:
+//!
+//! - Only the `volta` hash is rewritten. Every other byte of the file is left as it was, including
+//!   the order and formatting of the other keys.
+//! - The hash is indented like the rest of the file, and its lines end like the first line of the
+//!   file, so a file with CRLF line endings keeps them.
+//! - A byte order mark at the start of the file is kept, and one is never added.
+//! - The keys of the hash are written in the order of `VOLTA_KEY_ORDER`, so pinning the same
+//!   versions always produces the same file.
+//! - A file that can only be read by ignoring comments or trailing commas is never rewritten, as
+//!   they couldn't be preserved.
+
+use std::fs::{read_to_string, write};
+use std::ops::Range;
+use std::path::Path;
+
+use super::serial::{parse_lenient, ManifestKey};
+use crate::error::{Context, ErrorKind, Fallible, VoltaError};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// The byte order mark, as some Windows editors write it at the start of a file
+const BOM: &str = "\u{feff}";
+
+/// The order the keys of the `volta` hash are written in. Any other keys follow, in the order
+/// they were already in.
+const VOLTA_KEY_ORDER: [&str; 5] = ["node", "npm", "pnpm", "yarn", "extends"];
+
+/// Updates the `volta` hash in the specified manifest with the given keys and values, writing
+/// the file once
+///
+/// Will create the `volta` hash if it isn't already present
+///
+/// If a value is `None`, will remove its key from the hash
+///
+/// See the module documentation for how the rest of the file is preserved.
+pub(super) fn update_manifest(
+    file: &Path,
+    updates: &[(ManifestKey, Option<String>)],
+) -> Fallible<()> {
+    let contents = read_to_string(file).with_context(|| ErrorKind::PackageReadError {
+        file: file.to_owned(),
+    })?;
+
+    let updated = updated_manifest(&contents, updates)
+        .ok_or_else(|| manifest_update_error(file, strip_bom(&contents).1))?;
+
+    write(file, updated).with_context(|| ErrorKind::PackageWriteError {
+        file: file.to_owned(),
+    })
+}
+
+/// Explains why the contents of a manifest couldn't be updated
+fn manifest_update_error(file: &Path, contents: &str) -> VoltaError {
+    let file = file.to_owned();
+    match serde_json::from_str::<Value>(contents) {
+        Err(error) if parse_lenient::<Value>(contents).is_some() => {
+            VoltaError::from_source(error, ErrorKind::ManifestNotStrictJson { file })
+        }
+        Err(error) => VoltaError::from_source(error, ErrorKind::PackageParseError { file }),
+        Ok(_) => ErrorKind::PackageParseError { file }.into(),
+    }
+}
+
+/// Applies the updates to the `volta` hash of the manifest contents, returning `None` if the
+/// contents aren't a JSON object
+fn updated_manifest(contents: &str, updates: &[(ManifestKey, Option<String>)]) -> Option<String> {
+    let (bom, body) = strip_bom(contents);
+    updated_body(body, updates).map(|updated| format!("{}{}", bom, updated))
+}
+
+/// Splits the byte order mark from the start of the contents, if there is one
+///
+/// JSON parsers reject the mark, but some Windows editors write it.
+pub(super) fn strip_bom(contents: &str) -> (&str, &str) {
+    match contents.strip_prefix(BOM) {
+        Some(body) => (BOM, body),
+        None => ("", contents),
+    }
+}
+
+/// The line ending of the first line of the contents, or `\n` for a single line
+fn newline_style(contents: &str) -> &'static str {
+    match contents.find('\n') {
+        Some(end) if contents[..end].ends_with('\r') => "\r\n",
+        _ => "\n",
+    }
+}
+
+fn updated_body(contents: &str, updates: &[(ManifestKey, Option<String>)]) -> Option<String> {
+    let manifest: Value = serde_json::from_str(contents).ok()?;
+    let existing = manifest.as_object()?.get("volta");
+
+    let mut hash = match existing {
+        Some(Value::Object(hash)) => hash.clone(),
+        _ => Map::new(),
+    };
+    for (key, value) in updates {
+        match value {
+            Some(v) => {
+                hash.insert(key.to_string(), Value::String(v.clone()));
+            }
+            None => {
+                hash.remove(&key.to_string());
+            }
+        }
+    }
+
+    // Removing a key from a manifest without a `volta` hash leaves the file as it was
+    if existing.is_none() && hash.is_empty() {
+        return Some(contents.to_owned());
+    }
+
+    let indent = match detect_indent::detect_indent(contents).indent() {
+        "" => "  ".to_owned(),
+        indent => indent.to_owned(),
+    };
+    let newline = newline_style(contents);
+    let volta = format_volta_hash(hash, &indent, newline);
+
+    let members = root_members(contents)?;
+    let mut updated = String::with_capacity(contents.len() + volta.len());
+    match members.iter().rev().find(|member| member.key == "volta") {
+        Some(member) => {
+            updated.push_str(&contents[..member.value.start]);
+            updated.push_str(&volta);
+            updated.push_str(&contents[member.value.end..]);
+        }
+        None => {
+            let (position, separator) = match members.last() {
+                Some(last) => (last.value.end, ","),
+                None => (contents.find('{')? + 1, ""),
+            };
+            updated.push_str(&contents[..position]);
+            updated.push_str(separator);
+            updated.push_str(newline);
+            updated.push_str(&indent);
+            updated.push_str("\"volta\": ");
+            updated.push_str(&volta);
+            if members.is_empty() {
+                updated.push_str(newline);
+            }
+            updated.push_str(&contents[position..]);
+        }
+    }
+
+    Some(updated)
+}
+
+/// Formats the `volta` hash as the value of a top-level key, with its keys in a fixed order
+fn format_volta_hash(mut hash: Map<String, Value>, indent: &str, newline: &str) -> String {
+    let mut ordered = Map::new();
+    for key in VOLTA_KEY_ORDER {
+        if let Some(value) = hash.remove(key) {
+            ordered.insert(key.to_owned(), value);
+        }
+    }
+    ordered.extend(hash);
+
+    let mut output = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut ser = serde_json::Serializer::with_formatter(&mut output, formatter);
+    Value::Object(ordered)
+        .serialize(&mut ser)
+        .expect("JSON values can always be serialized");
+
+    // Every line after the first is nested one level below the top-level object
+    String::from_utf8(output)
+        .expect("serialized JSON is valid UTF-8")
+        .replace('\n', &format!("{}{}", newline, indent))
+}
+
+/// A member of the top-level object of a JSON document
+struct Member<'a> {
+    /// The key, as written in the document (without its quotes)
+    key: &'a str,
+    /// The location of the value in the document
+    value: Range<usize>,
+}
+
+/// Finds the location of each member of the top-level object of a JSON document
+///
+/// Returns `None` if the document isn't an object.
+fn root_members(contents: &str) -> Option<Vec<Member<'_>>> {
+    let bytes = contents.as_bytes();
+    let mut pos = skip_whitespace(bytes, 0);
+    if bytes.get(pos) != Some(&b'{') {
+        return None;
+    }
+    pos += 1;
+
+    let mut members = Vec::new();
+    loop {
+        pos = skip_whitespace(bytes, pos);
+        match bytes.get(pos)? {
+            b'}' => return Some(members),
+            b',' => {
+                pos += 1;
+                continue;
+            }
+            b'"' => {}
+            _ => return None,
+        }
+
+        let key_end = skip_string(bytes, pos)?;
+        let key = &contents[pos + 1..key_end - 1];
+
+        pos = skip_whitespace(bytes, key_end);
+        if bytes.get(pos) != Some(&b':') {
+            return None;
+        }
+        let value_start = skip_whitespace(bytes, pos + 1);
+        let value_end = skip_value(bytes, value_start)?;
+
+        members.push(Member {
+            key,
+            value: value_start..value_end,
+        });
+        pos = value_end;
+    }
+}
+
+pub(super) fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while bytes.get(pos).is_some_and(u8::is_ascii_whitespace) {
+        pos += 1;
+    }
+    pos
+}
+
+/// Finds the end of the JSON value that starts at `pos`
+fn skip_value(bytes: &[u8], mut pos: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    loop {
+        let byte = *bytes.get(pos)?;
+        match byte {
+            b'"' => {
+                pos = skip_string(bytes, pos)?;
+                if depth == 0 {
+                    return Some(pos);
+                }
+            }
+            b'{' | b'[' => {
+                depth += 1;
+                pos += 1;
+            }
+            b'}' | b']' if depth > 0 => {
+                depth -= 1;
+                pos += 1;
+                if depth == 0 {
+                    return Some(pos);
+                }
+            }
+            // Numbers, `true`, `false`, and `null` end at the next delimiter
+            b',' | b'}' | b']' if depth == 0 => return Some(pos),
+            _ if depth == 0 && byte.is_ascii_whitespace() => return Some(pos),
+            _ => pos += 1,
+        }
+    }
+}
+
+/// Finds the end of the JSON string that starts at `pos`, just past its closing quote
+pub(super) fn skip_string(bytes: &[u8], mut pos: usize) -> Option<usize> {
+    pos += 1;
+    loop {
+        match bytes.get(pos)? {
+            b'\\' => pos += 2,
+            b'"' => return Some(pos + 1),
+            _ => pos += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::serial::Manifest;
+    use super::*;
+
+    /// Applies the updates to a copy of a fixture manifest, and checks it byte-for-byte against
+    /// the expected output
+    fn check_golden(name: &str, updates: &[(ManifestKey, Option<String>)]) {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/manifest");
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("package.json");
+        std::fs::copy(fixtures.join(format!("{}.json", name)), &file).unwrap();
+
+        update_manifest(&file, updates).unwrap();
+
+        assert_eq!(
+            read_to_string(&file).unwrap(),
+            read_to_string(fixtures.join(format!("{}.expected.json", name))).unwrap()
+        );
+    }
+
+    #[test]
+    fn golden_tab_indented_manifest() {
+        check_golden("tabs", &[(ManifestKey::Node, Some("18.19.0".into()))]);
+    }
+
+    #[test]
+    fn golden_four_space_indented_manifest() {
+        check_golden(
+            "four-spaces",
+            &[
+                (ManifestKey::Npm, Some("10.2.3".into())),
+                (ManifestKey::Node, Some("18.19.0".into())),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_manifest_without_trailing_newline() {
+        check_golden(
+            "no-trailing-newline",
+            &[
+                (ManifestKey::Node, Some("18.19.0".into())),
+                (ManifestKey::Pnpm, None),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_windows_manifest() {
+        check_golden(
+            "crlf",
+            &[
+                (ManifestKey::Node, Some("18.19.0".into())),
+                (ManifestKey::Npm, Some("10.2.3".into())),
+            ],
+        );
+    }
+
+    #[test]
+    fn golden_windows_manifest_with_byte_order_mark() {
+        check_golden("bom-crlf", &[(ManifestKey::Node, Some("18.19.0".into()))]);
+    }
+
+    #[test]
+    fn byte_order_mark_is_never_added() {
+        let updated = updated_manifest(
+            "{\n  \"name\": \"plain\"\n}\n",
+            &[(ManifestKey::Node, Some("18.19.0".into()))],
+        )
+        .unwrap();
+
+        assert!(!updated.starts_with(BOM));
+        assert!(updated_manifest("\u{feff}{}", &[(ManifestKey::Node, None)])
+            .unwrap()
+            .starts_with(BOM));
+    }
+
+    #[test]
+    fn first_line_ending_is_used_for_mixed_line_endings() {
+        let updated = updated_manifest(
+            "{\n  \"name\": \"mixed\"\r\n}\r\n",
+            &[(ManifestKey::Node, Some("18.19.0".into()))],
+        );
+
+        assert_eq!(
+            updated.unwrap(),
+            "{\n  \"name\": \"mixed\",\n  \"volta\": {\n    \"node\": \"18.19.0\"\n  }\r\n}\r\n"
+        );
+    }
+
+    #[test]
+    fn manifest_with_byte_order_mark_is_read() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/manifest");
+        let manifest = Manifest::from_file(&fixtures.join("bom-crlf.expected.json"));
+
+        assert!(manifest.unwrap().platform.is_some());
+    }
+
+    #[test]
+    fn manifest_line_endings_are_preserved() {
+        let updated = updated_manifest(
+            "{\r\n  \"name\": \"crlf\"\r\n}\r\n",
+            &[(ManifestKey::Node, Some("18.19.0".into()))],
+        );
+
+        assert_eq!(
+            updated.unwrap(),
+            "{\r\n  \"name\": \"crlf\",\r\n  \"volta\": {\r\n    \"node\": \"18.19.0\"\r\n  }\r\n}\r\n"
+        );
+    }
+
+    #[test]
+    fn volta_hash_is_added_to_empty_manifest() {
+        let updated = updated_manifest("{}", &[(ManifestKey::Node, Some("18.19.0".into()))]);
+
+        assert_eq!(
+            updated.unwrap(),
+            "{\n  \"volta\": {\n    \"node\": \"18.19.0\"\n  }\n}"
+        );
+    }
+
+    #[test]
+    fn removing_missing_volta_key_leaves_manifest_unchanged() {
+        let contents = "{ \"name\": \"compact\" }";
+
+        assert_eq!(
+            updated_manifest(contents, &[(ManifestKey::Yarn, None)]).unwrap(),
+            contents
+        );
+        assert_eq!(updated_manifest("[]", &[]), None);
+    }
+}
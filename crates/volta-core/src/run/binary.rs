@@ -2,7 +2,8 @@ use std::env;
 use std::ffi::{OsStr, OsString};
 use std::path::PathBuf;
 
-use super::executor::{Executor, ToolCommand, ToolKind};
+use super::engines;
+use super::executor::{ExecutionContext, Executor, ToolCommand, ToolKind};
 use super::{debug_active_image, debug_no_platform};
 use crate::error::{Context, ErrorKind, Fallible};
 use crate::layout::volta_home;
@@ -11,6 +12,13 @@ use crate::session::Session;
 use crate::tool::package::BinConfig;
 use log::debug;
 
+/// Environment variable set to the name of a binary when it is run with `pnpm exec`
+///
+/// If pnpm can't find the binary in the project either, it looks it up on the PATH and finds the
+/// shim again, which would then call `pnpm exec` forever. Seeing its own name in this variable
+/// tells the shim to report the missing binary instead.
+const PNPM_EXEC_ENV_VAR: &str = "_VOLTA_PNPM_EXEC";
+
 /// Determine the correct command to run for a 3rd-party binary
 ///
 /// Will detect if we should delegate to the project-local version or use the default version
@@ -20,17 +28,21 @@ pub(super) fn command(exe: &OsStr, args: &[OsString], session: &mut Session) ->
     if let Some(project) = session.project()? {
         // Check if the executable is a direct dependency
         if project.has_direct_bin(exe)? {
+            let engine_strict = project.engine_strict();
             match project.find_bin(exe) {
                 Some(path_to_bin) => {
                     debug!("Found {} in project at '{}'", bin, path_to_bin.display());
 
                     let platform = Platform::current(session)?;
-                    return Ok(ToolCommand::new(
+                    if let Some(platform) = &platform {
+                        engines::check(&path_to_bin, &platform.node.value, engine_strict, session)?;
+                    }
+                    return Ok(binary_command(
                         path_to_bin,
                         args,
                         platform,
                         ToolKind::ProjectLocalBinary(bin),
-                    )
+                    )?
                     .into());
                 }
                 None => {
@@ -49,6 +61,21 @@ pub(super) fn command(exe: &OsStr, args: &[OsString], session: &mut Session) ->
                             ToolKind::Yarn,
                         )
                         .into());
+                    } else if project.needs_pnpm_exec()
+                        && !is_pnpm_exec_recursion(exe)
+                        && session.pnpm_enabled()?
+                    {
+                        debug!(
+                            "Project needs to use pnpm to run command, calling {} with 'pnpm exec'",
+                            bin
+                        );
+                        let platform = Platform::current(session)?;
+                        let mut exe_and_args = vec![OsString::from("exec"), exe.to_os_string()];
+                        exe_and_args.extend_from_slice(args);
+                        let mut command =
+                            ToolCommand::new("pnpm", exe_and_args, platform, ToolKind::Pnpm);
+                        command.env(PNPM_EXEC_ENV_VAR, exe);
+                        return Ok(command.into());
                     } else {
                         return Err(ErrorKind::ProjectLocalBinaryNotFound {
                             command: exe.to_string_lossy().to_string(),
@@ -68,12 +95,12 @@ pub(super) fn command(exe: &OsStr, args: &[OsString], session: &mut Session) ->
             default_tool.bin_path.display()
         );
 
-        let mut command = ToolCommand::new(
+        let mut command = binary_command(
             default_tool.bin_path,
             args,
             Some(default_tool.platform),
             ToolKind::DefaultBinary(bin),
-        );
+        )?;
         command.env("NODE_PATH", shared_module_path()?);
 
         return Ok(command.into());
@@ -84,51 +111,85 @@ pub(super) fn command(exe: &OsStr, args: &[OsString], session: &mut Session) ->
     Ok(ToolCommand::new(exe, args, None, ToolKind::DefaultBinary(bin)).into())
 }
 
-/// Determine the execution context (PATH and failure error message) for a project-local binary
+/// Determines whether the binary was already handed to `pnpm exec`, which came back to its shim
+fn is_pnpm_exec_recursion(exe: &OsStr) -> bool {
+    env::var_os(PNPM_EXEC_ENV_VAR).is_some_and(|bin| bin == exe)
+}
+
+/// Create the command to run a binary at the given path
+///
+/// On Windows, a Node script that Windows can't launch itself is run as `node <script>` instead,
+/// using the Node from the same platform.
+fn binary_command(
+    bin_path: PathBuf,
+    args: &[OsString],
+    platform: Option<Platform>,
+    kind: ToolKind,
+) -> Fallible<ToolCommand> {
+    #[cfg(windows)]
+    if super::shebang::is_node_script(&bin_path)? {
+        debug!("Running '{}' with node", bin_path.display());
+        let mut node_args = vec![bin_path.into_os_string()];
+        node_args.extend_from_slice(args);
+        return Ok(ToolCommand::new("node", node_args, platform, kind));
+    }
+
+    Ok(ToolCommand::new(bin_path, args, platform, kind))
+}
+
+/// Determine the execution context (PATH, failure error message and image) for a project-local binary
 pub(super) fn local_execution_context(
     tool: String,
     platform: Option<Platform>,
     session: &mut Session,
-) -> Fallible<(OsString, ErrorKind)> {
+) -> Fallible<ExecutionContext> {
     match platform {
         Some(plat) => {
             let image = plat.checkout(session)?;
             let path = image.path()?;
             debug_active_image(&image);
 
-            Ok((
+            Ok(ExecutionContext::image(
                 path,
                 ErrorKind::ProjectLocalBinaryExecError { command: tool },
+                image,
             ))
         }
         None => {
             let path = System::path()?;
             debug_no_platform();
 
-            Ok((path, ErrorKind::NoPlatform))
+            Ok(ExecutionContext::system(path, ErrorKind::NoPlatform))
         }
     }
 }
 
-/// Determine the execution context (PATH and failure error message) for a default binary
+/// Determine the execution context (PATH, failure error message and image) for a default binary
 pub(super) fn default_execution_context(
     tool: String,
     platform: Option<Platform>,
     session: &mut Session,
-) -> Fallible<(OsString, ErrorKind)> {
+) -> Fallible<ExecutionContext> {
     match platform {
         Some(plat) => {
             let image = plat.checkout(session)?;
             let path = image.path()?;
             debug_active_image(&image);
 
-            Ok((path, ErrorKind::BinaryExecError))
+            Ok(ExecutionContext::image(
+                path,
+                ErrorKind::BinaryExecError,
+                image,
+            ))
         }
         None => {
             let path = System::path()?;
             debug_no_platform();
 
-            Ok((path, ErrorKind::BinaryNotFound { name: tool }))
+            Ok(ExecutionContext::system(
+                path,
+                ErrorKind::BinaryNotFound { name: tool },
+            ))
         }
     }
 }
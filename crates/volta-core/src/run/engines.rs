@@ -0,0 +1,192 @@
+//! Checks the `engines.node` requirement of the package that owns a project-local binary
+//!
+//! The check is opt-in: a project with `volta.engineStrict: true` refuses to run a binary whose
+//! package requires a different Node version than the platform's, while a project without the
+//! setting only gets a warning. Binaries whose package can't be found or doesn't declare a
+//! requirement always run.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{ErrorKind, Fallible};
+use crate::session::Session;
+use dunce::canonicalize;
+use log::{debug, warn};
+use node_semver::{Range, Version};
+use serde::Deserialize;
+
+/// The parts of a dependency's manifest that the check needs
+pub(crate) struct PackageEngines {
+    name: String,
+    node: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawManifest {
+    name: Option<String>,
+    engines: Option<RawEngines>,
+}
+
+#[derive(Deserialize)]
+struct RawEngines {
+    node: Option<String>,
+}
+
+impl PackageEngines {
+    /// Reads the engines from the manifest in a package directory, if it can be parsed
+    pub(crate) fn read(package_dir: &Path) -> Option<Self> {
+        let file = package_dir.join("package.json");
+        let contents = fs::read_to_string(&file).ok()?;
+        let manifest: RawManifest = match serde_json::from_str(&contents) {
+            Ok(manifest) => manifest,
+            Err(error) => {
+                debug!("Could not parse '{}': {}", file.display(), error);
+                return None;
+            }
+        };
+
+        // Packages are installed under their name, so the directory name is a good fallback
+        let name = manifest.name.or_else(|| {
+            package_dir
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })?;
+
+        Some(PackageEngines {
+            name,
+            node: manifest.engines.and_then(|engines| engines.node),
+        })
+    }
+}
+
+/// Checks that the package owning a project-local binary accepts the platform's Node version
+///
+/// `strict` is the project's `volta.engineStrict` setting: with `Some(true)` an incompatible
+/// package is an error, with `None` it is a warning, and with `Some(false)` nothing is checked.
+pub(super) fn check(
+    bin_path: &Path,
+    node: &Version,
+    strict: Option<bool>,
+    session: &mut Session,
+) -> Fallible<()> {
+    if strict == Some(false) {
+        return Ok(());
+    }
+
+    let Some(package_dir) = owning_package(bin_path) else {
+        debug!(
+            "Could not find the package that provides '{}', skipping the engines check",
+            bin_path.display()
+        );
+        return Ok(());
+    };
+    let Some(engines) = session.package_engines(&package_dir) else {
+        return Ok(());
+    };
+    let Some(required) = &engines.node else {
+        debug!("'{}' doesn't declare `engines.node`", engines.name);
+        return Ok(());
+    };
+
+    match Range::parse(required) {
+        Ok(range) if range.satisfies(node) => {
+            debug!(
+                "'{}' accepts Node {} (requires {})",
+                engines.name, node, required
+            );
+            Ok(())
+        }
+        Ok(_) if strict == Some(true) => Err(ErrorKind::IncompatibleNodeEngine {
+            package: engines.name.clone(),
+            required: required.clone(),
+            node: node.to_string(),
+        }
+        .into()),
+        Ok(_) => {
+            warn!(
+                "'{}' requires Node {}, which doesn't match Node {} used by this project. Set `volta.engineStrict` to true in package.json to stop it from running.",
+                engines.name, required, node
+            );
+            Ok(())
+        }
+        Err(error) => {
+            debug!(
+                "Could not parse `engines.node` of '{}' ('{}'): {}",
+                engines.name, required, error
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Finds the directory of the package that provides a binary in `node_modules/.bin`
+///
+/// Package managers link each binary to a file within its package, so the link is resolved and
+/// the directories above the file are searched for a manifest. The search stops at the
+/// `node_modules` directory, so the project's own manifest is never used. A binary that isn't a
+/// link (such as a Windows command script) has no owning package.
+fn owning_package(bin_path: &Path) -> Option<PathBuf> {
+    let target = canonicalize(bin_path).ok()?;
+
+    target
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| dir.file_name().is_some_and(|name| name != "node_modules"))
+        .find(|dir| dir.join("package.json").is_file())
+        .map(Path::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn finds_package_through_bin_link() {
+        let dir = tempfile::tempdir().unwrap();
+        let modules = dir.path().join("node_modules");
+        let package = modules.join("cowsay");
+        fs::create_dir_all(package.join("cli")).unwrap();
+        fs::create_dir_all(modules.join(".bin")).unwrap();
+        fs::write(package.join("package.json"), "{}").unwrap();
+        fs::write(package.join("cli/index.js"), "").unwrap();
+        std::os::unix::fs::symlink("../cowsay/cli/index.js", modules.join(".bin/cowsay")).unwrap();
+
+        assert_eq!(
+            owning_package(&modules.join(".bin/cowsay")),
+            Some(canonicalize(&package).unwrap())
+        );
+    }
+
+    #[test]
+    fn stops_at_node_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        let bin_dir = dir.path().join("node_modules/.bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+        fs::write(dir.path().join("package.json"), "{}").unwrap();
+        fs::write(bin_dir.join("cowsay"), "").unwrap();
+
+        assert_eq!(owning_package(&bin_dir.join("cowsay")), None);
+    }
+
+    #[test]
+    fn reads_name_and_node_engine() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = dir.path().join("cowsay");
+        fs::create_dir_all(&package).unwrap();
+
+        fs::write(
+            package.join("package.json"),
+            r#"{ "name": "@cows/say", "engines": { "node": ">=18" } }"#,
+        )
+        .unwrap();
+        let engines = PackageEngines::read(&package).unwrap();
+        assert_eq!(engines.name, "@cows/say");
+        assert_eq!(engines.node.as_deref(), Some(">=18"));
+
+        fs::write(package.join("package.json"), r#"{ "engines": {} }"#).unwrap();
+        let engines = PackageEngines::read(&package).unwrap();
+        assert_eq!(engines.name, "cowsay");
+        assert_eq!(engines.node, None);
+    }
+}
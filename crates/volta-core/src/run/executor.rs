@@ -1,23 +1,30 @@
 use std::collections::HashMap;
-use std::ffi::OsStr;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::io;
 #[cfg(unix)]
 use std::os::unix::process::ExitStatusExt;
 #[cfg(windows)]
 use std::os::windows::process::ExitStatusExt;
+use std::path::PathBuf;
 use std::process::{Command, ExitStatus};
 
 use super::RECURSION_ENV_VAR;
 use crate::command::create_command;
 use crate::error::{Context, ErrorKind, Fallible};
 use crate::layout::volta_home;
-use crate::platform::{CliPlatform, Platform, System};
+use crate::platform::{CliPlatform, Image, OptionalTool, Platform, System};
 use crate::session::Session;
 use crate::signal::pass_control_to_shim;
 use crate::style::{note_prefix, tool_version};
 use crate::sync::VoltaLock;
-use crate::tool::package::{DirectInstall, InPlaceUpgrade, PackageConfig, PackageManager};
+use crate::tool::package::{
+    registry, DirectInstall, InPlaceUpgrade, PackageConfig, PackageManager,
+};
 use crate::tool::Spec;
+use crate::usage;
 use log::{info, warn};
+use which::which_in;
 
 pub enum Executor {
     Tool(Box<ToolCommand>),
@@ -70,6 +77,17 @@ impl Executor {
         }
     }
 
+    /// Resolves the executable that `execute` would run, without running it
+    ///
+    /// Only a single tool command can be located: the other executors are implemented by Volta
+    /// itself or run more than one command, so they return `None`.
+    pub fn locate(self, session: &mut Session) -> Fallible<Option<Location>> {
+        match self {
+            Executor::Tool(cmd) => cmd.locate(session).map(Some),
+            _ => Ok(None),
+        }
+    }
+
     pub fn execute(self, session: &mut Session) -> Fallible<ExitStatus> {
         match self {
             Executor::Tool(cmd) => cmd.execute(session),
@@ -77,7 +95,7 @@ impl Executor {
             Executor::PackageLink(cmd) => cmd.execute(session),
             Executor::PackageUpgrade(cmd) => cmd.execute(session),
             Executor::InternalInstall(cmd) => cmd.execute(session),
-            Executor::Uninstall(cmd) => cmd.execute(),
+            Executor::Uninstall(cmd) => cmd.execute(session),
             Executor::Multiple(executors) => {
                 info!(
                     "{} Volta is processing each package separately",
@@ -114,6 +132,7 @@ impl From<Vec<Executor>> for Executor {
 /// to customize the behavior before execution.
 pub struct ToolCommand {
     command: Command,
+    exe: OsString,
     platform: Option<Platform>,
     kind: ToolKind,
 }
@@ -137,11 +156,12 @@ impl ToolCommand {
         A: IntoIterator<Item = S>,
         S: AsRef<OsStr>,
     {
-        let mut command = create_command(exe);
+        let mut command = create_command(&exe);
         command.args(args);
 
         Self {
             command,
+            exe: exe.as_ref().to_os_string(),
             platform,
             kind,
         }
@@ -176,29 +196,148 @@ impl ToolCommand {
 
     /// Runs the command, returning the `ExitStatus` if it successfully launches
     pub fn execute(mut self, session: &mut Session) -> Fallible<ExitStatus> {
-        let (path, on_failure) = match self.kind {
-            ToolKind::Node => super::node::execution_context(self.platform, session)?,
-            ToolKind::Npm => super::npm::execution_context(self.platform, session)?,
-            ToolKind::Npx => super::npx::execution_context(self.platform, session)?,
-            ToolKind::Pnpm => super::pnpm::execution_context(self.platform, session)?,
-            ToolKind::Yarn => super::yarn::execution_context(self.platform, session)?,
+        let context = self.kind.execution_context(self.platform, session)?;
+        if let Some(image) = &context.image {
+            usage::record(session, image, self.kind.package_manager());
+        }
+
+        self.command.env(RECURSION_ENV_VAR, "1");
+        self.command.env("PATH", &context.path);
+
+        pass_control_to_shim();
+        match self.command.status() {
+            Ok(status) => Ok(status),
+            Err(error) => {
+                let kind = match non_executable_binary(&error, &self.exe, &context.path) {
+                    Some(binary) => ErrorKind::BinaryNotExecutable { binary },
+                    None => context.on_failure,
+                };
+                Err(error).with_context(|| kind)
+            }
+        }
+    }
+
+    /// Finds the executable that the command would run, using the same PATH as `execute`
+    pub fn locate(self, session: &mut Session) -> Fallible<Location> {
+        let context = self.kind.execution_context(self.platform, session)?;
+        let cwd = env::current_dir().with_context(|| ErrorKind::CurrentDirError)?;
+        let executable =
+            which_in(&self.exe, Some(&context.path), cwd).with_context(|| context.on_failure)?;
+
+        Ok(Location {
+            executable,
+            image: context.image,
+        })
+    }
+}
+
+/// Finds the binary that a command couldn't launch because it isn't executable
+///
+/// Launching a binary that isn't executable fails with a generic permission error, so this looks
+/// for the binary on the command's PATH to report the file that needs its permissions fixed.
+#[cfg(unix)]
+fn non_executable_binary(error: &io::Error, exe: &OsStr, path: &OsStr) -> Option<PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if error.kind() != io::ErrorKind::PermissionDenied {
+        return None;
+    }
+
+    env::split_paths(path)
+        .map(|dir| dir.join(exe))
+        .find(|candidate| match candidate.metadata() {
+            Ok(metadata) => metadata.is_file() && metadata.permissions().mode() & 0o111 == 0,
+            Err(_) => false,
+        })
+}
+
+/// Finds the binary that a command couldn't launch because it isn't executable
+///
+/// Windows doesn't have an executable permission, so any failure is reported as is.
+#[cfg(windows)]
+fn non_executable_binary(_error: &io::Error, _exe: &OsStr, _path: &OsStr) -> Option<PathBuf> {
+    None
+}
+
+impl ToolKind {
+    /// Determines the PATH for the command, checking out the platform if there is one
+    fn execution_context(
+        &self,
+        platform: Option<Platform>,
+        session: &mut Session,
+    ) -> Fallible<ExecutionContext> {
+        match self {
+            ToolKind::Node => super::node::execution_context(platform, session),
+            ToolKind::Npm => super::npm::execution_context(platform, session),
+            ToolKind::Npx => super::npx::execution_context(platform, session),
+            ToolKind::Pnpm => super::pnpm::execution_context(platform, session),
+            ToolKind::Yarn => super::yarn::execution_context(platform, session),
             ToolKind::DefaultBinary(bin) => {
-                super::binary::default_execution_context(bin, self.platform, session)?
+                super::binary::default_execution_context(bin.clone(), platform, session)
             }
             ToolKind::ProjectLocalBinary(bin) => {
-                super::binary::local_execution_context(bin, self.platform, session)?
+                super::binary::local_execution_context(bin.clone(), platform, session)
             }
-            ToolKind::Bypass(command) => (System::path()?, ErrorKind::BypassError { command }),
-        };
+            ToolKind::Bypass(command) => Ok(ExecutionContext::system(
+                System::path()?,
+                ErrorKind::BypassError {
+                    command: command.clone(),
+                },
+            )),
+        }
+    }
 
-        self.command.env(RECURSION_ENV_VAR, "1");
-        self.command.env("PATH", path);
+    /// The package manager whose usage is recorded alongside Node when the command runs
+    fn package_manager(&self) -> Option<OptionalTool> {
+        match self {
+            ToolKind::Npm | ToolKind::Npx => Some(OptionalTool::Npm),
+            ToolKind::Pnpm => Some(OptionalTool::Pnpm),
+            ToolKind::Yarn => Some(OptionalTool::Yarn),
+            ToolKind::Node
+            | ToolKind::ProjectLocalBinary(_)
+            | ToolKind::DefaultBinary(_)
+            | ToolKind::Bypass(_) => None,
+        }
+    }
+}
 
-        pass_control_to_shim();
-        self.command.status().with_context(|| on_failure)
+/// The environment that a tool command runs in
+pub(super) struct ExecutionContext {
+    /// The PATH for the command
+    path: OsString,
+    /// The error to report if the command can't be launched
+    on_failure: ErrorKind,
+    /// The image that provides the PATH, if the command runs with a Volta-managed platform
+    image: Option<Image>,
+}
+
+impl ExecutionContext {
+    /// A context using the PATH of a checked-out image
+    pub(super) fn image(path: OsString, on_failure: ErrorKind, image: Image) -> Self {
+        ExecutionContext {
+            path,
+            on_failure,
+            image: Some(image),
+        }
+    }
+
+    /// A context using the system PATH, without a Volta-managed platform
+    pub(super) fn system(path: OsString, on_failure: ErrorKind) -> Self {
+        ExecutionContext {
+            path,
+            on_failure,
+            image: None,
+        }
     }
 }
 
+/// The executable that a tool command resolves to, along with the image it would run with
+pub struct Location {
+    pub executable: PathBuf,
+    /// The image that provides the PATH, or `None` if the command would use the system PATH
+    pub image: Option<Image>,
+}
+
 impl From<ToolCommand> for Executor {
     fn from(cmd: ToolCommand) -> Self {
         Executor::Tool(Box::new(cmd))
@@ -282,6 +421,9 @@ impl PackageInstallCommand {
         self.command.env(RECURSION_ENV_VAR, "1");
         self.command.env("PATH", path);
         self.installer.setup_command(&mut self.command);
+        if let Some(registry) = registry(session)? {
+            self.command.args(["--registry", registry]);
+        }
 
         let status = self
             .command
@@ -289,7 +431,8 @@ impl PackageInstallCommand {
             .with_context(|| ErrorKind::BinaryExecError)?;
 
         if status.success() {
-            self.installer.complete_install(&image)?;
+            let changes = self.installer.complete_install(&image)?;
+            session.record_changes(changes);
         }
 
         Ok(status)
@@ -475,6 +618,9 @@ impl PackageUpgradeCommand {
         self.command.env(RECURSION_ENV_VAR, "1");
         self.command.env("PATH", path);
         self.upgrader.setup_command(&mut self.command);
+        if let Some(registry) = registry(session)? {
+            self.command.args(["--registry", registry]);
+        }
 
         let status = self
             .command
@@ -482,7 +628,8 @@ impl PackageUpgradeCommand {
             .with_context(|| ErrorKind::BinaryExecError)?;
 
         if status.success() {
-            self.upgrader.complete_upgrade(&image)?;
+            let changes = self.upgrader.complete_upgrade(&image)?;
+            session.record_changes(changes);
         }
 
         Ok(status)
@@ -543,14 +690,14 @@ impl UninstallCommand {
     }
 
     /// Runs the uninstall with Volta's internal uninstall logic
-    fn execute(self) -> Fallible<ExitStatus> {
+    fn execute(self, session: &mut Session) -> Fallible<ExitStatus> {
         info!(
             "{} using Volta to uninstall {}",
             note_prefix(),
             self.tool.name()
         );
 
-        self.tool.uninstall()?;
+        self.tool.uninstall(session)?;
 
         Ok(ExitStatus::from_raw(0))
     }
@@ -561,3 +708,53 @@ impl From<UninstallCommand> for Executor {
         Executor::Uninstall(Box::new(cmd))
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn permission_denied() -> io::Error {
+        io::Error::from(io::ErrorKind::PermissionDenied)
+    }
+
+    #[test]
+    fn finds_non_executable_binary_on_path() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        let binary = second.path().join("node");
+        fs::write(&binary, "").unwrap();
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let path = env::join_paths([first.path(), second.path()]).unwrap();
+        assert_eq!(
+            non_executable_binary(&permission_denied(), OsStr::new("node"), &path),
+            Some(binary)
+        );
+    }
+
+    #[test]
+    fn ignores_executable_binaries_and_other_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("node");
+        fs::write(&binary, "").unwrap();
+        let path = dir.path().as_os_str();
+
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(
+            non_executable_binary(&permission_denied(), OsStr::new("node"), path),
+            None
+        );
+
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(
+            non_executable_binary(
+                &io::Error::from(io::ErrorKind::NotFound),
+                OsStr::new("node"),
+                path
+            ),
+            None
+        );
+    }
+}
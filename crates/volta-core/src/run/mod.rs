@@ -5,21 +5,28 @@ use std::path::Path;
 use std::process::ExitStatus;
 
 use crate::error::{ErrorKind, Fallible};
+use crate::layout::{volta_home, volta_install};
 use crate::platform::{CliPlatform, Image, Sourced};
 use crate::session::Session;
-use crate::VOLTA_FEATURE_PNPM;
+use crate::shim;
 use log::debug;
 use node_semver::Version;
 
 pub mod binary;
+pub(crate) mod engines;
 mod executor;
 mod node;
 mod npm;
 mod npx;
 mod parser;
 mod pnpm;
+// Only Windows needs help to run scripts that start with a `#!` line
+#[cfg(any(windows, test))]
+mod shebang;
 mod yarn;
 
+pub use executor::Location;
+
 /// Environment variable set internally when a shim has been executed and the context evaluated
 ///
 /// This is set when executing a shim command. If this is already, then the built-in shims (Node,
@@ -61,11 +68,38 @@ where
 
     let mut runner = get_executor(exe, args, session)?;
     runner.cli_platform(cli);
+    // The executor sets its own variables (such as the recursion guard) when it runs, so these
+    // can't override them
     runner.envs(envs);
 
     runner.execute(session)
 }
 
+/// Resolve the executable that `execute_tool` would run with the provided arguments, without
+/// running it
+///
+/// The platform is checked out exactly as it would be to run the tool, so missing tools are
+/// fetched unless the session doesn't allow it.
+pub fn locate_tool(
+    exe: &OsStr,
+    args: &[OsString],
+    cli: CliPlatform,
+    session: &mut Session,
+) -> Fallible<Location> {
+    // Evaluate the context as `execute_tool` would, even when called from a Node script
+    env::remove_var(RECURSION_ENV_VAR);
+
+    let mut runner = get_executor(exe, args, session)?;
+    runner.cli_platform(cli);
+
+    runner.locate(session)?.ok_or_else(|| {
+        ErrorKind::RunWhereUnsupported {
+            command: exe.to_string_lossy().to_string(),
+        }
+        .into()
+    })
+}
+
 /// Get the appropriate Tool command, based on the requested executable and arguments
 fn get_executor(
     exe: &OsStr,
@@ -83,6 +117,12 @@ fn get_executor(
     } else {
         match exe.to_str() {
             Some("volta-shim") => Err(ErrorKind::RunShimDirectly.into()),
+            Some(name) if shim::is_reserved(name) => Err(ErrorKind::ShimShadowsVolta {
+                name: name.to_owned(),
+                shim: volta_home()?.shim_file(name),
+                volta: volta_install()?.main_executable().to_owned(),
+            }
+            .into()),
             Some("node") => node::command(args, session),
             Some("npm") => npm::command(args, session),
             Some("npx") => npx::command(args, session),
@@ -90,7 +130,7 @@ fn get_executor(
                 // If the pnpm feature flag variable is set, delegate to the pnpm handler
                 // If not, use the binary handler as a fallback (prior to pnpm support, installing
                 // pnpm would be handled the same as any other global binary)
-                if env::var_os(VOLTA_FEATURE_PNPM).is_some() {
+                if session.pnpm_enabled()? {
                     pnpm::command(args, session)
                 } else {
                     binary::command(exe, args, session)
@@ -1,7 +1,7 @@
 use std::env;
 use std::ffi::OsString;
 
-use super::executor::{Executor, ToolCommand, ToolKind};
+use super::executor::{ExecutionContext, Executor, ToolCommand, ToolKind};
 use super::{debug_active_image, debug_no_platform, RECURSION_ENV_VAR};
 use crate::error::{ErrorKind, Fallible};
 use crate::platform::{Platform, System};
@@ -9,7 +9,7 @@ use crate::session::{ActivityKind, Session};
 
 /// Build a `ToolCommand` for Node
 pub(super) fn command(args: &[OsString], session: &mut Session) -> Fallible<Executor> {
-    session.add_event_start(ActivityKind::Node);
+    session.add_event_subcommand_start(ActivityKind::Node);
     // Don't re-evaluate the platform if this is a recursive call
     let platform = match env::var_os(RECURSION_ENV_VAR) {
         Some(_) => None,
@@ -19,23 +19,27 @@ pub(super) fn command(args: &[OsString], session: &mut Session) -> Fallible<Exec
     Ok(ToolCommand::new("node", args, platform, ToolKind::Node).into())
 }
 
-/// Determine the execution context (PATH and failure error message) for Node
+/// Determine the execution context (PATH, failure error message and image) for Node
 pub(super) fn execution_context(
     platform: Option<Platform>,
     session: &mut Session,
-) -> Fallible<(OsString, ErrorKind)> {
+) -> Fallible<ExecutionContext> {
     match platform {
         Some(plat) => {
             let image = plat.checkout(session)?;
             let path = image.path()?;
             debug_active_image(&image);
 
-            Ok((path, ErrorKind::BinaryExecError))
+            Ok(ExecutionContext::image(
+                path,
+                ErrorKind::BinaryExecError,
+                image,
+            ))
         }
         None => {
             let path = System::path()?;
             debug_no_platform();
-            Ok((path, ErrorKind::NoPlatform))
+            Ok(ExecutionContext::system(path, ErrorKind::NoPlatform))
         }
     }
 }
@@ -2,7 +2,7 @@ use std::env;
 use std::ffi::OsString;
 use std::fs::File;
 
-use super::executor::{Executor, ToolCommand, ToolKind, UninstallCommand};
+use super::executor::{ExecutionContext, Executor, ToolCommand, ToolKind, UninstallCommand};
 use super::parser::{CommandArg, InterceptedCommand};
 use super::{debug_active_image, debug_no_platform, RECURSION_ENV_VAR};
 use crate::error::{ErrorKind, Fallible};
@@ -15,21 +15,24 @@ use crate::version::VersionSpec;
 ///
 /// If the command is a global install or uninstall and we have a default platform available, then
 /// we will use custom logic to ensure that the package is correctly installed / uninstalled in the
-/// Volta directory.
+/// Volta directory. Packages listed in the `install.unmanagedGlobals` setting are the exception,
+/// and are installed by npm itself.
 ///
 /// If the command is _not_ a global install / uninstall or we don't have a default platform, then
 /// we will allow npm to execute the command as usual.
 pub(super) fn command(args: &[OsString], session: &mut Session) -> Fallible<Executor> {
-    session.add_event_start(ActivityKind::Npm);
+    session.add_event_subcommand_start(ActivityKind::Npm);
     // Don't re-evaluate the context or global install interception if this is a recursive call
     let platform = match env::var_os(RECURSION_ENV_VAR) {
         Some(_) => None,
+        // With unsafe globals, global installs are left to npm itself
+        None if session.unsafe_global()? => Platform::current(session)?,
         None => {
             match CommandArg::for_npm(args) {
                 CommandArg::Global(cmd) => {
                     // For globals, only intercept if the default platform exists
                     if let Some(default_platform) = session.default_platform()? {
-                        return cmd.executor(default_platform);
+                        return cmd.executor(default_platform, session.settings()?);
                     }
                 }
                 CommandArg::Intercepted(InterceptedCommand::Link(link)) => {
@@ -62,23 +65,27 @@ pub(super) fn command(args: &[OsString], session: &mut Session) -> Fallible<Exec
     Ok(ToolCommand::new("npm", args, platform, ToolKind::Npm).into())
 }
 
-/// Determine the execution context (PATH and failure error message) for npm
+/// Determine the execution context (PATH, failure error message and image) for npm
 pub(super) fn execution_context(
     platform: Option<Platform>,
     session: &mut Session,
-) -> Fallible<(OsString, ErrorKind)> {
+) -> Fallible<ExecutionContext> {
     match platform {
         Some(plat) => {
             let image = plat.checkout(session)?;
             let path = image.path()?;
             debug_active_image(&image);
 
-            Ok((path, ErrorKind::BinaryExecError))
+            Ok(ExecutionContext::image(
+                path,
+                ErrorKind::BinaryExecError,
+                image,
+            ))
         }
         None => {
             let path = System::path()?;
             debug_no_platform();
-            Ok((path, ErrorKind::NoPlatform))
+            Ok(ExecutionContext::system(path, ErrorKind::NoPlatform))
         }
     }
 }
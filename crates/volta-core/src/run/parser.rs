@@ -1,19 +1,18 @@
-use std::env;
 use std::ffi::OsStr;
 use std::iter::once;
 
 use super::executor::{
     Executor, InternalInstallCommand, PackageInstallCommand, PackageLinkCommand,
-    PackageUpgradeCommand, UninstallCommand,
+    PackageUpgradeCommand, ToolCommand, ToolKind, UninstallCommand,
 };
 use crate::error::{ErrorKind, Fallible};
 use crate::inventory::package_configs;
 use crate::platform::{Platform, PlatformSpec};
+use crate::settings::Settings;
 use crate::tool::package::PackageManager;
 use crate::tool::Spec;
-use log::debug;
+use log::{debug, info};
 
-const UNSAFE_GLOBAL: &str = "VOLTA_UNSAFE_GLOBAL";
 /// Aliases that npm supports for the 'install' command
 const NPM_INSTALL_ALIASES: [&str; 12] = [
     "i", "in", "ins", "inst", "insta", "instal", "install", "isnt", "isnta", "isntal", "isntall",
@@ -47,11 +46,6 @@ impl<'a> CommandArg<'a> {
     where
         S: AsRef<OsStr>,
     {
-        // If VOLTA_UNSAFE_GLOBAL is set, then we always skip any interception parsing
-        if env::var_os(UNSAFE_GLOBAL).is_some() {
-            return CommandArg::Standard;
-        }
-
         let mut positionals = args.iter().filter(is_positional).map(AsRef::as_ref);
 
         // The first positional argument will always be the command, however npm supports multiple
@@ -141,11 +135,6 @@ impl<'a> CommandArg<'a> {
     where
         S: AsRef<OsStr>,
     {
-        // If VOLTA_UNSAFE_GLOBAL is set, then we always skip any global parsing
-        if env::var_os(UNSAFE_GLOBAL).is_some() {
-            return CommandArg::Standard;
-        }
-
         let (flags, positionals): (Vec<&OsStr>, Vec<&OsStr>) =
             args.iter().map(AsRef::<OsStr>::as_ref).partition(is_flag);
 
@@ -214,11 +203,6 @@ impl<'a> CommandArg<'a> {
     where
         S: AsRef<OsStr>,
     {
-        // If VOLTA_UNSAFE_GLOBAL is set, then we always skip any global parsing
-        if env::var_os(UNSAFE_GLOBAL).is_some() {
-            return CommandArg::Standard;
-        }
-
         let mut positionals = args.iter().filter(is_positional).map(AsRef::as_ref);
 
         // Yarn globals must always start with `global <command>`
@@ -275,9 +259,9 @@ pub enum GlobalCommand<'a> {
 }
 
 impl GlobalCommand<'_> {
-    pub fn executor(self, platform: &PlatformSpec) -> Fallible<Executor> {
+    pub fn executor(self, platform: &PlatformSpec, settings: &Settings) -> Fallible<Executor> {
         match self {
-            GlobalCommand::Install(cmd) => cmd.executor(platform),
+            GlobalCommand::Install(cmd) => cmd.executor(platform, settings),
             GlobalCommand::Uninstall(cmd) => cmd.executor(),
             GlobalCommand::Upgrade(cmd) => cmd.executor(platform),
         }
@@ -300,15 +284,32 @@ impl InstallArgs<'_> {
     /// If there are multiple packages specified to install, then they will be broken out into
     /// individual commands and run separately. That allows us to keep Volta's sandboxing for each
     /// package while still supporting the ability to install multiple packages at once.
-    pub fn executor(self, platform_spec: &PlatformSpec) -> Fallible<Executor> {
+    ///
+    /// Packages listed in the `install.unmanagedGlobals` setting aren't intercepted: they are
+    /// installed together by the package manager itself, into the global prefix of the default
+    /// image.
+    pub fn executor(self, platform_spec: &PlatformSpec, settings: &Settings) -> Fallible<Executor> {
+        let (exe, kind) = match self.manager {
+            PackageManager::Npm => ("npm", ToolKind::Npm),
+            PackageManager::Pnpm => ("pnpm", ToolKind::Pnpm),
+            PackageManager::Yarn => ("yarn", ToolKind::Yarn),
+        };
         let mut executors = Vec::with_capacity(self.tools.len());
+        let mut unmanaged = Vec::new();
 
         for tool in self.tools {
-            // External tool installs may be in a form that doesn't match a `Spec` (such as a git
-            // URL or path to a tarball). If parsing into a `Spec` fails, we assume that it's a
-            // 3rd-party Tool and attempt to install anyway.
+            // External tool installs may be in a form that doesn't match a `Spec` (such as a
+            // registry alias or a tarball URL). If parsing into a `Spec` fails, we assume that
+            // it's a 3rd-party Tool and attempt to install anyway.
             match Spec::try_from_str(&tool.to_string_lossy()) {
-                Ok(Spec::Package(_, _)) | Err(_) => {
+                Ok(Spec::Package(name, _)) if settings.is_unmanaged_global(&name) => {
+                    info!(
+                        "Installing {} with {} directly, as it is listed in `install.unmanagedGlobals`",
+                        name, exe
+                    );
+                    unmanaged.push(tool);
+                }
+                Ok(Spec::Package(_, _) | Spec::PackageSource(_)) | Err(_) => {
                     let platform = platform_spec.as_default();
                     // The args for an individual install command are the common args combined
                     // with the name of the tool.
@@ -320,6 +321,12 @@ impl InstallArgs<'_> {
             }
         }
 
+        if !unmanaged.is_empty() {
+            let args = self.common_args.iter().chain(&unmanaged);
+            let platform = Some(platform_spec.as_default());
+            executors.push(ToolCommand::new(exe, args, platform, kind).into());
+        }
+
         Ok(executors.into())
     }
 }
@@ -377,16 +384,16 @@ impl UpgradeArgs<'_> {
                         PackageUpgradeCommand::new(args, package, platform, self.manager)?.into(),
                     );
                 }
-                Ok(internal) => {
-                    executors.push(UninstallCommand::new(internal).into());
-                }
-                Err(_) => {
+                Ok(Spec::PackageSource(_)) | Err(_) => {
                     return Err(ErrorKind::UpgradePackageNotFound {
                         package: tool.to_string_lossy().to_string(),
                         manager: self.manager,
                     }
                     .into())
                 }
+                Ok(internal) => {
+                    executors.push(UninstallCommand::new(internal).into());
+                }
             }
         }
 
@@ -1,14 +1,14 @@
 use std::env;
 use std::ffi::OsString;
 
-use super::executor::{Executor, ToolCommand, ToolKind};
+use super::executor::{ExecutionContext, Executor, ToolCommand, ToolKind};
 use super::{debug_active_image, debug_no_platform, RECURSION_ENV_VAR};
 use crate::error::{ErrorKind, Fallible};
 use crate::platform::{Platform, Source, System};
 use crate::session::{ActivityKind, Session};
 
 pub(super) fn command(args: &[OsString], session: &mut Session) -> Fallible<Executor> {
-    session.add_event_start(ActivityKind::Pnpm);
+    session.add_event_subcommand_start(ActivityKind::Pnpm);
     // Don't re-evaluate the context or global install interception if this is a recursive call
     let platform = match env::var_os(RECURSION_ENV_VAR) {
         Some(_) => None,
@@ -31,11 +31,11 @@ pub(super) fn command(args: &[OsString], session: &mut Session) -> Fallible<Exec
     Ok(ToolCommand::new("pnpm", args, platform, ToolKind::Pnpm).into())
 }
 
-/// Determine the execution context (PATH and failure error message) for pnpm
+/// Determine the execution context (PATH, failure error message and image) for pnpm
 pub(super) fn execution_context(
     platform: Option<Platform>,
     session: &mut Session,
-) -> Fallible<(OsString, ErrorKind)> {
+) -> Fallible<ExecutionContext> {
     match platform {
         Some(plat) => {
             validate_platform_pnpm(&plat)?;
@@ -44,12 +44,16 @@ pub(super) fn execution_context(
             let path = image.path()?;
             debug_active_image(&image);
 
-            Ok((path, ErrorKind::BinaryExecError))
+            Ok(ExecutionContext::image(
+                path,
+                ErrorKind::BinaryExecError,
+                image,
+            ))
         }
         None => {
             let path = System::path()?;
             debug_no_platform();
-            Ok((path, ErrorKind::NoPlatform))
+            Ok(ExecutionContext::system(path, ErrorKind::NoPlatform))
         }
     }
 }
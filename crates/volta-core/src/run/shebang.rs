@@ -0,0 +1,145 @@
+//! Provides support for running scripts that start with a `#!` line on Windows
+//!
+//! Windows can't launch those scripts itself, so the ones written for Node are run as
+//! `node <script>`, the same way as the cmd shims that npm writes for package binaries.
+
+use std::env;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use crate::error::{ErrorKind, Fallible};
+
+/// The longest first line that is read to find the interpreter of a script
+const MAX_SHEBANG_LENGTH: u64 = 512;
+
+/// The extensions that Windows can launch when `PATHEXT` isn't set
+const DEFAULT_PATHEXT: &str = ".COM;.EXE;.BAT;.CMD";
+
+/// Determines whether a binary is a Node script that Windows can't launch itself, which needs
+/// to be run with `node` instead
+///
+/// Binaries that have a launchable variant next to them, such as the `.cmd` shim that npm writes
+/// alongside each script, are launched as before, as are files that don't start with `#!`.
+/// Scripts for any interpreter other than Node can't be run at all.
+pub(super) fn is_node_script(file: &Path) -> Fallible<bool> {
+    if has_launchable_variant(file) {
+        return Ok(false);
+    }
+
+    match read_interpreter(file) {
+        Some(interpreter) if interpreter == "node" => Ok(true),
+        Some(interpreter) => Err(ErrorKind::UnsupportedScriptInterpreter {
+            file: file.to_owned(),
+            interpreter,
+        }
+        .into()),
+        None => Ok(false),
+    }
+}
+
+/// Determines whether there is a file next to the binary with the same name and one of the
+/// extensions in `PATHEXT`, which Windows launches instead of the binary itself
+fn has_launchable_variant(file: &Path) -> bool {
+    let pathext = env::var("PATHEXT").unwrap_or_else(|_| DEFAULT_PATHEXT.into());
+
+    pathext
+        .split(';')
+        .filter(|ext| !ext.is_empty())
+        .flat_map(|ext| [ext.to_owned(), ext.to_ascii_lowercase()])
+        .any(|ext| {
+            let mut variant = OsString::from(file);
+            variant.push(ext);
+            Path::new(&variant).is_file()
+        })
+}
+
+/// Reads the interpreter from the `#!` line of a file, without reading past the first line
+///
+/// Files that can't be read or don't start with `#!`, including PE executables (which start with
+/// `MZ`), have no interpreter.
+fn read_interpreter(file: &Path) -> Option<String> {
+    let file = File::open(file).ok()?;
+    let mut line = Vec::new();
+    BufReader::new(file.take(MAX_SHEBANG_LENGTH))
+        .read_until(b'\n', &mut line)
+        .ok()?;
+
+    let shebang = line.strip_prefix(b"#!")?;
+    parse_interpreter(&String::from_utf8_lossy(shebang))
+}
+
+/// Finds the name of the interpreter in a `#!` line, following `env` to the program it runs
+fn parse_interpreter(shebang: &str) -> Option<String> {
+    let mut words = shebang.split_whitespace();
+    let mut program = program_name(words.next()?);
+
+    if program == "env" {
+        // `env` runs the first word that isn't an option or a variable, as in `env -S node --flag`
+        program = program_name(words.find(|word| !word.starts_with('-') && !word.contains('='))?);
+    }
+
+    Some(program.to_owned())
+}
+
+/// The name of a program without its directory or `.exe` extension
+fn program_name(program: &str) -> &str {
+    let name = program.rsplit(['/', '\\']).next().unwrap_or(program);
+    name.strip_suffix(".exe").unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_path(name: &str) -> PathBuf {
+        let mut cargo_manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        cargo_manifest_dir.push("fixtures");
+        cargo_manifest_dir.push("shebang");
+        cargo_manifest_dir.push(name);
+        cargo_manifest_dir
+    }
+
+    #[test]
+    fn parses_interpreters() {
+        assert_eq!(parse_interpreter("/usr/bin/env node"), Some("node".into()));
+        assert_eq!(parse_interpreter(" /usr/bin/node\r"), Some("node".into()));
+        assert_eq!(
+            parse_interpreter("/usr/bin/env -S NODE_ENV=production node --harmony"),
+            Some("node".into())
+        );
+        assert_eq!(
+            parse_interpreter(r"C:\nodejs\node.exe"),
+            Some("node".into())
+        );
+        assert_eq!(
+            parse_interpreter("/usr/bin/env python3"),
+            Some("python3".into())
+        );
+        assert_eq!(parse_interpreter("/usr/bin/env"), None);
+        assert_eq!(parse_interpreter(""), None);
+    }
+
+    #[test]
+    fn detects_node_scripts() {
+        assert!(is_node_script(&fixture_path("env-node")).unwrap());
+        assert!(is_node_script(&fixture_path("node")).unwrap());
+    }
+
+    #[test]
+    fn launches_other_binaries_as_before() {
+        assert!(!is_node_script(&fixture_path("pe-executable")).unwrap());
+        assert!(!is_node_script(&fixture_path("with-cmd")).unwrap());
+        assert!(!is_node_script(&fixture_path("missing")).unwrap());
+    }
+
+    #[test]
+    fn rejects_scripts_for_other_interpreters() {
+        let error = is_node_script(&fixture_path("python")).unwrap_err();
+
+        assert_eq!(error.exit_code(), crate::error::ExitCode::ExecutionFailure);
+        assert!(error.to_string().contains("It is a script for `python3`"));
+    }
+}
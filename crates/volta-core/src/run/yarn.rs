@@ -1,7 +1,7 @@
 use std::env;
 use std::ffi::OsString;
 
-use super::executor::{Executor, ToolCommand, ToolKind};
+use super::executor::{ExecutionContext, Executor, ToolCommand, ToolKind};
 use super::parser::CommandArg;
 use super::{debug_active_image, debug_no_platform, RECURSION_ENV_VAR};
 use crate::error::{ErrorKind, Fallible};
@@ -12,20 +12,23 @@ use crate::session::{ActivityKind, Session};
 ///
 /// If the command is a global add or remove and we have a default platform available, then we will
 /// use custom logic to ensure that the package is correctly installed / uninstalled in the Volta
-/// directory.
+/// directory. Packages listed in the `install.unmanagedGlobals` setting are the exception, and are
+/// installed by Yarn itself.
 ///
 /// If the command is _not_ a global add / remove or we don't have a default platform, then
 /// we will allow Yarn to execute the command as usual.
 pub(super) fn command(args: &[OsString], session: &mut Session) -> Fallible<Executor> {
-    session.add_event_start(ActivityKind::Yarn);
+    session.add_event_subcommand_start(ActivityKind::Yarn);
     // Don't re-evaluate the context or global install interception if this is a recursive call
     let platform = match env::var_os(RECURSION_ENV_VAR) {
         Some(_) => None,
+        // With unsafe globals, global installs are left to Yarn itself
+        None if session.unsafe_global()? => Platform::current(session)?,
         None => {
             if let CommandArg::Global(cmd) = CommandArg::for_yarn(args) {
                 // For globals, only intercept if the default platform exists
                 if let Some(default_platform) = session.default_platform()? {
-                    return cmd.executor(default_platform);
+                    return cmd.executor(default_platform, session.settings()?);
                 }
             }
 
@@ -36,11 +39,11 @@ pub(super) fn command(args: &[OsString], session: &mut Session) -> Fallible<Exec
     Ok(ToolCommand::new("yarn", args, platform, ToolKind::Yarn).into())
 }
 
-/// Determine the execution context (PATH and failure error message) for Yarn
+/// Determine the execution context (PATH, failure error message and image) for Yarn
 pub(super) fn execution_context(
     platform: Option<Platform>,
     session: &mut Session,
-) -> Fallible<(OsString, ErrorKind)> {
+) -> Fallible<ExecutionContext> {
     match platform {
         Some(plat) => {
             validate_platform_yarn(&plat)?;
@@ -49,12 +52,16 @@ pub(super) fn execution_context(
             let path = image.path()?;
             debug_active_image(&image);
 
-            Ok((path, ErrorKind::BinaryExecError))
+            Ok(ExecutionContext::image(
+                path,
+                ErrorKind::BinaryExecError,
+                image,
+            ))
         }
         None => {
             let path = System::path()?;
             debug_no_platform();
-            Ok((path, ErrorKind::NoPlatform))
+            Ok(ExecutionContext::system(path, ErrorKind::NoPlatform))
         }
     }
 }
@@ -0,0 +1,469 @@
+//! Provides `volta self update`, which replaces the Volta binaries with those of the latest release
+//!
+//! Only installations made with the shell installer, or from an unpacked release archive, can
+//! update themselves: when a package manager installed Volta, it keeps its own record of the
+//! files, so the user is sent to it instead.
+//!
+//! The release is always downloaded from the official source. The hooks that point tools at a
+//! mirror are not consulted, though the proxy settings in the environment and the `.npmrc` files
+//! still apply. The archive is only unpacked once it matches the checksum that is published with
+//! the release, and the binaries are swapped in as a set: if any of them can't be replaced, the
+//! ones that already were are restored.
+//!
+//! No signature is verified. The checksum file is downloaded from the same server as the archive,
+//! so it catches a corrupted or truncated download, but not a release that was tampered with at
+//! its source.
+
+use std::env::consts::{ARCH, OS};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::download::DownloadPermit;
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::{
+    create_staging_file, read_dir_eager, remove_dir_if_exists, rename, set_executable,
+};
+use crate::style::progress_bar;
+use crate::tool::checksum::{find_checksum, sha256_file};
+use crate::tool::npmrc;
+use crate::version::parse_version;
+use archive::Origin;
+use attohttpc::Response;
+use cfg_if::cfg_if;
+use log::debug;
+use node_semver::Version;
+use tempfile::TempDir;
+
+/// The checksum file published with each release
+const SHASUMS_FILE_NAME: &str = "SHASUMS256.txt";
+
+/// The prefix of the directory in the install directory that a release is staged in
+///
+/// On Windows the running executable can be moved but not deleted, so the previous binaries may
+/// be left behind in it. Those directories are removed the next time Volta updates itself.
+const STAGING_PREFIX: &str = ".volta-update";
+
+cfg_if! {
+    if #[cfg(feature = "mock-network")] {
+        #[allow(deprecated)]
+        fn latest_version_url() -> String {
+            format!("{}/volta-releases/latest-version", mockito::SERVER_URL)
+        }
+
+        #[allow(deprecated)]
+        fn release_url(version: &Version, file: &str) -> String {
+            format!("{}/volta-releases/v{}/{}", mockito::SERVER_URL, version, file)
+        }
+    } else {
+        fn latest_version_url() -> String {
+            "https://volta.sh/latest-version".into()
+        }
+
+        fn release_url(version: &Version, file: &str) -> String {
+            format!(
+                "https://github.com/volta-cli/volta/releases/download/v{}/{}",
+                version, file
+            )
+        }
+    }
+}
+
+/// How Volta was installed, as far as can be told from the directory of its binaries
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InstallSource {
+    Homebrew,
+    Winget,
+    Msi,
+    /// The shell installer, or a release archive that was unpacked by hand
+    Direct,
+}
+
+impl InstallSource {
+    /// Determines the installation method from the directory of the Volta binaries
+    ///
+    /// Both separators are recognized on every platform, so the Windows paths can be told apart
+    /// wherever this runs.
+    pub fn detect(install_dir: &Path) -> Self {
+        let path = install_dir.to_string_lossy().to_ascii_lowercase();
+        let mut components = path.split(['/', '\\']);
+
+        if components
+            .clone()
+            .any(|part| matches!(part, "cellar" | "homebrew" | "linuxbrew"))
+        {
+            InstallSource::Homebrew
+        } else if components.clone().any(|part| part == "winget") {
+            InstallSource::Winget
+        } else if components.any(|part| part.starts_with("program files")) {
+            InstallSource::Msi
+        } else {
+            InstallSource::Direct
+        }
+    }
+
+    /// The package manager that owns the installation and how to update with it, if any
+    pub fn manager(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            InstallSource::Homebrew => Some(("Homebrew", "`brew upgrade volta`")),
+            InstallSource::Winget => Some(("winget", "`winget upgrade Volta.Volta`")),
+            InstallSource::Msi => Some((
+                "the Windows installer",
+                "the latest installer from https://volta.sh (or `winget upgrade Volta.Volta`)",
+            )),
+            InstallSource::Direct => None,
+        }
+    }
+}
+
+/// Refuses to update an installation that a package manager owns
+pub fn check_updatable(install_dir: &Path) -> Fallible<()> {
+    match InstallSource::detect(install_dir).manager() {
+        Some((manager, update)) => Err(ErrorKind::SelfUpdateManagedInstall {
+            manager: manager.into(),
+            dir: install_dir.to_owned(),
+            update: update.into(),
+        }
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// Looks up the version of the latest Volta release
+pub fn latest_version() -> Fallible<Version> {
+    let url = latest_version_url();
+    debug!("Checking for the latest release of Volta at {}", url);
+
+    let _permit = DownloadPermit::acquire();
+    let text = npmrc::get(&url)
+        .send()
+        .and_then(Response::error_for_status)
+        .and_then(Response::text)
+        .with_context(|| ErrorKind::SelfUpdateFetchError {
+            from_url: url.clone(),
+        })?;
+
+    parse_version(text.trim())
+}
+
+/// The name of the release archive for the current operating system and architecture
+fn archive_filename(version: &Version) -> Fallible<String> {
+    let platform = match (OS, ARCH) {
+        ("linux", "x86_64") => "linux",
+        ("linux", "aarch64") => "linux-arm",
+        // The macOS release is a universal build
+        ("macos", "x86_64" | "aarch64") => "macos",
+        ("windows", "x86_64") => "windows",
+        ("windows", "aarch64") => "windows-arm64",
+        (os, arch) => {
+            return Err(ErrorKind::SelfUpdateUnsupportedPlatform {
+                os: os.into(),
+                arch: arch.into(),
+            }
+            .into())
+        }
+    };
+
+    let extension = if cfg!(windows) { "zip" } else { "tar.gz" };
+    Ok(format!("volta-{}-{}.{}", version, platform, extension))
+}
+
+/// A release that was downloaded, checked against its checksum, and unpacked next to the
+/// installed binaries
+pub struct StagedRelease {
+    version: Version,
+    // The staging directory is in the install directory, so the binaries can be renamed into
+    // place rather than copied
+    dir: TempDir,
+}
+
+impl StagedRelease {
+    fn new_dir(&self) -> PathBuf {
+        self.dir.path().join("new")
+    }
+
+    fn old_dir(&self) -> PathBuf {
+        self.dir.path().join("old")
+    }
+}
+
+/// Downloads the release of a version, checks its checksum, and unpacks it into the install
+/// directory, without replacing anything yet
+pub fn fetch_release(version: &Version, install_dir: &Path) -> Fallible<StagedRelease> {
+    remove_previous_staging(install_dir);
+
+    let file = archive_filename(version)?;
+    let shasums_url = release_url(version, SHASUMS_FILE_NAME);
+    let archive_url = release_url(version, &file);
+
+    let permit = DownloadPermit::acquire();
+
+    debug!("Downloading the release checksums from {}", shasums_url);
+    let shasums = npmrc::get(&shasums_url)
+        .send()
+        .and_then(Response::error_for_status)
+        .and_then(Response::text)
+        .with_context(|| ErrorKind::SelfUpdateFetchError {
+            from_url: shasums_url.clone(),
+        })?;
+    let expected = find_checksum(&shasums, &file)
+        .ok_or_else(|| ErrorKind::SelfUpdateChecksumNotFound { file: file.clone() })?
+        .to_ascii_lowercase();
+
+    let dir = tempfile::Builder::new()
+        .prefix(STAGING_PREFIX)
+        .tempdir_in(install_dir)
+        .with_context(|| ErrorKind::CreateTempDirError {
+            in_dir: install_dir.to_owned(),
+        })?;
+    let staged = StagedRelease {
+        version: version.clone(),
+        dir,
+    };
+
+    debug!("Downloading Volta {} from {}", version, archive_url);
+    let staging_file = create_staging_file()?;
+    download_archive(version, &archive_url, staging_file.path())?;
+    drop(permit);
+
+    // The archive is checked in full before any of it is unpacked
+    verify_checksum(staging_file.path(), &expected, version)?;
+
+    File::open(staging_file.path())
+        .map_err(archive::ArchiveError::from)
+        .and_then(archive::load_native)
+        .and_then(|archive| archive.unpack(&staged.new_dir(), &mut |_, _| ()))
+        .with_context(|| ErrorKind::UnpackArchiveError {
+            tool: "Volta".into(),
+            version: version.to_string(),
+        })?;
+
+    let main_executable = format!("volta{}", std::env::consts::EXE_SUFFIX);
+    if !staged.new_dir().join(main_executable).is_file() {
+        return Err(ErrorKind::SelfUpdateInvalidRelease { file }.into());
+    }
+
+    Ok(staged)
+}
+
+/// Downloads the release archive into the file, through the proxies configured for the other
+/// requests, showing its progress
+fn download_archive(version: &Version, url: &str, file: &Path) -> Fallible<()> {
+    let fetch_error = || ErrorKind::SelfUpdateFetchError {
+        from_url: url.to_owned(),
+    };
+    let (size, mut data) =
+        archive::download(url, file, npmrc::proxy_settings()).with_context(fetch_error)?;
+
+    let mut progress = progress_bar(Origin::Remote, "volta", version, size);
+    let mut buffer = [0; 8192];
+    loop {
+        let read = data.read(&mut buffer).with_context(fetch_error)?;
+        if read == 0 {
+            break;
+        }
+        progress.inc(read as u64);
+    }
+    progress.finish_and_clear();
+
+    Ok(())
+}
+
+/// Checks the SHA-256 checksum of the downloaded release archive
+fn verify_checksum(file: &Path, expected: &str, version: &Version) -> Fallible<()> {
+    let actual = sha256_file(file).with_context(|| ErrorKind::UnpackArchiveError {
+        tool: "Volta".into(),
+        version: version.to_string(),
+    })?;
+
+    if actual == expected {
+        debug!("Verified the checksum of the Volta archive ({})", actual);
+        Ok(())
+    } else {
+        Err(ErrorKind::ChecksumMismatch {
+            tool: "Volta".into(),
+            version: version.to_string(),
+            expected: expected.into(),
+            actual,
+        }
+        .into())
+    }
+}
+
+/// Replaces the installed binaries with those of the staged release, returning its version
+///
+/// Each binary is first moved aside, which works even for the running executable on Windows, and
+/// the new one is moved into its place. If any step fails, the binaries that were already
+/// replaced are moved back.
+pub fn install_release(staged: StagedRelease, install_dir: &Path) -> Fallible<Version> {
+    let new_dir = staged.new_dir();
+    let old_dir = staged.old_dir();
+    fs::create_dir_all(&old_dir).with_context(|| ErrorKind::CreateDirError {
+        dir: old_dir.clone(),
+    })?;
+
+    let mut binaries: Vec<_> = read_dir_eager(&new_dir)
+        .with_context(|| ErrorKind::ReadDirError {
+            dir: new_dir.clone(),
+        })?
+        .filter(|(_, metadata)| metadata.is_file())
+        .map(|(entry, _)| entry.file_name())
+        .collect();
+    binaries.sort();
+
+    let mut replaced = Vec::new();
+    for name in &binaries {
+        let target = install_dir.join(name);
+        let backup = old_dir.join(name);
+        let moved_aside = target.exists();
+
+        let result = (|| {
+            set_executable(&new_dir.join(name))?;
+            if moved_aside {
+                rename(&target, &backup)?;
+            }
+            rename(new_dir.join(name), &target)
+        })();
+
+        match result {
+            Ok(()) => replaced.push((target, backup, moved_aside)),
+            Err(error) => {
+                debug!("Could not replace {}: {}", target.display(), error);
+                if moved_aside && !target.exists() {
+                    replaced.push((target.clone(), backup, true));
+                }
+                restore(&replaced);
+                return Err(ErrorKind::SelfUpdateReplaceError { file: target }.into());
+            }
+        }
+    }
+
+    Ok(staged.version)
+}
+
+/// Moves the previous binaries back into place, after a failed update
+fn restore(replaced: &[(PathBuf, PathBuf, bool)]) {
+    for (target, backup, moved_aside) in replaced.iter().rev() {
+        let result = if *moved_aside {
+            rename(backup, target)
+        } else {
+            fs::remove_file(target)
+        };
+
+        if let Err(error) = result {
+            debug!("Could not restore {}: {}", target.display(), error);
+        }
+    }
+}
+
+/// Removes the staging directories that earlier updates left in the install directory
+fn remove_previous_staging(install_dir: &Path) {
+    let Ok(entries) = read_dir_eager(install_dir) else {
+        return;
+    };
+
+    for (entry, metadata) in entries {
+        let is_staging = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.starts_with(STAGING_PREFIX));
+
+        if metadata.is_dir() && is_staging {
+            if let Err(error) = remove_dir_if_exists(entry.path()) {
+                debug!(
+                    "Could not remove the previous update in {}: {}",
+                    entry.path().display(),
+                    error
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_package_manager_installs() {
+        for (dir, source) in [
+            (
+                "/opt/homebrew/Cellar/volta/2.0.2/bin",
+                InstallSource::Homebrew,
+            ),
+            ("/usr/local/Cellar/volta/2.0.2/bin", InstallSource::Homebrew),
+            (
+                "/home/linuxbrew/.linuxbrew/Cellar/volta/2.0.2/bin",
+                InstallSource::Homebrew,
+            ),
+            (
+                r"C:\Users\me\AppData\Local\Microsoft\WinGet\Packages\Volta.Volta_x\bin",
+                InstallSource::Winget,
+            ),
+            (r"C:\Program Files\Volta", InstallSource::Msi),
+            (r"C:\Program Files (x86)\Volta", InstallSource::Msi),
+            ("/home/me/.volta/bin", InstallSource::Direct),
+            (r"C:\Users\me\.volta\bin", InstallSource::Direct),
+        ] {
+            assert_eq!(InstallSource::detect(Path::new(dir)), source, "{}", dir);
+        }
+    }
+
+    #[test]
+    fn managed_installs_are_refused() {
+        assert!(check_updatable(Path::new("/opt/homebrew/Cellar/volta/2.0.2/bin")).is_err());
+        assert!(check_updatable(Path::new("/home/me/.volta/bin")).is_ok());
+    }
+
+    #[test]
+    fn failed_replacement_restores_the_previous_binaries() {
+        let install = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir_in(install.path()).unwrap();
+        let staged = StagedRelease {
+            version: Version::parse("9.9.9").unwrap(),
+            dir,
+        };
+
+        fs::create_dir_all(staged.new_dir()).unwrap();
+        fs::write(staged.new_dir().join("a-volta"), "new").unwrap();
+        fs::write(staged.new_dir().join("b-volta-shim"), "new").unwrap();
+        fs::write(install.path().join("a-volta"), "old").unwrap();
+        fs::write(install.path().join("b-volta-shim"), "old").unwrap();
+        // A file can't be moved aside onto a directory that isn't empty
+        fs::create_dir_all(staged.old_dir().join("b-volta-shim").join("busy")).unwrap();
+
+        assert!(install_release(staged, install.path()).is_err());
+        assert_eq!(
+            fs::read_to_string(install.path().join("a-volta")).unwrap(),
+            "old"
+        );
+        assert_eq!(
+            fs::read_to_string(install.path().join("b-volta-shim")).unwrap(),
+            "old"
+        );
+    }
+
+    #[test]
+    fn replaces_every_binary_of_the_release() {
+        let install = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir_in(install.path()).unwrap();
+        let staged = StagedRelease {
+            version: Version::parse("9.9.9").unwrap(),
+            dir,
+        };
+
+        fs::create_dir_all(staged.new_dir()).unwrap();
+        fs::write(staged.new_dir().join("volta"), "new").unwrap();
+        fs::write(staged.new_dir().join("volta-migrate"), "new").unwrap();
+        fs::write(install.path().join("volta"), "old").unwrap();
+
+        let version = install_release(staged, install.path()).unwrap();
+        assert_eq!(version.to_string(), "9.9.9");
+        for binary in ["volta", "volta-migrate"] {
+            assert_eq!(
+                fs::read_to_string(install.path().join(binary)).unwrap(),
+                "new"
+            );
+        }
+    }
+}
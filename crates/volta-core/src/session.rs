@@ -2,16 +2,33 @@
 //! execution of a Volta tool, including their current directory, Volta
 //! hook configuration, and the state of the local inventory.
 
+use std::collections::{HashMap, HashSet};
+use std::env;
 use std::fmt::{self, Display, Formatter};
-use std::process::exit;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
 
+use crate::alias::{self, Aliases};
+use crate::change::Change;
 use crate::error::{ExitCode, Fallible, VoltaError};
 use crate::event::EventLog;
 use crate::hook::{HookConfig, LazyHookConfig};
-use crate::platform::PlatformSpec;
+use crate::layout::volta_home;
+use crate::platform::{OptionalTool, PlatformSpec};
+use crate::policy::{self, Policies};
 use crate::project::{LazyProject, Project};
+use crate::run::engines::PackageEngines;
+use crate::settings::{InstallMode, LazySettings, Settings};
+use crate::signal::exit_like_tool;
 use crate::toolchain::{LazyToolchain, Toolchain};
+use crate::version::{VersionSpec, VersionTag};
+use crate::VOLTA_FEATURE_PNPM;
 use log::debug;
+use node_semver::Version;
+use once_cell::unsync::OnceCell;
+
+const VOLTA_OFFLINE: &str = "VOLTA_OFFLINE";
+const VOLTA_UNSAFE_GLOBAL: &str = "VOLTA_UNSAFE_GLOBAL";
 
 #[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Copy)]
 pub enum ActivityKind {
@@ -22,6 +39,7 @@ pub enum ActivityKind {
     Current,
     Default,
     Pin,
+    Lock,
     Node,
     Npm,
     Npx,
@@ -37,7 +55,18 @@ pub enum ActivityKind {
     Which,
     Setup,
     Run,
+    Exec,
     Args,
+    Import,
+    Doctor,
+    Layout,
+    VerifyBinaries,
+    Migrate,
+    Hook,
+    Config,
+    Profile,
+    SelfUpdate,
+    Env,
 }
 
 impl Display for ActivityKind {
@@ -50,6 +79,7 @@ impl Display for ActivityKind {
             ActivityKind::Current => "current",
             ActivityKind::Default => "default",
             ActivityKind::Pin => "pin",
+            ActivityKind::Lock => "lock",
             ActivityKind::Node => "node",
             ActivityKind::Npm => "npm",
             ActivityKind::Npx => "npx",
@@ -65,6 +95,17 @@ impl Display for ActivityKind {
             ActivityKind::Completions => "completions",
             ActivityKind::Which => "which",
             ActivityKind::Run => "run",
+            ActivityKind::Exec => "exec",
+            ActivityKind::Import => "import",
+            ActivityKind::Doctor => "doctor",
+            ActivityKind::Layout => "layout",
+            ActivityKind::VerifyBinaries => "verify-binaries",
+            ActivityKind::Migrate => "migrate",
+            ActivityKind::Hook => "hook",
+            ActivityKind::Config => "config",
+            ActivityKind::Profile => "profile",
+            ActivityKind::SelfUpdate => "self-update",
+            ActivityKind::Env => "env",
             ActivityKind::Args => "args",
         };
         f.write_str(s)
@@ -78,12 +119,39 @@ impl Display for ActivityKind {
 /// - the current directory
 /// - the Node project tree that contains the current directory (if any)
 /// - the Volta hook configuration
+/// - the user's Volta settings
+/// - the version aliases defined in the settings and hooks
 /// - the inventory of locally-fetched Volta tools
+/// - the `engines` of the project dependencies whose binaries are run
+///
+/// Each of these is loaded the first time it's needed and then shared for the rest of the
+/// invocation, so installing several tools at once parses the project manifests and the default
+/// platform only once. Saving the default platform still re-reads its file, to detect changes
+/// made by another process.
 pub struct Session {
     hooks: LazyHookConfig,
     toolchain: LazyToolchain,
     project: LazyProject,
+    settings: LazySettings,
+    aliases: OnceCell<Aliases>,
+    policies: OnceCell<Policies>,
     event_log: EventLog,
+    continue_on_missing: Vec<OptionalTool>,
+    install_mode: Option<InstallMode>,
+    installs_allowed: bool,
+    take_over_npm_globals: bool,
+    package_shims: bool,
+    project_platform_installs: bool,
+    /// The tool versions that a checkout has made available so far, which later checkouts in the
+    /// same invocation don't look up again
+    checked_out: HashSet<(&'static str, Version)>,
+    offline_archives: Option<PathBuf>,
+    offline: bool,
+    override_policy: bool,
+    /// The engines of the packages whose binaries were run, by package directory
+    package_engines: HashMap<PathBuf, Option<PackageEngines>>,
+    /// The changes made to the Volta directory so far, in order
+    changes: Vec<Change>,
 }
 
 impl Session {
@@ -93,7 +161,22 @@ impl Session {
             hooks: LazyHookConfig::init(),
             toolchain: LazyToolchain::init(),
             project: LazyProject::init(),
+            settings: LazySettings::init(),
+            aliases: OnceCell::new(),
+            policies: OnceCell::new(),
             event_log: EventLog::init(),
+            continue_on_missing: Vec::new(),
+            install_mode: None,
+            installs_allowed: false,
+            take_over_npm_globals: false,
+            package_shims: true,
+            project_platform_installs: false,
+            checked_out: HashSet::new(),
+            offline_archives: None,
+            offline: false,
+            override_policy: false,
+            package_engines: HashMap::new(),
+            changes: Vec::new(),
         }
     }
 
@@ -107,6 +190,15 @@ impl Session {
         self.project.get_mut()
     }
 
+    /// Returns the engines declared by the package in a directory, reading its manifest only the
+    /// first time
+    pub(crate) fn package_engines(&mut self, package_dir: &Path) -> Option<&PackageEngines> {
+        self.package_engines
+            .entry(package_dir.to_owned())
+            .or_insert_with(|| PackageEngines::read(package_dir))
+            .as_ref()
+    }
+
     /// Returns the user's default platform, if any
     pub fn default_platform(&self) -> Fallible<Option<&PlatformSpec>> {
         self.toolchain.get().map(Toolchain::platform)
@@ -130,6 +222,208 @@ impl Session {
         self.toolchain.get_mut()
     }
 
+    /// Produces a reference to the user's Volta settings
+    pub fn settings(&self) -> Fallible<&Settings> {
+        self.settings.get()
+    }
+
+    /// Whether pnpm is managed as a tool, rather than as a global package
+    ///
+    /// Setting the `VOLTA_FEATURE_PNPM` environment variable enables it regardless of the
+    /// `feature.pnpm` setting.
+    pub fn pnpm_enabled(&self) -> Fallible<bool> {
+        if env::var_os(VOLTA_FEATURE_PNPM).is_some() {
+            return Ok(true);
+        }
+
+        self.settings().map(Settings::pnpm_enabled)
+    }
+
+    /// Whether global installs are left to the package manager, rather than intercepted
+    ///
+    /// Setting the `VOLTA_UNSAFE_GLOBAL` environment variable enables it regardless of the
+    /// `install.unsafeGlobal` setting.
+    pub fn unsafe_global(&self) -> Fallible<bool> {
+        if env::var_os(VOLTA_UNSAFE_GLOBAL).is_some() {
+            return Ok(true);
+        }
+
+        self.settings().map(Settings::unsafe_global)
+    }
+
+    /// Produces the version aliases that apply in the current directory
+    ///
+    /// Aliases defined in the hooks files of the current project take precedence over the ones in
+    /// the user's hooks file, which in turn take precedence over the ones in the settings file.
+    pub fn aliases(&self) -> Fallible<&Aliases> {
+        self.aliases.get_or_try_init(|| {
+            let settings_file = volta_home()?.settings_file();
+            let settings = alias::from_raw(self.settings()?.aliases().clone(), settings_file)?;
+            let hooks = self
+                .hooks()?
+                .aliases()
+                .map(|hooks| hooks.aliases.clone())
+                .unwrap_or_default();
+
+            Ok(Aliases::from_layers([hooks, settings]))
+        })
+    }
+
+    /// Replaces a version alias of a tool with the version or range it stands for
+    ///
+    /// The aliases are only loaded for custom tags, so that other versions don't depend on them.
+    pub(crate) fn resolve_alias(&self, tool: &str, version: VersionSpec) -> Fallible<VersionSpec> {
+        match version {
+            VersionSpec::Tag(VersionTag::Custom(_)) => self.aliases()?.resolve(tool, version),
+            other => Ok(other),
+        }
+    }
+
+    /// Produces the version policies that apply in the current directory
+    ///
+    /// The policies in the hooks files and the settings file all apply, so the hooks of a project
+    /// can only add restrictions.
+    pub fn policies(&self) -> Fallible<&Policies> {
+        self.policies.get_or_try_init(|| {
+            let settings_file = volta_home()?.settings_file();
+            let settings = policy::from_raw(self.settings()?.policies().clone(), settings_file)?;
+            let hooks = self
+                .hooks()?
+                .policies()
+                .map(|hooks| hooks.policies.clone())
+                .unwrap_or_default();
+
+            Ok(Policies::from_layers([hooks, settings]))
+        })
+    }
+
+    /// Ignores the version policies that are overridable, for this invocation
+    pub fn override_policies(&mut self) {
+        self.override_policy = true;
+    }
+
+    /// Checks that a version of a tool is allowed by the version policies
+    pub(crate) fn check_policy(&self, tool: &str, version: &Version) -> Fallible<()> {
+        self.policies()?.check(tool, version, self.override_policy)
+    }
+
+    /// Allows platform checkouts to continue without the given tools if they can't be fetched
+    pub fn continue_on_missing(&mut self, tools: Vec<OptionalTool>) {
+        self.continue_on_missing = tools;
+    }
+
+    /// Determines whether a platform checkout may continue without the given tool
+    pub(crate) fn may_continue_without(&self, tool: OptionalTool) -> bool {
+        self.continue_on_missing.contains(&tool)
+    }
+
+    /// Overrides the `install.mode` setting for this invocation
+    pub fn set_install_mode(&mut self, mode: InstallMode) {
+        self.install_mode = Some(mode);
+    }
+
+    /// Allows missing tools to be fetched regardless of the install mode, for the commands whose
+    /// purpose is to fetch tools (`volta fetch` and `volta install`)
+    pub fn allow_installs(&mut self) {
+        self.installs_allowed = true;
+    }
+
+    /// Determines whether a missing tool may be fetched
+    pub(crate) fn may_fetch(&self) -> Fallible<bool> {
+        if self.installs_allowed {
+            return Ok(true);
+        }
+
+        let mode = match self.install_mode {
+            Some(mode) => mode,
+            None => self.settings()?.install_mode(),
+        };
+        Ok(mode == InstallMode::Auto)
+    }
+
+    /// Allows package installs to remove copies of the same package that were installed globally
+    /// with npm, outside of Volta
+    pub fn take_over_npm_globals(&mut self) {
+        self.take_over_npm_globals = true;
+    }
+
+    /// Determines whether package installs may remove copies installed globally with npm
+    pub(crate) fn takes_over_npm_globals(&self) -> bool {
+        self.take_over_npm_globals
+    }
+
+    /// Installs packages without creating shims for their binaries
+    pub fn skip_package_shims(&mut self) {
+        self.package_shims = false;
+    }
+
+    /// Determines whether package installs create shims for the package binaries
+    pub(crate) fn creates_package_shims(&self) -> bool {
+        self.package_shims
+    }
+
+    /// Installs packages with the platform of the current project, rather than the default one
+    pub fn install_packages_with_project_platform(&mut self) {
+        self.project_platform_installs = true;
+    }
+
+    /// Determines whether packages are installed with the platform of the current project
+    pub(crate) fn installs_packages_with_project_platform(&self) -> bool {
+        self.project_platform_installs
+    }
+
+    /// Determines whether an earlier checkout in this invocation made a tool version available
+    pub(crate) fn is_checked_out(&self, tool: &'static str, version: &Version) -> bool {
+        self.checked_out.contains(&(tool, version.clone()))
+    }
+
+    /// Records that a checkout made a tool version available
+    pub(crate) fn record_checkout(&mut self, tool: &'static str, version: &Version) {
+        self.checked_out.insert((tool, version.clone()));
+    }
+
+    /// Installs tools from the archives in the given directory, instead of downloading them
+    pub fn install_from_archives(&mut self, dir: PathBuf) {
+        self.offline_archives = Some(dir);
+    }
+
+    /// The directory that tools are installed from, if they shouldn't be downloaded
+    pub(crate) fn offline_archives(&self) -> Option<&Path> {
+        self.offline_archives.as_deref()
+    }
+
+    /// Resolves versions and fetches tools without network access, using only what is available
+    /// locally
+    pub fn go_offline(&mut self) {
+        self.offline = true;
+    }
+
+    /// Determines whether network access is disabled, with `--offline` or by setting
+    /// `VOLTA_OFFLINE` (to anything other than `0`)
+    pub(crate) fn is_offline(&self) -> bool {
+        if self.offline {
+            return true;
+        }
+
+        match env::var_os(VOLTA_OFFLINE) {
+            Some(value) => !value.is_empty() && value != "0",
+            None => false,
+        }
+    }
+
+    /// Records changes made to the Volta directory, so they can be reported with `--porcelain`
+    pub fn record_changes<C>(&mut self, changes: C)
+    where
+        C: IntoIterator<Item = Change>,
+    {
+        self.changes.extend(changes);
+    }
+
+    /// Takes the changes recorded so far, in the order they were made
+    pub fn take_changes(&mut self) -> Vec<Change> {
+        std::mem::take(&mut self.changes)
+    }
+
     /// Produces a reference to the hook configuration
     pub fn hooks(&self) -> Fallible<&HookConfig> {
         self.hooks.get(self.project()?)
@@ -138,6 +432,9 @@ impl Session {
     pub fn add_event_start(&mut self, activity_kind: ActivityKind) {
         self.event_log.add_event_start(activity_kind)
     }
+    pub fn add_event_subcommand_start(&mut self, activity_kind: ActivityKind) {
+        self.event_log.add_event_subcommand_start(activity_kind)
+    }
     pub fn add_event_end(&mut self, activity_kind: ActivityKind, exit_code: ExitCode) {
         self.event_log.add_event_end(activity_kind, exit_code)
     }
@@ -175,18 +472,24 @@ impl Session {
         code.exit();
     }
 
-    pub fn exit_tool(self, code: i32) -> ! {
+    /// Exit with the same status as a tool that was run by Volta, after publishing the events
+    pub fn exit_tool(self, status: ExitStatus) -> ! {
         self.publish_to_event_log();
-        exit(code);
+        exit_like_tool(status);
     }
 }
 
 #[cfg(test)]
 pub mod tests {
 
+    use crate::fs::open_count;
     use crate::session::Session;
     use std::env;
     use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    /// Held by the tests that change the current directory, since it is shared by every thread
+    static CURRENT_DIR: Mutex<()> = Mutex::new(());
 
     fn fixture_path(fixture_dir: &str) -> PathBuf {
         let mut cargo_manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -197,6 +500,9 @@ pub mod tests {
 
     #[test]
     fn test_in_pinned_project() {
+        let _guard = CURRENT_DIR
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
         let project_pinned = fixture_path("basic");
         env::set_current_dir(project_pinned).expect("Could not set current directory");
         let pinned_session = Session::init();
@@ -213,4 +519,36 @@ pub mod tests {
             .expect("Couldn't create Project");
         assert!(unpinned_platform.is_none());
     }
+
+    #[test]
+    fn project_manifests_are_read_once_per_session() {
+        let _guard = CURRENT_DIR
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let nested = dunce::canonicalize(fixture_path("nested")).unwrap();
+        let manifests = [
+            nested.join("subproject/inner_project/package.json"),
+            nested.join("subproject/package.json"),
+            nested.join("package.json"),
+        ];
+        env::set_current_dir(nested.join("subproject/inner_project"))
+            .expect("Could not set current directory");
+        let mut session = Session::init();
+
+        // Installing several tools looks up the project for each of them
+        for _ in 0..3 {
+            assert!(session.project().unwrap().is_some());
+            assert!(session.project_platform().unwrap().is_some());
+            assert!(session.project_mut().unwrap().is_some());
+        }
+
+        for manifest in &manifests {
+            assert_eq!(
+                open_count(manifest),
+                1,
+                "{} was read more than once",
+                manifest.display()
+            );
+        }
+    }
 }
@@ -0,0 +1,757 @@
+//! Provides types for working with the user's Volta settings file.
+
+use std::fmt;
+use std::fs::{self, File};
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::str::FromStr;
+use std::thread;
+
+use crate::alias::RawAliases;
+use crate::download::DEFAULT_MAX_CONCURRENT_DOWNLOADS;
+use crate::error::{Context, ErrorKind, Fallible, VoltaError};
+use crate::layout::volta_home;
+use crate::policy::RawPolicies;
+use fs_utils::ensure_containing_dir_exists;
+use log::{debug, LevelFilter};
+use once_cell::unsync::OnceCell;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Lazily loaded Volta settings
+pub struct LazySettings {
+    settings: OnceCell<Settings>,
+}
+
+impl LazySettings {
+    /// Constructs a new `LazySettings`
+    pub fn init() -> LazySettings {
+        LazySettings {
+            settings: OnceCell::new(),
+        }
+    }
+
+    /// Forces the loading of the settings from the user's settings file
+    pub fn get(&self) -> Fallible<&Settings> {
+        self.settings.get_or_try_init(Settings::current)
+    }
+}
+
+/// The user's Volta settings
+#[derive(Deserialize, Default, Debug, PartialEq, Eq)]
+pub struct Settings {
+    #[serde(default)]
+    usage: UsageSettings,
+    #[serde(default)]
+    install: InstallSettings,
+    #[serde(default)]
+    node: NodeSettings,
+    #[serde(default)]
+    network: NetworkSettings,
+    #[serde(default)]
+    log: LogSettings,
+    #[serde(default)]
+    aliases: RawAliases,
+    #[serde(default)]
+    policies: RawPolicies,
+    #[serde(default)]
+    shims: ShimSettings,
+    #[serde(default)]
+    feature: FeatureSettings,
+}
+
+/// Settings for the local usage counters
+#[derive(Deserialize, Default, Debug, PartialEq, Eq)]
+struct UsageSettings {
+    /// Whether shims record how often each tool version is used
+    #[serde(default)]
+    stats: bool,
+}
+
+/// Settings for making tools available
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct InstallSettings {
+    /// Whether missing tools are fetched automatically
+    #[serde(default)]
+    mode: InstallMode,
+    /// Whether the macOS quarantine attribute is removed from the executables of unpacked tools
+    #[serde(default = "enabled")]
+    clear_quarantine: bool,
+    /// The packages whose global installs are left to the package manager, rather than Volta
+    #[serde(default)]
+    unmanaged_globals: Vec<String>,
+    /// Whether global installs are always left to the package manager, rather than Volta
+    #[serde(default)]
+    unsafe_global: bool,
+}
+
+impl Default for InstallSettings {
+    fn default() -> Self {
+        InstallSettings {
+            mode: InstallMode::default(),
+            clear_quarantine: enabled(),
+            unmanaged_globals: Vec::new(),
+            unsafe_global: false,
+        }
+    }
+}
+
+/// Settings for fetching Node
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct NodeSettings {
+    /// Whether a fetched Node is run once on Linux, to explain failures caused by an old glibc
+    #[serde(default = "enabled")]
+    glibc_check: bool,
+}
+
+impl Default for NodeSettings {
+    fn default() -> Self {
+        NodeSettings {
+            glibc_check: enabled(),
+        }
+    }
+}
+
+/// Settings for network requests
+#[derive(Deserialize, Default, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct NetworkSettings {
+    /// How many downloads may run at the same time
+    #[serde(default)]
+    max_concurrent_downloads: Option<NonZeroUsize>,
+}
+
+/// Settings for Volta's own output
+#[derive(Deserialize, Default, Debug, PartialEq, Eq)]
+struct LogSettings {
+    /// How much is logged when neither `--verbose` nor `--quiet` is given
+    #[serde(default)]
+    level: LogLevel,
+}
+
+/// Settings for regenerating shims
+#[derive(Deserialize, Default, Debug, PartialEq, Eq)]
+struct ShimSettings {
+    /// How many shims are regenerated at once
+    #[serde(default)]
+    jobs: Option<NonZeroUsize>,
+}
+
+/// Settings for enabling features that are still in development
+#[derive(Deserialize, Default, Debug, PartialEq, Eq)]
+struct FeatureSettings {
+    /// Whether pnpm is managed as a tool, rather than as a global package
+    #[serde(default)]
+    pnpm: bool,
+}
+
+/// The most shims regenerated at once by default, however many CPUs there are
+const MAX_DEFAULT_SHIM_JOBS: NonZeroUsize = match NonZeroUsize::new(8) {
+    Some(jobs) => jobs,
+    None => unreachable!(),
+};
+
+fn enabled() -> bool {
+    true
+}
+
+/// Whether commands may fetch the tools they need (`install.mode`)
+#[derive(Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallMode {
+    /// Missing tools are fetched whenever they are needed
+    #[default]
+    Auto,
+    /// Missing tools are an error, and are only fetched by `volta fetch` and `volta install`. Other
+    /// commands resolve version requirements against the fetched versions instead of the index.
+    Manual,
+}
+
+impl fmt::Display for InstallMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            InstallMode::Auto => "auto",
+            InstallMode::Manual => "manual",
+        })
+    }
+}
+
+impl FromStr for InstallMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(InstallMode::Auto),
+            "manual" => Ok(InstallMode::Manual),
+            _ => Err(format!(
+                "unsupported install mode '{}', the supported modes are: auto, manual",
+                s
+            )),
+        }
+    }
+}
+
+/// How much Volta logs by default (`log.level`)
+#[derive(Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    /// Info messages are shown in a terminal, and only errors otherwise
+    #[default]
+    Auto,
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    /// The level to log at, or `None` if it depends on whether stdout is a terminal
+    pub fn filter(self) -> Option<LevelFilter> {
+        match self {
+            LogLevel::Auto => None,
+            LogLevel::Off => Some(LevelFilter::Off),
+            LogLevel::Error => Some(LevelFilter::Error),
+            LogLevel::Warn => Some(LevelFilter::Warn),
+            LogLevel::Info => Some(LevelFilter::Info),
+            LogLevel::Debug => Some(LevelFilter::Debug),
+            LogLevel::Trace => Some(LevelFilter::Trace),
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.filter() {
+            Some(level) => f.write_str(&level.as_str().to_lowercase()),
+            None => f.write_str("auto"),
+        }
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(LogLevel::Auto),
+            "off" => Ok(LogLevel::Off),
+            "error" => Ok(LogLevel::Error),
+            "warn" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            _ => Err(format!(
+                "unsupported log level '{}', the supported levels are: {}",
+                s, LOG_LEVELS
+            )),
+        }
+    }
+}
+
+const LOG_LEVELS: &str = "auto, off, error, warn, info, debug, trace";
+
+impl Settings {
+    fn current() -> Fallible<Self> {
+        let path = volta_home()?.settings_file();
+        match Settings::from_file(path)? {
+            Some(settings) => {
+                debug!("Found settings file at '{}'", path.display());
+                Ok(settings)
+            }
+            None => Ok(Settings::default()),
+        }
+    }
+
+    fn from_file(file_path: &Path) -> Fallible<Option<Self>> {
+        if !file_path.is_file() {
+            return Ok(None);
+        }
+
+        let file = File::open(file_path).with_context(|| ErrorKind::ReadSettingsError {
+            file: file_path.to_path_buf(),
+        })?;
+
+        serde_json::de::from_reader(file)
+            .with_context(|| ErrorKind::ParseSettingsError {
+                file: file_path.to_path_buf(),
+            })
+            .map(Some)
+    }
+
+    /// Whether local usage counters (`usage.stats`) are enabled
+    pub fn usage_stats(&self) -> bool {
+        self.usage.stats
+    }
+
+    /// Whether missing tools are fetched automatically (`install.mode`)
+    pub fn install_mode(&self) -> InstallMode {
+        self.install.mode
+    }
+
+    /// Whether unpacked tools are cleared of the macOS quarantine attribute
+    /// (`install.clearQuarantine`)
+    pub fn clear_quarantine(&self) -> bool {
+        self.install.clear_quarantine
+    }
+
+    /// Whether global installs of a package are left to the package manager
+    /// (`install.unmanagedGlobals`)
+    ///
+    /// Each entry is either a package name or a whole scope, written as `@scope/*`.
+    pub fn is_unmanaged_global(&self, name: &str) -> bool {
+        self.install
+            .unmanaged_globals
+            .iter()
+            .any(|entry| match entry.strip_suffix("/*") {
+                Some(scope) => {
+                    scope.starts_with('@')
+                        && name
+                            .strip_prefix(scope)
+                            .is_some_and(|rest| rest.starts_with('/'))
+                }
+                None => entry == name,
+            })
+    }
+
+    /// Whether global installs are always left to the package manager (`install.unsafeGlobal`)
+    ///
+    /// The `VOLTA_UNSAFE_GLOBAL` environment variable takes precedence over this setting, see
+    /// `Session::unsafe_global`.
+    pub fn unsafe_global(&self) -> bool {
+        self.install.unsafe_global
+    }
+
+    /// How much Volta logs by default (`log.level`)
+    ///
+    /// The `VOLTA_LOGLEVEL` environment variable, `--verbose` and `--quiet` take precedence over
+    /// this setting.
+    pub fn log_level(&self) -> LogLevel {
+        self.log.level
+    }
+
+    /// Whether a fetched Node is checked against the system's glibc (`node.glibcCheck`)
+    pub fn node_glibc_check(&self) -> bool {
+        self.node.glibc_check
+    }
+
+    /// How many downloads may run at the same time (`network.maxConcurrentDownloads`)
+    ///
+    /// The `VOLTA_MAX_CONCURRENT_DOWNLOADS` environment variable and the
+    /// `--max-concurrent-downloads` flag take precedence over this setting.
+    pub fn max_concurrent_downloads(&self) -> NonZeroUsize {
+        self.network
+            .max_concurrent_downloads
+            .unwrap_or(DEFAULT_MAX_CONCURRENT_DOWNLOADS)
+    }
+
+    /// How many shims are regenerated at once (`shims.jobs`)
+    ///
+    /// Defaults to the number of CPUs, up to a limit.
+    pub fn shim_jobs(&self) -> NonZeroUsize {
+        self.shims.jobs.unwrap_or_else(|| {
+            thread::available_parallelism()
+                .map_or(NonZeroUsize::MIN, |cpus| cpus.min(MAX_DEFAULT_SHIM_JOBS))
+        })
+    }
+
+    /// Whether pnpm is managed as a tool (`feature.pnpm`)
+    ///
+    /// The `VOLTA_FEATURE_PNPM` environment variable takes precedence over this setting, see
+    /// `Session::pnpm_enabled`.
+    pub fn pnpm_enabled(&self) -> bool {
+        self.feature.pnpm
+    }
+
+    /// The version aliases defined for each tool (`aliases`)
+    pub(crate) fn aliases(&self) -> &RawAliases {
+        &self.aliases
+    }
+
+    /// The version policies defined for each tool (`policies`)
+    pub(crate) fn policies(&self) -> &RawPolicies {
+        &self.policies
+    }
+
+    /// The value of a setting, as it is shown by `volta config`
+    pub fn value(&self, key: SettingKey) -> String {
+        match key {
+            SettingKey::FeaturePnpm => self.pnpm_enabled().to_string(),
+            SettingKey::InstallClearQuarantine => self.clear_quarantine().to_string(),
+            SettingKey::InstallMode => self.install_mode().to_string(),
+            SettingKey::InstallUnsafeGlobal => self.unsafe_global().to_string(),
+            SettingKey::LogLevel => self.log_level().to_string(),
+            SettingKey::NetworkMaxConcurrentDownloads => {
+                self.max_concurrent_downloads().to_string()
+            }
+            SettingKey::NodeGlibcCheck => self.node_glibc_check().to_string(),
+            SettingKey::ShimsJobs => self.shim_jobs().to_string(),
+            SettingKey::UsageStats => self.usage_stats().to_string(),
+        }
+    }
+}
+
+/// A setting that can be read and changed with `volta config`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingKey {
+    FeaturePnpm,
+    InstallClearQuarantine,
+    InstallMode,
+    InstallUnsafeGlobal,
+    LogLevel,
+    NetworkMaxConcurrentDownloads,
+    NodeGlibcCheck,
+    ShimsJobs,
+    UsageStats,
+}
+
+impl SettingKey {
+    /// All of the settings, sorted by name
+    pub const ALL: [SettingKey; 9] = [
+        SettingKey::FeaturePnpm,
+        SettingKey::InstallClearQuarantine,
+        SettingKey::InstallMode,
+        SettingKey::InstallUnsafeGlobal,
+        SettingKey::LogLevel,
+        SettingKey::NetworkMaxConcurrentDownloads,
+        SettingKey::NodeGlibcCheck,
+        SettingKey::ShimsJobs,
+        SettingKey::UsageStats,
+    ];
+
+    /// The name of the setting, which is its path in the settings file
+    pub fn name(self) -> &'static str {
+        match self {
+            SettingKey::FeaturePnpm => "feature.pnpm",
+            SettingKey::InstallClearQuarantine => "install.clearQuarantine",
+            SettingKey::InstallMode => "install.mode",
+            SettingKey::InstallUnsafeGlobal => "install.unsafeGlobal",
+            SettingKey::LogLevel => "log.level",
+            SettingKey::NetworkMaxConcurrentDownloads => "network.maxConcurrentDownloads",
+            SettingKey::NodeGlibcCheck => "node.glibcCheck",
+            SettingKey::ShimsJobs => "shims.jobs",
+            SettingKey::UsageStats => "usage.stats",
+        }
+    }
+
+    /// Parse a value given on the command line into the JSON value stored in the settings file
+    fn parse_value(self, value: &str) -> Fallible<Value> {
+        let parsed = match self {
+            SettingKey::FeaturePnpm
+            | SettingKey::InstallClearQuarantine
+            | SettingKey::InstallUnsafeGlobal
+            | SettingKey::NodeGlibcCheck
+            | SettingKey::UsageStats => value.parse::<bool>().ok().map(Value::from),
+            SettingKey::InstallMode => value
+                .parse::<InstallMode>()
+                .ok()
+                .map(|mode| Value::from(mode.to_string())),
+            SettingKey::LogLevel => value
+                .parse::<LogLevel>()
+                .ok()
+                .map(|level| Value::from(level.to_string())),
+            SettingKey::NetworkMaxConcurrentDownloads | SettingKey::ShimsJobs => value
+                .parse::<NonZeroUsize>()
+                .ok()
+                .map(|jobs| jobs.get().into()),
+        };
+
+        parsed.ok_or_else(|| {
+            ErrorKind::InvalidSettingValue {
+                key: self.name().into(),
+                value: value.into(),
+                expected: match self {
+                    SettingKey::InstallMode => "one of: auto, manual".into(),
+                    SettingKey::LogLevel => format!("one of: {}", LOG_LEVELS),
+                    SettingKey::NetworkMaxConcurrentDownloads | SettingKey::ShimsJobs => {
+                        "a number greater than 0".into()
+                    }
+                    _ => "true or false".into(),
+                },
+            }
+            .into()
+        })
+    }
+}
+
+impl FromStr for SettingKey {
+    type Err = VoltaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SettingKey::ALL
+            .into_iter()
+            .find(|key| key.name() == s)
+            .ok_or_else(|| {
+                ErrorKind::UnknownSetting {
+                    key: s.into(),
+                    known: SettingKey::ALL
+                        .iter()
+                        .map(|key| key.name().to_owned())
+                        .collect(),
+                }
+                .into()
+            })
+    }
+}
+
+impl fmt::Display for SettingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Change a setting in the user's settings file, creating the file if necessary
+///
+/// The rest of the file, including any formatting that JSON preserves, is left as it is.
+pub fn save_setting(key: SettingKey, value: &str) -> Fallible<()> {
+    let value = key.parse_value(value)?;
+    let path = volta_home()?.settings_file();
+    let parse_error = || ErrorKind::ParseSettingsError {
+        file: path.to_path_buf(),
+    };
+
+    let mut contents: Map<String, Value> = if path.is_file() {
+        let raw = fs::read_to_string(path).with_context(|| ErrorKind::ReadSettingsError {
+            file: path.to_path_buf(),
+        })?;
+        serde_json::from_str(&raw).with_context(parse_error)?
+    } else {
+        Map::new()
+    };
+
+    let (section, name) = key
+        .name()
+        .split_once('.')
+        .expect("setting names have a section");
+    let section = contents
+        .entry(section)
+        .or_insert_with(|| Value::Object(Map::new()));
+    match section {
+        Value::Object(section) => {
+            section.insert(name.into(), value);
+        }
+        _ => return Err(parse_error().into()),
+    }
+
+    // Make sure the rest of the file is still valid, so that Volta can read it afterwards
+    serde_json::from_value::<Settings>(Value::Object(contents.clone()))
+        .with_context(parse_error)?;
+
+    let write_error = || ErrorKind::WriteSettingsError {
+        file: path.to_path_buf(),
+    };
+    let mut json = serde_json::to_string_pretty(&contents).with_context(write_error)?;
+    json.push('\n');
+    ensure_containing_dir_exists(&path).with_context(write_error)?;
+    fs::write(path, json).with_context(write_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_stats_are_disabled_by_default() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert!(!settings.usage_stats());
+
+        let settings: Settings = serde_json::from_str(r#"{ "usage": {} }"#).unwrap();
+        assert!(!settings.usage_stats());
+    }
+
+    #[test]
+    fn usage_stats_can_be_enabled() {
+        let settings: Settings = serde_json::from_str(r#"{ "usage": { "stats": true } }"#).unwrap();
+        assert!(settings.usage_stats());
+    }
+
+    #[test]
+    fn install_mode_is_auto_by_default() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.install_mode(), InstallMode::Auto);
+
+        let settings: Settings = serde_json::from_str(r#"{ "install": {} }"#).unwrap();
+        assert_eq!(settings.install_mode(), InstallMode::Auto);
+    }
+
+    #[test]
+    fn install_mode_can_be_manual() {
+        let settings: Settings =
+            serde_json::from_str(r#"{ "install": { "mode": "manual" } }"#).unwrap();
+        assert_eq!(settings.install_mode(), InstallMode::Manual);
+
+        assert!(serde_json::from_str::<Settings>(r#"{ "install": { "mode": "never" } }"#).is_err());
+    }
+
+    #[test]
+    fn node_glibc_check_is_enabled_by_default() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert!(settings.node_glibc_check());
+
+        let settings: Settings = serde_json::from_str(r#"{ "node": {} }"#).unwrap();
+        assert!(settings.node_glibc_check());
+
+        let settings: Settings =
+            serde_json::from_str(r#"{ "node": { "glibcCheck": false } }"#).unwrap();
+        assert!(!settings.node_glibc_check());
+    }
+
+    #[test]
+    fn shim_jobs_default_to_a_limited_number_of_cpus() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert!(settings.shim_jobs() <= MAX_DEFAULT_SHIM_JOBS);
+
+        let settings: Settings = serde_json::from_str(r#"{ "shims": { "jobs": 32 } }"#).unwrap();
+        assert_eq!(settings.shim_jobs().get(), 32);
+
+        assert!(serde_json::from_str::<Settings>(r#"{ "shims": { "jobs": 0 } }"#).is_err());
+    }
+
+    #[test]
+    fn max_concurrent_downloads_can_be_configured() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert_eq!(
+            settings.max_concurrent_downloads(),
+            DEFAULT_MAX_CONCURRENT_DOWNLOADS
+        );
+
+        let settings: Settings =
+            serde_json::from_str(r#"{ "network": { "maxConcurrentDownloads": 2 } }"#).unwrap();
+        assert_eq!(settings.max_concurrent_downloads().get(), 2);
+
+        assert!(serde_json::from_str::<Settings>(
+            r#"{ "network": { "maxConcurrentDownloads": 0 } }"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn aliases_are_read_per_tool() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert!(settings.aliases().is_empty());
+
+        let settings: Settings = serde_json::from_str(
+            r#"{ "aliases": { "node": { "company-standard": "20.11.1", "legacy": "^16" } } }"#,
+        )
+        .unwrap();
+        let node = &settings.aliases()["node"];
+        assert_eq!(node["company-standard"], "20.11.1");
+        assert_eq!(node["legacy"], "^16");
+    }
+
+    #[test]
+    fn install_mode_from_str() {
+        assert_eq!("auto".parse(), Ok(InstallMode::Auto));
+        assert_eq!("manual".parse(), Ok(InstallMode::Manual));
+        assert!("Manual".parse::<InstallMode>().is_err());
+    }
+
+    #[test]
+    fn clear_quarantine_is_enabled_by_default() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert!(settings.clear_quarantine());
+
+        let settings: Settings =
+            serde_json::from_str(r#"{ "install": { "mode": "manual" } }"#).unwrap();
+        assert!(settings.clear_quarantine());
+
+        let settings: Settings =
+            serde_json::from_str(r#"{ "install": { "clearQuarantine": false } }"#).unwrap();
+        assert!(!settings.clear_quarantine());
+    }
+
+    #[test]
+    fn unmanaged_globals_match_names_and_scopes() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert!(!settings.is_unmanaged_global("typescript"));
+
+        let settings: Settings = serde_json::from_str(
+            r#"{ "install": { "unmanagedGlobals": ["corepack", "@company/*", "@types/node"] } }"#,
+        )
+        .unwrap();
+        assert!(settings.is_unmanaged_global("corepack"));
+        assert!(!settings.is_unmanaged_global("corepack-extra"));
+        assert!(settings.is_unmanaged_global("@company/cli"));
+        assert!(!settings.is_unmanaged_global("@company-other/cli"));
+        assert!(!settings.is_unmanaged_global("@company"));
+        assert!(settings.is_unmanaged_global("@types/node"));
+        assert!(!settings.is_unmanaged_global("@types/react"));
+        assert!(!settings.is_unmanaged_global("node"));
+    }
+
+    #[test]
+    fn pnpm_feature_is_disabled_by_default() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert!(!settings.pnpm_enabled());
+
+        let settings: Settings =
+            serde_json::from_str(r#"{ "feature": { "pnpm": true } }"#).unwrap();
+        assert!(settings.pnpm_enabled());
+    }
+
+    #[test]
+    fn unsafe_global_is_disabled_by_default() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert!(!settings.unsafe_global());
+
+        let settings: Settings =
+            serde_json::from_str(r#"{ "install": { "unsafeGlobal": true } }"#).unwrap();
+        assert!(settings.unsafe_global());
+    }
+
+    #[test]
+    fn log_level_depends_on_the_terminal_by_default() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.log_level(), LogLevel::Auto);
+        assert_eq!(settings.log_level().filter(), None);
+
+        let settings: Settings = serde_json::from_str(r#"{ "log": { "level": "warn" } }"#).unwrap();
+        assert_eq!(settings.log_level().filter(), Some(LevelFilter::Warn));
+
+        assert!(serde_json::from_str::<Settings>(r#"{ "log": { "level": "loud" } }"#).is_err());
+    }
+
+    #[test]
+    fn log_level_round_trips() {
+        for level in LOG_LEVELS.split(", ") {
+            assert_eq!(level.parse::<LogLevel>().unwrap().to_string(), level);
+        }
+        assert!("Warn".parse::<LogLevel>().is_err());
+    }
+
+    #[test]
+    fn setting_keys_round_trip() {
+        for key in SettingKey::ALL {
+            assert_eq!(key.name().parse::<SettingKey>().unwrap(), key);
+        }
+
+        let error = "feature.yarn".parse::<SettingKey>().unwrap_err();
+        assert!(error.to_string().contains(
+            "The known settings are: feature.pnpm, install.clearQuarantine, install.mode, install.unsafeGlobal, log.level, network.maxConcurrentDownloads, node.glibcCheck, shims.jobs, usage.stats"
+        ));
+    }
+
+    #[test]
+    fn setting_values_are_validated() {
+        assert_eq!(
+            SettingKey::FeaturePnpm.parse_value("true").unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            SettingKey::InstallMode.parse_value("manual").unwrap(),
+            Value::from("manual")
+        );
+        assert_eq!(
+            SettingKey::ShimsJobs.parse_value("4").unwrap(),
+            Value::from(4)
+        );
+
+        assert!(SettingKey::FeaturePnpm.parse_value("yes").is_err());
+        assert!(SettingKey::InstallMode.parse_value("sometimes").is_err());
+        assert!(SettingKey::LogLevel.parse_value("loud").is_err());
+        assert!(SettingKey::ShimsJobs.parse_value("0").is_err());
+    }
+}
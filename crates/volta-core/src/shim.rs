@@ -1,74 +1,471 @@
 //! Provides utilities for modifying shims for 3rd-party executables
 
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
 use std::fs;
 use std::io;
-use std::path::Path;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
 
+use crate::change::{self, Action, Change, Subject};
 use crate::error::{Context, ErrorKind, Fallible, VoltaError};
-use crate::fs::read_dir_eager;
+use crate::fs::{dir_entry_match, ok_if_not_found, read_dir_eager, remove_file_if_exists};
 use crate::layout::volta_home;
+use crate::style::progress_spinner;
 use crate::sync::VoltaLock;
-use log::debug;
+use crate::tool::{BinConfig, PackageConfig};
+use indicatif::ProgressBar;
+use log::{debug, warn};
+use tempfile::Builder;
 
-pub use platform::create;
+/// The shims that Volta provides for its own tools, rather than for installed packages
+const DEFAULT_SHIMS: [&str; 6] = ["node", "npm", "npx", "pnpm", "yarn", "yarnpkg"];
 
-pub fn regenerate_shims_for_dir(dir: &Path) -> Fallible<()> {
+/// The prefix of the temporary files that new shims are written to before replacing the old ones
+const STAGING_PREFIX: &str = ".volta-shim-staging";
+
+/// How many times to retry replacing a shim that is in use by another process
+const REPLACE_RETRIES: u64 = 10;
+
+/// The names of Volta's own executables, which a shim must never shadow
+const RESERVED_SHIMS: [&str; 3] = ["volta", "volta-shim", "volta-migrate"];
+
+/// Determines whether a shim with the given name would shadow one of Volta's own executables
+pub fn is_reserved(shim_name: &str) -> bool {
+    RESERVED_SHIMS
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(shim_name))
+}
+
+/// Lists the shims that shadow Volta's own executables
+///
+/// Older versions of Volta created these for packages that provide a bin with a reserved name,
+/// such as the `volta` package on npm. Only actual shims are reported, never the executables.
+pub fn shadowing_shims() -> Fallible<Vec<String>> {
+    let mut shims = Vec::new();
+    for shim_name in RESERVED_SHIMS {
+        if platform::is_shim(shim_name)? {
+            shims.push(shim_name.to_owned());
+        }
+    }
+    Ok(shims)
+}
+
+/// Removes a shim that shadows one of Volta's own executables, along with its bin config
+///
+/// The bin is also dropped from the config of the package that provided it, so that the package
+/// can still be uninstalled normally.
+pub fn remove_shadowing_shim(shim_name: &str) -> Fallible<()> {
+    let home = volta_home()?;
+    let bin_config_file = home.default_tool_bin_config(shim_name);
+
+    if let Some(bin_config) = BinConfig::from_file_if_exists(&bin_config_file)? {
+        let package_config_file = home.default_package_config_file(&bin_config.package);
+        if let Some(mut package_config) = PackageConfig::from_file_if_exists(package_config_file)? {
+            package_config.bins.retain(|bin| bin != shim_name);
+            package_config.write()?;
+        }
+    }
+
+    remove_file_if_exists(bin_config_file)?;
+    delete(shim_name)?;
+    Ok(())
+}
+
+/// Lists the shims for package binaries that are no longer installed
+///
+/// A shim is orphaned when it has no bin config, or when the package that its bin config names
+/// has no package config, such as after an uninstall that was interrupted. Volta's own shims are
+/// never orphaned, and shims shadowing its executables are reported by `shadowing_shims`.
+pub fn orphaned_shims() -> Fallible<Vec<String>> {
+    let home = volta_home()?;
+    let dir = home.shim_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = read_dir_eager(dir).with_context(|| ErrorKind::ReadDirError {
+        dir: dir.to_owned(),
+    })?;
+
+    let shims: BTreeSet<String> = contents
+        .filter_map(platform::entry_to_shim_name)
+        .filter(|shim_name| {
+            !shim_name.starts_with(STAGING_PREFIX)
+                && !is_reserved(shim_name)
+                && !DEFAULT_SHIMS.contains(&shim_name.as_str())
+        })
+        .collect();
+
+    let mut orphans = Vec::new();
+    for shim_name in shims {
+        let installed = BinConfig::from_file_if_exists(home.default_tool_bin_config(&shim_name))?
+            .is_some_and(|config| home.default_package_config_file(&config.package).exists());
+        if !installed {
+            orphans.push(shim_name);
+        }
+    }
+    Ok(orphans)
+}
+
+/// Removes a shim for a package binary that is no longer installed, along with its bin config
+pub fn remove_orphaned_shim(shim_name: &str) -> Fallible<()> {
+    remove_file_if_exists(volta_home()?.default_tool_bin_config(shim_name))?;
+    delete(shim_name)?;
+    Ok(())
+}
+
+/// Creates a shim for the given executable name
+///
+/// A shim that already points at the current shim executable is left as it is. Names reserved
+/// for Volta's own executables are refused, as the shim would shadow Volta itself.
+pub fn create(shim_name: &str) -> Fallible<ShimResult> {
+    if is_reserved(shim_name) {
+        return Err(ErrorKind::ShimNameReserved {
+            name: shim_name.to_owned(),
+        }
+        .into());
+    }
+
+    let path = volta_home()?.shim_file(shim_name);
+    let status = if platform::is_current(shim_name)? {
+        ShimStatus::AlreadyExists
+    } else if change::exists(&path) {
+        platform::create(shim_name)?;
+        ShimStatus::Replaced
+    } else {
+        platform::create(shim_name)?;
+        ShimStatus::Created
+    };
+
+    Ok(ShimResult::new(shim_name, path, status))
+}
+
+/// Regenerates every shim in the given shim directory
+///
+/// Shims that already point at the current shim executable are left untouched, so any attributes
+/// set on them are preserved. Collisions between shims that differ only by case are detected up
+/// front on case-insensitive file systems, before anything is removed. If an individual shim
+/// can't be replaced, the rest are still regenerated and the failures are reported together at
+/// the end, so running the regeneration again after fixing the problem completes the set.
+///
+/// Up to `jobs` shims are regenerated at once. Returns the result for each shim, in name order.
+pub fn regenerate_shims_for_dir(dir: &Path, jobs: NonZeroUsize) -> Fallible<Vec<ShimResult>> {
     // Acquire a lock on the Volta directory, if possible, to prevent concurrent changes
     let _lock = VoltaLock::acquire();
     debug!("Rebuilding shims for directory: {}", dir.display());
-    for shim_name in get_shim_list_deduped(dir)?.iter() {
-        delete(shim_name)?;
-        create(shim_name)?;
+
+    let owners = bin_owners(volta_home()?.default_bin_dir())?;
+    let shims = get_shim_list_deduped(dir, &owners)?;
+
+    if is_case_insensitive(dir) {
+        debug!("Shim directory is case-insensitive, checking for collisions");
+        check_case_collisions(&shims, &owners)?;
     }
 
-    Ok(())
+    let shims: Vec<String> = shims.into_iter().collect();
+    let progress = progress_spinner(format!("Regenerating shims (0/{})", shims.len()));
+    let (results, failures) = regenerate_all(&shims, jobs, &progress)?;
+    progress.finish_and_clear();
+
+    if failures.is_empty() {
+        Ok(results)
+    } else {
+        Err(ErrorKind::ShimRegenerationError { failures }.into())
+    }
+}
+
+/// Regenerates the shims on up to `jobs` threads, returning the results of the ones that were
+/// regenerated and the paths of the ones that failed
+fn regenerate_all(
+    shims: &[String],
+    jobs: NonZeroUsize,
+    progress: &ProgressBar,
+) -> Fallible<(Vec<ShimResult>, Vec<PathBuf>)> {
+    let home = volta_home()?;
+    let next = AtomicUsize::new(0);
+    let finished = AtomicUsize::new(0);
+    let workers = jobs.get().min(shims.len()).max(1);
+    debug!("Regenerating {} shims on {} threads", shims.len(), workers);
+
+    let mut outcomes: Vec<(usize, Result<ShimResult, PathBuf>)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut outcomes = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(shim_name) = shims.get(index) else {
+                            break outcomes;
+                        };
+
+                        match regenerate(shim_name) {
+                            Ok(result) => outcomes.push((index, Ok(result))),
+                            Err(error) => {
+                                let path = home.shim_file(shim_name);
+                                match error.source() {
+                                    Some(cause) => {
+                                        warn!(
+                                            "Could not regenerate shim {}: {}",
+                                            path.display(),
+                                            cause
+                                        )
+                                    }
+                                    None => warn!("Could not regenerate shim {}", path.display()),
+                                }
+                                outcomes.push((index, Err(path)));
+                            }
+                        }
+
+                        let count = finished.fetch_add(1, Ordering::Relaxed) + 1;
+                        progress.set_message(format!(
+                            "Regenerating shims ({}/{})",
+                            count,
+                            shims.len()
+                        ));
+                    }
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .expect("shim regeneration threads don't panic")
+            })
+            .collect()
+    });
+
+    // Report the outcomes in the same order as the shims, however the threads finished
+    outcomes.sort_by_key(|(index, _)| *index);
+    let (results, failures): (Vec<_>, Vec<_>) = outcomes
+        .into_iter()
+        .map(|(_, outcome)| outcome)
+        .partition(Result::is_ok);
+
+    Ok((
+        results.into_iter().filter_map(Result::ok).collect(),
+        failures.into_iter().filter_map(Result::err).collect(),
+    ))
 }
 
-fn get_shim_list_deduped(dir: &Path) -> Fallible<HashSet<String>> {
+fn regenerate(shim_name: &str) -> Fallible<ShimResult> {
+    // Creating a shim replaces the old one in a single step, so it is never missing in between
+    let result = create(shim_name)?;
+    if result.status == ShimStatus::AlreadyExists {
+        debug!("Shim for '{}' is up to date", shim_name);
+    }
+    Ok(result)
+}
+
+/// Writes a shim atomically, so that a concurrent run never sees a missing or half-written shim
+///
+/// `write` creates the new shim at the temporary path it is given, which is then renamed over the
+/// old one. The temporary file is in the shim directory itself, since a rename from the Volta tmp
+/// directory isn't atomic when it is on another device. On Windows, a shim that is being executed
+/// can't be replaced, so the rename is retried for a while before giving up.
+fn write_atomically<F>(shim: &Path, write: F) -> io::Result<()>
+where
+    F: FnMut(&Path) -> io::Result<()>,
+{
+    let dir = shim.parent().unwrap_or_else(|| Path::new("."));
+    let mut staged = Builder::new()
+        .prefix(STAGING_PREFIX)
+        .make_in(dir, write)?
+        .into_temp_path();
+
+    let mut attempt = 0;
+    loop {
+        match staged.persist(shim) {
+            Ok(()) => return Ok(()),
+            Err(error) if attempt < REPLACE_RETRIES && is_in_use(&error.error) => {
+                attempt += 1;
+                debug!(
+                    "Shim {} is in use, retrying the replacement (attempt {})",
+                    shim.display(),
+                    attempt
+                );
+                thread::sleep(Duration::from_millis(10 * attempt));
+                staged = error.path;
+            }
+            Err(error) => return Err(error.error),
+        }
+    }
+}
+
+/// Determines whether replacing a file failed because another process has it open
+#[cfg(windows)]
+fn is_in_use(error: &io::Error) -> bool {
+    // ERROR_ACCESS_DENIED and ERROR_SHARING_VIOLATION
+    matches!(error.raw_os_error(), Some(5 | 32))
+}
+
+#[cfg(unix)]
+fn is_in_use(_error: &io::Error) -> bool {
+    false
+}
+
+/// Lists the shims to regenerate, both from the shim directory and from the installed bin configs
+///
+/// Including the bin configs means that a shim which was removed but not recreated by an earlier,
+/// interrupted regeneration is still restored.
+fn get_shim_list_deduped(
+    dir: &Path,
+    owners: &HashMap<String, String>,
+) -> Fallible<BTreeSet<String>> {
     let contents = read_dir_eager(dir).with_context(|| ErrorKind::ReadDirError {
         dir: dir.to_owned(),
     })?;
 
+    // Staging files left behind by an interrupted write are never shims themselves
+    let mut shims: BTreeSet<String> = contents
+        .filter_map(platform::entry_to_shim_name)
+        .filter(|shim_name| !shim_name.starts_with(STAGING_PREFIX))
+        .collect();
+    shims.extend(owners.keys().cloned());
+
+    // Never recreate a shim over one of Volta's own executables
+    shims.retain(|shim_name| !is_reserved(shim_name));
+
+    // On Windows, the default shims are installed in Program Files, so we don't need to generate them here
     #[cfg(unix)]
-    {
-        let mut shims: HashSet<String> =
-            contents.filter_map(platform::entry_to_shim_name).collect();
-        shims.insert("node".into());
-        shims.insert("npm".into());
-        shims.insert("npx".into());
-        shims.insert("pnpm".into());
-        shims.insert("yarn".into());
-        shims.insert("yarnpkg".into());
-        Ok(shims)
+    shims.extend(DEFAULT_SHIMS.iter().map(|&name| name.to_owned()));
+
+    Ok(shims)
+}
+
+/// Reads the bin configs in the given directory, mapping each bin name to the package that owns it
+fn bin_owners(bin_config_dir: &Path) -> Fallible<HashMap<String, String>> {
+    dir_entry_match(bin_config_dir, |entry| {
+        BinConfig::from_file(entry.path())
+            .ok()
+            .map(|config| (config.name, config.package))
+    })
+    .or_else(ok_if_not_found)
+    .map(|owners| owners.into_iter().collect())
+    .with_context(|| ErrorKind::ReadBinConfigDirError {
+        dir: bin_config_dir.to_owned(),
+    })
+}
+
+/// Determines whether the given directory is on a case-insensitive file system
+///
+/// This creates a temporary, lowercase-named file in the directory and checks whether it can also
+/// be found using an uppercase name.
+fn is_case_insensitive(dir: &Path) -> bool {
+    let probe = match Builder::new().prefix(".case-probe").tempfile_in(dir) {
+        Ok(probe) => probe,
+        Err(error) => {
+            debug!(
+                "Could not check the case sensitivity of {}: {}",
+                dir.display(),
+                error
+            );
+            return false;
+        }
+    };
+
+    probe
+        .path()
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| fs::symlink_metadata(dir.join(name.to_uppercase())).is_ok())
+        .unwrap_or(false)
+}
+
+/// Fails if any two shims would resolve to the same file on a case-insensitive file system
+fn check_case_collisions(
+    shims: &BTreeSet<String>,
+    owners: &HashMap<String, String>,
+) -> Fallible<()> {
+    let mut seen: HashMap<String, &str> = HashMap::new();
+
+    for shim_name in shims {
+        if let Some(existing) = seen.insert(shim_name.to_lowercase(), shim_name) {
+            return Err(ErrorKind::ShimCaseCollision {
+                first_bin: existing.to_owned(),
+                first_owner: describe_owner(existing, owners),
+                second_bin: shim_name.clone(),
+                second_owner: describe_owner(shim_name, owners),
+            }
+            .into());
+        }
     }
 
-    #[cfg(windows)]
-    {
-        // On Windows, the default shims are installed in Program Files, so we don't need to generate them here
-        Ok(contents.filter_map(platform::entry_to_shim_name).collect())
+    Ok(())
+}
+
+fn describe_owner(shim_name: &str, owners: &HashMap<String, String>) -> String {
+    match owners.get(shim_name) {
+        Some(package) => format!("package '{}'", package),
+        None if DEFAULT_SHIMS.contains(&shim_name) => "Volta".into(),
+        None => "an unknown package".into(),
     }
 }
 
-#[derive(PartialEq, Eq)]
-pub enum ShimResult {
+/// What creating or deleting a shim did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShimStatus {
+    /// There was no shim, so one was created
     Created,
+    /// There was an outdated shim, or another file, which was replaced
+    Replaced,
+    /// The shim already pointed at the current shim executable, so it was left as it was
     AlreadyExists,
     Deleted,
     DoesntExist,
 }
 
+/// The outcome of creating or deleting a single shim
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShimResult {
+    pub name: String,
+    pub path: PathBuf,
+    pub status: ShimStatus,
+}
+
+impl ShimResult {
+    fn new(name: &str, path: PathBuf, status: ShimStatus) -> Self {
+        ShimResult {
+            name: name.to_owned(),
+            path,
+            status,
+        }
+    }
+
+    /// The change to the shim directory, if anything changed
+    pub fn change(&self) -> Option<Change> {
+        let action = match self.status {
+            ShimStatus::Created => Action::Created,
+            ShimStatus::Replaced => Action::Replaced,
+            ShimStatus::Deleted => Action::Removed,
+            ShimStatus::AlreadyExists | ShimStatus::DoesntExist => return None,
+        };
+        Some(Change::new(Subject::Shim, action, self.path.clone()))
+    }
+}
+
 pub fn delete(shim_name: &str) -> Fallible<ShimResult> {
     let shim = volta_home()?.shim_file(shim_name);
+    let result = |status| Ok(ShimResult::new(shim_name, shim.clone(), status));
+
+    // Volta's own executables may live in the shim directory, so only remove actual shims
+    if is_reserved(shim_name) && !platform::is_shim(shim_name)? {
+        return result(ShimStatus::DoesntExist);
+    }
 
     #[cfg(windows)]
     platform::delete_git_bash_script(shim_name)?;
 
-    match fs::remove_file(shim) {
-        Ok(_) => Ok(ShimResult::Deleted),
+    match fs::remove_file(&shim) {
+        Ok(_) => result(ShimStatus::Deleted),
         Err(err) => {
             if err.kind() == io::ErrorKind::NotFound {
-                Ok(ShimResult::DoesntExist)
+                result(ShimStatus::DoesntExist)
             } else {
                 Err(VoltaError::from_source(
                     err,
@@ -88,34 +485,59 @@ mod platform {
     //! On macOS and Linux, creating a shim involves creating a symlink to the `volta-shim`
     //! executable. Additionally, filtering the shims from directory entries means looking
     //! for symlinks and ignoring the actual binaries
+    //!
+    //! When Volta is installed inside the Volta directory (as the installer does by default), the
+    //! links are relative, so that the shims keep working when the directory is moved or copied.
     use std::ffi::OsStr;
-    use std::fs::{DirEntry, Metadata};
-    use std::io;
+    use std::fs::{read_link, DirEntry, Metadata};
+    use std::path::PathBuf;
 
-    use super::ShimResult;
-    use crate::error::{ErrorKind, Fallible, VoltaError};
-    use crate::fs::symlink_file;
+    use super::write_atomically;
+    use crate::error::{Context, ErrorKind, Fallible};
+    use crate::fs::{relative_path, symlink_file};
     use crate::layout::{volta_home, volta_install};
 
-    pub fn create(shim_name: &str) -> Fallible<ShimResult> {
-        let executable = volta_install()?.shim_executable();
+    /// Links the shim to the `volta-shim` executable, replacing any existing shim
+    pub fn create(shim_name: &str) -> Fallible<()> {
+        let target = link_target()?;
         let shim = volta_home()?.shim_file(shim_name);
 
-        match symlink_file(executable, shim) {
-            Ok(_) => Ok(ShimResult::Created),
-            Err(err) => {
-                if err.kind() == io::ErrorKind::AlreadyExists {
-                    Ok(ShimResult::AlreadyExists)
-                } else {
-                    Err(VoltaError::from_source(
-                        err,
-                        ErrorKind::ShimCreateError {
-                            name: shim_name.to_string(),
-                        },
-                    ))
-                }
+        write_atomically(&shim, |staged| symlink_file(&target, staged)).with_context(|| {
+            ErrorKind::ShimCreateError {
+                name: shim_name.to_string(),
+            }
+        })
+    }
+
+    /// Checks whether the shim is already a link to the current shim executable, in the form
+    /// that `create` would write
+    pub fn is_current(shim_name: &str) -> Fallible<bool> {
+        let target = link_target()?;
+        let shim = volta_home()?.shim_file(shim_name);
+
+        Ok(read_link(shim).is_ok_and(|link| link == target))
+    }
+
+    /// The path that the shims link to: relative to the shim directory if the `volta-shim`
+    /// executable is inside the Volta directory, and absolute otherwise
+    fn link_target() -> Fallible<PathBuf> {
+        let executable = volta_install()?.shim_executable();
+        let home = volta_home()?;
+
+        if executable.starts_with(home.root()) {
+            if let Some(relative) = relative_path(home.shim_dir(), executable) {
+                return Ok(relative);
             }
         }
+        Ok(executable.to_owned())
+    }
+
+    /// Checks whether the file with the shim's name is a link to any `volta-shim` executable
+    pub fn is_shim(shim_name: &str) -> Fallible<bool> {
+        let shim = volta_home()?.shim_file(shim_name);
+
+        Ok(read_link(shim)
+            .is_ok_and(|target| target.file_name().and_then(OsStr::to_str) == Some("volta-shim")))
     }
 
     pub fn entry_to_shim_name((entry, metadata): (DirEntry, Metadata)) -> Option<String> {
@@ -144,9 +566,11 @@ mod platform {
     //! Finally, filtering directory entries to find the shim files involves looking for the .cmd
     //! files.
     use std::ffi::OsStr;
-    use std::fs::{write, DirEntry, Metadata};
+    use std::fs::{read_to_string, DirEntry, Metadata, OpenOptions};
+    use std::io::{self, Write};
+    use std::path::Path;
 
-    use super::ShimResult;
+    use super::write_atomically;
     use crate::error::{Context, ErrorKind, Fallible};
     use crate::fs::remove_file_if_exists;
     use crate::layout::volta_home;
@@ -158,22 +582,49 @@ volta run %~n0 %*
     const GIT_BASH_SCRIPT_CONTENTS: &str = r#"#!/bin/bash
 volta run "$(basename $0)" "$@""#;
 
-    pub fn create(shim_name: &str) -> Fallible<ShimResult> {
+    pub fn create(shim_name: &str) -> Fallible<()> {
         let shim = volta_home()?.shim_file(shim_name);
 
-        write(shim, SHIM_SCRIPT_CONTENTS).with_context(|| ErrorKind::ShimCreateError {
+        write_script(&shim, SHIM_SCRIPT_CONTENTS).with_context(|| ErrorKind::ShimCreateError {
             name: shim_name.to_owned(),
         })?;
 
         let git_bash_script = volta_home()?.shim_git_bash_script_file(shim_name);
 
-        write(git_bash_script, GIT_BASH_SCRIPT_CONTENTS).with_context(|| {
+        write_script(&git_bash_script, GIT_BASH_SCRIPT_CONTENTS).with_context(|| {
             ErrorKind::ShimCreateError {
                 name: shim_name.to_owned(),
             }
-        })?;
+        })
+    }
 
-        Ok(ShimResult::Created)
+    fn write_script(path: &Path, contents: &str) -> io::Result<()> {
+        write_atomically(path, |staged| {
+            OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(staged)?
+                .write_all(contents.as_bytes())
+        })
+    }
+
+    /// Checks whether both shim scripts already have the current contents
+    pub fn is_current(shim_name: &str) -> Fallible<bool> {
+        let shim = volta_home()?.shim_file(shim_name);
+        let git_bash_script = volta_home()?.shim_git_bash_script_file(shim_name);
+
+        Ok(
+            read_to_string(shim).is_ok_and(|contents| contents == SHIM_SCRIPT_CONTENTS)
+                && read_to_string(git_bash_script)
+                    .is_ok_and(|contents| contents == GIT_BASH_SCRIPT_CONTENTS),
+        )
+    }
+
+    /// Checks whether the shim script exists
+    ///
+    /// Volta's own executables have the `.exe` extension, so the script is never one of them.
+    pub fn is_shim(shim_name: &str) -> Fallible<bool> {
+        Ok(volta_home()?.shim_file(shim_name).is_file())
     }
 
     pub fn entry_to_shim_name((entry, _): (DirEntry, Metadata)) -> Option<String> {
@@ -195,3 +646,159 @@ volta run "$(basename $0)" "$@""#;
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn owners(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|&(bin, package)| (bin.to_owned(), package.to_owned()))
+            .collect()
+    }
+
+    fn shims(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|&name| name.to_owned()).collect()
+    }
+
+    #[test]
+    fn reserved_names_cover_volta_executables() {
+        assert!(is_reserved("volta"));
+        assert!(is_reserved("volta-shim"));
+        assert!(is_reserved("volta-migrate"));
+        assert!(is_reserved("Volta"));
+        assert!(!is_reserved("volta-wrapper"));
+        assert!(!is_reserved("node"));
+    }
+
+    #[test]
+    fn case_collision_names_both_packages() {
+        let owners = owners(&[("Foo", "foo-upper"), ("foo", "foo-lower")]);
+
+        let error = check_case_collisions(&shims(&["Foo", "bar", "foo"]), &owners).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::ShimCaseCollision {
+                first_bin: "Foo".into(),
+                first_owner: "package 'foo-upper'".into(),
+                second_bin: "foo".into(),
+                second_owner: "package 'foo-lower'".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn case_collision_with_default_shim() {
+        let owners = owners(&[("Node", "node-cli")]);
+
+        let error = check_case_collisions(&shims(&["Node", "node"]), &owners).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::ShimCaseCollision {
+                first_bin: "Node".into(),
+                first_owner: "package 'node-cli'".into(),
+                second_bin: "node".into(),
+                second_owner: "Volta".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn distinct_names_do_not_collide() {
+        let owners = owners(&[("tsc", "typescript"), ("tsserver", "typescript")]);
+
+        assert!(check_case_collisions(&shims(&["node", "tsc", "tsserver"]), &owners).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn bin_owners_reads_collision_fixture() {
+        let dir = tempdir().unwrap();
+        let bin_config = |name: &str, package: &str| {
+            format!(
+                r#"{{"name":"{}","package":"{}","version":"1.0.0","platform":{{"node":"20.11.1","npm":null,"yarn":null}},"manager":"Npm"}}"#,
+                name, package
+            )
+        };
+        fs::write(dir.path().join("Foo.json"), bin_config("Foo", "foo-upper")).unwrap();
+        fs::write(dir.path().join("foo.json"), bin_config("foo", "foo-lower")).unwrap();
+        fs::write(dir.path().join("broken.json"), "not json").unwrap();
+
+        let owners = bin_owners(dir.path()).unwrap();
+        assert_eq!(owners.len(), 2);
+
+        // Linux file systems are case-sensitive, so the collision has to be checked explicitly
+        let shims = owners.keys().cloned().collect();
+        let error = check_case_collisions(&shims, &owners).unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::ShimCaseCollision { first_bin, second_bin, .. }
+                if first_bin == "Foo" && second_bin == "foo"
+        ));
+    }
+
+    #[test]
+    fn concurrent_writes_are_never_observed_partially() {
+        use std::io::Write;
+        use std::sync::atomic::AtomicBool;
+
+        let dir = tempdir().unwrap();
+        let shim = dir.path().join("shim");
+        let contents = |writer: usize| vec![b'a' + writer as u8; 64 * 1024];
+        let write = |writer: usize| {
+            write_atomically(&shim, |staged| {
+                fs::OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(staged)?
+                    .write_all(&contents(writer))
+            })
+        };
+        write(0).unwrap();
+
+        let done = AtomicBool::new(false);
+        thread::scope(|scope| {
+            let reader = scope.spawn(|| {
+                let mut reads = 0;
+                while !done.load(Ordering::Relaxed) {
+                    let read = fs::read(&shim).unwrap();
+                    assert_eq!(read.len(), 64 * 1024, "read a truncated shim");
+                    assert!(
+                        read.iter().all(|&byte| byte == read[0]),
+                        "read a mixed shim"
+                    );
+                    reads += 1;
+                }
+                reads
+            });
+
+            let writers: Vec<_> = (0..8)
+                .map(|writer| {
+                    scope.spawn(move || {
+                        for _ in 0..50 {
+                            write(writer).unwrap();
+                        }
+                    })
+                })
+                .collect();
+            for writer in writers {
+                writer.join().unwrap();
+            }
+
+            done.store(true, Ordering::Relaxed);
+            assert!(reader.join().unwrap() > 0);
+        });
+
+        // Every staging file was renamed over the shim
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn missing_bin_config_dir_has_no_owners() {
+        let dir = tempdir().unwrap();
+        let owners = bin_owners(&dir.path().join("missing")).unwrap();
+        assert!(owners.is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use std::process::exit;
+use std::process::{exit, ExitStatus};
 use std::sync::atomic::{AtomicBool, Ordering};
 
 use log::debug;
@@ -21,3 +21,49 @@ pub fn setup_signal_handler() {
         debug!("Unable to set Ctrl+C handler, SIGINT will not be handled correctly");
     }
 }
+
+/// The exit code to report for a tool that was run by Volta
+pub fn tool_exit_code(status: ExitStatus) -> i32 {
+    status.code().unwrap_or(1)
+}
+
+/// Exit with the same status as a tool that was run by Volta
+///
+/// On Unix, a tool that was terminated by a signal (such as SIGTERM or SIGSEGV) has no exit code,
+/// so the same signal is raised on this process after restoring its default handler. That way, a
+/// parent process that inspects the signal, like a process supervisor, observes the same
+/// termination as if it had run the tool directly.
+pub fn exit_like_tool(status: ExitStatus) -> ! {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+
+        if let Some(signal) = status.signal() {
+            raise_with_default_handler(signal);
+        }
+    }
+
+    exit(tool_exit_code(status));
+}
+
+/// Raise a signal on this process with its default handler, which terminates the process for the
+/// signals that can terminate a tool
+///
+/// If the signal doesn't end the process, this returns so that the caller can exit instead.
+#[cfg(unix)]
+fn raise_with_default_handler(signal: i32) {
+    debug!("Tool was terminated by signal {}, raising it again", signal);
+
+    // SAFETY: These calls only change the disposition and mask of the signal for this process,
+    // which is about to exit, and don't access any memory owned by Rust.
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+
+        let mut unblocked = std::mem::zeroed();
+        libc::sigemptyset(&mut unblocked);
+        libc::sigaddset(&mut unblocked, signal);
+        libc::pthread_sigmask(libc::SIG_UNBLOCK, &unblocked, std::ptr::null_mut());
+
+        libc::raise(signal);
+    }
+}
@@ -1,16 +1,33 @@
 //! The view layer of Volta, with utilities for styling command-line output.
 use std::borrow::Cow;
+use std::env;
 use std::error::Error;
-use std::time::Duration;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 use archive::Origin;
 use cfg_if::cfg_if;
 use console::{style, StyledObject};
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use once_cell::sync::Lazy;
+use serde::Serialize;
 use terminal_size::{terminal_size, Width};
 
 pub const MAX_WIDTH: usize = 100;
+/// The narrowest width used for output, even if the terminal reports a smaller size
+pub const MIN_WIDTH: usize = 20;
 const MAX_PROGRESS_WIDTH: usize = 40;
+const MIN_PROGRESS_WIDTH: usize = 10;
+
+const VOLTA_NO_PROGRESS: &str = "VOLTA_NO_PROGRESS";
+const VOLTA_PROGRESS: &str = "VOLTA_PROGRESS";
+
+/// The shortest time between two JSON progress records for the same download
+const JSON_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+static PROGRESS_DISABLED: AtomicBool = AtomicBool::new(false);
+static JSON_PROGRESS: AtomicBool = AtomicBool::new(false);
 
 /// Generate the styled prefix for a success message
 pub fn success_prefix() -> StyledObject<&'static str> {
@@ -48,16 +65,160 @@ where
     format!("{:}@{:}", name, version)
 }
 
-/// Get the width of the terminal, limited to a maximum of MAX_WIDTH
+/// Get the width of the terminal, clamped between MIN_WIDTH and MAX_WIDTH
+///
+/// Returns `None` if the output isn't a terminal, or if the terminal reports a width of zero (as
+/// some CI runners and minimal containers do), in which case callers shouldn't wrap their output
+/// or should fall back to MAX_WIDTH. The width is only detected once per process.
 pub fn text_width() -> Option<usize> {
-    terminal_size().map(|(Width(w), _)| (w as usize).min(MAX_WIDTH))
+    static TEXT_WIDTH: Lazy<Option<usize>> =
+        Lazy::new(|| clamp_width(terminal_size().map(|(Width(w), _)| w)));
+
+    *TEXT_WIDTH
+}
+
+fn clamp_width(width: Option<u16>) -> Option<usize> {
+    match width {
+        None | Some(0) => None,
+        Some(width) => Some((width as usize).clamp(MIN_WIDTH, MAX_WIDTH)),
+    }
+}
+
+/// Hides every progress bar and spinner for the rest of the process, as `--no-progress` does
+///
+/// Messages logged alongside them are still shown.
+pub fn disable_progress() {
+    PROGRESS_DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Determines whether progress should be drawn, which can be turned off with `--no-progress` or
+/// by setting `VOLTA_NO_PROGRESS` (to anything other than `0`)
+pub fn progress_enabled() -> bool {
+    if PROGRESS_DISABLED.load(Ordering::Relaxed) {
+        return false;
+    }
+
+    match env::var_os(VOLTA_NO_PROGRESS) {
+        Some(value) => value.is_empty() || value == "0",
+        None => true,
+    }
+}
+
+/// Reports the progress of downloads as JSON records on stderr instead of drawing progress bars, as
+/// `--progress json` does
+pub fn enable_json_progress() {
+    JSON_PROGRESS.store(true, Ordering::Relaxed);
+}
+
+/// Determines whether download progress is reported as JSON, which can also be turned on by setting
+/// `VOLTA_PROGRESS=json`
+///
+/// Turning progress off takes precedence, so nothing is reported with `--no-progress`.
+fn json_progress_enabled() -> bool {
+    progress_enabled()
+        && (JSON_PROGRESS.load(Ordering::Relaxed)
+            || env::var(VOLTA_PROGRESS).is_ok_and(|format| format.eq_ignore_ascii_case("json")))
+}
+
+/// Creates the underlying indicator for every progress bar (with a length) and spinner (without
+/// one), so that none of them can be drawn when progress is disabled
+fn new_progress(len: Option<u64>) -> ProgressBar {
+    let target = if progress_enabled() {
+        ProgressDrawTarget::stderr()
+    } else {
+        ProgressDrawTarget::hidden()
+    };
+
+    ProgressBar::with_draw_target(len, target)
+}
+
+/// The progress of fetching a tool, either drawn as a bar or reported as JSON records
+pub struct FetchProgress {
+    reporter: Reporter,
+}
+
+enum Reporter {
+    Bar(ProgressBar),
+    Json(JsonProgress),
+}
+
+impl FetchProgress {
+    /// Advances the progress by the given number of bytes
+    pub fn inc(&mut self, delta: u64) {
+        match &mut self.reporter {
+            Reporter::Bar(bar) => bar.inc(delta),
+            Reporter::Json(json) => {
+                json.record.read += delta;
+                if let Some(line) = json.line_if_due(Instant::now()) {
+                    eprintln!("{}", line);
+                }
+            }
+        }
+    }
+
+    /// Clears the bar, or reports the final amount that was read
+    pub fn finish_and_clear(self) {
+        match self.reporter {
+            Reporter::Bar(bar) => bar.finish_and_clear(),
+            Reporter::Json(json) => eprintln!("{}", json.line()),
+        }
+    }
 }
 
-/// Constructs a command-line progress bar based on the specified Origin enum
-/// (e.g., `Origin::Remote`), details string (e.g., `"v1.23.4"`), and logical
-/// length (i.e., the number of logical progress steps in the process being
-/// visualized by the progress bar).
-pub fn progress_bar(origin: Origin, details: &str, len: u64) -> ProgressBar {
+/// A newline-delimited JSON progress record, e.g.
+/// `{"tool":"node","version":"18.17.0","read":123456,"total":23456789}`
+#[derive(Serialize)]
+struct ProgressRecord {
+    tool: String,
+    version: String,
+    read: u64,
+    total: u64,
+}
+
+struct JsonProgress {
+    record: ProgressRecord,
+    last_reported: Option<Instant>,
+}
+
+impl JsonProgress {
+    /// Produces the current record, unless one was already reported within the last interval
+    fn line_if_due(&mut self, now: Instant) -> Option<String> {
+        match self.last_reported {
+            Some(last) if now.saturating_duration_since(last) < JSON_PROGRESS_INTERVAL => None,
+            _ => {
+                self.last_reported = Some(now);
+                Some(self.line())
+            }
+        }
+    }
+
+    fn line(&self) -> String {
+        // Serializing can't fail: the record only contains strings and numbers
+        serde_json::to_string(&self.record).expect("progress record is serializable")
+    }
+}
+
+/// Constructs the progress of fetching a tool based on the specified Origin enum (e.g.,
+/// `Origin::Remote`), the tool and its version, and logical length (i.e., the number of logical
+/// progress steps in the process being visualized by the progress bar).
+///
+/// With JSON progress, records are written to stderr at most every 100ms instead.
+pub fn progress_bar<V: Display>(origin: Origin, tool: &str, version: V, len: u64) -> FetchProgress {
+    if json_progress_enabled() {
+        return FetchProgress {
+            reporter: Reporter::Json(JsonProgress {
+                record: ProgressRecord {
+                    tool: tool.to_owned(),
+                    version: version.to_string(),
+                    read: 0,
+                    total: len,
+                },
+                last_reported: None,
+            }),
+        };
+    }
+
+    let details = tool_version(tool, version);
     let action = action_str(origin);
     let action_width = action.len() + 2; // plus 2 spaces to look nice
     let msg_width = action_width + 1 + details.len();
@@ -65,12 +226,9 @@ pub fn progress_bar(origin: Origin, details: &str, len: u64) -> ProgressBar {
     //   Fetching node@9.11.2  [=============>                          ]  34%
     // |--------| |---------|   |--------------------------------------|  |-|
     //    action    details                      bar                 percentage
-    let bar_width = match text_width() {
-        Some(width) => MAX_PROGRESS_WIDTH.min(width - 2 - msg_width - 2 - 2 - 1 - 3 - 1),
-        None => MAX_PROGRESS_WIDTH,
-    };
+    let bar_width = progress_bar_width(text_width(), msg_width);
 
-    let progress = ProgressBar::new(len);
+    let progress = new_progress(Some(len));
 
     progress.set_message(format!(
         "{: >width$} {}",
@@ -88,7 +246,21 @@ pub fn progress_bar(origin: Origin, details: &str, len: u64) -> ProgressBar {
             .progress_chars("=> "),
     );
 
-    progress
+    FetchProgress {
+        reporter: Reporter::Bar(progress),
+    }
+}
+
+/// Determines the width of the bar itself, so that the whole progress line fits in the terminal
+fn progress_bar_width(text_width: Option<usize>, msg_width: usize) -> usize {
+    match text_width {
+        Some(width) => {
+            // The message, the brackets and spacing around the bar, and the percentage
+            let available = width.saturating_sub(2 + msg_width + 2 + 2 + 1 + 3 + 1);
+            available.clamp(MIN_PROGRESS_WIDTH, MAX_PROGRESS_WIDTH)
+        }
+        None => MAX_PROGRESS_WIDTH,
+    }
 }
 
 cfg_if! {
@@ -99,13 +271,15 @@ cfg_if! {
         where
             S: Into<Cow<'static, str>>,
         {
-            let spinner = ProgressBar::new_spinner();
+            let spinner = new_progress(None);
             // Windows CMD prompt doesn't support Unicode characters, so use a simplified spinner
             let style = ProgressStyle::default_spinner().tick_chars(r#"-\|/-"#);
 
             spinner.set_message(message);
             spinner.set_style(style);
-            spinner.enable_steady_tick(Duration::from_millis(100));
+            if !spinner.is_hidden() {
+                spinner.enable_steady_tick(Duration::from_millis(100));
+            }
 
             spinner
         }
@@ -117,13 +291,89 @@ cfg_if! {
             S: Into<Cow<'static, str>>,
         {
             // ⠋ Fetching public registry: https://nodejs.org/dist/index.json
-            let spinner = ProgressBar::new_spinner();
+            let spinner = new_progress(None);
 
             spinner.set_message(message);
             spinner.set_style(ProgressStyle::default_spinner());
-            spinner.enable_steady_tick(Duration::from_millis(50));
+            if !spinner.is_hidden() {
+                spinner.enable_steady_tick(Duration::from_millis(50));
+            }
 
             spinner
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_width_is_treated_as_unknown() {
+        assert_eq!(clamp_width(Some(0)), None);
+        assert_eq!(
+            progress_bar_width(clamp_width(Some(0)), 30),
+            MAX_PROGRESS_WIDTH
+        );
+    }
+
+    #[test]
+    fn missing_terminal_has_no_width() {
+        assert_eq!(clamp_width(None), None);
+        assert_eq!(progress_bar_width(None, 30), MAX_PROGRESS_WIDTH);
+    }
+
+    #[test]
+    fn narrow_width_is_raised_to_minimum() {
+        assert_eq!(clamp_width(Some(1)), Some(MIN_WIDTH));
+        assert_eq!(clamp_width(Some(50)), Some(50));
+        assert_eq!(progress_bar_width(Some(MIN_WIDTH), 30), MIN_PROGRESS_WIDTH);
+    }
+
+    #[test]
+    fn json_progress_is_throttled() {
+        let mut progress = JsonProgress {
+            record: ProgressRecord {
+                tool: "node".into(),
+                version: "18.17.0".into(),
+                read: 0,
+                total: 2048,
+            },
+            last_reported: None,
+        };
+        let start = Instant::now();
+
+        assert_eq!(
+            progress.line_if_due(start).unwrap(),
+            r#"{"tool":"node","version":"18.17.0","read":0,"total":2048}"#
+        );
+        progress.record.read = 1024;
+        assert_eq!(
+            progress.line_if_due(start + Duration::from_millis(50)),
+            None
+        );
+        assert_eq!(
+            progress.line_if_due(start + Duration::from_millis(99)),
+            None
+        );
+        assert_eq!(
+            progress.line_if_due(start + Duration::from_millis(100)),
+            Some(r#"{"tool":"node","version":"18.17.0","read":1024,"total":2048}"#.into())
+        );
+
+        // The interval starts over from the last record
+        assert_eq!(
+            progress.line_if_due(start + Duration::from_millis(150)),
+            None
+        );
+        assert!(progress
+            .line_if_due(start + Duration::from_millis(200))
+            .is_some());
+    }
+
+    #[test]
+    fn huge_width_is_limited_to_maximum() {
+        assert_eq!(clamp_width(Some(u16::MAX)), Some(MAX_WIDTH));
+        assert_eq!(progress_bar_width(Some(MAX_WIDTH), 10), MAX_PROGRESS_WIDTH);
+    }
+}
@@ -22,6 +22,7 @@
 //! concurrent changes.
 
 use std::fs::{File, OpenOptions};
+use std::io;
 use std::marker::PhantomData;
 use std::ops::Drop;
 use std::sync::Mutex;
@@ -72,14 +73,7 @@ impl VoltaLock {
                 inner.count += 1;
             }
             None => {
-                let path = volta_home()?.root().join(LOCK_FILE);
-                debug!("Acquiring lock on Volta directory: {}", path.display());
-
-                let file = OpenOptions::new()
-                    .write(true)
-                    .create(true)
-                    .open(path)
-                    .with_context(|| ErrorKind::LockAcquireError)?;
+                let file = open_lock_file()?;
                 // First we try to lock the file without blocking. If that fails, then we show a spinner
                 // and block until the lock completes.
                 if file.try_lock_exclusive().is_err() {
@@ -102,6 +96,74 @@ impl VoltaLock {
     }
 }
 
+impl VoltaLock {
+    /// Acquires the lock on the Volta directory, or returns `None` if another process holds it
+    ///
+    /// Unlike `acquire`, this never waits, for work that can just as well be done later.
+    pub fn try_acquire() -> Fallible<Option<Self>> {
+        let mut state = LOCK_STATE
+            .lock()
+            .with_context(|| ErrorKind::LockAcquireError)?;
+
+        match &mut *state {
+            Some(inner) => {
+                inner.count += 1;
+            }
+            None => {
+                let file = open_lock_file()?;
+                if file.try_lock_exclusive().is_err() {
+                    return Ok(None);
+                }
+
+                *state = Some(LockState { file, count: 1 });
+            }
+        }
+
+        Ok(Some(Self {
+            _private: PhantomData,
+        }))
+    }
+
+    /// Determines whether another process currently holds the lock on the Volta directory,
+    /// without waiting for it
+    ///
+    /// The lock is released by the operating system when its process exits, even after a crash,
+    /// so a held lock means another Volta process is still running (or is stuck).
+    pub fn is_held_elsewhere() -> Fallible<bool> {
+        let state = LOCK_STATE
+            .lock()
+            .with_context(|| ErrorKind::LockAcquireError)?;
+        if state.is_some() {
+            return Ok(false);
+        }
+
+        let path = volta_home()?.root().join(LOCK_FILE);
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(error) => return Err(error).with_context(|| ErrorKind::LockAcquireError),
+        };
+
+        if file.try_lock_exclusive().is_ok() {
+            let _ = file.unlock();
+            Ok(false)
+        } else {
+            Ok(true)
+        }
+    }
+}
+
+fn open_lock_file() -> Fallible<File> {
+    let path = volta_home()?.root().join(LOCK_FILE);
+    debug!("Acquiring lock on Volta directory: {}", path.display());
+
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .open(path)
+        .with_context(|| ErrorKind::LockAcquireError)
+}
+
 impl Drop for VoltaLock {
     fn drop(&mut self) {
         // On drop, decrement the count of active locks. If the count is 1,
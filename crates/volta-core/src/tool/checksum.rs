@@ -0,0 +1,81 @@
+//! Provides helpers for verifying the SHA-256 checksums of tool archives
+
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use ring::digest::{Context as DigestContext, SHA256};
+
+/// Find the checksum for a file in the content of a `SHASUMS256.txt` file
+pub(crate) fn find_checksum<'a>(shasums: &'a str, file: &str) -> Option<&'a str> {
+    shasums.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        match (parts.next(), parts.next(), parts.next()) {
+            // `sha256sum` marks files that were read in binary mode with a leading `*`
+            (Some(checksum), Some(name), None) if name.trim_start_matches('*') == file => {
+                Some(checksum)
+            }
+            _ => None,
+        }
+    })
+}
+
+/// The file that records the checksum of an archive saved in the inventory, next to the archive
+pub(crate) fn checksum_file(archive: &Path) -> PathBuf {
+    let mut file = archive.as_os_str().to_owned();
+    file.push(".sha256");
+    file.into()
+}
+
+/// Compute the SHA-256 checksum of a file, as a lowercase hex string
+pub(crate) fn sha256_file(file: &Path) -> io::Result<String> {
+    let mut file = File::open(file)?;
+    let mut context = DigestContext::new(&SHA256);
+    let mut buffer = [0; 8192];
+
+    loop {
+        let read = match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+        context.update(&buffer[..read]);
+    }
+
+    let mut hex = String::new();
+    for byte in context.finish().as_ref() {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    Ok(hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHASUMS: &str = "\
+0a1b  node-v20.0.0-darwin-arm64.tar.gz
+2c3d  node-v20.0.0-linux-x64.tar.gz
+4e5f *node-v20.0.0-win-x64.zip
+";
+
+    #[test]
+    fn finds_listed_files() {
+        assert_eq!(
+            find_checksum(SHASUMS, "node-v20.0.0-linux-x64.tar.gz"),
+            Some("2c3d")
+        );
+        assert_eq!(
+            find_checksum(SHASUMS, "node-v20.0.0-win-x64.zip"),
+            Some("4e5f")
+        );
+    }
+
+    #[test]
+    fn ignores_unlisted_files() {
+        assert_eq!(find_checksum(SHASUMS, "node-v20.0.0-linux-x64.tar"), None);
+        assert_eq!(find_checksum("", "node-v20.0.0-linux-x64.tar.gz"), None);
+    }
+}
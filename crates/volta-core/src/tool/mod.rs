@@ -1,6 +1,6 @@
-use std::env;
 use std::fmt::{self, Display};
 use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
 
 use crate::error::{ErrorKind, Fallible};
 use crate::layout::volta_home;
@@ -8,14 +8,19 @@ use crate::session::Session;
 use crate::style::{note_prefix, success_prefix, tool_version};
 use crate::sync::VoltaLock;
 use crate::version::VersionSpec;
-use crate::VOLTA_FEATURE_PNPM;
+use attohttpc::header::HeaderMap;
 use cfg_if::cfg_if;
+use headers::{CacheControl, Expires, HeaderMapExt};
 use log::{debug, info};
 
+pub(crate) mod checksum;
 pub mod node;
 pub mod npm;
+pub(crate) mod npmrc;
+mod offline;
 pub mod package;
 pub mod pnpm;
+mod quarantine;
 mod registry;
 mod serial;
 pub mod yarn;
@@ -24,7 +29,9 @@ pub use node::{
     load_default_npm_version, Node, NODE_DISTRO_ARCH, NODE_DISTRO_EXTENSION, NODE_DISTRO_OS,
 };
 pub use npm::{BundledNpm, Npm};
-pub use package::{BinConfig, Package, PackageConfig, PackageManifest};
+pub use package::{
+    BinConfig, Package, PackageConfig, PackageManifest, PackageSource, SourcePackage,
+};
 pub use pnpm::Pnpm;
 pub use registry::PackageDetails;
 pub use yarn::Yarn;
@@ -41,10 +48,6 @@ fn info_fetched<T: Display>(tool: T) {
     info!("{} fetched {tool}", success_prefix());
 }
 
-fn info_pinned<T: Display>(tool: T) {
-    info!("{} pinned {tool} in package.json", success_prefix());
-}
-
 fn info_project_version<P, D>(project_version: P, default_version: D)
 where
     P: Display,
@@ -64,7 +67,10 @@ pub trait Tool: Display {
     /// Install a tool, making it the default so it is available everywhere on the user's machine
     fn install(self: Box<Self>, session: &mut Session) -> Fallible<()>;
     /// Pin a tool in the local project so that it is usable within the project
-    fn pin(self: Box<Self>, session: &mut Session) -> Fallible<()>;
+    ///
+    /// The pin is staged in the project and written by `Project::save_pins`, so it returns a
+    /// description of the pinned tool for the message shown once everything is saved.
+    fn pin(self: Box<Self>, session: &mut Session) -> Fallible<String>;
 }
 
 /// Specification for a tool and its associated version.
@@ -76,6 +82,8 @@ pub enum Spec {
     Pnpm(VersionSpec),
     Yarn(VersionSpec),
     Package(String, VersionSpec),
+    /// A package from a local directory, a tarball, or a git URL, rather than the registry
+    PackageSource(PackageSource),
 }
 
 impl Spec {
@@ -95,10 +103,11 @@ impl Spec {
                 // to handle resolving (and ultimately fetching / installing) pnpm. If not, then
                 // fall back to the global package behavior, which was the case prior to pnpm
                 // support being added
-                if env::var_os(VOLTA_FEATURE_PNPM).is_some() {
+                if session.pnpm_enabled()? {
                     let version = pnpm::resolve(version, session)?;
                     Ok(Box::new(Pnpm::new(version)))
                 } else {
+                    let version = session.resolve_alias("pnpm", version)?;
                     let package = Package::new("pnpm".to_owned(), version)?;
                     Ok(Box::new(package))
                 }
@@ -109,9 +118,11 @@ impl Spec {
             }
             // When using global package install, we allow the package manager to perform the version resolution
             Spec::Package(name, version) => {
+                let version = session.resolve_alias(&name, version)?;
                 let package = Package::new(name, version)?;
                 Ok(Box::new(package))
             }
+            Spec::PackageSource(source) => Ok(Box::new(SourcePackage::new(source)?)),
         }
     }
 
@@ -119,31 +130,57 @@ impl Spec {
     ///
     /// This is implemented on Spec, instead of Resolved, because there is currently no need to
     /// resolve the specific version before uninstalling a tool.
-    pub fn uninstall(self) -> Fallible<()> {
+    pub fn uninstall(self, session: &mut Session) -> Fallible<()> {
         match self {
-            Spec::Node(_) => Err(ErrorKind::Unimplemented {
-                feature: "Uninstalling node".into(),
+            Spec::Node(version) => node::uninstall(version, session),
+            _ => {
+                let changes = package::uninstall(&self.uninstall_package(session)?)?;
+                session.record_changes(changes);
+                Ok(())
             }
-            .into()),
+        }
+    }
+
+    /// Show what uninstalling a tool would remove from the local inventory, without removing it
+    pub fn uninstall_dry_run(self, session: &mut Session) -> Fallible<()> {
+        match self {
+            Spec::Node(version) => node::uninstall_dry_run(version, session),
+            _ => package::uninstall_dry_run(&self.uninstall_package(session)?),
+        }
+    }
+
+    /// The name of the global package to remove when uninstalling the tool
+    fn uninstall_package(self, session: &Session) -> Fallible<String> {
+        match self {
+            Spec::Node(_) => unreachable!("Node is uninstalled from its own inventory"),
             Spec::Npm(_) => Err(ErrorKind::Unimplemented {
                 feature: "Uninstalling npm".into(),
             }
             .into()),
             Spec::Pnpm(_) => {
-                if env::var_os(VOLTA_FEATURE_PNPM).is_some() {
+                if session.pnpm_enabled()? {
                     Err(ErrorKind::Unimplemented {
                         feature: "Uninstalling pnpm".into(),
                     }
                     .into())
                 } else {
-                    package::uninstall("pnpm")
+                    Ok("pnpm".into())
                 }
             }
             Spec::Yarn(_) => Err(ErrorKind::Unimplemented {
                 feature: "Uninstalling yarn".into(),
             }
             .into()),
-            Spec::Package(name, _) => package::uninstall(&name),
+            Spec::Package(name, _) => Ok(name),
+            // Global packages are tracked by name, so look up the name of the local package
+            Spec::PackageSource(PackageSource::Directory(path)) => {
+                PackageManifest::for_dir(&path.to_string_lossy(), &path)
+                    .map(|manifest| manifest.name)
+            }
+            Spec::PackageSource(_) => Err(ErrorKind::Unimplemented {
+                feature: "Uninstalling a package by its tarball or git URL".into(),
+            }
+            .into()),
         }
     }
 
@@ -155,6 +192,10 @@ impl Spec {
             Spec::Pnpm(_) => "pnpm",
             Spec::Yarn(_) => "Yarn",
             Spec::Package(name, _) => name,
+            Spec::PackageSource(PackageSource::Directory(path) | PackageSource::Tarball(path)) => {
+                path.to_str().unwrap_or("local package")
+            }
+            Spec::PackageSource(PackageSource::Git(url)) => url,
         }
     }
 }
@@ -167,6 +208,7 @@ impl Display for Spec {
             Spec::Pnpm(ref version) => tool_version("pnpm", version),
             Spec::Yarn(ref version) => tool_version("yarn", version),
             Spec::Package(ref name, ref version) => tool_version(name, version),
+            Spec::PackageSource(ref source) => source.to_string(),
         };
         f.write_str(&s)
     }
@@ -211,6 +253,19 @@ where
     }
 }
 
+/// Checks that a tool which isn't available locally may be fetched, which is only the case in
+/// `volta fetch` and `volta install` when automatic installs are disabled
+fn check_may_fetch<T: Display>(tool: T, session: &Session) -> Fallible<()> {
+    if session.may_fetch()? {
+        Ok(())
+    } else {
+        Err(ErrorKind::ToolsNotInstalled {
+            tools: vec![tool.to_string()],
+        }
+        .into())
+    }
+}
+
 fn download_tool_error(tool: Spec, from_url: impl AsRef<str>) -> impl FnOnce() -> ErrorKind {
     let from_url = from_url.as_ref().to_string();
     || ErrorKind::DownloadToolNetworkError { tool, from_url }
@@ -225,6 +280,23 @@ fn registry_fetch_error(
     || ErrorKind::RegistryFetchError { tool, from_url }
 }
 
+/// Get the time until which a downloaded index may be cached, from the `Expires` or the
+/// `Cache-Control: max-age` header of the response, or four hours if it has neither.
+fn cache_expiry(headers: &HeaderMap) -> SystemTime {
+    const FOUR_HOURS: Duration = Duration::from_secs(4 * 60 * 60);
+
+    headers
+        .typed_get::<Expires>()
+        .map(SystemTime::from)
+        .unwrap_or_else(|| {
+            let max_age = headers
+                .typed_get::<CacheControl>()
+                .and_then(|cache_control| cache_control.max_age())
+                .unwrap_or(FOUR_HOURS);
+            SystemTime::now() + max_age
+        })
+}
+
 cfg_if!(
     if #[cfg(windows)] {
         const PATH_VAR_NAME: &str = "Path";
@@ -233,33 +305,54 @@ cfg_if!(
     }
 );
 
+/// The reason that running a shim by its name doesn't reach the shim
+pub enum Unreachable {
+    /// The command can't be found on the PATH at all
+    NotFound { expected_dir: PathBuf },
+    /// Another binary of the same name comes first on the PATH
+    Shadowed {
+        expected_dir: PathBuf,
+        resolved: PathBuf,
+    },
+}
+
+/// Determines whether running the shim by its name would run the shim, and if not, why
+pub fn shim_unreachable(shim_name: &str) -> Option<Unreachable> {
+    let expected_dir = find_expected_shim_dir(shim_name)?;
+
+    match which::which(shim_name) {
+        Err(_) => Some(Unreachable::NotFound { expected_dir }),
+        Ok(resolved) if !resolved.starts_with(&expected_dir) => Some(Unreachable::Shadowed {
+            expected_dir,
+            resolved,
+        }),
+        Ok(_) => None,
+    }
+}
+
 /// Check if a newly-installed shim is first on the PATH. If it isn't, we want to inform the user
 /// that they'll want to move it to the start of PATH to make sure things work as expected.
 pub fn check_shim_reachable(shim_name: &str) {
-    let Some(expected_dir) = find_expected_shim_dir(shim_name) else {
-        return;
-    };
-
-    let Ok(resolved) = which::which(shim_name) else {
-        info!(
+    match shim_unreachable(shim_name) {
+        Some(Unreachable::NotFound { expected_dir }) => info!(
             "{} cannot find command {}. Please ensure that {} is available on your {}.",
             note_prefix(),
             shim_name,
             expected_dir.display(),
             PATH_VAR_NAME,
-        );
-        return;
-    };
-
-    if !resolved.starts_with(&expected_dir) {
-        info!(
+        ),
+        Some(Unreachable::Shadowed {
+            expected_dir,
+            resolved,
+        }) => info!(
             "{} {} is shadowed by another binary of the same name at {}. To ensure your commands work as expected, please move {} to the start of your {}.",
             note_prefix(),
             shim_name,
             resolved.display(),
             expected_dir.display(),
             PATH_VAR_NAME
-        );
+        ),
+        None => {}
     }
 }
 
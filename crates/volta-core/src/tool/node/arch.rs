@@ -0,0 +1,148 @@
+//! Provides the architecture override for Node builds
+//!
+//! Volta uses the Node builds for the architecture it was compiled for, falling back to x64 for
+//! the versions that have no native build (see `default_distro_arch`). A project can choose
+//! another architecture with `volta.arch` in its manifest, as can `--arch` on `volta install node`
+//! and `volta run`, such as to run the x64 build under Rosetta 2 for native addons that have no
+//! arm64 prebuilds.
+//!
+//! The images of an overridden architecture are kept in a directory of their own, so that both
+//! builds of a version can be fetched at the same time.
+
+use std::collections::HashSet;
+
+use super::{default_distro_arch, NODE_DISTRO_ARCH, NODE_DISTRO_IDENTIFIER};
+use crate::error::{ErrorKind, Fallible};
+use node_semver::Version;
+use once_cell::sync::OnceCell;
+
+static ARCH_OVERRIDE: OnceCell<String> = OnceCell::new();
+
+/// Overrides the architecture of the Node builds for the rest of the process
+///
+/// The first override wins, so `--arch` takes precedence over the `volta.arch` of the project as
+/// long as it is set before the project is loaded.
+pub fn set_arch_override(arch: &str) -> Fallible<()> {
+    let arch = parse_arch(arch)?;
+    let _ = ARCH_OVERRIDE.set(arch);
+    Ok(())
+}
+
+/// The architecture that was chosen with `--arch` or `volta.arch`, if any
+pub(crate) fn arch_override() -> Option<&'static str> {
+    ARCH_OVERRIDE.get().map(String::as_str)
+}
+
+/// Checks that an architecture can be used in the name of a distro file and an image directory
+pub(crate) fn parse_arch(arch: &str) -> Fallible<String> {
+    let valid = !arch.is_empty()
+        && arch
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit());
+
+    if valid {
+        Ok(arch.to_owned())
+    } else {
+        Err(ErrorKind::InvalidNodeArch {
+            arch: arch.to_owned(),
+        }
+        .into())
+    }
+}
+
+/// The overridden architecture for a version, if it differs from the one that would be used
+/// without the override
+pub(crate) fn overridden_arch<'a>(version: &Version, arch: Option<&'a str>) -> Option<&'a str> {
+    arch.filter(|arch| *arch != default_distro_arch(version))
+}
+
+/// The identifier of the build for an architecture in the `files` of a Node index entry
+pub(super) fn distro_identifier(arch: &str) -> String {
+    NODE_DISTRO_IDENTIFIER.replacen(NODE_DISTRO_ARCH, arch, 1)
+}
+
+/// Lists the architectures that a Node index entry has a build of for this operating system
+pub(super) fn available_arches(files: &HashSet<String>) -> Vec<String> {
+    let (prefix, suffix) = NODE_DISTRO_IDENTIFIER
+        .split_once(NODE_DISTRO_ARCH)
+        .expect("The distro identifier contains the distro architecture");
+
+    let mut arches: Vec<String> = files
+        .iter()
+        .filter_map(|file| file.strip_prefix(prefix)?.strip_suffix(suffix))
+        .filter_map(|arch| parse_arch(arch).ok())
+        .collect();
+    arches.sort_unstable();
+    arches
+}
+
+/// Checks that a Node index entry has a build for the architecture
+pub(super) fn check_available(
+    version: &Version,
+    arch: &str,
+    files: &HashSet<String>,
+) -> Fallible<()> {
+    if files.contains(&distro_identifier(arch)) {
+        Ok(())
+    } else {
+        Err(ErrorKind::NodeArchNotAvailable {
+            version: version.to_string(),
+            arch: arch.to_owned(),
+            available: available_arches(files),
+        }
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(identifiers: &[&str]) -> HashSet<String> {
+        identifiers.iter().map(|file| (*file).to_owned()).collect()
+    }
+
+    #[test]
+    fn rejects_arches_that_are_not_names() {
+        assert_eq!(parse_arch("x64").unwrap(), "x64");
+        assert_eq!(parse_arch("armv7l").unwrap(), "armv7l");
+        for arch in ["", "X64", "../x64", "x64-musl", "arm 64"] {
+            assert!(parse_arch(arch).is_err(), "{:?}", arch);
+        }
+    }
+
+    #[test]
+    fn override_matching_the_default_is_ignored() {
+        let version = Version::parse("20.11.1").unwrap();
+
+        assert_eq!(overridden_arch(&version, None), None);
+        assert_eq!(
+            overridden_arch(&version, Some(default_distro_arch(&version))),
+            None
+        );
+        assert_eq!(overridden_arch(&version, Some("s390x")), Some("s390x"));
+    }
+
+    #[test]
+    fn selects_the_build_of_the_overridden_arch_from_the_index() {
+        let version = Version::parse("20.11.1").unwrap();
+        let entry = files(&[
+            &distro_identifier("x64"),
+            &distro_identifier("arm64"),
+            "src",
+            "headers",
+        ]);
+
+        assert!(check_available(&version, "x64", &entry).is_ok());
+        assert!(check_available(&version, "arm64", &entry).is_ok());
+        match check_available(&version, "ppc64le", &entry) {
+            Err(error) => match error.kind() {
+                ErrorKind::NodeArchNotAvailable { available, .. } => {
+                    assert_eq!(available, &["arm64", "x64"])
+                }
+                kind => panic!("Unexpected error: {:?}", kind),
+            },
+            Ok(()) => panic!("Expected ppc64le to be rejected"),
+        }
+    }
+}
@@ -0,0 +1,310 @@
+//! Provides a compact copy of the cached Node index, which is much faster to read than the JSON
+//!
+//! The public index is several megabytes of JSON, most of which Volta never looks at, so parsing
+//! it dominates resolving a version on slow machines even when the cache is fresh. Whenever the
+//! JSON is cached, the fields that resolution needs are also written to a line-oriented file:
+//!
+//! ```text
+//! volta-node-index 1
+//! <index URL>
+//! <length of the JSON cache> <modification time of the JSON cache, in nanoseconds>
+//! <version> <npm version or -> <1 if LTS, else 0> <comma-separated files>
+//! ...
+//! ```
+//!
+//! The JSON stays in the cache for anything else that reads it. The compact copy is only used
+//! while it matches the JSON it was written from, so that a JSON cache written by another version
+//! of Volta is never shadowed by an outdated copy, and it is written again whenever it is missing,
+//! outdated, or has a different format version.
+
+use std::fs::{self, File, Metadata};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use super::metadata::{RawNodeEntry, RawNodeIndex};
+use crate::layout::volta_home;
+use fs_utils::ensure_containing_dir_exists;
+use log::debug;
+use node_semver::Version;
+use tempfile::NamedTempFile;
+
+/// The first line of the compact index, which changes whenever its format does
+const HEADER: &str = "volta-node-index 1";
+
+/// Identifies the JSON cache that a compact index was written from
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+struct Source {
+    length: u64,
+    modified: u128,
+}
+
+impl Source {
+    fn of(metadata: &Metadata) -> Option<Self> {
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(Source {
+            length: metadata.len(),
+            modified: modified.as_nanos(),
+        })
+    }
+
+    /// Reads the current state of the JSON cache
+    fn current() -> Option<Self> {
+        let metadata = fs::metadata(volta_home().ok()?.node_index_file()).ok()?;
+        Self::of(&metadata)
+    }
+}
+
+/// Reads the compact index, if there is an up-to-date one for the given URL
+pub(super) fn read(url: &str) -> Option<RawNodeIndex> {
+    let source = Source::current()?;
+    let file = volta_home().ok()?.node_index_compact_file();
+    let contents = match fs::read_to_string(file) {
+        Ok(contents) => contents,
+        Err(error) => {
+            if error.kind() != io::ErrorKind::NotFound {
+                debug!("Could not read the compact Node index: {}", error);
+            }
+            return None;
+        }
+    };
+
+    let index = parse(&contents, url, source);
+    match index {
+        Some(_) => debug!("Read the compact Node index from {}", file.display()),
+        None => debug!("The compact Node index is outdated, using the JSON index"),
+    }
+    index
+}
+
+/// Writes the compact index for the JSON cache that was just written
+///
+/// The compact index is only an optimization, so a failure is logged rather than reported.
+pub(super) fn write(url: &str, index: &RawNodeIndex) {
+    let (Ok(home), Some(source)) = (volta_home(), Source::current()) else {
+        return;
+    };
+    let file = home.node_index_compact_file();
+
+    match write_atomic(home.tmp_dir(), file, &serialize(url, source, index)) {
+        Ok(()) => debug!("Wrote the compact Node index to {}", file.display()),
+        Err(error) => debug!("Could not write the compact Node index: {}", error),
+    }
+}
+
+/// Writes a file through a staging file, so that a concurrent read never sees a partial index
+fn write_atomic(tmp_dir: &Path, file: &Path, contents: &str) -> io::Result<()> {
+    let staging = NamedTempFile::new_in(tmp_dir)?;
+    let mut staging_file: &File = staging.as_file();
+    staging_file.write_all(contents.as_bytes())?;
+    ensure_containing_dir_exists(&file)?;
+    staging.persist(file).map_err(|error| error.error)?;
+    Ok(())
+}
+
+fn serialize(url: &str, source: Source, index: &RawNodeIndex) -> String {
+    let mut output = format!(
+        "{}\n{}\n{} {}\n",
+        HEADER, url, source.length, source.modified
+    );
+
+    for entry in &index.0 {
+        let npm = entry
+            .npm
+            .as_ref()
+            .map_or_else(|| "-".to_owned(), Version::to_string);
+        let mut files: Vec<&str> = entry.files.iter().map(String::as_str).collect();
+        files.sort_unstable();
+
+        output.push_str(&format!(
+            "{} {} {} {}\n",
+            entry.version,
+            npm,
+            u8::from(entry.lts),
+            files.join(",")
+        ));
+    }
+
+    output
+}
+
+/// Parses a compact index, returning `None` if it has a different format version, or was written
+/// for another URL or from another JSON cache
+fn parse(contents: &str, url: &str, source: Source) -> Option<RawNodeIndex> {
+    let mut lines = contents.lines();
+    if lines.next()? != HEADER || lines.next()? != url {
+        return None;
+    }
+
+    let (length, modified) = lines.next()?.split_once(' ')?;
+    let written_from = Source {
+        length: length.parse().ok()?,
+        modified: modified.parse().ok()?,
+    };
+    if written_from != source {
+        return None;
+    }
+
+    lines
+        .map(parse_entry)
+        .collect::<Option<_>>()
+        .map(RawNodeIndex)
+}
+
+fn parse_entry(line: &str) -> Option<RawNodeEntry> {
+    let mut fields = line.split(' ');
+    let version = Version::parse(fields.next()?).ok()?;
+    let npm = match fields.next()? {
+        "-" => None,
+        npm => Some(Version::parse(npm).ok()?),
+    };
+    let lts = match fields.next()? {
+        "1" => true,
+        "0" => false,
+        _ => return None,
+    };
+    let files = fields
+        .next()?
+        .split(',')
+        .filter(|file| !file.is_empty())
+        .map(String::from)
+        .collect();
+
+    if fields.next().is_some() {
+        return None;
+    }
+
+    Some(RawNodeEntry {
+        version,
+        npm,
+        files,
+        lts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool::node::metadata::{NodeEntry, NodeIndex};
+    use node_semver::Range;
+    use std::path::PathBuf;
+
+    const URL: &str = "https://nodejs.org/dist/index.json";
+
+    const SOURCE: Source = Source {
+        length: 1234,
+        modified: 1_700_000_000_000_000_000,
+    };
+
+    fn fixture_index() -> RawNodeIndex {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("fixtures");
+        path.push("node-index");
+        path.push("index.json");
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    fn round_trip(index: &RawNodeIndex) -> RawNodeIndex {
+        parse(&serialize(URL, SOURCE, index), URL, SOURCE).expect("compact index is parsed")
+    }
+
+    fn first_match(index: RawNodeIndex, predicate: impl Fn(&NodeEntry) -> bool) -> Option<Version> {
+        NodeIndex::from(index)
+            .entries
+            .into_iter()
+            .find(predicate)
+            .map(|entry| entry.version)
+    }
+
+    #[test]
+    fn round_trips_every_entry() {
+        let index = fixture_index();
+        assert!(round_trip(&index) == index);
+    }
+
+    #[test]
+    fn resolves_the_same_versions_as_the_json() {
+        let versions: Vec<Version> = fixture_index()
+            .0
+            .iter()
+            .map(|entry| entry.version.clone())
+            .collect();
+
+        // Every exact version, and the ranges that `volta pin` and `volta install` commonly use
+        let mut requirements: Vec<String> = Vec::new();
+        for version in &versions {
+            requirements.push(version.to_string());
+            requirements.push(format!("^{}", version));
+            requirements.push(format!("~{}", version));
+            requirements.push(format!(">={}", version));
+            requirements.push(format!("<{}", version));
+            requirements.push(format!("{}", version.major));
+            requirements.push(format!("{}.{}", version.major, version.minor));
+        }
+        requirements.push("*".into());
+        requirements.push("99".into());
+
+        for requirement in &requirements {
+            let range = Range::parse(requirement).unwrap();
+            let matches = |entry: &NodeEntry| range.satisfies(&entry.version);
+            assert_eq!(
+                first_match(round_trip(&fixture_index()), matches),
+                first_match(fixture_index(), matches),
+                "{}",
+                requirement
+            );
+        }
+
+        let lts = |entry: &NodeEntry| entry.lts;
+        assert_eq!(
+            first_match(round_trip(&fixture_index()), lts),
+            first_match(fixture_index(), lts)
+        );
+        let latest = |_: &NodeEntry| true;
+        assert_eq!(
+            first_match(round_trip(&fixture_index()), latest),
+            first_match(fixture_index(), latest)
+        );
+    }
+
+    #[test]
+    fn ignores_outdated_compact_index() {
+        let compact = serialize(URL, SOURCE, &fixture_index());
+
+        assert!(parse(&compact, URL, SOURCE).is_some());
+        assert!(parse(&compact, "https://example.com/index.json", SOURCE).is_none());
+        let rewritten = Source {
+            modified: SOURCE.modified + 1,
+            ..SOURCE
+        };
+        assert!(parse(&compact, URL, rewritten).is_none());
+        let newer_format = compact.replacen(HEADER, "volta-node-index 2", 1);
+        assert!(parse(&newer_format, URL, SOURCE).is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_entries() {
+        let header = format!(
+            "{}\n{}\n{} {}\n",
+            HEADER, URL, SOURCE.length, SOURCE.modified
+        );
+
+        for entry in [
+            "20.11.1 10.2.4 1",
+            "20.11.1 10.2.4 yes linux-x64",
+            "not-a-version - 0 linux-x64",
+            "20.11.1 10.2.4 1 linux-x64 extra",
+        ] {
+            assert!(
+                parse(&format!("{}{}\n", header, entry), URL, SOURCE).is_none(),
+                "{}",
+                entry
+            );
+        }
+
+        let index = parse(&format!("{}0.1.14 - 0 \n", header), URL, SOURCE).unwrap();
+        assert!(index.0[0].npm.is_none());
+        assert!(index.0[0].files.is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+//! Checks that a fetched Node runs on this system
+//!
+//! Official Node builds for Linux require a minimum version of glibc, which has been raised over
+//! time. On an older distribution, a newly fetched Node fails as soon as it's run, with an error
+//! from the dynamic linker that doesn't explain the cause, so each fetched Node is run once to
+//! catch that failure and explain it instead.
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::error::{ErrorKind, Fallible};
+use crate::style::tool_version;
+use log::debug;
+use node_semver::Version;
+
+/// A glibc version, as `(major, minor)`
+type GlibcVersion = (u64, u64);
+
+/// The first Node major version that requires each version of glibc, newest first
+const GLIBC_REQUIREMENTS: [(u64, GlibcVersion); 3] = [(18, (2, 28)), (12, (2, 17)), (4, (2, 12))];
+
+/// Runs `node --version` from an unpacked Node image, failing with an explanation if the dynamic
+/// linker can't load it because the system's glibc is too old
+///
+/// Any other failure is left for the command that runs Node to report, so that an unusual
+/// environment can't prevent fetching.
+pub(super) fn check_runs(image_dir: &Path, version: &Version) -> Fallible<()> {
+    check_runs_with(image_dir, version, detect_glibc)
+}
+
+fn check_runs_with<D>(image_dir: &Path, version: &Version, detect_glibc: D) -> Fallible<()>
+where
+    D: FnOnce() -> Option<GlibcVersion>,
+{
+    let bin = image_dir.join("bin").join("node");
+    let output = match Command::new(&bin)
+        .arg("--version")
+        .current_dir(image_dir)
+        .stdin(Stdio::null())
+        .output()
+    {
+        Ok(output) => output,
+        Err(error) => {
+            debug!("Could not run '{}' to check it: {}", bin.display(), error);
+            return Ok(());
+        }
+    };
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match loader_error(&stderr) {
+        Some(loader_error) => {
+            let glibc = detect_glibc();
+            Err(ErrorKind::NodeRequiresNewerGlibc {
+                version: tool_version("node", version),
+                loader_error: loader_error.trim().to_owned(),
+                glibc: glibc.map(|(major, minor)| format!("{}.{}", major, minor)),
+                newest_major: glibc.and_then(newest_supported_major),
+            }
+            .into())
+        }
+        None => {
+            debug!(
+                "'{} --version' failed ({}), continuing anyway: {}",
+                bin.display(),
+                output.status,
+                stderr.trim()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Finds the dynamic linker's error about a missing glibc (or libstdc++) symbol version, e.g.:
+///
+/// ```text
+/// node: /lib64/libc.so.6: version `GLIBC_2.28' not found (required by node)
+/// ```
+fn loader_error(stderr: &str) -> Option<&str> {
+    stderr
+        .lines()
+        .find(|line| line.contains("`GLIBC") && line.contains("not found"))
+}
+
+/// Determines the version of glibc on this system from `ldd --version`, if it uses glibc
+fn detect_glibc() -> Option<GlibcVersion> {
+    let output = Command::new("ldd")
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+
+    // musl's `ldd` prints its version to stderr and fails, so it isn't mistaken for glibc
+    if !output.status.success() {
+        return None;
+    }
+
+    parse_ldd_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parses the glibc version from the first line of `ldd --version`, e.g. `ldd (GNU libc) 2.17`
+fn parse_ldd_version(output: &str) -> Option<GlibcVersion> {
+    let version = output.lines().next()?.split_whitespace().last()?;
+    let (major, minor) = version.split_once('.')?;
+    let minor: String = minor.chars().take_while(char::is_ascii_digit).collect();
+
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// The newest LTS major version of Node that is known to run with the given glibc
+///
+/// There is no answer when the system's glibc is new enough for every known Node version (so the
+/// failure has another cause), or too old for every known Node version.
+fn newest_supported_major(glibc: GlibcVersion) -> Option<u64> {
+    let supported = GLIBC_REQUIREMENTS
+        .iter()
+        .position(|&(_, required)| required <= glibc)?;
+    let (unsupported, _) = GLIBC_REQUIREMENTS[supported.checked_sub(1)?];
+
+    // LTS lines have even major versions
+    let newest = unsupported - 1;
+    Some(newest - newest % 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::{tempdir, TempDir};
+
+    const LOADER_ERROR: &str =
+        "node: /lib64/libc.so.6: version `GLIBC_2.28' not found (required by node)";
+
+    /// Creates an image whose `bin/node` is a script with the given body
+    fn image_with_node(script: &str) -> TempDir {
+        let image = tempdir().unwrap();
+        let bin = image.path().join("bin");
+        fs::create_dir_all(&bin).unwrap();
+
+        let node = bin.join("node");
+        fs::write(&node, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        fs::set_permissions(&node, fs::Permissions::from_mode(0o755)).unwrap();
+
+        image
+    }
+
+    fn node_18() -> Version {
+        Version::from((18, 20, 0))
+    }
+
+    #[test]
+    fn parses_ldd_versions() {
+        assert_eq!(parse_ldd_version("ldd (GNU libc) 2.17\n"), Some((2, 17)));
+        assert_eq!(
+            parse_ldd_version("ldd (Ubuntu GLIBC 2.35-0ubuntu3.8) 2.35\nCopyright (C) 2022"),
+            Some((2, 35))
+        );
+        assert_eq!(parse_ldd_version("musl libc (x86_64)"), None);
+        assert_eq!(parse_ldd_version(""), None);
+    }
+
+    #[test]
+    fn finds_newest_supported_major() {
+        assert_eq!(newest_supported_major((2, 17)), Some(16));
+        assert_eq!(newest_supported_major((2, 27)), Some(16));
+        assert_eq!(newest_supported_major((2, 12)), Some(10));
+        assert_eq!(newest_supported_major((2, 28)), None);
+        assert_eq!(newest_supported_major((2, 5)), None);
+    }
+
+    #[test]
+    fn working_node_passes() {
+        let image = image_with_node("echo v18.20.0");
+        assert!(check_runs_with(image.path(), &node_18(), || panic!("not needed")).is_ok());
+    }
+
+    #[test]
+    fn loader_errors_explain_glibc_requirement() {
+        let image = image_with_node(&format!("cat >&2 <<'EOF'\n{}\nEOF\nexit 1", LOADER_ERROR));
+
+        let error = check_runs_with(image.path(), &node_18(), || Some((2, 17))).unwrap_err();
+        match error.kind() {
+            ErrorKind::NodeRequiresNewerGlibc {
+                version,
+                loader_error,
+                glibc,
+                newest_major,
+            } => {
+                assert_eq!(version, "node@18.20.0");
+                assert_eq!(loader_error, LOADER_ERROR);
+                assert_eq!(glibc.as_deref(), Some("2.17"));
+                assert_eq!(*newest_major, Some(16));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn other_failures_are_ignored() {
+        let image = image_with_node("echo \"Segmentation fault\" >&2\nexit 139");
+        assert!(check_runs_with(image.path(), &node_18(), || panic!("not needed")).is_ok());
+
+        let missing = tempdir().unwrap();
+        assert!(check_runs_with(missing.path(), &node_18(), || panic!("not needed")).is_ok());
+    }
+}
@@ -0,0 +1,207 @@
+//! Provides the expiration of the cached Node index, in a way that doesn't depend on the local
+//! clock agreeing with the clock of the server
+//!
+//! The cache records how long the index may be used for and when it was received, by the local
+//! clock. The lifetime comes from the response headers: `Cache-Control: max-age` if there is one,
+//! otherwise the difference between `Expires` and `Date`, which are both set by the server. Only
+//! the local clock is then used to tell how old the cache is, so a clock that is off by a day still
+//! keeps the index for as long as the server intended. A clock that has moved backwards since the
+//! index was received makes the cache expire, as its age can't be known.
+//!
+//! The expiry file holds one `name: value` line for each field, with the times as HTTP dates:
+//!
+//! ```text
+//! received: Wed, 21 Oct 2015 07:28:00 GMT
+//! max-age: 14400
+//! date: Wed, 21 Oct 2015 07:28:00 GMT
+//! ```
+
+use std::fmt::{self, Display};
+use std::time::{Duration, SystemTime};
+
+use attohttpc::header::HeaderMap;
+use headers::{CacheControl, Date, Expires, HeaderMapExt};
+
+/// How long the index is cached for when the response doesn't say
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(4 * 60 * 60);
+
+/// The lifetime of a cached index, relative to when it was received
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct IndexExpiry {
+    /// When the index was received, by the local clock
+    received: SystemTime,
+    /// How long after it was received the index may be used for
+    max_age: Duration,
+    /// When the index was sent, by the server's clock, which is only kept for debugging
+    date: Option<SystemTime>,
+}
+
+impl IndexExpiry {
+    /// Determines the lifetime of an index from the headers of the response that it was in
+    pub(super) fn from_headers(headers: &HeaderMap, received: SystemTime) -> Self {
+        let date = headers.typed_get::<Date>().map(SystemTime::from);
+        let expires = headers.typed_get::<Expires>().map(SystemTime::from);
+        let max_age = headers
+            .typed_get::<CacheControl>()
+            .and_then(|cache_control| cache_control.max_age())
+            .or_else(|| {
+                // Without a `Date`, `Expires` can only be compared against the local clock
+                let sent = date.unwrap_or(received);
+                expires.map(|expires| expires.duration_since(sent).unwrap_or_default())
+            })
+            .unwrap_or(DEFAULT_MAX_AGE);
+
+        IndexExpiry {
+            received,
+            max_age,
+            date,
+        }
+    }
+
+    /// Parses the contents of an expiry file, returning `None` if it can't be understood
+    pub(super) fn parse(contents: &str) -> Option<Self> {
+        let mut received = None;
+        let mut max_age = None;
+        let mut date = None;
+
+        for line in contents.lines() {
+            let (name, value) = line.split_once(':')?;
+            let value = value.trim();
+            match name.trim() {
+                "received" => received = Some(httpdate::parse_http_date(value).ok()?),
+                "max-age" => max_age = Some(Duration::from_secs(value.parse().ok()?)),
+                "date" => date = Some(httpdate::parse_http_date(value).ok()?),
+                _ => {}
+            }
+        }
+
+        Some(IndexExpiry {
+            received: received?,
+            max_age: max_age?,
+            date,
+        })
+    }
+
+    /// Determines whether the index may still be used at the given local time
+    pub(super) fn is_fresh(&self, now: SystemTime) -> bool {
+        now.duration_since(self.received)
+            .is_ok_and(|age| age < self.max_age)
+    }
+}
+
+impl Display for IndexExpiry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "received: {}", httpdate::fmt_http_date(self.received))?;
+        writeln!(f, "max-age: {}", self.max_age.as_secs())?;
+        if let Some(date) = self.date {
+            writeln!(f, "date: {}", httpdate::fmt_http_date(date))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use attohttpc::header::HeaderValue;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// A time on the server, with whole seconds as HTTP dates have
+    fn server_now() -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+    }
+
+    fn headers(entries: &[(&'static str, String)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in entries {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn expires_in_four_hours() -> HeaderMap {
+        headers(&[
+            ("date", httpdate::fmt_http_date(server_now())),
+            ("expires", httpdate::fmt_http_date(server_now() + 4 * HOUR)),
+        ])
+    }
+
+    #[test]
+    fn local_clock_skewed_forward() {
+        // By an absolute `Expires`, the index would have expired before it was received
+        let received = server_now() + DAY;
+        let expiry = IndexExpiry::from_headers(&expires_in_four_hours(), received);
+
+        assert!(expiry.is_fresh(received + HOUR));
+        assert!(!expiry.is_fresh(received + 5 * HOUR));
+    }
+
+    #[test]
+    fn local_clock_skewed_backward() {
+        // By an absolute `Expires`, the index would be used for more than a day
+        let received = server_now() - DAY;
+        let expiry = IndexExpiry::from_headers(&expires_in_four_hours(), received);
+
+        assert!(expiry.is_fresh(received + HOUR));
+        assert!(!expiry.is_fresh(received + 5 * HOUR));
+    }
+
+    #[test]
+    fn local_clock_moved_backward_since_receiving() {
+        let expiry = IndexExpiry::from_headers(&expires_in_four_hours(), server_now());
+
+        assert!(!expiry.is_fresh(server_now() - HOUR));
+    }
+
+    #[test]
+    fn max_age_takes_precedence() {
+        let mut headers = expires_in_four_hours();
+        headers.insert("cache-control", HeaderValue::from_static("max-age=60"));
+        let expiry = IndexExpiry::from_headers(&headers, server_now());
+
+        assert!(expiry.is_fresh(server_now() + Duration::from_secs(59)));
+        assert!(!expiry.is_fresh(server_now() + Duration::from_secs(61)));
+    }
+
+    #[test]
+    fn missing_headers() {
+        let expiry = IndexExpiry::from_headers(&HeaderMap::new(), server_now());
+        assert_eq!(expiry.max_age, DEFAULT_MAX_AGE);
+        assert_eq!(expiry.date, None);
+
+        // An `Expires` in the past means the index mustn't be cached at all
+        let headers = headers(&[
+            ("date", httpdate::fmt_http_date(server_now())),
+            ("expires", httpdate::fmt_http_date(server_now() - HOUR)),
+        ]);
+        let expiry = IndexExpiry::from_headers(&headers, server_now());
+        assert!(!expiry.is_fresh(server_now()));
+    }
+
+    #[test]
+    fn round_trips_through_the_expiry_file() {
+        let expiry = IndexExpiry::from_headers(&expires_in_four_hours(), server_now() + HOUR);
+
+        assert_eq!(IndexExpiry::parse(&expiry.to_string()), Some(expiry));
+    }
+
+    #[test]
+    fn unparseable_expiry_files() {
+        // The format of older versions, which only held the `Expires` date
+        assert_eq!(
+            IndexExpiry::parse(&httpdate::fmt_http_date(server_now())),
+            None
+        );
+        assert_eq!(IndexExpiry::parse(""), None);
+        assert_eq!(
+            IndexExpiry::parse("received: yesterday\nmax-age: 60\n"),
+            None
+        );
+        assert_eq!(
+            IndexExpiry::parse("max-age: 60\ndate: Tue, 14 Nov 2023 22:13:20 GMT\n"),
+            None
+        );
+    }
+}
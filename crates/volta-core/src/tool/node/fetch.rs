@@ -1,23 +1,35 @@
 //! Provides fetcher for Node distributions
 
-use std::fs::{read_to_string, write, File};
+use std::fs::{read_to_string, remove_file, write, File};
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
+use std::process::Command;
 
+#[cfg(target_os = "linux")]
+use super::compat;
 use super::NodeVersion;
-use crate::error::{Context, ErrorKind, Fallible};
-use crate::fs::{create_staging_dir, create_staging_file, rename};
+use crate::download::DownloadPermit;
+use crate::error::{Context, ErrorKind, Fallible, VoltaError};
+use crate::fs::{copy_dir_all, create_staging_dir, create_staging_file, rename};
+use crate::hook::tool::Shasums;
 use crate::hook::ToolHooks;
 use crate::layout::volta_home;
 use crate::style::{progress_bar, tool_version};
+use crate::tool::checksum::{checksum_file, find_checksum, sha256_file};
+use crate::tool::npmrc;
 use crate::tool::{self, download_tool_error, Node};
 use crate::version::{parse_version, VersionSpec};
-use archive::{self, Archive};
+use archive::{self, Archive, Origin};
+use attohttpc::{ProxySettings, Response};
 use cfg_if::cfg_if;
 use fs_utils::ensure_containing_dir_exists;
 use log::debug;
 use node_semver::Version;
 use serde::Deserialize;
 
+/// The checksum file published alongside the Node distros
+const SHASUMS_FILE_NAME: &str = "SHASUMS256.txt";
+
 cfg_if! {
     if #[cfg(feature = "mock-network")] {
         // TODO: We need to reconsider our mocking strategy in light of mockito deprecating the
@@ -34,8 +46,9 @@ cfg_if! {
     }
 }
 
-fn npm_manifest_path(version: &Version) -> PathBuf {
-    let mut manifest = PathBuf::from(Node::archive_basename(version));
+/// The path to the `package.json` file of the npm bundled in a Node installation
+fn npm_manifest_path(install_dir: &Path) -> PathBuf {
+    let mut manifest = install_dir.to_owned();
 
     #[cfg(unix)]
     manifest.push("lib");
@@ -47,7 +60,15 @@ fn npm_manifest_path(version: &Version) -> PathBuf {
     manifest
 }
 
-pub fn fetch(version: &Version, hooks: Option<&ToolHooks<Node>>) -> Fallible<NodeVersion> {
+/// Fetch a Node version into the image directory
+///
+/// With `glibc_check`, the unpacked Node is run once on Linux before it's installed, so that a
+/// system glibc that is too old for it is reported clearly.
+pub fn fetch(
+    version: &Version,
+    hooks: Option<&ToolHooks<Node>>,
+    glibc_check: bool,
+) -> Fallible<NodeVersion> {
     let home = volta_home()?;
     let node_dir = home.node_inventory_dir();
     let cache_file = node_dir.join(Node::archive_filename(version));
@@ -62,70 +83,103 @@ pub fn fetch(version: &Version, hooks: Option<&ToolHooks<Node>>) -> Fallible<Nod
             (archive, None)
         }
         None => {
+            let _permit = DownloadPermit::acquire();
             let staging = create_staging_file()?;
             let remote_url = determine_remote_url(version, hooks)?;
-            let archive = fetch_remote_distro(version, &remote_url, staging.path())?;
-            (archive, Some(staging))
+            let checksum = expected_checksum(version, &remote_url, hooks)?;
+            let archive =
+                fetch_remote_distro(version, &remote_url, staging.path(), checksum.as_deref())?;
+            (archive, Some((staging, checksum)))
         }
     };
 
-    let node_version = unpack_archive(archive, version)?;
+    // A downloaded archive already had its progress shown while it was fetched
+    let show_progress = staging.is_none();
+    let node_version = unpack_archive(archive, version, show_progress, glibc_check)?;
+
+    if let Some((staging_file, checksum)) = staging {
+        // The checksum is recorded even when there was none to verify the download against, so
+        // that the cached archive can be checked for corruption when it's loaded
+        let checksum = match checksum {
+            Some(checksum) => checksum,
+            None => sha256_file(staging_file.path()).with_context(|| {
+                ErrorKind::PersistInventoryError {
+                    tool: "Node".into(),
+                }
+            })?,
+        };
 
-    if let Some(staging_file) = staging {
         ensure_containing_dir_exists(&cache_file).with_context(|| {
             ErrorKind::ContainingDirError {
                 path: cache_file.clone(),
             }
         })?;
         staging_file
-            .persist(cache_file)
+            .persist(&cache_file)
             .with_context(|| ErrorKind::PersistInventoryError {
                 tool: "Node".into(),
             })?;
+        write(checksum_file(&cache_file), checksum).with_context(|| {
+            ErrorKind::PersistInventoryError {
+                tool: "Node".into(),
+            }
+        })?;
     }
 
     Ok(node_version)
 }
 
 /// Unpack the node archive into the image directory so that it is ready for use
-fn unpack_archive(archive: Box<dyn Archive>, version: &Version) -> Fallible<NodeVersion> {
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn unpack_archive(
+    archive: Box<dyn Archive>,
+    version: &Version,
+    show_progress: bool,
+    glibc_check: bool,
+) -> Fallible<NodeVersion> {
     let temp = create_staging_dir()?;
     debug!("Unpacking node into '{}'", temp.path().display());
 
-    let progress = progress_bar(
-        archive.origin(),
-        &tool_version("node", version),
-        archive.compressed_size(),
-    );
+    let mut progress = show_progress
+        .then(|| progress_bar(archive.origin(), "node", version, archive.compressed_size()));
     let version_string = version.to_string();
 
     archive
         .unpack(temp.path(), &mut |_, read| {
-            progress.inc(read as u64);
+            if let Some(progress) = progress.as_mut() {
+                progress.inc(read as u64);
+            }
         })
         .with_context(|| ErrorKind::UnpackArchiveError {
             tool: "Node".into(),
             version: version_string.clone(),
         })?;
 
+    let staged = temp.path().join(Node::archive_basename(version));
+
+    #[cfg(target_os = "linux")]
+    if glibc_check {
+        compat::check_runs(&staged, version)?;
+    }
+
     // Save the npm version number in the npm version file for this distro
-    let npm_package_json = temp.path().join(npm_manifest_path(version));
+    let npm_package_json = npm_manifest_path(&staged);
     let npm = Manifest::version(&npm_package_json)?;
     save_default_npm_version(version, &npm)?;
 
-    let dest = volta_home()?.node_image_dir(&version_string);
+    let dest = Node::image_dir(version)?;
     ensure_containing_dir_exists(&dest)
         .with_context(|| ErrorKind::ContainingDirError { path: dest.clone() })?;
 
-    rename(temp.path().join(Node::archive_basename(version)), &dest).with_context(|| {
-        ErrorKind::SetupToolImageError {
-            tool: "Node".into(),
-            version: version_string,
-            dir: dest.clone(),
-        }
+    rename(staged, &dest).with_context(|| ErrorKind::SetupToolImageError {
+        tool: "Node".into(),
+        version: version_string,
+        dir: dest.clone(),
     })?;
 
-    progress.finish_and_clear();
+    if let Some(progress) = progress {
+        progress.finish_and_clear();
+    }
 
     // Note: We write these after the progress bar is finished to avoid display bugs with re-renders of the progress
     debug!("Saving bundled npm version ({})", npm);
@@ -137,15 +191,107 @@ fn unpack_archive(archive: Box<dyn Archive>, version: &Version) -> Fallible<Node
     })
 }
 
-/// Return the archive if it is valid. It may have been corrupted or interrupted in the middle of
-/// downloading.
-// ISSUE(#134) - verify checksum
-fn load_cached_distro(file: &Path) -> Option<Box<dyn Archive>> {
-    if file.is_file() {
-        let file = File::open(file).ok()?;
-        archive::load_native(file).ok()
+/// Copy an existing Node installation, such as one managed by another version manager, into the
+/// image directory so that it is ready for use
+///
+/// The installation is validated by running `node --version` before anything is copied, and the
+/// source directory is never modified.
+pub fn import_existing(version: &Version, install_dir: &Path) -> Fallible<NodeVersion> {
+    let version_string = version.to_string();
+    let found = installed_node_version(install_dir)?;
+    if found.trim() != format!("v{}", version_string) {
+        return Err(ErrorKind::NodeInstallVersionMismatch {
+            dir: install_dir.to_owned(),
+            expected: version_string,
+            found: found.trim().to_owned(),
+        }
+        .into());
+    }
+
+    let npm = Manifest::version(&npm_manifest_path(install_dir))?;
+
+    let temp = create_staging_dir()?;
+    debug!(
+        "Copying node from '{}' into '{}'",
+        install_dir.display(),
+        temp.path().display()
+    );
+    let staged = temp.path().join(Node::archive_basename(version));
+    copy_dir_all(install_dir, &staged).with_context(|| ErrorKind::CopyNodeInstallError {
+        version: version_string.clone(),
+        dir: install_dir.to_owned(),
+    })?;
+
+    save_default_npm_version(version, &npm)?;
+
+    let dest = Node::image_dir(version)?;
+    ensure_containing_dir_exists(&dest)
+        .with_context(|| ErrorKind::ContainingDirError { path: dest.clone() })?;
+
+    rename(&staged, &dest).with_context(|| ErrorKind::SetupToolImageError {
+        tool: "Node".into(),
+        version: version_string,
+        dir: dest.clone(),
+    })?;
+
+    debug!("Saving bundled npm version ({})", npm);
+    debug!("Installing node in '{}'", dest.display());
+
+    Ok(NodeVersion {
+        runtime: version.clone(),
+        npm,
+    })
+}
+
+/// Run `node --version` for an existing Node installation
+fn installed_node_version(install_dir: &Path) -> Fallible<String> {
+    cfg_if! {
+        if #[cfg(windows)] {
+            let bin = install_dir.join("node.exe");
+        } else {
+            let bin = install_dir.join("bin").join("node");
+        }
+    }
+
+    let output = Command::new(&bin)
+        .arg("--version")
+        .output()
+        .with_context(|| ErrorKind::ReadNodeInstallVersionError { bin: bin.clone() })?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
     } else {
-        None
+        Err(ErrorKind::ReadNodeInstallVersionError { bin }.into())
+    }
+}
+
+/// Return the cached archive if it still matches the checksum recorded when it was saved
+///
+/// A cached archive may have been corrupted, or saved by an older version of Volta without a
+/// checksum. Either way it can't be trusted, so it's removed and the archive is downloaded again.
+fn load_cached_distro(file: &Path) -> Option<Box<dyn Archive>> {
+    if !file.is_file() {
+        return None;
+    }
+
+    let checksum_file = checksum_file(file);
+    let expected = read_to_string(&checksum_file).ok();
+    let actual = sha256_file(file).ok();
+
+    match (expected, actual) {
+        (Some(expected), Some(actual)) if expected.trim().eq_ignore_ascii_case(&actual) => {
+            let file = File::open(file).ok()?;
+            archive::load_native(file).ok()
+        }
+        _ => {
+            debug!(
+                "Removing cached archive at '{}', as it doesn't match its recorded checksum",
+                file.display()
+            );
+            let _ = remove_file(file);
+            let _ = remove_file(&checksum_file);
+            None
+        }
     }
 }
 
@@ -169,17 +315,146 @@ fn determine_remote_url(version: &Version, hooks: Option<&ToolHooks<Node>>) -> F
     }
 }
 
+/// Determine the SHA-256 checksum that the distro archive is expected to have
+///
+/// The checksum file is required for archives from the public Node server, and when the
+/// `node.shasums` hook provides it. For an archive from the `node.distro` hook, the checksum
+/// file is downloaded from the same directory, but not every mirror hosts it, so verification is
+/// skipped if it isn't available.
+fn expected_checksum(
+    version: &Version,
+    remote_url: &str,
+    hooks: Option<&ToolHooks<Node>>,
+) -> Fallible<Option<String>> {
+    let (shasums, required) = match hooks {
+        Some(&ToolHooks {
+            shasums: Some(ref hook),
+            ..
+        }) => {
+            debug!("Using node.shasums hook to determine the checksum file");
+            (hook.resolve(version, SHASUMS_FILE_NAME)?, true)
+        }
+        Some(&ToolHooks {
+            distro: Some(_), ..
+        }) => match remote_url.rsplit_once('/') {
+            Some((dir, _)) => (
+                Shasums::Url(format!("{}/{}", dir, SHASUMS_FILE_NAME)),
+                false,
+            ),
+            None => return Ok(None),
+        },
+        _ => (
+            Shasums::Url(format!(
+                "{}/v{}/{}",
+                public_node_server_root(),
+                version,
+                SHASUMS_FILE_NAME
+            )),
+            true,
+        ),
+    };
+
+    let content = match shasums {
+        Shasums::Content(content) => content,
+        Shasums::Url(url) => {
+            debug!("Downloading Node checksums from {}", url);
+            let fetched = npmrc::get(&url)
+                .send()
+                .and_then(Response::error_for_status)
+                .and_then(Response::text);
+
+            match fetched {
+                Ok(content) => content,
+                Err(error) if !required => {
+                    debug!(
+                        "Skipping checksum verification, as the checksums could not be downloaded: {}",
+                        error
+                    );
+                    return Ok(None);
+                }
+                Err(error) => {
+                    return Err(error)
+                        .with_context(|| ErrorKind::NodeShasumsFetchError { from_url: url })
+                }
+            }
+        }
+    };
+
+    let file = Node::archive_filename(version);
+    match find_checksum(&content, &file) {
+        Some(checksum) => Ok(Some(checksum.to_ascii_lowercase())),
+        None if required => Err(ErrorKind::NodeChecksumNotFound { file }.into()),
+        None => {
+            debug!(
+                "Skipping checksum verification, as the checksums don't include {}",
+                file
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// Check the SHA-256 checksum of the downloaded archive
+fn verify_checksum(file: &Path, expected: &str, version: &Version) -> Fallible<()> {
+    let actual = sha256_file(file).with_context(|| ErrorKind::UnpackArchiveError {
+        tool: "Node".into(),
+        version: version.to_string(),
+    })?;
+
+    if actual == expected {
+        debug!("Verified the checksum of the Node archive ({})", actual);
+        Ok(())
+    } else {
+        Err(ErrorKind::ChecksumMismatch {
+            tool: "Node".into(),
+            version: version.to_string(),
+            expected: expected.into(),
+            actual,
+        }
+        .into())
+    }
+}
+
 /// Fetch the distro archive from the internet
+///
+/// The archive is downloaded in full and, if `checksum` is provided, verified before it is loaded,
+/// so that nothing from an archive with the wrong checksum is ever unpacked.
 fn fetch_remote_distro(
     version: &Version,
     url: &str,
     staging_path: &Path,
+    checksum: Option<&str>,
 ) -> Fallible<Box<dyn Archive>> {
+    let spec = || tool::Spec::Node(VersionSpec::Exact(version.clone()));
+
     debug!("Downloading {} from {}", tool_version("node", version), url);
-    archive::fetch_native(url, staging_path).with_context(download_tool_error(
-        tool::Spec::Node(VersionSpec::Exact(version.clone())),
-        url,
-    ))
+    let (size, mut data) = archive::download(url, staging_path, ProxySettings::from_env())
+        .with_context(download_tool_error(spec(), url))?;
+
+    let mut progress = progress_bar(Origin::Remote, "node", version, size);
+    let mut buffer = [0; 8192];
+    loop {
+        let read = data
+            .read(&mut buffer)
+            .with_context(download_tool_error(spec(), url))?;
+        if read == 0 {
+            break;
+        }
+        progress.inc(read as u64);
+    }
+    progress.finish_and_clear();
+
+    if let Some(expected) = checksum {
+        verify_checksum(staging_path, expected, version)?;
+    }
+
+    File::open(staging_path)
+        .map_err(archive::ArchiveError::from)
+        .and_then(archive::load_native)
+        .with_context(|| ErrorKind::UnpackArchiveError {
+            tool: "Node".into(),
+            version: version.to_string(),
+        })
 }
 
 /// The portion of npm's `package.json` file that we care about
@@ -199,13 +474,36 @@ impl Manifest {
 }
 
 /// Load the local npm version file to determine the default npm version for a given version of Node
+///
+/// If the file is missing but the Node image is available, the version is read from the npm
+/// bundled in the image and the file is written, so later lookups don't need the image.
 pub fn load_default_npm_version(node: &Version) -> Fallible<Version> {
-    let npm_version_file_path = volta_home()?.node_npm_version_file(&node.to_string());
-    let npm_version =
-        read_to_string(&npm_version_file_path).with_context(|| ErrorKind::ReadDefaultNpmError {
-            file: npm_version_file_path,
-        })?;
-    parse_version(npm_version)
+    let home = volta_home()?;
+    let npm_version_file_path = home.node_npm_version_file(&node.to_string());
+
+    match read_to_string(&npm_version_file_path) {
+        Ok(npm_version) => parse_version(npm_version),
+        Err(error) => {
+            let npm_package_json = npm_manifest_path(&Node::image_dir(node)?);
+            if error.kind() != io::ErrorKind::NotFound || !npm_package_json.exists() {
+                return Err(VoltaError::from_source(
+                    error,
+                    ErrorKind::ReadDefaultNpmError {
+                        file: npm_version_file_path,
+                    },
+                ));
+            }
+
+            debug!(
+                "Recording bundled npm version for {} from '{}'",
+                tool_version("node", node),
+                npm_package_json.display()
+            );
+            let npm = Manifest::version(&npm_package_json)?;
+            save_default_npm_version(node, &npm)?;
+            Ok(npm)
+        }
+    }
 }
 
 /// Save the default npm version to the filesystem for a given version of Node
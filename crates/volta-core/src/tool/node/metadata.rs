@@ -1,5 +1,6 @@
 use std::collections::HashSet;
 
+use super::arch::{arch_override, distro_identifier};
 use super::NODE_DISTRO_IDENTIFIER;
 #[cfg(any(
     all(target_os = "macos", target_arch = "aarch64"),
@@ -22,18 +23,20 @@ pub struct NodeEntry {
 }
 
 #[derive(Deserialize)]
-pub struct RawNodeIndex(Vec<RawNodeEntry>);
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct RawNodeIndex(pub(super) Vec<RawNodeEntry>);
 
 #[derive(Deserialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
 pub struct RawNodeEntry {
     #[serde(with = "version_serde")]
-    version: Version,
+    pub(super) version: Version,
     #[serde(default)] // handles Option
     #[serde(with = "option_version_serde")]
-    npm: Option<Version>,
-    files: HashSet<String>,
+    pub(super) npm: Option<Version>,
+    pub(super) files: HashSet<String>,
     #[serde(deserialize_with = "lts_version_serde")]
-    lts: bool,
+    pub(super) lts: bool,
 }
 
 impl From<RawNodeIndex> for NodeIndex {
@@ -41,35 +44,10 @@ impl From<RawNodeIndex> for NodeIndex {
         let entries = raw
             .0
             .into_iter()
-            .filter_map(|entry| {
-                #[cfg(not(any(
-                    all(target_os = "macos", target_arch = "aarch64"),
-                    all(target_os = "windows", target_arch = "aarch64")
-                )))]
-                if entry.npm.is_some() && entry.files.contains(NODE_DISTRO_IDENTIFIER) {
-                    Some(NodeEntry {
-                        version: entry.version,
-                        lts: entry.lts,
-                    })
-                } else {
-                    None
-                }
-
-                #[cfg(any(
-                    all(target_os = "macos", target_arch = "aarch64"),
-                    all(target_os = "windows", target_arch = "aarch64")
-                ))]
-                if entry.npm.is_some()
-                    && (entry.files.contains(NODE_DISTRO_IDENTIFIER)
-                        || entry.files.contains(NODE_DISTRO_IDENTIFIER_FALLBACK))
-                {
-                    Some(NodeEntry {
-                        version: entry.version,
-                        lts: entry.lts,
-                    })
-                } else {
-                    None
-                }
+            .filter(|entry| entry.npm.is_some() && has_build(&entry.files))
+            .map(|entry| NodeEntry {
+                version: entry.version,
+                lts: entry.lts,
             })
             .collect();
 
@@ -77,6 +55,31 @@ impl From<RawNodeIndex> for NodeIndex {
     }
 }
 
+/// Checks whether the `files` of an index entry include a build that Volta can use, which is the
+/// one for the overridden architecture if there is one
+fn has_build(files: &HashSet<String>) -> bool {
+    match arch_override() {
+        Some(arch) => files.contains(&distro_identifier(arch)),
+        None => has_native_build(files),
+    }
+}
+
+#[cfg(not(any(
+    all(target_os = "macos", target_arch = "aarch64"),
+    all(target_os = "windows", target_arch = "aarch64")
+)))]
+fn has_native_build(files: &HashSet<String>) -> bool {
+    files.contains(NODE_DISTRO_IDENTIFIER)
+}
+
+#[cfg(any(
+    all(target_os = "macos", target_arch = "aarch64"),
+    all(target_os = "windows", target_arch = "aarch64")
+))]
+fn has_native_build(files: &HashSet<String>) -> bool {
+    files.contains(NODE_DISTRO_IDENTIFIER) || files.contains(NODE_DISTRO_IDENTIFIER_FALLBACK)
+}
+
 #[allow(clippy::unnecessary_wraps)] // Needs to match the API expected by Serde
 fn lts_version_serde<'de, D>(deserializer: D) -> Result<bool, D::Error>
 where
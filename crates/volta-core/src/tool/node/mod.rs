@@ -1,24 +1,38 @@
 use std::fmt::{self, Display};
+use std::path::PathBuf;
 
 use super::{
-    check_fetched, check_shim_reachable, debug_already_fetched, info_fetched, info_installed,
-    info_pinned, info_project_version, FetchStatus, Tool,
+    check_fetched, check_may_fetch, check_shim_reachable, debug_already_fetched, info_fetched,
+    info_installed, info_project_version, offline, quarantine, FetchStatus, Tool,
 };
+use crate::change::{Action, Change, Subject};
 use crate::error::{ErrorKind, Fallible};
-use crate::inventory::node_available;
+use crate::inventory::{node_available, node_versions};
+use crate::layout::volta_home;
 use crate::session::Session;
 use crate::style::{note_prefix, tool_version};
 use crate::sync::VoltaLock;
 use cfg_if::cfg_if;
-use log::info;
+use log::{info, warn};
 use node_semver::Version;
 
+mod arch;
+mod compact;
+#[cfg(target_os = "linux")]
+mod compat;
+mod expiry;
 mod fetch;
 mod metadata;
 mod resolve;
+mod schedule;
+mod uninstall;
 
-pub use fetch::load_default_npm_version;
-pub use resolve::resolve;
+pub use arch::set_arch_override;
+pub(crate) use arch::{arch_override, overridden_arch, parse_arch};
+pub use fetch::{import_existing, load_default_npm_version};
+pub use resolve::{refresh_index, resolve, resolve_all_lts};
+pub use schedule::ReleaseSchedule;
+pub use uninstall::{uninstall, uninstall_dry_run};
 
 cfg_if! {
     if #[cfg(all(target_os = "windows", target_arch = "x86"))] {
@@ -114,6 +128,37 @@ cfg_if! {
     }
 }
 
+/// The architecture component of the Node distro filename for a version, when it isn't overridden
+#[cfg(not(any(
+    all(target_os = "macos", target_arch = "aarch64"),
+    all(target_os = "windows", target_arch = "aarch64")
+)))]
+fn default_distro_arch(_version: &Version) -> &'static str {
+    NODE_DISTRO_ARCH
+}
+
+#[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+fn default_distro_arch(version: &Version) -> &'static str {
+    // Note: Node began shipping pre-built binaries for Apple Silicon with Major version 16
+    // Prior to that, we need to fall back on the x64 binaries
+    if version.major >= 16 {
+        NODE_DISTRO_ARCH
+    } else {
+        NODE_DISTRO_ARCH_FALLBACK
+    }
+}
+
+#[cfg(all(target_os = "windows", target_arch = "aarch64"))]
+fn default_distro_arch(version: &Version) -> &'static str {
+    // Note: Node began shipping pre-built binaries for Windows ARM with Major version 20
+    // Prior to that, we need to fall back on the x64 binaries
+    if version.major >= 20 {
+        NODE_DISTRO_ARCH
+    } else {
+        NODE_DISTRO_ARCH_FALLBACK
+    }
+}
+
 /// A full Node version including not just the version of Node itself
 /// but also the specific version of npm installed globally with that
 /// Node installation.
@@ -146,43 +191,18 @@ impl Node {
         Node { version }
     }
 
-    #[cfg(not(any(
-        all(target_os = "macos", target_arch = "aarch64"),
-        all(target_os = "windows", target_arch = "aarch64")
-    )))]
-    pub fn archive_basename(version: &Version) -> String {
-        format!("node-v{}-{}-{}", version, NODE_DISTRO_OS, NODE_DISTRO_ARCH)
-    }
-
-    #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
     pub fn archive_basename(version: &Version) -> String {
-        // Note: Node began shipping pre-built binaries for Apple Silicon with Major version 16
-        // Prior to that, we need to fall back on the x64 binaries
-        format!(
-            "node-v{}-{}-{}",
-            version,
-            NODE_DISTRO_OS,
-            if version.major >= 16 {
-                NODE_DISTRO_ARCH
-            } else {
-                NODE_DISTRO_ARCH_FALLBACK
-            }
-        )
+        Node::archive_basename_for(version, arch_override())
     }
 
-    #[cfg(all(target_os = "windows", target_arch = "aarch64"))]
-    pub fn archive_basename(version: &Version) -> String {
-        // Note: Node began shipping pre-built binaries for Windows ARM with Major version 20
-        // Prior to that, we need to fall back on the x64 binaries
+    /// The basename of the distro archive for a version, built for the overridden architecture
+    /// if there is one
+    pub fn archive_basename_for(version: &Version, arch: Option<&str>) -> String {
         format!(
             "node-v{}-{}-{}",
             version,
             NODE_DISTRO_OS,
-            if version.major >= 20 {
-                NODE_DISTRO_ARCH
-            } else {
-                NODE_DISTRO_ARCH_FALLBACK
-            }
+            arch.unwrap_or_else(|| default_distro_arch(version))
         )
     }
 
@@ -194,6 +214,25 @@ impl Node {
         )
     }
 
+    /// The image directory of a version, which is kept apart from the native images when the
+    /// architecture is overridden
+    pub fn image_dir(version: &Version) -> Fallible<PathBuf> {
+        let home = volta_home()?;
+        Ok(match overridden_arch(version, arch_override()) {
+            Some(arch) => home.node_arch_image_dir(arch, &version.to_string()),
+            None => home.node_image_dir(&version.to_string()),
+        })
+    }
+
+    /// The directory of the executables in the image of a version
+    pub fn image_bin_dir(version: &Version) -> Fallible<PathBuf> {
+        let home = volta_home()?;
+        Ok(match overridden_arch(version, arch_override()) {
+            Some(arch) => home.node_arch_image_bin_dir(arch, &version.to_string()),
+            None => home.node_image_bin_dir(&version.to_string()),
+        })
+    }
+
     pub(crate) fn ensure_fetched(&self, session: &mut Session) -> Fallible<NodeVersion> {
         match check_fetched(|| node_available(&self.version))? {
             FetchStatus::AlreadyFetched => {
@@ -205,7 +244,28 @@ impl Node {
                     npm,
                 })
             }
-            FetchStatus::FetchNeeded(_lock) => fetch::fetch(&self.version, session.hooks()?.node()),
+            FetchStatus::FetchNeeded(_lock) => {
+                check_may_fetch(self, session)?;
+                offline::check_may_download("node", &self.version, session, node_versions)?;
+                let overridden = overridden_arch(&self.version, arch_override());
+                if let Some(arch) = overridden {
+                    resolve::check_arch_available(&self.version, arch, session)?;
+                }
+                offline::stage_archive(
+                    self,
+                    session,
+                    &Node::archive_filename(&self.version),
+                    volta_home()?.node_inventory_dir(),
+                )?;
+                // A build for another architecture may only run under an emulator, if at all
+                let glibc_check = session.settings()?.node_glibc_check() && overridden.is_none();
+                let node_version =
+                    fetch::fetch(&self.version, session.hooks()?.node(), glibc_check)?;
+                let image_dir = Node::image_dir(&self.version)?;
+                quarantine::clear_image(&image_dir, session)?;
+                session.record_changes([Change::new(Subject::Image, Action::Created, image_dir)]);
+                Ok(node_version)
+            }
         }
     }
 }
@@ -223,7 +283,8 @@ impl Tool for Node {
         let node_version = self.ensure_fetched(session)?;
 
         let default_toolchain = session.toolchain_mut()?;
-        default_toolchain.set_active_node(&self.version)?;
+        let first_default = default_toolchain.platform().is_none();
+        let change = default_toolchain.set_active_node(&self.version)?;
 
         // If the user has a default version of `npm`, we shouldn't show the "(with npm@X.Y.ZZZ)" text in the success message
         // Instead we should check if the bundled version is higher than the default and inform the user
@@ -243,7 +304,16 @@ impl Tool for Node {
             info_installed(node_version); // includes node and npm version
         }
 
+        if first_default {
+            info!(
+                "{} this is now your default Node version, used everywhere outside of projects
+      that pin their own. To pin a version for a project, run `volta pin node` in it.",
+                note_prefix()
+            );
+        }
+
         check_shim_reachable("node");
+        session.record_changes(change);
 
         if let Ok(Some(project)) = session.project_platform() {
             info_project_version(tool_version("node", &project.node), &self);
@@ -251,20 +321,19 @@ impl Tool for Node {
 
         Ok(())
     }
-    fn pin(self: Box<Self>, session: &mut Session) -> Fallible<()> {
+    fn pin(self: Box<Self>, session: &mut Session) -> Fallible<String> {
         if session.project()?.is_some() {
             let node_version = self.ensure_fetched(session)?;
 
             // Note: We know this will succeed, since we checked above
             let project = session.project_mut()?.unwrap();
             project.pin_node(self.version.clone())?;
+            let version = self.version.clone();
 
             // If the user has a pinned version of `npm`, we shouldn't show the "(with npm@X.Y.ZZZ)" text in the success message
             // Instead we should check if the bundled version is higher than the pinned and inform the user
             // Note: The pin operation guarantees there will be a platform
-            if let Some(pinned_npm) = &project.platform().unwrap().npm {
-                info_pinned(self); // includes node version
-
+            let pinned = if let Some(pinned_npm) = &project.platform().unwrap().npm {
                 if node_version.npm > *pinned_npm {
                     info!("{} this version of Node includes {}, which is higher than your pinned version ({}).
       To use the version included with Node, run `volta pin npm@bundled`",
@@ -273,17 +342,36 @@ impl Tool for Node {
                         pinned_npm.to_string()
                     );
                 }
+
+                self.to_string() // includes node version
             } else {
-                info_pinned(node_version); // includes node and npm version
-            }
+                node_version.to_string() // includes node and npm version
+            };
 
-            Ok(())
+            warn_end_of_life(&version);
+
+            Ok(pinned)
         } else {
             Err(ErrorKind::NotInPackage.into())
         }
     }
 }
 
+/// Warns when the pinned version belongs to a release line that has reached end-of-life
+fn warn_end_of_life(version: &Version) {
+    let schedule = ReleaseSchedule::load();
+    if let Some(end) = schedule.end_of_life(version) {
+        if schedule.is_end_of_life_today(version) {
+            warn!(
+                "{} reached end-of-life on {} and no longer receives security updates.
+         Consider pinning a supported version, such as `volta pin node@lts`",
+                tool_version("node", version),
+                end
+            );
+        }
+    }
+}
+
 impl Display for Node {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(&tool_version("node", &self.version))
@@ -302,6 +390,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_node_archive_basename_with_arch_override() {
+        // The override is used even for versions that fall back to another architecture natively
+        for version in ["20.2.3", "15.2.3"] {
+            assert_eq!(
+                Node::archive_basename_for(&Version::parse(version).unwrap(), Some("x64")),
+                format!("node-v{}-{}-x64", version, NODE_DISTRO_OS)
+            );
+        }
+        assert_eq!(
+            Node::archive_basename_for(&Version::parse("20.2.3").unwrap(), None),
+            format!("node-v20.2.3-{}-{}", NODE_DISTRO_OS, NODE_DISTRO_ARCH)
+        );
+    }
+
     #[test]
     fn test_node_archive_filename() {
         assert_eq!(
@@ -1,25 +1,33 @@
 //! Provides resolution of Node requirements into specific versions, using the NodeJS index
+//!
+//! In offline mode, only the versions that have already been fetched are considered, and the cached
+//! index is only used to find out which of them are LTS releases.
 
+use std::collections::BTreeSet;
 use std::fs::File;
 use std::io::Write;
-use std::time::{Duration, SystemTime};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
 
-use super::super::registry_fetch_error;
+use super::super::{npmrc, offline, registry_fetch_error};
+use super::expiry::IndexExpiry;
 use super::metadata::{NodeEntry, NodeIndex, RawNodeIndex};
+use super::schedule::{self, ReleaseSchedule};
+use super::{arch, compact};
+use crate::download::DownloadPermit;
 use crate::error::{Context, ErrorKind, Fallible};
 use crate::fs::{create_staging_file, read_file};
 use crate::hook::ToolHooks;
+use crate::inventory::node_versions;
 use crate::layout::volta_home;
 use crate::session::Session;
-use crate::style::progress_spinner;
+use crate::style::{progress_spinner, tool_version};
 use crate::tool::Node;
 use crate::version::{VersionSpec, VersionTag};
-use attohttpc::header::HeaderMap;
 use attohttpc::Response;
 use cfg_if::cfg_if;
 use fs_utils::ensure_containing_dir_exists;
-use headers::{CacheControl, Expires, HeaderMapExt};
-use log::debug;
+use log::{debug, warn};
 use node_semver::{Range, Version};
 
 // ISSUE (#86): Move public repository URLs to config file
@@ -41,17 +49,195 @@ cfg_if! {
     }
 }
 
+/// The version tag for the newest Node release, whether or not it is an LTS release
+const CURRENT_TAG: &str = "current";
+
+static REFRESH_INDEX: AtomicBool = AtomicBool::new(false);
+
+/// Downloads the Node index again the next time it is needed, even if the cache hasn't expired,
+/// as `volta fetch --refresh-index` does
+pub fn refresh_index() {
+    REFRESH_INDEX.store(true, Ordering::Relaxed);
+}
+
 pub fn resolve(matching: VersionSpec, session: &mut Session) -> Fallible<Version> {
+    let matching = session.resolve_alias("node", matching)?;
     let hooks = session.hooks()?.node();
-    match matching {
-        VersionSpec::Semver(requirement) => resolve_semver(requirement, hooks),
+    let version = match matching {
+        // Node doesn't have "tagged" versions (apart from 'latest' and 'lts'), so custom tags will always be an error
+        VersionSpec::Tag(VersionTag::Custom(tag)) if tag != CURRENT_TAG => Err(session
+            .aliases()?
+            .unknown_alias_error("node", &tag)
+            .unwrap_or(ErrorKind::NodeVersionTagNotSupported { tag })
+            .into()),
+        matching if session.is_offline() => resolve_offline(matching, hooks),
         VersionSpec::Exact(version) => Ok(version),
-        VersionSpec::None | VersionSpec::Tag(VersionTag::Lts) => resolve_lts(hooks),
+        matching if !session.may_fetch()? => {
+            offline::resolve_installed("node", matching, |matching| {
+                resolve_offline(matching, hooks)
+            })
+        }
+        VersionSpec::Semver(requirement) => resolve_semver(requirement, hooks),
+        VersionSpec::None => {
+            debug!(
+                "No Node version specified, using the newest LTS release (use `node@{}` for the newest release)",
+                CURRENT_TAG
+            );
+            resolve_lts(hooks)
+        }
+        VersionSpec::Tag(VersionTag::Lts) => resolve_lts(hooks),
         VersionSpec::Tag(VersionTag::Latest) => resolve_latest(hooks),
-        // Node doesn't have "tagged" versions (apart from 'latest' and 'lts'), so custom tags will always be an error
-        VersionSpec::Tag(VersionTag::Custom(tag)) => {
-            Err(ErrorKind::NodeVersionNotFound { matching: tag }.into())
+        VersionSpec::Tag(VersionTag::Custom(_)) => {
+            debug!("Using the newest Node release, which may not be an LTS release");
+            resolve_latest(hooks)
+        }
+    }?;
+
+    session.check_policy("node", &version)?;
+    Ok(version)
+}
+
+/// Resolves the newest release of each LTS line that hasn't reached end-of-life, newest line first
+///
+/// The index is read once for all of the lines, from the cache when Volta is offline.
+pub fn resolve_all_lts(session: &mut Session) -> Fallible<Vec<Version>> {
+    let hooks = session.hooks()?.node();
+    let url = index_url(hooks)?;
+    let raw = if session.is_offline() {
+        match read_cached_index(&url)? {
+            Some(raw) => raw,
+            None => {
+                return Err(offline::version_not_available("node", "lts", &node_versions()?).into())
+            }
+        }
+    } else {
+        resolve_node_versions(&url, hooks)?
+    };
+    let index: NodeIndex = raw.into();
+    let schedule = ReleaseSchedule::load();
+
+    // The index lists releases from newest to oldest, so the first of each line is its newest
+    let mut versions: Vec<Version> = Vec::new();
+    for NodeEntry { version, lts } in index.entries {
+        if !lts || versions.iter().any(|newest| newest.major == version.major) {
+            continue;
         }
+        if schedule.is_end_of_life_today(&version) {
+            debug!(
+                "Skipping node@{}, its release line has reached end-of-life",
+                version
+            );
+            continue;
+        }
+        versions.push(version);
+    }
+
+    if versions.is_empty() {
+        return Err(ErrorKind::NodeVersionNotFound {
+            matching: "lts".into(),
+        }
+        .into());
+    }
+
+    for version in &versions {
+        session.check_policy("node", version)?;
+    }
+    debug!(
+        "Found the newest releases of the active LTS lines from {}",
+        url
+    );
+    Ok(versions)
+}
+
+/// Checks that the Node index has a build of a version for an overridden architecture, before it
+/// is fetched
+///
+/// When Volta is offline, the cached index is used if there is one, and the check is skipped if
+/// there isn't, as the archive can only come from a local directory then.
+pub(super) fn check_arch_available(
+    version: &Version,
+    arch: &str,
+    session: &mut Session,
+) -> Fallible<()> {
+    let hooks = session.hooks()?.node();
+    let url = index_url(hooks)?;
+    let raw = if session.is_offline() {
+        match read_cached_index(&url)? {
+            Some(raw) => raw,
+            None => return Ok(()),
+        }
+    } else {
+        resolve_node_versions(&url, hooks)?
+    };
+
+    match raw.0.iter().find(|entry| entry.version == *version) {
+        Some(entry) => arch::check_available(version, arch, &entry.files),
+        None => {
+            debug!(
+                "{} is not in the Node index, not checking its {} build",
+                tool_version("node", version),
+                arch
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Resolves a Node requirement against the versions that have been fetched, without network access
+fn resolve_offline(matching: VersionSpec, hooks: Option<&ToolHooks<Node>>) -> Fallible<Version> {
+    let fetched = node_versions()?;
+    match matching {
+        VersionSpec::None | VersionSpec::Tag(VersionTag::Lts) => {
+            resolve_lts_offline(hooks, &fetched)
+        }
+        VersionSpec::Tag(VersionTag::Custom(_)) => {
+            offline::resolve_fetched("node", &VersionSpec::Tag(VersionTag::Latest), &fetched)
+        }
+        matching => offline::resolve_fetched("node", &matching, &fetched),
+    }
+}
+
+/// Finds the newest fetched LTS release, using the cached index whether or not it has expired
+///
+/// A release never stops being an LTS release, so an expired index is still correct about the
+/// versions that have been fetched.
+fn resolve_lts_offline(
+    hooks: Option<&ToolHooks<Node>>,
+    fetched: &BTreeSet<Version>,
+) -> Fallible<Version> {
+    let url = index_url(hooks)?;
+    let index: Option<NodeIndex> = read_cached_index(&url)?.map(Into::into);
+    let version_opt = index.and_then(|index| {
+        index
+            .entries
+            .into_iter()
+            .find(|entry| entry.lts && fetched.contains(&entry.version))
+            .map(|NodeEntry { version, .. }| version)
+    });
+
+    match version_opt {
+        Some(version) => {
+            debug!(
+                "Found newest fetched LTS node version ({}) from the cached index",
+                version
+            );
+            Ok(version)
+        }
+        None => Err(offline::version_not_available("node", "lts", fetched).into()),
+    }
+}
+
+/// The URL of the Node index, which may be set by the `node.index` hook
+fn index_url(hooks: Option<&ToolHooks<Node>>) -> Fallible<String> {
+    match hooks {
+        Some(&ToolHooks {
+            index: Some(ref hook),
+            ..
+        }) => {
+            debug!("Using node.index hook to determine node index URL");
+            hook.resolve("index.json")
+        }
+        _ => Ok(public_node_version_index()),
     }
 }
 
@@ -69,7 +255,7 @@ fn resolve_latest(hooks: Option<&ToolHooks<Node>>) -> Fallible<Version> {
         }
         _ => public_node_version_index(),
     };
-    let version_opt = match_node_version(&url, |_| true)?;
+    let version_opt = match_node_version(&url, hooks, |_| true)?;
 
     match version_opt {
         Some(version) => {
@@ -84,17 +270,8 @@ fn resolve_latest(hooks: Option<&ToolHooks<Node>>) -> Fallible<Version> {
 }
 
 fn resolve_lts(hooks: Option<&ToolHooks<Node>>) -> Fallible<Version> {
-    let url = match hooks {
-        Some(&ToolHooks {
-            index: Some(ref hook),
-            ..
-        }) => {
-            debug!("Using node.index hook to determine node index URL");
-            hook.resolve("index.json")?
-        }
-        _ => public_node_version_index(),
-    };
-    let version_opt = match_node_version(&url, |&NodeEntry { lts, .. }| lts)?;
+    let url = index_url(hooks)?;
+    let version_opt = match_node_version(&url, hooks, |&NodeEntry { lts, .. }| lts)?;
 
     match version_opt {
         Some(version) => {
@@ -109,17 +286,8 @@ fn resolve_lts(hooks: Option<&ToolHooks<Node>>) -> Fallible<Version> {
 }
 
 fn resolve_semver(matching: Range, hooks: Option<&ToolHooks<Node>>) -> Fallible<Version> {
-    let url = match hooks {
-        Some(&ToolHooks {
-            index: Some(ref hook),
-            ..
-        }) => {
-            debug!("Using node.index hook to determine node index URL");
-            hook.resolve("index.json")?
-        }
-        _ => public_node_version_index(),
-    };
-    let version_opt = match_node_version(&url, |NodeEntry { version, .. }| {
+    let url = index_url(hooks)?;
+    let version_opt = match_node_version(&url, hooks, |NodeEntry { version, .. }| {
         matching.satisfies(version)
     })?;
 
@@ -140,9 +308,10 @@ fn resolve_semver(matching: Range, hooks: Option<&ToolHooks<Node>>) -> Fallible<
 
 fn match_node_version(
     url: &str,
+    hooks: Option<&ToolHooks<Node>>,
     predicate: impl Fn(&NodeEntry) -> bool,
 ) -> Fallible<Option<Version>> {
-    let index: NodeIndex = resolve_node_versions(url)?.into();
+    let index: NodeIndex = resolve_node_versions(url, hooks)?.into();
     let mut entries = index.entries.into_iter();
     Ok(entries
         .find(predicate)
@@ -150,21 +319,45 @@ fn match_node_version(
 }
 
 /// Reads a public index from the Node cache, if it exists and hasn't expired.
+///
+/// An expiry file that can't be parsed, such as one written by an older version of Volta, means
+/// that the cache has expired.
 fn read_cached_opt(url: &str) -> Fallible<Option<RawNodeIndex>> {
-    let expiry_file = volta_home()?.node_index_expiry_file();
-    let expiry = read_file(expiry_file).with_context(|| ErrorKind::ReadNodeIndexExpiryError {
-        file: expiry_file.to_owned(),
-    })?;
+    if REFRESH_INDEX.load(Ordering::Relaxed) {
+        debug!("Refreshing the Node index, ignoring any cache");
+        return Ok(None);
+    }
 
-    if !expiry
-        .map(|date| httpdate::parse_http_date(&date))
-        .transpose()
-        .with_context(|| ErrorKind::ParseNodeIndexExpiryError)?
-        .is_some_and(|expiry_date| SystemTime::now() < expiry_date)
-    {
+    let expiry_file = volta_home()?.node_index_expiry_file();
+    let Some(contents) =
+        read_file(expiry_file).with_context(|| ErrorKind::ReadNodeIndexExpiryError {
+            file: expiry_file.to_owned(),
+        })?
+    else {
         return Ok(None);
     };
 
+    match IndexExpiry::parse(&contents) {
+        Some(expiry) if expiry.is_fresh(SystemTime::now()) => read_cached_index(url),
+        Some(_) => Ok(None),
+        None => {
+            debug!(
+                "Could not parse the Node index expiry file '{}', treating the cache as expired",
+                expiry_file.display()
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// Reads a public index from the Node cache, if it exists, whether or not it has expired.
+///
+/// The compact copy of the index is read when it is up to date, and written when it isn't.
+fn read_cached_index(url: &str) -> Fallible<Option<RawNodeIndex>> {
+    if let Some(index) = compact::read(url) {
+        return Ok(Some(index));
+    }
+
     let index_file = volta_home()?.node_index_file();
     let cached = read_file(index_file).with_context(|| ErrorKind::ReadNodeIndexCacheError {
         file: index_file.to_owned(),
@@ -177,94 +370,128 @@ fn read_cached_opt(url: &str) -> Fallible<Option<RawNodeIndex>> {
         return Ok(None);
     };
 
-    serde_json::de::from_str(json).with_context(|| ErrorKind::ParseNodeIndexCacheError)
+    let index: RawNodeIndex =
+        serde_json::de::from_str(json).with_context(|| ErrorKind::ParseNodeIndexCacheError)?;
+    compact::write(url, &index);
+    Ok(Some(index))
 }
 
-/// Get the cache max-age of an HTTP response.
-fn max_age(headers: &HeaderMap) -> Duration {
-    const FOUR_HOURS: Duration = Duration::from_secs(4 * 60 * 60);
-    headers
-        .typed_get::<CacheControl>()
-        .and_then(|cache_control| cache_control.max_age())
-        .unwrap_or(FOUR_HOURS)
-}
+/// Reads the Node index from the cache, or downloads it if the cache has expired
+///
+/// If the index can't be downloaded or parsed, the expired cache is used instead (if there is
+/// one), so that an outage of the index server doesn't stop Volta from resolving versions.
+///
+/// The release schedule is refreshed along with the index, so it is never more stale than the index.
+fn resolve_node_versions(url: &str, hooks: Option<&ToolHooks<Node>>) -> Fallible<RawNodeIndex> {
+    if let Some(serial) = read_cached_opt(url)? {
+        debug!("Found valid cache of Node version index");
+        return Ok(serial);
+    }
 
-fn resolve_node_versions(url: &str) -> Fallible<RawNodeIndex> {
-    match read_cached_opt(url)? {
-        Some(serial) => {
-            debug!("Found valid cache of Node version index");
-            Ok(serial)
-        }
-        None => {
-            debug!("Node index cache was not found or was invalid");
-            let spinner = progress_spinner(format!("Fetching public registry: {}", url));
-
-            let (_, headers, response) = attohttpc::get(url)
-                .send()
-                .and_then(Response::error_for_status)
-                .with_context(registry_fetch_error("Node", url))?
-                .split();
-
-            let expires = headers
-                .typed_get::<Expires>()
-                .map(SystemTime::from)
-                .unwrap_or_else(|| SystemTime::now() + max_age(&headers));
-
-            let response_text = response
-                .text()
-                .with_context(registry_fetch_error("Node", url))?;
-
-            let index: RawNodeIndex =
-                serde_json::de::from_str(&response_text).with_context(|| {
-                    ErrorKind::ParseNodeIndexError {
-                        from_url: url.to_string(),
-                    }
-                })?;
-
-            let cached = create_staging_file()?;
-
-            let mut cached_file: &File = cached.as_file();
-            writeln!(cached_file, "{}", url)
-                .and_then(|_| cached_file.write(response_text.as_bytes()))
-                .with_context(|| ErrorKind::WriteNodeIndexCacheError {
-                    file: cached.path().to_path_buf(),
-                })?;
-
-            let index_cache_file = volta_home()?.node_index_file();
-            ensure_containing_dir_exists(&index_cache_file).with_context(|| {
-                ErrorKind::ContainingDirError {
-                    path: index_cache_file.to_owned(),
-                }
-            })?;
-            cached.persist(index_cache_file).with_context(|| {
-                ErrorKind::WriteNodeIndexCacheError {
-                    file: index_cache_file.to_owned(),
+    debug!("Node index cache was not found or was invalid");
+    let _permit = DownloadPermit::acquire();
+    let spinner = progress_spinner(format!("Fetching public registry: {}", url));
+    let download = download_node_index(url);
+    spinner.finish_and_clear();
+
+    let (index, response_text, expires) = match download {
+        Ok(download) => download,
+        Err(error) => {
+            return match read_cached_index(url).ok().flatten() {
+                Some(serial) => {
+                    debug!("{}", error);
+                    warn!(
+                        "Could not refresh the Node version index from {}, using the cached index instead. It may not include the newest Node releases.",
+                        url
+                    );
+                    Ok(serial)
                 }
-            })?;
+                None => Err(error),
+            };
+        }
+    };
 
-            let expiry = create_staging_file()?;
-            let mut expiry_file: &File = expiry.as_file();
+    // The index is only written to the cache once it has been parsed, so that an invalid
+    // response can never replace a valid cache
+    let cached = create_staging_file()?;
 
-            write!(expiry_file, "{}", httpdate::fmt_http_date(expires)).with_context(|| {
-                ErrorKind::WriteNodeIndexExpiryError {
-                    file: expiry.path().to_path_buf(),
-                }
-            })?;
+    let mut cached_file: &File = cached.as_file();
+    writeln!(cached_file, "{}", url)
+        .and_then(|_| cached_file.write(response_text.as_bytes()))
+        .with_context(|| ErrorKind::WriteNodeIndexCacheError {
+            file: cached.path().to_path_buf(),
+        })?;
 
-            let index_expiry_file = volta_home()?.node_index_expiry_file();
-            ensure_containing_dir_exists(&index_expiry_file).with_context(|| {
-                ErrorKind::ContainingDirError {
-                    path: index_expiry_file.to_owned(),
-                }
-            })?;
-            expiry.persist(index_expiry_file).with_context(|| {
-                ErrorKind::WriteNodeIndexExpiryError {
-                    file: index_expiry_file.to_owned(),
-                }
-            })?;
+    let index_cache_file = volta_home()?.node_index_file();
+    ensure_containing_dir_exists(&index_cache_file).with_context(|| {
+        ErrorKind::ContainingDirError {
+            path: index_cache_file.to_owned(),
+        }
+    })?;
+    cached
+        .persist(index_cache_file)
+        .with_context(|| ErrorKind::WriteNodeIndexCacheError {
+            file: index_cache_file.to_owned(),
+        })?;
+    compact::write(url, &index);
+
+    let expiry = create_staging_file()?;
+    let mut expiry_file: &File = expiry.as_file();
 
-            spinner.finish_and_clear();
-            Ok(index)
+    write!(expiry_file, "{}", expires).with_context(|| ErrorKind::WriteNodeIndexExpiryError {
+        file: expiry.path().to_path_buf(),
+    })?;
+
+    let index_expiry_file = volta_home()?.node_index_expiry_file();
+    ensure_containing_dir_exists(&index_expiry_file).with_context(|| {
+        ErrorKind::ContainingDirError {
+            path: index_expiry_file.to_owned(),
+        }
+    })?;
+    expiry
+        .persist(index_expiry_file)
+        .with_context(|| ErrorKind::WriteNodeIndexExpiryError {
+            file: index_expiry_file.to_owned(),
+        })?;
+
+    schedule::refresh(hooks);
+
+    Ok(index)
+}
+
+/// Downloads and parses the Node index, returning it along with the raw response (to be cached)
+/// and how long it may be cached for
+fn download_node_index(url: &str) -> Fallible<(RawNodeIndex, String, IndexExpiry)> {
+    let (_, headers, response) = npmrc::get(url)
+        .send()
+        .and_then(Response::error_for_status)
+        .with_context(registry_fetch_error("Node", url))?
+        .split();
+
+    let expires = IndexExpiry::from_headers(&headers, SystemTime::now());
+
+    let response_text = response
+        .text()
+        .with_context(registry_fetch_error("Node", url))?;
+
+    let index: RawNodeIndex = serde_json::de::from_str(&response_text).with_context(|| {
+        ErrorKind::ParseNodeIndexError {
+            from_url: url.to_string(),
+            response_start: response_start(&response_text),
         }
+    })?;
+
+    Ok((index, response_text, expires))
+}
+
+/// The start of a response body, to help identify what was returned instead of the index (such
+/// as the login page of a captive portal)
+fn response_start(body: &str) -> String {
+    const MAX_LEN: usize = 200;
+
+    let body = body.trim();
+    match body.char_indices().nth(MAX_LEN) {
+        Some((end, _)) => format!("{}...", &body[..end]),
+        None => body.to_string(),
     }
 }
@@ -0,0 +1,231 @@
+//! Provides the Node release schedule, which records when each major version reaches end-of-life
+//!
+//! A copy of the schedule is built into Volta, and a newer one is downloaded into the cache
+//! alongside the Node index, so that new end-of-life dates don't require a new Volta release.
+
+use std::collections::HashMap;
+use std::fs;
+
+use crate::download::DownloadPermit;
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::{create_staging_file, read_file};
+use crate::hook::ToolHooks;
+use crate::layout::volta_home;
+use crate::tool::Node;
+use attohttpc::Response;
+use cfg_if::cfg_if;
+use chrono::{Local, NaiveDate};
+use fs_utils::ensure_containing_dir_exists;
+use log::debug;
+use node_semver::Version;
+use serde::Deserialize;
+
+cfg_if! {
+    if #[cfg(feature = "mock-network")] {
+        #[allow(deprecated)]
+        const SERVER_URL: &str = mockito::SERVER_URL;
+        fn public_node_schedule() -> String {
+            format!("{}/node-schedule.json", SERVER_URL)
+        }
+    } else {
+        /// Returns the URL of the release schedule published by the Node release team.
+        fn public_node_schedule() -> String {
+            "https://raw.githubusercontent.com/nodejs/Release/main/schedule.json".to_string()
+        }
+    }
+}
+
+/// The schedule that was current when this version of Volta was built
+const BUILT_IN_SCHEDULE: &str = include_str!("schedule.json");
+
+#[derive(Deserialize)]
+struct RawScheduleEntry {
+    end: Option<String>,
+}
+
+/// The end-of-life dates of each release line, keyed like the published schedule (`v16`, `v0.12`)
+pub struct ReleaseSchedule {
+    end_of_life: HashMap<String, NaiveDate>,
+}
+
+impl ReleaseSchedule {
+    /// Loads the downloaded schedule from the cache, falling back to the built-in schedule
+    pub fn load() -> Self {
+        read_cached().unwrap_or_else(Self::built_in)
+    }
+
+    fn built_in() -> Self {
+        Self::parse(BUILT_IN_SCHEDULE).expect("built-in release schedule is valid")
+    }
+
+    fn parse(json: &str) -> Result<Self, serde_json::Error> {
+        let raw: HashMap<String, RawScheduleEntry> = serde_json::from_str(json)?;
+
+        Ok(ReleaseSchedule {
+            end_of_life: raw
+                .into_iter()
+                .filter_map(|(line, entry)| Some((line, entry.end?.parse().ok()?)))
+                .collect(),
+        })
+    }
+
+    /// The date on which the release line of the given version reaches end-of-life, if known
+    pub fn end_of_life(&self, version: &Version) -> Option<NaiveDate> {
+        // Before 4.0, each minor version was its own release line
+        let line = if version.major == 0 {
+            format!("v0.{}", version.minor)
+        } else {
+            format!("v{}", version.major)
+        };
+
+        self.end_of_life.get(&line).copied()
+    }
+
+    /// Determines whether the given version has reached end-of-life as of `today`
+    pub fn is_end_of_life(&self, version: &Version, today: NaiveDate) -> bool {
+        self.end_of_life(version).is_some_and(|end| end <= today)
+    }
+
+    /// Determines whether the given version has reached end-of-life, in the local time zone
+    pub fn is_end_of_life_today(&self, version: &Version) -> bool {
+        self.is_end_of_life(version, Local::now().date_naive())
+    }
+}
+
+/// Reads the schedule downloaded into the cache, if there is one
+fn read_cached() -> Option<ReleaseSchedule> {
+    let schedule_file = volta_home().ok()?.node_schedule_file();
+    let json = read_file(schedule_file).ok()??;
+
+    match ReleaseSchedule::parse(&json) {
+        Ok(schedule) => Some(schedule),
+        Err(error) => {
+            debug!(
+                "Could not parse the cached Node release schedule: {}",
+                error
+            );
+            None
+        }
+    }
+}
+
+/// Downloads the newest schedule into the cache
+///
+/// The schedule is only used for informational warnings, so failures are logged rather than
+/// interrupting the command that refreshed the Node index.
+pub(super) fn refresh(hooks: Option<&ToolHooks<Node>>) {
+    if let Err(error) = download(hooks) {
+        debug!("Could not refresh the Node release schedule: {}", error);
+    }
+}
+
+fn download(hooks: Option<&ToolHooks<Node>>) -> Fallible<()> {
+    let url = match hooks {
+        Some(&ToolHooks {
+            schedule: Some(ref hook),
+            ..
+        }) => {
+            debug!("Using node.schedule hook to determine the release schedule URL");
+            hook.resolve("schedule.json")?
+        }
+        _ => public_node_schedule(),
+    };
+
+    let _permit = DownloadPermit::acquire();
+    let response = attohttpc::get(&url)
+        .send()
+        .and_then(Response::error_for_status)
+        .and_then(Response::text)
+        .with_context(|| ErrorKind::NodeScheduleFetchError {
+            from_url: url.clone(),
+        })?;
+
+    // Make sure the schedule is usable before replacing the cached copy
+    ReleaseSchedule::parse(&response).with_context(|| ErrorKind::ParseNodeScheduleError {
+        from_url: url.clone(),
+    })?;
+
+    let schedule_file = volta_home()?.node_schedule_file();
+    let staged = create_staging_file()?;
+    fs::write(staged.path(), response).with_context(|| ErrorKind::WriteNodeScheduleCacheError {
+        file: staged.path().to_owned(),
+    })?;
+
+    ensure_containing_dir_exists(&schedule_file).with_context(|| {
+        ErrorKind::ContainingDirError {
+            path: schedule_file.to_owned(),
+        }
+    })?;
+    staged
+        .persist(schedule_file)
+        .with_context(|| ErrorKind::WriteNodeScheduleCacheError {
+            file: schedule_file.to_owned(),
+        })?;
+
+    debug!("Refreshed the Node release schedule from {}", url);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    fn schedule() -> ReleaseSchedule {
+        ReleaseSchedule::parse(
+            r#"{
+                "v0.12": { "start": "2015-02-06", "end": "2016-12-31" },
+                "v16": { "start": "2021-04-20", "lts": "2021-10-26", "end": "2023-09-11" },
+                "v26": { "start": "2026-04-21" }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn built_in_schedule_is_valid() {
+        let schedule = ReleaseSchedule::built_in();
+        assert_eq!(
+            schedule.end_of_life(&Version::from((16, 20, 2))),
+            Some(date("2023-09-11"))
+        );
+    }
+
+    #[test]
+    fn end_of_life_by_release_line() {
+        let schedule = schedule();
+        assert_eq!(
+            schedule.end_of_life(&Version::from((16, 0, 0))),
+            Some(date("2023-09-11"))
+        );
+        assert_eq!(
+            schedule.end_of_life(&Version::from((0, 12, 18))),
+            Some(date("2016-12-31"))
+        );
+        assert_eq!(schedule.end_of_life(&Version::from((0, 10, 48))), None);
+        assert_eq!(schedule.end_of_life(&Version::from((26, 0, 0))), None);
+        assert_eq!(schedule.end_of_life(&Version::from((99, 0, 0))), None);
+    }
+
+    #[test]
+    fn end_of_life_depends_on_today() {
+        let schedule = schedule();
+        let node_16 = Version::from((16, 20, 2));
+
+        assert!(!schedule.is_end_of_life(&node_16, date("2022-01-01")));
+        assert!(!schedule.is_end_of_life(&node_16, date("2023-09-10")));
+        assert!(schedule.is_end_of_life(&node_16, date("2023-09-11")));
+        assert!(schedule.is_end_of_life(&node_16, date("2030-01-01")));
+    }
+
+    #[test]
+    fn unknown_release_lines_are_never_end_of_life() {
+        let schedule = schedule();
+
+        assert!(!schedule.is_end_of_life(&Version::from((26, 0, 0)), date("2099-01-01")));
+        assert!(!schedule.is_end_of_life(&Version::from((99, 0, 0)), date("2099-01-01")));
+    }
+}
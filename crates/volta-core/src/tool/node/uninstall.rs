@@ -0,0 +1,164 @@
+use std::path::PathBuf;
+
+use super::Node;
+use crate::change::{Change, Subject};
+use crate::error::{ErrorKind, Fallible};
+use crate::fs::{remove_dir_if_exists, remove_file_if_exists};
+use crate::inventory::node_versions;
+use crate::layout::volta_home;
+use crate::session::Session;
+use crate::style::{note_prefix, success_prefix, tool_version};
+use crate::sync::VoltaLock;
+use crate::tool::checksum::checksum_file;
+use crate::version::VersionSpec;
+use log::{info, warn};
+use node_semver::Version;
+
+/// Uninstalls a version of Node.
+///
+/// This removes:
+///
+/// - The Node image
+/// - The archive of the Node distro in the inventory
+/// - The file in the inventory that records the version of npm bundled with it
+///
+/// The default version of Node, and the version used by the current project, are never removed.
+pub fn uninstall(matching: VersionSpec, session: &mut Session) -> Fallible<()> {
+    // Acquire a lock on the Volta directory, if possible, to prevent concurrent changes
+    let _lock = VoltaLock::acquire();
+
+    let plan = UninstallPlan::collect(matching, session)?;
+    let changes = plan.execute()?;
+    session.record_changes(changes);
+    plan.report(Mode::Execute);
+
+    Ok(())
+}
+
+/// Shows what uninstalling a version of Node would remove, without removing anything.
+pub fn uninstall_dry_run(matching: VersionSpec, session: &mut Session) -> Fallible<()> {
+    let plan = UninstallPlan::collect(matching, session)?;
+    plan.report(Mode::DryRun);
+
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum Mode {
+    Execute,
+    DryRun,
+}
+
+impl Mode {
+    fn verb(self) -> &'static str {
+        match self {
+            Mode::Execute => "Removed",
+            Mode::DryRun => "Would remove",
+        }
+    }
+}
+
+/// The files and directories that uninstalling a version of Node will remove
+struct UninstallPlan {
+    version: Version,
+    image: Option<PathBuf>,
+    inventory_files: Vec<PathBuf>,
+}
+
+impl UninstallPlan {
+    fn collect(matching: VersionSpec, session: &mut Session) -> Fallible<Self> {
+        let VersionSpec::Exact(version) = matching else {
+            let installed = node_versions()?.iter().map(Version::to_string).collect();
+            return Err(ErrorKind::NodeUninstallVersionRequired { installed }.into());
+        };
+
+        let home = volta_home()?;
+        let inventory_dir = home.node_inventory_dir();
+        let version_string = version.to_string();
+
+        let image = Some(Node::image_dir(&version)?).filter(|image| image.exists());
+        let archive = inventory_dir.join(Node::archive_filename(&version));
+        let archive_checksum = checksum_file(&archive);
+        let inventory_files: Vec<PathBuf> = [
+            archive,
+            archive_checksum,
+            home.node_npm_version_file(&version_string),
+        ]
+        .into_iter()
+        .filter(|file| file.exists())
+        .collect();
+
+        if image.is_some() || !inventory_files.is_empty() {
+            check_not_in_use(&version, session)?;
+        }
+
+        Ok(UninstallPlan {
+            version,
+            image,
+            inventory_files,
+        })
+    }
+
+    fn execute(&self) -> Fallible<Vec<Change>> {
+        let mut changes = Vec::new();
+        if let Some(image) = &self.image {
+            remove_dir_if_exists(image)?;
+            changes.push(Change::removed(Subject::Image, image));
+        }
+        for file in &self.inventory_files {
+            remove_file_if_exists(file)?;
+            changes.push(Change::removed(Subject::Inventory, file));
+        }
+
+        Ok(changes)
+    }
+
+    fn report(&self, mode: Mode) {
+        let version = tool_version("node", &self.version);
+
+        if self.image.is_none() && self.inventory_files.is_empty() {
+            warn!("No {} found to uninstall", version);
+            return;
+        }
+
+        if let Some(image) = &self.image {
+            info!("{} Node image: {}", mode.verb(), image.display());
+        }
+        for file in &self.inventory_files {
+            info!("{} inventory file: {}", mode.verb(), file.display());
+        }
+
+        match mode {
+            Mode::Execute => info!("{} {} uninstalled", success_prefix(), version),
+            Mode::DryRun => info!("{} dry run, {} was not uninstalled", note_prefix(), version),
+        }
+    }
+}
+
+/// Refuses to remove the default version of Node, or the one the current project uses, since
+/// Volta would have to fetch it again the next time it's run
+fn check_not_in_use(version: &Version, session: &mut Session) -> Fallible<()> {
+    if let Some(platform) = session.default_platform()? {
+        if platform.node == *version {
+            return Err(ErrorKind::UninstallDefaultNode {
+                version: version.to_string(),
+            }
+            .into());
+        }
+    }
+
+    if let Some(project) = session.project()? {
+        if project
+            .platform()
+            .is_some_and(|platform| platform.node == *version)
+        {
+            return Err(ErrorKind::UninstallProjectNode {
+                version: version.to_string(),
+                manifest: project.manifest_file().to_owned(),
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
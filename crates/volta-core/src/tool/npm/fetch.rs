@@ -5,6 +5,7 @@ use std::path::Path;
 
 use super::super::download_tool_error;
 use super::super::registry::public_registry_package;
+use crate::download::DownloadPermit;
 use crate::error::{Context, ErrorKind, Fallible};
 use crate::fs::{create_staging_dir, create_staging_file, rename, set_executable};
 use crate::hook::ToolHooks;
@@ -17,24 +18,42 @@ use fs_utils::ensure_containing_dir_exists;
 use log::debug;
 use node_semver::Version;
 
+/// Fetch an npm version into the image directory
+///
+/// The images are keyed by the exact version, so an npm that is already unpacked, such as for
+/// another Node version, is reused as it is. Otherwise the archive in the inventory is unpacked if
+/// there is one, and it is only downloaded when it isn't.
 pub fn fetch(version: &Version, hooks: Option<&ToolHooks<Npm>>) -> Fallible<()> {
-    let npm_dir = volta_home()?.npm_inventory_dir();
+    let home = volta_home()?;
+    let image_dir = home.npm_image_dir(&version.to_string());
+    if image_dir.is_dir() {
+        debug!(
+            "Reusing {} from '{}'",
+            tool_version("npm", version),
+            image_dir.display()
+        );
+        return Ok(());
+    }
+
+    let npm_dir = home.npm_inventory_dir();
     let cache_file = npm_dir.join(Npm::archive_filename(&version.to_string()));
 
-    let (archive, staging) = match load_cached_distro(&cache_file) {
+    let (archive, staging, _permit) = match load_cached_distro(&cache_file) {
         Some(archive) => {
             debug!(
                 "Loading {} from cached archive at '{}'",
                 tool_version("npm", version),
                 cache_file.display()
             );
-            (archive, None)
+            (archive, None, None)
         }
         None => {
+            // The permit is held until unpacking completes, since the archive is streamed
+            let permit = DownloadPermit::acquire();
             let staging = create_staging_file()?;
             let remote_url = determine_remote_url(version, hooks)?;
             let archive = fetch_remote_distro(version, &remote_url, staging.path())?;
-            (archive, Some(staging))
+            (archive, Some(staging), Some(permit))
         }
     };
 
@@ -59,11 +78,7 @@ fn unpack_archive(archive: Box<dyn Archive>, version: &Version) -> Fallible<()>
     let temp = create_staging_dir()?;
     debug!("Unpacking npm into '{}'", temp.path().display());
 
-    let progress = progress_bar(
-        archive.origin(),
-        &tool_version("npm", version),
-        archive.compressed_size(),
-    );
+    let mut progress = progress_bar(archive.origin(), "npm", version, archive.compressed_size());
     let version_string = version.to_string();
 
     archive
@@ -115,7 +130,7 @@ fn load_cached_distro(file: &Path) -> Option<Box<dyn Archive>> {
     }
 }
 
-/// Determine the remote URL to download from, using the hooks if avaialble
+/// Determine the remote URL to download from, using the hooks if available
 fn determine_remote_url(version: &Version, hooks: Option<&ToolHooks<Npm>>) -> Fallible<String> {
     let version_str = version.to_string();
     match hooks {
@@ -1,15 +1,18 @@
 use std::fmt::{self, Display};
 
-use super::node::load_default_npm_version;
+use super::node::{self, load_default_npm_version};
 use super::{
-    check_fetched, check_shim_reachable, debug_already_fetched, info_fetched, info_installed,
-    info_pinned, info_project_version, FetchStatus, Tool,
+    check_fetched, check_may_fetch, check_shim_reachable, debug_already_fetched, info_fetched,
+    info_installed, info_project_version, offline, quarantine, FetchStatus, Node, Tool,
 };
+use crate::change::{Action, Change, Subject};
 use crate::error::{Context, ErrorKind, Fallible};
-use crate::inventory::npm_available;
+use crate::inventory::{npm_available, npm_versions};
+use crate::layout::volta_home;
 use crate::session::Session;
 use crate::style::{success_prefix, tool_version};
 use crate::sync::VoltaLock;
+use crate::version::VersionSpec;
 use log::info;
 use node_semver::Version;
 
@@ -18,6 +21,10 @@ mod resolve;
 
 pub use resolve::resolve;
 
+/// The version tag used to fetch the npm bundled with a given Node version,
+/// as in `volta fetch npm@bundled-for node@20`
+pub const BUNDLED_FOR_TAG: &str = "bundled-for";
+
 /// The Tool implementation for fetching and installing npm
 pub struct Npm {
     pub(super) version: Version,
@@ -42,7 +49,21 @@ impl Npm {
                 debug_already_fetched(self);
                 Ok(())
             }
-            FetchStatus::FetchNeeded(_lock) => fetch::fetch(&self.version, session.hooks()?.npm()),
+            FetchStatus::FetchNeeded(_lock) => {
+                check_may_fetch(self, session)?;
+                offline::check_may_download("npm", &self.version, session, npm_versions)?;
+                offline::stage_archive(
+                    self,
+                    session,
+                    &Npm::archive_filename(&self.version.to_string()),
+                    volta_home()?.npm_inventory_dir(),
+                )?;
+                fetch::fetch(&self.version, session.hooks()?.npm())?;
+                let image_dir = volta_home()?.npm_image_dir(&self.version.to_string());
+                quarantine::clear_image(&image_dir, session)?;
+                session.record_changes([Change::new(Subject::Image, Action::Created, image_dir)]);
+                Ok(())
+            }
         }
     }
 }
@@ -59,9 +80,10 @@ impl Tool for Npm {
         let _lock = VoltaLock::acquire();
         self.ensure_fetched(session)?;
 
-        session
+        let change = session
             .toolchain_mut()?
             .set_active_npm(Some(self.version.clone()))?;
+        session.record_changes(change);
 
         info_installed(&self);
         check_shim_reachable("npm");
@@ -73,7 +95,7 @@ impl Tool for Npm {
         }
         Ok(())
     }
-    fn pin(self: Box<Self>, session: &mut Session) -> Fallible<()> {
+    fn pin(self: Box<Self>, session: &mut Session) -> Fallible<String> {
         if session.project()?.is_some() {
             self.ensure_fetched(session)?;
 
@@ -81,8 +103,7 @@ impl Tool for Npm {
             let project = session.project_mut()?.unwrap();
             project.pin_npm(Some(self.version.clone()))?;
 
-            info_pinned(self);
-            Ok(())
+            Ok(self.to_string())
         } else {
             Err(ErrorKind::NotInPackage.into())
         }
@@ -107,7 +128,7 @@ impl Tool for BundledNpm {
     fn install(self: Box<Self>, session: &mut Session) -> Fallible<()> {
         let toolchain = session.toolchain_mut()?;
 
-        toolchain.set_active_npm(None)?;
+        let change = toolchain.set_active_npm(None)?;
 
         let bundled_version = match toolchain.platform() {
             Some(platform) => {
@@ -131,11 +152,12 @@ impl Tool for BundledNpm {
             success_prefix(),
             bundled_version
         );
+        session.record_changes(change);
 
         Ok(())
     }
 
-    fn pin(self: Box<Self>, session: &mut Session) -> Fallible<()> {
+    fn pin(self: Box<Self>, session: &mut Session) -> Fallible<String> {
         match session.project_mut()? {
             Some(project) => {
                 project.pin_npm(None)?;
@@ -158,19 +180,30 @@ impl Tool for BundledNpm {
                     }
                 };
 
-                info!(
-                    "{} set package.json to use bundled npm (currently {})",
-                    success_prefix(),
-                    bundled_version
-                );
-
-                Ok(())
+                Ok(format!("bundled npm (currently {})", bundled_version))
             }
             None => Err(ErrorKind::NotInPackage.into()),
         }
     }
 }
 
+/// Fetches a Node version and records the version of npm bundled with it
+///
+/// Once recorded, the bundled npm can be resolved for that Node version without network access,
+/// for example by `volta pin npm@bundled`.
+pub fn fetch_bundled_for(node: VersionSpec, session: &mut Session) -> Fallible<()> {
+    let version = node::resolve(node, session)?;
+    let node_version = Node::new(version).ensure_fetched(session)?;
+
+    info!(
+        "{} recorded bundled {} for {}",
+        success_prefix(),
+        tool_version("npm", &node_version.npm),
+        tool_version("node", &node_version.runtime)
+    );
+    Ok(())
+}
+
 impl Display for BundledNpm {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(&tool_version("npm", "bundled"))
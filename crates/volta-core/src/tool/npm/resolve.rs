@@ -1,10 +1,12 @@
 //! Provides resolution of npm Version requirements into specific versions
 
+use super::super::offline;
 use super::super::registry::{
     fetch_npm_registry, public_registry_index, PackageDetails, PackageIndex,
 };
 use crate::error::{ErrorKind, Fallible};
 use crate::hook::ToolHooks;
+use crate::inventory::npm_versions;
 use crate::session::Session;
 use crate::tool::Npm;
 use crate::version::{VersionSpec, VersionTag};
@@ -12,16 +14,31 @@ use log::debug;
 use node_semver::{Range, Version};
 
 pub fn resolve(matching: VersionSpec, session: &mut Session) -> Fallible<Option<Version>> {
+    let matching = session.resolve_alias("npm", matching)?;
     let hooks = session.hooks()?.npm();
-    match matching {
-        VersionSpec::Semver(requirement) => resolve_semver(requirement, hooks).map(Some),
+    let version = match matching {
+        VersionSpec::Tag(VersionTag::Custom(tag)) if tag == "bundled" => Ok(None),
+        matching if session.is_offline() => {
+            offline::resolve_fetched("npm", &matching, &npm_versions()?).map(Some)
+        }
         VersionSpec::Exact(version) => Ok(Some(version)),
+        matching if !session.may_fetch()? => {
+            offline::resolve_installed("npm", matching, |matching| {
+                offline::resolve_fetched("npm", &matching, &npm_versions()?)
+            })
+            .map(Some)
+        }
+        VersionSpec::Semver(requirement) => resolve_semver(requirement, hooks).map(Some),
         VersionSpec::None | VersionSpec::Tag(VersionTag::Latest) => {
             resolve_tag("latest", hooks).map(Some)
         }
-        VersionSpec::Tag(VersionTag::Custom(tag)) if tag == "bundled" => Ok(None),
         VersionSpec::Tag(tag) => resolve_tag(&tag.to_string(), hooks).map(Some),
+    }?;
+
+    if let Some(version) = &version {
+        session.check_policy("npm", version)?;
     }
+    Ok(version)
 }
 
 fn fetch_npm_index(hooks: Option<&ToolHooks<Npm>>) -> Fallible<(String, PackageIndex)> {
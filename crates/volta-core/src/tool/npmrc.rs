@@ -0,0 +1,531 @@
+//! Reads the settings from the npm configuration (`.npmrc`) that apply to Volta's own registry
+//! requests, and the global prefix that npm installs packages into
+//!
+//! Only the keys that affect how a registry is reached are supported: the registry URLs, auth
+//! tokens, and proxies, plus `prefix`. As with npm, the project-level file overrides the
+//! user-level one, which overrides the global one. Hooks still take precedence over the registry
+//! configured here.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::project::find_closest_root;
+use attohttpc::{ProxySettings, RequestBuilder};
+use log::debug;
+use once_cell::sync::Lazy;
+use url::Url;
+
+static NPMRC: Lazy<Npmrc> = Lazy::new(Npmrc::load);
+
+/// The registry settings from the global, the user's and the project's `.npmrc` files
+///
+/// This intentionally doesn't implement `Debug`, so that the auth tokens can't end up in a log.
+#[derive(Default)]
+struct Npmrc {
+    registry: Option<String>,
+    scoped_registries: HashMap<String, String>,
+    /// Auth tokens, keyed by the registry URL without its scheme, e.g. `//registry.example.com/`
+    auth_tokens: HashMap<String, String>,
+    proxy: Option<String>,
+    https_proxy: Option<String>,
+    /// The hosts that are reached without a proxy, separated by commas
+    noproxy: Option<String>,
+    prefix: Option<String>,
+}
+
+/// Starts a GET request to the given URL, using the proxy and auth token from the `.npmrc` files
+pub(crate) fn get(url: &str) -> RequestBuilder {
+    NPMRC.get(url)
+}
+
+/// The proxies configured in the `.npmrc` files or the environment, for requests that aren't made
+/// with [`get`]
+pub(crate) fn proxy_settings() -> ProxySettings {
+    NPMRC.proxy_settings()
+}
+
+/// The registry configured in the `.npmrc` files for the given package, if any
+pub(super) fn registry_for(package: &str) -> Option<&'static str> {
+    NPMRC.registry_for(package)
+}
+
+/// The global prefix configured for npm in the environment or in the `.npmrc` files, if any
+///
+/// `node_prefix` is the prefix that npm uses by default, i.e. the Node install it runs with,
+/// which holds npm's global configuration file.
+pub(crate) fn prefix(node_prefix: &Path) -> Option<PathBuf> {
+    if let Some(prefix) = env_path(["npm_config_prefix", "NPM_CONFIG_PREFIX"]) {
+        return Some(prefix);
+    }
+
+    let npmrc = Npmrc::load_from([
+        global_npmrc(Some(node_prefix)),
+        user_npmrc(),
+        project_npmrc(),
+    ]);
+    expand_home(npmrc.prefix.as_deref()?, dirs::home_dir().as_deref())
+}
+
+impl Npmrc {
+    /// Loads the settings for Volta's registry requests
+    ///
+    /// Which Node install npm will run with isn't known here, so the global file is only found
+    /// when it's given in the environment.
+    fn load() -> Self {
+        Npmrc::load_from([global_npmrc(None), user_npmrc(), project_npmrc()])
+    }
+
+    /// Loads the settings from the given files, each overriding the ones before it
+    fn load_from(files: [Option<PathBuf>; 3]) -> Self {
+        let mut npmrc = Npmrc::default();
+
+        for file in files.into_iter().flatten() {
+            if let Ok(contents) = fs::read_to_string(&file) {
+                debug!("Reading npm settings from '{}'", file.display());
+                npmrc.merge(Npmrc::parse(&contents));
+            }
+        }
+
+        npmrc
+    }
+
+    /// Parses the supported keys from the contents of an `.npmrc` file, ignoring all others
+    fn parse(contents: &str) -> Self {
+        let mut npmrc = Npmrc::default();
+
+        let settings = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#') && !line.starts_with(';'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim(), expand_env(unquote(value))))
+            .filter(|(_, value)| !value.is_empty());
+
+        for (key, value) in settings {
+            match key {
+                "registry" => npmrc.registry = Some(value),
+                "proxy" => npmrc.proxy = Some(value),
+                "https-proxy" => npmrc.https_proxy = Some(value),
+                "noproxy" => npmrc.noproxy = Some(value),
+                "prefix" => npmrc.prefix = Some(value),
+                _ => {
+                    if let Some(scope) = key.strip_suffix(":registry") {
+                        npmrc.scoped_registries.insert(scope.into(), value);
+                    } else if let Some(registry) = key.strip_suffix(":_authToken") {
+                        npmrc
+                            .auth_tokens
+                            .insert(with_trailing_slash(registry), value);
+                    }
+                }
+            }
+        }
+
+        npmrc
+    }
+
+    /// Overrides these settings with the ones from a file of higher precedence
+    fn merge(&mut self, other: Npmrc) {
+        if other.registry.is_some() {
+            self.registry = other.registry;
+        }
+        if other.proxy.is_some() {
+            self.proxy = other.proxy;
+        }
+        if other.https_proxy.is_some() {
+            self.https_proxy = other.https_proxy;
+        }
+        if other.noproxy.is_some() {
+            self.noproxy = other.noproxy;
+        }
+        if other.prefix.is_some() {
+            self.prefix = other.prefix;
+        }
+        self.scoped_registries.extend(other.scoped_registries);
+        self.auth_tokens.extend(other.auth_tokens);
+    }
+
+    fn registry_for(&self, package: &str) -> Option<&str> {
+        let scoped = package
+            .split('/')
+            .next()
+            .filter(|scope| scope.starts_with('@'))
+            .and_then(|scope| self.scoped_registries.get(scope));
+
+        scoped.or(self.registry.as_ref()).map(String::as_str)
+    }
+
+    /// Finds the auth token for a URL, from the most specific registry URL that contains it
+    fn auth_token_for(&self, url: &str) -> Option<&str> {
+        let (_, without_scheme) = url.split_once(':')?;
+
+        self.auth_tokens
+            .iter()
+            .filter(|(registry, _)| without_scheme.starts_with(registry.as_str()))
+            .max_by_key(|(registry, _)| registry.len())
+            .map(|(_, token)| token.as_str())
+    }
+
+    /// The proxies from the `.npmrc` files, or from the environment if none are configured
+    ///
+    /// As with npm, `https-proxy` falls back to `proxy` for HTTPS requests, and the hosts in
+    /// `noproxy`, or in `NO_PROXY` if it isn't set, are reached without a proxy.
+    fn proxy_settings(&self) -> ProxySettings {
+        self.proxy_settings_with(proxy_env)
+    }
+
+    /// The proxy settings, reading the proxy environment variables with the given function
+    fn proxy_settings_with<F>(&self, env: F) -> ProxySettings
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let (http_proxy, https_proxy) = if self.proxy.is_some() || self.https_proxy.is_some() {
+            let https_proxy = self.https_proxy.clone().or_else(|| self.proxy.clone());
+            (self.proxy.clone(), https_proxy)
+        } else {
+            // The same variables that attohttpc reads by default
+            let all_proxy = env("all_proxy");
+            let https_proxy = env("https_proxy").or_else(|| all_proxy.clone());
+            (env("http_proxy").or(all_proxy), https_proxy)
+        };
+
+        let no_proxy = self.noproxy.clone().or_else(|| env("no_proxy"));
+        if no_proxy.as_deref().map(str::trim) == Some("*") {
+            return ProxySettings::builder().build();
+        }
+
+        let parse = |proxy: Option<String>| proxy.and_then(|proxy| Url::parse(&proxy).ok());
+        let builder = ProxySettings::builder()
+            .http_proxy(parse(http_proxy))
+            .https_proxy(parse(https_proxy));
+
+        no_proxy
+            .iter()
+            .flat_map(|hosts| hosts.split(','))
+            .map(|host| host.trim().trim_start_matches('.'))
+            .filter(|host| !host.is_empty())
+            .fold(builder, |builder, host| builder.add_no_proxy_host(host))
+            .build()
+    }
+
+    fn get(&self, url: &str) -> RequestBuilder {
+        let request = attohttpc::get(url).proxy_settings(self.proxy_settings());
+
+        match self.auth_token_for(url) {
+            Some(token) => {
+                debug!("Using the auth token from .npmrc for {}", url);
+                request.bearer_auth(token)
+            }
+            None => request,
+        }
+    }
+}
+
+/// Reads a proxy environment variable, preferring the lowercase name as curl does
+fn proxy_env(name: &str) -> Option<String> {
+    [name.to_owned(), name.to_ascii_uppercase()]
+        .into_iter()
+        .find_map(|name| env::var(name).ok())
+        .filter(|value| !value.trim().is_empty())
+}
+
+/// The first of the given environment variables that is set to a path
+fn env_path<const N: usize>(names: [&str; N]) -> Option<PathBuf> {
+    names
+        .into_iter()
+        .find_map(env::var_os)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// The global `npmrc`, which npm keeps in `etc` of its prefix unless the `globalconfig` setting
+/// moves it
+fn global_npmrc(node_prefix: Option<&Path>) -> Option<PathBuf> {
+    env_path(["npm_config_globalconfig", "NPM_CONFIG_GLOBALCONFIG"]).or_else(|| {
+        let prefix = env_path(["npm_config_prefix", "NPM_CONFIG_PREFIX"])
+            .or_else(|| node_prefix.map(Path::to_path_buf))?;
+        Some(prefix.join("etc").join("npmrc"))
+    })
+}
+
+/// The user-level `.npmrc`, which npm allows to be moved with the `userconfig` setting
+fn user_npmrc() -> Option<PathBuf> {
+    env_path(["npm_config_userconfig", "NPM_CONFIG_USERCONFIG"])
+        .or_else(|| dirs::home_dir().map(|home| home.join(".npmrc")))
+}
+
+/// The `.npmrc` in the root of the current project
+fn project_npmrc() -> Option<PathBuf> {
+    let root = find_closest_root(env::current_dir().ok()?)?;
+    Some(root.join(".npmrc"))
+}
+
+/// Resolves a path from an `.npmrc` file, where npm expands a leading `~/` to the home directory
+fn expand_home(value: &str, home: Option<&Path>) -> Option<PathBuf> {
+    match value.strip_prefix("~/") {
+        Some(relative) => Some(home?.join(relative)),
+        None => Some(PathBuf::from(value)),
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim().trim_matches('"')
+}
+
+fn with_trailing_slash(registry: &str) -> String {
+    if registry.ends_with('/') {
+        registry.into()
+    } else {
+        format!("{}/", registry)
+    }
+}
+
+/// Replaces `${NAME}` with the value of the environment variable, as npm does
+///
+/// References to variables that aren't set are left as they are.
+fn expand_env(value: &str) -> String {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 2..start + length];
+
+        expanded.push_str(&rest[..start]);
+        match env::var(name) {
+            Ok(variable) => expanded.push_str(&variable),
+            Err(_) => expanded.push_str(&rest[start..=start + length]),
+        }
+        rest = &rest[start + length + 1..];
+    }
+
+    expanded.push_str(rest);
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER_NPMRC: &str = r#"
+# Settings for the company registry
+registry=https://registry.example.com/
+@corp:registry = https://npm.corp.example.com/
+//registry.example.com/:_authToken=user-token
+//npm.corp.example.com/:_authToken="corp-token"
+proxy=http://proxy.example.com:8080
+"#;
+
+    const PROJECT_NPMRC: &str = r#"
+registry=https://project.example.com/npm/
+//project.example.com/npm/:_authToken=project-token
+https-proxy=http://secure-proxy.example.com:8443
+"#;
+
+    #[test]
+    fn parses_supported_keys() {
+        let npmrc = Npmrc::parse(USER_NPMRC);
+
+        assert_eq!(
+            npmrc.registry.as_deref(),
+            Some("https://registry.example.com/")
+        );
+        assert_eq!(
+            npmrc.registry_for("@corp/tool"),
+            Some("https://npm.corp.example.com/")
+        );
+        assert_eq!(
+            npmrc.auth_token_for("https://npm.corp.example.com/@corp/tool"),
+            Some("corp-token")
+        );
+        assert_eq!(
+            npmrc.proxy.as_deref(),
+            Some("http://proxy.example.com:8080")
+        );
+        assert_eq!(npmrc.https_proxy, None);
+    }
+
+    #[test]
+    fn ignores_comments_and_other_keys() {
+        let npmrc = Npmrc::parse("; registry=https://commented.example.com/\nsave-exact=true\n");
+
+        assert_eq!(npmrc.registry, None);
+        assert!(npmrc.auth_tokens.is_empty());
+        assert!(npmrc.scoped_registries.is_empty());
+    }
+
+    #[test]
+    fn project_overrides_user() {
+        let mut npmrc = Npmrc::parse(USER_NPMRC);
+        npmrc.merge(Npmrc::parse(PROJECT_NPMRC));
+
+        assert_eq!(
+            npmrc.registry_for("yarn"),
+            Some("https://project.example.com/npm/")
+        );
+        // Settings that the project doesn't have are kept from the user's file
+        assert_eq!(
+            npmrc.registry_for("@corp/tool"),
+            Some("https://npm.corp.example.com/")
+        );
+        assert_eq!(
+            npmrc.proxy.as_deref(),
+            Some("http://proxy.example.com:8080")
+        );
+        assert_eq!(
+            npmrc.https_proxy.as_deref(),
+            Some("http://secure-proxy.example.com:8443")
+        );
+    }
+
+    /// The proxy for a URL with the given settings and proxy environment variables
+    fn proxy_for(npmrc: &Npmrc, env: &[(&str, &str)], url: &str) -> Option<String> {
+        let settings = npmrc.proxy_settings_with(|name| {
+            env.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        });
+        settings
+            .for_url(&Url::parse(url).unwrap())
+            .map(|proxy| proxy.host_str().unwrap().to_owned())
+    }
+
+    #[test]
+    fn npmrc_proxy_overrides_environment() {
+        let npmrc = Npmrc::parse(USER_NPMRC);
+        let env = [("https_proxy", "http://env-proxy.example.com:3128")];
+
+        assert_eq!(
+            proxy_for(&npmrc, &env, "https://registry.npmjs.org/yarn"),
+            Some("proxy.example.com".into())
+        );
+        assert_eq!(
+            proxy_for(&Npmrc::default(), &env, "https://registry.npmjs.org/yarn"),
+            Some("env-proxy.example.com".into())
+        );
+    }
+
+    #[test]
+    fn npmrc_proxy_keeps_hosts_excluded_from_proxy() {
+        let npmrc = Npmrc::parse(USER_NPMRC);
+        let env = [("no_proxy", "localhost, .corp.example.com")];
+
+        // The hosts in NO_PROXY still bypass the proxy from the .npmrc files
+        assert_eq!(
+            proxy_for(&npmrc, &env, "https://npm.corp.example.com/@corp/tool"),
+            None
+        );
+        assert_eq!(
+            proxy_for(&npmrc, &env, "https://registry.npmjs.org/yarn"),
+            Some("proxy.example.com".into())
+        );
+
+        // The noproxy key takes the place of NO_PROXY
+        let mut npmrc = npmrc;
+        npmrc.merge(Npmrc::parse("noproxy=registry.npmjs.org\n"));
+        assert_eq!(
+            proxy_for(&npmrc, &env, "https://registry.npmjs.org/yarn"),
+            None
+        );
+        assert_eq!(
+            proxy_for(&npmrc, &env, "https://npm.corp.example.com/@corp/tool"),
+            Some("proxy.example.com".into())
+        );
+
+        let env = [("no_proxy", "*")];
+        assert_eq!(
+            proxy_for(
+                &Npmrc::parse(USER_NPMRC),
+                &env,
+                "https://registry.npmjs.org/yarn"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn scoped_registry_only_applies_to_scope() {
+        let npmrc = Npmrc::parse("@corp:registry=https://npm.corp.example.com/\n");
+
+        assert_eq!(
+            npmrc.registry_for("@corp"),
+            Some("https://npm.corp.example.com/")
+        );
+        assert_eq!(npmrc.registry_for("@yarnpkg/cli-dist"), None);
+        assert_eq!(npmrc.registry_for("corp"), None);
+    }
+
+    #[test]
+    fn auth_token_matches_most_specific_registry() {
+        let npmrc = Npmrc::parse(
+            "//example.com/:_authToken=host-token\n//example.com/npm:_authToken=path-token\n",
+        );
+
+        assert_eq!(
+            npmrc.auth_token_for("https://example.com/npm/yarn"),
+            Some("path-token")
+        );
+        assert_eq!(
+            npmrc.auth_token_for("https://example.com/other/yarn"),
+            Some("host-token")
+        );
+        assert_eq!(npmrc.auth_token_for("https://example.org/npm/yarn"), None);
+        // The token for one host is never sent to another whose name starts the same way
+        assert_eq!(
+            npmrc.auth_token_for("https://example.com.evil.test/npm/yarn"),
+            None
+        );
+    }
+
+    #[test]
+    fn parses_prefix() {
+        let home = Path::new("/home/user");
+
+        let npmrc = Npmrc::parse("prefix=/opt/npm\n");
+        assert_eq!(npmrc.prefix.as_deref(), Some("/opt/npm"));
+
+        let npmrc = Npmrc::parse("registry=https://registry.npmjs.org/\nprefix = ~/.npm-global\n");
+        assert_eq!(
+            expand_home(npmrc.prefix.as_deref().unwrap(), Some(home)),
+            Some(PathBuf::from("/home/user/.npm-global"))
+        );
+
+        for contents in [
+            "",
+            "# prefix=/opt/npm\n",
+            "globalprefix=/opt/npm\n",
+            "prefix=\n",
+        ] {
+            assert_eq!(Npmrc::parse(contents).prefix, None);
+        }
+    }
+
+    #[test]
+    fn project_prefix_overrides_global() {
+        let mut npmrc = Npmrc::parse("prefix=/usr/local\n");
+        npmrc.merge(Npmrc::parse(USER_NPMRC));
+        assert_eq!(npmrc.prefix.as_deref(), Some("/usr/local"));
+
+        npmrc.merge(Npmrc::parse("prefix=\"/opt/project\"\n"));
+        assert_eq!(npmrc.prefix.as_deref(), Some("/opt/project"));
+    }
+
+    #[test]
+    fn expands_environment_variables() {
+        env::set_var("VOLTA_TEST_NPMRC_TOKEN", "secret");
+
+        assert_eq!(expand_env("${VOLTA_TEST_NPMRC_TOKEN}"), "secret");
+        assert_eq!(
+            expand_env("prefix-${VOLTA_TEST_NPMRC_TOKEN}-suffix"),
+            "prefix-secret-suffix"
+        );
+        assert_eq!(
+            expand_env("${VOLTA_TEST_NPMRC_UNSET}"),
+            "${VOLTA_TEST_NPMRC_UNSET}"
+        );
+        assert_eq!(expand_env("${unterminated"), "${unterminated");
+    }
+}
@@ -0,0 +1,267 @@
+//! Provides support for installing tools without network access, either from a directory of
+//! archives that were downloaded ahead of time (`volta install --offline-from`), or from the
+//! versions that have already been fetched (`--offline` and `VOLTA_OFFLINE`)
+
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::fs::{copy, read_to_string, write};
+use std::path::{Path, PathBuf};
+
+use super::checksum::{checksum_file, find_checksum, sha256_file};
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::create_staging_file;
+use crate::session::Session;
+use crate::style::tool_version;
+use crate::version::{VersionSpec, VersionTag};
+use fs_utils::ensure_containing_dir_exists;
+use log::debug;
+use node_semver::Version;
+
+/// The checksum file that may list the archives in the directory, as published with the Node distros
+const SHASUMS_FILE_NAME: &str = "SHASUMS256.txt";
+
+/// Find the archive for a tool in the directory of archives
+///
+/// If the directory has a checksum file for the archive, either `<archive>.sha256` or an entry in
+/// `SHASUMS256.txt`, the archive must match it.
+pub(crate) fn find_archive<T: Display>(tool: T, dir: &Path, file_name: &str) -> Fallible<PathBuf> {
+    let archive = dir.join(file_name);
+    if !archive.is_file() {
+        return Err(ErrorKind::OfflineArchiveNotFound {
+            tool: tool.to_string(),
+            dir: dir.to_owned(),
+            file: file_name.into(),
+        }
+        .into());
+    }
+
+    if let Some(expected) = expected_checksum(dir, file_name) {
+        let actual = sha256_file(&archive).with_context(|| ErrorKind::OfflineArchiveReadError {
+            file: archive.clone(),
+        })?;
+
+        if actual != expected {
+            return Err(ErrorKind::OfflineArchiveChecksumMismatch {
+                file: archive,
+                expected,
+                actual,
+            }
+            .into());
+        }
+        debug!(
+            "Verified the checksum of '{}' ({})",
+            archive.display(),
+            actual
+        );
+    }
+
+    Ok(archive)
+}
+
+/// Copy the archive for a tool into the inventory, if tools are being installed from a directory
+/// of archives, so that it is unpacked from there instead of being downloaded
+///
+/// The checksum of the copy is recorded next to it, so that it can be verified when it's loaded.
+pub(crate) fn stage_archive<T: Display>(
+    tool: T,
+    session: &Session,
+    file_name: &str,
+    inventory_dir: &Path,
+) -> Fallible<()> {
+    let Some(dir) = session.offline_archives() else {
+        return Ok(());
+    };
+
+    let archive = find_archive(&tool, dir, file_name)?;
+    let dest = inventory_dir.join(file_name);
+    debug!(
+        "Copying {} from '{}' into '{}'",
+        tool,
+        archive.display(),
+        dest.display()
+    );
+
+    let staging = create_staging_file()?;
+    copy(&archive, staging.path()).with_context(|| ErrorKind::OfflineArchiveReadError {
+        file: archive.clone(),
+    })?;
+    let checksum =
+        sha256_file(staging.path()).with_context(|| ErrorKind::OfflineArchiveReadError {
+            file: archive.clone(),
+        })?;
+
+    ensure_containing_dir_exists(&dest)
+        .with_context(|| ErrorKind::ContainingDirError { path: dest.clone() })?;
+    staging
+        .persist(&dest)
+        .with_context(|| ErrorKind::PersistInventoryError {
+            tool: tool.to_string(),
+        })?;
+    write(checksum_file(&dest), checksum).with_context(|| ErrorKind::PersistInventoryError {
+        tool: tool.to_string(),
+    })?;
+
+    Ok(())
+}
+
+/// Resolve a version requirement against the versions of a tool that have been fetched, for
+/// offline mode
+///
+/// Exact versions don't need to be resolved, so they are returned as they are and are checked
+/// when the tool is fetched. Tags other than `latest` can only be looked up in the tool's index,
+/// so they never match.
+pub(crate) fn resolve_fetched(
+    tool: &str,
+    matching: &VersionSpec,
+    fetched: &BTreeSet<Version>,
+) -> Fallible<Version> {
+    let found = match matching {
+        VersionSpec::Exact(version) => Some(version.clone()),
+        VersionSpec::Semver(range) => fetched
+            .iter()
+            .rev()
+            .find(|version| range.satisfies(version))
+            .cloned(),
+        VersionSpec::None | VersionSpec::Tag(VersionTag::Latest) => fetched.last().cloned(),
+        VersionSpec::Tag(_) => None,
+    };
+
+    match found {
+        Some(version) => {
+            debug!(
+                "Found {}@{} matching '{}' without network access",
+                tool, version, matching
+            );
+            Ok(version)
+        }
+        None if matches!(matching, VersionSpec::None) => {
+            Err(version_not_available(tool, VersionTag::Latest, fetched).into())
+        }
+        None => Err(version_not_available(tool, matching, fetched).into()),
+    }
+}
+
+/// Resolve a version requirement against the fetched versions of a tool when automatic installs
+/// are disabled, as only `volta fetch` and `volta install` may download the tool's index then
+///
+/// A requirement that none of the fetched versions match fails in the same way as fetching a
+/// missing tool does.
+pub(crate) fn resolve_installed<F>(
+    tool: &str,
+    matching: VersionSpec,
+    resolve: F,
+) -> Fallible<Version>
+where
+    F: FnOnce(VersionSpec) -> Fallible<Version>,
+{
+    // Ranges are shown with spaces, so quote them to keep the suggested command valid
+    let requested = match &matching {
+        VersionSpec::None => tool.to_string(),
+        VersionSpec::Semver(range) => format!("\"{}\"", tool_version(tool, range)),
+        matching => tool_version(tool, matching),
+    };
+
+    resolve(matching).map_err(|error| match error.kind() {
+        ErrorKind::OfflineVersionNotAvailable { .. } => ErrorKind::ToolsNotInstalled {
+            tools: vec![requested],
+        }
+        .into(),
+        _ => error,
+    })
+}
+
+/// Check that a tool which isn't available locally may be downloaded, which in offline mode is
+/// only the case when it is installed from a directory of archives
+pub(crate) fn check_may_download<F>(
+    tool: &str,
+    version: &Version,
+    session: &Session,
+    fetched: F,
+) -> Fallible<()>
+where
+    F: FnOnce() -> Fallible<BTreeSet<Version>>,
+{
+    if session.is_offline() && session.offline_archives().is_none() {
+        return Err(version_not_available(tool, version, &fetched()?).into());
+    }
+
+    Ok(())
+}
+
+/// The error for a requirement that none of the versions of a tool available locally match
+pub(crate) fn version_not_available<V: Display>(
+    tool: &str,
+    matching: V,
+    available: &BTreeSet<Version>,
+) -> ErrorKind {
+    ErrorKind::OfflineVersionNotAvailable {
+        tool: tool.into(),
+        matching: matching.to_string(),
+        available: available.iter().map(ToString::to_string).collect(),
+    }
+}
+
+/// Read the checksum for an archive from its checksum file in the directory, if it has one
+fn expected_checksum(dir: &Path, file_name: &str) -> Option<String> {
+    let sidecar = read_to_string(dir.join(format!("{}.sha256", file_name)))
+        .ok()
+        .and_then(|content| content.split_whitespace().next().map(str::to_owned));
+
+    sidecar
+        .or_else(|| {
+            let shasums = read_to_string(dir.join(SHASUMS_FILE_NAME)).ok()?;
+            find_checksum(&shasums, file_name).map(str::to_owned)
+        })
+        .map(|checksum| checksum.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn reads_checksum_from_sidecar_or_shasums() {
+        let dir = tempdir().unwrap();
+        write(
+            dir.path().join(SHASUMS_FILE_NAME),
+            "0A1B  yarn-v1.22.19.tar.gz\n2c3d  npm-9.6.7.tgz\n",
+        )
+        .unwrap();
+        write(dir.path().join("npm-9.6.7.tgz.sha256"), "4e5f\n").unwrap();
+
+        assert_eq!(
+            expected_checksum(dir.path(), "yarn-v1.22.19.tar.gz"),
+            Some("0a1b".into())
+        );
+        assert_eq!(
+            expected_checksum(dir.path(), "npm-9.6.7.tgz"),
+            Some("4e5f".into())
+        );
+        assert_eq!(expected_checksum(dir.path(), "pnpm-8.6.0.tgz"), None);
+    }
+
+    #[test]
+    fn resolves_fetched_versions() {
+        let fetched: BTreeSet<Version> = ["1.22.4", "1.22.19", "3.6.4"]
+            .iter()
+            .map(|version| version.parse().unwrap())
+            .collect();
+        let resolve = |matching: &str| {
+            let matching: VersionSpec = matching.parse().unwrap();
+            resolve_fetched("yarn", &matching, &fetched).map(|version| version.to_string())
+        };
+
+        assert_eq!(resolve("^1").unwrap(), "1.22.19");
+        assert_eq!(resolve("1.22.4").unwrap(), "1.22.4");
+        assert_eq!(resolve("latest").unwrap(), "3.6.4");
+        // Exact versions are only checked when they are fetched
+        assert_eq!(resolve("2.4.3").unwrap(), "2.4.3");
+
+        let error = resolve("^2").unwrap_err().to_string();
+        assert!(error.starts_with("Could not find a version of yarn matching"));
+        assert!(error.contains("Available locally: 1.22.4, 1.22.19, 3.6.4"));
+        assert!(resolve("berry").is_err());
+    }
+}
@@ -1,33 +1,170 @@
-use std::path::PathBuf;
+#[cfg(unix)]
+use std::fs;
+#[cfg(unix)]
+use std::io;
+use std::path::{Path, PathBuf};
 
 use super::manager::PackageManager;
-use super::metadata::{BinConfig, PackageConfig, PackageManifest};
+use super::metadata::{bin_paths, BinConfig, PackageConfig, PackageManifest};
+use crate::change::{Change, Subject};
 use crate::error::{ErrorKind, Fallible};
+use crate::fs::remove_file_if_exists;
 use crate::layout::volta_home;
 use crate::platform::{Image, PlatformSpec};
 use crate::shim;
 use crate::tool::check_shim_reachable;
+use log::{debug, warn};
 
 /// Read the manifest for the package being installed
+///
+/// This must only be called once the package manager has exited, as postinstall scripts may still
+/// rewrite the manifest and the files its bins point to. Bins that would shadow Volta's own
+/// executables, or whose files don't exist once the install is done, are dropped from the
+/// manifest, so that the rest of the package can still be installed.
 pub(super) fn parse_manifest(
     package_name: &str,
     staging_dir: PathBuf,
     manager: PackageManager,
 ) -> Fallible<PackageManifest> {
-    let mut package_dir = manager.source_dir(staging_dir);
+    let bin_dir = manager.binary_dir(staging_dir.clone());
+    let mut package_dir = manager.source_dir(staging_dir.clone());
     package_dir.push(package_name);
 
-    PackageManifest::for_dir(package_name, &package_dir)
+    let mut manifest = PackageManifest::for_dir(package_name, &package_dir)?;
+    let (skipped, shimmed): (Vec<_>, Vec<_>) = manifest
+        .bin
+        .drain(..)
+        .partition(|bin_name| shim::is_reserved(bin_name));
+
+    if !skipped.is_empty() {
+        warn!(
+            "Package '{}' provides executables that would shadow Volta's own commands, so they were skipped: {}",
+            package_name,
+            skipped.join(", ")
+        );
+        if !shimmed.is_empty() {
+            warn!(
+                "The other executables were installed: {}",
+                shimmed.join(", ")
+            );
+        }
+    }
+
+    manifest.bin = shimmed;
+    verify_bins(&mut manifest, &package_dir, &bin_dir, &staging_dir);
+    Ok(manifest)
+}
+
+/// Check that each bin of the package points to a file that exists
+///
+/// Some packages swap their bin from a stub to the real script in a postinstall, after the package
+/// manager has linked the stub. If the link no longer resolves but the manifest now points to a
+/// file that exists, the link is recreated to point there. Bins that still don't resolve are
+/// skipped with a warning, as their shims would fail to run.
+fn verify_bins(manifest: &mut PackageManifest, package_dir: &Path, bin_dir: &Path, root: &Path) {
+    let paths = bin_paths(package_dir);
+
+    manifest.bin.retain(|bin_name| {
+        let target = paths.get(bin_name).map(|path| package_dir.join(path));
+        if let Some(target) = target.as_ref().filter(|target| !target.is_file()) {
+            warn!(
+                "Skipping executable '{}' of package '{}', as the file it points to doesn't exist after the install: {}",
+                bin_name,
+                manifest.name,
+                target.display()
+            );
+            return false;
+        }
+
+        if !is_linked(&bin_dir.join(bin_name), target.as_deref(), root) {
+            warn!(
+                "Skipping executable '{}' of package '{}', as it could not be linked to its file",
+                bin_name, manifest.name
+            );
+            return false;
+        }
+
+        true
+    });
+}
+
+/// Check that the link for a bin resolves, recreating it to point at the bin's file if it doesn't
+///
+/// Bins whose file isn't known (such as those from `directories.bin`) are left as they are.
+#[cfg(unix)]
+fn is_linked(link: &Path, target: Option<&Path>, root: &Path) -> bool {
+    link.exists() || target.map_or(true, |target| relink_bin(link, target, root).is_ok())
+}
+
+/// On Windows, the package managers write scripts that run the bins rather than linking to them,
+/// so there is no link to repair
+#[cfg(windows)]
+fn is_linked(_link: &Path, _target: Option<&Path>, _root: &Path) -> bool {
+    true
+}
+
+/// Point the link for a bin at its file, relative to the link so that it can still be found once
+/// the install is moved out of the staging directory
+#[cfg(unix)]
+fn relink_bin(link: &Path, target: &Path, root: &Path) -> io::Result<()> {
+    use std::iter::{once, repeat};
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    let outside_root = || io::Error::from(io::ErrorKind::InvalidInput);
+    let depth = link
+        .parent()
+        .and_then(|dir| dir.strip_prefix(root).ok())
+        .ok_or_else(outside_root)?
+        .components()
+        .count();
+    let relative: PathBuf = repeat(Path::new(".."))
+        .take(depth)
+        .chain(once(target.strip_prefix(root).map_err(|_| outside_root())?))
+        .collect();
+
+    debug!(
+        "Relinking executable {} to {}",
+        link.display(),
+        relative.display()
+    );
+    match fs::remove_file(link) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+        _ => {}
+    }
+    if let Some(dir) = link.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    symlink(&relative, link)?;
+
+    // The package manager makes the files it links executable, so do the same
+    let mut permissions = fs::metadata(target)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o111);
+    fs::set_permissions(target, permissions)
 }
 
 /// Generate configuration files and shims for the package and each of its bins
+///
+/// If the package was requested by a registry dist-tag, the tag is recorded in the package config
+/// alongside the resolved version. Likewise, packages installed from a local directory record the
+/// directory they came from.
+///
+/// Without `shims`, the package config lists the bins but no shims or bin configs are written for
+/// them, so that they don't shadow the versions installed in projects. Any shims that a previous
+/// install of the package created are removed.
+///
+/// Returns the changes to the shims and config files, in the order they were made.
 pub(super) fn write_config_and_shims(
     name: &str,
     manifest: &PackageManifest,
     image: &Image,
     manager: PackageManager,
-) -> Fallible<()> {
-    validate_bins(name, manifest)?;
+    tag: Option<String>,
+    source: Option<String>,
+    shims: bool,
+) -> Fallible<Vec<Change>> {
+    let shimmed: &[String] = if shims { &manifest.bin } else { &[] };
+    validate_bins(name, shimmed)?;
+    let mut changes = remove_stale_bins(name, shimmed)?;
 
     let platform = PlatformSpec {
         node: image.node.value.clone(),
@@ -37,11 +174,11 @@ pub(super) fn write_config_and_shims(
     };
 
     // Generate the shims and bin configs for each bin provided by the package
-    for bin_name in &manifest.bin {
-        shim::create(bin_name)?;
+    for bin_name in shimmed {
+        changes.extend(shim::create(bin_name)?.change());
         check_shim_reachable(bin_name);
 
-        BinConfig {
+        let change = BinConfig {
             name: bin_name.clone(),
             package: name.into(),
             version: manifest.version.clone(),
@@ -49,26 +186,31 @@ pub(super) fn write_config_and_shims(
             manager,
         }
         .write()?;
+        changes.push(change);
     }
 
     // Write the config for the package
-    PackageConfig {
+    let change = PackageConfig {
         name: name.into(),
         version: manifest.version.clone(),
         platform,
         bins: manifest.bin.clone(),
         manager,
+        tag,
+        source,
+        shims,
     }
     .write()?;
+    changes.push(change);
 
-    Ok(())
+    Ok(changes)
 }
 
 /// Validate that we aren't attempting to install a bin that is already installed by
 /// another package.
-fn validate_bins(package_name: &str, manifest: &PackageManifest) -> Fallible<()> {
+fn validate_bins(package_name: &str, bins: &[String]) -> Fallible<()> {
     let home = volta_home()?;
-    for bin_name in &manifest.bin {
+    for bin_name in bins {
         // Check for name conflicts with already-installed bins
         // Some packages may install bins with the same name
         if let Ok(config) = BinConfig::from_file(home.default_tool_bin_config(bin_name)) {
@@ -87,3 +229,31 @@ fn validate_bins(package_name: &str, manifest: &PackageManifest) -> Fallible<()>
 
     Ok(())
 }
+
+/// Remove the shims and bin configs for any bins that a previous install of the package provided,
+/// but which the package being installed no longer shims.
+fn remove_stale_bins(package_name: &str, bins: &[String]) -> Fallible<Vec<Change>> {
+    let home = volta_home()?;
+    let Ok(Some(previous)) =
+        PackageConfig::from_file_if_exists(home.default_package_config_file(package_name))
+    else {
+        return Ok(Vec::new());
+    };
+
+    // Without shims, the bins may since have been installed by another package
+    if !previous.shims {
+        return Ok(Vec::new());
+    }
+
+    let mut changes = Vec::new();
+    for bin_name in previous.bins.iter().filter(|bin| !bins.contains(bin)) {
+        let bin_config = home.default_tool_bin_config(bin_name);
+        if bin_config.exists() {
+            remove_file_if_exists(&bin_config)?;
+            changes.push(Change::removed(Subject::Config, bin_config));
+        }
+        changes.extend(shim::delete(bin_name)?.change());
+    }
+
+    Ok(changes)
+}
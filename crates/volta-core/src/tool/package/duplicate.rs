@@ -0,0 +1,220 @@
+//! Finds copies of a package that were installed globally with plain npm, outside of Volta
+//!
+//! Such a copy (e.g. in the Node image, or in a prefix configured in `.npmrc`) stays on the PATH
+//! alongside the one installed by Volta, so which of them runs depends on the order of the PATH.
+//!
+//! The Node image is shared by every tool that runs with that version of Node, so copies in it
+//! are only ever reported, never removed, and the packages that ship with Node are ignored.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::manager::PackageManager;
+use super::metadata::PackageManifest;
+use crate::error::Fallible;
+use crate::fs::{remove_dir_if_exists, remove_file_if_exists};
+use crate::platform::Image;
+use crate::style::{success_prefix, tool_version};
+use crate::tool::{npmrc, Node};
+use log::{debug, info, warn};
+
+/// The packages that are part of the Node distribution, rather than installed with npm
+const BUNDLED_WITH_NODE: [&str; 2] = ["npm", "corepack"];
+
+/// A copy of a package in an npm global prefix
+struct Duplicate {
+    prefix: PathBuf,
+    dir: PathBuf,
+    manifest: PackageManifest,
+}
+
+/// Reports copies of a newly installed package that were installed globally with npm, or removes
+/// them when `take_over` is set
+///
+/// The prefixes that don't exist are skipped, so this is cheap when there are no duplicates.
+pub(super) fn check_npm_duplicates(name: &str, image: &Image, take_over: bool) -> Fallible<()> {
+    let node_prefix = Node::image_dir(&image.node.value)?;
+
+    for prefix in npm_prefixes(&node_prefix) {
+        let in_node_image = prefix.starts_with(&node_prefix);
+        if in_node_image && BUNDLED_WITH_NODE.contains(&name) {
+            continue;
+        }
+
+        let Some(duplicate) = find_duplicate(name, prefix) else {
+            continue;
+        };
+
+        if take_over && !in_node_image {
+            duplicate.remove()?;
+            info!(
+                "{} removed {} that was installed with npm in {}",
+                success_prefix(),
+                tool_version(&duplicate.manifest.name, &duplicate.manifest.version),
+                duplicate.dir.display()
+            );
+        } else {
+            let removal = if in_node_image {
+                "It is in a Node image managed by Volta, so it isn't removed automatically."
+                    .to_owned()
+            } else {
+                format!(
+                    "Or run `volta install --take-over {}` to remove it automatically.",
+                    name
+                )
+            };
+
+            warn!(
+                "{} is also installed globally with npm, in:
+    {}
+
+Which copy runs depends on the order of your PATH. To remove the copy installed with npm, run:
+    npm uninstall --global --prefix {} {}
+
+{}",
+                tool_version(&duplicate.manifest.name, &duplicate.manifest.version),
+                duplicate.dir.display(),
+                duplicate.prefix.display(),
+                name,
+                removal
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The global prefixes that npm may have installed packages into before Volta managed them
+fn npm_prefixes(node_prefix: &Path) -> Vec<PathBuf> {
+    let mut prefixes = vec![node_prefix.to_owned()];
+
+    if let Some(prefix) = npmrc::prefix(node_prefix) {
+        if !prefixes.contains(&prefix) {
+            prefixes.push(prefix);
+        }
+    }
+
+    prefixes
+}
+
+/// Finds the package in the given prefix, ignoring any failure to read it
+fn find_duplicate(name: &str, prefix: PathBuf) -> Option<Duplicate> {
+    let dir = PackageManager::Npm.source_dir(prefix.clone()).join(name);
+    if !dir.join("package.json").is_file() {
+        return None;
+    }
+
+    match PackageManifest::for_dir(name, &dir) {
+        Ok(manifest) => Some(Duplicate {
+            prefix,
+            dir,
+            manifest,
+        }),
+        Err(error) => {
+            debug!(
+                "Could not read the copy of {} in '{}': {}",
+                name,
+                dir.display(),
+                error
+            );
+            None
+        }
+    }
+}
+
+impl Duplicate {
+    /// Removes the package and the links to its executables, as `npm uninstall --global` would
+    fn remove(&self) -> Fallible<()> {
+        let bin_dir = PackageManager::Npm.binary_dir(self.prefix.clone());
+        for bin in &self.manifest.bin {
+            for link in bin_links(&bin_dir, bin) {
+                if links_into(&link, &self.dir) {
+                    remove_file_if_exists(&link)?;
+                }
+            }
+        }
+
+        remove_dir_if_exists(&self.dir)
+    }
+}
+
+/// The files that npm creates in the bin directory for an executable
+#[cfg(unix)]
+fn bin_links(bin_dir: &Path, bin: &str) -> Vec<PathBuf> {
+    vec![bin_dir.join(bin)]
+}
+
+/// The files that npm creates in the bin directory for an executable
+#[cfg(windows)]
+fn bin_links(bin_dir: &Path, bin: &str) -> Vec<PathBuf> {
+    vec![
+        bin_dir.join(bin),
+        bin_dir.join(format!("{}.cmd", bin)),
+        bin_dir.join(format!("{}.ps1", bin)),
+    ]
+}
+
+/// Determines whether a link in the bin directory points into the package, so that links
+/// belonging to other packages are never removed
+#[cfg(unix)]
+fn links_into(link: &Path, package_dir: &Path) -> bool {
+    match (fs::canonicalize(link), fs::canonicalize(package_dir)) {
+        (Ok(target), Ok(package_dir)) => target.starts_with(package_dir),
+        _ => false,
+    }
+}
+
+/// Determines whether a link in the bin directory points into the package, so that links
+/// belonging to other packages are never removed
+///
+/// On Windows, npm creates scripts rather than symlinks, which contain the path to the package.
+#[cfg(windows)]
+fn links_into(link: &Path, package_dir: &Path) -> bool {
+    let Some(name) = package_dir.file_name() else {
+        return false;
+    };
+    let marker = format!("node_modules\\{}\\", name.to_string_lossy());
+
+    fs::read_to_string(link).is_ok_and(|script| script.contains(&marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn removes_only_links_into_package() {
+        use std::os::unix::fs::symlink;
+
+        let prefix = tempfile::tempdir().unwrap();
+        let modules = prefix.path().join("lib").join("node_modules");
+        let bin_dir = prefix.path().join("bin");
+        fs::create_dir_all(modules.join("cowsay")).unwrap();
+        fs::create_dir_all(modules.join("other")).unwrap();
+        fs::create_dir_all(&bin_dir).unwrap();
+        fs::write(
+            modules.join("cowsay").join("package.json"),
+            r#"{ "name": "cowsay", "version": "1.5.0", "bin": { "cowsay": "cli.js", "cowthink": "cli.js" } }"#,
+        )
+        .unwrap();
+        fs::write(modules.join("cowsay").join("cli.js"), "").unwrap();
+        fs::write(modules.join("other").join("cli.js"), "").unwrap();
+        symlink("../lib/node_modules/cowsay/cli.js", bin_dir.join("cowsay")).unwrap();
+        symlink("../lib/node_modules/other/cli.js", bin_dir.join("cowthink")).unwrap();
+
+        let duplicate = find_duplicate("cowsay", prefix.path().to_owned()).unwrap();
+        assert_eq!(duplicate.manifest.version.to_string(), "1.5.0");
+        duplicate.remove().unwrap();
+
+        assert!(!modules.join("cowsay").exists());
+        assert!(fs::symlink_metadata(bin_dir.join("cowsay")).is_err());
+        assert!(bin_dir.join("cowthink").exists());
+    }
+
+    #[test]
+    fn missing_prefix_has_no_duplicate() {
+        let prefix = tempfile::tempdir().unwrap();
+        assert!(find_duplicate("cowsay", prefix.path().join("missing")).is_none());
+    }
+}
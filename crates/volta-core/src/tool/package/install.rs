@@ -7,6 +7,14 @@ use crate::platform::Image;
 use crate::style::progress_spinner;
 use log::debug;
 
+/// Where npm installs a package from
+pub(super) enum Source<'a> {
+    /// The registry, which replaces the one npm would otherwise use if one is given
+    Registry(Option<&'a str>),
+    /// A local archive, which npm installs without network access
+    Offline,
+}
+
 /// Use `npm install --global` to install the package
 ///
 /// Sets the environment variable `npm_config_prefix` to redirect the install to the Volta
@@ -16,6 +24,7 @@ pub(super) fn run_global_install(
     package: String,
     staging_dir: PathBuf,
     platform_image: &Image,
+    source: Source,
 ) -> Fallible<()> {
     let mut command = create_command("npm");
     command.args([
@@ -25,6 +34,15 @@ pub(super) fn run_global_install(
         "--no-update-notifier",
         "--no-audit",
     ]);
+    match source {
+        Source::Registry(Some(registry)) => {
+            command.args(["--registry", registry]);
+        }
+        Source::Registry(None) => {}
+        Source::Offline => {
+            command.arg("--offline");
+        }
+    }
     command.arg(&package);
     command.env("PATH", platform_image.path()?);
     PackageManager::Npm.setup_global_command(&mut command, staging_dir);
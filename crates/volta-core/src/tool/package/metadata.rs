@@ -1,15 +1,18 @@
 use std::collections::HashMap;
 use std::fs::File;
 use std::io;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
 use super::manager::PackageManager;
+use crate::change::{self, Change, Subject};
 use crate::error::{Context, ErrorKind, Fallible, VoltaError};
 use crate::layout::volta_home;
 use crate::platform::PlatformSpec;
 use crate::version::{option_version_serde, version_serde};
 use fs_utils::ensure_containing_dir_exists;
+use log::warn;
 use node_semver::Version;
+use walkdir::WalkDir;
 
 /// Configuration information about an installed package
 ///
@@ -28,6 +31,25 @@ pub struct PackageConfig {
     pub bins: Vec<String>,
     /// The package manager that was used to install this package
     pub manager: PackageManager,
+    /// The registry dist-tag that was requested when installing this package, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// The local path or git URL this package was installed from, for installs that don't come
+    /// from the registry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Whether shims and bin configs were created for the binaries, which `volta install
+    /// --no-shims` skips
+    #[serde(default = "with_shims", skip_serializing_if = "has_shims")]
+    pub shims: bool,
+}
+
+fn with_shims() -> bool {
+    true
+}
+
+fn has_shims(shims: &bool) -> bool {
+    *shims
 }
 
 impl PackageConfig {
@@ -66,8 +88,9 @@ impl PackageConfig {
     }
 
     /// Write this `PackageConfig` into the appropriate config file
-    pub fn write(self) -> Fallible<()> {
+    pub fn write(self) -> Fallible<Change> {
         let config_file_path = volta_home()?.default_package_config_file(&self.name);
+        let existed = change::exists(&config_file_path);
 
         ensure_containing_dir_exists(&config_file_path).with_context(|| {
             ErrorKind::ContainingDirError {
@@ -77,11 +100,13 @@ impl PackageConfig {
 
         let file = File::create(&config_file_path).with_context(|| {
             ErrorKind::WritePackageConfigError {
-                file: config_file_path,
+                file: config_file_path.clone(),
             }
         })?;
         serde_json::to_writer_pretty(file, &self)
-            .with_context(|| ErrorKind::StringifyPackageConfigError)
+            .with_context(|| ErrorKind::StringifyPackageConfigError)?;
+
+        Ok(Change::written(Subject::Config, config_file_path, existed))
     }
 }
 
@@ -140,8 +165,9 @@ impl BinConfig {
     }
 
     /// Write this `BinConfig` to the appropriate config file
-    pub fn write(self) -> Fallible<()> {
+    pub fn write(self) -> Fallible<Change> {
         let config_file_path = volta_home()?.default_tool_bin_config(&self.name);
+        let existed = change::exists(&config_file_path);
 
         ensure_containing_dir_exists(&config_file_path).with_context(|| {
             ErrorKind::ContainingDirError {
@@ -151,10 +177,12 @@ impl BinConfig {
 
         let file =
             File::create(&config_file_path).with_context(|| ErrorKind::WriteBinConfigError {
-                file: config_file_path,
+                file: config_file_path.clone(),
             })?;
         serde_json::to_writer_pretty(file, &self)
-            .with_context(|| ErrorKind::StringifyBinConfigError)
+            .with_context(|| ErrorKind::StringifyBinConfigError)?;
+
+        Ok(Change::written(Subject::Config, config_file_path, existed))
     }
 }
 
@@ -190,6 +218,14 @@ pub struct PackageManifest {
     /// The `bin` section, containing a map of binary names to locations
     #[serde(default, deserialize_with = "serde_bins::deserialize")]
     pub bin: Vec<String>,
+    /// The `directories` section, which may point to a directory of binaries
+    #[serde(default)]
+    directories: ManifestDirectories,
+}
+
+#[derive(serde::Deserialize, Default)]
+struct ManifestDirectories {
+    bin: Option<String>,
 }
 
 impl PackageManifest {
@@ -216,10 +252,79 @@ impl PackageManifest {
             manifest.bin.push(default_binary_name(&manifest.name));
         }
 
+        // Without a `bin` field, npm treats every file in `directories.bin` as a binary
+        if manifest.bin.is_empty() {
+            if let Some(bin_dir) = &manifest.directories.bin {
+                manifest.bin = directory_bins(&manifest.name, package_root, bin_dir);
+            }
+        }
+
         Ok(manifest)
     }
 }
 
+/// List the binaries in a package's `directories.bin` directory, named after each file
+fn directory_bins(package: &str, package_root: &Path, bin_dir: &str) -> Vec<String> {
+    // The directory has to be within the package, to match the behavior of npm
+    let inside_package = Path::new(bin_dir)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !inside_package {
+        warn!(
+            "Ignoring `directories.bin` for {}, as '{}' is outside of the package",
+            package, bin_dir
+        );
+        return Vec::new();
+    }
+
+    let mut bins: Vec<String> = WalkDir::new(package_root.join(bin_dir))
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(Result::ok)
+        .filter(|entry| !entry.file_type().is_dir())
+        .filter_map(|entry| entry.file_name().to_str().map(String::from))
+        .collect();
+
+    bins.sort();
+    bins.dedup();
+    bins
+}
+
+/// Read the files that a package's bins point to, relative to the package root
+///
+/// The paths aren't part of the `PackageManifest`, as they are only needed to check the bins once
+/// the package manager has finished installing the package. Bins from `directories.bin` are
+/// found by listing the directory, so they aren't included.
+pub(super) fn bin_paths(package_root: &Path) -> HashMap<String, PathBuf> {
+    #[derive(serde::Deserialize)]
+    struct RawBins {
+        #[serde(default)]
+        name: String,
+        #[serde(default)]
+        bin: serde_json::Value,
+    }
+
+    let raw: Option<RawBins> = File::open(package_root.join("package.json"))
+        .ok()
+        .and_then(|file| serde_json::de::from_reader(file).ok());
+
+    match raw {
+        Some(RawBins {
+            name,
+            bin: serde_json::Value::String(path),
+        }) => HashMap::from([(default_binary_name(&name), PathBuf::from(path))]),
+        Some(RawBins {
+            bin: serde_json::Value::Object(entries),
+            ..
+        }) => entries
+            .into_iter()
+            .filter_map(|(name, path)| Some((name, PathBuf::from(path.as_str()?))))
+            .collect(),
+        _ => HashMap::new(),
+    }
+}
+
 #[derive(serde::Deserialize)]
 /// Struct to read the `dependencies` out of Yarn's global manifest.
 ///
@@ -233,7 +338,9 @@ pub(super) struct GlobalYarnManifest {
 mod serde_bins {
     use std::fmt;
 
+    use log::warn;
     use serde::de::{Deserializer, Error, MapAccess, Visitor};
+    use serde_json::Value;
 
     pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
     where
@@ -267,7 +374,17 @@ mod serde_bins {
             M: MapAccess<'de>,
         {
             let mut bins = Vec::new();
-            while let Some((name, _)) = access.next_entry::<String, String>()? {
+            while let Some((name, path)) = access.next_entry::<String, Value>()? {
+                // Entries that aren't a path (such as objects rewritten per-platform during a
+                // postinstall script) can't be linked, so skip them rather than failing the install
+                if !path.is_string() {
+                    warn!(
+                        "Skipping executable '{}', as its `bin` entry is not a path",
+                        name
+                    );
+                    continue;
+                }
+
                 // Bin names that include path separators are invalid, as they would then point to
                 // other locations on the filesystem. To match the behavior of npm & Yarn, we
                 // filter those values out of the list of bins.
@@ -309,7 +426,161 @@ fn default_binary_name(package_name: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::default_binary_name;
+    use super::{bin_paths, default_binary_name, PackageConfig, PackageManager, PackageManifest};
+    use crate::platform::PlatformSpec;
+    use node_semver::Version;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::{tempdir, TempDir};
+
+    fn manifest_dir(manifest: &str) -> TempDir {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("package.json"), manifest).unwrap();
+        dir
+    }
+
+    fn manifest_bins(dir: &TempDir) -> Vec<String> {
+        PackageManifest::for_dir("test", dir.path()).unwrap().bin
+    }
+
+    fn package_config(tag: Option<&str>) -> PackageConfig {
+        PackageConfig {
+            name: "typescript".into(),
+            version: Version::parse("5.4.0-beta").unwrap(),
+            platform: PlatformSpec {
+                node: Version::parse("20.11.1").unwrap(),
+                npm: None,
+                pnpm: None,
+                yarn: None,
+            },
+            bins: vec!["tsc".into(), "tsserver".into()],
+            manager: PackageManager::Npm,
+            tag: tag.map(String::from),
+            source: None,
+            shims: true,
+        }
+    }
+
+    #[test]
+    fn package_config_round_trips_tag() {
+        let config = package_config(Some("beta"));
+        let json = serde_json::to_string(&config).unwrap();
+        let parsed: PackageConfig = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.tag.as_deref(), Some("beta"));
+        assert!(parsed == config);
+    }
+
+    #[test]
+    fn package_config_records_skipped_shims() {
+        let json = serde_json::to_string(&package_config(None)).unwrap();
+        assert!(!json.contains("\"shims\""));
+        let parsed: PackageConfig = serde_json::from_str(&json).unwrap();
+        assert!(parsed.shims);
+
+        let config = PackageConfig {
+            shims: false,
+            ..package_config(None)
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains("\"shims\":false"), "{}", json);
+        let parsed: PackageConfig = serde_json::from_str(&json).unwrap();
+        assert!(!parsed.shims);
+    }
+
+    #[test]
+    fn package_config_omits_missing_tag() {
+        let json = serde_json::to_string(&package_config(None)).unwrap();
+        assert!(!json.contains("\"tag\""));
+
+        let parsed: PackageConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.tag, None);
+    }
+
+    #[test]
+    fn package_config_round_trips_source() {
+        let mut config = package_config(None);
+        config.source = Some("/work/my-cli".into());
+        let json = serde_json::to_string(&config).unwrap();
+        let parsed: PackageConfig = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.source, Some("/work/my-cli".into()));
+
+        let json = serde_json::to_string(&package_config(None)).unwrap();
+        assert!(!json.contains("\"source\""));
+    }
+
+    #[test]
+    fn manifest_bin_string_uses_package_name() {
+        let dir = manifest_dir(r#"{ "name": "@scope/tool", "version": "1.0.0", "bin": "cli.js" }"#);
+        assert_eq!(manifest_bins(&dir), ["tool"]);
+    }
+
+    #[test]
+    fn manifest_bin_map_lists_names() {
+        let dir = manifest_dir(
+            r#"{ "name": "tool", "version": "1.0.0", "bin": { "a": "a.js", "b": "b.js", "../c": "c.js" } }"#,
+        );
+        assert_eq!(manifest_bins(&dir), ["a", "b"]);
+    }
+
+    #[test]
+    fn manifest_bin_map_skips_object_values() {
+        let dir = manifest_dir(
+            r#"{ "name": "tool", "version": "1.0.0", "bin": { "a": "a.js", "b": { "win32": "b.cmd" } } }"#,
+        );
+        assert_eq!(manifest_bins(&dir), ["a"]);
+    }
+
+    #[test]
+    fn bin_paths_reads_bin_files() {
+        let dir = manifest_dir(r#"{ "name": "@scope/tool", "version": "1.0.0", "bin": "cli.js" }"#);
+        assert_eq!(
+            bin_paths(dir.path()),
+            [("tool".to_owned(), PathBuf::from("cli.js"))].into()
+        );
+
+        let dir = manifest_dir(
+            r#"{ "name": "tool", "version": "1.0.0", "bin": { "a": "./a.js", "b": { "win32": "b.cmd" } } }"#,
+        );
+        assert_eq!(
+            bin_paths(dir.path()),
+            [("a".to_owned(), PathBuf::from("./a.js"))].into()
+        );
+    }
+
+    #[test]
+    fn manifest_directories_bin_lists_files() {
+        let dir = manifest_dir(
+            r#"{ "name": "tool", "version": "1.0.0", "directories": { "bin": "./scripts" } }"#,
+        );
+        fs::create_dir_all(dir.path().join("scripts").join("nested")).unwrap();
+        fs::write(dir.path().join("scripts").join("zeta"), "").unwrap();
+        fs::write(dir.path().join("scripts").join("alpha.js"), "").unwrap();
+        fs::write(dir.path().join("scripts").join(".hidden"), "").unwrap();
+        fs::write(dir.path().join("scripts").join("nested").join("beta"), "").unwrap();
+
+        assert_eq!(manifest_bins(&dir), ["alpha.js", "beta", "zeta"]);
+    }
+
+    #[test]
+    fn manifest_bin_takes_precedence_over_directories_bin() {
+        let dir = manifest_dir(
+            r#"{ "name": "tool", "version": "1.0.0", "bin": { "a": "a.js" }, "directories": { "bin": "scripts" } }"#,
+        );
+        fs::create_dir_all(dir.path().join("scripts")).unwrap();
+        fs::write(dir.path().join("scripts").join("other"), "").unwrap();
+
+        assert_eq!(manifest_bins(&dir), ["a"]);
+    }
+
+    #[test]
+    fn manifest_directories_bin_outside_package_is_ignored() {
+        let dir = manifest_dir(
+            r#"{ "name": "tool", "version": "1.0.0", "directories": { "bin": "../elsewhere" } }"#,
+        );
+        assert!(manifest_bins(&dir).is_empty());
+    }
 
     #[test]
     fn default_binary_uses_full_name_if_unscoped() {
@@ -1,30 +1,37 @@
+use std::collections::BTreeSet;
 use std::fmt::{self, Display};
 use std::fs::create_dir_all;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use super::Tool;
+use super::registry::{fetch_npm_registry, public_registry_index};
+use super::{offline, Tool};
+use crate::change::{self, Change, Subject};
 use crate::error::{Context, ErrorKind, Fallible};
-use crate::fs::{remove_dir_if_exists, rename, symlink_dir};
+use crate::fs::{copy_dir_all, remove_dir_if_exists, rename};
 use crate::layout::volta_home;
-use crate::platform::{Image, PlatformSpec};
+use crate::platform::{Image, Platform, PlatformSpec};
 use crate::session::Session;
-use crate::style::{success_prefix, tool_version};
+use crate::style::{note_prefix, success_prefix, tool_version};
 use crate::sync::VoltaLock;
 use crate::version::VersionSpec;
-use fs_utils::ensure_containing_dir_exists;
-use log::info;
+use fs_utils::{ensure_containing_dir_exists, long_path};
+use log::{debug, info};
+use node_semver::Version;
 use tempfile::{tempdir_in, TempDir};
 
 mod configure;
+mod duplicate;
 mod install;
 mod manager;
 mod metadata;
+mod relink;
 mod uninstall;
 
 pub use manager::PackageManager;
 pub use metadata::{BinConfig, PackageConfig, PackageManifest};
-pub use uninstall::uninstall;
+pub use relink::{relink_packages, RelinkStatus, RelinkedPackage};
+pub use uninstall::{uninstall, uninstall_dry_run};
 
 /// The Tool implementation for installing 3rd-party global packages
 pub struct Package {
@@ -44,24 +51,88 @@ impl Package {
         })
     }
 
-    pub fn run_install(&self, platform_image: &Image) -> Fallible<()> {
-        install::run_global_install(
-            self.to_string(),
-            self.staging.path().to_owned(),
-            platform_image,
+    /// The name of the archive that `npm pack` creates for a version of a package, such as
+    /// `cowsay-1.4.0.tgz`, or `types-node-20.0.0.tgz` for the scoped package `@types/node`
+    pub fn archive_filename(name: &str, version: &Version) -> String {
+        format!(
+            "{}-{}.tgz",
+            name.trim_start_matches('@').replace('/', "-"),
+            version
         )
     }
 
-    pub fn complete_install(self, image: &Image) -> Fallible<PackageManifest> {
+    pub fn run_install(&self, platform_image: &Image, session: &Session) -> Fallible<()> {
+        match session.offline_archives() {
+            Some(dir) => {
+                let VersionSpec::Exact(version) = &self.version else {
+                    return Err(ErrorKind::OfflineInstallNeedsVersion {
+                        tool: self.to_string(),
+                    }
+                    .into());
+                };
+                let file_name = Package::archive_filename(&self.name, version);
+                let archive = offline::find_archive(self, dir, &file_name)?;
+
+                install::run_global_install(
+                    format!("file:{}", archive.display()),
+                    self.staging.path().to_owned(),
+                    platform_image,
+                    install::Source::Offline,
+                )
+            }
+            None => install::run_global_install(
+                self.to_string(),
+                self.staging.path().to_owned(),
+                platform_image,
+                install::Source::Registry(registry(session)?),
+            ),
+        }
+    }
+
+    /// Checks that a matching version of the package is already installed, since installing one
+    /// needs the registry, which isn't available in offline mode
+    fn check_installed(&self) -> Fallible<()> {
+        let config_file = volta_home()?.default_package_config_file(&self.name);
+        let installed: BTreeSet<Version> = PackageConfig::from_file_if_exists(config_file)?
+            .map(|config| config.version)
+            .into_iter()
+            .collect();
+
+        let version = offline::resolve_fetched(&self.name, &self.version, &installed)?;
+        if !installed.contains(&version) {
+            return Err(offline::version_not_available(&self.name, version, &installed).into());
+        }
+
+        info!(
+            "{} {} is already installed, skipping the install without network access",
+            note_prefix(),
+            tool_version(&self.name, version)
+        );
+        Ok(())
+    }
+
+    /// Moves the package into place and writes its configs and shims
+    ///
+    /// Returns the installed manifest along with the changes made to the Volta directory.
+    pub fn complete_install(
+        self,
+        image: &Image,
+        shims: bool,
+    ) -> Fallible<(PackageManifest, Vec<Change>)> {
         let manager = PackageManager::Npm;
         let manifest =
             configure::parse_manifest(&self.name, self.staging.path().to_owned(), manager)?;
 
-        persist_install(&self.name, &self.version, self.staging.path())?;
-        link_package_to_shared_dir(&self.name, manager)?;
-        configure::write_config_and_shims(&self.name, &manifest, image, manager)?;
+        let mut changes = vec![
+            persist_install(&self.name, &self.version, self.staging.path())?,
+            link_package_to_shared_dir(&self.name, manager)?,
+        ];
+        let tag = self.version.dist_tag().map(String::from);
+        changes.extend(configure::write_config_and_shims(
+            &self.name, &manifest, image, manager, tag, None, shims,
+        )?);
 
-        Ok(manifest)
+        Ok((manifest, changes))
     }
 }
 
@@ -74,38 +145,25 @@ impl Tool for Package {
     }
 
     fn install(self: Box<Self>, session: &mut Session) -> Fallible<()> {
-        let _lock = VoltaLock::acquire();
-
-        let default_image = session
-            .default_platform()?
-            .map(PlatformSpec::as_default)
-            .ok_or(ErrorKind::NoPlatform)?
-            .checkout(session)?;
+        if session.is_offline() && session.offline_archives().is_none() {
+            return self.check_installed();
+        }
 
-        self.run_install(&default_image)?;
-        let manifest = self.complete_install(&default_image)?;
+        let _lock = VoltaLock::acquire();
 
-        let bins = manifest.bin.join(", ");
+        let image = install_image(session)?;
 
-        if bins.is_empty() {
-            info!(
-                "{} installed {}",
-                success_prefix(),
-                tool_version(manifest.name, manifest.version)
-            );
-        } else {
-            info!(
-                "{} installed {} with executables: {}",
-                success_prefix(),
-                tool_version(manifest.name, manifest.version),
-                bins
-            );
-        }
+        self.run_install(&image, session)?;
+        let shims = session.creates_package_shims();
+        let (manifest, changes) = self.complete_install(&image, shims)?;
+        session.record_changes(changes);
+        duplicate::check_npm_duplicates(&manifest.name, &image, session.takes_over_npm_globals())?;
 
+        report_install(&manifest, shims);
         Ok(())
     }
 
-    fn pin(self: Box<Self>, _session: &mut Session) -> Fallible<()> {
+    fn pin(self: Box<Self>, _session: &mut Session) -> Fallible<String> {
         Err(ErrorKind::CannotPinPackage { package: self.name }.into())
     }
 }
@@ -119,6 +177,210 @@ impl Display for Package {
     }
 }
 
+/// Where a global package that isn't installed from the registry comes from
+#[derive(Clone, Debug)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub enum PackageSource {
+    /// A local directory containing a `package.json`
+    Directory(PathBuf),
+    /// A local package tarball, as created by `npm pack`
+    Tarball(PathBuf),
+    /// A git URL, such as `git+https://github.com/org/tool.git` or `github:org/tool`
+    Git(String),
+}
+
+impl PackageSource {
+    /// The path or URL of the package, as recorded in its config
+    pub fn location(&self) -> String {
+        match self {
+            PackageSource::Directory(path) | PackageSource::Tarball(path) => {
+                path.display().to_string()
+            }
+            PackageSource::Git(url) => url.clone(),
+        }
+    }
+}
+
+impl Display for PackageSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackageSource::Directory(path) | PackageSource::Tarball(path) => {
+                write!(f, "file:{}", path.display())
+            }
+            PackageSource::Git(url) => f.write_str(url),
+        }
+    }
+}
+
+/// The Tool implementation for installing a global package from a local directory, a tarball, or
+/// a git URL
+///
+/// The source is passed to `npm install --global` as it was given, and the name of the package is
+/// read from the installed manifest afterwards, as it isn't known ahead of time.
+pub struct SourcePackage {
+    source: PackageSource,
+    staging: TempDir,
+}
+
+impl SourcePackage {
+    pub fn new(source: PackageSource) -> Fallible<Self> {
+        let staging = setup_staging_directory(PackageManager::Npm, NeedsScope::No)?;
+
+        Ok(SourcePackage { source, staging })
+    }
+
+    fn complete_install(
+        self,
+        image: &Image,
+        shims: bool,
+    ) -> Fallible<(PackageManifest, Vec<Change>)> {
+        let manager = PackageManager::Npm;
+        let name = manager
+            .get_installed_package(self.staging.path().to_owned())
+            .ok_or(ErrorKind::InstalledPackageNameError)?;
+        copy_linked_package(&name, &manager.source_dir(self.staging.path().to_owned()))?;
+        let manifest = configure::parse_manifest(&name, self.staging.path().to_owned(), manager)?;
+
+        let mut changes = vec![
+            persist_install(&name, &manifest.version, self.staging.path())?,
+            link_package_to_shared_dir(&name, manager)?,
+        ];
+        changes.extend(configure::write_config_and_shims(
+            &name,
+            &manifest,
+            image,
+            manager,
+            None,
+            Some(self.source.location()),
+            shims,
+        )?);
+
+        Ok((manifest, changes))
+    }
+}
+
+impl Tool for SourcePackage {
+    fn fetch(self: Box<Self>, _session: &mut Session) -> Fallible<()> {
+        Err(ErrorKind::CannotFetchPackage {
+            package: self.to_string(),
+        }
+        .into())
+    }
+
+    fn install(self: Box<Self>, session: &mut Session) -> Fallible<()> {
+        let _lock = VoltaLock::acquire();
+
+        let image = install_image(session)?;
+
+        install::run_global_install(
+            self.to_string(),
+            self.staging.path().to_owned(),
+            &image,
+            install::Source::Registry(registry(session)?),
+        )?;
+        let shims = session.creates_package_shims();
+        let (manifest, changes) = self.complete_install(&image, shims)?;
+        session.record_changes(changes);
+        duplicate::check_npm_duplicates(&manifest.name, &image, session.takes_over_npm_globals())?;
+
+        report_install(&manifest, shims);
+        Ok(())
+    }
+
+    fn pin(self: Box<Self>, _session: &mut Session) -> Fallible<String> {
+        Err(ErrorKind::CannotPinPackage {
+            package: self.to_string(),
+        }
+        .into())
+    }
+}
+
+impl Display for SourcePackage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.source.fmt(f)
+    }
+}
+
+/// Replaces the link that npm creates when installing a local directory with a copy of it
+///
+/// npm links `file:` directories into the global prefix instead of copying them, so the installed
+/// package would break as soon as the checkout changes or is removed.
+fn copy_linked_package(name: &str, source_dir: &Path) -> Fallible<()> {
+    let installed = source_dir.join(name);
+    let is_link = installed
+        .symlink_metadata()
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false);
+    if !is_link {
+        return Ok(());
+    }
+
+    let checkout =
+        dunce::canonicalize(&installed).with_context(|| ErrorKind::CopyLocalPackageError {
+            package: name.into(),
+            dir: installed.clone(),
+        })?;
+    debug!(
+        "Copying {} from {} into the package image",
+        name,
+        checkout.display()
+    );
+
+    remove_link(&installed)
+        .and_then(|_| copy_dir_all(&checkout, &installed))
+        .with_context(|| ErrorKind::CopyLocalPackageError {
+            package: name.into(),
+            dir: checkout.clone(),
+        })
+}
+
+#[cfg(unix)]
+fn remove_link(link: &Path) -> std::io::Result<()> {
+    std::fs::remove_file(link)
+}
+
+/// npm creates junctions for local directories on Windows, which are removed like directories
+#[cfg(windows)]
+fn remove_link(link: &Path) -> std::io::Result<()> {
+    std::fs::remove_dir(link)
+}
+
+/// Checks out the platform that packages are installed with, which is the default platform unless
+/// the session asks for the project's
+fn install_image(session: &mut Session) -> Fallible<Image> {
+    let platform = if session.installs_packages_with_project_platform() {
+        Platform::current(session)?
+    } else {
+        session.default_platform()?.map(PlatformSpec::as_default)
+    };
+
+    platform.ok_or(ErrorKind::NoPlatform)?.checkout(session)
+}
+
+/// Reports the installed package and its executables
+fn report_install(manifest: &PackageManifest, shims: bool) {
+    let package = tool_version(&manifest.name, &manifest.version);
+    let bins = manifest.bin.join(", ");
+
+    if bins.is_empty() {
+        info!("{} installed {}", success_prefix(), package);
+    } else if shims {
+        info!(
+            "{} installed {} with executables: {}",
+            success_prefix(),
+            package,
+            bins
+        );
+    } else {
+        info!(
+            "{} installed {} without shims for its executables: {}",
+            success_prefix(),
+            package,
+            bins
+        );
+    }
+}
+
 /// Helper struct for direct installs through `npm i -g` or `yarn global add`
 ///
 /// Provides methods to simplify installing into a staging directory and then moving that install
@@ -158,7 +420,7 @@ impl DirectInstall {
             .setup_global_command(command, self.staging.path().to_owned());
     }
 
-    pub fn complete_install(self, image: &Image) -> Fallible<()> {
+    pub fn complete_install(self, image: &Image) -> Fallible<Vec<Change>> {
         let DirectInstall {
             staging,
             name,
@@ -170,9 +432,14 @@ impl DirectInstall {
             .ok_or(ErrorKind::InstalledPackageNameError)?;
         let manifest = configure::parse_manifest(&name, staging.path().to_owned(), manager)?;
 
-        persist_install(&name, &manifest.version, staging.path())?;
-        link_package_to_shared_dir(&name, manager)?;
-        configure::write_config_and_shims(&name, &manifest, image, manager)
+        let mut changes = vec![
+            persist_install(&name, &manifest.version, staging.path())?,
+            link_package_to_shared_dir(&name, manager)?,
+        ];
+        changes.extend(configure::write_config_and_shims(
+            &name, &manifest, image, manager, None, None, true,
+        )?);
+        Ok(changes)
     }
 }
 
@@ -223,11 +490,25 @@ impl InPlaceUpgrade {
             .setup_global_command(command, self.directory.clone());
     }
 
-    pub fn complete_upgrade(self, image: &Image) -> Fallible<()> {
+    pub fn complete_upgrade(self, image: &Image) -> Fallible<Vec<Change>> {
+        // An upgrade keeps a package that was installed without shims that way
+        let shims = PackageConfig::from_file_if_exists(
+            volta_home()?.default_package_config_file(&self.package),
+        )?
+        .map_or(true, |config| config.shims);
         let manifest = configure::parse_manifest(&self.package, self.directory, self.manager)?;
 
-        link_package_to_shared_dir(&self.package, self.manager)?;
-        configure::write_config_and_shims(&self.package, &manifest, image, self.manager)
+        let mut changes = vec![link_package_to_shared_dir(&self.package, self.manager)?];
+        changes.extend(configure::write_config_and_shims(
+            &self.package,
+            &manifest,
+            image,
+            self.manager,
+            None,
+            None,
+            shims,
+        )?);
+        Ok(changes)
     }
 }
 
@@ -247,6 +528,38 @@ impl From<bool> for NeedsScope {
     }
 }
 
+/// The registry that packages should be installed from, if one is set in the hooks
+pub fn registry(session: &Session) -> Fallible<Option<&str>> {
+    Ok(session
+        .hooks()?
+        .package()
+        .and_then(|hooks| hooks.registry.as_deref()))
+}
+
+/// Finds the version of a package that a dist-tag, such as `latest`, points to in the registry
+/// that packages are installed from
+pub fn tagged_version(name: &str, tag: &str, session: &Session) -> Fallible<Version> {
+    let url = match registry(session)? {
+        Some(registry) => format!("{}/{}", registry.trim_end_matches('/'), name),
+        None => public_registry_index(name),
+    };
+    let (url, mut index) = fetch_npm_registry(url, name)?;
+
+    match index.tags.remove(tag) {
+        Some(version) => {
+            debug!(
+                "Found {}@{} matching tag '{}' from {}",
+                name, version, tag, url
+            );
+            Ok(version)
+        }
+        None => Err(ErrorKind::PackageNotFound {
+            package: tool_version(name, tag),
+        }
+        .into()),
+    }
+}
+
 /// Create the temporary staging directory we will use to install and ensure expected
 /// subdirectories exist within it
 fn setup_staging_directory(manager: PackageManager, needs_scope: NeedsScope) -> Fallible<TempDir> {
@@ -283,11 +596,16 @@ fn setup_staging_directory(manager: PackageManager, needs_scope: NeedsScope) ->
     Ok(staging)
 }
 
-fn persist_install<V>(package_name: &str, package_version: V, staging_dir: &Path) -> Fallible<()>
+fn persist_install<V>(
+    package_name: &str,
+    package_version: V,
+    staging_dir: &Path,
+) -> Fallible<Change>
 where
     V: Display,
 {
     let package_dir = volta_home()?.package_image_dir(package_name);
+    let existed = change::exists(&package_dir);
 
     remove_dir_if_exists(&package_dir)?;
 
@@ -296,21 +614,23 @@ where
         path: package_dir.to_owned(),
     })?;
 
-    rename(staging_dir, &package_dir).with_context(|| ErrorKind::SetupToolImageError {
-        tool: package_name.into(),
-        version: package_version.to_string(),
-        dir: package_dir,
+    // Scoped packages with deeply nested dependencies can pass the legacy Windows path limit
+    rename(long_path(staging_dir), long_path(&package_dir)).with_context(|| {
+        ErrorKind::SetupToolImageError {
+            tool: package_name.into(),
+            version: package_version.to_string(),
+            dir: package_dir.clone(),
+        }
     })?;
 
-    Ok(())
+    Ok(Change::written(Subject::Image, package_dir, existed))
 }
 
-fn link_package_to_shared_dir(package_name: &str, manager: PackageManager) -> Fallible<()> {
+fn link_package_to_shared_dir(package_name: &str, manager: PackageManager) -> Fallible<Change> {
     let home = volta_home()?;
-    let mut source = manager.source_dir(home.package_image_dir(package_name));
-    source.push(package_name);
-
+    let source = relink::shared_link_source(home, package_name, manager);
     let target = home.shared_lib_dir(package_name);
+    let existed = change::exists(&target);
 
     remove_dir_if_exists(&target)?;
 
@@ -319,7 +639,11 @@ fn link_package_to_shared_dir(package_name: &str, manager: PackageManager) -> Fa
         path: target.clone(),
     })?;
 
-    symlink_dir(source, target).with_context(|| ErrorKind::CreateSharedLinkError {
-        name: package_name.into(),
-    })
+    relink::create_shared_link(&long_path(&source), &long_path(&target)).with_context(|| {
+        ErrorKind::CreateSharedLinkError {
+            name: package_name.into(),
+        }
+    })?;
+
+    Ok(Change::written(Subject::Link, target, existed))
 }
@@ -0,0 +1,261 @@
+//! Provides the links from the shared library directory to the installed package images.
+//!
+//! Every installed package is linked into `<VOLTA_HOME>/tools/shared`. On Unix, the links are
+//! relative, so that they keep working when the Volta directory is moved or restored from a backup
+//! to a different location. Windows junctions must be absolute, so they are recreated as-is.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::PackageManager;
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::{relative_path, remove_dir_if_exists, symlink_dir};
+use crate::inventory::package_configs;
+use crate::layout::volta_home;
+use fs_utils::ensure_containing_dir_exists;
+use volta_layout::v4::VoltaHome;
+
+/// The result of checking the shared link for a single package
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelinkStatus {
+    /// The link already pointed at the package image
+    Unchanged,
+    /// The link was missing, and has been created
+    Created,
+    /// The link was dangling or pointed somewhere else, and has been recreated
+    Repaired,
+    /// The link pointed at the package image with an absolute path, and has been made relative
+    Converted,
+    /// The package image is missing, so there is nothing to link to
+    MissingImage,
+}
+
+/// A package whose shared link was checked by `relink_packages`
+pub struct RelinkedPackage {
+    pub name: String,
+    pub status: Fallible<RelinkStatus>,
+}
+
+/// Checks the shared link of every installed package, recreating any that are missing, dangling,
+/// or pointing somewhere other than the package image
+pub fn relink_packages() -> Fallible<Vec<RelinkedPackage>> {
+    let home = volta_home()?;
+
+    Ok(package_configs()?
+        .into_iter()
+        .map(|config| {
+            let source = shared_link_source(home, &config.name, config.manager);
+            let target = home.shared_lib_dir(&config.name);
+            let status = relink(&config.name, &source, &target);
+
+            RelinkedPackage {
+                name: config.name,
+                status,
+            }
+        })
+        .collect())
+}
+
+/// The directory within the package image that the shared link points at
+pub(super) fn shared_link_source(
+    home: &VoltaHome,
+    package_name: &str,
+    manager: PackageManager,
+) -> PathBuf {
+    let mut source = manager.source_dir(home.package_image_dir(package_name));
+    source.push(package_name);
+    source
+}
+
+/// Creates the link at `target`, pointing at `source`
+pub(super) fn create_shared_link(source: &Path, target: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    if let Some(relative) = target
+        .parent()
+        .and_then(|parent| relative_path(parent, source))
+    {
+        return symlink_dir(relative, target);
+    }
+
+    symlink_dir(source, target)
+}
+
+fn relink(package_name: &str, source: &Path, target: &Path) -> Fallible<RelinkStatus> {
+    if !source.is_dir() {
+        return Ok(RelinkStatus::MissingImage);
+    }
+
+    let status = match fs::read_link(target) {
+        Ok(link) => {
+            let resolved = match target.parent() {
+                Some(parent) => parent.join(&link),
+                None => link.clone(),
+            };
+
+            if !points_to(&resolved, source) {
+                RelinkStatus::Repaired
+            } else if cfg!(unix) && link.is_absolute() {
+                RelinkStatus::Converted
+            } else {
+                return Ok(RelinkStatus::Unchanged);
+            }
+        }
+        Err(error) if error.kind() == io::ErrorKind::NotFound => RelinkStatus::Created,
+        // Something other than a link is in the way, which is replaced like a dangling link
+        Err(_) => RelinkStatus::Repaired,
+    };
+
+    if status != RelinkStatus::Created {
+        remove_dir_if_exists(target)?;
+    }
+
+    // Handle scoped packages (@vue/cli), which have an extra directory for the scope
+    ensure_containing_dir_exists(&target).with_context(|| ErrorKind::ContainingDirError {
+        path: target.to_owned(),
+    })?;
+
+    create_shared_link(source, target).with_context(|| ErrorKind::CreateSharedLinkError {
+        name: package_name.into(),
+    })?;
+
+    Ok(status)
+}
+
+/// Determines whether the resolved link destination is the package image
+fn points_to(resolved: &Path, source: &Path) -> bool {
+    if resolved == source {
+        return true;
+    }
+
+    // Relative links and Windows junctions can name the same directory with a different path
+    match (dunce::canonicalize(resolved), dunce::canonicalize(source)) {
+        (Ok(resolved), Ok(source)) => resolved == source,
+        _ => false,
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+    use tempfile::{tempdir, TempDir};
+
+    /// Creates a package image and returns the (source, target) paths of its shared link
+    fn fixture(home: &TempDir, name: &str) -> (PathBuf, PathBuf) {
+        let source = home
+            .path()
+            .join("tools/image/packages")
+            .join(name)
+            .join("lib/node_modules")
+            .join(name);
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("package.json"), "{}").unwrap();
+
+        (source, home.path().join("tools/shared").join(name))
+    }
+
+    fn assert_relative_link(source: &Path, target: &Path) {
+        let link = fs::read_link(target).unwrap();
+        assert!(link.is_relative(), "{} is absolute", link.display());
+        assert_eq!(
+            fs::canonicalize(target).unwrap(),
+            fs::canonicalize(source).unwrap()
+        );
+    }
+
+    #[test]
+    fn relative_path_between_siblings() {
+        assert_eq!(
+            relative_path(
+                Path::new("/home/.volta/tools/shared/@scope"),
+                Path::new("/home/.volta/tools/image/packages/@scope/pkg")
+            ),
+            Some(PathBuf::from("../../image/packages/@scope/pkg"))
+        );
+    }
+
+    #[test]
+    fn creates_missing_links() {
+        let home = tempdir().unwrap();
+        let (source, target) = fixture(&home, "cowsay");
+
+        assert_eq!(
+            relink("test-package", &source, &target).unwrap(),
+            RelinkStatus::Created
+        );
+        assert_relative_link(&source, &target);
+    }
+
+    #[test]
+    fn repairs_dangling_links() {
+        let home = tempdir().unwrap();
+        let (source, target) = fixture(&home, "@scope/pkg");
+        fs::create_dir_all(target.parent().unwrap()).unwrap();
+        symlink("/old/home/.volta/tools/image/packages/@scope/pkg", &target).unwrap();
+
+        assert_eq!(
+            relink("test-package", &source, &target).unwrap(),
+            RelinkStatus::Repaired
+        );
+        assert_relative_link(&source, &target);
+    }
+
+    #[test]
+    fn repairs_links_pointing_elsewhere() {
+        let home = tempdir().unwrap();
+        let (source, target) = fixture(&home, "cowsay");
+        let (elsewhere, _) = fixture(&home, "typescript");
+        fs::create_dir_all(target.parent().unwrap()).unwrap();
+        symlink(elsewhere, &target).unwrap();
+
+        assert_eq!(
+            relink("test-package", &source, &target).unwrap(),
+            RelinkStatus::Repaired
+        );
+        assert_relative_link(&source, &target);
+    }
+
+    #[test]
+    fn converts_absolute_links() {
+        let home = tempdir().unwrap();
+        let (source, target) = fixture(&home, "cowsay");
+        fs::create_dir_all(target.parent().unwrap()).unwrap();
+        symlink(&source, &target).unwrap();
+
+        assert_eq!(
+            relink("test-package", &source, &target).unwrap(),
+            RelinkStatus::Converted
+        );
+        assert_relative_link(&source, &target);
+    }
+
+    #[test]
+    fn leaves_relative_links_alone() {
+        let home = tempdir().unwrap();
+        let (source, target) = fixture(&home, "cowsay");
+        fs::create_dir_all(target.parent().unwrap()).unwrap();
+        create_shared_link(&source, &target).unwrap();
+
+        assert_eq!(
+            relink("test-package", &source, &target).unwrap(),
+            RelinkStatus::Unchanged
+        );
+        assert_relative_link(&source, &target);
+    }
+
+    #[test]
+    fn reports_missing_images() {
+        let home = tempdir().unwrap();
+        let source = home
+            .path()
+            .join("tools/image/packages/cowsay/lib/node_modules/cowsay");
+        let target = home.path().join("tools/shared/cowsay");
+
+        assert_eq!(
+            relink("test-package", &source, &target).unwrap(),
+            RelinkStatus::MissingImage
+        );
+        assert!(fs::symlink_metadata(&target).is_err());
+    }
+}
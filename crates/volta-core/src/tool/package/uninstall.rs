@@ -1,13 +1,20 @@
+use std::fmt::{self, Display};
+use std::fs;
+use std::path::{Path, PathBuf};
+
 use super::metadata::{BinConfig, PackageConfig};
+use crate::change::{Change, Subject};
 use crate::error::{Context, ErrorKind, Fallible};
 use crate::fs::{
     dir_entry_match, ok_if_not_found, read_dir_eager, remove_dir_if_exists, remove_file_if_exists,
 };
 use crate::layout::volta_home;
-use crate::shim;
-use crate::style::success_prefix;
+use crate::shim::{self, ShimStatus};
+use crate::style::{note_prefix, success_prefix};
 use crate::sync::VoltaLock;
+use indicatif::HumanBytes;
 use log::{info, warn};
+use walkdir::WalkDir;
 
 /// Uninstalls the specified package.
 ///
@@ -16,62 +23,263 @@ use log::{info, warn};
 /// - The JSON configuration files for both the package and its bins
 /// - The shims for the package bins
 /// - The package directory itself
-pub fn uninstall(name: &str) -> Fallible<()> {
-    let home = volta_home()?;
+///
+/// Returns the changes made to the Volta directory, in the order they were made.
+pub fn uninstall(name: &str) -> Fallible<Vec<Change>> {
     // Acquire a lock on the Volta directory, if possible, to prevent concurrent changes
     let _lock = VoltaLock::acquire();
 
-    // If the package config file exists, use that to remove any installed bins and shims
-    let package_config_file = home.default_package_config_file(name);
+    let plan = UninstallPlan::collect(name)?;
+    let changes = plan.execute()?;
+    plan.report(Mode::Execute);
 
-    let package_found = match PackageConfig::from_file_if_exists(&package_config_file)? {
-        None => {
-            // there is no package config - check for orphaned binaries
-            let package_binary_list = binaries_from_package(name)?;
-            if !package_binary_list.is_empty() {
-                for bin_name in package_binary_list {
-                    remove_config_and_shim(&bin_name, name)?;
-                }
-                true
-            } else {
-                false
+    Ok(changes)
+}
+
+/// Shows what uninstalling the specified package would remove, without removing anything.
+///
+/// The discovery is the same as for `uninstall`, and the summary has the same structure, so the
+/// two outputs can be compared directly.
+pub fn uninstall_dry_run(name: &str) -> Fallible<()> {
+    let plan = UninstallPlan::collect(name)?;
+    plan.report(Mode::DryRun);
+
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum Mode {
+    Execute,
+    DryRun,
+}
+
+impl Mode {
+    fn verb(self) -> &'static str {
+        match self {
+            Mode::Execute => "Removed",
+            Mode::DryRun => "Would remove",
+        }
+    }
+}
+
+/// The full set of files and directories that uninstalling a package will remove
+struct UninstallPlan {
+    name: String,
+    package_found: bool,
+    bins: Vec<String>,
+    removals: Vec<Removal>,
+    size: u64,
+}
+
+impl UninstallPlan {
+    /// Determines everything that needs to be removed to uninstall the package, without making
+    /// any changes
+    fn collect(name: &str) -> Fallible<Self> {
+        let home = volta_home()?;
+        let mut removals = Vec::new();
+
+        // If the package config file exists, use that to find any installed bins and shims
+        let package_config_file = home.default_package_config_file(name);
+
+        let (package_found, bins) = match PackageConfig::from_file_if_exists(&package_config_file)?
+        {
+            None => {
+                // there is no package config - check for orphaned binaries
+                let package_binary_list = binaries_from_package(name)?;
+                (!package_binary_list.is_empty(), package_binary_list)
+            }
+            Some(package_config) => {
+                removals.push(Removal::PackageConfig(package_config_file));
+                // Without shims, any bin configs and shims for the bins belong to another package
+                let bins = if package_config.shims {
+                    package_config.bins
+                } else {
+                    Vec::new()
+                };
+                (true, bins)
+            }
+        };
+
+        // A bin with a reserved name may share its path with Volta's own executable, which must
+        // be left in place unless it really is a shim
+        let shadowing = shim::shadowing_shims()?;
+
+        for bin_name in &bins {
+            removals.push(Removal::BinConfig(home.default_tool_bin_config(bin_name)));
+            if !shim::is_reserved(bin_name) || shadowing.contains(bin_name) {
+                removals.push(Removal::Shim {
+                    name: bin_name.clone(),
+                    path: home.shim_file(bin_name),
+                });
             }
         }
-        Some(package_config) => {
-            for bin_name in package_config.bins {
-                remove_config_and_shim(&bin_name, name)?;
+
+        removals.extend(shared_link_removals(name)?);
+        removals.push(Removal::PackageImage(home.package_image_dir(name)));
+
+        // Only report the entries that are actually present on disk
+        removals.retain(|removal| exists(removal.path()));
+        let size = removals
+            .iter()
+            .map(|removal| disk_size(removal.path()))
+            .sum();
+
+        Ok(UninstallPlan {
+            name: name.to_owned(),
+            package_found,
+            bins,
+            removals,
+            size,
+        })
+    }
+
+    fn execute(&self) -> Fallible<Vec<Change>> {
+        let mut changes = Vec::new();
+        for removal in &self.removals {
+            if removal.execute()? {
+                changes.push(Change::removed(removal.subject(), removal.path()));
             }
+        }
+
+        Ok(changes)
+    }
 
-            remove_file_if_exists(package_config_file)?;
-            true
+    fn report(&self, mode: Mode) {
+        for bin_name in &self.bins {
+            info!(
+                "{} executable '{}' installed by '{}'",
+                mode.verb(),
+                bin_name,
+                self.name
+            );
         }
-    };
 
-    remove_shared_link_dir(name)?;
+        for removal in &self.removals {
+            info!(
+                "{} {}: {}",
+                mode.verb(),
+                removal.category(),
+                removal.path().display()
+            );
+        }
 
-    // Remove the package directory itself
-    let package_image_dir = home.package_image_dir(name);
-    remove_dir_if_exists(package_image_dir)?;
+        if !self.removals.is_empty() {
+            info!(
+                "{} {} {} (approximately {})",
+                mode.verb(),
+                self.removals.len(),
+                if self.removals.len() == 1 {
+                    "entry"
+                } else {
+                    "entries"
+                },
+                HumanBytes(self.size)
+            );
+        }
 
-    if package_found {
-        info!("{} package '{}' uninstalled", success_prefix(), name);
-    } else {
-        warn!("No package '{}' found to uninstall", name);
+        match (mode, self.package_found) {
+            (Mode::Execute, true) => {
+                info!("{} package '{}' uninstalled", success_prefix(), self.name)
+            }
+            (Mode::DryRun, true) => info!(
+                "{} dry run, package '{}' was not uninstalled",
+                note_prefix(),
+                self.name
+            ),
+            (_, false) => warn!("No package '{}' found to uninstall", self.name),
+        }
     }
+}
 
-    Ok(())
+/// A single file or directory that is removed as part of uninstalling a package
+enum Removal {
+    PackageConfig(PathBuf),
+    BinConfig(PathBuf),
+    Shim {
+        name: String,
+        path: PathBuf,
+    },
+    SharedLink(PathBuf),
+    /// The scope directory of a scoped package, which is only removed once it is empty
+    SharedScope(PathBuf),
+    PackageImage(PathBuf),
 }
 
-/// Remove a shim and its associated configuration file
-fn remove_config_and_shim(bin_name: &str, pkg_name: &str) -> Fallible<()> {
-    shim::delete(bin_name)?;
-    let config_file = volta_home()?.default_tool_bin_config(bin_name);
-    remove_file_if_exists(config_file)?;
-    info!(
-        "Removed executable '{}' installed by '{}'",
-        bin_name, pkg_name
-    );
-    Ok(())
+impl Removal {
+    fn path(&self) -> &Path {
+        match self {
+            Removal::PackageConfig(path)
+            | Removal::BinConfig(path)
+            | Removal::Shim { path, .. }
+            | Removal::SharedLink(path)
+            | Removal::SharedScope(path)
+            | Removal::PackageImage(path) => path,
+        }
+    }
+
+    fn category(&self) -> RemovalCategory {
+        match self {
+            Removal::PackageConfig(_) => RemovalCategory::PackageConfig,
+            Removal::BinConfig(_) => RemovalCategory::BinConfig,
+            Removal::Shim { .. } => RemovalCategory::Shim,
+            Removal::SharedLink(_) | Removal::SharedScope(_) => RemovalCategory::SharedLink,
+            Removal::PackageImage(_) => RemovalCategory::PackageImage,
+        }
+    }
+
+    fn subject(&self) -> Subject {
+        match self {
+            Removal::PackageConfig(_) | Removal::BinConfig(_) => Subject::Config,
+            Removal::Shim { .. } => Subject::Shim,
+            Removal::SharedLink(_) | Removal::SharedScope(_) => Subject::Link,
+            Removal::PackageImage(_) => Subject::Image,
+        }
+    }
+
+    /// Removes the file or directory, returning whether anything was removed
+    fn execute(&self) -> Fallible<bool> {
+        match self {
+            Removal::PackageConfig(path) | Removal::BinConfig(path) => {
+                remove_file_if_exists(path).map(|()| true)
+            }
+            Removal::Shim { name, .. } => {
+                shim::delete(name).map(|result| result.status == ShimStatus::Deleted)
+            }
+            Removal::SharedLink(path) | Removal::PackageImage(path) => {
+                remove_dir_if_exists(path).map(|()| true)
+            }
+            Removal::SharedScope(path) => {
+                if let Ok(mut entries) = read_dir_eager(path) {
+                    if entries.next().is_none() {
+                        remove_dir_if_exists(path)?;
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+        }
+    }
+}
+
+enum RemovalCategory {
+    PackageConfig,
+    BinConfig,
+    Shim,
+    SharedLink,
+    PackageImage,
+}
+
+impl Display for RemovalCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            RemovalCategory::PackageConfig => "package config",
+            RemovalCategory::BinConfig => "executable config",
+            RemovalCategory::Shim => "shim",
+            RemovalCategory::SharedLink => "shared library link",
+            RemovalCategory::PackageImage => "package image",
+        };
+        f.write_str(s)
+    }
 }
 
 /// Reads the contents of a directory and returns a Vec containing the names of
@@ -94,24 +302,43 @@ fn binaries_from_package(package: &str) -> Fallible<Vec<String>> {
     })
 }
 
-/// Remove the link to the package in the shared lib directory
+/// Finds the link to the package in the shared lib directory
 ///
-/// For scoped packages, if the scope directory is now empty, it will also be removed
-fn remove_shared_link_dir(name: &str) -> Fallible<()> {
-    // Remove the link in the shared package directory, if it exists
-    let mut shared_lib_dir = volta_home()?.shared_lib_dir(name);
-    remove_dir_if_exists(&shared_lib_dir)?;
+/// For scoped packages, if the scope directory will be empty once the link is removed, it will
+/// also be removed
+fn shared_link_removals(name: &str) -> Fallible<Vec<Removal>> {
+    let shared_lib_dir = volta_home()?.shared_lib_dir(name);
+    let mut removals = Vec::new();
 
-    // For scoped packages, clean up the scope directory if it is now empty
     if name.starts_with('@') {
-        shared_lib_dir.pop();
+        if let Some(scope_dir) = shared_lib_dir.parent() {
+            let only_this_package = read_dir_eager(scope_dir)
+                .map(|mut entries| entries.all(|(entry, _)| entry.path() == shared_lib_dir))
+                .unwrap_or(false);
 
-        if let Ok(mut entries) = read_dir_eager(&shared_lib_dir) {
-            if entries.next().is_none() {
-                remove_dir_if_exists(&shared_lib_dir)?;
+            if only_this_package {
+                removals.push(Removal::SharedLink(shared_lib_dir.clone()));
+                removals.push(Removal::SharedScope(scope_dir.to_owned()));
+                return Ok(removals);
             }
         }
     }
 
-    Ok(())
+    removals.push(Removal::SharedLink(shared_lib_dir));
+    Ok(removals)
+}
+
+fn exists(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+/// Estimates the space used by a file or directory, without following symlinks
+fn disk_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| !metadata.is_dir())
+        .map(|metadata| metadata.len())
+        .sum()
 }
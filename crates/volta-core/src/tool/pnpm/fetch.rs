@@ -8,6 +8,7 @@ use fs_utils::ensure_containing_dir_exists;
 use log::debug;
 use node_semver::Version;
 
+use crate::download::DownloadPermit;
 use crate::error::{Context, ErrorKind, Fallible};
 use crate::fs::{create_staging_dir, create_staging_file, rename, set_executable};
 use crate::hook::ToolHooks;
@@ -21,20 +22,22 @@ pub fn fetch(version: &Version, hooks: Option<&ToolHooks<Pnpm>>) -> Fallible<()>
     let pnpm_dir = volta_home()?.pnpm_inventory_dir();
     let cache_file = pnpm_dir.join(Pnpm::archive_filename(&version.to_string()));
 
-    let (archive, staging) = match load_cached_distro(&cache_file) {
+    let (archive, staging, _permit) = match load_cached_distro(&cache_file) {
         Some(archive) => {
             debug!(
                 "Loading {} from cached archive at '{}'",
                 tool_version("pnpm", version),
                 cache_file.display(),
             );
-            (archive, None)
+            (archive, None, None)
         }
         None => {
+            // The permit is held until unpacking completes, since the archive is streamed
+            let permit = DownloadPermit::acquire();
             let staging = create_staging_file()?;
             let remote_url = determine_remote_url(version, hooks)?;
             let archive = fetch_remote_distro(version, &remote_url, staging.path())?;
-            (archive, Some(staging))
+            (archive, Some(staging), Some(permit))
         }
     };
 
@@ -61,11 +64,7 @@ fn unpack_archive(archive: Box<dyn Archive>, version: &Version) -> Fallible<()>
     let temp = create_staging_dir()?;
     debug!("Unpacking pnpm into '{}'", temp.path().display());
 
-    let progress = progress_bar(
-        archive.origin(),
-        &tool_version("pnpm", version),
-        archive.compressed_size(),
-    );
+    let mut progress = progress_bar(archive.origin(), "pnpm", version, archive.compressed_size());
     let version_string = version.to_string();
 
     archive
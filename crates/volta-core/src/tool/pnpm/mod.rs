@@ -1,15 +1,17 @@
 use node_semver::Version;
 use std::fmt::{self, Display};
 
+use crate::change::{Action, Change, Subject};
 use crate::error::{ErrorKind, Fallible};
-use crate::inventory::pnpm_available;
+use crate::inventory::{pnpm_available, pnpm_versions};
+use crate::layout::volta_home;
 use crate::session::Session;
 use crate::style::tool_version;
 use crate::sync::VoltaLock;
 
 use super::{
-    check_fetched, check_shim_reachable, debug_already_fetched, info_fetched, info_installed,
-    info_pinned, info_project_version, FetchStatus, Tool,
+    check_fetched, check_may_fetch, check_shim_reachable, debug_already_fetched, info_fetched,
+    info_installed, info_project_version, offline, quarantine, FetchStatus, Tool,
 };
 
 mod fetch;
@@ -41,7 +43,21 @@ impl Pnpm {
                 debug_already_fetched(self);
                 Ok(())
             }
-            FetchStatus::FetchNeeded(_lock) => fetch::fetch(&self.version, session.hooks()?.pnpm()),
+            FetchStatus::FetchNeeded(_lock) => {
+                check_may_fetch(self, session)?;
+                offline::check_may_download("pnpm", &self.version, session, pnpm_versions)?;
+                offline::stage_archive(
+                    self,
+                    session,
+                    &Pnpm::archive_filename(&self.version.to_string()),
+                    volta_home()?.pnpm_inventory_dir(),
+                )?;
+                fetch::fetch(&self.version, session.hooks()?.pnpm())?;
+                let image_dir = volta_home()?.pnpm_image_dir(&self.version.to_string());
+                quarantine::clear_image(&image_dir, session)?;
+                session.record_changes([Change::new(Subject::Image, Action::Created, image_dir)]);
+                Ok(())
+            }
         }
     }
 }
@@ -59,9 +75,10 @@ impl Tool for Pnpm {
         let _lock = VoltaLock::acquire();
         self.ensure_fetched(session)?;
 
-        session
+        let change = session
             .toolchain_mut()?
             .set_active_pnpm(Some(self.version.clone()))?;
+        session.record_changes(change);
 
         info_installed(&self);
         check_shim_reachable("pnpm");
@@ -74,7 +91,7 @@ impl Tool for Pnpm {
         Ok(())
     }
 
-    fn pin(self: Box<Self>, session: &mut Session) -> Fallible<()> {
+    fn pin(self: Box<Self>, session: &mut Session) -> Fallible<String> {
         if session.project()?.is_some() {
             self.ensure_fetched(session)?;
 
@@ -82,8 +99,7 @@ impl Tool for Pnpm {
             let project = session.project_mut()?.unwrap();
             project.pin_pnpm(Some(self.version.clone()))?;
 
-            info_pinned(self);
-            Ok(())
+            Ok(self.to_string())
         } else {
             Err(ErrorKind::NotInPackage.into())
         }
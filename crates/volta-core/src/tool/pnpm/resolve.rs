@@ -3,19 +3,33 @@ use node_semver::{Range, Version};
 
 use crate::error::{ErrorKind, Fallible};
 use crate::hook::ToolHooks;
+use crate::inventory::pnpm_versions;
 use crate::session::Session;
+use crate::tool::offline;
 use crate::tool::registry::{fetch_npm_registry, public_registry_index, PackageIndex};
 use crate::tool::{PackageDetails, Pnpm};
 use crate::version::{VersionSpec, VersionTag};
 
 pub fn resolve(matching: VersionSpec, session: &mut Session) -> Fallible<Version> {
+    let matching = session.resolve_alias("pnpm", matching)?;
     let hooks = session.hooks()?.pnpm();
-    match matching {
-        VersionSpec::Semver(requirement) => resolve_semver(requirement, hooks),
+    let version = match matching {
+        matching if session.is_offline() => {
+            offline::resolve_fetched("pnpm", &matching, &pnpm_versions()?)
+        }
         VersionSpec::Exact(version) => Ok(version),
+        matching if !session.may_fetch()? => {
+            offline::resolve_installed("pnpm", matching, |matching| {
+                offline::resolve_fetched("pnpm", &matching, &pnpm_versions()?)
+            })
+        }
+        VersionSpec::Semver(requirement) => resolve_semver(requirement, hooks),
         VersionSpec::None | VersionSpec::Tag(VersionTag::Latest) => resolve_tag("latest", hooks),
         VersionSpec::Tag(tag) => resolve_tag(&tag.to_string(), hooks),
-    }
+    }?;
+
+    session.check_policy("pnpm", &version)?;
+    Ok(version)
 }
 
 fn resolve_tag(tag: &str, hooks: Option<&ToolHooks<Pnpm>>) -> Fallible<Version> {
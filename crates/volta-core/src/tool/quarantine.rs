@@ -0,0 +1,165 @@
+//! Clears the macOS quarantine attribute from unpacked tool images
+//!
+//! Archives downloaded through some proxies, or by a Volta that was itself launched from a
+//! quarantined context, can pass the `com.apple.quarantine` attribute on to the files unpacked
+//! from them. Gatekeeper then prompts for, or on managed Macs kills, the first run of each of those
+//! executables, so the attribute is removed as soon as a tool is unpacked.
+
+use std::path::Path;
+
+use crate::error::Fallible;
+use crate::session::Session;
+#[cfg(target_os = "macos")]
+use log::debug;
+
+/// The name of the extended attribute, as a C string
+#[cfg(target_os = "macos")]
+const QUARANTINE_ATTRIBUTE: &[u8] = b"com.apple.quarantine\0";
+
+/// Removes the quarantine attribute from the executables in a freshly unpacked image, unless
+/// `install.clearQuarantine` is turned off
+///
+/// Nothing is done on other platforms. Files that can't be cleared are only logged, since a file
+/// that is still quarantined can be run once Gatekeeper allows it.
+#[cfg_attr(not(target_os = "macos"), allow(unused_variables))]
+pub(super) fn clear_image(image_dir: &Path, session: &Session) -> Fallible<()> {
+    #[cfg(target_os = "macos")]
+    if session.settings()?.clear_quarantine() {
+        let cleared = clear_executables(image_dir);
+        debug!(
+            "Cleared the quarantine attribute from {} files in '{}'",
+            cleared,
+            image_dir.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Removes the quarantine attribute from every executable file in a directory, returning how many
+/// of them had it
+#[cfg(target_os = "macos")]
+fn clear_executables(dir: &Path) -> usize {
+    use std::os::unix::fs::PermissionsExt;
+    use walkdir::WalkDir;
+
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry.file_type().is_file()
+                && entry
+                    .metadata()
+                    .is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+        })
+        .filter(|entry| match remove_quarantine(entry.path()) {
+            Ok(cleared) => cleared,
+            Err(error) => {
+                debug!(
+                    "Could not clear the quarantine attribute from '{}': {}",
+                    entry.path().display(),
+                    error
+                );
+                false
+            }
+        })
+        .count()
+}
+
+/// Removes the quarantine attribute from a file, returning whether it was set
+#[cfg(target_os = "macos")]
+fn remove_quarantine(path: &Path) -> std::io::Result<bool> {
+    use std::ffi::CString;
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(path.as_os_str().as_bytes())?;
+
+    // SAFETY: Both arguments are NUL-terminated strings that outlive the call.
+    let result = unsafe {
+        libc::removexattr(
+            path.as_ptr(),
+            QUARANTINE_ATTRIBUTE.as_ptr().cast(),
+            libc::XATTR_NOFOLLOW,
+        )
+    };
+
+    if result == 0 {
+        return Ok(true);
+    }
+
+    let error = io::Error::last_os_error();
+    if error.raw_os_error() == Some(libc::ENOATTR) {
+        Ok(false)
+    } else {
+        Err(error)
+    }
+}
+
+#[cfg(all(test, target_os = "macos"))]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+    use std::fs;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::PermissionsExt;
+    use std::ptr;
+
+    fn c_path(path: &Path) -> CString {
+        CString::new(path.as_os_str().as_bytes()).unwrap()
+    }
+
+    fn quarantine(path: &Path) {
+        let value = b"0081;00000000;Safari;";
+        // SAFETY: The path and attribute name are NUL-terminated, and the value is read for its
+        // given length.
+        let result = unsafe {
+            libc::setxattr(
+                c_path(path).as_ptr(),
+                QUARANTINE_ATTRIBUTE.as_ptr().cast(),
+                value.as_ptr().cast(),
+                value.len(),
+                0,
+                libc::XATTR_NOFOLLOW,
+            )
+        };
+        assert_eq!(result, 0, "{}", std::io::Error::last_os_error());
+    }
+
+    fn is_quarantined(path: &Path) -> bool {
+        // SAFETY: A null buffer of size 0 only asks for the size of the attribute.
+        let result = unsafe {
+            libc::getxattr(
+                c_path(path).as_ptr(),
+                QUARANTINE_ATTRIBUTE.as_ptr().cast(),
+                ptr::null_mut(),
+                0,
+                0,
+                libc::XATTR_NOFOLLOW,
+            )
+        };
+        result >= 0
+    }
+
+    #[test]
+    fn clears_quarantined_executables() {
+        let dir = tempfile::tempdir().unwrap();
+        let bin = dir.path().join("bin").join("node");
+        let readme = dir.path().join("README.md");
+        fs::create_dir_all(bin.parent().unwrap()).unwrap();
+        fs::write(&bin, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&bin, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::write(&readme, "# node\n").unwrap();
+
+        quarantine(&bin);
+        quarantine(&readme);
+
+        assert_eq!(clear_executables(dir.path()), 1);
+        assert!(!is_quarantined(&bin));
+        assert!(is_quarantined(&readme));
+
+        // Files without the attribute are left as they are
+        assert_eq!(clear_executables(dir.path()), 0);
+        assert!(!remove_quarantine(&bin).unwrap());
+    }
+}
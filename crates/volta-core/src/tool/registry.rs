@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-use super::registry_fetch_error;
+use super::{cache_expiry, npmrc, registry_fetch_error};
+use crate::download::DownloadPermit;
 use crate::error::{Context, ErrorKind, Fallible};
 use crate::fs::read_dir_eager;
 use crate::style::progress_spinner;
@@ -23,29 +25,44 @@ cfg_if! {
         // SERVER_URL constant: Since our acceptance tests run the binary in a separate process,
         // we can't use `mockito::server_url()`, which relies on shared memory.
         #[allow(deprecated)]
-        const SERVER_URL: &str = mockito::SERVER_URL;
-        pub fn public_registry_index(package: &str) -> String {
-            format!("{}/{}", SERVER_URL, package)
-        }
+        const PUBLIC_REGISTRY: &str = mockito::SERVER_URL;
     } else {
-        pub fn public_registry_index(package: &str) -> String {
-            format!("https://registry.npmjs.org/{}", package)
-        }
+        const PUBLIC_REGISTRY: &str = "https://registry.npmjs.org";
     }
 }
 
+/// The URL of a package in the registry configured in `.npmrc`, or in the public npm registry
+pub fn public_registry_index(package: &str) -> String {
+    let registry = npmrc::registry_for(package).unwrap_or(PUBLIC_REGISTRY);
+    format!("{}/{}", registry.trim_end_matches('/'), package)
+}
+
 // fetch a registry that returns info in Npm format
 pub fn fetch_npm_registry(url: String, name: &str) -> Fallible<(String, PackageIndex)> {
+    let (index, _) = fetch_npm_registry_with_expiry(&url, name)?;
+    Ok((url, index))
+}
+
+/// Fetches a registry that returns info in Npm format, along with the time until which the
+/// response may be cached
+pub fn fetch_npm_registry_with_expiry(
+    url: &str,
+    name: &str,
+) -> Fallible<(PackageIndex, SystemTime)> {
+    let _permit = DownloadPermit::acquire();
     let spinner = progress_spinner(format!("Fetching npm registry: {}", url));
-    let metadata: RawPackageMetadata = attohttpc::get(&url)
+    let (_, headers, response) = npmrc::get(url)
         .header(ACCEPT, NPM_ABBREVIATED_ACCEPT_HEADER)
         .send()
         .and_then(Response::error_for_status)
-        .and_then(Response::json)
-        .with_context(registry_fetch_error(name, &url))?;
+        .with_context(registry_fetch_error(name, url))?
+        .split();
+    let metadata: RawPackageMetadata = response
+        .json()
+        .with_context(registry_fetch_error(name, url))?;
 
     spinner.finish_and_clear();
-    Ok((url, metadata.into()))
+    Ok((metadata.into(), cache_expiry(&headers)))
 }
 
 pub fn public_registry_package(package: &str, version: &str) -> String {
@@ -1,16 +1,24 @@
 use std::cmp::Ordering;
+use std::env;
+use std::path::Path;
 
-use super::Spec;
-use crate::error::{ErrorKind, Fallible};
+use super::{PackageSource, Spec};
+use crate::error::{Context, ErrorKind, Fallible};
 use crate::version::{VersionSpec, VersionTag};
 use once_cell::sync::Lazy;
 use regex::Regex;
-use validate_npm_package_name::{validate, Validity};
+use validate_npm_package_name::{validate, Validity, ValidityIssue};
 
 static TOOL_SPEC_PATTERN: Lazy<Regex> = Lazy::new(|| {
     Regex::new("^(?P<name>(?:@([^/]+?)[/])?([^/]+?))(@(?P<version>.+))?$").expect("regex is valid")
 });
 static HAS_VERSION: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[^\s]+@").expect("regex is valid"));
+/// Git specs that npm understands: `git+<protocol>://`, `git://`, the hosted shortcuts, and plain
+/// URLs to a `.git` repository, optionally followed by a `#<committish>`
+static GIT_SPEC: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:git\+|git://|github:|gitlab:|bitbucket:|gist:|[a-z]+://[^#]+\.git(?:#|$))")
+        .expect("regex is valid")
+});
 
 /// Methods for parsing a Spec out of string values
 impl Spec {
@@ -25,7 +33,23 @@ impl Spec {
     }
 
     /// Try to parse a tool and version from a string like `<tool>[@<version>].
+    ///
+    /// Strings like `file:<path>`, paths to a tarball or directory, and git URLs instead refer to
+    /// a package that isn't in the registry. They are passed to npm as they are, so their names
+    /// aren't validated.
     pub fn try_from_str(tool_spec: &str) -> Fallible<Self> {
+        if let Some(path) = tool_spec.strip_prefix("file:") {
+            return Self::local_package(path);
+        }
+
+        if GIT_SPEC.is_match(tool_spec) {
+            return Ok(Spec::PackageSource(PackageSource::Git(tool_spec.into())));
+        }
+
+        if is_local_path(tool_spec) {
+            return Self::local_package(tool_spec);
+        }
+
         let captures =
             TOOL_SPEC_PATTERN
                 .captures(tool_spec)
@@ -38,7 +62,8 @@ impl Spec {
         if let Validity::Invalid { errors, .. } = validate(name) {
             return Err(ErrorKind::InvalidToolName {
                 name: name.into(),
-                errors,
+                hint: invalid_name_hint(name, &errors),
+                errors: errors.iter().map(ToString::to_string).collect(),
             }
             .into());
         }
@@ -58,6 +83,26 @@ impl Spec {
         })
     }
 
+    /// Resolve the path of a local tarball or package directory, relative to the current directory
+    fn local_package(path: &str) -> Fallible<Self> {
+        let path = env::current_dir()
+            .with_context(|| ErrorKind::CurrentDirError)?
+            .join(Path::new(path));
+
+        let is_tarball = path.is_file();
+        if !is_tarball && !path.join("package.json").is_file() {
+            return Err(ErrorKind::LocalPackageNotFound { path }.into());
+        }
+
+        let path =
+            dunce::canonicalize(&path).with_context(|| ErrorKind::LocalPackageNotFound { path })?;
+        Ok(Spec::PackageSource(if is_tarball {
+            PackageSource::Tarball(path)
+        } else {
+            PackageSource::Directory(path)
+        }))
+    }
+
     /// Get a valid, sorted `Vec<Spec>` given a `Vec<String>`.
     ///
     /// Accounts for the following error conditions:
@@ -148,11 +193,45 @@ impl Spec {
             (Spec::Yarn(_), Spec::Yarn(_)) => Ordering::Equal,
             (Spec::Yarn(_), _) => Ordering::Less,
             (_, Spec::Yarn(_)) => Ordering::Greater,
-            (Spec::Package(_, _), Spec::Package(_, _)) => Ordering::Equal,
+            (
+                Spec::Package(_, _) | Spec::PackageSource(_),
+                Spec::Package(_, _) | Spec::PackageSource(_),
+            ) => Ordering::Equal,
         }
     }
 }
 
+/// Suggest how to fix the first problem with a tool name that has a known remedy
+fn invalid_name_hint(name: &str, errors: &[ValidityIssue]) -> Option<String> {
+    errors.iter().find_map(|issue| match issue {
+        ValidityIssue::LeadingPeriod | ValidityIssue::LeadingUnderscore => Some(format!(
+            "Names that start with a period or an underscore are only allowed in a scope, as in \
+`@scope/{name}`. To install a package from a directory, use a path like `./{name}`."
+        )),
+        ValidityIssue::LeadingOrTrailingSpaces => Some(format!(
+            "The name has spaces at its start or end, which usually come from the quotes around \
+it. Did you mean `{}`?",
+            name.trim()
+        )),
+        ValidityIssue::InvalidScope => Some(String::from(
+            "Scopes can only contain URL-friendly characters, as in `@my-scope/package`.",
+        )),
+        ValidityIssue::NonUrlSafe => Some(String::from(
+            "To install a package that isn't in the registry, use a path like `./package`, a \
+`file:` spec, or a git URL.",
+        )),
+        _ => None,
+    })
+}
+
+/// Determine if a given string refers to a local tarball or directory rather than a package name
+///
+/// Scoped package names (`@scope/name`) are the only names that contain a slash.
+fn is_local_path(value: &str) -> bool {
+    let is_path = !value.starts_with('@') && (value.contains('/') || value.contains('\\'));
+    is_path || value.ends_with(".tgz") || value.ends_with(".tar.gz")
+}
+
 /// Determine if a given string is "version-like".
 ///
 /// This means it is either 'latest', 'lts', a Version, or a Version Range.
@@ -171,7 +250,8 @@ mod tests {
     mod try_from_str {
         use std::str::FromStr as _;
 
-        use super::super::super::Spec;
+        use super::super::super::{PackageSource, Spec};
+        use crate::error::ErrorKind;
         use crate::version::{VersionSpec, VersionTag};
 
         const LTS: &str = "lts";
@@ -226,6 +306,31 @@ mod tests {
             );
         }
 
+        #[test]
+        fn invalid_names_include_a_hint() {
+            let hint = |spec: &str| match Spec::try_from_str(spec).unwrap_err().kind() {
+                ErrorKind::InvalidToolName { hint, .. } => hint.clone(),
+                other => panic!("unexpected error: {:?}", other),
+            };
+
+            assert!(hint(".eslintrc")
+                .unwrap()
+                .contains("as in `@scope/.eslintrc`"));
+            assert!(hint("_private@1")
+                .unwrap()
+                .contains("use a path like `./_private`"));
+            assert!(hint(" typescript ")
+                .unwrap()
+                .contains("Did you mean `typescript`?"));
+            assert!(hint("@my:scope/package")
+                .unwrap()
+                .contains("Scopes can only contain URL-friendly characters"));
+            assert!(hint("contain:colons")
+                .unwrap()
+                .contains("a `file:` spec, or a git URL"));
+            assert_eq!(hint("node_modules"), None);
+        }
+
         #[test]
         fn parses_bare_yarn() {
             assert_eq!(
@@ -370,6 +475,74 @@ mod tests {
                 )
             );
         }
+
+        #[test]
+        fn parses_local_package() {
+            let dir = tempfile::tempdir().expect("can create temp dir");
+            std::fs::write(dir.path().join("package.json"), "{}").expect("can write manifest");
+
+            assert_eq!(
+                Spec::try_from_str(&format!("file:{}", dir.path().display())).expect("succeeds"),
+                Spec::PackageSource(PackageSource::Directory(
+                    dunce::canonicalize(dir.path()).unwrap()
+                ))
+            );
+        }
+
+        #[test]
+        fn parses_paths_without_file_prefix() {
+            let dir = tempfile::tempdir().expect("can create temp dir");
+            let tarball = dir.path().join("my-tool-1.0.0.tgz");
+            std::fs::write(&tarball, "").expect("can write tarball");
+            std::fs::create_dir(dir.path().join("my-tool")).expect("can create package dir");
+            std::fs::write(dir.path().join("my-tool").join("package.json"), "{}")
+                .expect("can write manifest");
+            let dir = dunce::canonicalize(dir.path()).unwrap();
+
+            assert_eq!(
+                Spec::try_from_str(&tarball.display().to_string()).expect("succeeds"),
+                Spec::PackageSource(PackageSource::Tarball(dir.join("my-tool-1.0.0.tgz")))
+            );
+            assert_eq!(
+                Spec::try_from_str(&format!("{}/my-tool", dir.display())).expect("succeeds"),
+                Spec::PackageSource(PackageSource::Directory(dir.join("my-tool")))
+            );
+            assert!(Spec::try_from_str("./missing-tool-1.0.0.tgz").is_err());
+        }
+
+        #[test]
+        fn parses_git_urls() {
+            for url in [
+                "git+https://github.com/org/tool.git",
+                "git+ssh://git@github.com/org/tool.git#v1.0.0",
+                "git://github.com/org/tool.git",
+                "github:org/tool",
+                "https://github.com/org/tool.git",
+            ] {
+                assert_eq!(
+                    Spec::try_from_str(url).expect("succeeds"),
+                    Spec::PackageSource(PackageSource::Git(url.into()))
+                );
+            }
+        }
+
+        #[test]
+        fn scoped_packages_are_not_paths() {
+            assert_eq!(
+                Spec::try_from_str("@scope/tool@1.0.0").expect("succeeds"),
+                Spec::Package(
+                    "@scope/tool".into(),
+                    VersionSpec::from_str("1.0.0").unwrap()
+                )
+            );
+        }
+
+        #[test]
+        fn rejects_local_package_without_manifest() {
+            let dir = tempfile::tempdir().expect("can create temp dir");
+
+            assert!(Spec::try_from_str(&format!("file:{}", dir.path().display())).is_err());
+        }
     }
 
     mod from_strings {
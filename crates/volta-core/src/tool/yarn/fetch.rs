@@ -7,6 +7,7 @@ use super::super::download_tool_error;
 use super::super::registry::{
     find_unpack_dir, public_registry_package, scoped_public_registry_package,
 };
+use crate::download::DownloadPermit;
 use crate::error::{Context, ErrorKind, Fallible};
 use crate::fs::{create_staging_dir, create_staging_file, rename, set_executable};
 use crate::hook::YarnHooks;
@@ -23,20 +24,22 @@ pub fn fetch(version: &Version, hooks: Option<&YarnHooks>) -> Fallible<()> {
     let yarn_dir = volta_home()?.yarn_inventory_dir();
     let cache_file = yarn_dir.join(Yarn::archive_filename(&version.to_string()));
 
-    let (archive, staging) = match load_cached_distro(&cache_file) {
+    let (archive, staging, _permit) = match load_cached_distro(&cache_file) {
         Some(archive) => {
             debug!(
                 "Loading {} from cached archive at '{}'",
                 tool_version("yarn", version),
                 cache_file.display(),
             );
-            (archive, None)
+            (archive, None, None)
         }
         None => {
+            // The permit is held until unpacking completes, since the archive is streamed
+            let permit = DownloadPermit::acquire();
             let staging = create_staging_file()?;
             let remote_url = determine_remote_url(version, hooks)?;
             let archive = fetch_remote_distro(version, &remote_url, staging.path())?;
-            (archive, Some(staging))
+            (archive, Some(staging), Some(permit))
         }
     };
 
@@ -63,11 +66,7 @@ fn unpack_archive(archive: Box<dyn Archive>, version: &Version) -> Fallible<()>
     let temp = create_staging_dir()?;
     debug!("Unpacking yarn into '{}'", temp.path().display());
 
-    let progress = progress_bar(
-        archive.origin(),
-        &tool_version("yarn", version),
-        archive.compressed_size(),
-    );
+    let mut progress = progress_bar(archive.origin(), "yarn", version, archive.compressed_size());
     let version_string = version.to_string();
 
     archive
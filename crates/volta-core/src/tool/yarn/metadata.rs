@@ -1,8 +1,9 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap};
 
+use super::super::registry::{PackageDetails, PackageIndex};
 use crate::version::version_serde;
 use node_semver::Version;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// The public Yarn index.
 pub struct YarnIndex {
@@ -53,3 +54,58 @@ impl From<RawYarnIndex> for YarnIndex {
         YarnIndex { entries }
     }
 }
+
+impl From<YarnIndex> for PackageIndex {
+    fn from(index: YarnIndex) -> PackageIndex {
+        PackageIndex {
+            tags: HashMap::new(),
+            entries: index
+                .entries
+                .into_iter()
+                .rev()
+                .map(|version| PackageDetails { version })
+                .collect(),
+        }
+    }
+}
+
+/// The Yarn indexes that have been downloaded, keyed by the URL they were downloaded from
+///
+/// Will be stored in `<VOLTA_HOME>/cache/yarn/index.json`
+#[derive(Serialize, Deserialize, Default)]
+#[serde(transparent)]
+pub struct YarnIndexCache(HashMap<String, CachedYarnIndex>);
+
+#[derive(Serialize, Deserialize)]
+pub struct CachedYarnIndex {
+    tags: HashMap<String, Version>,
+    /// The available versions, newest first
+    versions: Vec<Version>,
+}
+
+impl YarnIndexCache {
+    pub fn get(&self, url: &str) -> Option<PackageIndex> {
+        self.0.get(url).map(|cached| PackageIndex {
+            tags: cached.tags.clone(),
+            entries: cached
+                .versions
+                .iter()
+                .map(|version| PackageDetails {
+                    version: version.clone(),
+                })
+                .collect(),
+        })
+    }
+
+    pub fn insert(&mut self, url: &str, index: &PackageIndex) {
+        let cached = CachedYarnIndex {
+            tags: index.tags.clone(),
+            versions: index
+                .entries
+                .iter()
+                .map(|details| details.version.clone())
+                .collect(),
+        };
+        self.0.insert(url.to_owned(), cached);
+    }
+}
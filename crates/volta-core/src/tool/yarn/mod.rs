@@ -1,11 +1,13 @@
 use std::fmt::{self, Display};
 
 use super::{
-    check_fetched, check_shim_reachable, debug_already_fetched, info_fetched, info_installed,
-    info_pinned, info_project_version, FetchStatus, Tool,
+    check_fetched, check_may_fetch, check_shim_reachable, debug_already_fetched, info_fetched,
+    info_installed, info_project_version, offline, quarantine, FetchStatus, Tool,
 };
+use crate::change::{Action, Change, Subject};
 use crate::error::{ErrorKind, Fallible};
-use crate::inventory::yarn_available;
+use crate::inventory::{yarn_available, yarn_versions};
+use crate::layout::volta_home;
 use crate::session::Session;
 use crate::style::tool_version;
 use crate::sync::VoltaLock;
@@ -41,7 +43,21 @@ impl Yarn {
                 debug_already_fetched(self);
                 Ok(())
             }
-            FetchStatus::FetchNeeded(_lock) => fetch::fetch(&self.version, session.hooks()?.yarn()),
+            FetchStatus::FetchNeeded(_lock) => {
+                check_may_fetch(self, session)?;
+                offline::check_may_download("yarn", &self.version, session, yarn_versions)?;
+                offline::stage_archive(
+                    self,
+                    session,
+                    &Yarn::archive_filename(&self.version.to_string()),
+                    volta_home()?.yarn_inventory_dir(),
+                )?;
+                fetch::fetch(&self.version, session.hooks()?.yarn())?;
+                let image_dir = volta_home()?.yarn_image_dir(&self.version.to_string());
+                quarantine::clear_image(&image_dir, session)?;
+                session.record_changes([Change::new(Subject::Image, Action::Created, image_dir)]);
+                Ok(())
+            }
         }
     }
 }
@@ -58,9 +74,10 @@ impl Tool for Yarn {
         let _lock = VoltaLock::acquire();
         self.ensure_fetched(session)?;
 
-        session
+        let change = session
             .toolchain_mut()?
             .set_active_yarn(Some(self.version.clone()))?;
+        session.record_changes(change);
 
         info_installed(&self);
         check_shim_reachable("yarn");
@@ -72,7 +89,7 @@ impl Tool for Yarn {
         }
         Ok(())
     }
-    fn pin(self: Box<Self>, session: &mut Session) -> Fallible<()> {
+    fn pin(self: Box<Self>, session: &mut Session) -> Fallible<String> {
         if session.project()?.is_some() {
             self.ensure_fetched(session)?;
 
@@ -80,8 +97,7 @@ impl Tool for Yarn {
             let project = session.project_mut()?.unwrap();
             project.pin_yarn(Some(self.version.clone()))?;
 
-            info_pinned(self);
-            Ok(())
+            Ok(self.to_string())
         } else {
             Err(ErrorKind::NotInPackage.into())
         }
@@ -1,27 +1,52 @@
 //! Provides resolution of Yarn requirements into specific versions
+//!
+//! The indexes of Yarn versions are cached in the Volta home, as the Node index is, so that
+//! resolving a version doesn't fetch them from the registry (or GitHub) every time.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use super::super::registry::{
-    fetch_npm_registry, public_registry_index, PackageDetails, PackageIndex,
+    fetch_npm_registry_with_expiry, public_registry_index, PackageDetails, PackageIndex,
 };
-use super::super::registry_fetch_error;
-use super::metadata::{RawYarnIndex, YarnIndex};
+use super::super::{cache_expiry, npmrc, offline, registry_fetch_error};
+use super::metadata::{RawYarnIndex, YarnIndex, YarnIndexCache};
+use crate::download::DownloadPermit;
 use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::{create_staging_file, read_file};
 use crate::hook::{RegistryFormat, YarnHooks};
+use crate::inventory::yarn_versions;
+use crate::layout::volta_home;
 use crate::session::Session;
 use crate::style::progress_spinner;
 use crate::version::{parse_version, VersionSpec, VersionTag};
 use attohttpc::Response;
+use fs_utils::ensure_containing_dir_exists;
 use log::debug;
 use node_semver::{Range, Version};
 
 pub fn resolve(matching: VersionSpec, session: &mut Session) -> Fallible<Version> {
+    let matching = session.resolve_alias("yarn", matching)?;
     let hooks = session.hooks()?.yarn();
-    match matching {
-        VersionSpec::Semver(requirement) => resolve_semver(requirement, hooks),
+    let version = match matching {
+        matching if session.is_offline() => {
+            offline::resolve_fetched("yarn", &matching, &yarn_versions()?)
+        }
         VersionSpec::Exact(version) => Ok(version),
+        matching if !session.may_fetch()? => {
+            offline::resolve_installed("yarn", matching, |matching| {
+                offline::resolve_fetched("yarn", &matching, &yarn_versions()?)
+            })
+        }
+        VersionSpec::Semver(requirement) => resolve_semver(requirement, hooks),
         VersionSpec::None => resolve_tag(VersionTag::Latest, hooks),
         VersionSpec::Tag(tag) => resolve_tag(tag, hooks),
-    }
+    }?;
+
+    session.check_policy("yarn", &version)?;
+    Ok(version)
 }
 
 fn resolve_tag(tag: VersionTag, hooks: Option<&YarnHooks>) -> Fallible<Version> {
@@ -75,7 +100,8 @@ fn resolve_semver(matching: Range, hooks: Option<&YarnHooks>) -> Fallible<Versio
 
 fn fetch_yarn_index(package: &str) -> Fallible<(String, PackageIndex)> {
     let url = public_registry_index(package);
-    fetch_npm_registry(url, "Yarn")
+    let index = resolve_yarn_index(&url, fetch_npm_format)?;
+    Ok((url, index))
 }
 
 fn resolve_custom_tag(tag: String) -> Fallible<Version> {
@@ -105,7 +131,8 @@ fn resolve_custom_tag(tag: String) -> Fallible<Version> {
 }
 
 fn resolve_latest_legacy(url: String) -> Fallible<Version> {
-    let response_text = attohttpc::get(&url)
+    let _permit = DownloadPermit::acquire();
+    let response_text = npmrc::get(&url)
         .send()
         .and_then(Response::error_for_status)
         .and_then(Response::text)
@@ -174,16 +201,12 @@ fn resolve_semver_from_registry(matching: Range) -> Fallible<Version> {
 }
 
 fn resolve_semver_legacy(matching: Range, url: String) -> Fallible<Version> {
-    let spinner = progress_spinner(format!("Fetching registry: {}", url));
-    let releases: RawYarnIndex = attohttpc::get(&url)
-        .send()
-        .and_then(Response::error_for_status)
-        .and_then(Response::json)
-        .with_context(registry_fetch_error("Yarn", &url))?;
-    let index = YarnIndex::from(releases);
-    let releases = index.entries;
-    spinner.finish_and_clear();
-    let version_opt = releases.into_iter().rev().find(|v| matching.satisfies(v));
+    let index = resolve_yarn_index(&url, fetch_github_format)?;
+    let version_opt = index
+        .entries
+        .into_iter()
+        .map(|PackageDetails { version }| version)
+        .find(|v| matching.satisfies(v));
 
     match version_opt {
         Some(version) => {
@@ -201,7 +224,7 @@ fn resolve_semver_legacy(matching: Range, url: String) -> Fallible<Version> {
 }
 
 fn resolve_semver_npm(matching: Range, url: String) -> Fallible<Version> {
-    let (url, index) = fetch_npm_registry(url, "Yarn")?;
+    let index = resolve_yarn_index(&url, fetch_npm_format)?;
 
     let details_opt = index
         .entries
@@ -222,3 +245,119 @@ fn resolve_semver_npm(matching: Range, url: String) -> Fallible<Version> {
         .into()),
     }
 }
+
+fn fetch_npm_format(url: &str) -> Fallible<(PackageIndex, SystemTime)> {
+    fetch_npm_registry_with_expiry(url, "Yarn")
+}
+
+/// Fetches the Yarn releases from a registry in the format of the GitHub releases API
+fn fetch_github_format(url: &str) -> Fallible<(PackageIndex, SystemTime)> {
+    let _permit = DownloadPermit::acquire();
+    let spinner = progress_spinner(format!("Fetching registry: {}", url));
+    let (_, headers, response) = npmrc::get(url)
+        .send()
+        .and_then(Response::error_for_status)
+        .with_context(registry_fetch_error("Yarn", url))?
+        .split();
+    let releases: RawYarnIndex = response
+        .json()
+        .with_context(registry_fetch_error("Yarn", url))?;
+
+    spinner.finish_and_clear();
+    Ok((YarnIndex::from(releases).into(), cache_expiry(&headers)))
+}
+
+/// Reads the index from the given URL out of the Yarn index cache, or fetches it if it isn't
+/// cached or the cache has expired
+fn resolve_yarn_index(
+    url: &str,
+    fetch: impl FnOnce(&str) -> Fallible<(PackageIndex, SystemTime)>,
+) -> Fallible<PackageIndex> {
+    let cached = read_cached_opt()?;
+
+    if let Some(index) = cached.as_ref().and_then(|(_, cache)| cache.get(url)) {
+        debug!("Found valid cache of Yarn index from {}", url);
+        return Ok(index);
+    }
+
+    debug!("Yarn index cache for {} was not found or was invalid", url);
+    let (index, expires) = fetch(url)?;
+
+    // Indexes that are already cached keep their expiration, so the whole cache expires when
+    // the first of them does
+    let (expires, mut cache) = match cached {
+        Some((cached_expires, cache)) => (expires.min(cached_expires), cache),
+        None => (expires, YarnIndexCache::default()),
+    };
+    cache.insert(url, &index);
+    write_cache(&cache, expires)?;
+
+    Ok(index)
+}
+
+/// Reads the Yarn index cache, if it exists and hasn't expired
+fn read_cached_opt() -> Fallible<Option<(SystemTime, YarnIndexCache)>> {
+    let expiry_file = volta_home()?.yarn_index_expiry_file();
+    let expiry = read_file(expiry_file).with_context(|| ErrorKind::ReadYarnIndexExpiryError {
+        file: expiry_file.to_owned(),
+    })?;
+
+    let Some(expiry_date) = expiry
+        .map(|date| httpdate::parse_http_date(&date))
+        .transpose()
+        .with_context(|| ErrorKind::ParseYarnIndexExpiryError)?
+        .filter(|expiry_date| SystemTime::now() < *expiry_date)
+    else {
+        return Ok(None);
+    };
+
+    let index_file = volta_home()?.yarn_index_file();
+    let cached = read_file(index_file).with_context(|| ErrorKind::ReadYarnIndexCacheError {
+        file: index_file.to_owned(),
+    })?;
+
+    let Some(json) = cached else {
+        return Ok(None);
+    };
+
+    let cache =
+        serde_json::de::from_str(&json).with_context(|| ErrorKind::ParseYarnIndexCacheError)?;
+    Ok(Some((expiry_date, cache)))
+}
+
+fn write_cache(cache: &YarnIndexCache, expires: SystemTime) -> Fallible<()> {
+    let home = volta_home()?;
+
+    let json =
+        serde_json::to_string(cache).with_context(|| ErrorKind::StringifyYarnIndexCacheError)?;
+    write_cache_file(home.yarn_index_file(), &json, |file| {
+        ErrorKind::WriteYarnIndexCacheError { file }
+    })?;
+    write_cache_file(
+        home.yarn_index_expiry_file(),
+        &httpdate::fmt_http_date(expires),
+        |file| ErrorKind::WriteYarnIndexExpiryError { file },
+    )
+}
+
+/// Writes one of the cache files through a staging file, so it is never left partly written
+fn write_cache_file(
+    path: &Path,
+    contents: &str,
+    error: impl Fn(PathBuf) -> ErrorKind,
+) -> Fallible<()> {
+    let staged = create_staging_file()?;
+    let mut staged_file: &File = staged.as_file();
+    staged_file
+        .write_all(contents.as_bytes())
+        .with_context(|| error(staged.path().to_path_buf()))?;
+
+    ensure_containing_dir_exists(&path).with_context(|| ErrorKind::ContainingDirError {
+        path: path.to_owned(),
+    })?;
+    staged
+        .persist(path)
+        .with_context(|| error(path.to_owned()))?;
+
+    Ok(())
+}
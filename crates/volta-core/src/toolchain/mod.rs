@@ -1,13 +1,16 @@
-use std::fs::write;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 
+use crate::change::{Change, Subject};
 use crate::error::{Context, ErrorKind, Fallible};
-use crate::fs::touch;
+use crate::fs::{record_open, touch};
 use crate::layout::volta_home;
 use crate::platform::PlatformSpec;
 use log::debug;
 use node_semver::Version;
 use once_cell::unsync::OnceCell;
 use readext::ReadExt;
+use tempfile::NamedTempFile;
 
 pub mod serial;
 
@@ -36,24 +39,41 @@ impl LazyToolchain {
     }
 }
 
+/// The number of times a change to the default platform is re-applied when another process
+/// modifies the platform file concurrently
+const MAX_SAVE_ATTEMPTS: usize = 5;
+
 pub struct Toolchain {
     platform: Option<PlatformSpec>,
+    /// The location of the default platform file
+    file: PathBuf,
+    /// The contents of the platform file when it was last read or written, used to detect changes
+    /// made by other processes
+    snapshot: String,
 }
 
 impl Toolchain {
     fn current() -> Fallible<Toolchain> {
-        let path = volta_home()?.default_platform_file();
-        let src = touch(path)
-            .and_then(|mut file| file.read_into_string())
-            .with_context(|| ErrorKind::ReadPlatformError {
-                file: path.to_owned(),
-            })?;
-
-        let platform: Option<PlatformSpec> = serial::Platform::try_from(src)?.into();
-        if platform.is_some() {
-            debug!("Found default configuration at '{}'", path.display());
+        let toolchain = Toolchain::load(volta_home()?.default_platform_file())?;
+        if toolchain.platform.is_some() {
+            debug!(
+                "Found default configuration at '{}'",
+                toolchain.file.display()
+            );
         }
-        Ok(Toolchain { platform })
+        Ok(toolchain)
+    }
+
+    fn load(path: &Path) -> Fallible<Toolchain> {
+        record_open("default platform", path);
+        let snapshot = read_platform_file(path)?;
+        let platform = serial::Platform::try_from(snapshot.clone())?.into();
+
+        Ok(Toolchain {
+            platform,
+            file: path.to_owned(),
+            snapshot,
+        })
     }
 
     pub fn platform(&self) -> Option<&PlatformSpec> {
@@ -61,93 +81,264 @@ impl Toolchain {
     }
 
     /// Set the active Node version in the default platform file.
-    pub fn set_active_node(&mut self, node_version: &Version) -> Fallible<()> {
-        let mut dirty = false;
-
-        match self.platform.as_mut() {
+    pub fn set_active_node(&mut self, node_version: &Version) -> Fallible<Option<Change>> {
+        self.update(|platform| match platform {
             Some(platform) => {
                 if platform.node != *node_version {
                     platform.node = node_version.clone();
-                    dirty = true;
+                    Ok(true)
+                } else {
+                    Ok(false)
                 }
             }
             None => {
-                self.platform = Some(PlatformSpec {
+                *platform = Some(PlatformSpec {
                     node: node_version.clone(),
                     npm: None,
                     pnpm: None,
                     yarn: None,
                 });
-                dirty = true;
+                Ok(true)
             }
-        }
+        })
+    }
 
-        if dirty {
-            self.save()?;
-        }
+    /// Set the active Yarn version in the default platform file.
+    pub fn set_active_yarn(&mut self, yarn: Option<Version>) -> Fallible<Option<Change>> {
+        self.update(|platform| {
+            set_platform_tool(platform, "Yarn", &yarn, |platform| &mut platform.yarn)
+        })
+    }
+
+    /// Set the active pnpm version in the default platform file.
+    pub fn set_active_pnpm(&mut self, pnpm: Option<Version>) -> Fallible<Option<Change>> {
+        self.update(|platform| {
+            set_platform_tool(platform, "pnpm", &pnpm, |platform| &mut platform.pnpm)
+        })
+    }
 
-        Ok(())
+    /// Set the active Npm version in the default platform file.
+    pub fn set_active_npm(&mut self, npm: Option<Version>) -> Fallible<Option<Change>> {
+        self.update(|platform| {
+            set_platform_tool(platform, "npm", &npm, |platform| &mut platform.npm)
+        })
     }
 
-    /// Set the active Yarn version in the default platform file.
-    pub fn set_active_yarn(&mut self, yarn: Option<Version>) -> Fallible<()> {
-        if let Some(platform) = self.platform.as_mut() {
-            if platform.yarn != yarn {
-                platform.yarn = yarn;
-                self.save()?;
+    /// Applies a change to the default platform and saves it, if the change modified the platform
+    ///
+    /// The global lock is advisory, so another process may have written the platform file since we
+    /// read it. Rather than overwrite that process's change, we re-read the file and re-apply our
+    /// change on top of it, giving up after a bounded number of attempts.
+    ///
+    /// Returns the change to the platform file, if it was written.
+    fn update<F>(&mut self, mut change: F) -> Fallible<Option<Change>>
+    where
+        F: FnMut(&mut Option<PlatformSpec>) -> Fallible<bool>,
+    {
+        for _ in 0..MAX_SAVE_ATTEMPTS {
+            // An empty file is created when the platform is first read, so it doesn't count
+            let existed = !self.snapshot.is_empty();
+            if !change(&mut self.platform)? {
+                return Ok(None);
             }
-        } else if yarn.is_some() {
-            return Err(ErrorKind::NoDefaultNodeVersion {
-                tool: "Yarn".into(),
+            if self.save()? {
+                return Ok(Some(Change::written(
+                    Subject::Config,
+                    self.file.clone(),
+                    existed,
+                )));
             }
-            .into());
+
+            debug!(
+                "Default configuration at '{}' was changed by another process, retrying",
+                self.file.display()
+            );
+            *self = Toolchain::load(&self.file)?;
         }
 
-        Ok(())
+        Err(ErrorKind::PlatformWriteConflict {
+            file: self.file.clone(),
+        }
+        .into())
     }
 
-    /// Set the active pnpm version in the default platform file.
-    pub fn set_active_pnpm(&mut self, pnpm: Option<Version>) -> Fallible<()> {
-        if let Some(platform) = self.platform.as_mut() {
-            if platform.pnpm != pnpm {
-                platform.pnpm = pnpm;
-                self.save()?;
-            }
-        } else if pnpm.is_some() {
-            return Err(ErrorKind::NoDefaultNodeVersion {
-                tool: "pnpm".into(),
-            }
-            .into());
+    /// Writes the platform to the platform file, unless the file has changed since it was read
+    ///
+    /// Returns `false` if the file was changed by another process, in which case nothing is written.
+    fn save(&mut self) -> Fallible<bool> {
+        if read_platform_file(&self.file)? != self.snapshot {
+            return Ok(false);
         }
 
-        Ok(())
+        let src = match &self.platform {
+            Some(platform) => serial::Platform::of(platform).into_json()?,
+            None => String::from("{}"),
+        };
+
+        write_platform_file(&self.file, &src).with_context(|| ErrorKind::WritePlatformError {
+            file: self.file.clone(),
+        })?;
+        self.snapshot = src;
+
+        Ok(true)
     }
+}
 
-    /// Set the active Npm version in the default platform file.
-    pub fn set_active_npm(&mut self, npm: Option<Version>) -> Fallible<()> {
-        if let Some(platform) = self.platform.as_mut() {
-            if platform.npm != npm {
-                platform.npm = npm;
-                self.save()?;
+/// Sets the version of a tool other than Node in the platform, returning whether it changed
+///
+/// Errors if a version is set when there is no default platform, as Node must be set first.
+fn set_platform_tool<F>(
+    platform: &mut Option<PlatformSpec>,
+    tool: &str,
+    version: &Option<Version>,
+    field: F,
+) -> Fallible<bool>
+where
+    F: FnOnce(&mut PlatformSpec) -> &mut Option<Version>,
+{
+    match platform.as_mut() {
+        Some(platform) => {
+            let current = field(platform);
+            if current != version {
+                *current = version.clone();
+                Ok(true)
+            } else {
+                Ok(false)
             }
-        } else if npm.is_some() {
-            return Err(ErrorKind::NoDefaultNodeVersion { tool: "npm".into() }.into());
         }
-
-        Ok(())
+        None if version.is_some() => {
+            Err(ErrorKind::NoDefaultNodeVersion { tool: tool.into() }.into())
+        }
+        None => Ok(false),
     }
+}
 
-    pub fn save(&self) -> Fallible<()> {
-        let path = volta_home()?.default_platform_file();
-        let result = match &self.platform {
-            Some(platform) => {
-                let src = serial::Platform::of(platform).into_json()?;
-                write(path, src)
-            }
-            None => write(path, "{}"),
-        };
-        result.with_context(|| ErrorKind::WritePlatformError {
+fn read_platform_file(path: &Path) -> Fallible<String> {
+    touch(path)
+        .and_then(|mut file| file.read_into_string())
+        .with_context(|| ErrorKind::ReadPlatformError {
             file: path.to_owned(),
         })
+}
+
+/// Writes the platform file atomically, so that concurrent readers never see a partial file
+fn write_platform_file(path: &Path, src: &str) -> io::Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut staging = NamedTempFile::new_in(dir)?;
+    staging.write_all(src.as_bytes())?;
+    staging.persist(path).map_err(|error| error.error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::open_count;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const NODE_ONLY: &str = r#"{
+  "node": {
+    "runtime": "18.0.0",
+    "npm": null
+  },
+  "pnpm": null,
+  "yarn": null
+}"#;
+
+    const NODE_AND_YARN: &str = r#"{
+  "node": {
+    "runtime": "18.0.0",
+    "npm": null
+  },
+  "pnpm": null,
+  "yarn": "1.22.0"
+}"#;
+
+    fn version(v: &str) -> Version {
+        Version::parse(v).unwrap()
+    }
+
+    #[test]
+    fn saves_change() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("platform.json");
+        fs::write(&file, NODE_ONLY).unwrap();
+
+        let mut toolchain = Toolchain::load(&file).unwrap();
+        toolchain.set_active_yarn(Some(version("1.22.0"))).unwrap();
+
+        assert_eq!(fs::read_to_string(&file).unwrap(), NODE_AND_YARN);
+    }
+
+    #[test]
+    fn setting_several_tools_loads_the_platform_once() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("platform.json");
+        fs::write(&file, NODE_ONLY).unwrap();
+
+        let mut toolchain = Toolchain::load(&file).unwrap();
+        toolchain.set_active_node(&version("20.0.0")).unwrap();
+        toolchain.set_active_npm(Some(version("9.0.0"))).unwrap();
+        toolchain.set_active_yarn(Some(version("1.22.0"))).unwrap();
+
+        assert_eq!(open_count(&file), 1);
+    }
+
+    #[test]
+    fn reapplies_change_after_concurrent_write() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("platform.json");
+        fs::write(&file, NODE_ONLY).unwrap();
+
+        let mut toolchain = Toolchain::load(&file).unwrap();
+        // Another process sets the default Yarn after we read the platform
+        fs::write(&file, NODE_AND_YARN).unwrap();
+        toolchain.set_active_npm(Some(version("9.0.0"))).unwrap();
+
+        let saved = Toolchain::load(&file).unwrap();
+        let platform = saved.platform().unwrap();
+        assert_eq!(platform.node, version("18.0.0"));
+        assert_eq!(platform.npm, Some(version("9.0.0")));
+        assert_eq!(platform.yarn, Some(version("1.22.0")));
+        assert_eq!(toolchain.platform(), saved.platform());
+    }
+
+    #[test]
+    fn skips_write_when_concurrent_change_matches() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("platform.json");
+        fs::write(&file, NODE_ONLY).unwrap();
+
+        let mut toolchain = Toolchain::load(&file).unwrap();
+        // Another process writes the same change, with different formatting
+        let concurrent = NODE_AND_YARN.replace('\n', "");
+        fs::write(&file, &concurrent).unwrap();
+        toolchain.set_active_yarn(Some(version("1.22.0"))).unwrap();
+
+        assert_eq!(fs::read_to_string(&file).unwrap(), concurrent);
+    }
+
+    #[test]
+    fn gives_up_after_repeated_conflicts() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("platform.json");
+        fs::write(&file, NODE_ONLY).unwrap();
+
+        let mut toolchain = Toolchain::load(&file).unwrap();
+        let mut attempts = 0;
+        let result = toolchain.update(|platform| {
+            // Simulate another process writing the file between every read and write
+            attempts += 1;
+            fs::write(&file, format!("{}{}", NODE_ONLY, " ".repeat(attempts))).unwrap();
+            platform.as_mut().unwrap().npm = Some(version("9.0.0"));
+            Ok(true)
+        });
+
+        assert!(matches!(
+            result.unwrap_err().kind(),
+            ErrorKind::PlatformWriteConflict { .. }
+        ));
+        assert_eq!(attempts, MAX_SAVE_ATTEMPTS);
     }
 }
@@ -0,0 +1,373 @@
+//! Provides local usage counters for the tool versions that Volta runs
+//!
+//! When the `usage.stats` setting is enabled, each time a shim checks out a platform, the
+//! versions it runs with are counted. The counters never leave the machine: they are only read by
+//! `volta list --stats`.
+//!
+//! Recording must never slow down or fail the command being run, so each invocation only appends a
+//! line to a pending file. The pending entries are folded into the counter file once enough of them
+//! have accumulated or enough time has passed, and any failure along the way is only logged. The
+//! folding is done while holding the lock on the Volta directory, so that invocations running at
+//! the same time can't overwrite each other's counts; when another process holds the lock, it is
+//! left for a later invocation rather than waiting.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write as _};
+use std::path::Path;
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::layout::volta_home;
+use crate::platform::{Image, OptionalTool};
+use crate::session::Session;
+use crate::sync::VoltaLock;
+use chrono::{DateTime, Utc};
+use log::debug;
+use node_semver::Version;
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+
+/// How long pending entries may wait before they are folded into the counter file
+const COMPACT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The size at which the pending file is folded into the counter file, regardless of its age
+const MAX_PENDING_BYTES: u64 = 16 * 1024;
+
+/// The usage of a single tool version
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageRecord {
+    /// The number of times the version has been run
+    pub count: u64,
+    /// When the version was last run, in seconds since the Unix epoch
+    pub last_used: i64,
+}
+
+impl UsageRecord {
+    /// The date on which the version was last run, formatted as `YYYY-MM-DD` (UTC)
+    pub fn last_used_date(&self) -> String {
+        DateTime::<Utc>::from_timestamp(self.last_used, 0)
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// The usage counters for every recorded tool version, keyed by tool name and then by version
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq)]
+pub struct UsageStats {
+    #[serde(flatten)]
+    tools: BTreeMap<String, BTreeMap<String, UsageRecord>>,
+}
+
+impl UsageStats {
+    /// Loads the recorded usage, including entries that are still pending
+    pub fn current() -> Fallible<Self> {
+        let home = volta_home()?;
+        let mut stats =
+            read_stats(home.usage_file()).with_context(|| ErrorKind::ReadUsageStatsError {
+                file: home.usage_file().to_owned(),
+            })?;
+
+        match fs::read_to_string(home.usage_pending_file()) {
+            Ok(pending) => stats.apply_pending(&pending),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => {
+                return Err(error).with_context(|| ErrorKind::ReadUsageStatsError {
+                    file: home.usage_pending_file().to_owned(),
+                })
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Returns the usage of a specific tool version, if it has ever been recorded
+    pub fn get(&self, tool: &str, version: &Version) -> Option<&UsageRecord> {
+        self.tools.get(tool)?.get(&version.to_string())
+    }
+
+    fn add(&mut self, tool: &str, version: &str, timestamp: i64) {
+        let record = self
+            .tools
+            .entry(tool.to_owned())
+            .or_default()
+            .entry(version.to_owned())
+            .or_default();
+
+        record.count += 1;
+        record.last_used = record.last_used.max(timestamp);
+    }
+
+    /// Folds the lines from the pending file into the counters, skipping any that are malformed
+    fn apply_pending(&mut self, pending: &str) {
+        for line in pending.lines() {
+            let mut parts = line.split_whitespace();
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some(tool), Some(version), Some(timestamp), None) => match timestamp.parse() {
+                    Ok(timestamp) => self.add(tool, version, timestamp),
+                    Err(_) => debug!("Skipping malformed usage entry: '{}'", line),
+                },
+                _ => debug!("Skipping malformed usage entry: '{}'", line),
+            }
+        }
+    }
+}
+
+/// Records that the tools in the image were used, if the usage counters are enabled
+///
+/// Node is always counted, along with the package manager being run (if any). Failures are logged
+/// and otherwise ignored, so that recording usage can never affect the command itself.
+pub(crate) fn record(session: &Session, image: &Image, manager: Option<OptionalTool>) {
+    match session.settings() {
+        Ok(settings) if settings.usage_stats() => {}
+        Ok(_) => return,
+        Err(error) => {
+            debug!(
+                "Not recording tool usage, as the settings could not be loaded: {}",
+                error
+            );
+            return;
+        }
+    }
+
+    let mut entries = vec![("node", &image.node.value)];
+    let manager_version = match manager {
+        Some(OptionalTool::Npm) => image.npm.as_ref(),
+        Some(OptionalTool::Pnpm) => image.pnpm.as_ref(),
+        Some(OptionalTool::Yarn) => image.yarn.as_ref(),
+        None => None,
+    };
+    if let (Some(manager), Some(version)) = (manager, manager_version) {
+        entries.push((manager.as_str(), &version.value));
+    }
+
+    let result = volta_home()
+        .map_err(|error| error.to_string())
+        .and_then(|home| {
+            record_entries(
+                home.usage_file(),
+                home.usage_pending_file(),
+                &entries,
+                now(),
+            )
+            .map_err(|error| error.to_string())
+        });
+
+    if let Err(error) = result {
+        debug!("Could not record tool usage: {}", error);
+    }
+}
+
+fn record_entries(
+    usage_file: &Path,
+    pending_file: &Path,
+    entries: &[(&str, &Version)],
+    timestamp: i64,
+) -> io::Result<()> {
+    let mut lines = String::new();
+    for (tool, version) in entries {
+        let _ = writeln!(lines, "{} {} {}", tool, version, timestamp);
+    }
+
+    // Appending a single short write keeps concurrent invocations from interleaving their entries
+    if let Some(parent) = pending_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(pending_file)?
+        .write_all(lines.as_bytes())?;
+
+    let pending_bytes = fs::metadata(pending_file)?.len();
+    let since_last_compaction = fs::metadata(usage_file)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .map(|modified| {
+            SystemTime::now()
+                .duration_since(modified)
+                .unwrap_or_default()
+        });
+
+    if should_compact(pending_bytes, since_last_compaction) {
+        match VoltaLock::try_acquire() {
+            Ok(Some(_lock)) => compact(usage_file, pending_file)?,
+            Ok(None) => debug!("Not compacting tool usage, as another process holds the lock"),
+            Err(error) => debug!("Not compacting tool usage: {}", error),
+        }
+    }
+
+    Ok(())
+}
+
+/// Determines whether the pending entries should be folded into the counter file
+///
+/// `since_last_compaction` is `None` when the counter file doesn't exist yet.
+fn should_compact(pending_bytes: u64, since_last_compaction: Option<Duration>) -> bool {
+    if pending_bytes == 0 {
+        return false;
+    }
+
+    match since_last_compaction {
+        None => true,
+        Some(elapsed) => pending_bytes >= MAX_PENDING_BYTES || elapsed >= COMPACT_INTERVAL,
+    }
+}
+
+/// Folds the pending entries into the counter file
+///
+/// The caller must hold the `VoltaLock`, as the counter file is read, updated and written back.
+fn compact(usage_file: &Path, pending_file: &Path) -> io::Result<()> {
+    // Claiming the pending file with a rename means that entries appended by other invocations
+    // while compacting go to a new pending file, rather than being removed along with this one.
+    let claimed = pending_file.with_extension(format!("pending.{}", process::id()));
+    fs::rename(pending_file, &claimed)?;
+
+    let pending = fs::read_to_string(&claimed)?;
+    let mut stats = read_stats(usage_file)?;
+    stats.apply_pending(&pending);
+
+    let contents = serde_json::to_string_pretty(&stats)?;
+    let dir = usage_file.parent().unwrap_or_else(|| Path::new("."));
+    let mut staging = NamedTempFile::new_in(dir)?;
+    staging.write_all(contents.as_bytes())?;
+    staging.persist(usage_file).map_err(|error| error.error)?;
+
+    fs::remove_file(claimed)
+}
+
+/// Reads the counter file, resetting the counters if the file is corrupted
+fn read_stats(usage_file: &Path) -> io::Result<UsageStats> {
+    let contents = match fs::read_to_string(usage_file) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(UsageStats::default()),
+        Err(error) => return Err(error),
+    };
+
+    Ok(serde_json::from_str(&contents).unwrap_or_else(|error| {
+        debug!(
+            "Resetting usage counters, as '{}' could not be parsed: {}",
+            usage_file.display(),
+            error
+        );
+        UsageStats::default()
+    }))
+}
+
+/// The current time, in seconds since the Unix epoch
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn compacts_when_no_counter_file_exists() {
+        assert!(should_compact(10, None));
+    }
+
+    #[test]
+    fn never_compacts_without_pending_entries() {
+        assert!(!should_compact(0, None));
+        assert!(!should_compact(0, Some(COMPACT_INTERVAL * 2)));
+    }
+
+    #[test]
+    fn throttles_recent_small_batches() {
+        assert!(!should_compact(100, Some(Duration::from_secs(60))));
+    }
+
+    #[test]
+    fn compacts_once_the_interval_has_passed() {
+        assert!(should_compact(100, Some(COMPACT_INTERVAL)));
+    }
+
+    #[test]
+    fn compacts_large_batches_early() {
+        assert!(should_compact(
+            MAX_PENDING_BYTES,
+            Some(Duration::from_secs(1))
+        ));
+    }
+
+    #[test]
+    fn last_used_date_is_formatted_in_utc() {
+        let record = UsageRecord {
+            count: 1,
+            last_used: 1_700_000_000,
+        };
+        assert_eq!(record.last_used_date(), "2023-11-14");
+    }
+
+    #[test]
+    fn apply_pending_counts_entries() {
+        let mut stats = UsageStats::default();
+        stats.apply_pending("node 18.0.0 100\nyarn 1.22.0 150\nnode 18.0.0 200\nnot an entry\n");
+
+        let node = stats.get("node", &Version::parse("18.0.0").unwrap());
+        assert_eq!(
+            node,
+            Some(&UsageRecord {
+                count: 2,
+                last_used: 200
+            })
+        );
+        let yarn = stats.get("yarn", &Version::parse("1.22.0").unwrap());
+        assert_eq!(yarn.map(|record| record.count), Some(1));
+    }
+
+    #[test]
+    fn compact_merges_pending_entries() {
+        let dir = tempdir().unwrap();
+        let usage_file = dir.path().join("usage.json");
+        let pending_file = dir.path().join("usage.pending");
+
+        fs::write(
+            &usage_file,
+            r#"{"node":{"18.0.0":{"count":3,"lastUsed":50}}}"#,
+        )
+        .unwrap();
+        fs::write(&pending_file, "node 18.0.0 100\nnode 20.0.0 120\n").unwrap();
+
+        compact(&usage_file, &pending_file).unwrap();
+
+        assert!(!pending_file.exists());
+        let stats = read_stats(&usage_file).unwrap();
+        let node_18 = stats
+            .get("node", &Version::parse("18.0.0").unwrap())
+            .unwrap();
+        assert_eq!(node_18.count, 4);
+        assert_eq!(node_18.last_used, 100);
+        let node_20 = stats
+            .get("node", &Version::parse("20.0.0").unwrap())
+            .unwrap();
+        assert_eq!(node_20.count, 1);
+    }
+
+    #[test]
+    fn corrupted_counter_file_is_reset() {
+        let dir = tempdir().unwrap();
+        let usage_file = dir.path().join("usage.json");
+        let pending_file = dir.path().join("usage.pending");
+
+        fs::write(&usage_file, "{ not json").unwrap();
+        fs::write(&pending_file, "node 18.0.0 100\n").unwrap();
+
+        compact(&usage_file, &pending_file).unwrap();
+
+        let stats = read_stats(&usage_file).unwrap();
+        let node = stats
+            .get("node", &Version::parse("18.0.0").unwrap())
+            .unwrap();
+        assert_eq!(node.count, 1);
+    }
+}
@@ -36,6 +36,20 @@ pub enum VersionTag {
     Custom(String),
 }
 
+impl VersionSpec {
+    /// The registry dist-tag requested by this specifier, if any
+    ///
+    /// `latest` is what the registry resolves to when no version is given, so it isn't treated
+    /// as an explicit request for a tag.
+    pub fn dist_tag(&self) -> Option<&str> {
+        match self {
+            VersionSpec::Tag(VersionTag::Lts) => Some("lts"),
+            VersionSpec::Tag(VersionTag::Custom(tag)) => Some(tag),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for VersionSpec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -61,6 +75,8 @@ impl FromStr for VersionSpec {
     type Err = VoltaError;
 
     fn from_str(s: &str) -> Fallible<Self> {
+        let s = s.trim();
+
         if let Ok(version) = parse_version(s) {
             Ok(VersionSpec::Exact(version))
         } else if let Ok(req) = parse_requirements(s) {
@@ -87,23 +103,34 @@ impl FromStr for VersionTag {
 
 pub fn parse_requirements(s: impl AsRef<str>) -> Fallible<Range> {
     let s = s.as_ref();
-    serial::parse_requirements(s)
+    let normalized = strip_prefix_v(s.trim());
+
+    // Only a single `v` is allowed, and it must be directly followed by the range
+    if normalized.starts_with(|c: char| c == 'v' || c == 'V' || c.is_whitespace()) {
+        return Err(ErrorKind::VersionParseError { version: s.into() }.into());
+    }
+
+    serial::parse_requirements(normalized)
         .with_context(|| ErrorKind::VersionParseError { version: s.into() })
 }
 
 pub fn parse_version(s: impl AsRef<str>) -> Fallible<Version> {
     let s = s.as_ref();
-    s.parse()
+    let trimmed = s.trim();
+    let normalized = strip_prefix_v(trimmed.strip_prefix('=').unwrap_or(trimmed));
+
+    if !normalized.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(ErrorKind::VersionParseError { version: s.into() }.into());
+    }
+
+    normalized
+        .parse()
         .with_context(|| ErrorKind::VersionParseError { version: s.into() })
 }
 
-// remove the leading 'v' from the version string, if present
-fn trim_version(s: &str) -> &str {
-    let s = s.trim();
-    match s.strip_prefix('v') {
-        Some(stripped) => stripped,
-        None => s,
-    }
+// remove a single leading 'v' or 'V' from the version string, if present
+fn strip_prefix_v(s: &str) -> &str {
+    s.strip_prefix(['v', 'V']).unwrap_or(s)
 }
 
 // custom serialization and de-serialization for Version
@@ -128,7 +155,7 @@ pub mod version_serde {
         where
             E: Error,
         {
-            Version::parse(super::trim_version(value)).map_err(Error::custom)
+            super::parse_version(value).map_err(Error::custom)
         }
     }
 
@@ -170,9 +197,7 @@ pub mod option_version_serde {
     {
         let s: Option<String> = Option::deserialize(deserializer)?;
         if let Some(v) = s {
-            return Ok(Some(
-                Version::parse(super::trim_version(&v)).map_err(Error::custom)?,
-            ));
+            return Ok(Some(super::parse_version(v).map_err(Error::custom)?));
         }
         Ok(None)
     }
@@ -197,3 +222,78 @@ pub mod hashmap_version_serde {
         Ok(m.into_iter().map(|(k, Wrapper(v))| (k, v)).collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dist_tags() {
+        assert_eq!(
+            "beta".parse::<VersionSpec>().unwrap(),
+            VersionSpec::Tag(VersionTag::Custom("beta".into()))
+        );
+        assert_eq!(
+            "latest".parse::<VersionSpec>().unwrap(),
+            VersionSpec::Tag(VersionTag::Latest)
+        );
+        assert_eq!(
+            "5.4.0-beta".parse::<VersionSpec>().unwrap(),
+            VersionSpec::Exact(Version::parse("5.4.0-beta").unwrap())
+        );
+    }
+
+    #[test]
+    fn dist_tag_is_only_reported_for_explicit_tags() {
+        let spec = |s: &str| s.parse::<VersionSpec>().unwrap();
+
+        assert_eq!(spec("beta").dist_tag(), Some("beta"));
+        assert_eq!(spec("next").dist_tag(), Some("next"));
+        assert_eq!(spec("lts").dist_tag(), Some("lts"));
+        assert_eq!(spec("latest").dist_tag(), None);
+        assert_eq!(spec("5.4.0").dist_tag(), None);
+        assert_eq!(spec("^5").dist_tag(), None);
+        assert_eq!(VersionSpec::None.dist_tag(), None);
+    }
+
+    #[test]
+    fn normalizes_version_strings() {
+        let exact = |s: &str| VersionSpec::Exact(Version::parse(s).unwrap());
+        let range = |s: &str| VersionSpec::Semver(Range::parse(s).unwrap());
+
+        let accepted = [
+            ("18.16.0", exact("18.16.0")),
+            ("v18.16.0", exact("18.16.0")),
+            ("V18.16.0", exact("18.16.0")),
+            ("  18.16.0  ", exact("18.16.0")),
+            ("\tv18.16.0\n", exact("18.16.0")),
+            ("=18.16.0", exact("18.16.0")),
+            ("=v18.16.0", exact("18.16.0")),
+            ("v18.16.0-beta.1", exact("18.16.0-beta.1")),
+            ("18", range("18")),
+            ("v18", range("18")),
+            ("V18.16", range("18.16")),
+            (" ^18.16 ", range("^18.16")),
+            (">=18 <20", range(">=18 <20")),
+            (" latest ", VersionSpec::Tag(VersionTag::Latest)),
+            ("lts", VersionSpec::Tag(VersionTag::Lts)),
+        ];
+
+        for (input, expected) in accepted {
+            assert_eq!(
+                input.parse::<VersionSpec>().unwrap(),
+                expected,
+                "{:?}",
+                input
+            );
+        }
+
+        for input in ["vv18.16.0", "v 18.16.0", "==18.16.0", "18.16.0.1", "", "  "] {
+            assert!(parse_version(input).is_err(), "{:?}", input);
+        }
+
+        for input in ["vv18", "v 18", "Vv18", ""] {
+            assert!(parse_requirements(input).is_err(), "{:?}", input);
+        }
+    }
+}
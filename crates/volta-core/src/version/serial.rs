@@ -12,8 +12,6 @@ use node_semver::{Range, SemverError};
 // then serializing them to pass to `npm view`, they need to be handled in
 // a Node-compatible way (or we get the wrong version info returned).
 pub fn parse_requirements(src: &str) -> Result<Range, SemverError> {
-    let src = src.trim().trim_start_matches('v');
-
     Range::parse(src)
 }
 
@@ -29,10 +27,6 @@ pub mod tests {
             parse_requirements("1.2.3").unwrap(),
             Range::parse("=1.2.3").unwrap()
         );
-        assert_eq!(
-            parse_requirements("v1.5").unwrap(),
-            Range::parse("=1.5").unwrap()
-        );
         assert_eq!(
             parse_requirements("=1.2.3").unwrap(),
             Range::parse("=1.2.3").unwrap()
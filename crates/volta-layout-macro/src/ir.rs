@@ -79,9 +79,17 @@ impl Ir {
             impl #name {
                 /// Creates all subdirectories in this directory layout.
                 pub fn create(&self) -> ::std::io::Result<()> {
-                    #(::std::fs::create_dir_all(self.#dir_names())?;)*
+                    for dir in self.dirs() {
+                        ::std::fs::create_dir_all(dir)?;
+                    }
                     ::std::result::Result::Ok(())
                 }
+
+                /// Returns all subdirectories in this directory layout, in the order that
+                /// `create` creates them.
+                pub fn dirs(&self) -> ::std::vec::Vec<&::std::path::Path> {
+                    ::std::vec![#(self.#dir_names()),*]
+                }
             }
         }
     }
@@ -113,6 +121,28 @@ impl Ir {
         }
     }
 
+    fn to_all_paths_method(&self) -> TokenStream {
+        let name = &self.name;
+
+        let entries = self.field_names().map(|field_name| {
+            let logical_name = LitStr::new(&field_name.to_string(), field_name.span());
+
+            quote! {
+                (#logical_name, self.#field_name.clone())
+            }
+        });
+
+        quote! {
+            impl #name {
+                /// Returns every path in this directory layout, along with the name of its
+                /// accessor method. The root path is not included.
+                pub fn all_paths(&self) -> ::std::vec::Vec<(&'static str, ::std::path::PathBuf)> {
+                    ::std::vec![#(#entries),*]
+                }
+            }
+        }
+    }
+
     fn to_ctor(&self) -> TokenStream {
         let name = &self.name;
         let root = Ident::new("root", self.name.span());
@@ -152,12 +182,14 @@ impl Ir {
         let ctor = self.to_ctor();
         let item_methods = self.to_item_methods();
         let create_method = self.to_create_method();
+        let all_paths_method = self.to_all_paths_method();
 
         quote! {
             #struct_decl
             #ctor
             #item_methods
             #create_method
+            #all_paths_method
         }
     }
 }
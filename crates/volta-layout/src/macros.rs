@@ -9,3 +9,71 @@ macro_rules! path_buf {
         }
     }
 }
+
+/// Generates tests for the paths of each layout, as `module: Layout` pairs: every path must be
+/// distinct, contained in the root, and in the same place relative to any root.
+#[cfg(test)]
+macro_rules! layout_tests {
+    ($($module:ident: $layout:ident),+ $(,)?) => {
+        $(
+            mod $module {
+                use std::collections::HashSet;
+                use std::path::{Path, PathBuf};
+
+                use super::super::$layout;
+
+                fn relative_paths(root: &str) -> Vec<(&'static str, PathBuf)> {
+                    let layout = $layout::new(PathBuf::from(root));
+                    layout
+                        .all_paths()
+                        .into_iter()
+                        .map(|(name, path)| {
+                            let relative = path
+                                .strip_prefix(layout.root())
+                                .unwrap_or_else(|_| panic!("`{}` is outside the root", name));
+                            (name, relative.to_owned())
+                        })
+                        .collect()
+                }
+
+                #[test]
+                fn paths_are_unique() {
+                    let paths = $layout::new(PathBuf::from("/volta")).all_paths();
+
+                    let mut names = HashSet::new();
+                    let mut seen = HashSet::new();
+                    for (name, path) in &paths {
+                        assert!(names.insert(name), "`{}` is listed twice", name);
+                        assert!(seen.insert(path), "`{}` duplicates another path", name);
+                    }
+                }
+
+                #[test]
+                fn paths_are_inside_root() {
+                    for (name, relative) in relative_paths("/volta") {
+                        assert_ne!(relative, Path::new(""), "`{}` is the root", name);
+                    }
+                }
+
+                #[test]
+                fn dirs_are_listed_paths() {
+                    let layout = $layout::new(PathBuf::from("/volta"));
+                    let paths = layout.all_paths();
+
+                    for dir in layout.dirs() {
+                        assert!(
+                            paths.iter().any(|(_, path)| path == dir),
+                            "`{}` is not listed",
+                            dir.display()
+                        );
+                    }
+                }
+
+                #[test]
+                fn paths_follow_root() {
+                    assert_eq!(relative_paths("/volta"), relative_paths("/other/home/.volta"));
+                }
+            }
+        )+
+    };
+}
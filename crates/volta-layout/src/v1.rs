@@ -113,3 +113,11 @@ impl VoltaHome {
         path_buf!(self.node_image_dir(node, npm), "bin")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    layout_tests! {
+        install: VoltaInstall,
+        home: VoltaHome,
+    }
+}
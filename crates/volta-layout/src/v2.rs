@@ -119,3 +119,10 @@ impl VoltaHome {
         path_buf!(self.node_image_dir(node), "bin")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    layout_tests! {
+        home: VoltaHome,
+    }
+}
@@ -10,10 +10,17 @@ layout! {
             "node": node_cache_dir {
                 "index.json": node_index_file;
                 "index.json.expires": node_index_expiry_file;
+                "index.compact": node_index_compact_file;
+                "schedule.json": node_schedule_file;
+            }
+            "yarn": yarn_cache_dir {
+                "index.json": yarn_index_file;
+                "index.json.expires": yarn_index_expiry_file;
             }
         }
         "bin": shim_dir {}
         "log": log_dir {}
+        "profiles": profiles_dir {}
         "tools": tools_dir {
             "inventory": inventory_dir {
                 "node": node_inventory_dir {}
@@ -33,10 +40,13 @@ layout! {
                 "bins": default_bin_dir {}
                 "packages": default_package_dir {}
                 "platform.json": default_platform_file;
+                "usage.json": usage_file;
+                "usage.pending": usage_pending_file;
             }
         }
         "tmp": tmp_dir {}
         "hooks.json": default_hooks_file;
+        "settings.json": settings_file;
         "layout.v4": layout_file;
     }
 }
@@ -46,6 +56,11 @@ impl VoltaHome {
         path_buf!(self.node_image_root_dir.clone(), node)
     }
 
+    /// The image of a Node version built for an architecture other than the native one
+    pub fn node_arch_image_dir(&self, arch: &str, node: &str) -> PathBuf {
+        path_buf!(self.node_image_root_dir.clone(), arch, node)
+    }
+
     pub fn npm_image_dir(&self, npm: &str) -> PathBuf {
         path_buf!(self.npm_image_root_dir.clone(), npm)
     }
@@ -92,6 +107,10 @@ impl VoltaHome {
         )
     }
 
+    pub fn profile_file(&self, name: &str) -> PathBuf {
+        path_buf!(self.profiles_dir.clone(), format!("{}.json", name))
+    }
+
     pub fn shim_file(&self, toolname: &str) -> PathBuf {
         // On Windows, shims are created as `<name>.cmd` since they
         // are thin scripts that use `volta run` to execute the command
@@ -115,6 +134,10 @@ impl VoltaHome {
     pub fn node_image_bin_dir(&self, node: &str) -> PathBuf {
         self.node_image_dir(node)
     }
+
+    pub fn node_arch_image_bin_dir(&self, arch: &str, node: &str) -> PathBuf {
+        self.node_arch_image_dir(arch, node)
+    }
 }
 
 #[cfg(unix)]
@@ -122,4 +145,15 @@ impl VoltaHome {
     pub fn node_image_bin_dir(&self, node: &str) -> PathBuf {
         path_buf!(self.node_image_dir(node), "bin")
     }
+
+    pub fn node_arch_image_bin_dir(&self, arch: &str, node: &str) -> PathBuf {
+        path_buf!(self.node_arch_image_dir(arch, node), "bin")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    layout_tests! {
+        home: VoltaHome,
+    }
 }
@@ -7,10 +7,16 @@
 //! NOTE: Since the layout file is written once the migration is complete, all migration implementations
 //! need to be aware that they may be partially applied (if something fails in the process) and should be
 //! able to re-start gracefully from an interrupted migration
+//!
+//! Each migration also provides a `plan_from_*` function, which describes the changes it would make
+//! without making them, for `volta migrate --dry-run`
 
-use std::path::Path;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 mod empty;
+mod plan;
 mod v0;
 mod v1;
 mod v2;
@@ -23,13 +29,18 @@ use v2::V2;
 use v3::V3;
 use v4::V4;
 
+pub use plan::{Action, MigrationPlan, MigrationStep};
+
 use log::{debug, info};
-use volta_core::error::Fallible;
+use volta_core::error::{Context, ErrorKind, Fallible};
+use volta_core::fs::remove_file_if_exists;
 use volta_core::layout::volta_home;
 #[cfg(unix)]
 use volta_core::layout::volta_install;
+use volta_core::settings::{LazySettings, Settings};
 use volta_core::shim::regenerate_shims_for_dir;
 use volta_core::sync::VoltaLock;
+use volta_core::tool::package::{relink_packages, RelinkStatus};
 
 /// Represents the state of the Volta directory at every point in the migration process
 ///
@@ -136,6 +147,40 @@ impl MigrationState {
 
         Ok(MigrationState::Empty(empty::Empty::new(volta_home)))
     }
+
+    /// The name of the layout version, as used in the layout file (e.g. `v1`)
+    fn name(&self) -> &'static str {
+        match self {
+            MigrationState::Empty(_) => "empty",
+            MigrationState::V0(_) => "v0",
+            MigrationState::V1(_) => "v1",
+            MigrationState::V2(_) => "v2",
+            MigrationState::V3(_) => "v3",
+            MigrationState::V4(_) => "v4",
+        }
+    }
+
+    /// The layout file of a tagged layout that still needs to be migrated
+    fn outdated_layout_file(&self) -> Option<&Path> {
+        match self {
+            MigrationState::V1(one) => Some(one.home.layout_file()),
+            MigrationState::V2(two) => Some(two.home.layout_file()),
+            MigrationState::V3(three) => Some(three.home.layout_file()),
+            MigrationState::Empty(_) | MigrationState::V0(_) | MigrationState::V4(_) => None,
+        }
+    }
+}
+
+/// The layout that the migrations bring the Volta directory to
+pub const LATEST_LAYOUT: &str = "v4";
+
+/// The layout version recorded by the layout file in the Volta directory, if there is one
+///
+/// Unlike a migration, this doesn't try to tell the untagged legacy layouts apart, as those can
+/// only be recognized by what a migration would do with them.
+pub fn tagged_layout() -> Fallible<Option<&'static str>> {
+    let home = volta_home()?;
+    Ok(MigrationState::detect_tagged_state(home.root()).map(|state| state.name()))
 }
 
 pub fn run_migration() -> Fallible<()> {
@@ -153,10 +198,78 @@ pub fn run_migration() -> Fallible<()> {
     }
 }
 
+/// Describe the migrations that would bring the Volta directory up to date, without applying them
+pub fn plan_migration() -> Fallible<MigrationPlan> {
+    let mut state = MigrationState::current()?;
+    let home = volta_home()?.root().to_owned();
+    let layout = state.name();
+    let mut steps = Vec::new();
+
+    // Follows the same sequence as `detect_and_migrate`, but moves to each new state without
+    // changing anything on disk
+    loop {
+        let (step, next) = match state {
+            MigrationState::Empty(e) => (
+                V3::plan_from_empty(&e),
+                MigrationState::V3(Box::new(V3::new(e.home))),
+            ),
+            MigrationState::V0(zero) => (
+                V1::plan_from_v0(&zero)?,
+                MigrationState::V1(Box::new(V1::new(zero.home.root().to_owned()))),
+            ),
+            MigrationState::V1(one) => (
+                V2::plan_from_v1(&one)?,
+                MigrationState::V2(Box::new(V2::new(one.home.root().to_owned()))),
+            ),
+            MigrationState::V2(two) => (
+                V3::plan_from_v2(&two),
+                MigrationState::V3(Box::new(V3::new(two.home.root().to_owned()))),
+            ),
+            MigrationState::V3(three) => (
+                V4::plan_from_v3(&three)?,
+                MigrationState::V4(Box::new(V4::new(three.home.root().to_owned()))),
+            ),
+            MigrationState::V4(_) => {
+                break;
+            }
+        };
+        steps.push(step);
+        state = next;
+    }
+
+    // Since nothing is created along the way, later steps would list the directories that earlier
+    // steps create again, and would miss removing anything that earlier steps create
+    let mut created = HashSet::new();
+    for step in &mut steps {
+        step.actions.retain(|action| match action {
+            Action::CreateDir(dir) => created.insert(dir.clone()),
+            Action::CreateFile(file) => {
+                created.insert(file.clone());
+                true
+            }
+            Action::Remove(path) => path.exists() || created.contains(path),
+            _ => true,
+        });
+    }
+
+    Ok(MigrationPlan {
+        home,
+        layout,
+        steps,
+    })
+}
+
 fn detect_and_migrate() -> Fallible<()> {
     info!("Updating your Volta directory. This may take a few moments...");
     let mut state = MigrationState::current()?;
 
+    // Keep a copy of the layout file that the migration started from until it completes, so that
+    // an interrupted migration still records where it started
+    let backup = state
+        .outdated_layout_file()
+        .map(backup_layout_file)
+        .transpose()?;
+
     // To keep the complexity of writing a new migration from continuously increasing, each new
     // layout version only needs to implement a migration from 2 states: Empty and the previously
     // latest version. We then apply the migrations sequentially here: V0 -> V1 -> ... -> VX
@@ -173,7 +286,64 @@ fn detect_and_migrate() -> Fallible<()> {
         };
     }
 
-    regenerate_shims_for_dir(volta_home()?.shim_dir())?;
+    // A settings file that can't be read shouldn't stop the migration, so fall back to the default
+    let jobs = match LazySettings::init().get() {
+        Ok(settings) => settings.shim_jobs(),
+        Err(error) => {
+            debug!(
+                "Could not read the settings, using the default shim jobs: {}",
+                error
+            );
+            Settings::default().shim_jobs()
+        }
+    };
+    regenerate_shims_for_dir(volta_home()?.shim_dir(), jobs)?;
+    relink_shared_packages();
+
+    if let Some(backup) = backup {
+        remove_file_if_exists(backup)?;
+    }
 
     Ok(())
 }
+
+/// Recreates the shared links of the installed packages that don't point at the package image from
+/// its current location, as with links that older versions created with absolute paths, which
+/// break when the Volta directory is moved
+///
+/// Since `volta setup --relink-packages` can fix the links later, failures don't stop the
+/// migration.
+fn relink_shared_packages() {
+    let packages = match relink_packages() {
+        Ok(packages) => packages,
+        Err(error) => {
+            debug!("Could not check the shared package links: {}", error);
+            return;
+        }
+    };
+
+    for package in packages {
+        match package.status {
+            Ok(RelinkStatus::Unchanged | RelinkStatus::MissingImage) => {}
+            Ok(status) => debug!("Relinked shared package '{}': {:?}", package.name, status),
+            Err(error) => debug!(
+                "Could not relink shared package '{}': {}",
+                package.name, error
+            ),
+        }
+    }
+}
+
+/// Copy a layout file to `<layout file>.bak`, returning the path of the copy
+fn backup_layout_file(layout_file: &Path) -> Fallible<PathBuf> {
+    let mut backup = layout_file.as_os_str().to_owned();
+    backup.push(".bak");
+    let backup = PathBuf::from(backup);
+
+    debug!("Backing up layout file to {}", backup.display());
+    fs::copy(layout_file, &backup).with_context(|| ErrorKind::CreateLayoutFileError {
+        file: backup.clone(),
+    })?;
+
+    Ok(backup)
+}
@@ -0,0 +1,114 @@
+//! Provides types for describing the migrations that would be applied to the Volta directory,
+//! without applying them (as in `volta migrate --dry-run`)
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A single change that a migration makes to the Volta directory
+#[derive(Debug, PartialEq, Eq)]
+pub enum Action {
+    CreateDir(PathBuf),
+    CreateFile(PathBuf),
+    Update(PathBuf),
+    Move {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    Remove(PathBuf),
+    Reinstall(String),
+    #[cfg(windows)]
+    Recreate(PathBuf),
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::CreateDir(dir) => write!(f, "create directory {}", dir.display()),
+            Action::CreateFile(file) => write!(f, "create file {}", file.display()),
+            Action::Update(file) => write!(f, "update {}", file.display()),
+            Action::Move { from, to } => write!(f, "move {} to {}", from.display(), to.display()),
+            Action::Remove(path) => write!(f, "remove {}", path.display()),
+            Action::Reinstall(package) => write!(f, "reinstall {}", package),
+            #[cfg(windows)]
+            Action::Recreate(path) => write!(f, "recreate {}", path.display()),
+        }
+    }
+}
+
+/// The changes made by the migration from one layout version to the next
+pub struct MigrationStep {
+    pub from: &'static str,
+    pub to: &'static str,
+    pub actions: Vec<Action>,
+}
+
+impl MigrationStep {
+    pub(crate) fn new(from: &'static str, to: &'static str) -> Self {
+        MigrationStep {
+            from,
+            to,
+            actions: Vec::new(),
+        }
+    }
+
+    /// Adds an action to create each of the directories that doesn't exist yet
+    pub(crate) fn create_missing_dirs(&mut self, dirs: Vec<&Path>) {
+        self.actions.extend(
+            dirs.into_iter()
+                .filter(|dir| !dir.exists())
+                .map(|dir| Action::CreateDir(dir.to_owned())),
+        );
+    }
+}
+
+impl fmt::Display for MigrationStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.from, self.to)?;
+        for action in &self.actions {
+            write!(f, "\n    {}", action)?;
+        }
+        Ok(())
+    }
+}
+
+/// The migrations that would bring the Volta directory up to date, in the order they would be
+/// applied
+pub struct MigrationPlan {
+    /// The root of the Volta directory
+    pub home: PathBuf,
+    /// The layout version that the Volta directory currently has
+    pub layout: &'static str,
+    pub steps: Vec<MigrationStep>,
+}
+
+impl MigrationPlan {
+    pub fn is_up_to_date(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_step_with_actions() {
+        let mut step = MigrationStep::new("v1", "v2");
+        step.actions.push(Action::Move {
+            from: PathBuf::from("/volta/node/10.6.0/6.1.0"),
+            to: PathBuf::from("/volta/node/10.6.0"),
+        });
+        step.actions
+            .push(Action::CreateFile(PathBuf::from("/volta/layout.v2")));
+        step.actions
+            .push(Action::Remove(PathBuf::from("/volta/layout.v1")));
+
+        assert_eq!(
+            step.to_string(),
+            "v1 -> v2\n    \
+             move /volta/node/10.6.0/6.1.0 to /volta/node/10.6.0\n    \
+             create file /volta/layout.v2\n    \
+             remove /volta/layout.v1"
+        );
+    }
+}
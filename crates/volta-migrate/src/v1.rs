@@ -1,9 +1,12 @@
 #[cfg(unix)]
 use std::fs::remove_file;
 use std::fs::File;
+#[cfg(unix)]
+use std::path::Path;
 use std::path::PathBuf;
 
 use super::empty::Empty;
+use super::plan::{Action, MigrationStep};
 use super::v0::V0;
 use log::debug;
 use volta_core::error::{Context, ErrorKind, Fallible, VoltaError};
@@ -37,6 +40,38 @@ impl V1 {
 
         Ok(V1 { home })
     }
+
+    /// Describe the migration from a V0 layout, without applying it
+    pub(crate) fn plan_from_v0(old: &V0) -> Fallible<MigrationStep> {
+        let new_home = v1::VoltaHome::new(old.home.root().to_owned());
+        let mut step = MigrationStep::new("v0", "v1");
+        step.create_missing_dirs(new_home.dirs());
+
+        #[cfg(unix)]
+        {
+            let root_contents =
+                read_dir_eager(new_home.root()).with_context(|| ErrorKind::ReadDirError {
+                    dir: new_home.root().to_owned(),
+                })?;
+            for (entry, _) in root_contents {
+                let path = entry.path();
+                if is_load_script(&path) {
+                    step.actions.push(Action::Remove(path));
+                }
+            }
+
+            for old_bin in ["volta", "shim"] {
+                let path = new_home.root().join(old_bin);
+                if path.exists() {
+                    step.actions.push(Action::Remove(path));
+                }
+            }
+        }
+
+        step.actions
+            .push(Action::CreateFile(new_home.layout_file().to_owned()));
+        Ok(step)
+    }
 }
 
 impl TryFrom<Empty> for V1 {
@@ -76,11 +111,9 @@ impl TryFrom<V0> for V1 {
                 })?;
             for (entry, _) in root_contents {
                 let path = entry.path();
-                if let Some(stem) = path.file_stem() {
-                    if stem == "load" && path.is_file() {
-                        remove_file(&path)
-                            .with_context(|| ErrorKind::DeleteFileError { file: path })?;
-                    }
+                if is_load_script(&path) {
+                    remove_file(&path)
+                        .with_context(|| ErrorKind::DeleteFileError { file: path })?;
                 }
             }
 
@@ -96,3 +129,9 @@ impl TryFrom<V0> for V1 {
         V1::complete_migration(new_home)
     }
 }
+
+/// Whether a file is one of the 'load.*' scripts, which are no longer used as of V1
+#[cfg(unix)]
+fn is_load_script(path: &Path) -> bool {
+    path.file_stem().is_some_and(|stem| stem == "load") && path.is_file()
+}
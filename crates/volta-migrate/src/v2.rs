@@ -3,6 +3,7 @@ use std::io;
 use std::path::{Path, PathBuf};
 
 use super::empty::Empty;
+use super::plan::{Action, MigrationStep};
 use super::v1::V1;
 use log::debug;
 use node_semver::Version;
@@ -40,6 +41,44 @@ impl V2 {
 
         Ok(V2 { home })
     }
+
+    /// Describe the migration from a V1 layout, without applying it
+    pub(crate) fn plan_from_v1(old: &V1) -> Fallible<MigrationStep> {
+        let new_home = v2::VoltaHome::new(old.home.root().to_owned());
+        let mut step = MigrationStep::new("v1", "v2");
+        step.create_missing_dirs(new_home.dirs());
+
+        let platform_file = old.home.default_platform_file();
+        if platform_without_default_npm(platform_file)?.is_some() {
+            step.actions.push(Action::Update(platform_file.to_owned()));
+        }
+
+        let node_versions = if old.home.node_image_root_dir().exists() {
+            installed_node_versions(&old.home)?
+        } else {
+            Vec::new()
+        };
+        for node_version in node_versions {
+            let node_string = node_version.to_string();
+            let npm_version = load_default_npm_version(&node_version)?;
+            let old_install = old
+                .home
+                .node_image_dir(&node_string, &npm_version.to_string());
+
+            if old_install.exists() {
+                step.actions.push(Action::Move {
+                    from: old_install,
+                    to: new_home.node_image_dir(&node_string),
+                });
+            }
+        }
+
+        step.actions
+            .push(Action::CreateFile(new_home.layout_file().to_owned()));
+        step.actions
+            .push(Action::Remove(old.home.layout_file().to_owned()));
+        Ok(step)
+    }
 }
 
 impl TryFrom<Empty> for V2 {
@@ -89,11 +128,25 @@ impl TryFrom<V1> for V2 {
 /// This will ensure that we don't treat the default npm from a prior version of Volta as a "custom" npm that
 /// the user explicitly requested
 fn clear_default_npm(platform_file: &Path) -> Fallible<()> {
+    if let Some(platform) = platform_without_default_npm(platform_file)? {
+        write(platform_file, platform.into_json()?).with_context(|| {
+            ErrorKind::WritePlatformError {
+                file: platform_file.to_owned(),
+            }
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Read the default platform, returning it with npm removed if npm is set to the version bundled
+/// with Node, or `None` if the platform doesn't need to change
+fn platform_without_default_npm(platform_file: &Path) -> Fallible<Option<Platform>> {
     let platform_json = match read_to_string(platform_file) {
         Ok(json) => json,
         Err(error) => {
             if error.kind() == io::ErrorKind::NotFound {
-                return Ok(());
+                return Ok(None);
             } else {
                 return Err(VoltaError::from_source(
                     error,
@@ -111,17 +164,13 @@ fn clear_default_npm(platform_file: &Path) -> Fallible<()> {
             if let Ok(default_npm) = load_default_npm_version(&node_version.runtime) {
                 if *npm == default_npm {
                     node_version.npm = None;
-                    write(platform_file, existing_platform.into_json()?).with_context(|| {
-                        ErrorKind::WritePlatformError {
-                            file: platform_file.to_owned(),
-                        }
-                    })?;
+                    return Ok(Some(existing_platform));
                 }
             }
         }
     }
 
-    Ok(())
+    Ok(None)
 }
 
 /// Move all Node images up one directory, removing the default npm version directory
@@ -135,6 +184,15 @@ fn shift_node_images(old_home: &v1::VoltaHome, new_home: &v2::VoltaHome) -> Fall
         tempdir_in(new_home.tmp_dir()).with_context(|| ErrorKind::CreateTempDirError {
             in_dir: new_home.tmp_dir().to_owned(),
         })?;
+    for node_version in installed_node_versions(old_home)? {
+        remove_npm_version_from_node_image_dir(old_home, new_home, node_version, temp_dir.path())?;
+    }
+
+    Ok(())
+}
+
+/// Read the versions of Node that have an image directory in the V1 layout
+fn installed_node_versions(old_home: &v1::VoltaHome) -> Fallible<Vec<Version>> {
     let node_installs = read_dir_eager(old_home.node_image_root_dir())
         .with_context(|| ErrorKind::ReadDirError {
             dir: old_home.node_image_root_dir().to_owned(),
@@ -145,13 +203,10 @@ fn shift_node_images(old_home: &v1::VoltaHome, new_home: &v2::VoltaHome) -> Fall
             } else {
                 None
             }
-        });
-
-    for node_version in node_installs {
-        remove_npm_version_from_node_image_dir(old_home, new_home, node_version, temp_dir.path())?;
-    }
+        })
+        .collect();
 
-    Ok(())
+    Ok(node_installs)
 }
 
 /// Move a single node image up a directory, if it currently has the npm version in its path
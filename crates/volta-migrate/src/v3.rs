@@ -2,6 +2,7 @@ use std::fs::File;
 use std::path::{Path, PathBuf};
 
 use crate::empty::Empty;
+use crate::plan::{Action, MigrationStep};
 use crate::v2::V2;
 use log::{debug, warn};
 use volta_core::error::{Context, ErrorKind, Fallible, VoltaError};
@@ -43,6 +44,38 @@ impl V3 {
 
         Ok(V3 { home })
     }
+
+    /// Describe the creation of a fresh V3 layout, without applying it
+    pub(crate) fn plan_from_empty(old: &Empty) -> MigrationStep {
+        let home = v3::VoltaHome::new(old.home.clone());
+        let mut step = MigrationStep::new("empty", "v3");
+        step.create_missing_dirs(home.dirs());
+        step.actions
+            .push(Action::CreateFile(home.layout_file().to_owned()));
+        step
+    }
+
+    /// Describe the migration from a V2 layout, without applying it
+    pub(crate) fn plan_from_v2(old: &V2) -> MigrationStep {
+        let new_home = v3::VoltaHome::new(old.home.root().to_owned());
+        let mut step = MigrationStep::new("v2", "v3");
+        step.create_missing_dirs(new_home.dirs());
+
+        step.actions.extend(
+            get_installed_packages(&old.home)
+                .into_iter()
+                .map(|package| Action::Reinstall(format!("{}@{}", package.name, package.version))),
+        );
+
+        step.actions
+            .push(Action::Remove(old.home.package_inventory_dir().to_owned()));
+
+        step.actions
+            .push(Action::CreateFile(new_home.layout_file().to_owned()));
+        step.actions
+            .push(Action::Remove(old.home.layout_file().to_owned()));
+        step
+    }
 }
 
 impl TryFrom<Empty> for V3 {
@@ -159,9 +192,9 @@ fn migrate_single_package(config: LegacyPackageConfig, session: &mut Session) ->
     let image = platform.as_binary().checkout(session)?;
 
     // Run the global install command
-    tool.run_install(&image)?;
+    tool.run_install(&image, session)?;
     // Overwrite the config files and image directory
-    tool.complete_install(&image)?;
+    tool.complete_install(&image, true)?;
 
     Ok(())
 }
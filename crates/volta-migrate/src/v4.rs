@@ -2,6 +2,7 @@ use std::fs::File;
 use std::path::PathBuf;
 
 use super::empty::Empty;
+use super::plan::{Action, MigrationStep};
 use super::v3::V3;
 use log::debug;
 use volta_core::error::{Context, ErrorKind, Fallible, VoltaError};
@@ -36,6 +37,33 @@ impl V4 {
 
         Ok(V4 { home })
     }
+
+    /// Describe the migration from a V3 layout, without applying it
+    pub(crate) fn plan_from_v3(old: &V3) -> Fallible<MigrationStep> {
+        let new_home = v4::VoltaHome::new(old.home.root().to_owned());
+        let mut step = MigrationStep::new("v3", "v4");
+        step.create_missing_dirs(new_home.dirs());
+
+        #[cfg(windows)]
+        for dir in [new_home.shim_dir(), new_home.shared_lib_root()] {
+            if dir.exists() {
+                let entries = read_dir_eager(dir).with_context(|| ErrorKind::ReadDirError {
+                    dir: dir.to_owned(),
+                })?;
+                step.actions.extend(
+                    entries
+                        .filter(|(_, metadata)| metadata.is_symlink())
+                        .map(|(entry, _)| Action::Recreate(entry.path())),
+                );
+            }
+        }
+
+        step.actions
+            .push(Action::CreateFile(new_home.layout_file().to_owned()));
+        step.actions
+            .push(Action::Remove(old.home.layout_file().to_owned()));
+        Ok(step)
+    }
 }
 
 impl TryFrom<Empty> for V4 {
@@ -103,7 +131,11 @@ fn migrate_shims(new_home: &v4::VoltaHome) -> Fallible<()> {
             let path = entry.path();
             remove_file_if_exists(&path)?;
 
-            if let Some(shim_name) = path.file_stem().and_then(OsStr::to_str) {
+            if let Some(shim_name) = path
+                .file_stem()
+                .and_then(OsStr::to_str)
+                .filter(|name| !volta_core::shim::is_reserved(name))
+            {
                 volta_core::shim::create(shim_name)?;
             }
         }
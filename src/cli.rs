@@ -3,6 +3,7 @@ use clap::{builder::styling, ColorChoice, Parser};
 use crate::command::{self, Command};
 use volta_core::error::{ExitCode, Fallible};
 use volta_core::session::Session;
+use volta_core::settings::InstallMode;
 use volta_core::style::{text_width, MAX_WIDTH};
 
 #[derive(Parser)]
@@ -21,7 +22,8 @@ pub(crate) struct Volta {
     #[command(subcommand)]
     pub(crate) command: Option<Subcommand>,
 
-    /// Enables verbose diagnostics
+    /// Enables verbose diagnostics, including the code of an error (which is also shown when
+    /// `VOLTA_ERROR_CODES` is set)
     #[arg(long, global = true)]
     pub(crate) verbose: bool,
 
@@ -38,12 +40,76 @@ pub(crate) struct Volta {
     )]
     pub(crate) quiet: bool,
 
+    /// Hides progress bars and spinners, while still showing other output
+    #[arg(long, global = true)]
+    pub(crate) no_progress: bool,
+
+    /// Limits how many downloads may run at the same time [default: 4]
+    #[arg(
+        long,
+        global = true,
+        value_name = "count",
+        value_parser = clap::value_parser!(u16).range(1..)
+    )]
+    pub(crate) max_concurrent_downloads: Option<u16>,
+
+    /// Whether missing tools are fetched automatically, overriding the `install.mode` setting
+    ///
+    /// With `manual`, commands fail when a tool they need is not installed, and only
+    /// `volta fetch` and `volta install` download tools. [possible values: auto, manual]
+    #[arg(long, global = true, value_name = "mode")]
+    pub(crate) install_mode: Option<InstallMode>,
+
+    /// Fails when the project's `volta.lock` file is out of date with its `volta` settings,
+    /// instead of warning (also enabled by setting `VOLTA_FROZEN`)
+    #[arg(long, global = true)]
+    pub(crate) frozen: bool,
+
     /// Prints the current version of Volta
     #[arg(short, long)]
     pub(crate) version: bool,
 }
 
 impl Volta {
+    /// Whether the Volta directory must be migrated to the current layout before running
+    ///
+    /// `volta migrate` handles the migration itself, so that `--dry-run` can leave the directory
+    /// untouched. `volta _complete` runs on every press of Tab, so it reads whatever is there
+    /// rather than pausing for a migration.
+    pub(crate) fn needs_layout(&self) -> bool {
+        !matches!(
+            self.command,
+            Some(Subcommand::Migrate(_) | Subcommand::Complete(_))
+        )
+    }
+
+    /// Whether the command changes the toolchain, and so checks for a Notion directory first
+    pub(crate) fn mutates(&self) -> bool {
+        matches!(
+            self.command,
+            Some(
+                Subcommand::Fetch(_)
+                    | Subcommand::Install(_)
+                    | Subcommand::Uninstall(_)
+                    | Subcommand::Pin(_)
+                    | Subcommand::Lock(_)
+                    | Subcommand::Use(_)
+                    | Subcommand::Import(_)
+            )
+        )
+    }
+
+    /// Whether the command prints its changes with `--porcelain`, so that stdout must only hold
+    /// those lines, starting before the layout migration
+    pub(crate) fn porcelain(&self) -> bool {
+        match &self.command {
+            Some(Subcommand::Install(install)) => install.porcelain,
+            Some(Subcommand::Uninstall(uninstall)) => uninstall.porcelain,
+            Some(Subcommand::Setup(setup)) => setup.porcelain,
+            _ => false,
+        }
+    }
+
     pub(crate) fn run(self, session: &mut Session) -> Fallible<ExitCode> {
         if self.version {
             // suffix indicator for dev build
@@ -67,16 +133,21 @@ pub(crate) enum Subcommand {
     Fetch(command::Fetch),
 
     /// Installs a tool in your toolchain
+    #[command(visible_alias = "i")]
     Install(command::Install),
 
     /// Uninstalls a tool from your toolchain
+    #[command(visible_alias = "rm")]
     Uninstall(command::Uninstall),
 
     /// Pins your project's runtime or package manager
     Pin(command::Pin),
 
+    /// Records your project's exact platform in a `volta.lock` file
+    Lock(command::Lock),
+
     /// Displays the current toolchain
-    #[command(alias = "ls")]
+    #[command(visible_alias = "ls")]
     List(command::List),
 
     /// Generates Volta completions
@@ -100,7 +171,54 @@ pub(crate) enum Subcommand {
     Setup(command::Setup),
 
     /// Run a command with custom Node, npm, pnpm, and/or Yarn versions
+    ///
+    /// The shim directory doesn't need to be on your PATH, so scheduled jobs can run tools with
+    /// `/absolute/path/to/volta run <tool>`. See `volta env --cron` for a PATH to use instead.
     Run(command::Run),
+
+    /// Run a tool once at a specific version, like `volta exec node@20.11.0 -- script.js`
+    #[command(override_usage = "volta exec [OPTIONS] <TOOL@VERSION> [--] [ARGS]...")]
+    Exec(command::Exec),
+
+    /// Imports Node versions from another version manager
+    Import(command::Import),
+
+    /// Prints a PATH that runs your default tools without the shims, for cron jobs and systemd
+    /// units
+    Env(command::Env),
+
+    /// Checks your Volta installation for common problems
+    Doctor(command::Doctor),
+
+    /// Shows the paths that Volta manages, for debugging
+    #[command(hide = true)]
+    Layout(command::Layout),
+
+    /// Lists the hashes of the binaries that Volta manages, or checks them against a manifest
+    VerifyBinaries(command::VerifyBinaries),
+
+    /// Updates the Volta directory to the current layout, or shows what that would change
+    Migrate(command::Migrate),
+
+    /// Shows which hooks apply to a tool and what they resolve to, for debugging
+    Hook(command::Hook),
+
+    /// Reads and changes the settings in your Volta settings file
+    Config(command::Config),
+
+    /// Saves and manages named platforms for `volta run --profile`
+    Profile(command::Profile),
+
+    /// Manages the Volta installation itself, such as updating it to the latest release
+    #[command(name = "self")]
+    VoltaSelf(command::VoltaSelf),
+
+    /// Prints the candidates for the arguments of a command, for the dynamic shell completions
+    ///
+    /// The name has a single underscore, since clap_complete's bash script separates the names
+    /// of nested subcommands with two.
+    #[command(name = "_complete", hide = true)]
+    Complete(command::Complete),
 }
 
 impl Subcommand {
@@ -110,12 +228,25 @@ impl Subcommand {
             Subcommand::Install(install) => install.run(session),
             Subcommand::Uninstall(uninstall) => uninstall.run(session),
             Subcommand::Pin(pin) => pin.run(session),
+            Subcommand::Lock(lock) => lock.run(session),
             Subcommand::List(list) => list.run(session),
             Subcommand::Completions(completions) => completions.run(session),
             Subcommand::Which(which) => which.run(session),
             Subcommand::Use(r#use) => r#use.run(session),
             Subcommand::Setup(setup) => setup.run(session),
             Subcommand::Run(run) => run.run(session),
+            Subcommand::Exec(exec) => exec.run(session),
+            Subcommand::Import(import) => import.run(session),
+            Subcommand::Env(env) => env.run(session),
+            Subcommand::Doctor(doctor) => doctor.run(session),
+            Subcommand::Layout(layout) => layout.run(session),
+            Subcommand::VerifyBinaries(verify) => verify.run(session),
+            Subcommand::Migrate(migrate) => migrate.run(session),
+            Subcommand::Hook(hook) => hook.run(session),
+            Subcommand::Config(config) => config.run(session),
+            Subcommand::Profile(profile) => profile.run(session),
+            Subcommand::VoltaSelf(volta_self) => volta_self.run(session),
+            Subcommand::Complete(complete) => complete.run(session),
         }
     }
 }
@@ -0,0 +1,137 @@
+//! The summary shared by the commands that work through a list of tools: `volta fetch
+//! --manifest`, `volta fetch --all-lts` and `volta install --manifest`
+//!
+//! Each tool is recorded as it is processed, and the summary lists the status of every tool
+//! followed by the totals, so that a long run can be checked at a glance.
+
+use log::info;
+use volta_core::style::{note_prefix, success_prefix};
+
+/// What happened to a single tool
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Status {
+    Done,
+    Skipped,
+    Failed,
+}
+
+/// The status of each tool of a bulk operation, in the order they were processed
+pub(crate) struct Summary {
+    /// How a tool that was processed is described, e.g. `fetched`
+    done: &'static str,
+    /// How a tool that didn't need to be processed is described, e.g. `already fetched`
+    skipped: &'static str,
+    rows: Vec<(String, Status)>,
+}
+
+impl Summary {
+    pub(crate) fn new(done: &'static str, skipped: &'static str) -> Self {
+        Summary {
+            done,
+            skipped,
+            rows: Vec::new(),
+        }
+    }
+
+    pub(crate) fn record(&mut self, tool: String, status: Status) {
+        self.rows.push((tool, status));
+    }
+
+    /// The tools with the given status
+    pub(crate) fn tools(&self, status: Status) -> Vec<String> {
+        self.rows
+            .iter()
+            .filter(|(_, row)| *row == status)
+            .map(|(tool, _)| tool.clone())
+            .collect()
+    }
+
+    pub(crate) fn has_failures(&self) -> bool {
+        self.rows
+            .iter()
+            .any(|(_, status)| *status == Status::Failed)
+    }
+
+    /// Logs the status of every tool and the totals
+    pub(crate) fn report(&self) {
+        if self.rows.is_empty() {
+            return;
+        }
+
+        info!("{}", self.table());
+        if self.has_failures() {
+            info!("{} {}", note_prefix(), self.totals());
+        } else {
+            info!("{} {}", success_prefix(), self.totals());
+        }
+    }
+
+    fn describe(&self, status: Status) -> &'static str {
+        match status {
+            Status::Done => self.done,
+            Status::Skipped => self.skipped,
+            Status::Failed => "failed",
+        }
+    }
+
+    fn table(&self) -> String {
+        let width = self
+            .rows
+            .iter()
+            .map(|(tool, _)| tool.len())
+            .chain(Some("Tool".len()))
+            .max()
+            .unwrap_or_default();
+
+        let mut lines = vec![format!("{:<width$}  Status", "Tool")];
+        for (tool, status) in &self.rows {
+            lines.push(format!("{:<width$}  {}", tool, self.describe(*status)));
+        }
+        lines.join("\n")
+    }
+
+    fn totals(&self) -> String {
+        [Status::Done, Status::Skipped, Status::Failed]
+            .into_iter()
+            .map(|status| {
+                let count = self.rows.iter().filter(|(_, row)| *row == status).count();
+                format!("{} {}", count, self.describe(status))
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary() -> Summary {
+        let mut summary = Summary::new("fetched", "already fetched");
+        summary.record("node@22.11.0".into(), Status::Done);
+        summary.record("yarn@1.22.22".into(), Status::Skipped);
+        summary.record("npm@10".into(), Status::Failed);
+        summary
+    }
+
+    #[test]
+    fn formats_table() {
+        assert_eq!(
+            summary().table(),
+            "Tool          Status
+node@22.11.0  fetched
+yarn@1.22.22  already fetched
+npm@10        failed"
+        );
+    }
+
+    #[test]
+    fn counts_each_status() {
+        let summary = summary();
+
+        assert_eq!(summary.totals(), "1 fetched, 1 already fetched, 1 failed");
+        assert_eq!(summary.tools(Status::Done), ["node@22.11.0"]);
+        assert!(summary.has_failures());
+        assert!(!Summary::new("installed", "already installed").has_failures());
+    }
+}
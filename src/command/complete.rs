@@ -0,0 +1,96 @@
+//! Define the hidden `volta _complete` command, which prints the candidates that the dynamic
+//! shell completions (`volta completions --dynamic`) offer for the arguments of a command.
+//!
+//! The candidates come from the same inventory that `volta list` reads, one per line, so the
+//! completion scripts only need to split the output. Since the command runs on every press of
+//! Tab, a part of the inventory that can't be read only leaves out its candidates.
+
+use std::collections::BTreeSet;
+
+use log::debug;
+use node_semver::Version;
+use volta_core::error::{ExitCode, Fallible, VoltaError};
+use volta_core::inventory::{
+    node_versions, npm_versions, package_configs, pnpm_versions, yarn_versions,
+};
+use volta_core::session::{ActivityKind, Session};
+use volta_core::style::tool_version;
+
+use crate::command::Command;
+
+#[derive(clap::Args)]
+pub(crate) struct Complete {
+    /// The arguments to print the candidates for
+    #[arg(value_enum)]
+    target: Target,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone)]
+enum Target {
+    /// The installed packages and Node versions, which `volta uninstall` removes
+    UninstallTargets,
+    /// The tools that `volta pin` pins, along with their fetched versions
+    PinTargets,
+}
+
+impl Command for Complete {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_subcommand_start(ActivityKind::Completions);
+
+        let candidates = match self.target {
+            Target::UninstallTargets => uninstall_targets(),
+            Target::PinTargets => pin_targets(session.pnpm_enabled().unwrap_or(false)),
+        };
+        for candidate in candidates {
+            println!("{}", candidate);
+        }
+
+        session.add_event_end(ActivityKind::Completions, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+fn uninstall_targets() -> Vec<String> {
+    let mut candidates: Vec<String> = or_empty(package_configs())
+        .into_iter()
+        .map(|config| config.name)
+        .collect();
+    candidates.extend(with_versions("node", &or_empty(node_versions())));
+    candidates
+}
+
+fn pin_targets(pnpm_enabled: bool) -> Vec<String> {
+    let mut tools = vec![
+        ("node", or_empty(node_versions())),
+        ("npm", or_empty(npm_versions())),
+    ];
+    if pnpm_enabled {
+        tools.push(("pnpm", or_empty(pnpm_versions())));
+    }
+    tools.push(("yarn", or_empty(yarn_versions())));
+
+    let mut candidates: Vec<String> = tools.iter().map(|(name, _)| name.to_string()).collect();
+    for (name, versions) in &tools {
+        candidates.extend(with_versions(name, versions));
+    }
+    candidates
+}
+
+/// Treats a part of the inventory that can't be read (e.g. a directory that doesn't exist yet)
+/// as empty
+fn or_empty<T: Default>(result: Fallible<T>) -> T {
+    result.unwrap_or_else(|error: VoltaError| {
+        debug!("Leaving out completions that couldn't be read: {}", error);
+        T::default()
+    })
+}
+
+/// Formats each version of a tool as a spec, like `node@20.11.1`
+fn with_versions<'a>(
+    name: &'a str,
+    versions: &'a BTreeSet<Version>,
+) -> impl Iterator<Item = String> + 'a {
+    versions
+        .iter()
+        .map(move |version| tool_version(name, version))
+}
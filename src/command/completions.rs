@@ -1,3 +1,4 @@
+use std::io::Write;
 use std::path::PathBuf;
 
 use clap::CommandFactory;
@@ -25,11 +26,65 @@ pub(crate) struct Completions {
     /// Write over an existing file, if any.
     #[arg(short, long)]
     force: bool,
+
+    /// Also complete the installed packages and tool versions for `volta uninstall` and
+    /// `volta pin`, by running Volta when Tab is pressed (bash, zsh, and fish only)
+    #[arg(long)]
+    dynamic: bool,
+}
+
+/// Offers the candidates from `volta _complete` for the tools of `uninstall` and `pin`, falling
+/// back to the static completions for everything else
+const BASH_HOOK: &str = r#"
+_volta_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" target=""
+    if [[ ${COMP_CWORD} -ge 2 && ${cur} != -* ]]; then
+        case "${COMP_WORDS[1]}" in
+            uninstall|rm) target="uninstall-targets" ;;
+            pin) target="pin-targets" ;;
+        esac
+    fi
+    if [[ -n ${target} ]]; then
+        COMPREPLY=( $(compgen -W "$(volta _complete ${target} 2>/dev/null)" -- "${cur}") )
+        [[ ${#COMPREPLY[@]} -gt 0 ]] && return 0
+    fi
+    _volta "$@"
 }
+"#;
+
+const ZSH_HOOK: &str = r#"
+_volta_dynamic() {
+    local target
+    if (( CURRENT > 2 )) && [[ ${words[CURRENT]} != -* ]]; then
+        case ${words[2]} in
+            uninstall|rm) target=uninstall-targets ;;
+            pin) target=pin-targets ;;
+        esac
+    fi
+    if [[ -n $target ]]; then
+        local -a candidates
+        candidates=(${(f)"$(volta _complete $target 2>/dev/null)"})
+        compadd -a candidates && return 0
+    fi
+    _volta "$@"
+}
+"#;
+
+const FISH_HOOK: &str = r#"
+complete -c volta -n "__fish_volta_using_subcommand uninstall rm" -f -a "(volta _complete uninstall-targets 2>/dev/null)"
+complete -c volta -n "__fish_volta_using_subcommand pin" -f -a "(volta _complete pin-targets 2>/dev/null)"
+"#;
 
 impl Command for Completions {
     fn run(self, session: &mut Session) -> Fallible<ExitCode> {
-        session.add_event_start(ActivityKind::Completions);
+        session.add_event_subcommand_start(ActivityKind::Completions);
+
+        if self.dynamic && !matches!(self.shell, Shell::Bash | Shell::Zsh | Shell::Fish) {
+            return Err(ErrorKind::DynamicCompletionsUnsupported {
+                shell: self.shell.to_string(),
+            }
+            .into());
+        }
 
         let mut app = crate::cli::Volta::command();
         let app_name = app.get_name().to_owned();
@@ -62,7 +117,7 @@ impl Command for Completions {
                     }
                 })?;
 
-                clap_complete::generate(self.shell, &mut app, app_name, &mut file);
+                generate(self.shell, &mut app, app_name, self.dynamic, &mut file);
 
                 info!(
                     "{} installed completions to {}",
@@ -70,10 +125,207 @@ impl Command for Completions {
                     path.display()
                 );
             }
-            None => clap_complete::generate(self.shell, &mut app, app_name, &mut std::io::stdout()),
+            None => generate(
+                self.shell,
+                &mut app,
+                app_name,
+                self.dynamic,
+                &mut std::io::stdout(),
+            ),
         };
 
         session.add_event_end(ActivityKind::Completions, ExitCode::Success);
         Ok(ExitCode::Success)
     }
 }
+
+/// Generates the completions script for the given shell
+///
+/// clap_complete's fish script only completes a subcommand's arguments when it's called by its
+/// full name, so the conditions are widened to accept the visible aliases (e.g. `volta i`) too.
+/// With `dynamic`, the hook that calls `volta _complete` is added to the script.
+fn generate<W: Write>(
+    shell: Shell,
+    app: &mut clap::Command,
+    app_name: String,
+    dynamic: bool,
+    out: &mut W,
+) {
+    if shell != Shell::Fish && !dynamic {
+        clap_complete::generate(shell, app, app_name, out);
+        return;
+    }
+
+    let mut script = Vec::new();
+    clap_complete::generate(shell, app, app_name.clone(), &mut script);
+    let mut script = String::from_utf8_lossy(&script).into_owned();
+
+    if shell == Shell::Fish {
+        widen_fish_conditions(&mut script, app, &app_name);
+    }
+    if dynamic {
+        add_dynamic_hook(&mut script, shell);
+    }
+
+    // Match clap_complete, which also panics if the script can't be written
+    out.write_all(script.as_bytes())
+        .expect("failed to write completion file");
+}
+
+fn widen_fish_conditions(script: &mut String, app: &clap::Command, app_name: &str) {
+    for subcommand in app.get_subcommands() {
+        let aliases: Vec<&str> = subcommand.get_visible_aliases().collect();
+        if aliases.is_empty() {
+            continue;
+        }
+
+        let condition = format!(
+            "__fish_{}_using_subcommand {}",
+            app_name,
+            subcommand.get_name()
+        );
+        let widened = format!("{} {}", condition, aliases.join(" "));
+        for terminator in ['"', ';'] {
+            *script = script.replace(
+                &format!("{}{}", condition, terminator),
+                &format!("{}{}", widened, terminator),
+            );
+        }
+    }
+}
+
+/// Adds the hook for the dynamic completions, registering it in place of clap_complete's function
+/// for bash and zsh, which it falls back to
+fn add_dynamic_hook(script: &mut String, shell: Shell) {
+    match shell {
+        Shell::Bash => {
+            *script = script.replace("complete -F _volta ", "complete -F _volta_dynamic ");
+            script.insert_str(0, &format!("{}\n", BASH_HOOK.trim_start()));
+        }
+        Shell::Zsh => {
+            *script = script
+                .replace("    _volta \"$@\"\nelse", "    _volta_dynamic \"$@\"\nelse")
+                .replace("compdef _volta volta", "compdef _volta_dynamic volta");
+            // The `#compdef` line has to stay first
+            let after_compdef = script.find('\n').map_or(0, |index| index + 1);
+            script.insert_str(after_compdef, ZSH_HOOK);
+        }
+        Shell::Fish => script.push_str(FISH_HOOK),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Command, Stdio};
+
+    fn script(shell: Shell, dynamic: bool) -> String {
+        let mut app = crate::cli::Volta::command();
+        let app_name = app.get_name().to_owned();
+        let mut out = Vec::new();
+        generate(shell, &mut app, app_name, dynamic, &mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    /// Checks the syntax of the generated scripts, with and without the dynamic completions,
+    /// with the shell itself, skipping the check if the shell isn't installed
+    fn lint(shell: Shell, program: &str, args: &[&str]) {
+        for dynamic in [false, true] {
+            lint_script(shell, program, args, dynamic);
+        }
+    }
+
+    fn lint_script(shell: Shell, program: &str, args: &[&str], dynamic: bool) {
+        let child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                eprintln!(
+                    "Skipping {} completions lint, `{}` not found",
+                    shell, program
+                );
+                return;
+            }
+            Err(error) => panic!("could not run {}: {}", program, error),
+        };
+
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(script(shell, dynamic).as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+
+        assert!(
+            output.status.success(),
+            "{} completions are not valid:\n{}",
+            shell,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    #[test]
+    fn bash_completions_are_valid() {
+        lint(Shell::Bash, "bash", &["-n"]);
+    }
+
+    #[test]
+    fn zsh_completions_are_valid() {
+        lint(Shell::Zsh, "zsh", &["-n"]);
+    }
+
+    #[test]
+    fn fish_completions_are_valid() {
+        lint(Shell::Fish, "fish", &["--no-execute"]);
+    }
+
+    #[test]
+    fn completions_include_subcommand_aliases() {
+        let bash = script(Shell::Bash, false);
+        assert!(bash.contains("volta,i)"));
+        assert!(bash.contains("volta,rm)"));
+        assert!(bash.contains("volta,ls)"));
+
+        let zsh = script(Shell::Zsh, false);
+        assert!(zsh.contains("(i)"));
+        assert!(zsh.contains("(rm)"));
+        assert!(zsh.contains("(ls)"));
+    }
+
+    #[test]
+    fn fish_completes_arguments_after_aliases() {
+        let fish = script(Shell::Fish, false);
+        assert!(fish.contains(r#"-n "__fish_volta_using_subcommand install i""#));
+        assert!(fish.contains(r#"-n "__fish_volta_using_subcommand uninstall rm""#));
+        assert!(fish.contains(r#"-n "__fish_volta_using_subcommand list ls""#));
+        assert!(!fish.contains(r#"-n "__fish_volta_using_subcommand install""#));
+    }
+
+    #[test]
+    fn dynamic_completions_call_the_hook() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let dynamic = script(shell, true);
+            assert!(dynamic.contains("(volta _complete "), "{}", shell);
+            assert!(dynamic.contains("uninstall-targets"), "{}", shell);
+            assert!(dynamic.contains("pin-targets"), "{}", shell);
+
+            let fixed = script(shell, false);
+            assert!(!fixed.contains("(volta _complete "), "{}", shell);
+        }
+
+        let bash = script(Shell::Bash, true);
+        assert!(bash.contains("complete -F _volta_dynamic "));
+        assert!(!bash.contains("complete -F _volta "));
+
+        let zsh = script(Shell::Zsh, true);
+        assert!(zsh.starts_with("#compdef volta\n"));
+        assert!(zsh.contains("compdef _volta_dynamic volta"));
+    }
+}
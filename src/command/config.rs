@@ -0,0 +1,63 @@
+//! Define `volta config`, which reads and changes the settings in the user's settings file.
+
+use log::info;
+use volta_core::error::{ExitCode, Fallible};
+use volta_core::session::{ActivityKind, Session};
+use volta_core::settings::{save_setting, SettingKey};
+use volta_core::style::success_prefix;
+
+use crate::command::Command;
+
+#[derive(clap::Args)]
+pub(crate) struct Config {
+    #[command(subcommand)]
+    command: ConfigCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum ConfigCommand {
+    /// Prints the value of a setting
+    Get {
+        /// The name of the setting, like `feature.pnpm`
+        key: String,
+    },
+
+    /// Changes a setting in the settings file
+    Set {
+        /// The name of the setting, like `feature.pnpm`
+        key: String,
+
+        /// The new value of the setting
+        value: String,
+    },
+
+    /// Lists every setting with its current value
+    List,
+}
+
+impl Command for Config {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_subcommand_start(ActivityKind::Config);
+
+        match self.command {
+            ConfigCommand::Get { key } => {
+                let key: SettingKey = key.parse()?;
+                println!("{}", session.settings()?.value(key));
+            }
+            ConfigCommand::Set { key, value } => {
+                let key: SettingKey = key.parse()?;
+                save_setting(key, &value)?;
+                info!("{} set {} to {}", success_prefix(), key, value);
+            }
+            ConfigCommand::List => {
+                let settings = session.settings()?;
+                for key in SettingKey::ALL {
+                    println!("{} = {}", key, settings.value(key));
+                }
+            }
+        }
+
+        session.add_event_end(ActivityKind::Config, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
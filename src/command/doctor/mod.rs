@@ -0,0 +1,452 @@
+pub(crate) mod path;
+
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use log::{error, info, warn};
+use serde::Serialize;
+use volta_core::error::{ExitCode, Fallible, VoltaError};
+use volta_core::layout::volta_home;
+use volta_core::notion;
+use volta_core::session::{ActivityKind, Session};
+use volta_core::shim::{
+    orphaned_shims, remove_orphaned_shim, remove_shadowing_shim, shadowing_shims,
+};
+use volta_core::style::{note_prefix, success_prefix};
+use volta_core::sync::VoltaLock;
+use volta_core::tool::{shim_unreachable, Unreachable};
+use volta_migrate::{tagged_layout, LATEST_LAYOUT};
+
+use crate::command::Command;
+
+#[derive(clap::Args)]
+pub(crate) struct Doctor {
+    /// Repairs the problems that are found, where possible
+    #[arg(long)]
+    fix: bool,
+
+    /// Prints the results as JSON, such as for attaching to a bug report
+    #[arg(long)]
+    json: bool,
+}
+
+/// The outcome of a single check
+enum Outcome {
+    /// Nothing is wrong
+    Pass(String),
+    /// A problem was found, along with a hint for resolving it
+    Warn { message: String, hint: String },
+    /// A problem was found that keeps Volta from working, along with a hint for resolving it
+    Fail { message: String, hint: String },
+    /// A problem was found and repaired with `--fix`
+    Fixed(String),
+}
+
+impl Outcome {
+    fn is_problem(&self) -> bool {
+        matches!(self, Outcome::Warn { .. } | Outcome::Fail { .. })
+    }
+}
+
+/// A single check, which is only allowed to make changes when `fix` is set
+type Check = fn(session: &Session, fix: bool) -> Fallible<Outcome>;
+
+/// The checks, each with the name that identifies it in the JSON output
+const CHECKS: [(&str, Check); 8] = [
+    ("volta-home", check_volta_home),
+    ("shim-dir-on-path", check_shim_dir_on_path),
+    ("orphaned-shims", check_orphaned_shims),
+    ("shadowing-shims", check_shadowing_shims),
+    ("stale-path-entries", check_stale_path_entries),
+    ("lock", check_lock),
+    ("platform-files", check_platform_files),
+    ("notion-home", check_notion_home),
+];
+
+/// The version of the JSON output, which changes whenever its shape does
+const JSON_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonReport<'a> {
+    json_version: u32,
+    volta_version: &'static str,
+    checks: Vec<JsonCheck<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonCheck<'a> {
+    name: &'static str,
+    status: &'static str,
+    message: &'a str,
+    hint: Option<&'a str>,
+}
+
+impl<'a> JsonCheck<'a> {
+    fn new(name: &'static str, outcome: &'a Outcome) -> Self {
+        let (status, message, hint) = match outcome {
+            Outcome::Pass(message) => ("pass", message, None),
+            Outcome::Warn { message, hint } => ("warn", message, Some(hint)),
+            Outcome::Fail { message, hint } => ("fail", message, Some(hint)),
+            Outcome::Fixed(message) => ("fixed", message, None),
+        };
+        JsonCheck {
+            name,
+            status,
+            message,
+            hint: hint.map(String::as_str),
+        }
+    }
+}
+
+impl Command for Doctor {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_subcommand_start(ActivityKind::Doctor);
+
+        let _lock = self.fix.then(VoltaLock::acquire);
+
+        let mut outcomes = Vec::new();
+        for (name, check) in CHECKS {
+            let outcome = check(session, self.fix)?;
+            if !self.json {
+                print_outcome(&outcome);
+            }
+            outcomes.push((name, outcome));
+        }
+
+        if self.json {
+            let report = JsonReport {
+                json_version: JSON_VERSION,
+                volta_version: env!("CARGO_PKG_VERSION"),
+                checks: outcomes
+                    .iter()
+                    .map(|(name, outcome)| JsonCheck::new(name, outcome))
+                    .collect(),
+            };
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&report).expect("the report can be serialized")
+            );
+        }
+
+        let exit_code = if !outcomes.iter().any(|(_, outcome)| outcome.is_problem()) {
+            ExitCode::Success
+        } else {
+            ExitCode::ConfigurationError
+        };
+
+        session.add_event_end(ActivityKind::Doctor, exit_code);
+        Ok(exit_code)
+    }
+}
+
+fn print_outcome(outcome: &Outcome) {
+    match outcome {
+        Outcome::Pass(message) | Outcome::Fixed(message) => {
+            info!("{} {}", success_prefix(), message)
+        }
+        Outcome::Warn { message, hint } => {
+            warn!("{}", message);
+            info!("{} {}", note_prefix(), hint);
+        }
+        Outcome::Fail { message, hint } => {
+            error!("{}", message);
+            info!("{} {}", note_prefix(), hint);
+        }
+    }
+}
+
+/// Checks that the Volta directory exists and has been migrated to the current layout
+fn check_volta_home(_session: &Session, _fix: bool) -> Fallible<Outcome> {
+    let home = volta_home()?;
+    if !home.root().is_dir() {
+        return Ok(Outcome::Fail {
+            message: format!(
+                "The Volta directory {} does not exist",
+                home.root().display()
+            ),
+            hint: "Run `volta setup` to create it".into(),
+        });
+    }
+
+    match tagged_layout()? {
+        Some(layout) if layout == LATEST_LAYOUT => Ok(Outcome::Pass(format!(
+            "The Volta directory {} uses the current layout ({})",
+            home.root().display(),
+            layout
+        ))),
+        layout => Ok(Outcome::Warn {
+            message: format!(
+                "The Volta directory {} uses an old layout ({}), rather than {}",
+                home.root().display(),
+                layout.unwrap_or("untagged"),
+                LATEST_LAYOUT
+            ),
+            hint: "Run `volta migrate` to update it".into(),
+        }),
+    }
+}
+
+/// Checks that the shim directory is on the `PATH`, ahead of any other binaries with the names of
+/// Volta's shims
+fn check_shim_dir_on_path(_session: &Session, _fix: bool) -> Fallible<Outcome> {
+    let home = volta_home()?;
+    let shim_dir = home.shim_dir();
+    let on_path =
+        env::split_paths(&env::var_os("PATH").unwrap_or_default()).any(|entry| entry == shim_dir);
+
+    if !on_path {
+        return Ok(Outcome::Fail {
+            message: format!(
+                "The shim directory {} is not on your PATH",
+                shim_dir.display()
+            ),
+            hint: "Run `volta setup` to add it to your profile scripts, then open a new shell"
+                .into(),
+        });
+    }
+
+    // Only the shims that are installed can be shadowed, as the others are never generated on
+    // Windows and aren't found on the PATH at all
+    let shadowed: Vec<String> = ["node", "npm", "npx", "pnpm", "yarn"]
+        .into_iter()
+        .filter_map(|shim_name| match shim_unreachable(shim_name)? {
+            Unreachable::Shadowed {
+                expected_dir,
+                resolved,
+            } if which::which_in(shim_name, Some(&expected_dir), &expected_dir).is_ok() => {
+                Some(format!("{} ({})", shim_name, resolved.display()))
+            }
+            _ => None,
+        })
+        .collect();
+
+    if shadowed.is_empty() {
+        Ok(Outcome::Pass(format!(
+            "The shim directory {} is on your PATH",
+            shim_dir.display()
+        )))
+    } else {
+        Ok(Outcome::Warn {
+            message: format!(
+                "Binaries earlier on your PATH shadow Volta's shims: {}",
+                shadowed.join(", ")
+            ),
+            hint: format!("Move {} to the start of your PATH", shim_dir.display()),
+        })
+    }
+}
+
+/// Checks for shims of package binaries that are no longer installed, which fail when they are run
+fn check_orphaned_shims(_session: &Session, fix: bool) -> Fallible<Outcome> {
+    let shims = orphaned_shims()?;
+
+    if shims.is_empty() {
+        return Ok(Outcome::Pass(
+            "All shims belong to installed packages".into(),
+        ));
+    }
+
+    if fix {
+        for shim in &shims {
+            remove_orphaned_shim(shim)?;
+        }
+
+        return Ok(Outcome::Fixed(format!(
+            "Removed shims of packages that are not installed: {}",
+            shims.join(", ")
+        )));
+    }
+
+    Ok(Outcome::Warn {
+        message: format!(
+            "Shims in {} belong to packages that are not installed: {}",
+            volta_home()?.shim_dir().display(),
+            shims.join(", ")
+        ),
+        hint: "Run `volta doctor --fix` to remove them".into(),
+    })
+}
+
+/// Checks for shims that shadow Volta's own executables, left over from installing a package that
+/// provides a `volta` bin with an older version of Volta
+fn check_shadowing_shims(_session: &Session, fix: bool) -> Fallible<Outcome> {
+    let shims = shadowing_shims()?;
+
+    if shims.is_empty() {
+        return Ok(Outcome::Pass(
+            "No shims shadow Volta's own executables".into(),
+        ));
+    }
+
+    if fix {
+        for shim in &shims {
+            remove_shadowing_shim(shim)?;
+        }
+
+        return Ok(Outcome::Fixed(format!(
+            "Removed shims shadowing Volta's own executables: {}",
+            shims.join(", ")
+        )));
+    }
+
+    Ok(Outcome::Warn {
+        message: format!(
+            "Shims in {} shadow Volta's own executables: {}",
+            volta_home()?.shim_dir().display(),
+            shims.join(", ")
+        ),
+        hint: "Run `volta doctor --fix` to remove them".into(),
+    })
+}
+
+/// Checks for entries in the `PATH` that point at tool images or at directories in the Volta home
+/// that no longer exist, which are usually left in a profile script from using an old version
+/// directly
+fn check_stale_path_entries(_session: &Session, fix: bool) -> Fallible<Outcome> {
+    let home = volta_home()?;
+    let entries = path::stale_entries(
+        &env::var_os("PATH").unwrap_or_default(),
+        home.root(),
+        home.image_dir(),
+    );
+
+    if entries.is_empty() {
+        return Ok(Outcome::Pass(
+            "No PATH entries point at tool images or missing directories in the Volta home".into(),
+        ));
+    }
+
+    let home_dir = dirs::home_dir();
+    let profiles: Vec<(PathBuf, String)> = home_dir
+        .as_deref()
+        .map(path::profiles)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|profile| {
+            let contents = fs::read_to_string(&profile).ok()?;
+            Some((profile, contents))
+        })
+        .collect();
+
+    // The profile script that most likely adds each entry, if any
+    let mut found = Vec::new();
+    for entry in &entries {
+        let spellings = path::spellings(&entry.entry, home_dir.as_deref(), home.root());
+        let profile = profiles
+            .iter()
+            .find(|(_, contents)| path::mentions(contents, &spellings))
+            .map(|(profile, _)| profile.clone());
+        found.push((entry, spellings, profile));
+    }
+
+    if fix && found.iter().all(|(_, _, profile)| profile.is_some()) {
+        let mut fixed: Vec<PathBuf> = Vec::new();
+        for (_, spellings, profile) in &found {
+            let profile = profile.as_ref().expect("every entry has a profile");
+            if path::fix_profile(profile, spellings)? && !fixed.contains(profile) {
+                fixed.push(profile.clone());
+            }
+        }
+
+        return Ok(Outcome::Fixed(format!(
+            "Removed stale PATH entries from {}. Open a new shell to use the updated PATH",
+            fixed
+                .iter()
+                .map(|profile| profile.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+
+    let mut message = String::from("Found stale entries in your PATH:");
+    for (entry, _, profile) in &found {
+        message.push_str(&format!(
+            "\n    {} ({})",
+            entry.entry.display(),
+            entry.describe()
+        ));
+        if let Some(profile) = profile {
+            message.push_str(&format!(", likely added by {}", profile.display()));
+        }
+    }
+
+    let hint = if found.iter().all(|(_, _, profile)| profile.is_some()) {
+        "Run `volta doctor --fix` to remove them from your profile scripts"
+    } else {
+        "Remove them from the profile script or environment setting that changes your PATH"
+    };
+
+    Ok(Outcome::Warn {
+        message,
+        hint: hint.into(),
+    })
+}
+
+/// Checks whether another Volta process holds the lock on the Volta directory, which makes any
+/// command that changes the directory wait
+fn check_lock(_session: &Session, _fix: bool) -> Fallible<Outcome> {
+    if VoltaLock::is_held_elsewhere()? {
+        Ok(Outcome::Warn {
+            message: "Another Volta process holds the lock on the Volta directory".into(),
+            hint: "Wait for it to finish, or end it if it is stuck".into(),
+        })
+    } else {
+        Ok(Outcome::Pass(
+            "No other Volta process holds the lock on the Volta directory".into(),
+        ))
+    }
+}
+
+/// Checks that the default platform and the `volta` settings of the current project can be read
+fn check_platform_files(session: &Session, _fix: bool) -> Fallible<Outcome> {
+    // The underlying error explains what is wrong with the file, rather than how to report it
+    let cause = |error: VoltaError| {
+        error
+            .source()
+            .map_or_else(|| error.to_string(), ToString::to_string)
+    };
+
+    let mut problems = Vec::new();
+    if let Err(error) = session.default_platform() {
+        problems.push(format!(
+            "Could not read the default platform in {}: {}",
+            volta_home()?.default_platform_file().display(),
+            cause(error)
+        ));
+    }
+    if let Err(error) = session.project() {
+        problems.push(format!(
+            "Could not read the Volta settings of the current project: {}",
+            cause(error)
+        ));
+    }
+
+    if problems.is_empty() {
+        Ok(Outcome::Pass(
+            "The default platform and project settings can be read".into(),
+        ))
+    } else {
+        Ok(Outcome::Fail {
+            message: problems.join("\n"),
+            hint: "Fix or remove the files named above".into(),
+        })
+    }
+}
+
+/// Checks for the directory of Notion, the previous name of Volta, and for its shims on the `PATH`
+///
+/// Migrating moves the directory and imports its settings, which needs confirmation, so this is
+/// left to `volta setup --migrate-notion` even with `--fix`.
+fn check_notion_home(_session: &Session, _fix: bool) -> Fallible<Outcome> {
+    match notion::detect()? {
+        None => Ok(Outcome::Pass("No Notion directory or shims were found".into())),
+        Some(legacy) => Ok(Outcome::Warn {
+            message: legacy.describe(),
+            hint: "Run `volta setup --migrate-notion` to import its settings and move it out of the way"
+                .into(),
+        }),
+    }
+}
@@ -0,0 +1,379 @@
+//! Finds entries in the `PATH` that point into the Volta home but can't work
+//!
+//! Volta only puts the versioned tool images on the `PATH` of the tools it runs, so an image
+//! directory that stays on the `PATH` of the shell was added by hand, usually in a profile script,
+//! and stops existing once that version is removed.
+
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use volta_core::error::{Context, ErrorKind, Fallible};
+
+/// An entry in the `PATH` that shouldn't be there
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct StaleEntry {
+    pub(super) entry: PathBuf,
+    /// Whether the entry is a directory in the tool images, rather than just a missing directory
+    pub(super) in_image: bool,
+    pub(super) missing: bool,
+}
+
+impl StaleEntry {
+    pub(super) fn describe(&self) -> &'static str {
+        match (self.in_image, self.missing) {
+            (true, true) => "a tool image that no longer exists",
+            (true, false) => "a versioned tool image, which Volta adds to the PATH itself",
+            (false, _) => "a directory that no longer exists",
+        }
+    }
+}
+
+/// Finds the stale entries in a `PATH`, in order and without duplicates
+///
+/// Only entries inside the Volta home are considered; anything else on the `PATH` is the user's
+/// business.
+pub(super) fn stale_entries(path: &OsStr, volta_root: &Path, image_dir: &Path) -> Vec<StaleEntry> {
+    let mut stale: Vec<StaleEntry> = Vec::new();
+
+    for entry in env::split_paths(path) {
+        if entry.as_os_str().is_empty() || !is_within(&entry, volta_root) {
+            continue;
+        }
+
+        let in_image = is_within(&entry, image_dir);
+        let missing = !entry.is_dir();
+        if (in_image || missing) && !stale.iter().any(|known| same_path(&known.entry, &entry)) {
+            stale.push(StaleEntry {
+                entry,
+                in_image,
+                missing,
+            });
+        }
+    }
+
+    stale
+}
+
+#[cfg(unix)]
+fn is_within(path: &Path, dir: &Path) -> bool {
+    path.starts_with(dir)
+}
+
+/// Determines whether a path is inside a directory, ignoring case as Windows does
+#[cfg(windows)]
+fn is_within(path: &Path, dir: &Path) -> bool {
+    let lowercase = |path: &Path| PathBuf::from(path.to_string_lossy().to_lowercase());
+    lowercase(path).starts_with(lowercase(dir))
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    is_within(a, b) && is_within(b, a)
+}
+
+/// The profile scripts that exist, which may set the `PATH`
+///
+/// These are the scripts that `volta setup` writes to, along with the ones the common shells read.
+#[cfg(unix)]
+pub(crate) fn profiles(home: &Path) -> Vec<PathBuf> {
+    let zdotdir = env::var_os("ZDOTDIR")
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(|| home.to_owned(), PathBuf::from);
+
+    let mut profiles = vec![
+        home.join(".profile"),
+        home.join(".bashrc"),
+        home.join(".bash_profile"),
+        zdotdir.join(".zshenv"),
+        zdotdir.join(".zshrc"),
+        home.join(".config").join("fish").join("config.fish"),
+    ];
+    if let Some(profile) = env::var_os("PROFILE").filter(|profile| !profile.is_empty()) {
+        let profile = PathBuf::from(profile);
+        if !profiles.contains(&profile) {
+            profiles.push(profile);
+        }
+    }
+
+    profiles.retain(|profile| profile.is_file());
+    profiles
+}
+
+/// The profile scripts that exist, which may set the `PATH`
+///
+/// On Windows, the `PATH` is set in the registry rather than in scripts.
+#[cfg(windows)]
+pub(crate) fn profiles(_home: &Path) -> Vec<PathBuf> {
+    Vec::new()
+}
+
+/// The ways that a profile script may spell an entry, e.g. `$HOME/.volta/...` for `/home/me/.volta/...`
+pub(crate) fn spellings(entry: &Path, home: Option<&Path>, volta_root: &Path) -> Vec<String> {
+    let mut spellings = vec![entry.display().to_string()];
+
+    let mut relative_to = |variables: &[&str], dir: &Path| {
+        if let Ok(relative) = entry.strip_prefix(dir) {
+            let relative = relative.display();
+            for variable in variables {
+                spellings.push(format!("{}/{}", variable, relative));
+            }
+        }
+    };
+    relative_to(&["$VOLTA_HOME", "${VOLTA_HOME}"], volta_root);
+    if let Some(home) = home {
+        relative_to(&["$HOME", "${HOME}", "~"], home);
+    }
+
+    spellings
+}
+
+/// Determines whether a profile script adds any of the spellings of an entry to the `PATH`
+pub(super) fn mentions(contents: &str, spellings: &[String]) -> bool {
+    contents.lines().filter(|line| sets_path(line)).any(|line| {
+        spellings
+            .iter()
+            .any(|spelling| find_entry(line, spelling).is_some())
+    })
+}
+
+/// Removes the spellings of the stale entries from the lines of a profile script that set the
+/// `PATH`, dropping the lines that are left with nothing to add
+///
+/// Returns `None` if the script doesn't add any of them.
+pub(crate) fn remove_entries(contents: &str, spellings: &[String]) -> Option<String> {
+    let mut changed = false;
+    let mut lines = Vec::new();
+
+    for line in contents.lines() {
+        if !sets_path(line) {
+            lines.push(line.to_owned());
+            continue;
+        }
+
+        let mut rewritten = line.to_owned();
+        for spelling in spellings {
+            while let Some((start, end)) = find_entry(&rewritten, spelling) {
+                rewritten.replace_range(separated_range(&rewritten, start, end), "");
+                changed = true;
+            }
+        }
+
+        if rewritten == line || !only_reassigns_path(&rewritten) {
+            lines.push(rewritten);
+        }
+    }
+
+    if !changed {
+        return None;
+    }
+
+    let mut rewritten = lines.join("\n");
+    if contents.ends_with('\n') {
+        rewritten.push('\n');
+    }
+    Some(rewritten)
+}
+
+/// Removes the stale entries from a profile script, returning whether it was changed
+pub(super) fn fix_profile(profile: &Path, spellings: &[String]) -> Fallible<bool> {
+    let contents = fs::read_to_string(profile).with_context(|| ErrorKind::WriteProfileError {
+        file: profile.to_owned(),
+    })?;
+
+    match remove_entries(&contents, spellings) {
+        Some(rewritten) => {
+            fs::write(profile, rewritten).with_context(|| ErrorKind::WriteProfileError {
+                file: profile.to_owned(),
+            })?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Whether a line of a profile script (of any of the common shells) changes the `PATH`
+fn sets_path(line: &str) -> bool {
+    let line = line.trim_start();
+    !line.starts_with('#') && (line.contains("PATH") || line.contains("fish_add_path"))
+}
+
+/// Finds a spelling in a line where it's a whole entry, rather than part of a longer path
+fn find_entry(line: &str, spelling: &str) -> Option<(usize, usize)> {
+    let is_boundary = |c: char| matches!(c, ':' | ';' | '"' | '\'' | '=') || c.is_whitespace();
+
+    line.match_indices(spelling).find_map(|(start, _)| {
+        let before = line[..start].chars().next_back();
+        let mut end = start + spelling.len();
+        if line[end..].starts_with('/') {
+            end += 1;
+        }
+        let after = line[end..].chars().next();
+
+        let bounded = before.map_or(true, is_boundary) && after.map_or(true, is_boundary);
+        bounded.then_some((start, end))
+    })
+}
+
+/// Extends the range of an entry to include one of the separators around it, so that removing it
+/// leaves a valid list
+fn separated_range(line: &str, start: usize, end: usize) -> std::ops::Range<usize> {
+    if line[end..].starts_with(':') {
+        start..end + 1
+    } else if line[..start].ends_with(':') {
+        start - 1..end
+    } else if line[..start].ends_with('"') && line[end..].starts_with('"') {
+        // A quoted word of its own, as in `set -gx PATH "..." $PATH` for fish
+        start - 1..end + 1
+    } else {
+        start..end
+    }
+}
+
+/// Whether a line that set the `PATH` now only sets it to itself, as in `export PATH="$PATH"`
+fn only_reassigns_path(line: &str) -> bool {
+    line.split(|c: char| c.is_whitespace() || matches!(c, '=' | '"' | '\'' | ';'))
+        .filter(|word| !word.is_empty())
+        .all(|word| {
+            matches!(
+                word,
+                "export" | "set" | "PATH" | "$PATH" | "${PATH}" | "fish_add_path"
+            ) || word.starts_with('-')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VOLTA_ROOT: &str = "/home/me/.volta";
+    const IMAGE_DIR: &str = "/home/me/.volta/tools/image";
+
+    fn spellings_for(entry: &str) -> Vec<String> {
+        spellings(
+            Path::new(entry),
+            Some(Path::new("/home/me")),
+            Path::new(VOLTA_ROOT),
+        )
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn finds_image_and_missing_entries() {
+        let path = OsStr::new(
+            "/home/me/.volta/bin:/usr/bin:/home/me/.volta/tools/image/node/14.0.0/bin:\
+             /home/me/.volta/old-bin::/home/me/.volta/tools/image/node/14.0.0/bin/",
+        );
+
+        let stale = stale_entries(path, Path::new(VOLTA_ROOT), Path::new(IMAGE_DIR));
+
+        assert_eq!(
+            stale,
+            vec![
+                StaleEntry {
+                    entry: PathBuf::from("/home/me/.volta/bin"),
+                    in_image: false,
+                    missing: true,
+                },
+                StaleEntry {
+                    entry: PathBuf::from("/home/me/.volta/tools/image/node/14.0.0/bin"),
+                    in_image: true,
+                    missing: true,
+                },
+                StaleEntry {
+                    entry: PathBuf::from("/home/me/.volta/old-bin"),
+                    in_image: false,
+                    missing: true,
+                },
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_existing_entries_outside_images() {
+        let root = std::env::temp_dir();
+        let image_dir = root.join("volta-doctor-test-images");
+        let path = env::join_paths([root.clone(), PathBuf::from("/usr/bin")]).unwrap();
+
+        assert_eq!(stale_entries(&path, &root, &image_dir), Vec::new());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn finds_entries_with_windows_conventions() {
+        let path = OsStr::new(
+            r"C:\Windows;c:\users\me\appdata\local\volta\tools\image\node\14.0.0;C:/Users/Me/AppData/Local/Volta/missing",
+        );
+
+        let stale = stale_entries(
+            path,
+            Path::new(r"C:\Users\Me\AppData\Local\Volta"),
+            Path::new(r"C:\Users\Me\AppData\Local\Volta\tools\image"),
+        );
+
+        assert_eq!(stale.len(), 2);
+        assert!(stale[0].in_image);
+        assert!(!stale[1].in_image);
+        assert!(stale[1].missing);
+    }
+
+    #[test]
+    fn spells_entries_with_variables() {
+        let spellings = spellings_for("/home/me/.volta/tools/image/node/14.0.0/bin");
+
+        assert!(spellings.contains(&"/home/me/.volta/tools/image/node/14.0.0/bin".to_owned()));
+        assert!(spellings.contains(&"$VOLTA_HOME/tools/image/node/14.0.0/bin".to_owned()));
+        assert!(spellings.contains(&"$HOME/.volta/tools/image/node/14.0.0/bin".to_owned()));
+        assert!(spellings.contains(&"~/.volta/tools/image/node/14.0.0/bin".to_owned()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn removes_entries_from_sh_profile() {
+        let bashrc = r#"# Use the old Node directly
+export VOLTA_HOME="$HOME/.volta"
+export PATH="$VOLTA_HOME/bin:$PATH"
+export PATH="$HOME/.volta/tools/image/node/14.0.0/bin:$PATH"
+export PATH="$PATH:$HOME/go/bin:/home/me/.volta/tools/image/node/14.0.0/bin/"
+alias old-node=$HOME/.volta/tools/image/node/14.0.0/bin/node
+"#;
+        let spellings = spellings_for("/home/me/.volta/tools/image/node/14.0.0/bin");
+
+        assert!(mentions(bashrc, &spellings));
+        assert_eq!(
+            remove_entries(bashrc, &spellings).unwrap(),
+            r#"# Use the old Node directly
+export VOLTA_HOME="$HOME/.volta"
+export PATH="$VOLTA_HOME/bin:$PATH"
+export PATH="$PATH:$HOME/go/bin"
+alias old-node=$HOME/.volta/tools/image/node/14.0.0/bin/node
+"#
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn removes_entries_from_fish_profile() {
+        let config = r#"set -gx VOLTA_HOME "$HOME/.volta"
+set -gx PATH "$VOLTA_HOME/bin" $PATH
+set -gx PATH "$HOME/.volta/tools/image/node/14.0.0/bin" $PATH
+fish_add_path ~/.volta/tools/image/node/14.0.0/bin"#;
+        let spellings = spellings_for("/home/me/.volta/tools/image/node/14.0.0/bin");
+
+        assert_eq!(
+            remove_entries(config, &spellings).unwrap(),
+            r#"set -gx VOLTA_HOME "$HOME/.volta"
+set -gx PATH "$VOLTA_HOME/bin" $PATH"#
+        );
+    }
+
+    #[test]
+    fn leaves_unrelated_profiles_alone() {
+        let profile = "export PATH=\"$HOME/.volta/tools/image/node/14.0.0/bin2:$PATH\"\n";
+        let spellings = spellings_for("/home/me/.volta/tools/image/node/14.0.0/bin");
+
+        assert!(!mentions(profile, &spellings));
+        assert_eq!(remove_entries(profile, &spellings), None);
+    }
+}
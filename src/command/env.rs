@@ -0,0 +1,72 @@
+//! Define `volta env`, which prints a PATH that runs the default tools without going through the
+//! shims, for cron jobs and systemd units that don't load the user's shell profile.
+
+use volta_core::error::{ErrorKind, ExitCode, Fallible};
+use volta_core::session::{ActivityKind, Session};
+
+use crate::command::Command;
+
+#[derive(clap::Args)]
+pub(crate) struct Env {
+    /// Print a single `PATH=...` line to paste into a crontab or a systemd `Environment=`
+    /// directive, instead of a shell command
+    #[arg(long)]
+    cron: bool,
+}
+
+impl Command for Env {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_subcommand_start(ActivityKind::Env);
+
+        let platform = session
+            .default_platform()?
+            .ok_or(ErrorKind::NoPlatform)?
+            .as_default();
+        let path = platform.into_image().pinned_path()?;
+        let path = path.to_string_lossy();
+
+        if self.cron {
+            println!("PATH={}", path);
+        } else {
+            println!("{}", shell_command(&path));
+        }
+
+        session.add_event_end(ActivityKind::Env, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+/// Formats a command that sets the PATH in the current shell
+#[cfg(unix)]
+fn shell_command(path: &str) -> String {
+    let mut quoted = String::with_capacity(path.len());
+    for c in path.chars() {
+        if matches!(c, '"' | '\\' | '$' | '`') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    format!("export PATH=\"{}\"", quoted)
+}
+
+#[cfg(windows)]
+fn shell_command(path: &str) -> String {
+    format!("set \"PATH={}\"", path)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shell_command_quotes_special_characters() {
+        assert_eq!(
+            shell_command("/home/user/.volta/bin:/usr/bin"),
+            r#"export PATH="/home/user/.volta/bin:/usr/bin""#
+        );
+        assert_eq!(
+            shell_command(r#"/opt/$HOME/"bin":/usr/bin"#),
+            r#"export PATH="/opt/\$HOME/\"bin\":/usr/bin""#
+        );
+    }
+}
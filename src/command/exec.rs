@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+
+use crate::command::run::run_command;
+use crate::command::Command;
+use volta_core::error::{ErrorKind, ExitCode, Fallible};
+use volta_core::platform::{CliPlatform, InheritOption};
+use volta_core::session::{ActivityKind, Session};
+use volta_core::tool::{node, npm, pnpm, yarn, Spec};
+
+#[derive(Debug, clap::Args)]
+pub(crate) struct Exec {
+    /// The tool to run, with its version (like `node@20.11.0`, `node@lts`, or `yarn@1`), followed
+    /// by any arguments to pass to it
+    //
+    // Everything after the tool is passed to it verbatim, so flags that collide with Volta's own
+    // (e.g. `--verbose` or `--version`) are never interpreted by Volta. The tool is the first
+    // value, rather than an argument of its own, as otherwise Clap would interpret the flags that
+    // directly follow it. A `--` right after the tool is accepted but not required.
+    #[arg(
+        allow_hyphen_values = true,
+        trailing_var_arg = true,
+        value_name = "TOOL@VERSION",
+        required = true
+    )]
+    tool_and_args: Vec<OsString>,
+}
+
+impl Command for Exec {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_subcommand_start(ActivityKind::Exec);
+
+        let (tool, args) = self.split_tool()?;
+        let (command, platform) = platform_for(Spec::try_from_str(tool)?, session)?;
+
+        run_command(
+            ActivityKind::Exec,
+            OsStr::new(command),
+            args,
+            &HashMap::new(),
+            platform,
+            session,
+        )
+    }
+}
+
+impl Exec {
+    /// Splits the tool spec from the arguments to pass to the tool, dropping the `--` between them
+    fn split_tool(&self) -> Fallible<(&str, &[OsString])> {
+        // Safety: At least one value is required for `tool_and_args`, so there must be at least
+        // one value in the list. If no value is provided, Clap will show a "required argument
+        // missing" message and this function won't be called.
+        let tool = &self.tool_and_args[0];
+        let tool = tool.to_str().ok_or_else(|| ErrorKind::ParseToolSpecError {
+            tool_spec: tool.to_string_lossy().into_owned(),
+        })?;
+
+        let args = match &self.tool_and_args[1..] {
+            [separator, args @ ..] if separator == "--" => args,
+            args => args,
+        };
+
+        Ok((tool, args))
+    }
+}
+
+/// Resolves the version of the tool, returning its executable and a platform that overrides
+/// only that tool
+fn platform_for(spec: Spec, session: &mut Session) -> Fallible<(&'static str, CliPlatform)> {
+    let mut platform = CliPlatform {
+        node: None,
+        npm: InheritOption::Inherit,
+        pnpm: InheritOption::Inherit,
+        yarn: InheritOption::Inherit,
+    };
+
+    let command = match spec {
+        Spec::Node(version) => {
+            platform.node = Some(node::resolve(version, session)?);
+            "node"
+        }
+        Spec::Npm(version) => {
+            // `npm@bundled` runs the npm bundled with Node, as `volta run --bundled-npm` does
+            platform.npm = match npm::resolve(version, session)? {
+                Some(npm) => InheritOption::Some(npm),
+                None => InheritOption::None,
+            };
+            "npm"
+        }
+        Spec::Pnpm(version) => {
+            platform.pnpm = InheritOption::Some(pnpm::resolve(version, session)?);
+            "pnpm"
+        }
+        Spec::Yarn(version) => {
+            platform.yarn = InheritOption::Some(yarn::resolve(version, session)?);
+            "yarn"
+        }
+        Spec::Package(..) | Spec::PackageSource(_) => {
+            return Err(ErrorKind::CannotExecPackage {
+                package: spec.name().to_owned(),
+            }
+            .into())
+        }
+    };
+
+    Ok((command, platform))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+
+    use crate::cli::{Subcommand, Volta};
+    use clap::Parser;
+
+    fn parse_exec<const N: usize>(args: [&str; N]) -> (Volta, String, Vec<OsString>) {
+        let volta = Volta::try_parse_from(args).expect("arguments should parse");
+        let (tool, args) = match &volta.command {
+            Some(Subcommand::Exec(exec)) => {
+                let (tool, args) = exec.split_tool().expect("tool should be valid");
+                (tool.to_owned(), args.to_vec())
+            }
+            _ => panic!("expected the exec subcommand"),
+        };
+        (volta, tool, args)
+    }
+
+    #[test]
+    fn separator_after_tool_is_optional() {
+        let (_, tool, without) = parse_exec(["volta", "exec", "node@20", "script.js"]);
+        let (_, _, with) = parse_exec(["volta", "exec", "node@20", "--", "script.js"]);
+
+        assert_eq!(tool, "node@20");
+        assert_eq!(without, ["script.js"]);
+        assert_eq!(with, without);
+    }
+
+    #[test]
+    fn tool_flags_are_not_interpreted() {
+        let (volta, _, args) = parse_exec([
+            "volta",
+            "exec",
+            "node@20",
+            "--verbose",
+            "--version",
+            "--help",
+        ]);
+
+        assert!(!volta.verbose);
+        assert!(!volta.version);
+        assert_eq!(args, ["--verbose", "--version", "--help"]);
+    }
+
+    #[test]
+    fn later_separators_are_passed_through() {
+        let (_, _, args) = parse_exec(["volta", "exec", "yarn@1", "--", "--", "--version"]);
+        assert_eq!(args, ["--", "--version"]);
+
+        let (_, _, args) = parse_exec(["volta", "exec", "yarn@1", "run", "--", "--watch"]);
+        assert_eq!(args, ["run", "--", "--watch"]);
+    }
+
+    #[test]
+    fn volta_flags_before_tool_are_interpreted() {
+        let (volta, tool, args) = parse_exec(["volta", "exec", "--verbose", "node@lts", "-v"]);
+
+        assert!(volta.verbose);
+        assert_eq!(tool, "node@lts");
+        assert_eq!(args, ["-v"]);
+    }
+}
@@ -0,0 +1,60 @@
+//! Fetches the newest release of each active Node LTS line, with `volta fetch --all-lts`
+//!
+//! This is meant for provisioning images (such as for CI) that test against every supported
+//! Node version. The index is read once for all of the lines, and the versions that are already
+//! fetched are skipped, so running the command again only fetches the lines that have moved on.
+
+use log::debug;
+use volta_core::error::{ErrorKind, Fallible};
+use volta_core::inventory::node_available;
+use volta_core::session::Session;
+use volta_core::style::tool_version;
+use volta_core::tool::{node, Node, Spec, Tool};
+use volta_core::version::VersionSpec;
+
+use crate::command::bulk::{Status, Summary};
+
+/// Checks that the tools given along with `--all-lts` are at most a bare `node`
+pub(super) fn check_tools(tools: &[String]) -> Fallible<()> {
+    for tool in tools {
+        match Spec::try_from_str(tool)? {
+            Spec::Node(VersionSpec::None) => {}
+            _ => return Err(ErrorKind::AllLtsInvalidTool { tool: tool.clone() }.into()),
+        }
+    }
+    Ok(())
+}
+
+/// Fetches the newest release of each active LTS line, one after another
+pub(super) fn fetch_all(session: &mut Session) -> Fallible<()> {
+    let versions = node::resolve_all_lts(session)?;
+
+    let mut summary = Summary::new("fetched", "already fetched");
+    for version in versions {
+        let label = tool_version("node", &version);
+        let status = if node_available(&version)? {
+            debug!("Skipping {}, it is already fetched", label);
+            Status::Skipped
+        } else {
+            Box::new(Node::new(version)).fetch(session)?;
+            Status::Done
+        };
+        summary.record(label, status);
+    }
+
+    summary.report();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_accepts_a_bare_node() {
+        assert!(check_tools(&[]).is_ok());
+        assert!(check_tools(&["node".into()]).is_ok());
+        assert!(check_tools(&["node@20".into()]).is_err());
+        assert!(check_tools(&["yarn".into()]).is_err());
+    }
+}
@@ -0,0 +1,234 @@
+//! Fetches the tools listed in a manifest file, with `volta fetch --manifest`
+//!
+//! The manifest is a JSON document with a single `tools` list, holding the same specs as the
+//! command line. Every entry is checked before anything is fetched, so that a mistake in a long
+//! list is reported along with all of the others, rather than after a lengthy partial fetch.
+
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+use volta_core::error::{Context, ErrorKind, Fallible, VoltaError};
+use volta_core::inventory::{node_available, npm_available, pnpm_available, yarn_available};
+use volta_core::session::Session;
+use volta_core::style::tool_version;
+use volta_core::tool::{node, npm, pnpm, yarn, Node, Npm, Pnpm, Spec, Tool, Yarn};
+use volta_core::version::{VersionSpec, VersionTag};
+
+use crate::command::bulk::{Status, Summary};
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawManifest {
+    tools: Vec<String>,
+}
+
+/// A validated entry of the manifest
+pub(super) struct Entry {
+    raw: String,
+    spec: Spec,
+}
+
+/// Reads a manifest, reporting every invalid entry at once
+pub(super) fn read(file: &Path, session: &mut Session) -> Fallible<Vec<Entry>> {
+    let contents = fs::read_to_string(file).with_context(|| ErrorKind::ReadFetchManifestError {
+        file: file.to_owned(),
+    })?;
+
+    parse(&contents, session.pnpm_enabled()?).map_err(|errors| {
+        ErrorKind::InvalidFetchManifest {
+            file: file.to_owned(),
+            errors,
+        }
+        .into()
+    })
+}
+
+fn parse(contents: &str, pnpm_enabled: bool) -> Result<Vec<Entry>, Vec<String>> {
+    let manifest: RawManifest =
+        serde_json::from_str(contents).map_err(|error| vec![error.to_string()])?;
+
+    if manifest.tools.is_empty() {
+        return Err(vec!["`tools` doesn't list any tools".into()]);
+    }
+
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    for (index, raw) in manifest.tools.into_iter().enumerate() {
+        match validate(&raw, pnpm_enabled) {
+            Ok(spec) => entries.push(Entry { raw, spec }),
+            Err(reason) => errors.push(format!("tools[{}] '{}': {}", index, raw, reason)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(entries)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parses an entry, checking that it is a tool that can be fetched
+fn validate(raw: &str, pnpm_enabled: bool) -> Result<Spec, String> {
+    let spec = Spec::try_from_str(raw).map_err(|error| summary(&error))?;
+
+    match &spec {
+        Spec::Npm(VersionSpec::Tag(VersionTag::Custom(tag))) if tag == npm::BUNDLED_FOR_TAG => {
+            Err("`npm@bundled-for` is only supported on the command line".into())
+        }
+        Spec::Node(_) | Spec::Npm(_) | Spec::Yarn(_) => Ok(spec),
+        Spec::Pnpm(_) if pnpm_enabled => Ok(spec),
+        _ => Err("packages can't be fetched, only installed".into()),
+    }
+}
+
+/// Fetches every entry, continuing past failures so that they can be reported together
+pub(super) fn fetch_all(file: &Path, entries: Vec<Entry>, session: &mut Session) -> Fallible<()> {
+    let mut results = Summary::new("fetched", "already fetched");
+    let mut failures = Vec::new();
+
+    for entry in entries {
+        match fetch_entry(entry.spec, session) {
+            Ok((tool, status)) => results.record(tool, status),
+            Err(error) => {
+                results.record(entry.raw.clone(), Status::Failed);
+                failures.push((entry.raw, error));
+            }
+        }
+    }
+
+    results.report();
+    if failures.is_empty() {
+        return Ok(());
+    }
+
+    let failed = failures
+        .iter()
+        .map(|(tool, error)| (tool.clone(), error.to_string()))
+        .collect();
+    let (_, error) = failures.remove(0);
+    let kind = ErrorKind::FetchManifestError {
+        file: file.to_owned(),
+        fetched: results.tools(Status::Done),
+        skipped: results.tools(Status::Skipped),
+        failed,
+        exit_code: error.exit_code(),
+    };
+
+    Err(VoltaError::from_source(error, kind))
+}
+
+/// Fetches an entry, returning the tool that it resolved to and whether it was already fetched
+fn fetch_entry(spec: Spec, session: &mut Session) -> Fallible<(String, Status)> {
+    let (available, label, tool): (bool, String, Box<dyn Tool>) = match spec {
+        Spec::Node(version) => {
+            let version = node::resolve(version, session)?;
+            let label = tool_version("node", &version);
+            (
+                node_available(&version)?,
+                label,
+                Box::new(Node::new(version)),
+            )
+        }
+        Spec::Npm(version) => match npm::resolve(version, session)? {
+            Some(version) => {
+                let label = tool_version("npm", &version);
+                (npm_available(&version)?, label, Box::new(Npm::new(version)))
+            }
+            // The bundled npm comes with Node, so there is nothing to fetch
+            None => return Ok(("npm@bundled".into(), Status::Skipped)),
+        },
+        Spec::Pnpm(version) => {
+            let version = pnpm::resolve(version, session)?;
+            let label = tool_version("pnpm", &version);
+            (
+                pnpm_available(&version)?,
+                label,
+                Box::new(Pnpm::new(version)),
+            )
+        }
+        Spec::Yarn(version) => {
+            let version = yarn::resolve(version, session)?;
+            let label = tool_version("yarn", &version);
+            (
+                yarn_available(&version)?,
+                label,
+                Box::new(Yarn::new(version)),
+            )
+        }
+        // Entries are validated before fetching, so this is only reached for a package
+        other => {
+            return Err(ErrorKind::CannotFetchPackage {
+                package: other.to_string(),
+            }
+            .into())
+        }
+    };
+
+    if available {
+        Ok((label, Status::Skipped))
+    } else {
+        tool.fetch(session)?;
+        Ok((label, Status::Done))
+    }
+}
+
+/// The first line of an error message, which is enough to identify the problem in a list
+fn summary(error: &VoltaError) -> String {
+    let message = error.to_string();
+    message.lines().next().unwrap_or_default().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid(contents: &str) -> Vec<String> {
+        match parse(contents, false) {
+            Ok(_) => panic!("expected {} to be invalid", contents),
+            Err(errors) => errors,
+        }
+    }
+
+    #[test]
+    fn parses_tool_specs() {
+        let entries = parse(r#"{ "tools": ["node@20.11.1", "npm@10", "yarn"] }"#, false).unwrap();
+        let raw: Vec<&str> = entries.iter().map(|entry| entry.raw.as_str()).collect();
+        assert_eq!(raw, ["node@20.11.1", "npm@10", "yarn"]);
+
+        assert!(parse(r#"{ "tools": ["pnpm@8"] }"#, true).is_ok());
+    }
+
+    #[test]
+    fn reports_every_invalid_entry() {
+        let errors =
+            invalid(r#"{ "tools": ["node@20", "cowsay@1.5.0", "Bad Name@1", "npm@bundled-for"] }"#);
+
+        assert_eq!(errors.len(), 3);
+        assert_eq!(
+            errors[0],
+            "tools[1] 'cowsay@1.5.0': packages can't be fetched, only installed"
+        );
+        assert!(errors[1].starts_with("tools[2] 'Bad Name@1': "));
+        assert_eq!(
+            errors[2],
+            "tools[3] 'npm@bundled-for': `npm@bundled-for` is only supported on the command line"
+        );
+
+        // Without the pnpm feature, pnpm is a package like any other
+        assert_eq!(
+            invalid(r#"{ "tools": ["pnpm@8"] }"#),
+            ["tools[0] 'pnpm@8': packages can't be fetched, only installed"]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_manifests() {
+        assert_eq!(
+            invalid(r#"{ "tools": [] }"#),
+            ["`tools` doesn't list any tools"]
+        );
+        assert_eq!(invalid(r#"{ "node": ["20"] }"#).len(), 1);
+        assert_eq!(invalid(r#"["node@20"]"#).len(), 1);
+    }
+}
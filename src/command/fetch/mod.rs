@@ -0,0 +1,118 @@
+use std::path::PathBuf;
+
+use volta_core::error::{ErrorKind, ExitCode, Fallible};
+use volta_core::session::{ActivityKind, Session};
+use volta_core::style::enable_json_progress;
+use volta_core::tool::{node, npm, Spec};
+use volta_core::version::{VersionSpec, VersionTag};
+
+use crate::command::Command;
+
+mod lts;
+mod manifest;
+
+#[derive(clap::Args)]
+pub(crate) struct Fetch {
+    /// Tools to fetch, like `node`, `yarn@latest` or `your-package@^14.4.3`.
+    ///
+    /// Use `npm@bundled-for node@<version>` to fetch a Node version and record the npm bundled
+    /// with it, so that the bundled npm can be resolved later without network access.
+    #[arg(value_name = "tool[@version]", required_unless_present_any = ["manifest", "all_lts"])]
+    tools: Vec<String>,
+
+    /// Fetch the tools listed in a JSON file, like `{ "tools": ["node@20", "yarn@1.22"] }`
+    ///
+    /// Every entry is checked before fetching, and tools that are already fetched are skipped.
+    #[arg(long, value_name = "file", conflicts_with = "tools")]
+    manifest: Option<PathBuf>,
+
+    /// Fetch the newest release of each Node LTS line that hasn't reached end-of-life
+    ///
+    /// Releases that are already fetched are skipped. Use with `node` or on its own.
+    #[arg(long, conflicts_with = "manifest")]
+    all_lts: bool,
+
+    /// Ignore the version policies that don't forbid overriding them
+    #[arg(long)]
+    override_policy: bool,
+
+    /// Download the Node version index again, even if the cached copy hasn't expired
+    #[arg(long)]
+    refresh_index: bool,
+
+    /// Report download progress as JSON lines on stderr instead of drawing progress bars (the
+    /// same as setting VOLTA_PROGRESS=json)
+    #[arg(long, value_name = "format", value_parser = ["json"])]
+    progress: Option<String>,
+}
+
+impl Command for Fetch {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_subcommand_start(ActivityKind::Fetch);
+        session.allow_installs();
+        if self.override_policy {
+            session.override_policies();
+        }
+        if self.progress.is_some() {
+            enable_json_progress();
+        }
+        if self.refresh_index {
+            node::refresh_index();
+        }
+
+        if let Some(file) = &self.manifest {
+            let entries = manifest::read(file, session)?;
+            manifest::fetch_all(file, entries, session)?;
+
+            session.add_event_end(ActivityKind::Fetch, ExitCode::Success);
+            return Ok(ExitCode::Success);
+        }
+
+        if self.all_lts {
+            lts::check_tools(&self.tools)?;
+            lts::fetch_all(session)?;
+
+            session.add_event_end(ActivityKind::Fetch, ExitCode::Success);
+            return Ok(ExitCode::Success);
+        }
+
+        let mut tools = Spec::from_strings(&self.tools, "fetch")?;
+
+        if let Some(index) = tools.iter().position(is_bundled_for) {
+            tools.remove(index);
+
+            // With `npm@bundled-for`, the Node versions are the ones to record the bundled npm for
+            let mut nodes = Vec::new();
+            let mut rest = Vec::new();
+            for tool in tools {
+                match tool {
+                    Spec::Node(version) => nodes.push(version),
+                    other => rest.push(other),
+                }
+            }
+
+            if nodes.is_empty() {
+                return Err(ErrorKind::NoBundledNpmNodeVersion.into());
+            }
+
+            for version in nodes {
+                npm::fetch_bundled_for(version, session)?;
+            }
+            tools = rest;
+        }
+
+        for tool in tools {
+            tool.resolve(session)?.fetch(session)?;
+        }
+
+        session.add_event_end(ActivityKind::Fetch, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+fn is_bundled_for(tool: &Spec) -> bool {
+    matches!(
+        tool,
+        Spec::Npm(VersionSpec::Tag(VersionTag::Custom(tag))) if tag == npm::BUNDLED_FOR_TAG
+    )
+}
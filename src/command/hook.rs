@@ -0,0 +1,431 @@
+//! Define `volta hook test`, which shows the hooks that apply to a version of a tool and what they
+//! resolve to, without fetching or installing anything.
+
+use std::fmt;
+use std::path::PathBuf;
+
+use node_semver::Version;
+
+use crate::command::Command;
+use volta_core::error::{ExitCode, Fallible};
+use volta_core::hook::tool::{probe, DistroHook, MetadataHook};
+use volta_core::hook::{HookConfig, RegistryFormat};
+use volta_core::session::{ActivityKind, Session};
+use volta_core::style::tool_version;
+use volta_core::tool::{Node, Npm, Pnpm, Yarn};
+use volta_core::version::parse_version;
+
+static INDENTATION: &str = "    ";
+
+#[derive(clap::Args)]
+pub(crate) struct Hook {
+    #[command(subcommand)]
+    command: HookCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum HookCommand {
+    /// Shows which hooks apply to a version of a tool, and the URLs they resolve to
+    ///
+    /// Bin hooks are run with the same arguments as during an install, but nothing is
+    /// downloaded.
+    Test {
+        /// The tool to check the hooks of
+        #[arg(value_enum)]
+        tool: HookTool,
+
+        /// The exact version of the tool to resolve the hooks for
+        version: String,
+
+        /// Sends a HEAD request to each resolved URL and reports the status code
+        #[arg(long)]
+        probe: bool,
+    },
+}
+
+#[derive(clap::ValueEnum, Copy, Clone)]
+enum HookTool {
+    Node,
+    Npm,
+    Pnpm,
+    Yarn,
+}
+
+impl fmt::Display for HookTool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            HookTool::Node => "node",
+            HookTool::Npm => "npm",
+            HookTool::Pnpm => "pnpm",
+            HookTool::Yarn => "yarn",
+        })
+    }
+}
+
+/// A hook that is configured for one of the lookups a tool makes
+#[derive(Clone, Copy)]
+enum Configured<'a> {
+    Distro(&'a DistroHook),
+    Metadata(&'a MetadataHook),
+}
+
+impl Configured<'_> {
+    fn kind(self) -> &'static str {
+        match self {
+            Configured::Distro(DistroHook::Prefix(_))
+            | Configured::Metadata(MetadataHook::Prefix(_)) => "prefix",
+            Configured::Distro(DistroHook::Template(_))
+            | Configured::Metadata(MetadataHook::Template(_)) => "template",
+            Configured::Distro(DistroHook::Bin { .. })
+            | Configured::Metadata(MetadataHook::Bin { .. }) => "bin",
+        }
+    }
+
+    /// The command line that a bin hook runs, including the version passed to distro hooks
+    fn command(self, version: &Version) -> Option<String> {
+        match self {
+            Configured::Distro(DistroHook::Bin { bin, .. }) => {
+                Some(format!("{} {}", bin.trim(), version))
+            }
+            Configured::Metadata(MetadataHook::Bin { bin, .. }) => Some(bin.trim().to_owned()),
+            _ => None,
+        }
+    }
+
+    fn resolve(self, version: &Version, file_name: &str) -> Fallible<String> {
+        match self {
+            Configured::Distro(hook) => hook.resolve(version, file_name),
+            Configured::Metadata(hook) => hook.resolve(file_name),
+        }
+    }
+}
+
+/// One of the lookups that a tool makes, with the file name that is passed to its hook
+struct Lookup<'a> {
+    name: &'static str,
+    file_name: String,
+    hook: Option<Configured<'a>>,
+}
+
+/// Lists the lookups that a tool makes while it is installed, along with the hooks for them
+///
+/// npm and pnpm find their latest versions in their indexes, so they don't have a `latest` lookup.
+fn lookups<'a>(tool: HookTool, hooks: &'a HookConfig, version: &Version) -> Vec<Lookup<'a>> {
+    let version_str = version.to_string();
+
+    match tool {
+        HookTool::Node => {
+            let node = hooks.node();
+            vec![
+                Lookup {
+                    name: "distro",
+                    file_name: Node::archive_filename(version),
+                    hook: node.and_then(|h| h.distro.as_ref()).map(Configured::Distro),
+                },
+                Lookup {
+                    name: "latest",
+                    file_name: "index.json".into(),
+                    hook: node
+                        .and_then(|h| h.latest.as_ref())
+                        .map(Configured::Metadata),
+                },
+                Lookup {
+                    name: "index",
+                    file_name: "index.json".into(),
+                    hook: node
+                        .and_then(|h| h.index.as_ref())
+                        .map(Configured::Metadata),
+                },
+            ]
+        }
+        HookTool::Npm => {
+            let npm = hooks.npm();
+            vec![
+                Lookup {
+                    name: "distro",
+                    file_name: Npm::archive_filename(&version_str),
+                    hook: npm.and_then(|h| h.distro.as_ref()).map(Configured::Distro),
+                },
+                Lookup {
+                    name: "index",
+                    file_name: "npm".into(),
+                    hook: npm.and_then(|h| h.index.as_ref()).map(Configured::Metadata),
+                },
+            ]
+        }
+        HookTool::Pnpm => {
+            let pnpm = hooks.pnpm();
+            vec![
+                Lookup {
+                    name: "distro",
+                    file_name: Pnpm::archive_filename(&version_str),
+                    hook: pnpm.and_then(|h| h.distro.as_ref()).map(Configured::Distro),
+                },
+                Lookup {
+                    name: "index",
+                    file_name: "pnpm".into(),
+                    hook: pnpm
+                        .and_then(|h| h.index.as_ref())
+                        .map(Configured::Metadata),
+                },
+            ]
+        }
+        HookTool::Yarn => {
+            let yarn = hooks.yarn();
+            let index = yarn.and_then(|h| h.index.as_ref());
+            vec![
+                Lookup {
+                    name: "distro",
+                    file_name: Yarn::archive_filename(&version_str),
+                    hook: yarn.and_then(|h| h.distro.as_ref()).map(Configured::Distro),
+                },
+                Lookup {
+                    name: "latest",
+                    file_name: "latest-version".into(),
+                    hook: yarn
+                        .and_then(|h| h.latest.as_ref())
+                        .map(Configured::Metadata),
+                },
+                Lookup {
+                    name: "index",
+                    file_name: match index.map(|h| &h.format) {
+                        Some(RegistryFormat::Github) => "releases".into(),
+                        _ => String::new(),
+                    },
+                    hook: index.map(|h| Configured::Metadata(&h.metadata)),
+                },
+            ]
+        }
+    }
+}
+
+/// What a single lookup resolves to
+struct Report {
+    name: &'static str,
+    applied: Option<Applied>,
+}
+
+/// The hook that applies to a lookup, and the result of resolving it
+struct Applied {
+    kind: &'static str,
+    source: Option<PathBuf>,
+    command: Option<String>,
+    resolved: Result<String, String>,
+    status: Option<Result<u16, String>>,
+}
+
+impl Applied {
+    fn failed(&self) -> bool {
+        match (&self.resolved, &self.status) {
+            (Err(_), _) | (_, Some(Err(_))) => true,
+            (_, Some(Ok(status))) => !(200..400).contains(status),
+            _ => false,
+        }
+    }
+}
+
+impl Command for Hook {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_subcommand_start(ActivityKind::Hook);
+
+        let HookCommand::Test {
+            tool,
+            version,
+            probe: should_probe,
+        } = self.command;
+        let version = parse_version(version)?;
+
+        let layers = HookConfig::layers(session.project()?)?;
+        let hooks = session.hooks()?;
+
+        let reports: Vec<Report> = lookups(tool, hooks, &version)
+            .into_iter()
+            .enumerate()
+            .map(|(index, lookup)| {
+                let applied = lookup.hook.map(|hook| {
+                    // The current hooks take each hook from the first file that defines it
+                    let source = layers
+                        .iter()
+                        .find(|(_, layer)| lookups(tool, layer, &version)[index].hook.is_some())
+                        .map(|(file, _)| file.clone());
+                    let resolved = hook
+                        .resolve(&version, &lookup.file_name)
+                        .map_err(|error| error.to_string());
+                    let status = match &resolved {
+                        Ok(url) if should_probe => {
+                            Some(probe(url).map_err(|error| error.to_string()))
+                        }
+                        _ => None,
+                    };
+
+                    Applied {
+                        kind: hook.kind(),
+                        source,
+                        command: hook.command(&version),
+                        resolved,
+                        status,
+                    }
+                });
+
+                Report {
+                    name: lookup.name,
+                    applied,
+                }
+            })
+            .collect();
+
+        println!("{}", format_human(tool, &version, &reports));
+
+        let exit_code = if reports
+            .iter()
+            .any(|report| report.applied.as_ref().is_some_and(Applied::failed))
+        {
+            ExitCode::ConfigurationError
+        } else {
+            ExitCode::Success
+        };
+
+        session.add_event_end(ActivityKind::Hook, exit_code);
+        Ok(exit_code)
+    }
+}
+
+fn format_human(tool: HookTool, version: &Version, reports: &[Report]) -> String {
+    let width = reports
+        .iter()
+        .map(|report| report.name.len())
+        .max()
+        .unwrap_or_default();
+    let detail_indent = format!("{}{:width$}  ", INDENTATION, "", width = width);
+
+    let mut lines = vec![
+        format!("⚡️ Hooks for {}:", tool_version(tool, version)),
+        String::new(),
+    ];
+
+    for report in reports {
+        let applied = match &report.applied {
+            Some(applied) => applied,
+            None => {
+                lines.push(format!(
+                    "{}{:<width$}  not configured",
+                    INDENTATION,
+                    report.name,
+                    width = width
+                ));
+                continue;
+            }
+        };
+
+        let source = match &applied.source {
+            Some(file) => format!(" from {}", file.display()),
+            None => String::new(),
+        };
+        lines.push(format!(
+            "{}{:<width$}  {} hook{}",
+            INDENTATION,
+            report.name,
+            applied.kind,
+            source,
+            width = width
+        ));
+
+        let mut details = Vec::new();
+        if let Some(command) = &applied.command {
+            details.push(format!("command: {}", command));
+        }
+        match &applied.resolved {
+            Ok(output) if applied.command.is_some() => details.push(format!("output: {}", output)),
+            Ok(url) => details.push(url.clone()),
+            Err(error) => details.push(format!("error: {}", error)),
+        }
+        match &applied.status {
+            Some(Ok(status)) => details.push(format!("status: {}", status)),
+            Some(Err(error)) => details.push(format!("error: {}", error)),
+            None => {}
+        }
+
+        for detail in details {
+            for line in detail.lines() {
+                lines.push(format!("{}{}", detail_indent, line).trim_end().to_owned());
+            }
+        }
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_reports() -> Vec<Report> {
+        vec![
+            Report {
+                name: "distro",
+                applied: Some(Applied {
+                    kind: "template",
+                    source: Some(PathBuf::from("/home/user/.volta/hooks.json")),
+                    command: None,
+                    resolved: Ok("https://mirror.example/node/v20.11.1.tar.gz".into()),
+                    status: Some(Ok(200)),
+                }),
+            },
+            Report {
+                name: "latest",
+                applied: None,
+            },
+            Report {
+                name: "index",
+                applied: Some(Applied {
+                    kind: "bin",
+                    source: Some(PathBuf::from("/home/user/app/.volta/hooks.json")),
+                    command: Some("./index-url".into()),
+                    resolved: Err("Hook command './index-url' indicated a failure.\n\nPlease verify the requested tool and version.".into()),
+                    status: None,
+                }),
+            },
+        ]
+    }
+
+    #[test]
+    fn human_shows_each_lookup() {
+        let expected = "⚡️ Hooks for node@20.11.1:
+
+    distro  template hook from /home/user/.volta/hooks.json
+            https://mirror.example/node/v20.11.1.tar.gz
+            status: 200
+    latest  not configured
+    index   bin hook from /home/user/app/.volta/hooks.json
+            command: ./index-url
+            error: Hook command './index-url' indicated a failure.
+
+            Please verify the requested tool and version.";
+
+        assert_eq!(
+            format_human(
+                HookTool::Node,
+                &Version::from((20, 11, 1)),
+                &sample_reports()
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn failures_are_detected() {
+        let reports = sample_reports();
+
+        assert!(!reports[0].applied.as_ref().unwrap().failed());
+        assert!(reports[2].applied.as_ref().unwrap().failed());
+
+        let not_found = Applied {
+            kind: "prefix",
+            source: None,
+            command: None,
+            resolved: Ok("https://mirror.example/missing".into()),
+            status: Some(Ok(404)),
+        };
+        assert!(not_found.failed());
+    }
+}
@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use volta_core::error::{ExitCode, Fallible};
+use volta_core::import::{default_nvm_dir, import_nvm, NvmImportMode};
+use volta_core::session::{ActivityKind, Session};
+
+use crate::command::Command;
+
+#[derive(clap::Args)]
+pub(crate) struct Import {
+    #[command(subcommand)]
+    source: Source,
+}
+
+#[derive(clap::Subcommand)]
+enum Source {
+    /// Imports the Node versions and default alias from nvm
+    ///
+    /// The nvm directory is only read, never modified.
+    Nvm(Nvm),
+}
+
+#[derive(clap::Args)]
+struct Nvm {
+    /// The nvm directory to import from [default: $NVM_DIR or ~/.nvm]
+    #[arg(long, value_name = "path")]
+    nvm_dir: Option<PathBuf>,
+
+    /// Copies the existing installations instead of fetching them again
+    #[arg(long)]
+    link_existing: bool,
+}
+
+impl Command for Import {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_subcommand_start(ActivityKind::Import);
+
+        match self.source {
+            Source::Nvm(nvm) => {
+                let nvm_dir = match nvm.nvm_dir {
+                    Some(dir) => dir,
+                    None => default_nvm_dir()?,
+                };
+                let mode = if nvm.link_existing {
+                    NvmImportMode::LinkExisting
+                } else {
+                    NvmImportMode::Fetch
+                };
+
+                import_nvm(&nvm_dir, mode, session)?;
+            }
+        }
+
+        session.add_event_end(ActivityKind::Import, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
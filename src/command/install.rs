@@ -1,25 +1,196 @@
-use volta_core::error::{ExitCode, Fallible};
+use std::path::PathBuf;
+
+use log::info;
+use volta_core::error::{report_error, ErrorKind, ExitCode, Fallible};
+use volta_core::layout::volta_home;
+use volta_core::project::{Project, ToolsManifest};
 use volta_core::session::{ActivityKind, Session};
+use volta_core::style::{enable_json_progress, tool_version};
+use volta_core::tool::node::set_arch_override;
+use volta_core::tool::package::PackageConfig;
 use volta_core::tool::Spec;
+use volta_core::version::VersionSpec;
 
-use crate::command::Command;
+use crate::command::bulk::{Status, Summary};
+use crate::command::{print_porcelain, Command};
 
 #[derive(clap::Args)]
 pub(crate) struct Install {
     /// Tools to install, like `node`, `yarn@latest` or `your-package@^14.4.3`.
-    #[arg(value_name = "tool[@version]", required = true)]
+    #[arg(value_name = "tool[@version]", required_unless_present = "manifest")]
     tools: Vec<String>,
+
+    /// Install the global packages listed in the project's `.volta/tools.json`, with the
+    /// project's platform, continuing past the ones that fail
+    #[arg(long, conflicts_with_all = ["tools", "offline_from"])]
+    manifest: bool,
+
+    /// Remove copies of the packages that were installed globally with npm, outside of Volta
+    ///
+    /// Copies in the Node images managed by Volta are only reported, since every tool using that
+    /// version of Node shares them.
+    #[arg(long)]
+    take_over: bool,
+
+    /// Install packages without creating shims for their executables, so that they don't shadow
+    /// the versions installed in projects. Install again without the flag to add the shims.
+    #[arg(long)]
+    no_shims: bool,
+
+    /// Use the Node build for another architecture, like `x64` to run under Rosetta 2. Its image
+    /// is kept apart from the native one, and is used in projects that set `volta.arch`.
+    #[arg(long, value_name = "arch")]
+    arch: Option<String>,
+
+    /// Install the tools from a directory of archives instead of downloading them, which requires
+    /// exact versions, like `node@18.16.0`
+    #[arg(long, value_name = "dir")]
+    offline_from: Option<PathBuf>,
+
+    /// Install only versions that are available locally, without network access (the same as
+    /// setting VOLTA_OFFLINE=1)
+    #[arg(long)]
+    offline: bool,
+
+    /// Ignore the version policies that don't forbid overriding them
+    #[arg(long)]
+    override_policy: bool,
+    /// Report download progress as JSON lines on stderr instead of drawing progress bars (the
+    /// same as setting VOLTA_PROGRESS=json)
+    #[arg(long, value_name = "format", value_parser = ["json"])]
+    progress: Option<String>,
+
+    /// Print one line for each file that was changed, like `shim created <path>`, instead of the
+    /// usual messages, which are moved to stderr
+    #[arg(long)]
+    pub(crate) porcelain: bool,
 }
 
 impl Command for Install {
     fn run(self, session: &mut Session) -> Fallible<ExitCode> {
-        session.add_event_start(ActivityKind::Install);
+        session.add_event_subcommand_start(ActivityKind::Install);
+        session.allow_installs();
+        if let Some(arch) = &self.arch {
+            set_arch_override(arch)?;
+        }
+        if self.take_over {
+            session.take_over_npm_globals();
+        }
+        if self.no_shims {
+            session.skip_package_shims();
+        }
+        if self.offline {
+            session.go_offline();
+        }
+        if self.override_policy {
+            session.override_policies();
+        }
+        if self.progress.is_some() {
+            enable_json_progress();
+        }
+
+        if self.manifest {
+            install_from_manifest(session)?;
+        } else {
+            let tools = Spec::from_strings(&self.tools, "install")?;
+            if let Some(dir) = self.offline_from {
+                for tool in &tools {
+                    check_exact_version(tool)?;
+                }
+                session.install_from_archives(dir);
+            }
+
+            for tool in tools {
+                tool.resolve(session)?.install(session)?;
+            }
+        }
 
-        for tool in Spec::from_strings(&self.tools, "install")? {
-            tool.resolve(session)?.install(session)?;
+        if self.porcelain {
+            print_porcelain(session.take_changes());
         }
 
         session.add_event_end(ActivityKind::Install, ExitCode::Success);
         Ok(ExitCode::Success)
     }
 }
+
+/// Installs the packages in the project's tools manifest one after the other
+///
+/// A package that fails to install is reported and the rest are still installed, so that one bad
+/// entry doesn't hold up the others. Packages that already have a matching version installed are
+/// skipped.
+fn install_from_manifest(session: &mut Session) -> Fallible<()> {
+    let file = session
+        .project()?
+        .and_then(Project::tools_manifest)
+        .ok_or(ErrorKind::NoToolsManifest)?;
+    let manifest = ToolsManifest::from_file(&file)?;
+    info!("Installing the tools from {}", file.display());
+    session.install_packages_with_project_platform();
+
+    let mut summary = Summary::new("installed", "already installed");
+    for (name, version) in manifest.packages {
+        let tool = tool_version(&name, &version);
+        match install_package(name, &version, session) {
+            Ok(true) => summary.record(tool, Status::Done),
+            Ok(false) => summary.record(tool, Status::Skipped),
+            Err(error) => {
+                report_error(env!("CARGO_PKG_VERSION"), &error);
+                session.add_event_error(ActivityKind::Install, &error);
+                summary.record(tool, Status::Failed);
+            }
+        }
+    }
+
+    summary.report();
+    if summary.has_failures() {
+        Err(ErrorKind::ToolsManifestInstallError {
+            failed: summary.tools(Status::Failed),
+        }
+        .into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Installs a package from the tools manifest, returning `false` if it was skipped because a
+/// matching version is already installed
+fn install_package(name: String, version: &str, session: &mut Session) -> Fallible<bool> {
+    let version: VersionSpec = version.parse()?;
+    let config_file = volta_home()?.default_package_config_file(&name);
+    let matches = |installed: &PackageConfig| match &version {
+        VersionSpec::Exact(version) => installed.version == *version,
+        VersionSpec::Semver(range) => range.satisfies(&installed.version),
+        VersionSpec::Tag(_) | VersionSpec::None => false,
+    };
+
+    if PackageConfig::from_file_if_exists(config_file)?.is_some_and(|config| matches(&config)) {
+        return Ok(false);
+    }
+
+    Spec::Package(name, version)
+        .resolve(session)?
+        .install(session)?;
+    Ok(true)
+}
+
+/// Checks that a tool has an exact version, as the name of its archive depends on the version and
+/// other versions can't be resolved without network access
+fn check_exact_version(tool: &Spec) -> Fallible<()> {
+    let version = match tool {
+        Spec::Node(version)
+        | Spec::Npm(version)
+        | Spec::Pnpm(version)
+        | Spec::Yarn(version)
+        | Spec::Package(_, version) => version,
+        Spec::PackageSource(_) => return Ok(()),
+    };
+
+    match version {
+        VersionSpec::Exact(_) => Ok(()),
+        _ => Err(ErrorKind::OfflineInstallNeedsVersion {
+            tool: tool.to_string(),
+        }
+        .into()),
+    }
+}
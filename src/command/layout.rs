@@ -0,0 +1,136 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use volta_core::error::{ExitCode, Fallible};
+use volta_core::layout::volta_home;
+use volta_core::session::{ActivityKind, Session};
+
+use crate::command::{to_json, Command};
+
+#[derive(clap::Args)]
+pub(crate) struct Layout {
+    #[command(subcommand)]
+    command: LayoutCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum LayoutCommand {
+    /// Prints every path that Volta manages in the current Volta home
+    Paths {
+        /// Output JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// The version of the JSON output, which changes whenever its shape does
+const JSON_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonPaths<'a> {
+    json_version: u32,
+    root: &'a Path,
+    paths: Vec<JsonPath>,
+}
+
+#[derive(Serialize)]
+struct JsonPath {
+    name: &'static str,
+    path: PathBuf,
+}
+
+impl Command for Layout {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_subcommand_start(ActivityKind::Layout);
+
+        match self.command {
+            LayoutCommand::Paths { json } => {
+                let home = volta_home()?;
+                let mut paths = home.all_paths();
+                paths.sort_by(|(_, a), (_, b)| a.cmp(b));
+
+                let output = if json {
+                    format_json(home.root(), paths)?
+                } else {
+                    format_human(home.root(), &paths)
+                };
+                println!("{}", output);
+            }
+        }
+
+        session.add_event_end(ActivityKind::Layout, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+fn format_human(root: &Path, paths: &[(&'static str, PathBuf)]) -> String {
+    let width = paths
+        .iter()
+        .map(|(name, _)| name.len())
+        .chain(Some("root".len()))
+        .max()
+        .unwrap_or_default();
+
+    let mut lines = vec![format!("{:width$}  {}", "root", root.display())];
+    lines.extend(
+        paths
+            .iter()
+            .map(|(name, path)| format!("{:width$}  {}", name, path.display())),
+    );
+    lines.join("\n")
+}
+
+fn format_json(root: &Path, paths: Vec<(&'static str, PathBuf)>) -> Fallible<String> {
+    let document = JsonPaths {
+        json_version: JSON_VERSION,
+        root,
+        paths: paths
+            .into_iter()
+            .map(|(name, path)| JsonPath { name, path })
+            .collect(),
+    };
+
+    to_json(&document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths() -> Vec<(&'static str, PathBuf)> {
+        vec![
+            ("shim_dir", PathBuf::from("/volta/bin")),
+            ("settings_file", PathBuf::from("/volta/settings.json")),
+        ]
+    }
+
+    #[test]
+    fn human_aligns_names() {
+        assert_eq!(
+            format_human(Path::new("/volta"), &paths()),
+            "root           /volta\n\
+             shim_dir       /volta/bin\n\
+             settings_file  /volta/settings.json"
+        );
+    }
+
+    #[test]
+    fn json_lists_names_and_paths() {
+        let json: serde_json::Value =
+            serde_json::from_str(&format_json(Path::new("/volta"), paths()).unwrap()).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "jsonVersion": 1,
+                "root": "/volta",
+                "paths": [
+                    { "name": "shim_dir", "path": "/volta/bin" },
+                    { "name": "settings_file", "path": "/volta/settings.json" },
+                ]
+            })
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Define the output of `volta list --aliases`, which shows the version aliases and the files
+//! that define them.
+
+use super::{json, Format};
+use volta_core::alias::Alias;
+use volta_core::error::Fallible;
+use volta_core::style::tool_version;
+
+static INDENTATION: &str = "    ";
+
+pub(super) fn format(
+    aliases: &[&Alias],
+    format: Format,
+    json_version: u32,
+) -> Fallible<Option<String>> {
+    match format {
+        Format::Human => Ok(Some(format_human(aliases))),
+        Format::Plain => Ok(format_plain(aliases)),
+        Format::Json => json::format_aliases(aliases, json_version).map(Some),
+    }
+}
+
+fn format_human(aliases: &[&Alias]) -> String {
+    if aliases.is_empty() {
+        return String::from("⚡️ No version aliases are defined.");
+    }
+
+    let names: Vec<String> = aliases
+        .iter()
+        .map(|alias| tool_version(&alias.tool, &alias.name))
+        .collect();
+    let name_width = names.iter().map(String::len).max().unwrap_or_default();
+    let target_width = aliases
+        .iter()
+        .map(|alias| alias.target.len())
+        .max()
+        .unwrap_or_default();
+
+    let lines: Vec<String> = aliases
+        .iter()
+        .zip(names)
+        .map(|(alias, name)| {
+            format!(
+                "{}{:<name_width$}  {:<target_width$}  (from {})",
+                INDENTATION,
+                name,
+                alias.target,
+                alias.source.display(),
+                name_width = name_width,
+                target_width = target_width
+            )
+        })
+        .collect();
+
+    format!("⚡️ Version aliases:\n\n{}", lines.join("\n"))
+}
+
+fn format_plain(aliases: &[&Alias]) -> Option<String> {
+    if aliases.is_empty() {
+        return None;
+    }
+
+    let lines: Vec<String> = aliases
+        .iter()
+        .map(|alias| {
+            format!(
+                "{} target={} source={}",
+                tool_version(&alias.tool, &alias.name),
+                alias.target,
+                alias.source.display()
+            )
+        })
+        .collect();
+
+    Some(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn sample_aliases() -> Vec<Alias> {
+        vec![
+            Alias {
+                tool: "node".into(),
+                name: "company-standard".into(),
+                target: "20.11.1".into(),
+                source: PathBuf::from("/home/user/.volta/settings.json"),
+            },
+            Alias {
+                tool: "node".into(),
+                name: "legacy".into(),
+                target: "^16".into(),
+                source: PathBuf::from("/home/user/app/.volta/hooks.json"),
+            },
+        ]
+    }
+
+    #[test]
+    fn human_shows_aliases_with_their_files() {
+        let aliases = sample_aliases();
+        let expected = "⚡️ Version aliases:
+
+    node@company-standard  20.11.1  (from /home/user/.volta/settings.json)
+    node@legacy            ^16      (from /home/user/app/.volta/hooks.json)";
+
+        assert_eq!(format_human(&aliases.iter().collect::<Vec<_>>()), expected);
+        assert_eq!(format_human(&[]), "⚡️ No version aliases are defined.");
+    }
+
+    #[test]
+    fn plain_shows_aliases_with_their_files() {
+        let aliases = sample_aliases();
+        let expected = "node@company-standard target=20.11.1 source=/home/user/.volta/settings.json
+node@legacy target=^16 source=/home/user/app/.volta/hooks.json";
+
+        assert_eq!(
+            format_plain(&aliases.iter().collect::<Vec<_>>()).unwrap(),
+            expected
+        );
+        assert_eq!(format_plain(&[]), None);
+    }
+}
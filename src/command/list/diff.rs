@@ -0,0 +1,494 @@
+//! Define `volta list --diff`, which compares the toolchain with a snapshot saved by
+//! `volta list --snapshot-to`.
+//!
+//! A snapshot is the JSON output of `volta list all`, so the comparison works on the JSON data
+//! model: each item is identified by its kind and name (and version, for runtimes and package
+//! managers), and the rest of its fields are compared, except for those that differ between
+//! machines or over time.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{json, Format};
+use volta_core::error::{Context, ErrorKind, Fallible};
+use volta_core::style::tool_version;
+
+static INDENTATION: &str = "    ";
+
+/// Fields that depend on the machine or the date rather than on the toolchain
+///
+/// `eol` changes as release lines reach end-of-life, and `path` and `project` are absolute paths.
+const VOLATILE_FIELDS: [&str; 3] = ["eol", "path", "project"];
+
+/// The kind of an item in the toolchain
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub(super) enum Kind {
+    Runtime,
+    PackageManager,
+    Package,
+}
+
+impl Kind {
+    const ALL: [Kind; 3] = [Kind::Runtime, Kind::PackageManager, Kind::Package];
+
+    /// The name of the kind in the JSON output of `volta list --diff`
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Kind::Runtime => "runtime",
+            Kind::PackageManager => "packageManager",
+            Kind::Package => "package",
+        }
+    }
+
+    /// The list of items of this kind in the toolchain document
+    fn list(self) -> &'static str {
+        match self {
+            Kind::Runtime => "runtimes",
+            Kind::PackageManager => "packageManagers",
+            Kind::Package => "packages",
+        }
+    }
+}
+
+/// Identifies an item across snapshots
+///
+/// Several versions of a runtime or package manager can be fetched at once, so their version is
+/// part of what identifies them. A package is only installed once, so its version is one of the
+/// fields that can change.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub(super) struct Key {
+    pub kind: Kind,
+    pub name: String,
+    pub version: Option<String>,
+}
+
+/// An item of a snapshot, with the fields that are compared
+#[derive(Clone, PartialEq, Debug)]
+pub(super) struct Item {
+    pub key: Key,
+    pub fields: BTreeMap<String, Value>,
+}
+
+impl Item {
+    /// The version of the item, whether or not it's part of its key
+    pub(super) fn version(&self) -> Option<&str> {
+        self.key
+            .version
+            .as_deref()
+            .or_else(|| self.fields.get("version").and_then(Value::as_str))
+    }
+
+    /// Where the item comes from: `project`, `default`, or `fetched`
+    pub(super) fn source(&self) -> Option<&str> {
+        self.fields.get("source").and_then(Value::as_str)
+    }
+
+    fn label(&self) -> String {
+        match self.version() {
+            Some(version) => tool_version(&self.key.name, version),
+            None => self.key.name.clone(),
+        }
+    }
+}
+
+/// A field of an item that has a different value than in the snapshot
+#[derive(Serialize, PartialEq, Debug)]
+pub(super) struct FieldChange {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+/// A difference between the toolchain and a snapshot
+#[derive(PartialEq, Debug)]
+pub(super) enum Change {
+    Added(Item),
+    Removed(Item),
+    Changed { key: Key, fields: Vec<FieldChange> },
+}
+
+/// The items of the toolchain, from the JSON output of `volta list all`
+pub(super) struct Snapshot {
+    json_version: u32,
+    items: BTreeMap<Key, Item>,
+}
+
+impl Snapshot {
+    /// Reads a snapshot saved by `volta list --snapshot-to`
+    pub(super) fn read(file: &Path) -> Fallible<Snapshot> {
+        let parse_error = || ErrorKind::ParseListSnapshotError {
+            file: file.to_owned(),
+        };
+
+        let contents =
+            fs::read_to_string(file).with_context(|| ErrorKind::ReadListSnapshotError {
+                file: file.to_owned(),
+            })?;
+        let document: Value = serde_json::from_str(&contents).with_context(parse_error)?;
+
+        Snapshot::from_document(&document).ok_or_else(|| parse_error().into())
+    }
+
+    /// Collects the items of a toolchain document, or returns `None` if the document isn't one
+    pub(super) fn from_document(document: &Value) -> Option<Snapshot> {
+        let json_version = document
+            .get("jsonVersion")?
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .filter(|version| (1..=json::JSON_VERSION).contains(version))?;
+
+        let mut items = BTreeMap::new();
+        for kind in Kind::ALL {
+            for item in document.get(kind.list())?.as_array()? {
+                let mut fields = item.as_object()?.clone();
+                let name = fields.remove("name")?.as_str()?.to_owned();
+                let version = match kind {
+                    Kind::Package => None,
+                    _ => Some(fields.remove("version")?.as_str()?.to_owned()),
+                };
+                for field in VOLATILE_FIELDS {
+                    fields.remove(field);
+                }
+
+                let key = Key {
+                    kind,
+                    name,
+                    version,
+                };
+                let item = Item {
+                    key: key.clone(),
+                    fields: fields.into_iter().collect(),
+                };
+                items.insert(key, item);
+            }
+        }
+
+        Some(Snapshot {
+            json_version,
+            items,
+        })
+    }
+
+    /// The version of the JSON output the snapshot was saved in
+    pub(super) fn json_version(&self) -> u32 {
+        self.json_version
+    }
+}
+
+/// Lists the changes from one snapshot to another, sorted by the kind and name of the items
+///
+/// The order of the items within each snapshot doesn't matter.
+pub(super) fn compare(before: &Snapshot, after: &Snapshot) -> Vec<Change> {
+    let keys: BTreeSet<&Key> = before.items.keys().chain(after.items.keys()).collect();
+
+    keys.into_iter()
+        .filter_map(|key| match (before.items.get(key), after.items.get(key)) {
+            (Some(old), Some(new)) => {
+                let names: BTreeSet<&String> = old.fields.keys().chain(new.fields.keys()).collect();
+                let fields: Vec<FieldChange> = names
+                    .into_iter()
+                    .filter_map(|name| {
+                        let before = old.fields.get(name).cloned().unwrap_or(Value::Null);
+                        let after = new.fields.get(name).cloned().unwrap_or(Value::Null);
+                        (before != after).then(|| FieldChange {
+                            field: name.clone(),
+                            before,
+                            after,
+                        })
+                    })
+                    .collect();
+
+                (!fields.is_empty()).then(|| Change::Changed {
+                    key: key.clone(),
+                    fields,
+                })
+            }
+            (None, Some(new)) => Some(Change::Added(new.clone())),
+            (Some(old), None) => Some(Change::Removed(old.clone())),
+            (None, None) => None,
+        })
+        .collect()
+}
+
+pub(super) fn format(
+    changes: &[Change],
+    snapshot: &Path,
+    format: Format,
+    json_version: u32,
+) -> Fallible<Option<String>> {
+    match format {
+        Format::Human => Ok(Some(format_human(changes, snapshot))),
+        Format::Plain => Ok(format_plain(changes)),
+        Format::Json => json::format_diff(changes, json_version).map(Some),
+    }
+}
+
+/// Shows a field value without JSON quoting, for the human and plain formats
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        Value::Null => String::from("none"),
+        other => other.to_string(),
+    }
+}
+
+fn key_label(key: &Key) -> String {
+    match &key.version {
+        Some(version) => tool_version(&key.name, version),
+        None => key.name.clone(),
+    }
+}
+
+fn format_human(changes: &[Change], snapshot: &Path) -> String {
+    if changes.is_empty() {
+        return format!(
+            "⚡️ No changes since the snapshot in {}.",
+            snapshot.display()
+        );
+    }
+
+    let lines: Vec<String> = changes
+        .iter()
+        .map(|change| match change {
+            Change::Added(item) | Change::Removed(item) => {
+                let action = if matches!(change, Change::Added(_)) {
+                    "added"
+                } else {
+                    "removed"
+                };
+                let source = item
+                    .source()
+                    .map(|source| format!(" ({})", source))
+                    .unwrap_or_default();
+                format!("{}{:<7}  {}{}", INDENTATION, action, item.label(), source)
+            }
+            Change::Changed { key, fields } => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|change| {
+                        format!(
+                            "{}: {} -> {}",
+                            change.field,
+                            display_value(&change.before),
+                            display_value(&change.after)
+                        )
+                    })
+                    .collect();
+                format!(
+                    "{}changed  {}  {}",
+                    INDENTATION,
+                    key_label(key),
+                    fields.join(", ")
+                )
+            }
+        })
+        .collect();
+
+    format!(
+        "⚡️ Changes since the snapshot in {}:\n\n{}",
+        snapshot.display(),
+        lines.join("\n")
+    )
+}
+
+fn format_plain(changes: &[Change]) -> Option<String> {
+    if changes.is_empty() {
+        return None;
+    }
+
+    let lines: Vec<String> = changes
+        .iter()
+        .map(|change| match change {
+            Change::Added(item) | Change::Removed(item) => {
+                let action = if matches!(change, Change::Added(_)) {
+                    "added"
+                } else {
+                    "removed"
+                };
+                format!(
+                    "{} {} source={}",
+                    action,
+                    item.label(),
+                    item.source().unwrap_or("none")
+                )
+            }
+            Change::Changed { key, fields } => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|change| {
+                        format!(
+                            "{}={}->{}",
+                            change.field,
+                            display_value(&change.before),
+                            display_value(&change.after)
+                        )
+                    })
+                    .collect();
+                format!("changed {} {}", key_label(key), fields.join(" "))
+            }
+        })
+        .collect();
+
+    Some(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn snapshot(document: Value) -> Snapshot {
+        Snapshot::from_document(&document).unwrap()
+    }
+
+    fn before() -> Snapshot {
+        snapshot(json!({
+            "jsonVersion": 6,
+            "runtimes": [
+                { "name": "node", "version": "18.12.1", "source": "default", "project": null, "eol": false, "path": "/home/user/.volta/tools/image/node/18.12.1" },
+                { "name": "node", "version": "16.20.0", "source": "fetched", "project": null, "eol": false, "path": "/home/user/.volta/tools/image/node/16.20.0" }
+            ],
+            "packageManagers": [
+                { "name": "yarn", "version": "1.22.19", "source": "default", "project": null }
+            ],
+            "packages": [
+                { "name": "typescript", "version": "5.0.4", "tag": "latest", "source": "default", "project": null, "node": "18.12.1", "binaries": ["tsc", "tsserver"], "path": null }
+            ]
+        }))
+    }
+
+    #[test]
+    fn identical_snapshots_have_no_changes() {
+        assert_eq!(compare(&before(), &before()), vec![]);
+    }
+
+    #[test]
+    fn order_and_volatile_fields_are_ignored() {
+        // The same toolchain, listed in a different order on a different machine a year later
+        let after = snapshot(json!({
+            "jsonVersion": 6,
+            "runtimes": [
+                { "name": "node", "version": "16.20.0", "source": "fetched", "project": null, "eol": true, "path": "/Users/ci/.volta/tools/image/node/16.20.0" },
+                { "name": "node", "version": "18.12.1", "source": "default", "project": null, "eol": false, "path": "/Users/ci/.volta/tools/image/node/18.12.1" }
+            ],
+            "packageManagers": [
+                { "name": "yarn", "version": "1.22.19", "source": "default", "project": null }
+            ],
+            "packages": [
+                { "name": "typescript", "version": "5.0.4", "tag": "latest", "source": "default", "project": null, "node": "18.12.1", "binaries": ["tsc", "tsserver"], "path": "/Users/ci/.volta/tools/image/packages/typescript" }
+            ]
+        }));
+
+        assert_eq!(compare(&before(), &after), vec![]);
+    }
+
+    #[test]
+    fn lists_each_kind_of_change() {
+        let after = snapshot(json!({
+            "jsonVersion": 6,
+            "runtimes": [
+                { "name": "node", "version": "20.11.1", "source": "default", "project": null, "eol": false, "path": null },
+                { "name": "node", "version": "18.12.1", "source": "fetched", "project": null, "eol": false, "path": null }
+            ],
+            "packageManagers": [],
+            "packages": [
+                { "name": "cowsay", "version": "1.5.0", "tag": null, "source": "default", "project": null, "node": "20.11.1", "binaries": ["cowsay"], "path": null },
+                { "name": "typescript", "version": "5.3.3", "tag": "latest", "source": "default", "project": null, "node": "20.11.1", "binaries": ["tsc", "tsserver"], "path": null }
+            ]
+        }));
+
+        let changes = compare(&before(), &after);
+        let summary: Vec<String> = changes
+            .iter()
+            .map(|change| match change {
+                Change::Added(item) => format!("added {}", item.label()),
+                Change::Removed(item) => format!("removed {}", item.label()),
+                Change::Changed { key, fields } => format!(
+                    "changed {} {}",
+                    key_label(key),
+                    fields
+                        .iter()
+                        .map(|field| field.field.as_str())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                ),
+            })
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                "removed node@16.20.0",
+                "changed node@18.12.1 source",
+                "added node@20.11.1",
+                "removed yarn@1.22.19",
+                "added cowsay@1.5.0",
+                "changed typescript node,version",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_other_documents() {
+        assert!(Snapshot::from_document(&json!({ "jsonVersion": 6, "usage": [] })).is_none());
+        assert!(Snapshot::from_document(&json!({
+            "jsonVersion": 99,
+            "runtimes": [],
+            "packageManagers": [],
+            "packages": []
+        }))
+        .is_none());
+        assert!(Snapshot::from_document(&json!([])).is_none());
+    }
+
+    fn sample_changes() -> Vec<Change> {
+        let after = snapshot(json!({
+            "jsonVersion": 6,
+            "runtimes": [
+                { "name": "node", "version": "20.11.1", "source": "default", "project": null, "eol": false, "path": null },
+                { "name": "node", "version": "18.12.1", "source": "fetched", "project": null, "eol": false, "path": null },
+                { "name": "node", "version": "16.20.0", "source": "fetched", "project": null, "eol": false, "path": null }
+            ],
+            "packageManagers": [
+                { "name": "yarn", "version": "1.22.19", "source": "default", "project": null }
+            ],
+            "packages": [
+                { "name": "typescript", "version": "5.3.3", "tag": "latest", "source": "default", "project": null, "node": "18.12.1", "binaries": ["tsc", "tsserver"], "path": null }
+            ]
+        }));
+
+        compare(&before(), &after)
+    }
+
+    #[test]
+    fn human_shows_changes() {
+        let expected = "⚡️ Changes since the snapshot in /home/user/before.json:
+
+    changed  node@18.12.1  source: default -> fetched
+    added    node@20.11.1 (default)
+    changed  typescript  version: 5.0.4 -> 5.3.3";
+
+        assert_eq!(
+            format_human(&sample_changes(), Path::new("/home/user/before.json")),
+            expected
+        );
+        assert_eq!(
+            format_human(&[], Path::new("/home/user/before.json")),
+            "⚡️ No changes since the snapshot in /home/user/before.json."
+        );
+    }
+
+    #[test]
+    fn plain_shows_changes() {
+        let expected = "changed node@18.12.1 source=default->fetched
+added node@20.11.1 source=default
+changed typescript version=5.0.4->5.3.3";
+
+        assert_eq!(format_plain(&sample_changes()).unwrap(), expected);
+        assert_eq!(format_plain(&[]), None);
+    }
+}
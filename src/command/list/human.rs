@@ -2,8 +2,9 @@
 
 use std::collections::BTreeMap;
 
-use super::{Node, Package, PackageManager, PackageManagerKind, Toolchain};
-use once_cell::sync::Lazy;
+use super::{
+    ManagerVersion, Node, Package, PackageDetails, PackageManager, PackageManagerKind, Toolchain,
+};
 use textwrap::{fill, Options};
 use volta_core::style::{text_width, tool_version, MAX_WIDTH};
 
@@ -13,8 +14,6 @@ static NO_RUNTIME: &str = "⚡️ No Node runtimes installed!
     You can install a runtime by running `volta install node`. See `volta help install` for
     details and more options.";
 
-static TEXT_WIDTH: Lazy<usize> = Lazy::new(|| text_width().unwrap_or(MAX_WIDTH));
-
 #[allow(clippy::unnecessary_wraps)] // Needs to match the API of `plain::format`
 pub(super) fn format(toolchain: &Toolchain) -> Option<String> {
     // Formatting here depends on the toolchain: we do different degrees of
@@ -228,7 +227,12 @@ fn format_tool(package: &Package) -> String {
                 0 => String::from(""),
                 _ => tools.join(", "),
             };
-            wrap(format!("{}{}", tools, list_package_source(package)))
+            wrap(format!(
+                "{}{}{}",
+                tools,
+                list_package_source(package),
+                list_package_shims(package)
+            ))
         }
         Package::Fetched(..) => String::new(),
     }
@@ -247,7 +251,14 @@ fn format_runtime_list(runtimes: &[Node]) -> String {
 
 /// format a single version of `Toolchain::Node`.
 fn format_runtime(runtime: &Node) -> String {
-    format!("v{}{}", runtime.version, runtime.source)
+    let eol = if runtime.eol { " (end-of-life)" } else { "" };
+    format!(
+        "v{}{}{}{}",
+        runtime.version,
+        runtime.source,
+        eol,
+        runtime.bundled_npm_note()
+    )
 }
 
 /// format a list of `Toolchain::PackageManager`s in condensed form
@@ -295,7 +306,10 @@ fn format_package_manager_list_verbose(package_managers: &[PackageManager]) -> S
 
 /// format a single `Toolchain::PackageManager`.
 fn format_package_manager(package_manager: &PackageManager) -> String {
-    format!("v{}{}", package_manager.version, package_manager.source)
+    match &package_manager.version {
+        ManagerVersion::Exact(version) => format!("v{}{}", version, package_manager.source),
+        ManagerVersion::Bundled => format!("bundled{}", package_manager.source),
+    }
 }
 
 /// format the title for a kind of package manager
@@ -334,8 +348,18 @@ fn format_package(package: &Package) -> String {
                 _ => tools.join(", "),
             };
 
-            let version = format!("{}{}", details.version, list_package_source(package));
-            let binaries = wrap(format!("binary tools: {}", tools));
+            let version = format!(
+                "{}{}{}{}",
+                details.version,
+                format_package_tag(details),
+                format_package_installed_from(details),
+                list_package_source(package)
+            );
+            let binaries = wrap(format!(
+                "binary tools: {}{}",
+                tools,
+                list_package_shims(package)
+            ));
             let platform_detail = wrap(format!(
                 "runtime: {}\npackage manager: {}",
                 tool_version("node", node),
@@ -361,11 +385,32 @@ fn format_package(package: &Package) -> String {
                 "To make it available to execute, run `volta install {}`.",
                 package_info
             );
-            format!("{}\n\n{}", package_info, footer_message)
+            format!(
+                "{}{}\n\n{}",
+                package_info,
+                format_package_tag(details),
+                footer_message
+            )
         }
     }
 }
 
+/// Format the registry dist-tag a package was installed from, if there is one.
+fn format_package_tag(details: &PackageDetails) -> String {
+    match &details.tag {
+        Some(tag) => format!(" (from tag: {})", tag),
+        None => String::new(),
+    }
+}
+
+/// Format the local path or git URL a package was installed from, if there is one.
+fn format_package_installed_from(details: &PackageDetails) -> String {
+    match &details.installed_from {
+        Some(location) => format!(" (from {})", location),
+        None => String::new(),
+    }
+}
+
 /// List a the source from a `Toolchain::Package`.
 fn list_package_source(package: &Package) -> String {
     match package {
@@ -375,12 +420,20 @@ fn list_package_source(package: &Package) -> String {
     }
 }
 
+/// Note that a `Toolchain::Package` was installed without shims for its tools.
+fn list_package_shims(package: &Package) -> &'static str {
+    match package {
+        Package::Default { shims: false, .. } => " (no shims)",
+        _ => "",
+    }
+}
+
 /// Wrap and indent the output
 fn wrap<S>(text: S) -> String
 where
     S: AsRef<str>,
 {
-    let options = Options::new(*TEXT_WIDTH)
+    let options = Options::new(text_width().unwrap_or(MAX_WIDTH))
         .initial_indent(INDENTATION)
         .subsequent_indent(INDENTATION);
 
@@ -439,6 +492,9 @@ See options for more detailed reports by running `volta list --help`.";
             let runtime = Some(Box::new(Node {
                 source: Source::Default,
                 version: NODE_12.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
             }));
             let package_managers = vec![];
             let packages = vec![];
@@ -461,6 +517,9 @@ See options for more detailed reports by running `volta list --help`.";
             let runtime = Some(Box::new(Node {
                 source: Source::Project(PROJECT_PATH.clone()),
                 version: NODE_12.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
             }));
             let package_managers = vec![];
             let packages = vec![];
@@ -471,6 +530,36 @@ See options for more detailed reports by running `volta list --help`.";
             );
         }
 
+        #[test]
+        fn runtime_and_bundled_npm_project() {
+            let expected = "⚡️ Currently active tools:
+
+    Node: v12.2.0 (current @ ~/path/to/project.json)
+    npm: bundled (current @ ~/path/to/project.json)
+    Tool binaries available: NONE
+
+See options for more detailed reports by running `volta list --help`.";
+
+            let runtime = Some(Box::new(Node {
+                source: Source::Project(PROJECT_PATH.clone()),
+                version: NODE_12.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
+            }));
+            let package_managers = vec![PackageManager {
+                kind: PackageManagerKind::Npm,
+                source: Source::Project(PROJECT_PATH.clone()),
+                version: ManagerVersion::Bundled,
+            }];
+            let packages = vec![];
+
+            assert_eq!(
+                display_active(&runtime, &package_managers, &packages),
+                expected
+            );
+        }
+
         #[test]
         fn runtime_and_npm_default() {
             let expected = "⚡️ Currently active tools:
@@ -484,11 +573,14 @@ See options for more detailed reports by running `volta list --help`.";
             let runtime = Some(Box::new(Node {
                 source: Source::Default,
                 version: NODE_12.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
             }));
             let package_managers = vec![PackageManager {
                 kind: PackageManagerKind::Npm,
                 source: Source::Default,
-                version: NPM_VERSION.clone(),
+                version: ManagerVersion::Exact(NPM_VERSION.clone()),
             }];
             let packages = vec![];
 
@@ -511,11 +603,14 @@ See options for more detailed reports by running `volta list --help`.";
             let runtime = Some(Box::new(Node {
                 source: Source::Default,
                 version: NODE_12.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
             }));
             let package_managers = vec![PackageManager {
                 kind: PackageManagerKind::Yarn,
                 source: Source::Default,
-                version: YARN_VERSION.clone(),
+                version: ManagerVersion::Exact(YARN_VERSION.clone()),
             }];
             let packages = vec![];
 
@@ -538,11 +633,14 @@ See options for more detailed reports by running `volta list --help`.";
             let runtime = Some(Box::new(Node {
                 source: Source::Default,
                 version: NODE_12.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
             }));
             let package_managers = vec![PackageManager {
                 kind: PackageManagerKind::Npm,
                 source: Source::Project(PROJECT_PATH.clone()),
-                version: NPM_VERSION.clone(),
+                version: ManagerVersion::Exact(NPM_VERSION.clone()),
             }];
             let packages = vec![];
 
@@ -565,11 +663,14 @@ See options for more detailed reports by running `volta list --help`.";
             let runtime = Some(Box::new(Node {
                 source: Source::Default,
                 version: NODE_12.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
             }));
             let package_managers = vec![PackageManager {
                 kind: PackageManagerKind::Yarn,
                 source: Source::Project(PROJECT_PATH.clone()),
-                version: YARN_VERSION.clone(),
+                version: ManagerVersion::Exact(YARN_VERSION.clone()),
             }];
             let packages = vec![];
 
@@ -592,11 +693,14 @@ See options for more detailed reports by running `volta list --help`.";
             let runtime = Some(Box::new(Node {
                 source: Source::Project(PROJECT_PATH.clone()),
                 version: NODE_12.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
             }));
             let package_managers = vec![PackageManager {
                 kind: PackageManagerKind::Npm,
                 source: Source::Project(PROJECT_PATH.clone()),
-                version: NPM_VERSION.clone(),
+                version: ManagerVersion::Exact(NPM_VERSION.clone()),
             }];
             let packages = vec![];
 
@@ -619,11 +723,14 @@ See options for more detailed reports by running `volta list --help`.";
             let runtime = Some(Box::new(Node {
                 source: Source::Project(PROJECT_PATH.clone()),
                 version: NODE_12.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
             }));
             let package_managers = vec![PackageManager {
                 kind: PackageManagerKind::Yarn,
                 source: Source::Project(PROJECT_PATH.clone()),
-                version: YARN_VERSION.clone(),
+                version: ManagerVersion::Exact(YARN_VERSION.clone()),
             }];
             let packages = vec![];
 
@@ -647,17 +754,20 @@ See options for more detailed reports by running `volta list --help`.";
             let runtime = Some(Box::new(Node {
                 source: Source::Default,
                 version: NODE_12.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
             }));
             let package_managers = vec![
                 PackageManager {
                     kind: PackageManagerKind::Npm,
                     source: Source::Default,
-                    version: NPM_VERSION.clone(),
+                    version: ManagerVersion::Exact(NPM_VERSION.clone()),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Yarn,
                     source: Source::Default,
-                    version: YARN_VERSION.clone(),
+                    version: ManagerVersion::Exact(YARN_VERSION.clone()),
                 },
             ];
             let packages = vec![];
@@ -682,17 +792,20 @@ See options for more detailed reports by running `volta list --help`.";
             let runtime = Some(Box::new(Node {
                 source: Source::Project(PROJECT_PATH.clone()),
                 version: NODE_12.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
             }));
             let package_managers = vec![
                 PackageManager {
                     kind: PackageManagerKind::Npm,
                     source: Source::Project(PROJECT_PATH.clone()),
-                    version: NPM_VERSION.clone(),
+                    version: ManagerVersion::Exact(NPM_VERSION.clone()),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Yarn,
                     source: Source::Project(PROJECT_PATH.clone()),
-                    version: YARN_VERSION.clone(),
+                    version: ManagerVersion::Exact(YARN_VERSION.clone()),
                 },
             ];
             let packages = vec![];
@@ -717,17 +830,20 @@ See options for more detailed reports by running `volta list --help`.";
             let runtime = Some(Box::new(Node {
                 source: Source::Default,
                 version: NODE_12.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
             }));
             let package_managers = vec![
                 PackageManager {
                     kind: PackageManagerKind::Npm,
                     source: Source::Project(PROJECT_PATH.clone()),
-                    version: NPM_VERSION.clone(),
+                    version: ManagerVersion::Exact(NPM_VERSION.clone()),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Yarn,
                     source: Source::Default,
-                    version: YARN_VERSION.clone(),
+                    version: ManagerVersion::Exact(YARN_VERSION.clone()),
                 },
             ];
             let packages = vec![];
@@ -754,17 +870,20 @@ See options for more detailed reports by running `volta list --help`.";
             let runtime = Some(Box::new(Node {
                 source: Source::Project(PROJECT_PATH.clone()),
                 version: NODE_12.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
             }));
             let package_managers = vec![
                 PackageManager {
                     kind: PackageManagerKind::Npm,
                     source: Source::Project(PROJECT_PATH.clone()),
-                    version: NPM_VERSION.clone(),
+                    version: ManagerVersion::Exact(NPM_VERSION.clone()),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Yarn,
                     source: Source::Project(PROJECT_PATH.clone()),
-                    version: YARN_VERSION.clone(),
+                    version: ManagerVersion::Exact(YARN_VERSION.clone()),
                 },
             ];
             let packages = vec![
@@ -772,17 +891,25 @@ See options for more detailed reports by running `volta list --help`.";
                     details: PackageDetails {
                         name: "create-react-app".to_string(),
                         version: Version::from((3, 0, 1)),
+                        tag: None,
+                        installed_from: None,
+                        image: None,
                     },
                     node: NODE_12.clone(),
                     tools: vec!["create-react-app".to_string()],
+                    shims: true,
                 },
                 Package::Default {
                     details: PackageDetails {
                         name: "typescript".to_string(),
                         version: Version::from((3, 4, 3)),
+                        tag: None,
+                        installed_from: None,
+                        image: None,
                     },
                     node: NODE_12.clone(),
                     tools: vec!["tsc".to_string(), "tsserver".to_string()],
+                    shims: true,
                 },
             ];
 
@@ -808,32 +935,40 @@ See options for more detailed reports by running `volta list --help`.";
             let runtime = Some(Box::new(Node {
                 source: Source::Project(PROJECT_PATH.clone()),
                 version: NODE_12.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
             }));
             let package_managers = vec![
                 PackageManager {
                     kind: PackageManagerKind::Npm,
                     source: Source::Project(PROJECT_PATH.clone()),
-                    version: NPM_VERSION.clone(),
+                    version: ManagerVersion::Exact(NPM_VERSION.clone()),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Yarn,
                     source: Source::Project(PROJECT_PATH.clone()),
-                    version: YARN_VERSION.clone(),
+                    version: ManagerVersion::Exact(YARN_VERSION.clone()),
                 },
             ];
             let packages = vec![
                 Package::Project {
                     name: "create-react-app".to_string(),
                     path: PROJECT_PATH.clone(),
+                    image: None,
                     tools: vec!["create-react-app".to_string()],
                 },
                 Package::Default {
                     details: PackageDetails {
                         name: "typescript".to_string(),
                         version: Version::from((3, 4, 3)),
+                        tag: None,
+                        installed_from: None,
+                        image: None,
                     },
                     node: NODE_12.clone(),
                     tools: vec!["tsc".to_string(), "tsserver".to_string()],
+                    shims: true,
                 },
             ];
 
@@ -865,6 +1000,25 @@ See options for more detailed reports by running `volta list --help`.";
             let runtimes = [Node {
                 source: Source::Default,
                 version: NODE_10.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
+            }];
+
+            assert_eq!(display_node(&runtimes).as_str(), expected);
+        }
+
+        #[test]
+        fn single_with_bundled_npm() {
+            let expected = "⚡️ Node runtimes in your toolchain:
+
+    v10.15.3 (default) (bundles npm@6.4.1)";
+            let runtimes = [Node {
+                source: Source::Default,
+                version: NODE_10.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: Some(Version::from((6, 4, 1))),
             }];
 
             assert_eq!(display_node(&runtimes).as_str(), expected);
@@ -879,6 +1033,9 @@ See options for more detailed reports by running `volta list --help`.";
             let runtimes = [Node {
                 source: Source::Project(PROJECT_PATH.clone()),
                 version: NODE_12.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
             }];
 
             assert_eq!(display_node(&runtimes).as_str(), expected);
@@ -893,6 +1050,9 @@ See options for more detailed reports by running `volta list --help`.";
             let runtimes = [Node {
                 source: Source::None,
                 version: NODE_11.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
             }];
 
             assert_eq!(display_node(&runtimes).as_str(), expected);
@@ -910,14 +1070,50 @@ See options for more detailed reports by running `volta list --help`.";
                 Node {
                     source: Source::Project(PROJECT_PATH.clone()),
                     version: NODE_12.clone(),
+                    eol: false,
+                    image: None,
+                    bundled_npm: None,
                 },
                 Node {
                     source: Source::None,
                     version: NODE_11.clone(),
+                    eol: false,
+                    image: None,
+                    bundled_npm: None,
                 },
                 Node {
                     source: Source::Default,
                     version: NODE_10.clone(),
+                    eol: false,
+                    image: None,
+                    bundled_npm: None,
+                },
+            ];
+
+            assert_eq!(display_node(&runtimes), expected);
+        }
+
+        #[test]
+        fn end_of_life() {
+            let expected = "⚡️ Node runtimes in your toolchain:
+
+    v12.2.0 (current @ ~/path/to/project.json) (end-of-life)
+    v10.15.3 (end-of-life)";
+
+            let runtimes = [
+                Node {
+                    source: Source::Project(PROJECT_PATH.clone()),
+                    version: NODE_12.clone(),
+                    eol: true,
+                    image: None,
+                    bundled_npm: None,
+                },
+                Node {
+                    source: Source::None,
+                    version: NODE_10.clone(),
+                    eol: true,
+                    image: None,
+                    bundled_npm: None,
                 },
             ];
 
@@ -965,7 +1161,7 @@ See `volta help install` for details and more options.";
             let package_managers = [PackageManager {
                 kind: PackageManagerKind::Npm,
                 source: Source::Default,
-                version: NPM_VERSION.clone(),
+                version: ManagerVersion::Exact(NPM_VERSION.clone()),
             }];
 
             assert_eq!(
@@ -983,7 +1179,7 @@ See `volta help install` for details and more options.";
             let package_managers = [PackageManager {
                 kind: PackageManagerKind::Yarn,
                 source: Source::Default,
-                version: YARN_VERSION.clone(),
+                version: ManagerVersion::Exact(YARN_VERSION.clone()),
             }];
 
             assert_eq!(
@@ -1001,7 +1197,7 @@ See `volta help install` for details and more options.";
             let package_managers = [PackageManager {
                 kind: PackageManagerKind::Npm,
                 source: Source::Project(PROJECT_PATH.clone()),
-                version: NPM_VERSION.clone(),
+                version: ManagerVersion::Exact(NPM_VERSION.clone()),
             }];
 
             assert_eq!(
@@ -1019,7 +1215,7 @@ See `volta help install` for details and more options.";
             let package_managers = [PackageManager {
                 kind: PackageManagerKind::Yarn,
                 source: Source::Project(PROJECT_PATH.clone()),
-                version: YARN_VERSION.clone(),
+                version: ManagerVersion::Exact(YARN_VERSION.clone()),
             }];
 
             assert_eq!(
@@ -1037,7 +1233,7 @@ See `volta help install` for details and more options.";
             let package_managers = [PackageManager {
                 kind: PackageManagerKind::Npm,
                 source: Source::None,
-                version: NPM_VERSION.clone(),
+                version: ManagerVersion::Exact(NPM_VERSION.clone()),
             }];
 
             assert_eq!(
@@ -1055,7 +1251,7 @@ See `volta help install` for details and more options.";
             let package_managers = [PackageManager {
                 kind: PackageManagerKind::Yarn,
                 source: Source::None,
-                version: YARN_VERSION.clone(),
+                version: ManagerVersion::Exact(YARN_VERSION.clone()),
             }];
 
             assert_eq!(
@@ -1076,17 +1272,17 @@ See `volta help install` for details and more options.";
                 PackageManager {
                     kind: PackageManagerKind::Npm,
                     source: Source::None,
-                    version: Version::from((5, 6, 0)),
+                    version: ManagerVersion::Exact(Version::from((5, 6, 0))),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Npm,
                     source: Source::Default,
-                    version: NPM_VERSION.clone(),
+                    version: ManagerVersion::Exact(NPM_VERSION.clone()),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Npm,
                     source: Source::Project(PROJECT_PATH.clone()),
-                    version: Version::from((6, 14, 2)),
+                    version: ManagerVersion::Exact(Version::from((6, 14, 2))),
                 },
             ];
 
@@ -1108,17 +1304,17 @@ See `volta help install` for details and more options.";
                 PackageManager {
                     kind: PackageManagerKind::Yarn,
                     source: Source::None,
-                    version: Version::from((1, 3, 0)),
+                    version: ManagerVersion::Exact(Version::from((1, 3, 0))),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Yarn,
                     source: Source::Default,
-                    version: YARN_VERSION.clone(),
+                    version: ManagerVersion::Exact(YARN_VERSION.clone()),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Yarn,
                     source: Source::Project(PROJECT_PATH.clone()),
-                    version: Version::from((1, 17, 0)),
+                    version: ManagerVersion::Exact(Version::from((1, 17, 0))),
                 },
             ];
 
@@ -1158,9 +1354,65 @@ See `volta help install` for details and more options.";
                 details: PackageDetails {
                     name: "ember-cli".to_string(),
                     version: Version::from((3, 10, 1)),
+                    tag: None,
+                    installed_from: None,
+                    image: None,
                 },
                 node: NODE_12.clone(),
                 tools: vec!["ember".to_string()],
+                shims: true,
+            }];
+
+            assert_eq!(display_packages(&packages), expected);
+        }
+
+        #[test]
+        fn single_default_from_tag() {
+            let expected = "⚡️ Package versions in your toolchain:
+
+    typescript@5.4.0-beta (from tag: beta) (default)
+        binary tools: tsc, tsserver
+        platform:
+            runtime: node@12.2.0
+            package manager: npm@built-in";
+
+            let packages = [Package::Default {
+                details: PackageDetails {
+                    name: "typescript".to_string(),
+                    version: Version::parse("5.4.0-beta").unwrap(),
+                    tag: Some("beta".to_string()),
+                    installed_from: None,
+                    image: None,
+                },
+                node: NODE_12.clone(),
+                tools: vec!["tsc".to_string(), "tsserver".to_string()],
+                shims: true,
+            }];
+
+            assert_eq!(display_packages(&packages), expected);
+        }
+
+        #[test]
+        fn single_default_installed_from_directory() {
+            let expected = "⚡️ Package versions in your toolchain:
+
+    my-cli@1.0.0 (from /work/my-cli) (default)
+        binary tools: my-cli
+        platform:
+            runtime: node@12.2.0
+            package manager: npm@built-in";
+
+            let packages = [Package::Default {
+                details: PackageDetails {
+                    name: "my-cli".to_string(),
+                    version: Version::from((1, 0, 0)),
+                    tag: None,
+                    installed_from: Some("/work/my-cli".to_string()),
+                    image: None,
+                },
+                node: NODE_12.clone(),
+                tools: vec!["my-cli".to_string()],
+                shims: true,
             }];
 
             assert_eq!(display_packages(&packages), expected);
@@ -1176,6 +1428,7 @@ See `volta help install` for details and more options.";
             let packages = [Package::Project {
                 name: "ember-cli".to_string(),
                 path: PROJECT_PATH.clone(),
+                image: None,
                 tools: vec!["ember".to_string()],
             }];
 
@@ -1193,6 +1446,9 @@ See `volta help install` for details and more options.";
             let packages = [Package::Fetched(PackageDetails {
                 name: "ember-cli".to_string(),
                 version: Version::from((3, 10, 1)),
+                tag: None,
+                installed_from: None,
+                image: None,
             })];
 
             assert_eq!(display_packages(&packages), expected);
@@ -1213,10 +1469,16 @@ See `volta help install` for details and more options.";
                 Package::Fetched(PackageDetails {
                     name: "ember-cli".to_string(),
                     version: Version::from((3, 10, 1)),
+                    tag: None,
+                    installed_from: None,
+                    image: None,
                 }),
                 Package::Fetched(PackageDetails {
                     name: "ember-cli".to_string(),
                     version: Version::from((3, 8, 2)),
+                    tag: None,
+                    installed_from: None,
+                    image: None,
                 }),
             ];
 
@@ -1240,13 +1502,18 @@ See `volta help install` for details and more options.";
                     details: PackageDetails {
                         name: "ember-cli".to_string(),
                         version: Version::from((3, 10, 1)),
+                        tag: None,
+                        installed_from: None,
+                        image: None,
                     },
                     node: NODE_12.clone(),
                     tools: vec!["ember".to_string()],
+                    shims: true,
                 },
                 Package::Project {
                     name: "ember-cli".to_string(),
                     path: PROJECT_PATH.clone(),
+                    image: None,
                     tools: vec!["ember".to_string()],
                 },
             ];
@@ -1284,9 +1551,13 @@ See `volta help install` for details and more options.";
                 details: PackageDetails {
                     name: "ember-cli".to_string(),
                     version: Version::from((3, 10, 1)),
+                    tag: None,
+                    installed_from: None,
+                    image: None,
                 },
                 node: NODE_12.clone(),
                 tools: vec!["ember".to_string()],
+                shims: true,
             }];
 
             assert_eq!(display_tool("ember", &packages), expected);
@@ -1302,6 +1573,7 @@ See `volta help install` for details and more options.";
             let packages = [Package::Project {
                 name: "ember-cli".to_string(),
                 path: PROJECT_PATH.clone(),
+                image: None,
                 tools: vec!["ember".to_string()],
             }];
 
@@ -1319,6 +1591,9 @@ See `volta help install` for details and more options.";
             let packages = [Package::Fetched(PackageDetails {
                 name: "ember-cli".to_string(),
                 version: Version::from((3, 10, 1)),
+                tag: None,
+                installed_from: None,
+                image: None,
             })];
 
             assert_eq!(display_tool("ember", &packages), expected);
@@ -1339,10 +1614,16 @@ See `volta help install` for details and more options.";
                 Package::Fetched(PackageDetails {
                     name: "ember-cli".to_string(),
                     version: Version::from((3, 10, 1)),
+                    tag: None,
+                    installed_from: None,
+                    image: None,
                 }),
                 Package::Fetched(PackageDetails {
                     name: "ember-cli".to_string(),
                     version: Version::from((3, 8, 2)),
+                    tag: None,
+                    installed_from: None,
+                    image: None,
                 }),
             ];
 
@@ -1366,13 +1647,18 @@ See `volta help install` for details and more options.";
                     details: PackageDetails {
                         name: "ember-cli".to_string(),
                         version: Version::from((3, 10, 1)),
+                        tag: None,
+                        installed_from: None,
+                        image: None,
                     },
                     node: NODE_12.clone(),
                     tools: vec!["ember".to_string()],
+                    shims: true,
                 },
                 Package::Project {
                     name: "ember-cli".to_string(),
                     path: PROJECT_PATH.clone(),
+                    image: None,
                     tools: vec!["ember".to_string()],
                 },
             ];
@@ -1419,14 +1705,23 @@ See `volta help install` for details and more options.";
                 Node {
                     source: Source::Project(PROJECT_PATH.clone()),
                     version: NODE_12.clone(),
+                    eol: false,
+                    image: None,
+                    bundled_npm: None,
                 },
                 Node {
                     source: Source::None,
                     version: NODE_11.clone(),
+                    eol: false,
+                    image: None,
+                    bundled_npm: None,
                 },
                 Node {
                     source: Source::Default,
                     version: NODE_10.clone(),
+                    eol: false,
+                    image: None,
+                    bundled_npm: None,
                 },
             ];
 
@@ -1434,17 +1729,17 @@ See `volta help install` for details and more options.";
                 PackageManager {
                     kind: PackageManagerKind::Npm,
                     source: Source::Default,
-                    version: NPM_VERSION.clone(),
+                    version: ManagerVersion::Exact(NPM_VERSION.clone()),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Npm,
                     source: Source::Project(PROJECT_PATH.clone()),
-                    version: Version::from((6, 12, 0)),
+                    version: ManagerVersion::Exact(Version::from((6, 12, 0))),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Npm,
                     source: Source::None,
-                    version: Version::from((5, 6, 0)),
+                    version: ManagerVersion::Exact(Version::from((5, 6, 0))),
                 },
             ];
 
@@ -1477,14 +1772,23 @@ See `volta help install` for details and more options.";
                 Node {
                     source: Source::Project(PROJECT_PATH.clone()),
                     version: NODE_12.clone(),
+                    eol: false,
+                    image: None,
+                    bundled_npm: None,
                 },
                 Node {
                     source: Source::None,
                     version: NODE_11.clone(),
+                    eol: false,
+                    image: None,
+                    bundled_npm: None,
                 },
                 Node {
                     source: Source::Default,
                     version: NODE_10.clone(),
+                    eol: false,
+                    image: None,
+                    bundled_npm: None,
                 },
             ];
 
@@ -1492,17 +1796,17 @@ See `volta help install` for details and more options.";
                 PackageManager {
                     kind: PackageManagerKind::Yarn,
                     source: Source::Default,
-                    version: YARN_VERSION.clone(),
+                    version: ManagerVersion::Exact(YARN_VERSION.clone()),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Yarn,
                     source: Source::Project(PROJECT_PATH.clone()),
-                    version: Version::from((1, 17, 0)),
+                    version: ManagerVersion::Exact(Version::from((1, 17, 0))),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Yarn,
                     source: Source::None,
-                    version: Version::from((1, 4, 0)),
+                    version: ManagerVersion::Exact(Version::from((1, 4, 0))),
                 },
             ];
 
@@ -1552,14 +1856,23 @@ See `volta help install` for details and more options.";
                 Node {
                     source: Source::Project(PROJECT_PATH.clone()),
                     version: NODE_12.clone(),
+                    eol: false,
+                    image: None,
+                    bundled_npm: None,
                 },
                 Node {
                     source: Source::None,
                     version: NODE_11.clone(),
+                    eol: false,
+                    image: None,
+                    bundled_npm: None,
                 },
                 Node {
                     source: Source::Default,
                     version: NODE_10.clone(),
+                    eol: false,
+                    image: None,
+                    bundled_npm: None,
                 },
             ];
 
@@ -1567,32 +1880,32 @@ See `volta help install` for details and more options.";
                 PackageManager {
                     kind: PackageManagerKind::Npm,
                     source: Source::Default,
-                    version: NPM_VERSION.clone(),
+                    version: ManagerVersion::Exact(NPM_VERSION.clone()),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Npm,
                     source: Source::Project(PROJECT_PATH.clone()),
-                    version: Version::from((6, 12, 0)),
+                    version: ManagerVersion::Exact(Version::from((6, 12, 0))),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Npm,
                     source: Source::None,
-                    version: Version::from((5, 6, 0)),
+                    version: ManagerVersion::Exact(Version::from((5, 6, 0))),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Yarn,
                     source: Source::Default,
-                    version: YARN_VERSION.clone(),
+                    version: ManagerVersion::Exact(YARN_VERSION.clone()),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Yarn,
                     source: Source::Project(PROJECT_PATH.clone()),
-                    version: Version::from((1, 17, 0)),
+                    version: ManagerVersion::Exact(Version::from((1, 17, 0))),
                 },
                 PackageManager {
                     kind: PackageManagerKind::Yarn,
                     source: Source::None,
-                    version: Version::from((1, 4, 0)),
+                    version: ManagerVersion::Exact(Version::from((1, 4, 0))),
                 },
             ];
 
@@ -1601,27 +1914,37 @@ See `volta help install` for details and more options.";
                     details: PackageDetails {
                         name: "typescript".to_string(),
                         version: Version::from((3, 4, 3)),
+                        tag: None,
+                        installed_from: None,
+                        image: None,
                     },
                     node: NODE_12.clone(),
                     tools: vec!["tsc".to_string(), "tsserver".to_string()],
+                    shims: true,
                 },
                 Package::Project {
                     name: "typescript".to_string(),
                     path: PROJECT_PATH.clone(),
+                    image: None,
                     tools: vec!["tsc".to_string(), "tsserver".to_string()],
                 },
                 Package::Project {
                     name: "ember-cli".to_string(),
                     path: PROJECT_PATH.clone(),
+                    image: None,
                     tools: vec!["ember".to_string()],
                 },
                 Package::Default {
                     details: PackageDetails {
                         name: "ember-cli".to_string(),
                         version: Version::from((3, 8, 2)),
+                        tag: None,
+                        installed_from: None,
+                        image: None,
                     },
                     node: NODE_12.clone(),
                     tools: vec!["ember".to_string()],
+                    shims: true,
                 },
             ];
             assert_eq!(
@@ -0,0 +1,728 @@
+//! Define the "json" format style for list commands.
+//!
+//! The JSON output is meant for other programs, so its shape is versioned: every document includes
+//! a top-level `jsonVersion`, and the shape of a given version never changes. Adding, removing, or
+//! changing the meaning of a field requires a new version, which callers opt into with `--json=N`.
+//! The schema for each version is printed by `volta list --json-schema`.
+
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+use volta_core::alias::Alias;
+use volta_core::error::{Context, ErrorKind, Fallible};
+
+use super::diff::{Change, FieldChange, Item};
+use super::outdated::OutdatedRow;
+use super::stats::UsageRow;
+use super::{Node, Package, PackageManager, Source, Toolchain};
+use crate::command::to_json;
+
+/// The newest version of the JSON output, used when no specific version is requested
+///
+/// - Version 1: the original output
+/// - Version 2: adds `eol` to each runtime, for Node versions that have reached end-of-life
+/// - Version 3: adds `path` to each runtime and package, for the directory it's installed in
+/// - Version 4: adds the output of `volta list --outdated`, with the newest matching releases
+/// - Version 5: adds the output of `volta list --aliases`, with the version aliases
+/// - Version 6: adds the output of `volta list --diff`, with the changes since a snapshot
+/// - Version 7: adds `installedFrom` to each package, for the local directory or git URL it was
+///   installed from
+pub(super) const JSON_VERSION: u32 = 7;
+
+/// The first version of the JSON output that includes the output of `volta list --outdated`
+pub(super) const OUTDATED_JSON_VERSION: u32 = 4;
+
+/// The first version of the JSON output that includes the output of `volta list --aliases`
+pub(super) const ALIASES_JSON_VERSION: u32 = 5;
+
+/// The first version of the JSON output that includes the output of `volta list --diff`
+pub(super) const DIFF_JSON_VERSION: u32 = 6;
+
+/// The JSON Schema describing version 1 of the output
+const JSON_SCHEMA_V1: &str = include_str!("json-schema-v1.json");
+
+/// The JSON Schema describing version 2 of the output
+const JSON_SCHEMA_V2: &str = include_str!("json-schema-v2.json");
+
+/// The JSON Schema describing version 3 of the output
+const JSON_SCHEMA_V3: &str = include_str!("json-schema-v3.json");
+
+/// The JSON Schema describing version 4 of the output
+const JSON_SCHEMA_V4: &str = include_str!("json-schema-v4.json");
+
+/// The JSON Schema describing version 5 of the output
+const JSON_SCHEMA_V5: &str = include_str!("json-schema-v5.json");
+
+/// The JSON Schema describing version 6 of the output
+const JSON_SCHEMA_V6: &str = include_str!("json-schema-v6.json");
+
+/// The JSON Schema describing version 7 of the output
+const JSON_SCHEMA_V7: &str = include_str!("json-schema-v7.json");
+
+/// Parses the version requested with `--json=N`, rejecting versions that aren't supported
+pub(super) fn parse_json_version(value: &str) -> Result<u32, String> {
+    match value.parse() {
+        Ok(version @ 1..=JSON_VERSION) => Ok(version),
+        _ => Err(format!(
+            "unsupported JSON version '{}', the supported versions are: {}",
+            value,
+            (1..=JSON_VERSION)
+                .map(|version| version.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// The JSON Schema describing the given version of the output
+pub(super) fn schema(version: u32) -> &'static str {
+    match version {
+        1 => JSON_SCHEMA_V1,
+        2 => JSON_SCHEMA_V2,
+        3 => JSON_SCHEMA_V3,
+        4 => JSON_SCHEMA_V4,
+        5 => JSON_SCHEMA_V5,
+        6 => JSON_SCHEMA_V6,
+        _ => JSON_SCHEMA_V7,
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolchainDocument<'a> {
+    json_version: u32,
+    runtimes: Vec<JsonRuntime<'a>>,
+    package_managers: Vec<JsonPackageManager<'a>>,
+    packages: Vec<JsonPackage<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageDocument<'a> {
+    json_version: u32,
+    usage: Vec<JsonUsage<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OutdatedDocument<'a> {
+    json_version: u32,
+    tools: Vec<JsonOutdated<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AliasesDocument<'a> {
+    json_version: u32,
+    aliases: Vec<JsonAlias<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiffDocument<'a> {
+    json_version: u32,
+    added: Vec<JsonDiffItem<'a>>,
+    removed: Vec<JsonDiffItem<'a>>,
+    changed: Vec<JsonDiffChange<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+enum JsonSource {
+    /// The version is pinned in the current project
+    Project,
+    /// The version is the user's default
+    Default,
+    /// The version is available locally, but isn't in use
+    Fetched,
+}
+
+#[derive(Serialize)]
+struct JsonRuntime<'a> {
+    name: &'static str,
+    version: String,
+    source: JsonSource,
+    project: Option<&'a Path>,
+    /// Only included from version 2
+    #[serde(skip_serializing_if = "Option::is_none")]
+    eol: Option<bool>,
+    /// Only included from version 3
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<Option<&'a Path>>,
+}
+
+#[derive(Serialize)]
+struct JsonPackageManager<'a> {
+    name: String,
+    version: String,
+    source: JsonSource,
+    project: Option<&'a Path>,
+}
+
+#[derive(Serialize)]
+struct JsonPackage<'a> {
+    name: &'a str,
+    version: Option<String>,
+    tag: Option<&'a str>,
+    /// Only included from version 7
+    #[serde(rename = "installedFrom", skip_serializing_if = "Option::is_none")]
+    installed_from: Option<Option<&'a str>>,
+    source: JsonSource,
+    project: Option<&'a Path>,
+    node: Option<String>,
+    binaries: &'a [String],
+    /// Only included from version 3
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<Option<&'a Path>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonUsage<'a> {
+    name: &'a str,
+    version: String,
+    runs: u64,
+    last_used: Option<String>,
+}
+
+#[derive(Serialize)]
+struct JsonOutdated<'a> {
+    name: &'a str,
+    version: String,
+    source: JsonSource,
+    project: Option<&'a Path>,
+    latest: Option<String>,
+    outdated: bool,
+}
+
+#[derive(Serialize)]
+struct JsonAlias<'a> {
+    tool: &'a str,
+    name: &'a str,
+    target: &'a str,
+    source: &'a Path,
+}
+
+#[derive(Serialize)]
+struct JsonDiffItem<'a> {
+    kind: &'static str,
+    name: &'a str,
+    version: Option<&'a str>,
+    source: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct JsonDiffChange<'a> {
+    kind: &'static str,
+    name: &'a str,
+    /// The version of a runtime or package manager, which identifies it; null for packages
+    version: Option<&'a str>,
+    changes: &'a [FieldChange],
+}
+
+pub(super) fn format(toolchain: &Toolchain, version: u32) -> Fallible<String> {
+    to_json(&toolchain_document(toolchain, version))
+}
+
+/// The toolchain document as a JSON value, for comparing it with a snapshot
+pub(super) fn to_value(toolchain: &Toolchain, version: u32) -> Fallible<Value> {
+    serde_json::to_value(toolchain_document(toolchain, version))
+        .with_context(|| ErrorKind::JsonOutputError)
+}
+
+fn toolchain_document(toolchain: &Toolchain, version: u32) -> ToolchainDocument {
+    let (runtimes, package_managers, packages) = toolchain.items();
+
+    ToolchainDocument {
+        json_version: version,
+        runtimes: runtimes
+            .iter()
+            .map(|node| json_runtime(node, version))
+            .collect(),
+        package_managers: package_managers.iter().map(json_package_manager).collect(),
+        packages: packages
+            .iter()
+            .map(|package| json_package(package, version))
+            .collect(),
+    }
+}
+
+pub(super) fn format_usage(rows: &[UsageRow], version: u32) -> Fallible<String> {
+    let document = UsageDocument {
+        json_version: version,
+        usage: rows
+            .iter()
+            .map(|row| JsonUsage {
+                name: row.tool,
+                version: row.version.to_string(),
+                runs: row.record.map_or(0, |record| record.count),
+                last_used: row.record.map(|record| record.last_used_date()),
+            })
+            .collect(),
+    };
+
+    to_json(&document)
+}
+
+pub(super) fn format_outdated(rows: &[OutdatedRow], version: u32) -> Fallible<String> {
+    let document = OutdatedDocument {
+        json_version: version,
+        tools: rows
+            .iter()
+            .map(|row| {
+                let (source, project) = json_source(&row.source);
+                JsonOutdated {
+                    name: &row.name,
+                    version: row.version.to_string(),
+                    source,
+                    project,
+                    latest: row.latest.as_ref().map(ToString::to_string),
+                    outdated: row.is_outdated(),
+                }
+            })
+            .collect(),
+    };
+
+    to_json(&document)
+}
+
+pub(super) fn format_aliases(aliases: &[&Alias], version: u32) -> Fallible<String> {
+    let document = AliasesDocument {
+        json_version: version,
+        aliases: aliases
+            .iter()
+            .map(|alias| JsonAlias {
+                tool: &alias.tool,
+                name: &alias.name,
+                target: &alias.target,
+                source: &alias.source,
+            })
+            .collect(),
+    };
+
+    to_json(&document)
+}
+
+pub(super) fn format_diff(changes: &[Change], version: u32) -> Fallible<String> {
+    let mut document = DiffDocument {
+        json_version: version,
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+    };
+
+    for change in changes {
+        match change {
+            Change::Added(item) => document.added.push(json_diff_item(item)),
+            Change::Removed(item) => document.removed.push(json_diff_item(item)),
+            Change::Changed { key, fields } => document.changed.push(JsonDiffChange {
+                kind: key.kind.name(),
+                name: &key.name,
+                version: key.version.as_deref(),
+                changes: fields,
+            }),
+        }
+    }
+
+    to_json(&document)
+}
+
+fn json_diff_item(item: &Item) -> JsonDiffItem {
+    JsonDiffItem {
+        kind: item.key.kind.name(),
+        name: &item.key.name,
+        version: item.version(),
+        source: item.source(),
+    }
+}
+
+fn json_source(source: &Source) -> (JsonSource, Option<&Path>) {
+    match source {
+        Source::Project(path) => (JsonSource::Project, Some(path)),
+        Source::Default => (JsonSource::Default, None),
+        Source::None => (JsonSource::Fetched, None),
+    }
+}
+
+fn json_runtime(node: &Node, version: u32) -> JsonRuntime {
+    let (source, project) = json_source(&node.source);
+    JsonRuntime {
+        name: "node",
+        version: node.version.to_string(),
+        source,
+        project,
+        eol: (version >= 2).then_some(node.eol),
+        path: (version >= 3).then_some(node.image.as_deref()),
+    }
+}
+
+fn json_package_manager(package_manager: &PackageManager) -> JsonPackageManager {
+    let (source, project) = json_source(&package_manager.source);
+    JsonPackageManager {
+        name: package_manager.kind.to_string(),
+        version: package_manager.version.to_string(),
+        source,
+        project,
+    }
+}
+
+fn json_package(package: &Package, version: u32) -> JsonPackage {
+    let image = match package {
+        Package::Default { details, .. } | Package::Fetched(details) => details.image.as_deref(),
+        Package::Project { image, .. } => image.as_deref(),
+    };
+    let path = (version >= 3).then_some(image);
+
+    match package {
+        Package::Default {
+            details,
+            node,
+            tools,
+            ..
+        } => JsonPackage {
+            name: &details.name,
+            version: Some(details.version.to_string()),
+            tag: details.tag.as_deref(),
+            installed_from: (version >= 7).then_some(details.installed_from.as_deref()),
+            source: JsonSource::Default,
+            project: None,
+            node: Some(node.to_string()),
+            binaries: tools,
+            path,
+        },
+        Package::Project {
+            name,
+            tools,
+            path: project,
+            ..
+        } => JsonPackage {
+            name,
+            version: None,
+            tag: None,
+            installed_from: (version >= 7).then_some(None),
+            source: JsonSource::Project,
+            project: Some(project),
+            node: None,
+            binaries: tools,
+            path,
+        },
+        Package::Fetched(details) => JsonPackage {
+            name: &details.name,
+            version: Some(details.version.to_string()),
+            tag: details.tag.as_deref(),
+            installed_from: (version >= 7).then_some(details.installed_from.as_deref()),
+            source: JsonSource::Fetched,
+            project: None,
+            node: None,
+            binaries: &[],
+            path,
+        },
+    }
+}
+
+// The golden files lock the shape of each JSON version: if one of these tests fails, the change
+// to the output needs a new `jsonVersion` rather than an update to the golden file.
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use node_semver::Version;
+    use serde_json::json;
+
+    use super::*;
+    use crate::command::list::diff::{compare, Snapshot};
+    use crate::command::list::{ManagerVersion, PackageDetails, PackageManagerKind};
+    use volta_core::usage::UsageRecord;
+
+    fn project_path() -> PathBuf {
+        PathBuf::from("/home/user/project/package.json")
+    }
+
+    #[test]
+    fn version_parsing() {
+        assert_eq!(parse_json_version("1"), Ok(1));
+        assert_eq!(parse_json_version("2"), Ok(2));
+        assert_eq!(parse_json_version("3"), Ok(3));
+        assert_eq!(parse_json_version("4"), Ok(4));
+        assert_eq!(parse_json_version("5"), Ok(5));
+        assert_eq!(parse_json_version("6"), Ok(6));
+        assert_eq!(parse_json_version("7"), Ok(7));
+        assert!(parse_json_version("0").is_err());
+        assert!(parse_json_version("8").is_err());
+        assert!(parse_json_version("latest").is_err());
+    }
+
+    #[test]
+    fn schema_matches_version() {
+        for version in 1..=JSON_VERSION {
+            let schema: serde_json::Value = serde_json::from_str(schema(version)).unwrap();
+            assert_eq!(
+                schema["$defs"]["jsonVersion"]["const"],
+                serde_json::json!(version)
+            );
+        }
+    }
+
+    fn all_toolchain() -> Toolchain {
+        Toolchain::All {
+            runtimes: vec![
+                Node {
+                    source: Source::Project(project_path()),
+                    version: Version::from((18, 12, 1)),
+                    eol: false,
+                    image: None,
+                    bundled_npm: None,
+                },
+                Node {
+                    source: Source::Default,
+                    version: Version::from((16, 20, 0)),
+                    eol: true,
+                    image: Some(PathBuf::from("/home/user/.volta/tools/image/node/16.20.0")),
+                    bundled_npm: None,
+                },
+                Node {
+                    source: Source::None,
+                    version: Version::from((14, 21, 3)),
+                    eol: true,
+                    image: Some(PathBuf::from("/home/user/.volta/tools/image/node/14.21.3")),
+                    bundled_npm: None,
+                },
+            ],
+            package_managers: vec![
+                PackageManager {
+                    kind: PackageManagerKind::Npm,
+                    source: Source::Project(project_path()),
+                    version: ManagerVersion::Bundled,
+                },
+                PackageManager {
+                    kind: PackageManagerKind::Yarn,
+                    source: Source::Default,
+                    version: ManagerVersion::Exact(Version::from((1, 22, 19))),
+                },
+            ],
+            packages: vec![
+                Package::Default {
+                    details: PackageDetails {
+                        name: "typescript".into(),
+                        version: Version::from((5, 0, 4)),
+                        tag: Some("latest".into()),
+                        installed_from: None,
+                        image: Some(PathBuf::from(
+                            "/home/user/.volta/tools/image/packages/typescript",
+                        )),
+                    },
+                    node: Version::from((16, 20, 0)),
+                    tools: vec!["tsc".into(), "tsserver".into()],
+                    shims: true,
+                },
+                Package::Project {
+                    name: "ember-cli".into(),
+                    tools: vec!["ember".into()],
+                    path: project_path(),
+                    image: Some(PathBuf::from(
+                        "/home/user/.volta/tools/image/packages/ember-cli",
+                    )),
+                },
+                Package::Fetched(PackageDetails {
+                    name: "cowsay".into(),
+                    version: Version::from((1, 5, 0)),
+                    tag: None,
+                    installed_from: Some("https://github.com/piuccio/cowsay.git".into()),
+                    image: None,
+                }),
+            ],
+        }
+    }
+
+    #[test]
+    fn golden_v1_all() {
+        assert_eq!(
+            format(&all_toolchain(), 1).unwrap(),
+            include_str!("golden/v1-all.json").trim_end()
+        );
+    }
+
+    #[test]
+    fn golden_v2_all() {
+        assert_eq!(
+            format(&all_toolchain(), 2).unwrap(),
+            include_str!("golden/v2-all.json").trim_end()
+        );
+    }
+
+    #[test]
+    fn golden_v3_all() {
+        assert_eq!(
+            format(&all_toolchain(), 3).unwrap(),
+            include_str!("golden/v3-all.json").trim_end()
+        );
+    }
+
+    #[test]
+    fn golden_v4_all() {
+        assert_eq!(
+            format(&all_toolchain(), 4).unwrap(),
+            include_str!("golden/v4-all.json").trim_end()
+        );
+    }
+
+    #[test]
+    fn golden_v5_all() {
+        assert_eq!(
+            format(&all_toolchain(), 5).unwrap(),
+            include_str!("golden/v5-all.json").trim_end()
+        );
+    }
+
+    #[test]
+    fn golden_v6_all() {
+        assert_eq!(
+            format(&all_toolchain(), 6).unwrap(),
+            include_str!("golden/v6-all.json").trim_end()
+        );
+    }
+
+    #[test]
+    fn golden_v7_all() {
+        assert_eq!(
+            format(&all_toolchain(), 7).unwrap(),
+            include_str!("golden/v7-all.json").trim_end()
+        );
+    }
+
+    #[test]
+    fn golden_v1_empty() {
+        let toolchain = Toolchain::Active {
+            runtime: None,
+            package_managers: vec![],
+            packages: vec![],
+        };
+
+        assert_eq!(
+            format(&toolchain, 1).unwrap(),
+            include_str!("golden/v1-empty.json").trim_end()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn path_that_is_not_unicode_is_an_error() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let toolchain = Toolchain::Node(vec![Node {
+            source: Source::Project(PathBuf::from(OsStr::from_bytes(b"/home/\xff/package.json"))),
+            version: Version::from((18, 12, 1)),
+            eol: false,
+            image: None,
+            bundled_npm: None,
+        }]);
+
+        let error = format(&toolchain, JSON_VERSION).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::JsonOutputError));
+    }
+
+    #[test]
+    fn golden_v1_usage() {
+        let rows = [
+            UsageRow {
+                tool: "node",
+                version: Version::from((18, 12, 1)),
+                record: Some(UsageRecord {
+                    count: 42,
+                    last_used: 1_700_000_000,
+                }),
+            },
+            UsageRow {
+                tool: "yarn",
+                version: Version::from((1, 22, 19)),
+                record: None,
+            },
+        ];
+
+        assert_eq!(
+            format_usage(&rows, 1).unwrap(),
+            include_str!("golden/v1-usage.json").trim_end()
+        );
+    }
+
+    #[test]
+    fn golden_v4_outdated() {
+        let rows = [
+            OutdatedRow {
+                name: "node".into(),
+                version: Version::from((18, 12, 1)),
+                source: Source::Project(project_path()),
+                latest: Some(Version::from((18, 19, 0))),
+            },
+            OutdatedRow {
+                name: "cowsay".into(),
+                version: Version::from((1, 5, 0)),
+                source: Source::Default,
+                latest: None,
+            },
+        ];
+
+        assert_eq!(
+            format_outdated(&rows, 4).unwrap(),
+            include_str!("golden/v4-outdated.json").trim_end()
+        );
+    }
+
+    #[test]
+    fn golden_v5_aliases() {
+        let aliases = [
+            Alias {
+                tool: "node".into(),
+                name: "company-standard".into(),
+                target: "18.19.0".into(),
+                source: PathBuf::from("/home/user/project/.volta/hooks.json"),
+            },
+            Alias {
+                tool: "yarn".into(),
+                name: "company-standard".into(),
+                target: "^1.22".into(),
+                source: PathBuf::from("/home/user/.volta/settings.json"),
+            },
+        ];
+
+        assert_eq!(
+            format_aliases(&aliases.iter().collect::<Vec<_>>(), 5).unwrap(),
+            include_str!("golden/v5-aliases.json").trim_end()
+        );
+    }
+    #[test]
+    fn golden_v6_diff() {
+        let before = Snapshot::from_document(&json!({
+            "jsonVersion": 6,
+            "runtimes": [
+                { "name": "node", "version": "18.12.1", "source": "default", "project": null, "eol": false, "path": null }
+            ],
+            "packageManagers": [
+                { "name": "yarn", "version": "1.22.19", "source": "default", "project": null }
+            ],
+            "packages": [
+                { "name": "typescript", "version": "5.0.4", "tag": "latest", "source": "default", "project": null, "node": "18.12.1", "binaries": ["tsc"], "path": null }
+            ]
+        }))
+        .unwrap();
+        let after = Snapshot::from_document(&json!({
+            "jsonVersion": 6,
+            "runtimes": [
+                { "name": "node", "version": "18.12.1", "source": "fetched", "project": null, "eol": false, "path": null },
+                { "name": "node", "version": "20.11.1", "source": "default", "project": null, "eol": false, "path": null }
+            ],
+            "packageManagers": [],
+            "packages": [
+                { "name": "typescript", "version": "5.3.3", "tag": "latest", "source": "default", "project": null, "node": "18.12.1", "binaries": ["tsc"], "path": null }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            format_diff(&compare(&before, &after), 6).unwrap(),
+            include_str!("golden/v6-diff.json").trim_end()
+        );
+    }
+}
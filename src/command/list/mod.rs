@@ -1,24 +1,38 @@
+mod aliases;
+mod diff;
 mod human;
+mod json;
+mod outdated;
 mod plain;
+mod stats;
 mod toolchain;
 
+use std::fs;
 use std::io::IsTerminal as _;
+use std::time::Duration;
 use std::{fmt, path::PathBuf, str::FromStr};
 
 use node_semver::Version;
 
+use crate::command::lock::check_platform_lock;
 use crate::command::Command;
+use log::{info, warn};
 use toolchain::Toolchain;
-use volta_core::error::{ExitCode, Fallible};
+use volta_core::error::{Context, ErrorKind, ExitCode, Fallible};
 use volta_core::inventory::package_configs;
+use volta_core::layout::volta_home;
 use volta_core::project::Project;
 use volta_core::session::{ActivityKind, Session};
+use volta_core::style::{success_prefix, tool_version};
+use volta_core::tool::node::ReleaseSchedule;
 use volta_core::tool::PackageConfig;
+use volta_core::usage::{self, UsageStats};
 
 #[derive(clap::ValueEnum, Copy, Clone)]
 enum Format {
     Human,
     Plain,
+    Json,
 }
 
 /// The source of a given item, from the perspective of a user.
@@ -70,6 +84,13 @@ struct PackageDetails {
     pub name: String,
     /// The package's own version.
     pub version: Version,
+    /// The registry dist-tag the package was installed from, if any.
+    pub tag: Option<String>,
+    /// The local path or git URL the package was installed from, if it didn't come from the
+    /// registry.
+    pub installed_from: Option<String>,
+    /// The directory the package is installed in, if it's present on disk.
+    pub image: Option<PathBuf>,
 }
 
 enum Package {
@@ -79,21 +100,32 @@ enum Package {
         node: Version,
         /// The names of the tools associated with the package.
         tools: Vec<String>,
+        /// Whether shims were created for the tools.
+        shims: bool,
     },
     Project {
         name: String,
         /// The names of the tools associated with the package.
         tools: Vec<String>,
         path: PathBuf,
+        /// The directory the package is installed in, if it's present on disk.
+        image: Option<PathBuf>,
     },
     Fetched(PackageDetails),
 }
 
 impl Package {
     fn new(config: &PackageConfig, source: &Source) -> Package {
+        let image = volta_home()
+            .ok()
+            .map(|home| home.package_image_dir(&config.name))
+            .filter(|dir| dir.is_dir());
         let details = PackageDetails {
             name: config.name.clone(),
             version: config.version.clone(),
+            tag: config.tag.clone(),
+            installed_from: config.source.clone(),
+            image,
         };
 
         match source {
@@ -101,11 +133,13 @@ impl Package {
                 details,
                 node: config.platform.node.clone(),
                 tools: config.bins.clone(),
+                shims: config.shims,
             },
             Source::Project(path) => Package::Project {
                 name: details.name,
                 tools: config.bins.clone(),
                 path: path.clone(),
+                image: details.image,
             },
             Source::None => Package::Fetched(details),
         }
@@ -137,9 +171,40 @@ impl Package {
 struct Node {
     pub source: Source,
     pub version: Version,
+    /// Whether the version's release line has reached end-of-life
+    pub eol: bool,
+    /// The directory the version is unpacked in, if it has been fetched
+    pub image: Option<PathBuf>,
+    /// The npm version recorded as bundled with this version, shown by `volta list node`
+    pub bundled_npm: Option<Version>,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+impl Node {
+    fn new(source: Source, version: Version, schedule: &ReleaseSchedule) -> Node {
+        let eol = schedule.is_end_of_life_today(&version);
+        let image = volta_home()
+            .ok()
+            .map(|home| home.node_image_dir(&version.to_string()))
+            .filter(|dir| dir.is_dir());
+        Node {
+            source,
+            version,
+            eol,
+            image,
+            bundled_npm: None,
+        }
+    }
+
+    /// The note naming the recorded bundled npm version, if there is one
+    fn bundled_npm_note(&self) -> String {
+        match &self.bundled_npm {
+            Some(npm) => format!(" (bundles {})", tool_version("npm", npm)),
+            None => String::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum PackageManagerKind {
     Npm,
     Pnpm,
@@ -164,7 +229,24 @@ impl fmt::Display for PackageManagerKind {
 struct PackageManager {
     kind: PackageManagerKind,
     source: Source,
-    version: Version,
+    version: ManagerVersion,
+}
+
+/// The version of a package manager, which may be the one bundled with Node
+#[derive(Clone)]
+enum ManagerVersion {
+    Exact(Version),
+    /// The project explicitly pins the npm bundled with its Node version (`"npm": "bundled"`)
+    Bundled,
+}
+
+impl fmt::Display for ManagerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ManagerVersion::Exact(version) => version.fmt(f),
+            ManagerVersion::Bundled => f.write_str("bundled"),
+        }
+    }
 }
 
 /// How (if at all) should the list query be narrowed?
@@ -194,10 +276,29 @@ pub(crate) struct List {
 
     /// Specify the output format.
     ///
-    /// Defaults to `human` for TTYs, `plain` otherwise.
-    #[arg(long)]
+    /// Defaults to `human` for TTYs, `plain` otherwise. `json` is the same as `--json`.
+    #[arg(long, conflicts_with = "json")]
     format: Option<Format>,
 
+    /// Output JSON, optionally in a specific version of the format (e.g. `--json=2`).
+    ///
+    /// Every document includes a top-level `jsonVersion`. The shape of a version never changes,
+    /// so scripts can request the version they were written against. Defaults to the newest
+    /// version; see `--json-schema` for the schema.
+    #[arg(
+        long,
+        value_name = "version",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "7",
+        value_parser = json::parse_json_version
+    )]
+    json: Option<u32>,
+
+    /// Print the JSON Schema for the `--json` output and exit.
+    #[arg(long, exclusive = true)]
+    json_schema: bool,
+
     /// Show the currently-active tool(s).
     ///
     /// Equivalent to `volta list` when not specifying a specific tool.
@@ -207,6 +308,60 @@ pub(crate) struct List {
     /// Show your default tool(s).
     #[arg(short, long, conflicts_with = "current")]
     default: bool,
+
+    /// Show how often each fetched version has been run, from the local usage counters.
+    ///
+    /// Counters are only recorded while the `usage.stats` setting is enabled.
+    #[arg(long, conflicts_with_all = ["current", "default"])]
+    stats: bool,
+
+    /// Compare the listed versions with the newest matching releases.
+    ///
+    /// Node and package managers are compared with the newest release in the same major version,
+    /// and packages with the newest version of the tag they were installed from. Releases that
+    /// can't be looked up are shown as `unknown`.
+    #[arg(long, conflicts_with = "stats")]
+    outdated: bool,
+
+    /// Show the version aliases (e.g. `node@company-standard`) and the files that define them.
+    ///
+    /// Aliases are defined in the `aliases` section of the settings file, and may be overridden in
+    /// the hooks files of the current project.
+    #[arg(long, conflicts_with_all = ["current", "default", "stats", "outdated"])]
+    aliases: bool,
+
+    /// With `--stats`, show only versions that haven't been run within the given duration
+    /// (e.g. `12h`, `30d`, or `6w`).
+    #[arg(
+        long,
+        value_name = "duration",
+        requires = "stats",
+        value_parser = stats::parse_duration
+    )]
+    unused_since: Option<Duration>,
+
+    /// Save the JSON output of `volta list all` to a file, to compare with later using `--diff`.
+    ///
+    /// The snapshot is saved in the version of the JSON output given with `--json`, or the newest
+    /// version.
+    #[arg(
+        long,
+        value_name = "file",
+        conflicts_with_all = ["subcommand", "current", "default", "stats", "outdated", "aliases", "diff"]
+    )]
+    snapshot_to: Option<PathBuf>,
+
+    /// Compare your toolchain with a snapshot saved by `--snapshot-to`.
+    ///
+    /// Shows the runtimes, package managers, and packages that were added, removed, or changed,
+    /// ignoring the order they are listed in, their paths, and their end-of-life status. Exits
+    /// with 10 if there are any changes, so that they can be told apart from errors.
+    #[arg(
+        long,
+        value_name = "file",
+        conflicts_with_all = ["subcommand", "current", "default", "stats", "outdated", "aliases"]
+    )]
+    diff: Option<PathBuf>,
 }
 
 /// Which tool should we look up?
@@ -252,24 +407,183 @@ impl List {
         // have, that trumps our TTY-checking. Then, if the user has *not*
         // specified an option, we use `Human` mode for TTYs and `Plain` for
         // non-TTY contexts.
+        if self.json.is_some() {
+            return Format::Json;
+        }
+
         self.format.unwrap_or(if std::io::stdout().is_terminal() {
             Format::Human
         } else {
             Format::Plain
         })
     }
+
+    /// The version of the JSON output, when the output format is JSON
+    fn json_version(&self) -> u32 {
+        self.json.unwrap_or(json::JSON_VERSION)
+    }
+}
+
+impl List {
+    /// Shows the local usage counters for fetched Node and package manager versions
+    fn run_stats(self, session: &mut Session) -> Fallible<ExitCode> {
+        if !session.settings()?.usage_stats() {
+            warn!(
+                "Usage counters are disabled. To record them, set `usage.stats` to `true` in {}",
+                volta_home()?.settings_file().display()
+            );
+        }
+
+        let tools: &[stats::UsageTool] = match &self.subcommand {
+            None | Some(Subcommand::All) => &[stats::NODE, stats::NPM, stats::PNPM, stats::YARN],
+            Some(Subcommand::Node) => &[stats::NODE],
+            Some(Subcommand::Npm) => &[stats::NPM],
+            Some(Subcommand::Pnpm) => &[stats::PNPM],
+            Some(Subcommand::Yarn) => &[stats::YARN],
+            Some(Subcommand::PackageOrTool { name }) => {
+                warn!(
+                    "Usage counters are only recorded for Node and package managers, not '{}'",
+                    name
+                );
+                &[]
+            }
+        };
+
+        let mut rows = stats::rows(tools, &UsageStats::current()?)?;
+        if let Some(unused_since) = self.unused_since {
+            rows = stats::filter_unused(rows, unused_since, usage::now());
+        }
+
+        if let Some(string) = stats::format(&rows, self.output_format(), self.json_version())? {
+            println!("{}", string)
+        };
+
+        session.add_event_end(ActivityKind::List, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+
+    /// Shows the version aliases that apply in the current directory
+    fn run_aliases(self, session: &mut Session) -> Fallible<ExitCode> {
+        let format = self.output_format();
+        let json_version = self.json_version();
+        if matches!(format, Format::Json) && json_version < json::ALIASES_JSON_VERSION {
+            return Err(ErrorKind::AliasesJsonVersionError {
+                version: json_version,
+                required: json::ALIASES_JSON_VERSION,
+            }
+            .into());
+        }
+
+        let tool = match &self.subcommand {
+            None | Some(Subcommand::All) => None,
+            Some(Subcommand::Node) => Some("node"),
+            Some(Subcommand::Npm) => Some("npm"),
+            Some(Subcommand::Pnpm) => Some("pnpm"),
+            Some(Subcommand::Yarn) => Some("yarn"),
+            Some(Subcommand::PackageOrTool { name }) => Some(name.as_str()),
+        };
+
+        let aliases: Vec<_> = session
+            .aliases()?
+            .all()
+            .iter()
+            .filter(|alias| tool.map_or(true, |tool| alias.tool == tool))
+            .collect();
+
+        if let Some(string) = aliases::format(&aliases, format, json_version)? {
+            println!("{}", string)
+        };
+
+        session.add_event_end(ActivityKind::List, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+impl List {
+    /// Saves the JSON output of `volta list all` to a file
+    fn run_snapshot(self, file: PathBuf, session: &mut Session) -> Fallible<ExitCode> {
+        let toolchain = Toolchain::all(session.project()?, session.default_platform()?)?;
+        let mut contents = json::format(&toolchain, self.json_version())?;
+        contents.push('\n');
+
+        fs::write(&file, contents)
+            .with_context(|| ErrorKind::WriteListSnapshotError { file: file.clone() })?;
+        info!(
+            "{} saved a snapshot of your toolchain to {}",
+            success_prefix(),
+            file.display()
+        );
+
+        session.add_event_end(ActivityKind::List, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+
+    /// Compares the output of `volta list all` with a snapshot saved by `--snapshot-to`
+    fn run_diff(self, file: PathBuf, session: &mut Session) -> Fallible<ExitCode> {
+        let format = self.output_format();
+        let json_version = self.json_version();
+        if matches!(format, Format::Json) && json_version < json::DIFF_JSON_VERSION {
+            return Err(ErrorKind::DiffJsonVersionError {
+                version: json_version,
+                required: json::DIFF_JSON_VERSION,
+            }
+            .into());
+        }
+
+        let before = diff::Snapshot::read(&file)?;
+        let toolchain = Toolchain::all(session.project()?, session.default_platform()?)?;
+        // The toolchain is listed in the same version as the snapshot, so they have the same fields
+        let after =
+            diff::Snapshot::from_document(&json::to_value(&toolchain, before.json_version())?)
+                .expect("the JSON output of `volta list all` is a snapshot");
+
+        let changes = diff::compare(&before, &after);
+        if let Some(string) = diff::format(&changes, &file, format, json_version)? {
+            println!("{}", string)
+        };
+
+        let exit_code = if changes.is_empty() {
+            ExitCode::Success
+        } else {
+            ExitCode::DifferencesFound
+        };
+
+        session.add_event_end(ActivityKind::List, exit_code);
+        Ok(exit_code)
+    }
 }
 
 impl Command for List {
     fn run(self, session: &mut Session) -> Fallible<ExitCode> {
-        session.add_event_start(ActivityKind::List);
+        session.add_event_subcommand_start(ActivityKind::List);
+
+        if self.json_schema {
+            println!("{}", json::schema(json::JSON_VERSION).trim_end());
+            session.add_event_end(ActivityKind::List, ExitCode::Success);
+            return Ok(ExitCode::Success);
+        }
+        check_platform_lock(session)?;
+
+        if self.stats {
+            return self.run_stats(session);
+        }
+
+        if self.aliases {
+            return self.run_aliases(session);
+        }
+
+        if let Some(file) = self.snapshot_to.clone() {
+            return self.run_snapshot(file, session);
+        }
+
+        if let Some(file) = self.diff.clone() {
+            return self.run_diff(file, session);
+        }
 
         let project = session.project()?;
         let default_platform = session.default_platform()?;
-        let format = match self.output_format() {
-            Format::Human => human::format,
-            Format::Plain => plain::format,
-        };
+        let format = self.output_format();
+        let json_version = self.json_version();
 
         let filter = match (self.current, self.default) {
             (true, false) => Filter::Current,
@@ -291,7 +605,31 @@ impl Command for List {
             }
         };
 
-        if let Some(string) = format(&toolchain) {
+        if self.outdated {
+            if matches!(format, Format::Json) && json_version < json::OUTDATED_JSON_VERSION {
+                return Err(ErrorKind::OutdatedJsonVersionError {
+                    version: json_version,
+                    required: json::OUTDATED_JSON_VERSION,
+                }
+                .into());
+            }
+
+            let rows = outdated::rows(&toolchain, session);
+            if let Some(string) = outdated::format(&rows, format, json_version)? {
+                println!("{}", string)
+            };
+
+            session.add_event_end(ActivityKind::List, ExitCode::Success);
+            return Ok(ExitCode::Success);
+        }
+
+        let output = match format {
+            Format::Human => human::format(&toolchain),
+            Format::Plain => plain::format(&toolchain),
+            Format::Json => Some(json::format(&toolchain, json_version)?),
+        };
+
+        if let Some(string) = output {
             println!("{}", string)
         };
 
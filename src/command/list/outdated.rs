@@ -0,0 +1,279 @@
+//! Define the output of `volta list --outdated`, which compares the listed versions with the
+//! newest matching releases.
+
+use std::collections::HashMap;
+
+use super::{json, Format, ManagerVersion, Package, PackageManagerKind, Source, Toolchain};
+use log::debug;
+use node_semver::{Range, Version};
+use volta_core::error::Fallible;
+use volta_core::session::Session;
+use volta_core::style::tool_version;
+use volta_core::tool::{node, npm, package, pnpm, yarn};
+use volta_core::version::VersionSpec;
+
+static INDENTATION: &str = "    ";
+
+/// A listed tool version, along with the newest release that matches it
+pub(super) struct OutdatedRow {
+    pub name: String,
+    pub version: Version,
+    pub source: Source,
+    /// The newest matching release, if it could be determined
+    pub latest: Option<Version>,
+}
+
+impl OutdatedRow {
+    pub(super) fn is_outdated(&self) -> bool {
+        self.latest
+            .as_ref()
+            .is_some_and(|latest| latest > &self.version)
+    }
+}
+
+/// What a listed version is compared with
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Release {
+    /// The newest Node release in the same major version line
+    Node(u64),
+    /// The newest release of a package manager in the same major version line
+    PackageManager(PackageManagerKind, u64),
+    /// The version that a dist-tag of a package points to
+    Package { name: String, tag: String },
+}
+
+impl Release {
+    fn resolve(&self, session: &mut Session) -> Fallible<Option<Version>> {
+        match self {
+            Release::Node(major) => node::resolve(same_major(*major), session).map(Some),
+            Release::PackageManager(PackageManagerKind::Npm, major) => {
+                npm::resolve(same_major(*major), session)
+            }
+            Release::PackageManager(PackageManagerKind::Pnpm, major) => {
+                pnpm::resolve(same_major(*major), session).map(Some)
+            }
+            Release::PackageManager(PackageManagerKind::Yarn, major) => {
+                yarn::resolve(same_major(*major), session).map(Some)
+            }
+            Release::Package { name, tag } => package::tagged_version(name, tag, session).map(Some),
+        }
+    }
+}
+
+fn same_major(major: u64) -> VersionSpec {
+    let range = Range::parse(major.to_string()).expect("a major version is a valid range");
+    VersionSpec::Semver(range)
+}
+
+/// Looks up the newest matching release of each listed tool
+///
+/// Package managers that follow the npm bundled with Node and packages that are only listed as
+/// dependencies of the current project don't have a version to compare, so they are left out. If
+/// a release can't be looked up, for instance because the network is unavailable, its row has no
+/// latest version instead of failing the whole list.
+pub(super) fn rows(toolchain: &Toolchain, session: &mut Session) -> Vec<OutdatedRow> {
+    let (runtimes, package_managers, packages) = toolchain.items();
+    let mut items = Vec::new();
+
+    for runtime in runtimes {
+        items.push((
+            String::from("node"),
+            runtime.version.clone(),
+            runtime.source.clone(),
+            Release::Node(runtime.version.major),
+        ));
+    }
+
+    for manager in package_managers {
+        if let ManagerVersion::Exact(version) = &manager.version {
+            items.push((
+                manager.kind.to_string(),
+                version.clone(),
+                manager.source.clone(),
+                Release::PackageManager(manager.kind, version.major),
+            ));
+        }
+    }
+
+    for package in packages {
+        let (details, source) = match package {
+            Package::Default { details, .. } => (details, Source::Default),
+            Package::Fetched(details) => (details, Source::None),
+            Package::Project { .. } => continue,
+        };
+        items.push((
+            details.name.clone(),
+            details.version.clone(),
+            source,
+            Release::Package {
+                name: details.name.clone(),
+                tag: details.tag.clone().unwrap_or_else(|| "latest".into()),
+            },
+        ));
+    }
+
+    let mut latest = HashMap::new();
+    items
+        .into_iter()
+        .map(|(name, version, source, release)| {
+            let latest = latest
+                .entry(release.clone())
+                .or_insert_with(|| {
+                    release.resolve(session).unwrap_or_else(|error| {
+                        debug!(
+                            "Could not look up the newest release of {}: {}",
+                            name, error
+                        );
+                        None
+                    })
+                })
+                .clone();
+
+            OutdatedRow {
+                name,
+                version,
+                source,
+                latest,
+            }
+        })
+        .collect()
+}
+
+pub(super) fn format(
+    rows: &[OutdatedRow],
+    format: Format,
+    json_version: u32,
+) -> Fallible<Option<String>> {
+    match format {
+        Format::Human => Ok(Some(format_human(rows))),
+        Format::Plain => Ok(format_plain(rows)),
+        Format::Json => json::format_outdated(rows, json_version).map(Some),
+    }
+}
+
+fn latest_or_unknown(row: &OutdatedRow) -> String {
+    match &row.latest {
+        Some(latest) => latest.to_string(),
+        None => String::from("unknown"),
+    }
+}
+
+fn format_human(rows: &[OutdatedRow]) -> String {
+    if rows.is_empty() {
+        return String::from("⚡️ No matching tool versions found.");
+    }
+
+    let names: Vec<String> = rows
+        .iter()
+        .map(|row| format!("{}{}", tool_version(&row.name, &row.version), row.source))
+        .collect();
+    let width = names.iter().map(String::len).max().unwrap_or_default();
+
+    let lines: Vec<String> = rows
+        .iter()
+        .zip(names)
+        .map(|(row, name)| {
+            let line = format!(
+                "{}{:<width$}  latest: {}",
+                INDENTATION,
+                name,
+                latest_or_unknown(row),
+                width = width
+            );
+            if row.is_outdated() {
+                line + " (outdated)"
+            } else {
+                line
+            }
+        })
+        .collect();
+
+    format!(
+        "⚡️ Newest matching releases of your tools:\n\n{}",
+        lines.join("\n")
+    )
+}
+
+fn format_plain(rows: &[OutdatedRow]) -> Option<String> {
+    if rows.is_empty() {
+        return None;
+    }
+
+    let lines: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            format!(
+                "{}{} latest={} outdated={}",
+                tool_version(&row.name, &row.version),
+                row.source,
+                latest_or_unknown(row),
+                row.is_outdated()
+            )
+        })
+        .collect();
+
+    Some(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn row(
+        name: &str,
+        version: (u64, u64, u64),
+        source: Source,
+        latest: Option<&str>,
+    ) -> OutdatedRow {
+        OutdatedRow {
+            name: name.into(),
+            version: Version::from(version),
+            source,
+            latest: latest.map(|latest| Version::parse(latest).unwrap()),
+        }
+    }
+
+    fn sample_rows() -> Vec<OutdatedRow> {
+        vec![
+            row("node", (18, 12, 1), Source::Default, Some("18.19.0")),
+            row(
+                "yarn",
+                (1, 22, 19),
+                Source::Project(PathBuf::from("/home/user/app/package.json")),
+                None,
+            ),
+            row("cowsay", (1, 4, 0), Source::Default, Some("1.4.0")),
+        ]
+    }
+
+    #[test]
+    fn compares_with_latest() {
+        let rows = sample_rows();
+
+        assert!(rows[0].is_outdated());
+        assert!(!rows[1].is_outdated());
+        assert!(!rows[2].is_outdated());
+    }
+
+    #[test]
+    fn human_shows_latest_versions() {
+        let expected = "⚡️ Newest matching releases of your tools:
+
+    node@18.12.1 (default)                                latest: 18.19.0 (outdated)
+    yarn@1.22.19 (current @ /home/user/app/package.json)  latest: unknown
+    cowsay@1.4.0 (default)                                latest: 1.4.0";
+
+        assert_eq!(format_human(&sample_rows()), expected);
+    }
+
+    #[test]
+    fn plain_shows_latest_versions() {
+        let expected = "node@18.12.1 (default) latest=18.19.0 outdated=true
+yarn@1.22.19 (current @ /home/user/app/package.json) latest=unknown outdated=false
+cowsay@1.4.0 (default) latest=1.4.0 outdated=false";
+
+        assert_eq!(format_plain(&sample_rows()).unwrap(), expected);
+        assert_eq!(format_plain(&[]), None);
+    }
+}
@@ -1,10 +1,8 @@
 //! Define the "plain" format style for list commands.
 
-use node_semver::Version;
-
 use volta_core::style::tool_version;
 
-use super::{Node, Package, PackageManager, Source, Toolchain};
+use super::{Node, Package, PackageManager, Toolchain};
 
 pub(super) fn format(toolchain: &Toolchain) -> Option<String> {
     let (runtimes, package_managers, packages) = match toolchain {
@@ -64,7 +62,7 @@ fn describe_runtimes(runtimes: &[Node]) -> Option<String> {
         Some(
             runtimes
                 .iter()
-                .map(|runtime| display_node(&runtime.source, &runtime.version))
+                .map(display_node)
                 .collect::<Vec<String>>()
                 .join("\n"),
         )
@@ -107,8 +105,12 @@ fn describe_tool_set(name: &str, hosts: &[Package]) -> String {
         .join("\n")
 }
 
-fn display_node(source: &Source, version: &Version) -> String {
-    format!("runtime {}{}", tool_version("node", version), source)
+fn display_node(runtime: &Node) -> String {
+    format!(
+        "runtime {}{}",
+        tool_version("node", &runtime.version),
+        runtime.source
+    )
 }
 
 fn display_package_manager(package_manager: &PackageManager) -> String {
@@ -127,6 +129,13 @@ fn package_source(package: &Package) -> String {
     }
 }
 
+fn package_shims(package: &Package) -> &'static str {
+    match package {
+        Package::Default { shims: false, .. } => " (no shims)",
+        _ => "",
+    }
+}
+
 fn display_package(package: &Package) -> String {
     match package {
         Package::Default {
@@ -141,14 +150,15 @@ fn display_package(package: &Package) -> String {
             };
 
             format!(
-                "package {} /{}/ {} {}{}",
+                "package {} /{}/ {} {}{}{}",
                 tool_version(&details.name, &details.version),
                 tools,
                 tool_version("node", node),
                 // Should be updated when we support installing with custom package_managers,
                 // whether Yarn or non-built-in versions of npm
                 "npm@built-in",
-                package_source(package)
+                package_source(package),
+                package_shims(package)
             )
         }
         Package::Project { name, tools, .. } => {
@@ -206,7 +216,7 @@ mod tests {
     use node_semver::Version;
     use once_cell::sync::Lazy;
 
-    use crate::command::list::PackageDetails;
+    use crate::command::list::{PackageDetails, Source};
 
     static NODE_VERSION: Lazy<Version> = Lazy::new(|| Version::from((12, 4, 0)));
     static TYPESCRIPT_VERSION: Lazy<Version> = Lazy::new(|| Version::from((3, 4, 1)));
@@ -218,32 +228,51 @@ mod tests {
         use super::super::*;
         use super::*;
 
+        fn runtime(source: Source) -> Node {
+            Node {
+                source,
+                version: NODE_VERSION.clone(),
+                eol: false,
+                image: None,
+                bundled_npm: None,
+            }
+        }
+
         #[test]
         fn default() {
-            let source = Source::Default;
             assert_eq!(
-                display_node(&source, &NODE_VERSION).as_str(),
+                display_node(&runtime(Source::Default)).as_str(),
                 "runtime node@12.4.0 (default)"
             );
         }
 
         #[test]
         fn project() {
-            let source = Source::Project(PROJECT_PATH.clone());
             assert_eq!(
-                display_node(&source, &NODE_VERSION).as_str(),
+                display_node(&runtime(Source::Project(PROJECT_PATH.clone()))).as_str(),
                 "runtime node@12.4.0 (current @ /a/b/c)"
             );
         }
 
         #[test]
         fn installed_not_set() {
-            let source = Source::None;
             assert_eq!(
-                display_node(&source, &NODE_VERSION).as_str(),
+                display_node(&runtime(Source::None)).as_str(),
                 "runtime node@12.4.0"
             );
         }
+
+        #[test]
+        fn bundled_npm_is_not_shown() {
+            let node = Node {
+                bundled_npm: Some(NPM_VERSION.clone()),
+                ..runtime(Source::Default)
+            };
+            assert_eq!(
+                display_node(&node).as_str(),
+                "runtime node@12.4.0 (default)"
+            );
+        }
     }
 
     mod npm {
@@ -257,7 +286,7 @@ mod tests {
                 display_package_manager(&PackageManager {
                     kind: PackageManagerKind::Npm,
                     source: Source::Default,
-                    version: NPM_VERSION.clone(),
+                    version: ManagerVersion::Exact(NPM_VERSION.clone()),
                 })
                 .as_str(),
                 "package-manager npm@6.13.4 (default)"
@@ -270,20 +299,33 @@ mod tests {
                 display_package_manager(&PackageManager {
                     kind: PackageManagerKind::Npm,
                     source: Source::Project(PROJECT_PATH.clone()),
-                    version: NPM_VERSION.clone(),
+                    version: ManagerVersion::Exact(NPM_VERSION.clone()),
                 })
                 .as_str(),
                 "package-manager npm@6.13.4 (current @ /a/b/c)"
             );
         }
 
+        #[test]
+        fn bundled() {
+            assert_eq!(
+                display_package_manager(&PackageManager {
+                    kind: PackageManagerKind::Npm,
+                    source: Source::Project(PROJECT_PATH.clone()),
+                    version: ManagerVersion::Bundled,
+                })
+                .as_str(),
+                "package-manager npm@bundled (current @ /a/b/c)"
+            );
+        }
+
         #[test]
         fn installed_not_set() {
             assert_eq!(
                 display_package_manager(&PackageManager {
                     kind: PackageManagerKind::Npm,
                     source: Source::None,
-                    version: NPM_VERSION.clone(),
+                    version: ManagerVersion::Exact(NPM_VERSION.clone()),
                 })
                 .as_str(),
                 "package-manager npm@6.13.4"
@@ -302,7 +344,7 @@ mod tests {
                 display_package_manager(&PackageManager {
                     kind: PackageManagerKind::Yarn,
                     source: Source::Default,
-                    version: YARN_VERSION.clone(),
+                    version: ManagerVersion::Exact(YARN_VERSION.clone()),
                 })
                 .as_str(),
                 "package-manager yarn@1.16.0 (default)"
@@ -315,7 +357,7 @@ mod tests {
                 display_package_manager(&PackageManager {
                     kind: PackageManagerKind::Yarn,
                     source: Source::Project(PROJECT_PATH.clone()),
-                    version: YARN_VERSION.clone()
+                    version: ManagerVersion::Exact(YARN_VERSION.clone())
                 })
                 .as_str(),
                 "package-manager yarn@1.16.0 (current @ /a/b/c)"
@@ -328,7 +370,7 @@ mod tests {
                 display_package_manager(&PackageManager {
                     kind: PackageManagerKind::Yarn,
                     source: Source::None,
-                    version: YARN_VERSION.clone()
+                    version: ManagerVersion::Exact(YARN_VERSION.clone())
                 })
                 .as_str(),
                 "package-manager yarn@1.16.0"
@@ -347,9 +389,13 @@ mod tests {
                     details: PackageDetails {
                         name: "typescript".into(),
                         version: TYPESCRIPT_VERSION.clone(),
+                        tag: None,
+                        installed_from: None,
+                        image: None,
                     },
                     node: NODE_VERSION.clone(),
-                    tools: vec!["tsc".into(), "tsserver".into()]
+                    tools: vec!["tsc".into(), "tsserver".into()],
+                    shims: true
                 }])
                 .expect("Should always return a `String` if given a non-empty set")
                 .as_str(),
@@ -357,12 +403,55 @@ mod tests {
             );
         }
 
+        #[test]
+        fn default_installed_from_directory_is_not_shown() {
+            assert_eq!(
+                describe_packages(&[Package::Default {
+                    details: PackageDetails {
+                        name: "my-cli".into(),
+                        version: Version::from((1, 0, 0)),
+                        tag: None,
+                        installed_from: Some("/work/my-cli".into()),
+                        image: None,
+                    },
+                    node: NODE_VERSION.clone(),
+                    tools: vec!["my-cli".into()],
+                    shims: true
+                }])
+                .expect("Should always return a `String` if given a non-empty set")
+                .as_str(),
+                "package my-cli@1.0.0 / my-cli / node@12.4.0 npm@built-in (default)"
+            );
+        }
+
+        #[test]
+        fn default_without_shims() {
+            assert_eq!(
+                describe_packages(&[Package::Default {
+                    details: PackageDetails {
+                        name: "typescript".into(),
+                        version: TYPESCRIPT_VERSION.clone(),
+                        tag: None,
+                        installed_from: None,
+                        image: None,
+                    },
+                    node: NODE_VERSION.clone(),
+                    tools: vec!["tsc".into(), "tsserver".into()],
+                    shims: false
+                }])
+                .expect("Should always return a `String` if given a non-empty set")
+                .as_str(),
+                "package typescript@3.4.1 / tsc, tsserver / node@12.4.0 npm@built-in (default) (no shims)"
+            );
+        }
+
         #[test]
         fn single_project() {
             assert_eq!(
                 describe_packages(&[Package::Project {
                     name: "typescript".into(),
                     path: PROJECT_PATH.clone(),
+                    image: None,
                     tools: vec!["tsc".into(), "tsserver".into()]
                 }])
                 .expect("Should always return a `String` if given a non-empty set")
@@ -378,19 +467,27 @@ mod tests {
                     Package::Project {
                         name: "typescript".into(),
                         path: PROJECT_PATH.clone(),
+                        image: None,
                         tools: vec!["tsc".into(), "tsserver".into()]
                     },
                     Package::Default {
                         details: PackageDetails {
                             name: "ember-cli".into(),
                             version: Version::from((3, 10, 0)),
+                            tag: None,
+                            installed_from: None,
+                            image: None,
                         },
                         node: NODE_VERSION.clone(),
                         tools: vec!["ember".into()],
+                        shims: true,
                     },
                     Package::Fetched(PackageDetails {
                         name: "create-react-app".into(),
                         version: Version::from((1, 0, 0)),
+                        tag: None,
+                        installed_from: None,
+                        image: None,
                     })
                 ])
                 .expect("Should always return a `String` if given a non-empty set")
@@ -407,6 +504,9 @@ mod tests {
                 describe_packages(&[Package::Fetched(PackageDetails {
                     name: "typescript".into(),
                     version: TYPESCRIPT_VERSION.clone(),
+                    tag: None,
+                    installed_from: None,
+                    image: None,
                 })])
                 .expect("Should always return a `String` if given a non-empty set")
                 .as_str(),
@@ -428,9 +528,13 @@ mod tests {
                         details: PackageDetails {
                             name: "typescript".into(),
                             version: TYPESCRIPT_VERSION.clone(),
+                            tag: None,
+                            installed_from: None,
+                            image: None,
                         },
                         node: NODE_VERSION.clone(),
                         tools: vec!["tsc".into(), "tsserver".into()],
+                        shims: true,
                     }
                 )
                 .expect("should always return `Some` for `Default`")
@@ -447,6 +551,7 @@ mod tests {
                     &Package::Project {
                         name: "typescript".into(),
                         path: PROJECT_PATH.clone(),
+                        image: None,
                         tools: vec!["tsc".into(), "tsserver".into()],
                     }
                 )
@@ -463,7 +568,10 @@ mod tests {
                     "tsc",
                     &Package::Fetched(PackageDetails {
                         name: "typescript".into(),
-                        version: TYPESCRIPT_VERSION.clone()
+                        version: TYPESCRIPT_VERSION.clone(),
+                        tag: None,
+                        installed_from: None,
+                        image: None,
                     })
                 ),
                 None
@@ -474,7 +582,9 @@ mod tests {
     mod toolchain {
         use super::super::*;
         use super::*;
-        use crate::command::list::{Node, PackageManager, PackageManagerKind, Toolchain};
+        use crate::command::list::{
+            ManagerVersion, Node, PackageManager, PackageManagerKind, Toolchain,
+        };
 
         #[test]
         fn full() {
@@ -483,33 +593,39 @@ mod tests {
                     runtimes: vec![
                         Node {
                             source: Source::Default,
-                            version: NODE_VERSION.clone()
+                            version: NODE_VERSION.clone(),
+                            eol: false,
+                            image: None,
+                            bundled_npm: None,
                         },
                         Node {
                             source: Source::None,
-                            version: Version::from((8, 2, 4))
+                            version: Version::from((8, 2, 4)),
+                            eol: false,
+                            image: None,
+                            bundled_npm: None,
                         }
                     ],
                     package_managers: vec![
                         PackageManager {
                             kind: PackageManagerKind::Npm,
                             source: Source::Project(PROJECT_PATH.clone()),
-                            version: NPM_VERSION.clone(),
+                            version: ManagerVersion::Exact(NPM_VERSION.clone()),
                         },
                         PackageManager {
                             kind: PackageManagerKind::Npm,
                             source: Source::Default,
-                            version: Version::from((5, 10, 0))
+                            version: ManagerVersion::Exact(Version::from((5, 10, 0)))
                         },
                         PackageManager {
                             kind: PackageManagerKind::Yarn,
                             source: Source::Project(PROJECT_PATH.clone()),
-                            version: YARN_VERSION.clone()
+                            version: ManagerVersion::Exact(YARN_VERSION.clone())
                         },
                         PackageManager {
                             kind: PackageManagerKind::Yarn,
                             source: Source::Default,
-                            version: Version::from((1, 17, 0))
+                            version: ManagerVersion::Exact(Version::from((1, 17, 0)))
                         }
                     ],
                     packages: vec![
@@ -517,22 +633,31 @@ mod tests {
                             details: PackageDetails {
                                 name: "ember-cli".into(),
                                 version: Version::from((3, 10, 2)),
+                                tag: None,
+                                installed_from: None,
+                                image: None,
                             },
                             node: NODE_VERSION.clone(),
-                            tools: vec!["ember".into()]
+                            tools: vec!["ember".into()],
+                            shims: true
                         },
                         Package::Project {
                             name: "ember-cli".into(),
                             path: PROJECT_PATH.clone(),
+                            image: None,
                             tools: vec!["ember".into()]
                         },
                         Package::Default {
                             details: PackageDetails {
                                 name: "typescript".into(),
                                 version: TYPESCRIPT_VERSION.clone(),
+                                tag: None,
+                                installed_from: None,
+                                image: None,
                             },
                             node: NODE_VERSION.clone(),
-                            tools: vec!["tsc".into(), "tsserver".into()]
+                            tools: vec!["tsc".into(), "tsserver".into()],
+                            shims: true
                         }
                     ]
                 })
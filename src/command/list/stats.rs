@@ -0,0 +1,239 @@
+//! Define the output of `volta list --stats`, which shows the local usage counters.
+
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use super::{json, Format};
+use node_semver::Version;
+use volta_core::error::Fallible;
+use volta_core::inventory::{node_versions, npm_versions, pnpm_versions, yarn_versions};
+use volta_core::style::tool_version;
+use volta_core::usage::{UsageRecord, UsageStats};
+
+static INDENTATION: &str = "    ";
+
+/// A tool whose usage is recorded, along with the function that lists its fetched versions
+pub(super) type UsageTool = (&'static str, fn() -> Fallible<BTreeSet<Version>>);
+
+pub(super) const NODE: UsageTool = ("node", node_versions);
+pub(super) const NPM: UsageTool = ("npm", npm_versions);
+pub(super) const PNPM: UsageTool = ("pnpm", pnpm_versions);
+pub(super) const YARN: UsageTool = ("yarn", yarn_versions);
+
+/// The usage of a single fetched tool version
+pub(super) struct UsageRow {
+    pub tool: &'static str,
+    pub version: Version,
+    pub record: Option<UsageRecord>,
+}
+
+impl UsageRow {
+    /// Whether the version has not been used since the given time (in seconds since the epoch)
+    fn unused_since(&self, cutoff: i64) -> bool {
+        match &self.record {
+            Some(record) => record.last_used < cutoff,
+            None => true,
+        }
+    }
+}
+
+/// Collects the usage of every fetched version of the given tools
+pub(super) fn rows(tools: &[UsageTool], stats: &UsageStats) -> Fallible<Vec<UsageRow>> {
+    let mut rows = Vec::new();
+
+    for &(tool, versions) in tools {
+        rows.extend(versions()?.into_iter().rev().map(|version| UsageRow {
+            tool,
+            record: stats.get(tool, &version).copied(),
+            version,
+        }));
+    }
+
+    Ok(rows)
+}
+
+/// Narrows the rows to the versions that have not been used within the given duration
+pub(super) fn filter_unused(
+    rows: Vec<UsageRow>,
+    unused_since: Duration,
+    now: i64,
+) -> Vec<UsageRow> {
+    let cutoff = now.saturating_sub(unused_since.as_secs() as i64);
+    rows.into_iter()
+        .filter(|row| row.unused_since(cutoff))
+        .collect()
+}
+
+pub(super) fn format(
+    rows: &[UsageRow],
+    format: Format,
+    json_version: u32,
+) -> Fallible<Option<String>> {
+    match format {
+        Format::Human => Ok(Some(format_human(rows))),
+        Format::Plain => Ok(format_plain(rows)),
+        Format::Json => json::format_usage(rows, json_version).map(Some),
+    }
+}
+
+fn format_human(rows: &[UsageRow]) -> String {
+    if rows.is_empty() {
+        return String::from("⚡️ No matching tool versions found.");
+    }
+
+    let names: Vec<String> = rows
+        .iter()
+        .map(|row| tool_version(row.tool, &row.version))
+        .collect();
+    let width = names.iter().map(String::len).max().unwrap_or_default();
+
+    let lines: Vec<String> = rows
+        .iter()
+        .zip(names)
+        .map(|(row, name)| match &row.record {
+            Some(record) => format!(
+                "{}{:<width$}  {} {}, last used {}",
+                INDENTATION,
+                name,
+                record.count,
+                if record.count == 1 { "run" } else { "runs" },
+                record.last_used_date(),
+                width = width
+            ),
+            None => format!("{}{:<width$}  never used", INDENTATION, name, width = width),
+        })
+        .collect();
+
+    format!(
+        "⚡️ Tool usage recorded on this machine:\n\n{}",
+        lines.join("\n")
+    )
+}
+
+fn format_plain(rows: &[UsageRow]) -> Option<String> {
+    if rows.is_empty() {
+        return None;
+    }
+
+    let lines: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            let name = tool_version(row.tool, &row.version);
+            match &row.record {
+                Some(record) => format!(
+                    "{} runs={} last-used={}",
+                    name,
+                    record.count,
+                    record.last_used_date()
+                ),
+                None => format!("{} runs=0 last-used=never", name),
+            }
+        })
+        .collect();
+
+    Some(lines.join("\n"))
+}
+
+/// Parses a duration such as `12h`, `30d`, or `6w`
+pub(super) fn parse_duration(value: &str) -> Result<Duration, String> {
+    let error = || {
+        format!(
+            "invalid duration '{}', expected a number followed by 'h', 'd', or 'w' (e.g. '30d')",
+            value
+        )
+    };
+
+    let value = value.trim();
+    let Some(unit) = value.chars().last() else {
+        return Err(error());
+    };
+    let amount: u64 = value[..value.len() - unit.len_utf8()]
+        .parse()
+        .map_err(|_| error())?;
+    let hours = match unit {
+        'h' => amount,
+        'd' => amount * 24,
+        'w' => amount * 24 * 7,
+        _ => return Err(error()),
+    };
+
+    Ok(Duration::from_secs(hours * 60 * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2023-11-14T22:13:20Z
+    const NOW: i64 = 1_700_000_000;
+    const DAY: i64 = 24 * 60 * 60;
+
+    fn row(tool: &'static str, version: (u64, u64, u64), record: Option<(u64, i64)>) -> UsageRow {
+        UsageRow {
+            tool,
+            version: Version::from(version),
+            record: record.map(|(count, last_used)| UsageRecord { count, last_used }),
+        }
+    }
+
+    fn sample_rows() -> Vec<UsageRow> {
+        vec![
+            row("node", (18, 12, 1), Some((42, NOW))),
+            row("node", (16, 0, 0), Some((1, NOW - 90 * DAY))),
+            row("yarn", (1, 22, 19), None),
+        ]
+    }
+
+    #[test]
+    fn human_shows_counts_and_dates() {
+        let expected = "⚡️ Tool usage recorded on this machine:
+
+    node@18.12.1  42 runs, last used 2023-11-14
+    node@16.0.0   1 run, last used 2023-08-16
+    yarn@1.22.19  never used";
+
+        assert_eq!(format_human(&sample_rows()), expected);
+    }
+
+    #[test]
+    fn human_with_no_rows() {
+        assert_eq!(format_human(&[]), "⚡️ No matching tool versions found.");
+    }
+
+    #[test]
+    fn plain_shows_counts_and_dates() {
+        let expected = "node@18.12.1 runs=42 last-used=2023-11-14
+node@16.0.0 runs=1 last-used=2023-08-16
+yarn@1.22.19 runs=0 last-used=never";
+
+        assert_eq!(format_plain(&sample_rows()).unwrap(), expected);
+        assert_eq!(format_plain(&[]), None);
+    }
+
+    #[test]
+    fn filter_unused_keeps_stale_and_never_used_versions() {
+        let rows = filter_unused(sample_rows(), Duration::from_secs(30 * DAY as u64), NOW);
+        let names: Vec<String> = rows
+            .iter()
+            .map(|row| tool_version(row.tool, &row.version))
+            .collect();
+
+        assert_eq!(names, ["node@16.0.0", "yarn@1.22.19"]);
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("12h"), Ok(Duration::from_secs(12 * 60 * 60)));
+        assert_eq!(
+            parse_duration("30d"),
+            Ok(Duration::from_secs(30 * DAY as u64))
+        );
+        assert_eq!(
+            parse_duration("2w"),
+            Ok(Duration::from_secs(14 * DAY as u64))
+        );
+        assert!(parse_duration("30").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("3m").is_err());
+    }
+}
@@ -1,12 +1,14 @@
-use super::{Filter, Node, Package, PackageManager, Source};
+use super::{Filter, ManagerVersion, Node, Package, PackageManager, Source};
 use crate::command::list::PackageManagerKind;
+use log::LevelFilter;
 use node_semver::Version;
 use volta_core::error::Fallible;
 use volta_core::inventory::{
-    node_versions, npm_versions, package_configs, pnpm_versions, yarn_versions,
+    node_bundled_npm, node_versions, npm_versions, package_configs, pnpm_versions, yarn_versions,
 };
 use volta_core::platform::PlatformSpec;
 use volta_core::project::Project;
+use volta_core::tool::node::ReleaseSchedule;
 use volta_core::tool::PackageConfig;
 
 pub(super) enum Toolchain {
@@ -121,35 +123,68 @@ fn tool_source(name: &str, project: Option<&Project>) -> Fallible<Source> {
 }
 
 impl Toolchain {
+    /// The runtimes, package managers, and packages that are listed
+    pub(super) fn items(&self) -> (&[Node], &[PackageManager], &[Package]) {
+        match self {
+            Toolchain::Node(runtimes) => (runtimes, &[], &[]),
+            Toolchain::PackageManagers { managers, .. } => (&[], managers, &[]),
+            Toolchain::Packages(packages) => (&[], &[], packages),
+            Toolchain::Tool { host_packages, .. } => (&[], &[], host_packages),
+            Toolchain::Active {
+                runtime,
+                package_managers,
+                packages,
+            } => (
+                runtime.as_deref().map(std::slice::from_ref).unwrap_or(&[]),
+                package_managers,
+                packages,
+            ),
+            Toolchain::All {
+                runtimes,
+                package_managers,
+                packages,
+            } => (runtimes, package_managers, packages),
+        }
+    }
+
     pub(super) fn active(
         project: Option<&Project>,
         default_platform: Option<&PlatformSpec>,
     ) -> Fallible<Toolchain> {
+        let schedule = ReleaseSchedule::load();
         let runtime = Lookup::Runtime
             .active_tool(project, default_platform)
-            .map(|(source, version)| Box::new(Node { source, version }));
+            .map(|(source, version)| Box::new(Node::new(source, version, &schedule)));
 
-        let package_managers =
-            Lookup::Npm
+        let npm = match project {
+            Some(project) if project.pins_bundled_npm() => Some(PackageManager {
+                kind: PackageManagerKind::Npm,
+                source: Source::Project(project.manifest_file().to_owned()),
+                version: ManagerVersion::Bundled,
+            }),
+            _ => Lookup::Npm
                 .active_tool(project, default_platform)
                 .map(|(source, version)| PackageManager {
                     kind: PackageManagerKind::Npm,
                     source,
-                    version,
-                })
-                .into_iter()
+                    version: ManagerVersion::Exact(version),
+                }),
+        };
+
+        let package_managers =
+            npm.into_iter()
                 .chain(Lookup::Pnpm.active_tool(project, default_platform).map(
                     |(source, version)| PackageManager {
                         kind: PackageManagerKind::Pnpm,
                         source,
-                        version,
+                        version: ManagerVersion::Exact(version),
                     },
                 ))
                 .chain(Lookup::Yarn.active_tool(project, default_platform).map(
                     |(source, version)| PackageManager {
                         kind: PackageManagerKind::Yarn,
                         source,
-                        version,
+                        version: ManagerVersion::Exact(version),
                     },
                 ))
                 .collect();
@@ -167,11 +202,12 @@ impl Toolchain {
         project: Option<&Project>,
         default_platform: Option<&PlatformSpec>,
     ) -> Fallible<Toolchain> {
+        let schedule = ReleaseSchedule::load();
         let runtimes = node_versions()?
             .iter()
-            .map(|version| Node {
-                source: Lookup::Runtime.version_source(project, default_platform, version),
-                version: version.clone(),
+            .map(|version| {
+                let source = Lookup::Runtime.version_source(project, default_platform, version);
+                Node::new(source, version.clone(), &schedule)
             })
             .collect();
 
@@ -180,17 +216,17 @@ impl Toolchain {
             .map(|version| PackageManager {
                 kind: PackageManagerKind::Npm,
                 source: Lookup::Npm.version_source(project, default_platform, version),
-                version: version.clone(),
+                version: ManagerVersion::Exact(version.clone()),
             })
             .chain(pnpm_versions()?.iter().map(|version| PackageManager {
                 kind: PackageManagerKind::Pnpm,
                 source: Lookup::Pnpm.version_source(project, default_platform, version),
-                version: version.clone(),
+                version: ManagerVersion::Exact(version.clone()),
             }))
             .chain(yarn_versions()?.iter().map(|version| PackageManager {
                 kind: PackageManagerKind::Yarn,
                 source: Lookup::Yarn.version_source(project, default_platform, version),
-                version: version.clone(),
+                version: ManagerVersion::Exact(version.clone()),
             }))
             .collect();
 
@@ -208,13 +244,19 @@ impl Toolchain {
         default_platform: Option<&PlatformSpec>,
         filter: &Filter,
     ) -> Fallible<Toolchain> {
+        let schedule = ReleaseSchedule::load();
+        // The recorded bundled npm versions are only shown by `volta list node --verbose`
+        let verbose = log::max_level() >= LevelFilter::Debug;
         let runtimes = node_versions()?
             .iter()
             .filter_map(|version| {
                 let source = Lookup::Runtime.version_source(project, default_platform, version);
                 if source.allowed_with(filter) {
-                    let version = version.clone();
-                    Some(Node { source, version })
+                    let mut runtime = Node::new(source, version.clone(), &schedule);
+                    if verbose {
+                        runtime.bundled_npm = node_bundled_npm(version).unwrap_or(None);
+                    }
+                    Some(runtime)
                 } else {
                     None
                 }
@@ -237,7 +279,7 @@ impl Toolchain {
                     Some(PackageManager {
                         kind: PackageManagerKind::Npm,
                         source,
-                        version: version.clone(),
+                        version: ManagerVersion::Exact(version.clone()),
                     })
                 } else {
                     None
@@ -264,7 +306,7 @@ impl Toolchain {
                     Some(PackageManager {
                         kind: PackageManagerKind::Pnpm,
                         source,
-                        version: version.clone(),
+                        version: ManagerVersion::Exact(version.clone()),
                     })
                 } else {
                     None
@@ -291,7 +333,7 @@ impl Toolchain {
                     Some(PackageManager {
                         kind: PackageManagerKind::Yarn,
                         source,
-                        version: version.clone(),
+                        version: ManagerVersion::Exact(version.clone()),
                     })
                 } else {
                     None
@@ -0,0 +1,85 @@
+use log::info;
+use volta_core::error::{ErrorKind, ExitCode, Fallible};
+use volta_core::platform::PlatformSpec;
+use volta_core::project::{PlatformLockStatus, Project};
+use volta_core::session::{ActivityKind, Session};
+use volta_core::style::success_prefix;
+
+use crate::command::Command;
+
+#[derive(clap::Args)]
+pub(crate) struct Lock {
+    /// Updates an existing lock file to match the project's current `volta` settings.
+    #[arg(long)]
+    update: bool,
+}
+
+impl Command for Lock {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_subcommand_start(ActivityKind::Lock);
+
+        let project = session.project_mut()?.ok_or(ErrorKind::NotInPackage)?;
+        let file = project.platform_lock_file();
+
+        match (project.platform_lock_status()?, self.update) {
+            (PlatformLockStatus::UpToDate, _) => {
+                info!("{} {} is up to date", success_prefix(), file.display());
+            }
+            (PlatformLockStatus::Outdated, false) => {
+                return Err(ErrorKind::PlatformLockOutdated {
+                    file,
+                    manifest: project.manifest_file().to_owned(),
+                }
+                .into());
+            }
+            (PlatformLockStatus::Missing, _) | (PlatformLockStatus::Outdated, true) => {
+                write_lock(project)?;
+            }
+        }
+
+        session.add_event_end(ActivityKind::Lock, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+/// Checks the `volta.lock` file of the current project, if there is one, against its `volta`
+/// settings, for the commands that use the project's platform
+pub(crate) fn check_platform_lock(session: &Session) -> Fallible<()> {
+    match session.project()? {
+        Some(project) => project.check_platform_lock(),
+        None => Ok(()),
+    }
+}
+
+/// Writes the project's `volta.lock` file from its `volta` settings, as `volta pin --lock` does
+pub(crate) fn write_lock(project: &mut Project) -> Fallible<()> {
+    project.write_platform_lock()?;
+
+    if let Some(platform) = project.platform() {
+        info!(
+            "{} locked {} in {}",
+            success_prefix(),
+            describe(platform, project.pins_bundled_npm()),
+            project.platform_lock_file().display()
+        );
+    }
+
+    Ok(())
+}
+
+fn describe(platform: &PlatformSpec, bundled_npm: bool) -> String {
+    let mut tools = vec![format!("node@{}", platform.node)];
+    match &platform.npm {
+        Some(npm) => tools.push(format!("npm@{}", npm)),
+        None if bundled_npm => tools.push("npm@bundled".into()),
+        None => {}
+    }
+    if let Some(pnpm) = &platform.pnpm {
+        tools.push(format!("pnpm@{}", pnpm));
+    }
+    if let Some(yarn) = &platform.yarn {
+        tools.push(format!("yarn@{}", yarn));
+    }
+
+    tools.join(", ")
+}
@@ -0,0 +1,49 @@
+use log::info;
+use volta_core::error::{ExitCode, Fallible};
+use volta_core::session::{ActivityKind, Session};
+use volta_core::style::success_prefix;
+use volta_migrate::{plan_migration, run_migration, MigrationPlan};
+
+use crate::command::Command;
+
+#[derive(clap::Args)]
+pub(crate) struct Migrate {
+    /// Prints the migration steps that would be applied, without changing anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl Command for Migrate {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_subcommand_start(ActivityKind::Migrate);
+
+        if self.dry_run {
+            println!("{}", format_plan(&plan_migration()?));
+        } else {
+            run_migration()?;
+            info!("{} Your Volta directory is up to date", success_prefix());
+        }
+
+        session.add_event_end(ActivityKind::Migrate, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+fn format_plan(plan: &MigrationPlan) -> String {
+    if plan.is_up_to_date() {
+        return format!(
+            "Your Volta directory {} is up to date (layout {}).",
+            plan.home.display(),
+            plan.layout
+        );
+    }
+
+    let steps: Vec<String> = plan.steps.iter().map(ToString::to_string).collect();
+    format!(
+        "Your Volta directory {} has layout {}. Migrating it would apply {} step(s):\n\n{}\n\nNothing was changed.",
+        plan.home.display(),
+        plan.layout,
+        plan.steps.len(),
+        steps.join("\n\n")
+    )
+}
@@ -1,26 +1,58 @@
+mod bulk;
+pub(crate) mod complete;
 pub(crate) mod completions;
+pub(crate) mod config;
+pub(crate) mod doctor;
+pub(crate) mod env;
+pub(crate) mod exec;
 pub(crate) mod fetch;
+pub(crate) mod hook;
+pub(crate) mod import;
 pub(crate) mod install;
+pub(crate) mod layout;
 pub(crate) mod list;
+pub(crate) mod lock;
+pub(crate) mod migrate;
 pub(crate) mod pin;
+mod platform_json;
+pub(crate) mod profile;
 pub(crate) mod run;
 pub(crate) mod setup;
 pub(crate) mod uninstall;
 pub(crate) mod r#use;
+pub(crate) mod verify_binaries;
+pub(crate) mod volta_self;
 pub(crate) mod which;
 
 pub(crate) use self::which::Which;
+pub(crate) use complete::Complete;
 pub(crate) use completions::Completions;
+pub(crate) use config::Config;
+pub(crate) use doctor::Doctor;
+pub(crate) use env::Env;
+pub(crate) use exec::Exec;
 pub(crate) use fetch::Fetch;
+pub(crate) use hook::Hook;
+pub(crate) use import::Import;
 pub(crate) use install::Install;
+pub(crate) use layout::Layout;
 pub(crate) use list::List;
+pub(crate) use lock::Lock;
+pub(crate) use migrate::Migrate;
 pub(crate) use pin::Pin;
+pub(crate) use profile::Profile;
 pub(crate) use r#use::Use;
 pub(crate) use run::Run;
 pub(crate) use setup::Setup;
 pub(crate) use uninstall::Uninstall;
+pub(crate) use verify_binaries::VerifyBinaries;
+pub(crate) use volta_self::VoltaSelf;
 
-use volta_core::error::{ExitCode, Fallible};
+use std::io::{self, BufRead, IsTerminal as _, Write};
+
+use serde::Serialize;
+use volta_core::change::Change;
+use volta_core::error::{Context, ErrorKind, ExitCode, Fallible};
 use volta_core::session::Session;
 
 /// A Volta command.
@@ -30,3 +62,42 @@ pub(crate) trait Command: Sized {
     /// should return `e.exit_code()`.
     fn run(self, session: &mut Session) -> Fallible<ExitCode>;
 }
+
+/// Prints one line for each change to the Volta directory, for `--porcelain`
+pub(crate) fn print_porcelain<C>(changes: C)
+where
+    C: IntoIterator<Item = Change>,
+{
+    for change in changes {
+        println!("{}", change);
+    }
+}
+
+/// Serializes the JSON output of a command
+///
+/// This fails if the output includes a path that isn't valid Unicode.
+pub(crate) fn to_json<T: Serialize>(document: &T) -> Fallible<String> {
+    serde_json::to_string_pretty(document).with_context(|| ErrorKind::JsonOutputError)
+}
+
+/// Determines whether a change is confirmed, either with `--yes` or, when running interactively,
+/// by answering a prompt
+pub(crate) fn confirmed(yes: bool) -> bool {
+    // Volta must never consume stdin that was meant for the tool it runs (e.g. a script piped to
+    // `node`), so the prompt is only shown when stdin is a terminal
+    let interactive = io::stdin().is_terminal() && io::stderr().is_terminal();
+    yes || (interactive && prompt_to_continue())
+}
+
+/// Asks for confirmation on stdin, which must only happen when stdin is a terminal
+fn prompt_to_continue() -> bool {
+    eprint!("Continue? [y/N] ");
+    let _ = io::stderr().flush();
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+
+    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
@@ -1,25 +1,277 @@
-use volta_core::error::{ExitCode, Fallible};
+use log::{debug, info, warn};
+use volta_core::error::{ErrorKind, ExitCode, Fallible};
+use volta_core::project::{FoundVersion, PlatformLockStatus};
 use volta_core::session::{ActivityKind, Session};
+use volta_core::style::{note_prefix, success_prefix};
 use volta_core::tool::Spec;
 
-use crate::command::Command;
+use crate::command::lock::write_lock;
+use crate::command::{confirmed, Command};
 
 #[derive(clap::Args)]
 pub(crate) struct Pin {
     /// Tools to pin, like `node@lts` or `yarn@^1.14`.
-    #[arg(value_name = "tool[@version]", required = true)]
+    #[arg(
+        value_name = "tool[@version]",
+        required_unless_present_any = ["from_lockfile", "lock"]
+    )]
     tools: Vec<String>,
+
+    /// Pins the versions that the project's files record: Node (and npm) from `.nvmrc`,
+    /// `.node-version`, or `engines`, and the newest Yarn or pnpm version that can use its lockfile.
+    #[arg(long)]
+    from_lockfile: bool,
+
+    /// Also records the project's resulting platform in its `volta.lock` file.
+    #[arg(long)]
+    lock: bool,
+
+    /// Pins without asking for confirmation when other workspace members are affected, or when
+    /// the project's files disagree about a version with `--from-lockfile`.
+    #[arg(long, short = 'y')]
+    yes: bool,
+
+    /// Skips checking for workspace members that extend the project manifest.
+    #[arg(long)]
+    no_verify_members: bool,
+
+    /// Ignores the version policies that don't forbid overriding them.
+    #[arg(long)]
+    override_policy: bool,
+
+    /// Removes the pins of the given tools, like `npm` or `yarn`, from the project.
+    #[arg(long, conflicts_with_all = ["from_lockfile", "lock"])]
+    remove: bool,
 }
 
 impl Command for Pin {
     fn run(self, session: &mut Session) -> Fallible<ExitCode> {
-        session.add_event_start(ActivityKind::Pin);
+        session.add_event_subcommand_start(ActivityKind::Pin);
+        if self.override_policy {
+            session.override_policies();
+        }
+
+        let mut tools = Spec::from_strings(&self.tools, "pin")?;
+        if self.remove {
+            if !self.no_verify_members {
+                confirm_affected_members(session, self.yes)?;
+            }
+            remove_pins(session, tools)?;
+
+            session.add_event_end(ActivityKind::Pin, ExitCode::Success);
+            return Ok(ExitCode::Success);
+        }
+
+        if self.from_lockfile {
+            tools = infer_from_lockfile(session, tools, self.yes)?;
+        }
+
+        if !self.no_verify_members {
+            confirm_affected_members(session, self.yes)?;
+        }
 
-        for tool in Spec::from_strings(&self.tools, "pin")? {
-            tool.resolve(session)?.pin(session)?;
+        // Every tool is resolved before anything is pinned, so that a version that can't be found
+        // fails the whole command rather than leaving the manifest partially updated
+        let resolved = tools
+            .into_iter()
+            .map(|tool| tool.resolve(session))
+            .collect::<Fallible<Vec<_>>>()?;
+
+        let mut pinned = Vec::with_capacity(resolved.len());
+        for tool in resolved {
+            pinned.push(tool.pin(session)?);
+        }
+
+        // Pinning requires a project, so it's present once a tool has been pinned
+        if let Some(project) = session.project_mut()? {
+            project.save_pins()?;
+        }
+
+        if !pinned.is_empty() {
+            info!(
+                "{} pinned {} in package.json",
+                success_prefix(),
+                pinned.join(", ")
+            );
+        }
+
+        if self.lock {
+            let project = session.project_mut()?.ok_or(ErrorKind::NotInPackage)?;
+            write_lock(project)?;
+        } else if let Some(project) = session.project()? {
+            if project.platform_lock_status()? == PlatformLockStatus::Outdated {
+                warn!(
+                    "{} still records the previous platform, which the project keeps using.
+Use `volta lock --update` to update it.",
+                    project.platform_lock_file().display()
+                );
+            }
         }
 
         session.add_event_end(ActivityKind::Pin, ExitCode::Success);
         Ok(ExitCode::Success)
     }
 }
+
+/// Removes the pins of the tools from the project manifest, all at once
+///
+/// Node can't be removed, since the other tools can only be pinned along with it.
+fn remove_pins(session: &mut Session, tools: Vec<Spec>) -> Fallible<()> {
+    let project = session.project_mut()?.ok_or(ErrorKind::NotInPackage)?;
+
+    let mut removed = Vec::with_capacity(tools.len());
+    for tool in &tools {
+        match tool {
+            Spec::Node(_) => return Err(ErrorKind::CannotRemoveNodePin.into()),
+            Spec::Npm(_) => project.unpin_npm()?,
+            Spec::Pnpm(_) => project.pin_pnpm(None)?,
+            Spec::Yarn(_) => project.pin_yarn(None)?,
+            Spec::Package(..) | Spec::PackageSource(_) => {
+                return Err(ErrorKind::CannotPinPackage {
+                    package: tool.name().to_string(),
+                }
+                .into())
+            }
+        }
+        removed.push(tool.name());
+    }
+
+    project.save_pins()?;
+    info!(
+        "{} unpinned {} in package.json",
+        success_prefix(),
+        removed.join(", ")
+    );
+
+    Ok(())
+}
+
+/// Adds the tools to pin that the files in the project root record to the ones given on the
+/// command line
+///
+/// Node and npm are inferred from the version files and `engines`, and go first since the other
+/// tools can only be pinned along with Node. The package manager is inferred from the lockfile,
+/// which is only required when there is nothing else to pin. A tool given on the command line
+/// takes precedence over the inferred one.
+fn infer_from_lockfile(session: &mut Session, given: Vec<Spec>, yes: bool) -> Fallible<Vec<Spec>> {
+    let project = session.project()?.ok_or(ErrorKind::NotInPackage)?;
+    let given_names: Vec<String> = given.iter().map(|tool| tool.name().to_string()).collect();
+    let is_given = |spec: &Spec| given_names.iter().any(|name| *name == spec.name());
+
+    let mut tools = Vec::new();
+    for inferred in project.infer_tools() {
+        let spec = inferred.spec()?;
+        if is_given(&spec) {
+            continue;
+        }
+
+        let found: Vec<String> = inferred
+            .found
+            .iter()
+            .map(|found| format!("{} in {}", found.requirement, source(found)))
+            .collect();
+
+        if inferred.is_ambiguous() {
+            warn!(
+                "The project records different versions of {}:\n    {}",
+                inferred.tool,
+                found.join("\n    ")
+            );
+            if !confirmed(yes) {
+                return Err(ErrorKind::PinInferredNotConfirmed {
+                    tool: inferred.tool.into(),
+                    requirement: inferred.found[0].requirement.clone(),
+                    found,
+                }
+                .into());
+            }
+        }
+
+        info!(
+            "{} inferred {}@{} from {}",
+            note_prefix(),
+            spec.name(),
+            inferred.found[0].requirement,
+            source(&inferred.found[0])
+        );
+        tools.push(spec);
+    }
+    tools.extend(given);
+
+    match project.infer_package_manager() {
+        Ok(inferred) => {
+            let spec = inferred.spec()?;
+            info!(
+                "{} inferred {}@{} from {}, which has lockfile version {}",
+                note_prefix(),
+                spec.name(),
+                inferred.range,
+                inferred.lockfile.display(),
+                inferred.lockfile_version
+            );
+            if !is_given(&spec) {
+                tools.push(spec);
+            }
+        }
+        Err(error)
+            if !tools.is_empty()
+                && matches!(
+                    error.kind(),
+                    ErrorKind::NoLockfile { .. } | ErrorKind::LockfileNotInferred { .. }
+                ) =>
+        {
+            debug!("Did not infer a package manager: {}", error);
+        }
+        Err(error) => return Err(error),
+    }
+
+    Ok(tools)
+}
+
+/// Describes where a version was found, e.g. `engines.node of /path/to/package.json`
+fn source(found: &FoundVersion) -> String {
+    match found.field {
+        Some(field) => format!("{} of {}", field, found.file.display()),
+        None => found.file.display().to_string(),
+    }
+}
+
+/// Shows the workspace members that extend the project manifest, since pinning changes their
+/// platform too, and makes sure that the change is confirmed before anything is written.
+///
+/// Confirmation comes from `--yes` or, when running interactively, from a prompt.
+fn confirm_affected_members(session: &mut Session, yes: bool) -> Fallible<()> {
+    let Some(project) = session.project()? else {
+        return Ok(());
+    };
+
+    let members = project.extending_members();
+    if members.is_empty() {
+        return Ok(());
+    }
+
+    info!(
+        "{} pinning in {} also affects {} workspace {}:",
+        note_prefix(),
+        project.manifest_file().display(),
+        members.len(),
+        if members.len() == 1 {
+            "member"
+        } else {
+            "members"
+        }
+    );
+    for member in &members {
+        info!("    {}", member.display());
+    }
+
+    if confirmed(yes) {
+        Ok(())
+    } else {
+        Err(ErrorKind::PinMembersNotConfirmed {
+            manifest: project.manifest_file().to_owned(),
+            members,
+        }
+        .into())
+    }
+}
@@ -0,0 +1,78 @@
+//! The JSON representation of a platform, shared by the commands that report one
+
+use node_semver::Version;
+use serde::Serialize;
+use volta_core::platform::{Image, Platform, Sourced};
+use volta_core::tool::node::load_default_npm_version;
+
+#[derive(Serialize)]
+pub(super) struct JsonPlatform {
+    node: JsonTool,
+    npm: Option<JsonTool>,
+    pnpm: Option<JsonTool>,
+    yarn: Option<JsonTool>,
+}
+
+#[derive(Serialize)]
+struct JsonTool {
+    version: String,
+    source: String,
+}
+
+impl JsonPlatform {
+    fn new(
+        node: &Sourced<Version>,
+        npm: Option<&Sourced<Version>>,
+        pnpm: Option<&Sourced<Version>>,
+        yarn: Option<&Sourced<Version>>,
+    ) -> Self {
+        // A platform without a custom npm uses the version bundled with Node, if it's known
+        let npm = match npm {
+            Some(npm) => Some(npm.into()),
+            None => load_default_npm_version(&node.value)
+                .ok()
+                .map(|version| JsonTool {
+                    version: version.to_string(),
+                    source: node.source.to_string(),
+                }),
+        };
+
+        JsonPlatform {
+            node: node.into(),
+            npm,
+            pnpm: pnpm.map(JsonTool::from),
+            yarn: yarn.map(JsonTool::from),
+        }
+    }
+}
+
+impl From<&Platform> for JsonPlatform {
+    fn from(platform: &Platform) -> Self {
+        JsonPlatform::new(
+            &platform.node,
+            platform.npm.as_ref(),
+            platform.pnpm.as_ref(),
+            platform.yarn.as_ref(),
+        )
+    }
+}
+
+impl From<&Image> for JsonPlatform {
+    fn from(image: &Image) -> Self {
+        JsonPlatform::new(
+            &image.node,
+            image.npm.as_ref(),
+            image.pnpm.as_ref(),
+            image.yarn.as_ref(),
+        )
+    }
+}
+
+impl From<&Sourced<Version>> for JsonTool {
+    fn from(tool: &Sourced<Version>) -> Self {
+        JsonTool {
+            version: tool.value.to_string(),
+            source: tool.source.to_string(),
+        }
+    }
+}
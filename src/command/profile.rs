@@ -0,0 +1,102 @@
+//! Define `volta profile`, which manages the named platforms that `volta run --profile` uses.
+
+use log::info;
+use volta_core::error::{ErrorKind, ExitCode, Fallible};
+use volta_core::platform::{Platform, PlatformSpec};
+use volta_core::profile;
+use volta_core::session::{ActivityKind, Session};
+use volta_core::style::{success_prefix, tool_version};
+
+use crate::command::Command;
+
+#[derive(clap::Args)]
+pub(crate) struct Profile {
+    #[command(subcommand)]
+    command: ProfileCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum ProfileCommand {
+    /// Lists the saved profiles with their tools
+    List,
+
+    /// Saves the current platform as a profile, replacing any profile with the same name
+    ///
+    /// The current platform is the one that tools run with in the current directory: the
+    /// project's, or else the default.
+    Save {
+        /// The name of the profile, like `legacy`
+        name: String,
+    },
+
+    /// Deletes a profile
+    Delete {
+        /// The name of the profile
+        name: String,
+    },
+}
+
+impl Command for Profile {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_subcommand_start(ActivityKind::Profile);
+
+        match self.command {
+            ProfileCommand::List => {
+                let names = profile::list()?;
+                if names.is_empty() {
+                    info!("No profiles saved yet. Use `volta profile save <name>` to save one.");
+                }
+                for name in names {
+                    match profile::load(&name) {
+                        Ok(platform) => println!("{}: {}", name, describe(&platform)),
+                        Err(error) => {
+                            let message = error.to_string();
+                            let summary = message.lines().next().unwrap_or_default();
+                            println!("{}: invalid ({})", name, summary);
+                        }
+                    }
+                }
+            }
+            ProfileCommand::Save { name } => {
+                let platform = Platform::current(session)?.ok_or(ErrorKind::NoPlatform)?;
+                let platform = PlatformSpec {
+                    node: platform.node.value,
+                    npm: platform.npm.map(|npm| npm.value),
+                    pnpm: platform.pnpm.map(|pnpm| pnpm.value),
+                    yarn: platform.yarn.map(|yarn| yarn.value),
+                };
+
+                let replaced = profile::save(&name, &platform)?;
+                info!(
+                    "{} {} profile '{}' with {}",
+                    success_prefix(),
+                    if replaced { "replaced" } else { "saved" },
+                    name,
+                    describe(&platform)
+                );
+            }
+            ProfileCommand::Delete { name } => {
+                profile::delete(&name)?;
+                info!("{} deleted profile '{}'", success_prefix(), name);
+            }
+        }
+
+        session.add_event_end(ActivityKind::Profile, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+/// Lists the tools of a platform, as in `node@14.21.3, yarn@1.22.19`
+fn describe(platform: &PlatformSpec) -> String {
+    let mut tools = vec![tool_version("node", &platform.node)];
+    for (name, version) in [
+        ("npm", &platform.npm),
+        ("pnpm", &platform.pnpm),
+        ("yarn", &platform.yarn),
+    ] {
+        if let Some(version) = version {
+            tools.push(tool_version(name, version));
+        }
+    }
+    tools.join(", ")
+}
@@ -1,21 +1,40 @@
 use std::collections::HashMap;
-use std::ffi::OsString;
+use std::ffi::{OsStr, OsString};
+use std::path::Path;
 
-use crate::command::Command;
+use crate::command::lock::check_platform_lock;
+use crate::command::platform_json::JsonPlatform;
+use crate::command::{to_json, Command};
 use crate::common::{Error, IntoResult};
 use log::warn;
+use node_semver::Version;
+use serde::Serialize;
 use volta_core::error::{report_error, ExitCode, Fallible};
-use volta_core::platform::{CliPlatform, InheritOption};
-use volta_core::run::execute_tool;
+use volta_core::platform::{CliPlatform, InheritOption, OptionalTool, PlatformSpec};
+use volta_core::profile;
+use volta_core::run::{execute_tool, locate_tool, Location};
 use volta_core::session::{ActivityKind, Session};
+use volta_core::settings::InstallMode;
+use volta_core::signal::tool_exit_code;
 use volta_core::tool::{node, npm, pnpm, yarn};
 
 #[derive(Debug, clap::Args)]
 pub(crate) struct Run {
+    /// Use the platform saved in a named profile (see `volta profile`)
+    ///
+    /// The tool versions given with the other flags take precedence over the profile.
+    #[arg(long, value_name = "name")]
+    profile: Option<String>,
+
     /// Set the custom Node version
     #[arg(long, value_name = "version")]
     node: Option<String>,
 
+    /// Use the Node build for another architecture, like `x64` to run under Rosetta 2, instead of
+    /// the project's `volta.arch` or the native one
+    #[arg(long, value_name = "arch")]
+    arch: Option<String>,
+
     /// Set the custom npm version
     #[arg(long, value_name = "version", conflicts_with = "bundled_npm")]
     npm: Option<String>,
@@ -40,11 +59,50 @@ pub(crate) struct Run {
     #[arg(long, conflicts_with = "yarn")]
     no_yarn: bool,
 
+    /// Run the command even if this tool can't be fetched (can be used multiple times)
+    #[arg(
+        long,
+        value_name = "tool",
+        value_parser = parse_optional_tool,
+        num_args = 1
+    )]
+    continue_on_missing: Vec<OptionalTool>,
+
+    /// Fail instead of fetching any tools that are not installed
+    #[arg(long)]
+    no_install_missing: bool,
+
+    /// Use only the tool versions that are available locally, without network access (the same
+    /// as setting VOLTA_OFFLINE=1)
+    #[arg(long)]
+    offline: bool,
+
+    /// Ignore the version policies that don't forbid overriding them
+    #[arg(long)]
+    override_policy: bool,
+
     /// Set an environment variable (can be used multiple times)
-    #[arg(long = "env", value_name = "NAME=value", num_args = 1)]
-    envs: Vec<String>,
+    #[arg(
+        long = "env",
+        value_name = "NAME=value",
+        value_parser = parse_env,
+        num_args = 1
+    )]
+    envs: Vec<(String, String)>,
+
+    /// Print the path of the executable that would run, instead of running it
+    #[arg(long = "where")]
+    locate: bool,
+
+    /// With --where, print the executable and its platform as JSON
+    #[arg(long, requires = "locate")]
+    json: bool,
 
     /// The command to run, along with any arguments
+    //
+    // Everything from the command name onwards is passed to the command verbatim, so flags that
+    // collide with Volta's own (e.g. `--verbose` or `--version`) are never interpreted by Volta.
+    // A `--` before the command name is accepted but not required.
     #[arg(
         allow_hyphen_values = true,
         trailing_var_arg = true,
@@ -56,9 +114,22 @@ pub(crate) struct Run {
 
 impl Command for Run {
     fn run(self, session: &mut Session) -> Fallible<ExitCode> {
-        session.add_event_start(ActivityKind::Run);
+        session.add_event_subcommand_start(ActivityKind::Run);
 
-        let envs = self.parse_envs();
+        if let Some(arch) = &self.arch {
+            node::set_arch_override(arch)?;
+        }
+        session.continue_on_missing(self.continue_on_missing.clone());
+        if self.no_install_missing {
+            session.set_install_mode(InstallMode::Manual);
+        }
+        if self.offline {
+            session.go_offline();
+        }
+        if self.override_policy {
+            session.override_policies();
+        }
+        check_platform_lock(session)?;
         let platform = self.parse_platform(session)?;
 
         // Safety: At least one value is required for `command_and_args`, so there must be at
@@ -67,21 +138,50 @@ impl Command for Run {
         let command = &self.command_and_args[0];
         let args = &self.command_and_args[1..];
 
-        match execute_tool(command, args, &envs, platform, session).into_result() {
-            Ok(()) => {
-                session.add_event_end(ActivityKind::Run, ExitCode::Success);
-                Ok(ExitCode::Success)
-            }
-            Err(Error::Tool(code)) => {
-                session.add_event_tool_end(ActivityKind::Run, code);
-                Ok(ExitCode::ExecutionFailure)
-            }
-            Err(Error::Volta(err)) => {
-                report_error(env!("CARGO_PKG_VERSION"), &err);
-                session.add_event_error(ActivityKind::Run, &err);
-                session.add_event_end(ActivityKind::Run, err.exit_code());
-                Ok(err.exit_code())
-            }
+        if self.locate {
+            let location = locate_tool(command, args, platform, session)?;
+            let output = if self.json {
+                format_json(&location)?
+            } else {
+                location.executable.to_string_lossy().into_owned()
+            };
+            println!("{}", output);
+
+            session.add_event_end(ActivityKind::Run, ExitCode::Success);
+            return Ok(ExitCode::Success);
+        }
+
+        let envs = self.parse_envs();
+        run_command(ActivityKind::Run, command, args, &envs, platform, session)
+    }
+}
+
+/// Runs a command with a custom platform, reporting the outcome as the given activity
+///
+/// Errors are reported here, rather than returned, so that a failure of the command itself can
+/// be told apart from a failure in Volta.
+pub(super) fn run_command(
+    activity: ActivityKind,
+    command: &OsStr,
+    args: &[OsString],
+    envs: &HashMap<&str, &str>,
+    platform: CliPlatform,
+    session: &mut Session,
+) -> Fallible<ExitCode> {
+    match execute_tool(command, args, envs, platform, session).into_result() {
+        Ok(()) => {
+            session.add_event_end(activity, ExitCode::Success);
+            Ok(ExitCode::Success)
+        }
+        Err(Error::Tool(status)) => {
+            session.add_event_tool_end(activity, tool_exit_code(status));
+            Ok(ExitCode::ExecutionFailure)
+        }
+        Err(Error::Volta(err)) => {
+            report_error(env!("CARGO_PKG_VERSION"), &err);
+            session.add_event_error(activity, &err);
+            session.add_event_end(activity, err.exit_code());
+            Ok(err.exit_code())
         }
     }
 }
@@ -89,17 +189,25 @@ impl Command for Run {
 impl Run {
     /// Builds a CliPlatform from the provided cli options
     ///
-    /// Will resolve a semver / tag version if necessary
+    /// Will resolve a semver / tag version if necessary. Tools that aren't set on the command
+    /// line come from the profile, if there is one.
     fn parse_platform(&self, session: &mut Session) -> Fallible<CliPlatform> {
-        let node = self
-            .node
-            .as_ref()
-            .map(|version| node::resolve(version.parse()?, session))
-            .transpose()?;
+        let profile = self.profile.as_deref().map(profile::load).transpose()?;
+        let from_profile =
+            |select: fn(&PlatformSpec) -> Option<&Version>| match profile.as_ref().and_then(select)
+            {
+                Some(version) => InheritOption::Some(version.clone()),
+                None => InheritOption::Inherit,
+            };
+
+        let node = match &self.node {
+            Some(version) => Some(node::resolve(version.parse()?, session)?),
+            None => profile.as_ref().map(|profile| profile.node.clone()),
+        };
 
         let npm = match (self.bundled_npm, &self.npm) {
             (true, _) => InheritOption::None,
-            (false, None) => InheritOption::Inherit,
+            (false, None) => from_profile(|profile| profile.npm.as_ref()),
             (false, Some(version)) => match npm::resolve(version.parse()?, session)? {
                 None => InheritOption::Inherit,
                 Some(npm) => InheritOption::Some(npm),
@@ -108,7 +216,7 @@ impl Run {
 
         let pnpm = match (self.no_pnpm, &self.pnpm) {
             (true, _) => InheritOption::None,
-            (false, None) => InheritOption::Inherit,
+            (false, None) => from_profile(|profile| profile.pnpm.as_ref()),
             (false, Some(version)) => {
                 InheritOption::Some(pnpm::resolve(version.parse()?, session)?)
             }
@@ -116,7 +224,7 @@ impl Run {
 
         let yarn = match (self.no_yarn, &self.yarn) {
             (true, _) => InheritOption::None,
-            (false, None) => InheritOption::Inherit,
+            (false, None) => from_profile(|profile| profile.yarn.as_ref()),
             (false, Some(version)) => {
                 InheritOption::Some(yarn::resolve(version.parse()?, session)?)
             }
@@ -132,21 +240,224 @@ impl Run {
 
     /// Convert the environment variable settings passed to the command line into a map
     ///
-    /// We ignore any setting that doesn't have a value associated with it
-    /// We also ignore the PATH environment variable as that is set when running a command
+    /// We ignore the PATH environment variable as that is set when running a command
     fn parse_envs(&self) -> HashMap<&str, &str> {
-        self.envs.iter().filter_map(|entry| {
-            let mut key_value = entry.splitn(2, '=');
-
-            match (key_value.next(), key_value.next()) {
-                (None, _) => None,
-                (Some(_), None) => None,
-                (Some(key), _) if key.eq_ignore_ascii_case("PATH") => {
+        self.envs
+            .iter()
+            .filter_map(|(key, value)| {
+                if key.eq_ignore_ascii_case("PATH") {
                     warn!("Ignoring {} environment variable as it will be overwritten when executing the command", key);
                     None
+                } else {
+                    Some((key.as_str(), value.as_str()))
                 }
-                (Some(key), Some(value)) => Some((key, value)),
+            })
+            .collect()
+    }
+}
+
+/// The version of the JSON output of `--where`, which changes whenever its shape does
+const JSON_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonLocation<'a> {
+    json_version: u32,
+    path: &'a Path,
+    platform: Option<JsonPlatform>,
+}
+
+/// Formats the location of an executable, with the platform it would run with (`null` if it
+/// would run without a Volta-managed platform)
+fn format_json(location: &Location) -> Fallible<String> {
+    let document = JsonLocation {
+        json_version: JSON_VERSION,
+        path: &location.executable,
+        platform: location.image.as_ref().map(JsonPlatform::from),
+    };
+
+    to_json(&document)
+}
+
+/// Parses an environment variable setting of the form `NAME=value`
+///
+/// The value may be empty, or contain further `=` characters, but the name may not be empty.
+fn parse_env(setting: &str) -> Result<(String, String), String> {
+    match setting.split_once('=') {
+        Some((name, _)) if name.is_empty() => Err(format!(
+            "missing the variable name in '{}', expected NAME=value",
+            setting
+        )),
+        Some((name, value)) => Ok((name.to_string(), value.to_string())),
+        None => Err(format!("missing '=' in '{}', expected NAME=value", setting)),
+    }
+}
+
+/// Parses the name of a tool that the platform may be checked out without
+fn parse_optional_tool(name: &str) -> Result<OptionalTool, String> {
+    match name {
+        "npm" => Ok(OptionalTool::Npm),
+        "pnpm" => Ok(OptionalTool::Pnpm),
+        "yarn" => Ok(OptionalTool::Yarn),
+        _ => Err(String::from("expected one of 'npm', 'pnpm', or 'yarn'")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+
+    use crate::cli::{Subcommand, Volta};
+    use clap::Parser;
+
+    fn parse_run<I, T>(args: I) -> (Volta, Vec<OsString>)
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let volta = Volta::try_parse_from(args).expect("arguments should parse");
+        let command_and_args = match &volta.command {
+            Some(Subcommand::Run(run)) => run.command_and_args.clone(),
+            _ => panic!("expected the run subcommand"),
+        };
+        (volta, command_and_args)
+    }
+
+    #[test]
+    fn child_flags_are_not_interpreted() {
+        let (volta, command_and_args) = parse_run([
+            "volta",
+            "run",
+            "node",
+            "--verbose",
+            "--quiet",
+            "--version",
+            "--help",
+            "--node",
+            "12",
+        ]);
+
+        assert!(!volta.verbose);
+        assert!(!volta.quiet);
+        assert!(!volta.version);
+        assert_eq!(
+            command_and_args,
+            [
+                "node",
+                "--verbose",
+                "--quiet",
+                "--version",
+                "--help",
+                "--node",
+                "12"
+            ]
+        );
+    }
+
+    #[test]
+    fn child_flags_with_values_are_passed_through() {
+        let (_, command_and_args) =
+            parse_run(["volta", "run", "node", "--inspect-brk=9229", "-v", "app.js"]);
+
+        assert_eq!(
+            command_and_args,
+            ["node", "--inspect-brk=9229", "-v", "app.js"]
+        );
+    }
+
+    #[test]
+    fn leading_separator_is_optional() {
+        let (_, without) = parse_run(["volta", "run", "node", "--verbose", "app.js"]);
+        let (_, with) = parse_run(["volta", "run", "--", "node", "--verbose", "app.js"]);
+
+        assert_eq!(without, with);
+    }
+
+    #[test]
+    fn separator_after_command_is_passed_through() {
+        let (_, command_and_args) = parse_run(["volta", "run", "node", "--", "--verbose"]);
+        assert_eq!(command_and_args, ["node", "--", "--verbose"]);
+
+        let (_, command_and_args) = parse_run(["volta", "run", "--", "node", "--", "app.js"]);
+        assert_eq!(command_and_args, ["node", "--", "app.js"]);
+    }
+
+    #[test]
+    fn volta_flags_before_command_are_interpreted() {
+        let (volta, command_and_args) =
+            parse_run(["volta", "run", "--verbose", "node", "--verbose"]);
+
+        assert!(volta.verbose);
+        assert_eq!(command_and_args, ["node", "--verbose"]);
+    }
+
+    #[test]
+    fn where_flag_before_command_is_interpreted() {
+        let volta = Volta::try_parse_from(["volta", "run", "--where", "--json", "node", "--where"])
+            .expect("arguments should parse");
+
+        match volta.command {
+            Some(Subcommand::Run(run)) => {
+                assert!(run.locate);
+                assert!(run.json);
+                assert_eq!(run.command_and_args, ["node", "--where"]);
             }
-        }).collect()
+            _ => panic!("expected the run subcommand"),
+        }
+    }
+
+    #[test]
+    fn env_settings_are_split_at_the_first_equals() {
+        let volta = Volta::try_parse_from([
+            "volta",
+            "run",
+            "--env",
+            "NODE_OPTIONS=--max-old-space-size=4096",
+            "--env",
+            "EMPTY=",
+            "node",
+        ])
+        .expect("arguments should parse");
+
+        match volta.command {
+            Some(Subcommand::Run(run)) => assert_eq!(
+                run.envs,
+                [
+                    (
+                        "NODE_OPTIONS".to_string(),
+                        "--max-old-space-size=4096".to_string()
+                    ),
+                    ("EMPTY".to_string(), String::new())
+                ]
+            ),
+            _ => panic!("expected the run subcommand"),
+        }
+    }
+
+    #[test]
+    fn env_settings_require_name_and_equals() {
+        assert!(Volta::try_parse_from(["volta", "run", "--env", "NODE_OPTIONS", "node"]).is_err());
+        assert!(Volta::try_parse_from(["volta", "run", "--env", "=value", "node"]).is_err());
+    }
+
+    #[test]
+    fn json_requires_where() {
+        assert!(Volta::try_parse_from(["volta", "run", "--json", "node"]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_arguments_survive() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let invalid = OsString::from_vec(b"caf\xe9".to_vec());
+        let (_, command_and_args) = parse_run([
+            OsString::from("volta"),
+            OsString::from("run"),
+            OsString::from("node"),
+            invalid.clone(),
+        ]);
+
+        assert_eq!(command_and_args, [OsString::from("node"), invalid]);
     }
 }
@@ -1,21 +1,93 @@
-use log::info;
-use volta_core::error::{ExitCode, Fallible};
+mod notion;
+mod shell;
+
+use log::{info, warn};
+use volta_core::error::{ErrorKind, ExitCode, Fallible};
 use volta_core::layout::volta_home;
 use volta_core::session::{ActivityKind, Session};
-use volta_core::shim::regenerate_shims_for_dir;
+use volta_core::shim::{regenerate_shims_for_dir, ShimResult};
 use volta_core::style::success_prefix;
+use volta_core::sync::VoltaLock;
+use volta_core::tool::package::{relink_packages, RelinkStatus};
 
-use crate::command::Command;
+use crate::command::{print_porcelain, Command};
+use shell::Shell;
 
 #[derive(clap::Args)]
-pub(crate) struct Setup {}
+pub(crate) struct Setup {
+    /// Repairs the shared links of installed packages, e.g. after moving the Volta directory
+    ///
+    /// Only the links are checked, the shell profile scripts are left unchanged.
+    #[arg(long, conflicts_with_all = ["shell", "print"])]
+    relink_packages: bool,
+
+    /// Migrates from Notion, the previous name of Volta
+    ///
+    /// Imports the default platform, lists the packages to install again, moves the Notion
+    /// directory to `.notion.bak`, and removes Notion from the shell profile scripts.
+    #[arg(long, conflicts_with_all = ["relink_packages", "shell", "print"])]
+    migrate_notion: bool,
+
+    /// Migrates without asking for confirmation
+    #[arg(long, requires = "migrate_notion")]
+    yes: bool,
+
+    /// Only sets up the given shell, instead of detecting the profile scripts to change
+    ///
+    /// The supported shells are bash, zsh, and fish, as well as powershell on Windows.
+    #[arg(long, value_name = "shell")]
+    shell: Option<Shell>,
+
+    /// Prints the snippet that sets up the shell, without changing any files
+    #[arg(long, requires = "shell")]
+    print: bool,
+
+    /// Print one line for each file that was changed, like `shim created <path>`, instead of the
+    /// usual messages, which are moved to stderr
+    #[arg(long, conflicts_with_all = ["relink_packages", "migrate_notion", "print"])]
+    pub(crate) porcelain: bool,
+}
 
 impl Command for Setup {
     fn run(self, session: &mut Session) -> Fallible<ExitCode> {
-        session.add_event_start(ActivityKind::Setup);
+        session.add_event_subcommand_start(ActivityKind::Setup);
+
+        if self.relink_packages {
+            relink()?;
+            session.add_event_end(ActivityKind::Setup, ExitCode::Success);
+            return Ok(ExitCode::Success);
+        }
+
+        if self.migrate_notion {
+            notion::migrate(session, self.yes)?;
+            session.add_event_end(ActivityKind::Setup, ExitCode::Success);
+            return Ok(ExitCode::Success);
+        }
 
-        os::setup_environment()?;
-        regenerate_shims_for_dir(volta_home()?.shim_dir())?;
+        let home = volta_home()?;
+        let mut changes = match self.shell {
+            Some(shell) if self.print => {
+                print!("{}", shell.snippet(home.root(), home.shim_dir()));
+                session.add_event_end(ActivityKind::Setup, ExitCode::Success);
+                return Ok(ExitCode::Success);
+            }
+            Some(shell) => {
+                let change = shell::setup_profile(shell, home.root(), home.shim_dir())?;
+                info!(
+                    "Added Volta to the {} profile {}",
+                    shell,
+                    change.path.display()
+                );
+                vec![change]
+            }
+            None => os::setup_environment()?,
+        };
+        let shims = regenerate_shims_for_dir(home.shim_dir(), session.settings()?.shim_jobs())?;
+        changes.extend(shims.iter().filter_map(ShimResult::change));
+
+        if self.porcelain {
+            print_porcelain(changes);
+        }
 
         info!(
             "{} Setup complete. Open a new terminal to start using Volta!",
@@ -27,20 +99,59 @@ impl Command for Setup {
     }
 }
 
+/// Repairs the shared link of every installed package, reporting the result for each
+fn relink() -> Fallible<()> {
+    let _lock = VoltaLock::acquire();
+
+    let packages = relink_packages()?;
+    if packages.is_empty() {
+        info!("No packages are installed, so there is nothing to relink.");
+        return Ok(());
+    }
+
+    let mut failed = Vec::new();
+    for package in packages {
+        match package.status {
+            Ok(RelinkStatus::Unchanged) => info!("    {}: ok", package.name),
+            Ok(RelinkStatus::Created) => info!("    {}: created missing link", package.name),
+            Ok(RelinkStatus::Repaired) => info!("    {}: repaired broken link", package.name),
+            Ok(RelinkStatus::Converted) => {
+                info!("    {}: converted link to a relative path", package.name)
+            }
+            Ok(RelinkStatus::MissingImage) => warn!(
+                "{}: the package is missing from the Volta directory, reinstall it with `volta install {}`",
+                package.name,
+                package.name
+            ),
+            Err(error) => {
+                warn!("{}: {}", package.name, error);
+                failed.push(package.name);
+            }
+        }
+    }
+
+    if failed.is_empty() {
+        info!("{} Package links are up to date.", success_prefix());
+        Ok(())
+    } else {
+        Err(ErrorKind::RelinkPackagesError { packages: failed }.into())
+    }
+}
+
 #[cfg(unix)]
 mod os {
     use std::env;
-    use std::fs::File;
-    use std::io::{self, BufRead, BufReader, Write};
     use std::path::{Path, PathBuf};
 
+    use super::shell::{read_profile_without_volta, write_profile, Shell};
     use log::{debug, warn};
+    use volta_core::change::Change;
     use volta_core::error::{ErrorKind, Fallible};
     use volta_core::layout::volta_home;
 
-    pub fn setup_environment() -> Fallible<()> {
+    /// Adds Volta to the shell profile scripts, returning the scripts that were written
+    pub fn setup_environment() -> Fallible<Vec<Change>> {
         let home = volta_home()?;
-        let formatted_home = format_home(home.root());
 
         // Don't update the user's shell config files if VOLTA_HOME and PATH already contain what we need.
         let home_in_path = match env::var_os("PATH") {
@@ -52,35 +163,36 @@ mod os {
             debug!(
                 "Skipping dot-file modification as VOLTA_HOME is set, and included in the PATH."
             );
-            return Ok(());
+            return Ok(Vec::new());
         }
 
         debug!("Searching for profiles to update");
         let profiles = determine_profiles()?;
 
-        let found_profile = profiles.into_iter().fold(false, |prev, profile| {
+        let mut changes = Vec::new();
+        for profile in profiles {
             let contents = read_profile_without_volta(&profile).unwrap_or_default();
 
-            let write_profile = match profile.extension() {
-                Some(ext) if ext == "fish" => write_profile_fish,
-                _ => write_profile_sh,
+            let shell = match profile.extension() {
+                Some(ext) if ext == "fish" => Shell::Fish,
+                _ => Shell::Bash,
             };
+            let snippet = shell.snippet(home.root(), home.shim_dir());
 
-            match write_profile(&profile, contents, &formatted_home) {
-                Ok(()) => true,
+            match write_profile(&profile, contents, &snippet) {
+                Ok(change) => changes.push(change),
                 Err(err) => {
                     warn!(
                         "Found profile script, but could not modify it: {}",
                         profile.display()
                     );
                     debug!("Profile modification error: {}", err);
-                    prev
                 }
             }
-        });
+        }
 
-        if found_profile {
-            Ok(())
+        if !changes.is_empty() {
+            Ok(changes)
         } else {
             Err(ErrorKind::NoShellProfile {
                 env_profile: String::new(),
@@ -175,52 +287,6 @@ If you run into problems running Volta, create {} and run `volta setup` again.",
             profiles.push(fish_config);
         }
     }
-
-    fn read_profile_without_volta(path: &Path) -> Option<String> {
-        let file = File::open(path).ok()?;
-        let reader = BufReader::new(file);
-
-        reader
-            .lines()
-            .filter(|line_result| match line_result {
-                Ok(line) if !line.contains("VOLTA") => true,
-                Ok(_) => false,
-                Err(_) => true,
-            })
-            .collect::<io::Result<Vec<String>>>()
-            .map(|lines| lines.join("\n"))
-            .ok()
-    }
-
-    fn format_home(volta_home: &Path) -> String {
-        if let Some(home_dir) = env::var_os("HOME") {
-            if let Ok(suffix) = volta_home.strip_prefix(home_dir) {
-                // If the HOME environment variable is set _and_ the proposed VOLTA_HOME starts
-                // with that value, use $HOME when writing the profile scripts
-                return format!("$HOME/{}", suffix.display());
-            }
-        }
-
-        volta_home.display().to_string()
-    }
-
-    fn write_profile_sh(path: &Path, contents: String, volta_home: &str) -> io::Result<()> {
-        let mut file = File::create(path)?;
-        write!(
-            file,
-            "{}\nexport VOLTA_HOME=\"{}\"\nexport PATH=\"$VOLTA_HOME/bin:$PATH\"\n",
-            contents, volta_home,
-        )
-    }
-
-    fn write_profile_fish(path: &Path, contents: String, volta_home: &str) -> io::Result<()> {
-        let mut file = File::create(path)?;
-        write!(
-            file,
-            "{}\nset -gx VOLTA_HOME \"{}\"\nset -gx PATH \"$VOLTA_HOME/bin\" $PATH\n",
-            contents, volta_home,
-        )
-    }
 }
 
 #[cfg(windows)]
@@ -228,12 +294,16 @@ mod os {
     use std::process::Command;
 
     use log::debug;
+    use volta_core::change::Change;
     use volta_core::error::{Context, ErrorKind, Fallible};
     use volta_core::layout::volta_home;
     use winreg::enums::HKEY_CURRENT_USER;
     use winreg::RegKey;
 
-    pub fn setup_environment() -> Fallible<()> {
+    /// Adds the shim directory to the user's Path
+    ///
+    /// The Path is kept in the registry, so no files are changed.
+    pub fn setup_environment() -> Fallible<Vec<Change>> {
         let shim_dir = volta_home()?.shim_dir().to_string_lossy().to_string();
         let hkcu = RegKey::predef(HKEY_CURRENT_USER);
         let env = hkcu
@@ -261,6 +331,6 @@ mod os {
             }
         }
 
-        Ok(())
+        Ok(Vec::new())
     }
 }
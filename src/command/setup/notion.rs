@@ -0,0 +1,170 @@
+//! Migrates from Notion, the previous name of Volta, with `volta setup --migrate-notion`
+//!
+//! The Notion directory is renamed rather than removed, so the migration can be undone by renaming
+//! it back. Nothing changes until the migration is confirmed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::{info, warn};
+use volta_core::error::{Context, ErrorKind, Fallible};
+use volta_core::layout::volta_home;
+use volta_core::notion::{self, Salvage};
+use volta_core::platform::PlatformSpec;
+use volta_core::session::Session;
+use volta_core::style::{note_prefix, success_prefix, tool_version};
+use volta_core::sync::VoltaLock;
+
+use super::shell::remove_notion;
+use crate::command::confirmed;
+use crate::command::doctor::path::{profiles, spellings};
+
+/// A profile script that refers to Notion, along with its contents once those lines are removed
+struct Profile {
+    file: PathBuf,
+    rewritten: String,
+}
+
+pub(super) fn migrate(session: &mut Session, yes: bool) -> Fallible<()> {
+    let Some(legacy) = notion::detect()? else {
+        info!("No Notion directory or shims were found, so there is nothing to migrate.");
+        return Ok(());
+    };
+
+    let salvage = if legacy.exists {
+        legacy.salvage()
+    } else {
+        Salvage::default()
+    };
+    let import_platform = match (&salvage.platform, session.default_platform()?) {
+        (Some(platform), None) => Some(platform.clone()),
+        _ => None,
+    };
+
+    let home_dir = dirs::home_dir();
+    let volta_home = volta_home()?;
+    let mut shim_dirs = vec![legacy.root.join("bin")];
+    shim_dirs.extend(legacy.path_entries.iter().cloned());
+    let spellings: Vec<String> = shim_dirs
+        .iter()
+        .flat_map(|dir| spellings(dir, home_dir.as_deref(), volta_home.root()))
+        .collect();
+    let profiles: Vec<Profile> = home_dir
+        .as_deref()
+        .map(profiles)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|file| {
+            let contents = fs::read_to_string(&file).ok()?;
+            let rewritten = remove_notion(&contents, &spellings)?;
+            Some(Profile { file, rewritten })
+        })
+        .collect();
+
+    info!("{}", legacy.describe());
+    info!("Migrating will:");
+    if let Some(platform) = &import_platform {
+        info!("    set your default platform to {}", describe(platform));
+    }
+    if legacy.exists {
+        info!(
+            "    move {} to {}",
+            legacy.root.display(),
+            legacy.backup_dir().display()
+        );
+    }
+    for profile in &profiles {
+        info!("    remove Notion from {}", profile.file.display());
+    }
+
+    if !confirmed(yes) {
+        return Err(ErrorKind::NotionMigrationNotConfirmed {
+            dir: legacy.root.clone(),
+        }
+        .into());
+    }
+
+    let _lock = VoltaLock::acquire();
+
+    if let Some(platform) = import_platform {
+        let toolchain = session.toolchain_mut()?;
+        toolchain.set_active_node(&platform.node)?;
+        toolchain.set_active_npm(platform.npm.clone())?;
+        toolchain.set_active_yarn(platform.yarn.clone())?;
+        info!(
+            "{} Set your default platform to {}",
+            success_prefix(),
+            describe(&platform)
+        );
+    } else if let Some(platform) = &salvage.platform {
+        info!(
+            "{} Kept your default platform, Notion's was {}",
+            note_prefix(),
+            describe(platform)
+        );
+    }
+
+    if legacy.exists {
+        let backup = legacy.back_up()?;
+        info!(
+            "{} Moved {} to {}. To undo the migration, move it back.",
+            success_prefix(),
+            legacy.root.display(),
+            backup.display()
+        );
+    }
+
+    for profile in &profiles {
+        let backup = write_profile(&profile.file, &profile.rewritten)?;
+        info!(
+            "{} Removed Notion from {}, the previous version is saved as {}",
+            success_prefix(),
+            profile.file.display(),
+            backup.display()
+        );
+    }
+    if profiles.is_empty() && !legacy.path_entries.is_empty() {
+        warn!(
+            "Could not find the profile script that adds the shims of Notion to your PATH. Remove them from the profile script or environment setting that changes your PATH."
+        );
+    }
+
+    if !salvage.packages.is_empty() {
+        info!(
+            "{} These packages were installed with Notion, to install them again run:\n    volta install {}",
+            note_prefix(),
+            salvage.packages.join(" ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Rewrites a profile script, after copying the original next to it as `<profile>.notion.bak`
+///
+/// Returns the path of the copy.
+fn write_profile(file: &Path, contents: &str) -> Fallible<PathBuf> {
+    let mut backup = file.as_os_str().to_owned();
+    backup.push(".notion.bak");
+    let backup = PathBuf::from(backup);
+
+    fs::copy(file, &backup).with_context(|| ErrorKind::WriteProfileError {
+        file: backup.clone(),
+    })?;
+    fs::write(file, contents).with_context(|| ErrorKind::WriteProfileError {
+        file: file.to_owned(),
+    })?;
+    Ok(backup)
+}
+
+/// Lists the tools of a platform, as in `node@10.15.0, yarn@1.12.3`
+fn describe(platform: &PlatformSpec) -> String {
+    let mut tools = vec![tool_version("node", &platform.node)];
+    if let Some(npm) = &platform.npm {
+        tools.push(tool_version("npm", npm));
+    }
+    if let Some(yarn) = &platform.yarn {
+        tools.push(tool_version("yarn", yarn));
+    }
+    tools.join(", ")
+}
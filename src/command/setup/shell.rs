@@ -0,0 +1,300 @@
+//! The shells that `volta setup --shell` can configure, along with the snippets that add Volta to
+//! each of them and the profile scripts those snippets are written to
+
+use std::env;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::command::doctor::path::remove_entries;
+use volta_core::change::{Action, Change, Subject};
+use volta_core::error::{Context, ErrorKind, Fallible};
+
+/// The variable that the setup of Notion, the previous name of Volta, used in every line it wrote
+/// to a profile script
+const NOTION_MARKER: &str = "NOTION_HOME";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    #[cfg(windows)]
+    PowerShell,
+}
+
+impl Shell {
+    #[cfg(unix)]
+    const SUPPORTED: &'static str = "bash, zsh, fish";
+    #[cfg(windows)]
+    const SUPPORTED: &'static str = "bash, zsh, fish, powershell";
+
+    /// The lines that set `VOLTA_HOME` and add the shim directory to the `PATH`
+    pub(super) fn snippet(self, volta_home: &Path, shim_dir: &Path) -> String {
+        let home = format_home(volta_home);
+        // The shim directory is written relative to `VOLTA_HOME` whenever it's within it, as in
+        // the profile scripts written by earlier versions of Volta
+        let shim_dir = |home_var: &str, separator: char| match shim_dir.strip_prefix(volta_home) {
+            Ok(dir) => format!("{}{}{}", home_var, separator, dir.display()),
+            Err(_) => shim_dir.display().to_string(),
+        };
+
+        match self {
+            Shell::Bash | Shell::Zsh => format!(
+                "export VOLTA_HOME=\"{}\"\nexport PATH=\"{}:$PATH\"\n",
+                home,
+                shim_dir("$VOLTA_HOME", '/')
+            ),
+            Shell::Fish => format!(
+                "set -gx VOLTA_HOME \"{}\"\nset -gx PATH \"{}\" $PATH\n",
+                home,
+                shim_dir("$VOLTA_HOME", '/')
+            ),
+            #[cfg(windows)]
+            Shell::PowerShell => format!(
+                "$env:VOLTA_HOME = \"{}\"\n$env:Path = \"{};$env:Path\"\n",
+                home,
+                shim_dir("$env:VOLTA_HOME", '\\')
+            ),
+        }
+    }
+
+    /// The profile script that the shell reads when it starts, which may not exist yet
+    pub(super) fn profile(self, home_dir: &Path) -> PathBuf {
+        match self {
+            Shell::Bash => {
+                // Creating a `.bashrc` when only a `.bash_profile` exists (or vice versa) can stop
+                // bash from reading the existing one, so an existing script is always preferred
+                let existing = [".bashrc", ".bash_profile"]
+                    .into_iter()
+                    .map(|name| home_dir.join(name))
+                    .find(|profile| profile.exists());
+
+                existing.unwrap_or_else(|| {
+                    if cfg!(target_os = "macos") {
+                        home_dir.join(".bash_profile")
+                    } else {
+                        home_dir.join(".bashrc")
+                    }
+                })
+            }
+            Shell::Zsh => non_empty_env_path("ZDOTDIR")
+                .unwrap_or_else(|| home_dir.to_owned())
+                .join(".zshenv"),
+            Shell::Fish => non_empty_env_path("XDG_CONFIG_HOME")
+                .unwrap_or_else(|| home_dir.join(".config"))
+                .join("fish")
+                .join("config.fish"),
+            #[cfg(windows)]
+            Shell::PowerShell => home_dir
+                .join("Documents")
+                .join("PowerShell")
+                .join("Microsoft.PowerShell_profile.ps1"),
+        }
+    }
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            #[cfg(windows)]
+            "powershell" | "pwsh" => Ok(Shell::PowerShell),
+            _ => Err(format!(
+                "unsupported shell '{}', the supported shells are: {}",
+                s,
+                Shell::SUPPORTED
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Shell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+            #[cfg(windows)]
+            Shell::PowerShell => "powershell",
+        })
+    }
+}
+
+/// Adds the snippet for the shell to its profile script, creating the script if necessary
+pub(super) fn setup_profile(shell: Shell, volta_home: &Path, shim_dir: &Path) -> Fallible<Change> {
+    let home_dir = dirs::home_dir().ok_or(ErrorKind::NoHomeEnvironmentVar)?;
+    let profile = shell.profile(&home_dir);
+
+    if let Some(dir) = profile.parent() {
+        fs::create_dir_all(dir).with_context(|| ErrorKind::WriteProfileError {
+            file: profile.clone(),
+        })?;
+    }
+
+    let contents = read_profile_without_volta(&profile).unwrap_or_default();
+    write_profile(&profile, contents, &shell.snippet(volta_home, shim_dir))
+        .with_context(|| ErrorKind::WriteProfileError { file: profile })
+}
+
+/// Reads a profile script, leaving out any lines that were written by a previous setup
+pub(super) fn read_profile_without_volta(path: &Path) -> Option<String> {
+    let file = File::open(path).ok()?;
+    let reader = BufReader::new(file);
+
+    reader
+        .lines()
+        .filter(|line_result| match line_result {
+            Ok(line) if !line.contains("VOLTA") => true,
+            Ok(_) => false,
+            Err(_) => true,
+        })
+        .collect::<io::Result<Vec<String>>>()
+        .map(|lines| lines.join("\n"))
+        .ok()
+}
+
+/// Removes the lines that the setup of Notion wrote to a profile script, along with the spellings of
+/// its directories from any other lines that set the `PATH`
+///
+/// Returns `None` if the script doesn't refer to Notion. The script's line endings are kept.
+pub(super) fn remove_notion(contents: &str, spellings: &[String]) -> Option<String> {
+    let newline = if contents.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let without_entries = remove_entries(contents, spellings);
+    let source = without_entries.as_deref().unwrap_or(contents);
+
+    let lines: Vec<&str> = source
+        .lines()
+        .filter(|line| !line.contains(NOTION_MARKER))
+        .collect();
+    if without_entries.is_none() && lines.len() == source.lines().count() {
+        return None;
+    }
+
+    let mut rewritten = lines.join(newline);
+    if source.ends_with('\n') {
+        rewritten.push_str(newline);
+    }
+    Some(rewritten)
+}
+
+/// Writes a profile script with the given contents, followed by the snippet
+pub(super) fn write_profile(path: &Path, contents: String, snippet: &str) -> io::Result<Change> {
+    let action = if path.exists() {
+        Action::Updated
+    } else {
+        Action::Created
+    };
+    let mut file = File::create(path)?;
+    write!(file, "{}\n{}", contents, snippet)?;
+    Ok(Change::new(Subject::Profile, action, path))
+}
+
+fn format_home(volta_home: &Path) -> String {
+    if let Some(home_dir) = env::var_os("HOME") {
+        if let Ok(suffix) = volta_home.strip_prefix(home_dir) {
+            // If the HOME environment variable is set _and_ the proposed VOLTA_HOME starts
+            // with that value, use $HOME when writing the profile scripts
+            return format!("$HOME/{}", suffix.display());
+        }
+    }
+
+    volta_home.display().to_string()
+}
+
+fn non_empty_env_path(name: &str) -> Option<PathBuf> {
+    env::var_os(name)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    const VOLTA_HOME: &str = "/opt/volta";
+    const SHIM_DIR: &str = "/opt/volta/bin";
+
+    fn snippet(shell: Shell) -> String {
+        shell.snippet(Path::new(VOLTA_HOME), Path::new(SHIM_DIR))
+    }
+
+    #[test]
+    fn parses_supported_shells() {
+        assert_eq!("bash".parse(), Ok(Shell::Bash));
+        assert_eq!("zsh".parse(), Ok(Shell::Zsh));
+        assert_eq!("fish".parse(), Ok(Shell::Fish));
+    }
+
+    #[test]
+    fn unsupported_shell_lists_supported_values() {
+        assert_eq!(
+            "nu".parse::<Shell>(),
+            Err("unsupported shell 'nu', the supported shells are: bash, zsh, fish".into())
+        );
+        assert!("powershell".parse::<Shell>().is_err());
+    }
+
+    #[test]
+    fn posix_snippets() {
+        let expected = "export VOLTA_HOME=\"/opt/volta\"\nexport PATH=\"$VOLTA_HOME/bin:$PATH\"\n";
+
+        assert_eq!(snippet(Shell::Bash), expected);
+        assert_eq!(snippet(Shell::Zsh), expected);
+    }
+
+    #[test]
+    fn fish_snippet() {
+        assert_eq!(
+            snippet(Shell::Fish),
+            "set -gx VOLTA_HOME \"/opt/volta\"\nset -gx PATH \"$VOLTA_HOME/bin\" $PATH\n"
+        );
+    }
+
+    #[test]
+    fn removes_notion_lines_and_entries() {
+        let bashrc = "export NOTION_HOME=\"$HOME/.notion\"
+[ -s \"$NOTION_HOME/load.sh\" ] && \\. \"$NOTION_HOME/load.sh\"
+export PATH=\"$HOME/.notion/bin:$HOME/go/bin:$PATH\"
+export VOLTA_HOME=\"$HOME/.volta\"
+";
+        let spellings = vec!["/home/me/.notion/bin".into(), "$HOME/.notion/bin".into()];
+
+        assert_eq!(
+            remove_notion(bashrc, &spellings).unwrap(),
+            "export PATH=\"$HOME/go/bin:$PATH\"\nexport VOLTA_HOME=\"$HOME/.volta\"\n"
+        );
+        assert_eq!(
+            remove_notion("export PATH=\"$HOME/go/bin:$PATH\"\n", &spellings),
+            None
+        );
+    }
+
+    #[test]
+    fn removing_notion_keeps_crlf_line_endings() {
+        let profile = "# settings\r\nexport NOTION_HOME=\"$HOME/.notion\"\r\nexport PATH=\"$HOME/.notion/bin:$HOME/go/bin:$PATH\"\r\n";
+        let spellings = vec!["$HOME/.notion/bin".into()];
+
+        assert_eq!(
+            remove_notion(profile, &spellings).unwrap(),
+            "# settings\r\nexport PATH=\"$HOME/go/bin:$PATH\"\r\n"
+        );
+    }
+
+    #[test]
+    fn snippet_uses_absolute_shim_dir_outside_volta_home() {
+        let snippet = Shell::Fish.snippet(Path::new(VOLTA_HOME), Path::new("/usr/local/volta"));
+
+        assert!(snippet.ends_with("set -gx PATH \"/usr/local/volta\" $PATH\n"));
+    }
+}
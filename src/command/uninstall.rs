@@ -3,24 +3,33 @@ use volta_core::session::{ActivityKind, Session};
 use volta_core::tool;
 use volta_core::version::VersionSpec;
 
-use crate::command::Command;
+use crate::command::{print_porcelain, Command};
 
 #[derive(clap::Args)]
 pub(crate) struct Uninstall {
-    /// The tool to uninstall, like `ember-cli-update`, `typescript`, or <package>
+    /// The tool to uninstall, like `ember-cli-update`, `typescript`, `node@20.11.1`, or <package>
     tool: String,
+
+    /// Shows what would be removed, without removing anything
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Print one line for each file that was removed, like `shim removed <path>`, instead of the
+    /// usual messages, which are moved to stderr
+    #[arg(long, conflicts_with = "dry_run")]
+    pub(crate) porcelain: bool,
 }
 
 impl Command for Uninstall {
     fn run(self, session: &mut Session) -> Fallible<ExitCode> {
-        session.add_event_start(ActivityKind::Uninstall);
+        session.add_event_subcommand_start(ActivityKind::Uninstall);
 
         let tool = tool::Spec::try_from_str(&self.tool)?;
 
         // For packages, specifically report that we do not support uninstalling
-        // specific versions. For runtimes and package managers, we currently
-        // *intentionally* let this fall through to inform the user that we do
-        // not support uninstalling those *at all*.
+        // specific versions. Node is uninstalled by version, and for package
+        // managers, we currently *intentionally* let this fall through to inform
+        // the user that we do not support uninstalling those *at all*.
         if let tool::Spec::Package(_name, version) = &tool {
             let VersionSpec::None = version else {
                 return Err(ErrorKind::Unimplemented {
@@ -30,7 +39,15 @@ impl Command for Uninstall {
             };
         }
 
-        tool.uninstall()?;
+        if self.dry_run {
+            tool.uninstall_dry_run(session)?;
+        } else {
+            tool.uninstall(session)?;
+        }
+
+        if self.porcelain {
+            print_porcelain(session.take_changes());
+        }
 
         session.add_event_end(ActivityKind::Uninstall, ExitCode::Success);
         Ok(ExitCode::Success)
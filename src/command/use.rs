@@ -24,7 +24,7 @@ pub(crate) struct Use {
 
 impl Command for Use {
     fn run(self, session: &mut Session) -> Fallible<ExitCode> {
-        session.add_event_start(ActivityKind::Help);
+        session.add_event_subcommand_start(ActivityKind::Help);
         let result = Err(ErrorKind::DeprecatedCommandError {
             command: "use".to_string(),
             advice: ADVICE.to_string(),
@@ -0,0 +1,131 @@
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::thread;
+
+use log::{info, warn};
+use volta_core::binary_manifest::{self, ManifestDiff, ManifestEntry};
+use volta_core::error::{ExitCode, Fallible};
+use volta_core::session::{ActivityKind, Session};
+use volta_core::style::success_prefix;
+
+use crate::command::Command;
+
+#[derive(clap::Args)]
+pub(crate) struct VerifyBinaries {
+    /// Checks the binaries against a manifest written by a previous run, reporting any binaries
+    /// that were added, removed, or changed since
+    #[arg(long, value_name = "manifest")]
+    check: Option<PathBuf>,
+
+    /// How many binaries to hash at the same time [default: the number of CPUs]
+    #[arg(
+        long,
+        value_name = "count",
+        value_parser = clap::value_parser!(u16).range(1..)
+    )]
+    jobs: Option<u16>,
+}
+
+impl Command for VerifyBinaries {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_subcommand_start(ActivityKind::VerifyBinaries);
+
+        let entries = binary_manifest::build(self.jobs())?;
+
+        let exit_code = match self.check {
+            Some(file) => {
+                let diff = binary_manifest::diff(binary_manifest::read(&file)?, entries);
+                if diff.is_empty() {
+                    info!(
+                        "{} All binaries match the manifest {}",
+                        success_prefix(),
+                        file.display()
+                    );
+                    ExitCode::Success
+                } else {
+                    print!("{}", format_diff(&diff));
+                    warn!(
+                        "Found {} difference(s) from the manifest {}",
+                        diff.len(),
+                        file.display()
+                    );
+                    ExitCode::ConfigurationError
+                }
+            }
+            None => {
+                print!("{}", binary_manifest::to_json_lines(&entries));
+                ExitCode::Success
+            }
+        };
+
+        session.add_event_end(ActivityKind::VerifyBinaries, exit_code);
+        Ok(exit_code)
+    }
+}
+
+impl VerifyBinaries {
+    fn jobs(&self) -> NonZeroUsize {
+        self.jobs
+            .and_then(|jobs| NonZeroUsize::new(jobs.into()))
+            .or_else(|| thread::available_parallelism().ok())
+            .unwrap_or(NonZeroUsize::MIN)
+    }
+}
+
+/// Formats each difference on its own line, grouped by kind
+fn format_diff(diff: &ManifestDiff) -> String {
+    let mut lines = Vec::with_capacity(diff.len());
+    lines.extend(diff.added.iter().map(|entry| line("added", entry)));
+    lines.extend(diff.removed.iter().map(|entry| line("removed", entry)));
+    lines.extend(diff.changed.iter().map(|(expected, actual)| {
+        format!(
+            "{}: {} -> {}",
+            line("changed", actual),
+            expected.hash,
+            actual.hash
+        )
+    }));
+
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+fn line(kind: &str, entry: &ManifestEntry) -> String {
+    match &entry.version {
+        Some(version) => format!("{:<8} {} ({}@{})", kind, entry.path, entry.tool, version),
+        None => format!("{:<8} {} ({})", kind, entry.path, entry.tool),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, hash: &str, version: Option<&str>) -> ManifestEntry {
+        ManifestEntry {
+            path: path.into(),
+            size: 1,
+            hash: hash.into(),
+            tool: "node".into(),
+            version: version.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn formats_each_kind_of_difference() {
+        let diff = ManifestDiff {
+            added: vec![entry("bin/pnpm", "sha256-aa", None)],
+            removed: vec![entry("bin/yarn", "sha256-bb", Some("20.11.0"))],
+            changed: vec![(
+                entry("bin/node", "sha256-cc", Some("20.11.0")),
+                entry("bin/node", "sha256-dd", Some("20.11.0")),
+            )],
+        };
+
+        assert_eq!(
+            format_diff(&diff),
+            "added    bin/pnpm (node)\n\
+             removed  bin/yarn (node@20.11.0)\n\
+             changed  bin/node (node@20.11.0): sha256-cc -> sha256-dd\n"
+        );
+    }
+}
@@ -0,0 +1,90 @@
+//! Define `volta self`, which manages the Volta installation itself.
+
+use log::info;
+use node_semver::Version;
+use volta_core::error::{ExitCode, Fallible};
+use volta_core::layout::{volta_home, volta_install};
+use volta_core::self_update::{
+    check_updatable, fetch_release, install_release, latest_version, InstallSource,
+};
+use volta_core::session::{ActivityKind, Session};
+use volta_core::shim::regenerate_shims_for_dir;
+use volta_core::style::success_prefix;
+use volta_core::sync::VoltaLock;
+
+use crate::command::Command;
+
+#[derive(clap::Args)]
+pub(crate) struct VoltaSelf {
+    #[command(subcommand)]
+    command: SelfCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum SelfCommand {
+    /// Updates Volta to the latest release
+    ///
+    /// Only installations made with the install script can update themselves. The release is
+    /// always downloaded from the official source, ignoring any hooks, and is compared with the
+    /// checksum published alongside it before the binaries are replaced. This catches a corrupted
+    /// download, but no signature is checked.
+    Update {
+        /// Only reports whether a newer release is available, without installing it
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+impl Command for VoltaSelf {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_subcommand_start(ActivityKind::SelfUpdate);
+
+        match self.command {
+            SelfCommand::Update { check } => update(session, check)?,
+        }
+
+        session.add_event_end(ActivityKind::SelfUpdate, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+fn update(session: &mut Session, check: bool) -> Fallible<()> {
+    let install_dir = volta_install()?.root();
+    let current = Version::parse(env!("CARGO_PKG_VERSION")).expect("Volta's version is valid");
+    if !check {
+        check_updatable(install_dir)?;
+    }
+
+    let latest = latest_version()?;
+    if latest <= current {
+        info!("{} Volta {} is up to date.", success_prefix(), current);
+        return Ok(());
+    }
+
+    if check {
+        let how = match InstallSource::detect(install_dir).manager() {
+            Some((_, update)) => update.to_string(),
+            None => "`volta self update`".to_string(),
+        };
+        info!(
+            "Volta {} is available (you have {}). Update with {}.",
+            latest, current, how
+        );
+        return Ok(());
+    }
+
+    let _lock = VoltaLock::acquire();
+    let staged = fetch_release(&latest, install_dir)?;
+    let installed = install_release(staged, install_dir)?;
+
+    // The shims link to the shim executable, so they are checked in case it was renamed
+    regenerate_shims_for_dir(volta_home()?.shim_dir(), session.settings()?.shim_jobs())?;
+
+    info!(
+        "{} updated Volta from {} to {}",
+        success_prefix(),
+        current,
+        installed
+    );
+    Ok(())
+}
@@ -1,6 +1,8 @@
 use std::env;
 use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 
+use serde::Serialize;
 use which::which_in;
 
 use volta_core::error::{Context, ErrorKind, ExitCode, Fallible};
@@ -8,68 +10,200 @@ use volta_core::platform::{Platform, System};
 use volta_core::run::binary::DefaultBinary;
 use volta_core::session::{ActivityKind, Session};
 
-use crate::command::Command;
+use crate::command::lock::check_platform_lock;
+use crate::command::platform_json::JsonPlatform;
+use crate::command::{to_json, Command};
 
 #[derive(clap::Args)]
 pub(crate) struct Which {
+    /// Output JSON, including the platform the binary runs with
+    #[arg(long)]
+    json: bool,
+
     /// The binary to find, e.g. `node` or `npm`
     binary: OsString,
 }
 
+/// A binary that was found, along with the platform it runs with (if it's managed by Volta)
+struct Found {
+    path: PathBuf,
+    platform: Option<JsonPlatform>,
+    source: Option<String>,
+    /// The tool that provides the binary, if it hasn't been fetched yet
+    unfetched: Option<String>,
+}
+
+/// The version of the JSON output, which changes whenever its shape does
+const JSON_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonFound<'a> {
+    json_version: u32,
+    path: &'a Path,
+    installed: bool,
+    source: Option<&'a str>,
+    platform: Option<&'a JsonPlatform>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonError {
+    json_version: u32,
+    error: String,
+    /// The slug of the documentation for the error, when it comes from Volta
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slug: Option<&'static str>,
+}
+
 impl Command for Which {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_subcommand_start(ActivityKind::Which);
+
+        let exit_code = if self.json {
+            // Failures are reported on stdout as well, so callers only need to parse one stream
+            let found = self
+                .find(session)
+                .and_then(|found| found.as_ref().map(format_found).transpose());
+            let (output, exit_code) = match found {
+                Ok(Some(output)) => (Ok(output), ExitCode::Success),
+                Ok(None) => (
+                    format_error(
+                        format!("Could not find `{}`", self.binary.to_string_lossy()),
+                        None,
+                    ),
+                    ExitCode::UnknownError,
+                ),
+                Err(error) => {
+                    session.add_event_error(ActivityKind::Which, &error);
+                    (
+                        format_error(error.to_string(), Some(error.kind().slug())),
+                        error.exit_code(),
+                    )
+                }
+            };
+            println!("{}", output?);
+            exit_code
+        } else {
+            match self.find(session)? {
+                Some(found) => {
+                    match &found.unfetched {
+                        Some(tool) => println!(
+                            "{} (not yet installed — run 'volta install'/'volta fetch' for {})",
+                            found.path.to_string_lossy(),
+                            tool
+                        ),
+                        None => println!("{}", found.path.to_string_lossy()),
+                    }
+                    ExitCode::Success
+                }
+                // If the binary can't be found, we don't want to print anything out, but we want
+                // to return Exit Code 1 (ExitCode::UnknownError)
+                None => ExitCode::UnknownError,
+            }
+        };
+
+        session.add_event_end(ActivityKind::Which, exit_code);
+        Ok(exit_code)
+    }
+}
+
+impl Which {
     // 1. Start by checking if the user has a tool installed in the project or
     //    as a user default. If so, we're done.
     // 2. Otherwise, use the platform image and/or the system environment to
     //    determine a lookup path to run `which` in.
-    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
-        session.add_event_start(ActivityKind::Which);
+    fn find(&self, session: &mut Session) -> Fallible<Option<Found>> {
+        check_platform_lock(session)?;
 
         let default_tool = DefaultBinary::from_name(&self.binary, session)?;
         let project_bin_path = session
             .project()?
             .and_then(|project| project.find_bin(&self.binary));
 
-        let tool_path = match (default_tool, project_bin_path) {
-            (Some(_), Some(bin_path)) => Some(bin_path),
-            (Some(tool), _) => Some(tool.bin_path),
-            _ => None,
-        };
-
-        if let Some(path) = tool_path {
-            println!("{}", path.to_string_lossy());
-
-            let exit_code = ExitCode::Success;
-            session.add_event_end(ActivityKind::Which, exit_code);
-            return Ok(exit_code);
+        match (default_tool, project_bin_path) {
+            (Some(_), Some(bin_path)) => {
+                // The platform is only reported in the JSON output, so it isn't an error if it
+                // can't be determined
+                let platform = if self.json {
+                    Platform::current(session).unwrap_or(None)
+                } else {
+                    None
+                };
+                return Ok(Some(Found {
+                    path: bin_path,
+                    source: platform.as_ref().map(|p| p.node.source.to_string()),
+                    platform: platform.as_ref().map(JsonPlatform::from),
+                    unfetched: None,
+                }));
+            }
+            (Some(tool), _) => {
+                return Ok(Some(Found {
+                    path: tool.bin_path,
+                    source: Some(tool.platform.node.source.to_string()),
+                    platform: Some(JsonPlatform::from(&tool.platform)),
+                    unfetched: None,
+                }));
+            }
+            _ => {}
         }
 
-        // Treat any error with obtaining the current platform image as if the image doesn't exist
-        // However, errors in obtaining the current working directory or the System path should
-        // still be treated as errors.
-        let path = match Platform::current(session)
+        // The platform is never checked out, since finding a binary shouldn't fetch its tool.
+        // Treat any error with obtaining the current platform as if it doesn't exist. However,
+        // errors in obtaining the current working directory or the System path should still be
+        // treated as errors.
+        let image = Platform::current(session)
             .unwrap_or(None)
-            .and_then(|platform| platform.checkout(session).ok())
-            .and_then(|image| image.path().ok())
-        {
-            Some(path) => path,
-            None => System::path()?,
-        };
+            .map(Platform::into_image)
+            .and_then(|image| Some((image.path().ok()?, image)));
+        let (path, source, platform) = match image {
+            Some((path, image)) => {
+                let source = Some(image.node.source.to_string());
+                let platform = Some(JsonPlatform::from(&image));
 
-        let cwd = env::current_dir().with_context(|| ErrorKind::CurrentDirError)?;
-        let exit_code = match which_in(&self.binary, Some(path), cwd) {
-            Ok(result) => {
-                println!("{}", result.to_string_lossy());
-                ExitCode::Success
-            }
-            Err(_) => {
-                // `which_in` Will return an Err if it can't find the binary in the path
-                // In that case, we don't want to print anything out, but we want to return
-                // Exit Code 1 (ExitCode::UnknownError)
-                ExitCode::UnknownError
+                if let Some(unfetched) = image.unfetched(&self.binary)? {
+                    return Ok(Some(Found {
+                        path: unfetched.path,
+                        source,
+                        platform,
+                        unfetched: Some(unfetched.tool),
+                    }));
+                }
+
+                (path, source, platform)
             }
+            None => (System::path()?, None, None),
         };
 
-        session.add_event_end(ActivityKind::Which, exit_code);
-        Ok(exit_code)
+        let cwd = env::current_dir().with_context(|| ErrorKind::CurrentDirError)?;
+        // `which_in` Will return an Err if it can't find the binary in the path
+        Ok(which_in(&self.binary, Some(path), cwd)
+            .ok()
+            .map(|path| Found {
+                path,
+                source,
+                platform,
+                unfetched: None,
+            }))
     }
 }
+
+fn format_found(found: &Found) -> Fallible<String> {
+    let document = JsonFound {
+        json_version: JSON_VERSION,
+        path: &found.path,
+        installed: found.unfetched.is_none(),
+        source: found.source.as_deref(),
+        platform: found.platform.as_ref(),
+    };
+
+    to_json(&document)
+}
+
+fn format_error(error: String, slug: Option<&'static str>) -> Fallible<String> {
+    to_json(&JsonError {
+        json_version: JSON_VERSION,
+        error,
+        slug,
+    })
+}
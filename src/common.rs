@@ -1,11 +1,14 @@
+use std::io;
 use std::process::{Command, ExitStatus};
 
 use volta_core::error::{Context, ErrorKind, VoltaError};
 use volta_core::layout::{volta_home, volta_install};
+use volta_core::log::is_porcelain;
+use volta_core::style::progress_enabled;
 
 pub enum Error {
     Volta(VoltaError),
-    Tool(i32),
+    Tool(ExitStatus),
 }
 
 pub fn ensure_layout() -> Result<(), Error> {
@@ -13,8 +16,17 @@ pub fn ensure_layout() -> Result<(), Error> {
 
     if !home.layout_file().exists() {
         let install = volta_install().map_err(Error::Volta)?;
-        Command::new(install.migrate_executable())
-            .env("VOLTA_LOGLEVEL", format!("{}", log::max_level()))
+        let mut migrate = Command::new(install.migrate_executable());
+        migrate.env("VOLTA_LOGLEVEL", format!("{}", log::max_level()));
+        // The migration regenerates the shims, which shows progress unless it was turned off
+        if !progress_enabled() {
+            migrate.env("VOLTA_NO_PROGRESS", "1");
+        }
+        // Its messages mustn't mix with the lines of `--porcelain`
+        if is_porcelain() {
+            migrate.stdout(io::stderr());
+        }
+        migrate
             .status()
             .with_context(|| ErrorKind::CouldNotStartMigration)
             .into_result()?;
@@ -34,8 +46,7 @@ impl IntoResult<()> for Result<ExitStatus, VoltaError> {
                 if status.success() {
                     Ok(())
                 } else {
-                    let code = status.code().unwrap_or(1);
-                    Err(Error::Tool(code))
+                    Err(Error::Tool(status))
                 }
             }
             Err(err) => Err(Error::Volta(err)),
@@ -4,9 +4,14 @@ mod cli;
 
 use clap::Parser;
 
+use volta_core::download::set_max_concurrent_downloads;
 use volta_core::error::report_error;
-use volta_core::log::{LogContext, LogVerbosity, Logger};
+use volta_core::log::{set_porcelain, LogContext, LogVerbosity, Logger};
+use volta_core::notion;
+use volta_core::project::set_frozen;
 use volta_core::session::{ActivityKind, Session};
+use volta_core::signal::tool_exit_code;
+use volta_core::style::disable_progress;
 
 mod common;
 use common::{ensure_layout, Error};
@@ -31,18 +36,45 @@ pub fn main() {
     Logger::init(LogContext::Volta, verbosity).expect("Only a single logger should be initialized");
     log::trace!("log level: {verbosity:?}");
 
+    if volta.no_progress {
+        disable_progress();
+    }
+
+    if volta.porcelain() {
+        set_porcelain();
+    }
+
+    if let Some(limit) = volta.max_concurrent_downloads {
+        set_max_concurrent_downloads(limit.into());
+    }
+
+    if volta.frozen {
+        set_frozen();
+    }
+
     let mut session = Session::init();
+    if let Some(mode) = volta.install_mode {
+        session.set_install_mode(mode);
+    }
     session.add_event_start(ActivityKind::Volta);
 
-    let result = ensure_layout().and_then(|()| volta.run(&mut session).map_err(Error::Volta));
+    let layout = if volta.needs_layout() {
+        ensure_layout()
+    } else {
+        Ok(())
+    };
+    if layout.is_ok() && volta.mutates() {
+        notion::warn_weekly();
+    }
+    let result = layout.and_then(|()| volta.run(&mut session).map_err(Error::Volta));
     match result {
         Ok(exit_code) => {
             session.add_event_end(ActivityKind::Volta, exit_code);
             session.exit(exit_code);
         }
-        Err(Error::Tool(code)) => {
-            session.add_event_tool_end(ActivityKind::Volta, code);
-            session.exit_tool(code);
+        Err(Error::Tool(status)) => {
+            session.add_event_tool_end(ActivityKind::Volta, tool_exit_code(status));
+            session.exit_tool(status);
         }
         Err(Error::Volta(err)) => {
             report_error(env!("CARGO_PKG_VERSION"), &err);
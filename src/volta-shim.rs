@@ -5,7 +5,7 @@ use volta_core::error::{report_error, ExitCode};
 use volta_core::log::{LogContext, LogVerbosity, Logger};
 use volta_core::run::execute_shim;
 use volta_core::session::{ActivityKind, Session};
-use volta_core::signal::setup_signal_handler;
+use volta_core::signal::{setup_signal_handler, tool_exit_code};
 
 pub fn main() {
     Logger::init(LogContext::Shim, LogVerbosity::Default)
@@ -21,9 +21,9 @@ pub fn main() {
             session.add_event_end(ActivityKind::Tool, ExitCode::Success);
             session.exit(ExitCode::Success);
         }
-        Err(Error::Tool(code)) => {
-            session.add_event_tool_end(ActivityKind::Tool, code);
-            session.exit_tool(code);
+        Err(Error::Tool(status)) => {
+            session.add_event_tool_end(ActivityKind::Tool, tool_exit_code(status));
+            session.exit_tool(status);
         }
         Err(Error::Volta(err)) => {
             report_error(env!("CARGO_PKG_VERSION"), &err);
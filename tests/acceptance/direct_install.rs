@@ -1,4 +1,6 @@
-use crate::support::sandbox::{sandbox, DistroMetadata, NodeFixture, NpmFixture, Yarn1Fixture};
+use crate::support::sandbox::{
+    sandbox, DistroMetadata, NodeFixture, NpmFixture, Sandbox, SandboxBuilder, Yarn1Fixture,
+};
 use hamcrest2::assert_that;
 use hamcrest2::prelude::*;
 use test_support::matchers::execs;
@@ -411,3 +413,123 @@ fn yarn_global_with_override_does_not_intercept() {
             .with_stdout_does_not_contain("[..]using Volta to install npm")
     );
 }
+
+#[test]
+fn npm_global_with_unsafe_global_setting_does_not_intercept() {
+    let s = sandbox()
+        .platform(&platform_with_node("10.99.1040"))
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .file(
+            ".volta/settings.json",
+            r#"{ "install": { "unsafeGlobal": true } }"#,
+        )
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.npm("install --global npm@8"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_does_not_contain("[..]using Volta to install npm")
+    );
+}
+
+/// Records each run of npm, with the prefix that it installs into, and installs cowsay there
+#[cfg(unix)]
+fn recording_npm_bin() -> String {
+    format!(
+        r#"#!/bin/sh
+echo "$@ (prefix: $npm_config_prefix)" >> "{}"
+if [ -n "$npm_config_prefix" ]; then
+    dest="$npm_config_prefix/lib/node_modules/cowsay"
+    /bin/mkdir -p "$dest"
+    echo '{{"name": "cowsay", "version": "1.4.0"}}' > "$dest/package.json"
+fi
+"#,
+        Sandbox::home_path("npm-runs").display()
+    )
+}
+
+#[cfg(unix)]
+fn npm_runs() -> Vec<String> {
+    std::fs::read_to_string(Sandbox::home_path("npm-runs"))
+        .expect("npm should have been run")
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(unix)]
+fn unmanaged_globals_sandbox(unmanaged: &str) -> SandboxBuilder {
+    sandbox()
+        .platform(
+            r#"{
+  "node": {
+    "runtime": "11.10.1",
+    "npm": "6.7.0"
+  },
+  "pnpm": null,
+  "yarn": null
+}"#,
+        )
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", &recording_npm_bin())
+        .file(
+            ".volta/settings.json",
+            &format!(
+                r#"{{ "install": {{ "unmanagedGlobals": {} }} }}"#,
+                unmanaged
+            ),
+        )
+        .env("VOLTA_LOGLEVEL", "info")
+}
+
+#[test]
+#[cfg(unix)]
+fn npm_global_install_of_unmanaged_package_is_not_intercepted() {
+    let s = unmanaged_globals_sandbox(r#"["corepack"]"#).build();
+
+    assert_that!(
+        s.npm("i -g corepack@0.20"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(
+            "Installing corepack with npm directly, as it is listed in `install.unmanagedGlobals`"
+        )
+    );
+    assert_eq!(npm_runs(), ["i -g corepack@0.20 (prefix: )"]);
+}
+
+#[test]
+#[cfg(unix)]
+fn npm_global_install_of_other_package_is_intercepted() {
+    let s = unmanaged_globals_sandbox(r#"["corepack", "@company/*"]"#).build();
+
+    assert_that!(
+        s.npm("i -g cowsay"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_does_not_contain("[..]directly[..]")
+    );
+    let runs = npm_runs();
+    assert_eq!(runs.len(), 1);
+    assert!(runs[0].starts_with("i -g cowsay (prefix: /"));
+}
+
+#[test]
+#[cfg(unix)]
+fn npm_global_install_matches_unmanaged_scopes() {
+    let s = unmanaged_globals_sandbox(r#"["@company/*"]"#).build();
+
+    assert_that!(
+        s.npm("i -g @company/cli@2 cowsay"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("Installing @company/cli with npm directly[..]")
+            .with_stdout_does_not_contain("Installing cowsay with npm directly[..]")
+    );
+    let runs = npm_runs();
+    assert_eq!(runs.len(), 2);
+    assert!(runs[0].starts_with("i -g cowsay (prefix: /"));
+    assert_eq!(runs[1], "i -g @company/cli@2 (prefix: )");
+}
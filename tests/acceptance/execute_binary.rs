@@ -5,6 +5,7 @@ use cfg_if::cfg_if;
 use hamcrest2::assert_that;
 use hamcrest2::prelude::*;
 use test_support::matchers::execs;
+use volta_core::error::ExitCode;
 
 const PKG_CONFIG_BASIC: &str = r#"{
   "name": "cowsay",
@@ -345,3 +346,349 @@ fn project_local_binary_pnp() {
             .with_stdout_does_not_contain("Yarn version 1.23.483")
     );
 }
+
+/// A bin that prints the path it was run from, to compare against `volta run --where`
+#[cfg(unix)]
+fn echo_path_bin_info() -> Vec<PackageBinInfo> {
+    vec![PackageBinInfo {
+        name: "cowsay".to_string(),
+        contents: "#!/bin/sh\necho \"$0\"\n".to_string(),
+    }]
+}
+
+#[cfg(unix)]
+#[test]
+fn where_finds_default_binary() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE_NPM)
+        .package_config("cowsay", PKG_CONFIG_BASIC)
+        .binary_config("cowsay", &bin_config("cowsay"))
+        .package_image("cowsay", "1.4.0", Some(echo_path_bin_info()))
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin("11.10.1"))
+        .setup_npm_binary("6.7.0", &npm_bin("6.7.0"))
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    let located = s.volta("run --where cowsay").exec_with_output().unwrap();
+    let ran = s.volta("run cowsay").exec_with_output().unwrap();
+
+    assert_eq!(located.stdout, ran.stdout);
+    assert!(String::from_utf8(located.stdout)
+        .unwrap()
+        .trim_end()
+        .ends_with("tools/image/packages/cowsay/bin/cowsay"));
+}
+
+#[cfg(unix)]
+#[test]
+fn where_finds_project_local_binary() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE_NPM)
+        .package_json(PACKAGE_JSON_NPM_WITH_DEP)
+        .package_config("cowsay", PKG_CONFIG_BASIC)
+        .binary_config("cowsay", &bin_config("cowsay"))
+        .package_image("cowsay", "1.4.0", Some(echo_path_bin_info()))
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin("11.10.1"))
+        .setup_node_binary("10.99.1040", "6.7.0", &node_bin("10.99.1040"))
+        .setup_npm_binary("6.7.0", &npm_bin("6.7.0"))
+        .project_bins(echo_path_bin_info())
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    let located = s.volta("run --where cowsay").exec_with_output().unwrap();
+    let ran = s.volta("run cowsay").exec_with_output().unwrap();
+
+    assert_eq!(located.stdout, ran.stdout);
+    assert!(String::from_utf8(located.stdout)
+        .unwrap()
+        .trim_end()
+        .ends_with("node_modules/.bin/cowsay"));
+}
+
+/// Sets up the cowsay shim to run a script, with everything it needs to be found by default
+#[cfg(unix)]
+fn sandbox_with_cowsay_script(script: &str) -> crate::support::sandbox::Sandbox {
+    sandbox()
+        .platform(PLATFORM_NODE_NPM)
+        .package_config("cowsay", PKG_CONFIG_BASIC)
+        .binary_config("cowsay", &bin_config("cowsay"))
+        .shim("cowsay")
+        .package_image(
+            "cowsay",
+            "1.4.0",
+            Some(vec![PackageBinInfo {
+                name: "cowsay".to_string(),
+                contents: script.to_string(),
+            }]),
+        )
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin("11.10.1"))
+        .setup_npm_binary("6.7.0", &npm_bin("6.7.0"))
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build()
+}
+
+#[cfg(unix)]
+#[test]
+fn shim_is_terminated_by_the_same_signal_as_the_tool() {
+    use std::os::unix::process::ExitStatusExt;
+
+    // The number of SIGTERM is the same on every Unix that Volta supports
+    const SIGTERM: i32 = 15;
+
+    let s = sandbox_with_cowsay_script("#!/bin/sh\nkill -TERM $$\n");
+    let status = s.exec_shim("cowsay", "").build_command().status().unwrap();
+
+    assert_eq!(status.signal(), Some(SIGTERM));
+    assert_eq!(status.code(), None);
+}
+
+#[cfg(unix)]
+#[test]
+fn shim_exits_with_the_code_of_the_tool() {
+    let s = sandbox_with_cowsay_script("#!/bin/sh\nexit 3\n");
+    let status = s.exec_shim("cowsay", "").build_command().status().unwrap();
+
+    assert_eq!(status.code(), Some(3));
+}
+
+/// Sets up a project pinned to Node 10.99.1040, whose `cowsay` dependency is linked into
+/// `node_modules/.bin` the way package managers install it
+#[cfg(unix)]
+fn sandbox_with_linked_cowsay(
+    engine_strict: Option<bool>,
+    engines: Option<&str>,
+) -> crate::support::sandbox::Sandbox {
+    use std::fs;
+    use std::os::unix::fs::symlink;
+    use volta_core::fs::set_executable;
+
+    let engine_strict = match engine_strict {
+        Some(strict) => format!(r#", "engineStrict": {}"#, strict),
+        None => String::new(),
+    };
+    let package_json = format!(
+        r#"{{
+    "name": "with-deps",
+    "dependencies": {{ "cowsay": "1.5.0" }},
+    "volta": {{ "node": "10.99.1040"{} }}
+}}"#,
+        engine_strict
+    );
+    let engines = match engines {
+        Some(node) => format!(r#", "engines": {{ "node": "{}" }}"#, node),
+        None => String::new(),
+    };
+
+    let s = sandbox()
+        .platform(PLATFORM_NODE_NPM)
+        .package_json(&package_json)
+        .package_config("cowsay", PKG_CONFIG_BASIC)
+        .binary_config("cowsay", &bin_config("cowsay"))
+        .shim("cowsay")
+        .package_image("cowsay", "1.4.0", Some(cowsay_bin_info("1.4.0")))
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin("11.10.1"))
+        .setup_node_binary("10.99.1040", "6.7.0", &node_bin("10.99.1040"))
+        .setup_npm_binary("6.7.0", &npm_bin("6.7.0"))
+        .add_dir_to_path(PathBuf::from("/bin"))
+        // Warnings are only shown on a terminal by default
+        .env("VOLTA_LOGLEVEL", "warn")
+        .build();
+
+    let modules = s.root().join("node_modules");
+    let package = modules.join("cowsay");
+    fs::create_dir_all(package.join("bin")).unwrap();
+    fs::create_dir_all(modules.join(".bin")).unwrap();
+    fs::write(
+        package.join("package.json"),
+        format!(r#"{{ "name": "cowsay", "version": "1.5.0"{} }}"#, engines),
+    )
+    .unwrap();
+    fs::write(package.join("bin/cowsay"), cowsay_bin("cowsay", "1.5.0")).unwrap();
+    set_executable(&package.join("bin/cowsay")).unwrap();
+    symlink("../cowsay/bin/cowsay", modules.join(".bin/cowsay")).unwrap();
+
+    s
+}
+
+#[cfg(unix)]
+#[test]
+fn engine_strict_runs_compatible_dependency() {
+    let s = sandbox_with_linked_cowsay(Some(true), Some(">=10.0.0"));
+
+    assert_that!(
+        s.exec_shim("cowsay", "moo"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("cowsay version 1.5.0")
+            .with_stderr_does_not_contain("[..]requires Node[..]")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn engine_strict_rejects_incompatible_dependency() {
+    let s = sandbox_with_linked_cowsay(Some(true), Some("^18 || >=20"));
+
+    assert_that!(
+        s.exec_shim("cowsay", "moo"),
+        execs()
+            .with_status(ExitCode::ExecutionFailure as i32)
+            .with_stderr_contains(
+                "[..]'cowsay' requires Node ^18 || >=20, which doesn't match Node 10.99.1040 used by this project."
+            )
+            .with_stdout_does_not_contain("cowsay version[..]")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn incompatible_dependency_warns_without_engine_strict() {
+    let s = sandbox_with_linked_cowsay(None, Some("^18 || >=20"));
+
+    assert_that!(
+        s.exec_shim("cowsay", "moo"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("cowsay version 1.5.0")
+            .with_stderr_contains(
+                "[..]'cowsay' requires Node ^18 || >=20[..]`volta.engineStrict`[..]"
+            )
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn engine_strict_false_skips_check() {
+    let s = sandbox_with_linked_cowsay(Some(false), Some("^18 || >=20"));
+
+    assert_that!(
+        s.exec_shim("cowsay", "moo"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("cowsay version 1.5.0")
+            .with_stderr_does_not_contain("[..]requires Node[..]")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn engine_strict_runs_dependency_without_engines() {
+    let s = sandbox_with_linked_cowsay(Some(true), None);
+
+    assert_that!(
+        s.exec_shim("cowsay", "moo"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("cowsay version 1.5.0")
+            .with_stderr_does_not_contain("[..]requires Node[..]")
+    );
+}
+
+const PACKAGE_JSON_PNPM_WITH_DEP: &str = r#"{
+    "name": "pnpm-project",
+    "dependencies": { "cowsay": "1.5.0" },
+    "volta": { "node": "10.99.1040", "pnpm": "7.7.1" }
+}"#;
+
+/// Sets up a pnpm project that depends on cowsay, with a global cowsay installed as well
+#[cfg(unix)]
+fn sandbox_with_pnpm_project() -> crate::support::sandbox::Sandbox {
+    sandbox()
+        .platform(PLATFORM_NODE_NPM)
+        .package_json(PACKAGE_JSON_PNPM_WITH_DEP)
+        .project_file("pnpm-lock.yaml", "lockfileVersion: '5.4'\n")
+        .package_config("cowsay", PKG_CONFIG_BASIC)
+        .binary_config("cowsay", &bin_config("cowsay"))
+        .shim("cowsay")
+        .package_image("cowsay", "1.4.0", Some(cowsay_bin_info("1.4.0")))
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin("11.10.1"))
+        .setup_node_binary("10.99.1040", "6.7.0", &node_bin("10.99.1040"))
+        .setup_npm_binary("6.7.0", &npm_bin("6.7.0"))
+        .setup_pnpm_binary("7.7.1", &pnpm_bin("7.7.1"))
+        .env("VOLTA_FEATURE_PNPM", "1")
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build()
+}
+
+#[cfg(unix)]
+#[test]
+fn project_local_binary_in_pnpm_virtual_store() {
+    use std::fs;
+    use volta_core::fs::set_executable;
+
+    let s = sandbox_with_pnpm_project();
+    let store_bin = s.root().join("node_modules/.pnpm/node_modules/.bin");
+    fs::create_dir_all(&store_bin).unwrap();
+    fs::write(store_bin.join("cowsay"), cowsay_bin("cowsay", "1.5.0")).unwrap();
+    set_executable(&store_bin.join("cowsay")).unwrap();
+
+    assert_that!(
+        s.exec_shim("cowsay", "moo"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("cowsay version 1.5.0")
+            .with_stdout_contains("cowsay args: moo")
+            .with_stdout_does_not_contain("pnpm version")
+    );
+
+    let located = s.volta("run --where cowsay").exec_with_output().unwrap();
+    assert!(String::from_utf8(located.stdout)
+        .unwrap()
+        .trim_end()
+        .ends_with("node_modules/.pnpm/node_modules/.bin/cowsay"));
+
+    assert_that!(
+        s.volta("which cowsay"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("[..]node_modules/.pnpm/node_modules/.bin/cowsay")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn project_local_binary_with_pnpm_exec() {
+    let s = sandbox_with_pnpm_project();
+    std::fs::create_dir_all(s.root().join("node_modules/.pnpm")).unwrap();
+
+    assert_that!(
+        s.exec_shim("cowsay", "moo"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("pnpm version 7.7.1")
+            .with_stdout_contains("pnpm args: exec cowsay moo")
+            .with_stdout_does_not_contain("cowsay version")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn project_local_binary_with_only_pnpm_lockfile() {
+    let s = sandbox_with_pnpm_project();
+
+    assert_that!(
+        s.exec_shim("cowsay", "moo"),
+        execs()
+            .with_status(ExitCode::ExecutionFailure as i32)
+            .with_stderr_contains("[..]Could not locate executable `cowsay` in your project.")
+            .with_stderr_contains("[..]`pnpm install`")
+            .with_stdout_does_not_contain("pnpm args:[..]")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn project_local_binary_not_found_through_pnpm_exec() {
+    let s = sandbox_with_pnpm_project();
+    std::fs::create_dir_all(s.root().join("node_modules/.pnpm")).unwrap();
+
+    // pnpm exec looked cowsay up on the PATH and found its shim again
+    assert_that!(
+        s.exec_shim("cowsay", "moo")
+            .env("_VOLTA_PNPM_EXEC", "cowsay"),
+        execs()
+            .with_status(ExitCode::ExecutionFailure as i32)
+            .with_stderr_contains("[..]Could not locate executable `cowsay` in your project.")
+            .with_stdout_does_not_contain("pnpm args:[..]")
+    );
+}
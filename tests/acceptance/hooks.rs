@@ -2,14 +2,18 @@ use std::path::PathBuf;
 use std::{thread, time};
 
 use crate::support::events_helpers::{
-    assert_events, match_args, match_end, match_error, match_start,
+    assert_events, match_args, match_end, match_error, match_start, read_events,
+};
+use crate::support::sandbox::{
+    sandbox, DistroMetadata, NodeFixture, NpmFixture, Sandbox, SandboxBuilder,
 };
-use crate::support::sandbox::sandbox;
 use hamcrest2::assert_that;
 use hamcrest2::prelude::*;
 use mockito::mock;
+use node_semver::Version;
 use test_support::matchers::execs;
 use volta_core::error::ExitCode;
+use volta_core::event::EventKind;
 
 const WORKSPACE_PACKAGE_JSON: &str = r#"
 {
@@ -137,6 +141,122 @@ fn pnpm_hooks_json() -> String {
     )
 }
 
+fn npm_distro_hooks_json(distro: &str) -> String {
+    format!(
+        r#"
+{{
+    "npm": {{
+        "distro": {{
+            {}
+        }}
+    }}
+}}"#,
+        distro
+    )
+}
+
+fn platform_with_node(node: &str) -> String {
+    format!(
+        r#"{{
+  "node": {{
+    "runtime": "{}",
+    "npm": null
+  }}
+}}"#,
+        node
+    )
+}
+
+const NODE_VERSION_INFO: &str = r#"[
+{"version":"v10.99.1040","npm":"6.2.26","lts": "Dubnium","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]}
+]
+"#;
+
+const NPM_VERSION_INFO: &str = r#"
+{
+    "name":"npm",
+    "dist-tags": { "latest":"8.1.5" },
+    "versions": {
+        "8.1.5": { "version":"8.1.5", "dist": { "shasum":"", "tarball":"" }}
+    }
+}
+"#;
+
+const NPM_8_1_5: DistroMetadata = DistroMetadata {
+    version: "8.1.5",
+    compressed_size: 239,
+    uncompressed_size: Some(0x0028_0000),
+};
+
+// The archive served for Node 10.99.1040, along with its SHA-256 checksum
+cfg_if::cfg_if! {
+    if #[cfg(target_os = "macos")] {
+        const NODE_ARCHIVE: &str = "node-v10.99.1040-darwin-x64.tar.gz";
+        const NODE_ARCHIVE_SHA256: &str =
+            "8ff10106f53460b2963eff7ead6318c15ab6f24cb8bfd10d061f927ddb267efe";
+        const NODE_VERSION_FIXTURES: [DistroMetadata; 1] = [DistroMetadata {
+            version: "10.99.1040",
+            compressed_size: 273,
+            uncompressed_size: Some(0x0028_0000),
+        }];
+    } else if #[cfg(all(target_os = "linux", target_arch = "aarch64"))] {
+        const NODE_ARCHIVE: &str = "node-v10.99.1040-linux-arm64.tar.gz";
+        const NODE_ARCHIVE_SHA256: &str =
+            "e6a20898afc160e7443d964b57d01963684ddcd7b55ab7ebe9bfc32f932c9e44";
+        const NODE_VERSION_FIXTURES: [DistroMetadata; 1] = [DistroMetadata {
+            version: "10.99.1040",
+            compressed_size: 273,
+            uncompressed_size: Some(0x0028_0000),
+        }];
+    } else if #[cfg(target_os = "linux")] {
+        const NODE_ARCHIVE: &str = "node-v10.99.1040-linux-x64.tar.gz";
+        const NODE_ARCHIVE_SHA256: &str =
+            "76d9bb60fec9fec51567cfd7b4db23431d5ccd53329eead61358519e503e3844";
+        const NODE_VERSION_FIXTURES: [DistroMetadata; 1] = [DistroMetadata {
+            version: "10.99.1040",
+            compressed_size: 273,
+            uncompressed_size: Some(0x0028_0000),
+        }];
+    } else if #[cfg(windows)] {
+        const NODE_ARCHIVE: &str = "node-v10.99.1040-win-x64.zip";
+        const NODE_ARCHIVE_SHA256: &str =
+            "883112d3b58714565fa119124ed085ee94887782379dcdeb6e0227afccc09a80";
+        const NODE_VERSION_FIXTURES: [DistroMetadata; 1] = [DistroMetadata {
+            version: "10.99.1040",
+            compressed_size: 1096,
+            uncompressed_size: None,
+        }];
+    }
+}
+
+fn shasums_template_hooks_json() -> String {
+    format!(
+        r#"
+{{
+    "node": {{
+        "shasums": {{
+            "template": "{0}/attested/v{{{{version}}}}/{{{{filename}}}}"
+        }}
+    }}
+}}"#,
+        mockito::server_url()
+    )
+}
+
+fn shasums_bin_hooks_json(bin: &str) -> String {
+    format!(
+        r#"
+{{
+    "node": {{
+        "shasums": {{
+            "bin": "{}"
+        }}
+    }}
+}}"#,
+        bin
+    )
+}
+
 fn yarn_hooks_json() -> String {
     format!(
         r#"
@@ -205,6 +325,69 @@ fn redirects_download() {
     );
 }
 
+#[test]
+fn publishes_duration_and_summary_of_the_session() {
+    let s = sandbox()
+        .default_hooks(&default_hooks_json())
+        .env("VOLTA_WRITE_EVENTS_FILE", "true")
+        .executable_file(SCRIPT_FILENAME, EVENTS_EXECUTABLE)
+        .build();
+
+    assert_that!(
+        s.volta("install node@1.2.3"),
+        execs().with_status(ExitCode::NetworkError as i32)
+    );
+    thread::sleep(time::Duration::from_millis(500));
+
+    let events = read_events(&s);
+    match &events[2].event {
+        EventKind::Error { code, slug, .. } => {
+            assert_eq!(code, "VOLTA_DOWNLOAD_TOOL_NETWORK_ERROR");
+            assert_eq!(slug, "download-tool-network-error");
+        }
+        other => panic!("Expected: Error, Got: {:?}", other),
+    }
+    assert_that!(&events[3].name, eq("volta"));
+    assert!(matches!(
+        events[3].event,
+        EventKind::End {
+            exit_code: 5,
+            duration_ms: Some(_)
+        }
+    ));
+    match &events[4].event {
+        EventKind::Args {
+            subcommand,
+            arg_count,
+            fetched,
+            ..
+        } => {
+            assert_eq!(subcommand.as_deref(), Some("install"));
+            assert_eq!(*arg_count, 2);
+            assert!(*fetched);
+        }
+        other => panic!("Expected: Args, Got: {:?}", other),
+    }
+
+    assert_that!(s.volta("list"), execs().with_status(0));
+    thread::sleep(time::Duration::from_millis(500));
+
+    let events = read_events(&s);
+    match &events[events.len() - 1].event {
+        EventKind::Args {
+            subcommand,
+            arg_count,
+            fetched,
+            ..
+        } => {
+            assert_eq!(subcommand.as_deref(), Some("list"));
+            assert_eq!(*arg_count, 1);
+            assert!(!*fetched);
+        }
+        other => panic!("Expected: Args, Got: {:?}", other),
+    }
+}
+
 #[test]
 fn merges_project_and_default_hooks() {
     let local_hooks: PathBuf = [".volta", "hooks.json"].iter().collect();
@@ -512,3 +695,510 @@ fn yarn_semver_with_hook_uses_configured_format() {
             .with_stderr_contains("[..]Could not download yarn@3.12.99")
     );
 }
+
+#[test]
+fn node_shasums_template_hook_verifies_download() {
+    let s = sandbox()
+        .default_hooks(&shasums_template_hooks_json())
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+    let shasums = mock("GET", "/attested/v10.99.1040/SHASUMS256.txt")
+        .with_status(200)
+        .with_body(format!(
+            "0000000000000000000000000000000000000000000000000000000000000000  node-v10.99.1040.pkg\n{}  {}\n",
+            NODE_ARCHIVE_SHA256, NODE_ARCHIVE
+        ))
+        .create();
+
+    assert_that!(
+        s.volta("install node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Using node.shasums hook to determine the checksum file")
+            .with_stderr_contains("[..]Verified the checksum of the Node archive[..]")
+    );
+
+    shasums.assert();
+    assert!(Sandbox::node_image_exists("10.99.1040"));
+}
+
+#[test]
+fn node_shasums_template_hook_rejects_mismatch() {
+    let s = sandbox()
+        .default_hooks(&shasums_template_hooks_json())
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+    let _shasums = mock("GET", "/attested/v10.99.1040/SHASUMS256.txt")
+        .with_status(200)
+        .with_body(format!(
+            "0000000000000000000000000000000000000000000000000000000000000000  {}\n",
+            NODE_ARCHIVE
+        ))
+        .create();
+
+    assert_that!(
+        s.volta("install node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::NetworkError as i32)
+            .with_stderr_contains(
+            "[..]The downloaded archive for Node 10.99.1040 does not match its published checksum"
+        )
+    );
+
+    assert!(!Sandbox::node_image_exists("10.99.1040"));
+}
+
+#[test]
+fn node_shasums_template_hook_requires_listed_archive() {
+    let s = sandbox()
+        .default_hooks(&shasums_template_hooks_json())
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+    let _shasums = mock("GET", "/attested/v10.99.1040/SHASUMS256.txt")
+        .with_status(200)
+        .with_body("")
+        .create();
+
+    assert_that!(
+        s.volta("install node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Could not find the checksum for node-v10.99.1040[..]")
+    );
+}
+
+/// Serves the Node archive at its public path, without the checksum file that the sandbox would
+/// otherwise serve alongside it
+fn public_node_archive_without_shasums(s: SandboxBuilder) -> SandboxBuilder {
+    s.distro_mock_at::<NodeFixture>(
+        &NODE_VERSION_FIXTURES[0],
+        &format!("/v10.99.1040/{}", NODE_ARCHIVE),
+    )
+}
+
+#[test]
+fn node_checksums_are_required_from_public_server() {
+    let s =
+        public_node_archive_without_shasums(sandbox().node_available_versions(NODE_VERSION_INFO))
+            .build();
+
+    assert_that!(
+        s.volta("install node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::NetworkError as i32)
+            .with_stderr_contains("[..]Could not download the Node checksum file")
+            .with_stderr_contains("[..]/v10.99.1040/SHASUMS256.txt")
+    );
+
+    assert!(!Sandbox::node_image_exists("10.99.1040"));
+}
+
+#[test]
+fn node_checksums_from_public_server_must_list_archive() {
+    let s =
+        public_node_archive_without_shasums(sandbox().node_available_versions(NODE_VERSION_INFO))
+            .build();
+    let _shasums = mock("GET", "/v10.99.1040/SHASUMS256.txt")
+        .with_status(200)
+        .with_body("")
+        .create();
+
+    assert_that!(
+        s.volta("install node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Could not find the checksum for node-v10.99.1040[..]")
+    );
+}
+
+fn public_shasums_mock(sha256: &str) -> mockito::Mock {
+    mock("GET", "/v10.99.1040/SHASUMS256.txt")
+        .with_status(200)
+        .with_body(format!("{}  {}\n", sha256, NODE_ARCHIVE))
+        .create()
+}
+
+#[test]
+fn node_checksums_from_public_server_verify_download() {
+    let s =
+        public_node_archive_without_shasums(sandbox().node_available_versions(NODE_VERSION_INFO))
+            .env("VOLTA_LOGLEVEL", "debug")
+            .build();
+    let shasums = public_shasums_mock(NODE_ARCHIVE_SHA256);
+
+    assert_that!(
+        s.volta("install node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Verified the checksum of the Node archive[..]")
+    );
+
+    shasums.assert();
+    assert!(Sandbox::node_image_exists("10.99.1040"));
+}
+
+#[test]
+fn node_checksums_from_public_server_reject_mismatch() {
+    let s =
+        public_node_archive_without_shasums(sandbox().node_available_versions(NODE_VERSION_INFO))
+            .build();
+    let _shasums =
+        public_shasums_mock("0000000000000000000000000000000000000000000000000000000000000000");
+
+    assert_that!(
+        s.volta("install node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::NetworkError as i32)
+            .with_stderr_contains(
+                "[..]The downloaded archive for Node 10.99.1040 does not match its published checksum"
+            )
+            .with_stderr_contains(format!("  Actual SHA-256: {}", NODE_ARCHIVE_SHA256).as_str())
+            .with_stderr_contains("Please try again, the download may have been corrupted.")
+    );
+
+    assert!(!Sandbox::node_image_exists("10.99.1040"));
+    assert!(!s.node_inventory_archive_exists(&Version::parse("10.99.1040").unwrap()));
+}
+
+/// Installs Node with a corrupted archive in the inventory, which must be downloaded again
+fn install_over_corrupted_cache(s: SandboxBuilder) {
+    let s = public_node_archive_without_shasums(s.node_available_versions(NODE_VERSION_INFO))
+        .file(
+            &format!(".volta/tools/inventory/node/{}", NODE_ARCHIVE),
+            "not a node archive",
+        )
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+    let shasums = public_shasums_mock(NODE_ARCHIVE_SHA256);
+
+    assert_that!(
+        s.volta("install node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Removing cached archive at [..]")
+            .with_stderr_contains("[..]Verified the checksum of the Node archive[..]")
+    );
+
+    shasums.assert();
+    assert!(Sandbox::node_image_exists("10.99.1040"));
+    let recorded = std::fs::read_to_string(Sandbox::home_path(&format!(
+        ".volta/tools/inventory/node/{}.sha256",
+        NODE_ARCHIVE
+    )))
+    .unwrap();
+    assert_eq!(recorded, NODE_ARCHIVE_SHA256);
+}
+
+#[test]
+fn node_cached_archive_not_matching_recorded_checksum_is_downloaded_again() {
+    install_over_corrupted_cache(sandbox().file(
+        &format!(".volta/tools/inventory/node/{}.sha256", NODE_ARCHIVE),
+        NODE_ARCHIVE_SHA256,
+    ));
+}
+
+#[test]
+fn node_cached_archive_without_recorded_checksum_is_downloaded_again() {
+    install_over_corrupted_cache(sandbox());
+}
+
+fn node_distro_mirror_hooks_json() -> String {
+    format!(
+        r#"
+{{
+    "node": {{
+        "distro": {{
+            "template": "{0}/mirror/node/v{{{{version}}}}/{{{{filename}}}}"
+        }}
+    }}
+}}"#,
+        mockito::server_url()
+    )
+}
+
+#[test]
+fn node_checksums_are_downloaded_next_to_distro_hook_archive() {
+    let s = sandbox()
+        .default_hooks(&node_distro_mirror_hooks_json())
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mock_at::<NodeFixture>(
+            &NODE_VERSION_FIXTURES[0],
+            &format!("/mirror/node/v10.99.1040/{}", NODE_ARCHIVE),
+        )
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+    let shasums = mock("GET", "/mirror/node/v10.99.1040/SHASUMS256.txt")
+        .with_status(200)
+        .with_body(format!("{}  {}\n", NODE_ARCHIVE_SHA256, NODE_ARCHIVE))
+        .create();
+
+    assert_that!(
+        s.volta("install node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Using node.distro hook to determine download URL")
+            .with_stderr_contains("[..]Verified the checksum of the Node archive[..]")
+    );
+
+    shasums.assert();
+}
+
+#[test]
+fn node_checksums_are_skipped_when_distro_hook_mirror_lacks_them() {
+    let s = sandbox()
+        .default_hooks(&node_distro_mirror_hooks_json())
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mock_at::<NodeFixture>(
+            &NODE_VERSION_FIXTURES[0],
+            &format!("/mirror/node/v10.99.1040/{}", NODE_ARCHIVE),
+        )
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+
+    assert_that!(
+        s.volta("install node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains(
+                "[..]Downloading Node checksums from [..]/mirror/node/v10.99.1040/SHASUMS256.txt"
+            )
+            .with_stderr_contains("[..]Skipping checksum verification[..]")
+    );
+
+    assert!(Sandbox::node_image_exists("10.99.1040"));
+}
+
+#[test]
+#[cfg(unix)]
+fn node_shasums_bin_hook_provides_content() {
+    let script = format!(
+        "#!/bin/sh\n# The version is passed as the only argument\n[ \"$1\" = 10.99.1040 ] || exit 1\necho \"{}  {}\"\n",
+        NODE_ARCHIVE_SHA256, NODE_ARCHIVE
+    );
+    let s = sandbox()
+        .default_hooks(&shasums_bin_hooks_json("print-shasums.sh"))
+        .executable_file("print-shasums.sh", &script)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+
+    assert_that!(
+        s.volta("install node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Using node.shasums hook to determine the checksum file")
+            .with_stderr_contains("[..]Verified the checksum of the Node archive[..]")
+    );
+
+    assert!(Sandbox::node_image_exists("10.99.1040"));
+}
+
+#[test]
+fn npm_distro_template_hook_downloads_from_mirror() {
+    let hooks = npm_distro_hooks_json(&format!(
+        r#""template": "{}/mirror/npm/{{{{version}}}}""#,
+        mockito::server_url()
+    ));
+    let s = sandbox()
+        .platform(&platform_with_node("10.99.1040"))
+        .default_hooks(&hooks)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .npm_available_versions(NPM_VERSION_INFO)
+        .distro_mock_at::<NpmFixture>(&NPM_8_1_5, "/mirror/npm/8.1.5")
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+
+    assert_that!(
+        s.volta("install npm@8.1.5"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Using npm.distro hook to determine download URL")
+            .with_stderr_contains("[..]Downloading npm@8.1.5 from[..]/mirror/npm/8.1.5")
+    );
+}
+
+#[test]
+fn npm_distro_prefix_hook_downloads_from_mirror() {
+    let hooks = npm_distro_hooks_json(&format!(
+        r#""prefix": "{}/mirror/npm/""#,
+        mockito::server_url()
+    ));
+    let s = sandbox()
+        .platform(&platform_with_node("10.99.1040"))
+        .default_hooks(&hooks)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .npm_available_versions(NPM_VERSION_INFO)
+        .distro_mock_at::<NpmFixture>(&NPM_8_1_5, "/mirror/npm/npm-8.1.5.tgz")
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+
+    assert_that!(
+        s.volta("install npm@8.1.5"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Using npm.distro hook to determine download URL")
+            .with_stderr_contains("[..]Downloading npm@8.1.5 from[..]/mirror/npm/npm-8.1.5.tgz")
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn npm_distro_bin_hook_downloads_from_mirror() {
+    let script = format!(
+        "#!/bin/sh\n# The version is passed as the only argument\necho \"{}/mirror/npm/$1\"\n",
+        mockito::server_url()
+    );
+    let s = sandbox()
+        .platform(&platform_with_node("10.99.1040"))
+        .default_hooks(&npm_distro_hooks_json(r#""bin": "print-npm-url.sh""#))
+        .executable_file("print-npm-url.sh", &script)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .npm_available_versions(NPM_VERSION_INFO)
+        .distro_mock_at::<NpmFixture>(&NPM_8_1_5, "/mirror/npm/8.1.5")
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+
+    assert_that!(
+        s.volta("install npm@8.1.5"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Using npm.distro hook to determine download URL")
+            .with_stderr_contains("[..]Downloading npm@8.1.5 from[..]/mirror/npm/8.1.5")
+    );
+}
+
+/// An npm that records its arguments and installs an empty `cowsay` into the global prefix
+#[cfg(unix)]
+fn recording_npm_bin() -> String {
+    format!(
+        r#"#!/bin/sh
+echo "$@" > "{}"
+dest="$npm_config_prefix/lib/node_modules/cowsay"
+/bin/mkdir -p "$dest"
+echo '{{"name": "cowsay", "version": "1.4.0"}}' > "$dest/package.json"
+"#,
+        npm_args_file().display()
+    )
+}
+
+#[cfg(unix)]
+fn npm_args_file() -> PathBuf {
+    Sandbox::home_path("npm-args")
+}
+
+#[cfg(unix)]
+fn npm_args() -> String {
+    std::fs::read_to_string(npm_args_file()).expect("npm should have been run")
+}
+
+#[cfg(unix)]
+fn registry_hooks_json(registry: &str) -> String {
+    format!(
+        r#"{{
+    "package": {{
+        "registry": "{}"
+    }}
+}}"#,
+        registry
+    )
+}
+
+#[cfg(unix)]
+const NODE_NPM_PLATFORM: &str = r#"{
+  "node": {
+    "runtime": "11.10.1",
+    "npm": "6.7.0"
+  },
+  "pnpm": null,
+  "yarn": null
+}"#;
+
+#[test]
+#[cfg(unix)]
+fn volta_install_uses_registry_hook() {
+    let registry = format!("{}/registry/", mockito::server_url());
+    let s = sandbox()
+        .platform(NODE_NPM_PLATFORM)
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", &recording_npm_bin())
+        .default_hooks(&registry_hooks_json(&registry))
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install cowsay"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed cowsay@1.4.0[..]")
+    );
+    assert!(npm_args().contains(&format!("--registry {} cowsay", registry)));
+}
+
+#[test]
+#[cfg(unix)]
+fn direct_install_uses_registry_hook() {
+    let registry = format!("{}/registry/", mockito::server_url());
+    let s = sandbox()
+        .platform(NODE_NPM_PLATFORM)
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", &recording_npm_bin())
+        .default_hooks(&registry_hooks_json(&registry))
+        .build();
+
+    assert_that!(
+        s.npm("i -g cowsay"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    assert!(npm_args().ends_with(&format!("i -g cowsay --registry {}\n", registry)));
+}
+
+#[test]
+#[cfg(unix)]
+fn project_registry_hook_overrides_default() {
+    let local_hooks: PathBuf = [".volta", "hooks.json"].iter().collect();
+    let project_registry = format!("{}/project/registry/", mockito::server_url());
+    let s = sandbox()
+        .package_json("{}")
+        .platform(NODE_NPM_PLATFORM)
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", &recording_npm_bin())
+        .default_hooks(&registry_hooks_json(&format!(
+            "{}/registry/",
+            mockito::server_url()
+        )))
+        .project_file(
+            &local_hooks.to_string_lossy(),
+            &registry_hooks_json(&project_registry),
+        )
+        .build();
+
+    assert_that!(
+        s.volta("install cowsay"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    assert!(npm_args().contains(&format!("--registry {} cowsay", project_registry)));
+}
+
+#[test]
+#[cfg(unix)]
+fn install_without_registry_hook_uses_npm_registry() {
+    let s = sandbox()
+        .platform(NODE_NPM_PLATFORM)
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", &recording_npm_bin())
+        .build();
+
+    assert_that!(
+        s.volta("install cowsay"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    assert!(!npm_args().contains("--registry"));
+}
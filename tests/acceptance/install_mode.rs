@@ -0,0 +1,154 @@
+//! Tests for `install.mode`, which controls whether missing tools are fetched automatically
+
+use std::path::PathBuf;
+
+use crate::support::sandbox::{sandbox, DistroMetadata, NodeFixture, Sandbox};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use mockito::{mock, Matcher};
+use node_semver::Version;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const PACKAGE_JSON: &str = r#"{
+  "name": "test-package",
+  "volta": {
+    "node": "10.99.1040"
+  }
+}"#;
+
+const INSTALL_MODE_MANUAL: &str = r#"{
+    "install": {
+        "mode": "manual"
+    }
+}"#;
+
+const NODE_VERSION_INFO: &str = r#"[
+{"version":"v10.99.1040","npm":"6.2.26","lts": "Dubnium","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]}
+]
+"#;
+
+cfg_if::cfg_if! {
+    if #[cfg(target_os = "windows")] {
+        const NODE_VERSION_FIXTURES: [DistroMetadata; 1] = [
+            DistroMetadata {
+                version: "10.99.1040",
+                compressed_size: 1096,
+                uncompressed_size: None,
+            },
+        ];
+    } else {
+        const NODE_VERSION_FIXTURES: [DistroMetadata; 1] = [
+            DistroMetadata {
+                version: "10.99.1040",
+                compressed_size: 273,
+                uncompressed_size: Some(0x0028_0000),
+            },
+        ];
+    }
+}
+
+#[test]
+fn shim_fails_for_missing_pinned_node_in_manual_mode() {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON)
+        .file(".volta/settings.json", INSTALL_MODE_MANUAL)
+        .shim("node")
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+    let requests = mock("GET", Matcher::Any).expect(0).create();
+
+    assert_that!(
+        s.exec_shim("node", "--version"),
+        execs()
+            .with_status(ExitCode::ExecutionFailure as i32)
+            .with_stderr_contains(
+                "[..]This command needs tools that are not installed: node@10.99.1040"
+            )
+            .with_stderr_contains("    volta fetch node@10.99.1040")
+    );
+
+    requests.assert();
+    assert!(!Sandbox::path_exists(".volta/tools/image/node/10.99.1040"));
+}
+
+#[test]
+fn install_mode_flag_overrides_setting() {
+    let s = sandbox().package_json(PACKAGE_JSON).build();
+    let requests = mock("GET", Matcher::Any).expect(0).create();
+
+    assert_that!(
+        s.volta("--install-mode manual run node --version"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "[..]This command needs tools that are not installed: node@10.99.1040"
+            )
+    );
+
+    requests.assert();
+}
+
+#[test]
+fn fetch_is_allowed_in_manual_mode() {
+    let s = sandbox()
+        .file(".volta/settings.json", INSTALL_MODE_MANUAL)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("fetch node@10.99.1040"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert!(s.node_inventory_archive_exists(&Version::parse("10.99.1040").unwrap()));
+}
+
+#[test]
+fn pin_does_not_resolve_from_the_network_in_manual_mode() {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON)
+        .file(".volta/settings.json", INSTALL_MODE_MANUAL)
+        .build();
+    let requests = mock("GET", Matcher::Any).expect(0).create();
+
+    assert_that!(
+        s.volta("pin node@lts"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]This command needs tools that are not installed: node@lts")
+            .with_stderr_contains("    volta fetch node@lts")
+    );
+
+    requests.assert();
+}
+
+#[test]
+fn run_resolves_fetched_versions_in_manual_mode() {
+    let s = sandbox()
+        .file(".volta/settings.json", INSTALL_MODE_MANUAL)
+        .setup_node_binary("10.99.1040", "6.2.26", "#!/bin/sh\necho node-10\n")
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+    let requests = mock("GET", Matcher::Any).expect(0).create();
+
+    assert_that!(
+        s.volta("run --node 10 node"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("node-10")
+    );
+    assert_that!(
+        s.volta("run --node 12 node"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "[..]This command needs tools that are not installed: \"node@>=12.0.0 <13.0.0-0\""
+            )
+            .with_stderr_contains("    volta fetch \"node@>=12.0.0 <13.0.0-0\"")
+    );
+
+    requests.assert();
+}
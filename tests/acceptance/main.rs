@@ -10,14 +10,44 @@ cfg_if! {
         mod direct_uninstall;
         mod execute_binary;
         mod hooks;
+        mod install_mode;
         mod merged_platform;
         mod migrations;
         mod run_shim_directly;
+        // the fixture `node` is a shell script that reads stdin
+        #[cfg(unix)]
+        mod shim_stdin;
+        mod usage_stats;
         mod verbose_errors;
+        mod version_policies;
         mod volta_bypass;
+        mod volta_complete;
+        mod volta_config;
+        // the shadowing shims are created as Unix symlinks
+        #[cfg(unix)]
+        mod volta_doctor;
+        // the PATH separator and shell syntax are Unix-specific
+        #[cfg(unix)]
+        mod volta_env;
+        mod volta_fetch;
+        mod volta_hook;
+        // nvm is only available on Unix
+        #[cfg(unix)]
+        mod volta_import;
         mod volta_install;
+        mod volta_install_offline;
+        mod volta_list;
+        mod volta_lock;
         mod volta_pin;
         mod volta_run;
+        mod volta_self;
+        // shim regeneration failures are simulated with Unix file system semantics
+        #[cfg(unix)]
+        mod volta_setup;
         mod volta_uninstall;
+        mod volta_which;
+        // the fixture images only have a `bin` directory on Unix
+        #[cfg(unix)]
+        mod verify_binaries;
     }
 }
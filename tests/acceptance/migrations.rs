@@ -1,7 +1,16 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+#[cfg(unix)]
+use crate::support::sandbox::{cargo_dir, PackageBinInfo};
 use crate::support::sandbox::{sandbox, Sandbox};
 use hamcrest2::assert_that;
 use hamcrest2::prelude::*;
 use test_support::matchers::execs;
+#[cfg(unix)]
+use test_support::paths;
 
 #[test]
 fn empty_volta_home_is_created() {
@@ -231,3 +240,211 @@ fn current_v4_volta_home_is_unchanged() {
     assert!(Sandbox::path_exists(".volta/tools/inventory/node"));
     assert!(Sandbox::path_exists(".volta/tools/inventory/yarn"));
 }
+
+fn tagged_v1_sandbox() -> Sandbox {
+    sandbox()
+        .layout_file("v1")
+        .file(
+            ".volta/tools/image/node/10.6.0/6.1.0/README.md",
+            "Irrelevant Contents",
+        )
+        .node_npm_version_file("10.6.0", "6.1.0")
+        .platform(
+            r#"{
+            "node": {
+                "runtime": "10.6.0",
+                "npm": "6.1.0"
+            },
+            "yarn": null
+        }"#,
+        )
+        .env("VOLTA_LOGLEVEL", "info")
+        .build()
+}
+
+/// Hashes the paths and contents of everything within a directory
+fn hash_dir(dir: &Path) -> u64 {
+    fn visit(dir: &Path, root: &Path, hasher: &mut DefaultHasher) {
+        let mut entries: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        entries.sort();
+
+        for path in entries {
+            path.strip_prefix(root).unwrap().hash(hasher);
+            if path.is_dir() {
+                visit(&path, root, hasher);
+            } else {
+                fs::read(&path).unwrap().hash(hasher);
+            }
+        }
+    }
+
+    let mut hasher = DefaultHasher::new();
+    visit(dir, dir, &mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn migrate_dry_run_lists_steps_without_changes() {
+    let s = tagged_v1_sandbox();
+    let volta_home = Sandbox::home_path(".volta");
+    let before = hash_dir(&volta_home);
+
+    assert_that!(
+        s.volta("migrate --dry-run"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains(
+                "Your Volta directory [..].volta has layout v1. Migrating it would apply 3 step(s):"
+            )
+            .with_stdout_contains("v1 -> v2")
+            .with_stdout_contains("    update [..]platform.json")
+            .with_stdout_contains(
+                "    move [..]node[..]10.6.0[..]6.1.0 to [..]node[..]10.6.0"
+            )
+            .with_stdout_contains("    remove [..]layout.v1")
+            .with_stdout_contains("v2 -> v3")
+            .with_stdout_contains("    create file [..]layout.v3")
+            .with_stdout_contains("v3 -> v4")
+            .with_stdout_contains("    create file [..]layout.v4")
+            .with_stdout_contains("Nothing was changed.")
+            .with_stdout_does_not_contain("[..]Updating your Volta directory[..]")
+    );
+
+    assert_eq!(hash_dir(&volta_home), before);
+    assert!(Sandbox::path_exists(".volta/layout.v1"));
+    assert!(!Sandbox::path_exists(".volta/layout.v4"));
+}
+
+#[test]
+fn migrate_dry_run_on_current_layout() {
+    let s = sandbox().layout_file("v4").build();
+
+    assert_that!(
+        s.volta("migrate --dry-run"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Your Volta directory [..] is up to date (layout v4).")
+    );
+}
+
+#[test]
+fn migrate_applies_steps() {
+    let s = tagged_v1_sandbox();
+
+    assert_that!(
+        s.volta("migrate"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("[..]Your Volta directory is up to date")
+    );
+
+    assert!(Sandbox::path_exists(
+        ".volta/tools/image/node/10.6.0/README.md"
+    ));
+    assert!(!Sandbox::path_exists(".volta/layout.v1"));
+    assert!(!Sandbox::path_exists(".volta/layout.v1.bak"));
+    assert!(Sandbox::path_exists(".volta/layout.v4"));
+
+    assert_that!(
+        s.volta("migrate --dry-run"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("[..]is up to date (layout v4).")
+    );
+}
+
+#[cfg(unix)]
+const COWSAY_PACKAGE_CONFIG: &str = r#"{
+  "name": "cowsay",
+  "version": "1.4.0",
+  "platform": {
+    "node": "11.10.1",
+    "npm": null,
+    "yarn": null
+  },
+  "bins": [
+    "cowsay"
+  ],
+  "manager": "Npm"
+}"#;
+
+#[cfg(unix)]
+const COWSAY_BIN_CONFIG: &str = r#"{
+  "name": "cowsay",
+  "package": "cowsay",
+  "version": "1.4.0",
+  "platform": {
+    "node": "11.10.1",
+    "npm": null,
+    "yarn": null
+  },
+  "manager": "Npm"
+}"#;
+
+#[cfg(unix)]
+#[test]
+fn moved_volta_home_runs_package_shims() {
+    use std::os::unix::fs::symlink;
+    use volta_core::fs::copy_dir_all;
+
+    let s = sandbox()
+        .layout_file("v4")
+        .package_config("cowsay", COWSAY_PACKAGE_CONFIG)
+        .binary_config("cowsay", COWSAY_BIN_CONFIG)
+        .package_image(
+            "cowsay",
+            "1.4.0",
+            Some(vec![PackageBinInfo {
+                name: "cowsay".to_string(),
+                contents: "#!/bin/sh\necho \"cowsay version 1.4.0\"\n".to_string(),
+            }]),
+        )
+        .setup_node_binary("11.10.1", "6.7.0", "")
+        .build();
+
+    // Install Volta inside the Volta directory, as the installer does by default
+    let home = Sandbox::home_path(".volta");
+    let install_dir = home.join("bin");
+    for executable in ["volta", "volta-shim", "volta-migrate"] {
+        fs::copy(cargo_dir().join(executable), install_dir.join(executable)).unwrap();
+    }
+
+    // A shim and a shared link with absolute paths, as earlier versions created them
+    let shim = install_dir.join("cowsay");
+    symlink(install_dir.join("volta-shim"), &shim).unwrap();
+    let package_dir = Sandbox::package_image_path("cowsay").join("lib/node_modules/cowsay");
+    fs::create_dir_all(&package_dir).unwrap();
+    let shared_link = home.join("tools/shared/cowsay");
+    fs::create_dir_all(shared_link.parent().unwrap()).unwrap();
+    symlink(&package_dir, &shared_link).unwrap();
+
+    // Migrating rewrites both links relative to the Volta directory
+    assert_that!(
+        s.volta("migrate").env("VOLTA_INSTALL_DIR", &install_dir),
+        execs().with_status(0)
+    );
+    assert_eq!(fs::read_link(&shim).unwrap(), Path::new("volta-shim"));
+    assert!(fs::read_link(&shared_link).unwrap().is_relative());
+
+    // Move the whole directory, so that nothing is left at the old location
+    let moved = paths::root().join("moved/.volta");
+    copy_dir_all(&home, &moved).unwrap();
+    s.remove_volta_home();
+
+    assert_that!(
+        s.process(moved.join("bin/cowsay"))
+            .env("VOLTA_HOME", &moved)
+            .env_remove("VOLTA_INSTALL_DIR"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("cowsay version 1.4.0")
+    );
+    assert_eq!(
+        fs::canonicalize(moved.join("tools/shared/cowsay")).unwrap(),
+        fs::canonicalize(moved.join("tools/image/packages/cowsay/lib/node_modules/cowsay"))
+            .unwrap()
+    );
+}
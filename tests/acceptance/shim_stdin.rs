@@ -0,0 +1,155 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use std::thread;
+
+use crate::support::sandbox::{sandbox, DistroMetadata, NodeFixture, Sandbox};
+
+const PACKAGE_JSON: &str = r#"{
+  "name": "test-package",
+  "volta": {
+    "node": "11.22.33"
+  }
+}"#;
+
+const NODE_VERSION_INFO: &str = r#"[
+{"version":"v11.22.33","npm":"6.14.25","lts": false,"files":["linux-x64","osx-x64-tar","linux-arm64"]}
+]
+"#;
+
+// The `node` in these fixtures writes everything it reads from stdin to `received-script.js`
+cfg_if::cfg_if! {
+    if #[cfg(target_os = "macos")] {
+        const NODE_VERSION_FIXTURES: [DistroMetadata; 1] = [
+            DistroMetadata {
+                version: "11.22.33",
+                compressed_size: 363,
+                uncompressed_size: Some(0x0028_0000),
+            },
+        ];
+    } else if #[cfg(all(target_os = "linux", target_arch = "aarch64"))] {
+        const NODE_VERSION_FIXTURES: [DistroMetadata; 1] = [
+            DistroMetadata {
+                version: "11.22.33",
+                compressed_size: 364,
+                uncompressed_size: Some(0x0028_0000),
+            },
+        ];
+    } else {
+        const NODE_VERSION_FIXTURES: [DistroMetadata; 1] = [
+            DistroMetadata {
+                version: "11.22.33",
+                compressed_size: 363,
+                uncompressed_size: Some(0x0028_0000),
+            },
+        ];
+    }
+}
+
+/// A script larger than a pipe buffer, so that the shim is still fetching Node while the script
+/// is being written, and any bytes read by Volta itself would be missing from what Node receives
+fn here_doc_script() -> String {
+    let mut script = String::new();
+    for line in 0..4096 {
+        let _ = writeln!(script, "console.log('line {}');", line);
+    }
+    script
+}
+
+/// Runs the command with the script piped to its stdin, returning its output once it exits
+fn run_with_script(mut command: Command, script: &str) -> Output {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("could not start the command");
+
+    let mut stdin = child.stdin.take().unwrap();
+    let input = script.to_owned();
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let output = child.wait_with_output().unwrap();
+    writer.join().unwrap().expect("could not write the script");
+    output
+}
+
+fn assert_node_received(s: &Sandbox, output: &Output, script: &str) {
+    assert!(
+        output.status.success(),
+        "node failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(Sandbox::path_exists(".volta/tools/image/node/11.22.33"));
+
+    let received = fs::read_to_string(s.root().join("received-script.js")).unwrap();
+    assert_eq!(received.len(), script.len());
+    assert!(received == script, "node received a different script");
+}
+
+#[test]
+fn shimmed_node_receives_stdin_while_fetching() {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .shim("node")
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    let script = here_doc_script();
+    let output = run_with_script(s.exec_shim("node", "").build_command(), &script);
+
+    assert_node_received(&s, &output, &script);
+}
+
+#[test]
+fn volta_run_node_receives_stdin_while_fetching() {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    let script = here_doc_script();
+    let output = run_with_script(s.volta("run node").build_command(), &script);
+
+    assert_node_received(&s, &output, &script);
+}
+
+#[test]
+fn pin_confirmation_leaves_piped_stdin_unread() {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON)
+        .project_file(
+            "packages/app/package.json",
+            r#"{ "name": "app", "volta": { "extends": "../../package.json" } }"#,
+        )
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    // Whatever `volta pin` leaves of stdin is saved by `cat`, which runs after it with the same
+    // stdin. Since stdin isn't a terminal, the pin isn't confirmed even though it starts with "y".
+    let volta = s.volta("").build_command().get_program().to_owned();
+    let mut script = String::from("y\n");
+    script.push_str(&here_doc_script());
+    let mut command = s.process("/bin/sh").build_command();
+    command.arg("-c").arg(format!(
+        "'{}' pin node@11.22.33; cat > remaining-stdin.txt",
+        volta.to_string_lossy()
+    ));
+    let output = run_with_script(command, &script);
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("was not confirmed"));
+    assert_eq!(s.read_package_json(), PACKAGE_JSON);
+
+    let remaining = fs::read_to_string(s.root().join("remaining-stdin.txt")).unwrap();
+    assert_eq!(remaining.len(), script.len());
+    assert!(remaining == script, "volta pin read from stdin");
+}
@@ -34,13 +34,17 @@ pub fn match_args(argv: &str) -> EventKindMatcher {
     EventKindMatcher::Args { argv }
 }
 
-pub fn assert_events(sandbox: &Sandbox, matchers: Vec<(&str, EventKindMatcher)>) {
+/// Reads the events that the publish hook wrote to 'events.json' in the sandbox
+pub fn read_events(sandbox: &Sandbox) -> Vec<Event> {
     let events_path = sandbox.root().join("events.json");
     assert_that!(&events_path, file_exists());
 
     let events_file = File::open(events_path).expect("Error reading 'events.json' file in sandbox");
-    let events: Vec<Event> = serde_json::de::from_reader(events_file)
-        .expect("Error parsing 'events.json' file in sandbox");
+    serde_json::de::from_reader(events_file).expect("Error parsing 'events.json' file in sandbox")
+}
+
+pub fn assert_events(sandbox: &Sandbox, matchers: Vec<(&str, EventKindMatcher)>) {
+    let events = read_events(sandbox);
     assert_that!(events.len(), eq(matchers.len()));
 
     for (i, matcher) in matchers.iter().enumerate() {
@@ -52,7 +56,7 @@ pub fn assert_events(sandbox: &Sandbox, matchers: Vec<(&str, EventKindMatcher)>)
             EventKindMatcher::End {
                 exit_code: expected_exit_code,
             } => {
-                if let EventKind::End { exit_code } = &events[i].event {
+                if let EventKind::End { exit_code, .. } = &events[i].event {
                     assert_that!(*exit_code, eq(expected_exit_code));
                 } else {
                     panic!(
@@ -81,7 +85,7 @@ pub fn assert_events(sandbox: &Sandbox, matchers: Vec<(&str, EventKindMatcher)>)
             EventKindMatcher::ToolEnd {
                 exit_code: expected_exit_code,
             } => {
-                if let EventKind::End { exit_code } = &events[i].event {
+                if let EventKind::End { exit_code, .. } = &events[i].event {
                     assert_that!(*exit_code, eq(expected_exit_code));
                 } else {
                     panic!(
@@ -93,7 +97,7 @@ pub fn assert_events(sandbox: &Sandbox, matchers: Vec<(&str, EventKindMatcher)>)
             EventKindMatcher::Args {
                 argv: expected_argv,
             } => {
-                if let EventKind::Args { argv } = &events[i].event {
+                if let EventKind::Args { argv, .. } = &events[i].event {
                     assert_that!(argv.clone(), matches_regex(expected_argv));
                 } else {
                     panic!(
@@ -1,14 +1,17 @@
+use std::collections::HashMap;
 use std::env;
 use std::ffi::{OsStr, OsString};
+use std::fmt::Write as _;
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 
 use cfg_if::cfg_if;
-use headers::{Expires, Header};
+use headers::{Date, Header};
 use mockito::{self, mock, Matcher};
 use node_semver::Version;
+use ring::digest::{digest, SHA256};
 use test_support::{self, ok_or_panic, paths, paths::PathExt, process::ProcessBuilder};
 use volta_core::fs::{set_executable, symlink_file};
 use volta_core::tool::{Node, Pnpm, Yarn};
@@ -42,19 +45,24 @@ impl CacheBuilder {
         });
         ok_or_panic! { cache_file.write_all(self.contents.as_bytes()) };
 
-        // write expiry file
+        // write expiry file, for a cache that may be used for a day after it was received
         let one_day = Duration::from_secs(24 * 60 * 60);
-        let expiry_date = Expires::from(if self.expired {
-            SystemTime::now() - one_day
+        let received = if self.expired {
+            SystemTime::now() - 2 * one_day
         } else {
-            SystemTime::now() + one_day
-        });
+            SystemTime::now()
+        };
 
         let mut header_values = Vec::with_capacity(1);
-        expiry_date.encode(&mut header_values);
+        Date::from(received).encode(&mut header_values);
         // Since we just `.encode()`d into `header_values, it is guaranteed to
         // have a `.first()`.
-        let encoded_expiry_date = header_values.first().unwrap();
+        let encoded_received = header_values.first().unwrap().to_str().unwrap();
+        let expiry = format!(
+            "received: {}\nmax-age: {}\n",
+            encoded_received,
+            one_day.as_secs()
+        );
 
         let mut expiry_file = File::create(&self.expiry_path).unwrap_or_else(|e| {
             panic!(
@@ -63,7 +71,7 @@ impl CacheBuilder {
                 e
             )
         });
-        ok_or_panic! { expiry_file.write_all(encoded_expiry_date.as_bytes()) };
+        ok_or_panic! { expiry_file.write_all(expiry.as_bytes()) };
     }
 
     fn dirname(&self) -> &Path {
@@ -159,6 +167,11 @@ pub trait DistroFixture: From<DistroMetadata> {
     fn server_path(&self) -> String;
     fn fixture_path(&self) -> String;
     fn metadata(&self) -> &DistroMetadata;
+
+    /// The path of the checksum file that lists the distro on the public server, if there is one
+    fn checksums_path(&self) -> Option<String> {
+        None
+    }
 }
 
 #[derive(Clone)]
@@ -234,6 +247,10 @@ impl DistroFixture for NodeFixture {
     fn metadata(&self) -> &DistroMetadata {
         &self.metadata
     }
+
+    fn checksums_path(&self) -> Option<String> {
+        Some(format!("/v{}/SHASUMS256.txt", self.metadata.version))
+    }
 }
 
 impl DistroFixture for NpmFixture {
@@ -322,7 +339,6 @@ impl SandboxBuilder {
         }
     }
 
-    #[allow(dead_code)]
     /// Set the Node cache for the sandbox (chainable)
     pub fn node_cache(mut self, cache: &str, expired: bool) -> Self {
         self.caches.push(CacheBuilder::new(
@@ -424,6 +440,41 @@ impl SandboxBuilder {
         self
     }
 
+    /// Setup mock to return the registry metadata of a package (chainable)
+    pub fn package_metadata(mut self, name: &str, body: &str) -> Self {
+        let mock = mock("GET", format!("/{}", name).as_str())
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create();
+        self.root.mocks.push(mock);
+
+        self
+    }
+
+    /// Setup mock to return the version of the latest Volta release (chainable)
+    pub fn volta_latest_version(mut self, version: &str) -> Self {
+        let mock = mock("GET", "/volta-releases/latest-version")
+            .with_status(200)
+            .with_body(format!("{}\n", version))
+            .create();
+        self.root.mocks.push(mock);
+        self
+    }
+
+    /// Setup mock to serve a file of a Volta release (chainable)
+    pub fn volta_release_file(mut self, version: &str, file: &str, body: &[u8]) -> Self {
+        let mock = mock(
+            "GET",
+            format!("/volta-releases/v{}/{}", version, file).as_str(),
+        )
+        .with_status(200)
+        .with_body(body)
+        .create();
+        self.root.mocks.push(mock);
+        self
+    }
+
     /// Setup mock to return a 404 for any GET request
     /// Note: Mocks are matched in reverse order, so any created _after_ this will work
     ///       While those created before will not
@@ -433,10 +484,37 @@ impl SandboxBuilder {
         self
     }
 
-    fn distro_mock<T: DistroFixture>(mut self, fx: &T) -> Self {
+    fn distro_mock<T: DistroFixture>(self, fx: &T) -> Self {
+        let server_path = fx.server_path();
+        let mut this = self.mock_distro_at(fx, &server_path);
+
+        if let Some(checksums_path) = fx.checksums_path() {
+            let fixture_path = fx.fixture_path();
+            let file_name = Path::new(&fixture_path).file_name().unwrap();
+            let contents = fs::read(&fixture_path).unwrap();
+            let mut checksum = String::new();
+            for byte in digest(&SHA256, &contents).as_ref() {
+                let _ = write!(checksum, "{:02x}", byte);
+            }
+
+            let checksums_mock = mock("GET", checksums_path.as_str())
+                .with_body(format!("{}  {}\n", checksum, file_name.to_string_lossy()))
+                .create();
+            this.root.mocks.push(checksums_mock);
+        }
+
+        this
+    }
+
+    /// Serves the fixture for a distro at a different path than its public one, as a mirror
+    /// would (chainable)
+    pub fn distro_mock_at<T: DistroFixture>(self, fixture: &DistroMetadata, path: &str) -> Self {
+        self.mock_distro_at(&T::from(fixture.clone()), path)
+    }
+
+    fn mock_distro_at<T: DistroFixture>(mut self, fx: &T, server_path: &str) -> Self {
         // ISSUE(#145): this should actually use a real http server instead of these mocks
 
-        let server_path = fx.server_path();
         let fixture_path = fx.fixture_path();
 
         let metadata = fx.metadata();
@@ -450,14 +528,14 @@ impl SandboxBuilder {
                 (uncompressed_size & 0x0000_00ff) as u8,
             ];
 
-            let range_mock = mock("GET", &server_path[..])
+            let range_mock = mock("GET", server_path)
                 .match_header("Range", Matcher::Any)
                 .with_body(uncompressed_size_bytes)
                 .create();
             self.root.mocks.push(range_mock);
         }
 
-        let file_mock = mock("GET", &server_path[..])
+        let file_mock = mock("GET", server_path)
             .match_header("Range", Matcher::Missing)
             .with_header("Accept-Ranges", "bytes")
             .with_body_from_file(fixture_path)
@@ -613,6 +691,84 @@ impl SandboxBuilder {
         self.node_npm_version_file(node_version, npm_version)
     }
 
+    /// Write an executable node binary with the input contents in the image of a Node build for
+    /// another architecture than the native one (chainable)
+    pub fn setup_node_arch_binary(
+        mut self,
+        arch: &str,
+        node_version: &str,
+        contents: &str,
+    ) -> Self {
+        cfg_if! {
+            if #[cfg(target_os = "windows")] {
+                let node_file = "node.cmd";
+            } else {
+                let node_file = "node";
+            }
+        }
+        let node_bin_file = image_dir()
+            .join("node")
+            .join(arch)
+            .join(node_version)
+            .join("bin")
+            .join(node_file);
+        self.files
+            .push(FileBuilder::new(node_bin_file, contents).make_executable());
+        self
+    }
+
+    /// Write the manifest of the npm bundled in a Node image, without recording the "default npm"
+    /// file for that Node version (chainable)
+    pub fn node_image_npm_manifest(mut self, node_version: &str, npm_version: &str) -> Self {
+        cfg_if! {
+            if #[cfg(target_os = "windows")] {
+                let modules_dir = node_image_dir(node_version).join("node_modules");
+            } else {
+                let modules_dir = node_image_dir(node_version).join("lib").join("node_modules");
+            }
+        }
+        let npm_manifest = modules_dir.join("npm").join("package.json");
+        self.files.push(FileBuilder::new(
+            npm_manifest,
+            &format!(r#"{{"name":"npm","version":"{}"}}"#, npm_version),
+        ));
+        self
+    }
+
+    /// Write a fake nvm installation of Node, with an executable node binary with the input
+    /// contents and a bundled npm manifest (chainable)
+    pub fn nvm_node_binary(
+        mut self,
+        node_version: &str,
+        npm_version: &str,
+        contents: &str,
+    ) -> Self {
+        let install_dir = nvm_dir()
+            .join("versions")
+            .join("node")
+            .join(format!("v{}", node_version));
+        let npm_manifest = install_dir
+            .join("lib")
+            .join("node_modules")
+            .join("npm")
+            .join("package.json");
+        self.files.push(
+            FileBuilder::new(install_dir.join("bin").join("node"), contents).make_executable(),
+        );
+        self.files.push(FileBuilder::new(
+            npm_manifest,
+            &format!(r#"{{"name":"npm","version":"{}"}}"#, npm_version),
+        ));
+        self
+    }
+
+    /// Write an nvm alias file (chainable)
+    pub fn nvm_alias(mut self, name: &str, target: &str) -> Self {
+        let alias_file = nvm_dir().join("alias").join(name);
+        self.files.push(FileBuilder::new(alias_file, target));
+        self
+    }
+
     /// Write an executable npm binary with the input contents (chainable)
     pub fn setup_npm_binary(mut self, version: &str, contents: &str) -> Self {
         cfg_if! {
@@ -775,7 +931,6 @@ fn cache_dir() -> PathBuf {
 fn node_cache_dir() -> PathBuf {
     cache_dir().join("node")
 }
-#[allow(dead_code)]
 fn node_index_file() -> PathBuf {
     node_cache_dir().join("index.json")
 }
@@ -799,6 +954,9 @@ fn shim_file(name: &str) -> PathBuf {
 fn package_image_dir(name: &str) -> PathBuf {
     image_dir().join("packages").join(name)
 }
+fn nvm_dir() -> PathBuf {
+    home_dir().join(".nvm")
+}
 fn node_image_dir(version: &str) -> PathBuf {
     image_dir().join("node").join(version)
 }
@@ -856,6 +1014,10 @@ impl Sandbox {
             .env("VOLTA_INSTALL_DIR", cargo_dir())
             .env("PATH", &self.path)
             .env("VOLTA_POSTSCRIPT", volta_postscript())
+            // keep the registry settings in the user's `.npmrc` out of the tests
+            .env("NPM_CONFIG_USERCONFIG", home_dir().join(".npmrc"))
+            // and any Notion directory of the user
+            .env("NOTION_HOME", home_dir().join(".notion"))
             .env_remove("VOLTA_SHELL")
             .env_remove("MSYSTEM"); // assume cmd.exe everywhere on windows
 
@@ -957,6 +1119,12 @@ impl Sandbox {
     pub fn shim_exists(name: &str) -> bool {
         shim_file(name).exists()
     }
+    pub fn shim_path(name: &str) -> PathBuf {
+        shim_file(name)
+    }
+    pub fn home_path(path: &str) -> PathBuf {
+        sandbox_path(path)
+    }
     pub fn path_exists(path: &str) -> bool {
         sandbox_path(path).exists()
     }
@@ -964,9 +1132,30 @@ impl Sandbox {
         let package_img_dir = package_image_dir(name);
         package_img_dir.join("package.json").exists()
     }
+    pub fn node_image_exists(version: &str) -> bool {
+        node_image_dir(version).exists()
+    }
+    pub fn node_image_path(version: &str) -> PathBuf {
+        node_image_dir(version)
+    }
+    pub fn package_image_path(name: &str) -> PathBuf {
+        package_image_dir(name)
+    }
+    pub fn nvm_dir() -> PathBuf {
+        nvm_dir()
+    }
+    pub fn read_node_npm_version_file(node_version: &str) -> Option<String> {
+        fs::read_to_string(node_npm_version_file(node_version)).ok()
+    }
+    pub fn read_node_index_cache() -> String {
+        read_file_to_string(node_index_file())
+    }
     pub fn read_default_platform() -> String {
         read_file_to_string(default_platform_file())
     }
+    pub fn read_package_config(name: &str) -> String {
+        read_file_to_string(package_config_file(name))
+    }
 }
 
 impl Drop for Sandbox {
@@ -980,6 +1169,21 @@ pub fn sandbox() -> SandboxBuilder {
     SandboxBuilder::new(paths::root().join("sandbox"))
 }
 
+/// Counts how many times each configuration file of a kind, like `project manifest`, was read, from
+/// the debug output of a Volta command
+pub fn file_reads(stderr: &[u8], description: &str) -> HashMap<String, usize> {
+    let prefix = format!("Reading {} '", description);
+    let mut reads = HashMap::new();
+
+    for line in String::from_utf8_lossy(stderr).lines() {
+        if let Some(start) = line.find(&prefix) {
+            let file = line[start + prefix.len()..].trim_end_matches('\'');
+            *reads.entry(file.to_owned()).or_default() += 1;
+        }
+    }
+    reads
+}
+
 // Path to compiled executables
 pub fn cargo_dir() -> PathBuf {
     env::var_os("CARGO_BIN_PATH")
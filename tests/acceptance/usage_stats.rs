@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use crate::support::sandbox::{sandbox, Sandbox};
+use cfg_if::cfg_if;
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const PLATFORM_NODE: &str = r#"{
+    "node":{
+        "runtime":"11.10.1",
+        "npm":"6.7.0"
+    }
+}"#;
+
+const USAGE_STATS_ENABLED: &str = r#"{
+    "usage": {
+        "stats": true
+    }
+}"#;
+
+const VOLTA_LOGLEVEL: &str = "VOLTA_LOGLEVEL";
+
+fn node_bin() -> String {
+    cfg_if! {
+        if #[cfg(target_os = "windows")] {
+            r#"@echo off
+echo Node version 11.10.1
+"#
+            .to_string()
+        } else {
+            r#"#!/bin/sh
+echo "Node version 11.10.1"
+"#
+            .to_string()
+        }
+    }
+}
+
+#[test]
+fn shims_record_usage_when_enabled() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .file(".volta/settings.json", USAGE_STATS_ENABLED)
+        .shim("node")
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin())
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    assert_that!(
+        s.exec_shim("node", "--version"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("Node version 11.10.1")
+    );
+    assert_that!(
+        s.exec_shim("node", "--version"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert!(Sandbox::path_exists(".volta/tools/user/usage.json"));
+    assert_that!(
+        s.volta("list node --stats --format plain"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("node@11.10.1 runs=2 last-used=[..]")
+    );
+}
+
+#[test]
+fn shims_do_not_record_usage_when_disabled() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .shim("node")
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin())
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .env(VOLTA_LOGLEVEL, "warn")
+        .build();
+
+    assert_that!(
+        s.exec_shim("node", "--version"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert!(!Sandbox::path_exists(".volta/tools/user/usage.json"));
+    assert!(!Sandbox::path_exists(".volta/tools/user/usage.pending"));
+    assert_that!(
+        s.volta("list --stats --format plain"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Usage counters are disabled.[..]")
+            .with_stdout_contains("node@11.10.1 runs=0 last-used=never")
+    );
+}
@@ -134,3 +134,22 @@ fn no_error_log_in_ci() {
         assert_that!(log_dir_contents.next(), none());
     }
 }
+
+#[test]
+fn learn_more_link_shown_unless_quiet() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("use"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("Learn more: https://volta.sh/errors/deprecated-command-error")
+    );
+
+    assert_that!(
+        s.volta("use --quiet"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_does_not_contain("[..]Learn more[..]")
+    );
+}
@@ -0,0 +1,167 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::support::sandbox::{sandbox, PackageBinInfo, Sandbox};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const PKG_CONFIG_COWSAY: &str = r#"{
+  "name": "cowsay",
+  "version": "1.4.0",
+  "platform": {
+    "node": "10.99.1040",
+    "npm": null,
+    "yarn": null
+  },
+  "bins": [
+    "cowsay",
+    "cowthink"
+  ],
+  "manager": "Npm"
+}"#;
+
+fn cowsay_bins() -> Vec<PackageBinInfo> {
+    vec![
+        PackageBinInfo {
+            name: "cowsay".into(),
+            contents: "#!/bin/sh\necho moo\n".into(),
+        },
+        PackageBinInfo {
+            name: "cowthink".into(),
+            contents: "#!/bin/sh\necho hmm\n".into(),
+        },
+    ]
+}
+
+fn fixture_home() -> Sandbox {
+    sandbox()
+        .setup_node_binary("10.99.1040", "6.2.26", "#!/bin/sh\necho node\n")
+        .setup_yarn_binary("1.23.483", "#!/bin/sh\necho yarn\n")
+        .package_config("cowsay", PKG_CONFIG_COWSAY)
+        .package_image("cowsay", "1.4.0", Some(cowsay_bins()))
+        .env("VOLTA_LOGLEVEL", "info")
+        .build()
+}
+
+fn manifest(s: &Sandbox, args: &str) -> String {
+    let output = s
+        .volta(&format!("--quiet verify-binaries {}", args))
+        .exec_with_output()
+        .expect("verify-binaries should succeed");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn volta_home_path(path: &str) -> PathBuf {
+    Sandbox::home_path(".volta").join(path)
+}
+
+fn write_manifest(s: &Sandbox) -> PathBuf {
+    let file = s.root().join("binaries.jsonl");
+    fs::write(&file, manifest(s, "")).unwrap();
+    file
+}
+
+#[test]
+fn lists_managed_binaries() {
+    let s = fixture_home();
+
+    assert_that!(
+        s.volta("verify-binaries"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(
+                r#"{"path":"bin/node","size":[..],"hash":"sha256-[..]","tool":"volta"}"#
+            )
+            .with_stdout_contains(
+                r#"{"path":"tools/image/node/10.99.1040/bin/node","size":20,"hash":"sha256-[..]","tool":"node","version":"10.99.1040"}"#
+            )
+            .with_stdout_contains(
+                r#"{"path":"tools/image/yarn/1.23.483/bin/yarn","size":20,"hash":"sha256-[..]","tool":"yarn","version":"1.23.483"}"#
+            )
+            .with_stdout_contains(
+                r#"{"path":"tools/image/packages/cowsay/bin/cowsay","size":19,"hash":"sha256-[..]","tool":"cowsay","version":"1.4.0"}"#
+            )
+            .with_stdout_contains(
+                r#"{"path":"tools/image/packages/cowsay/bin/cowthink","size":19,"hash":"sha256-[..]","tool":"cowsay","version":"1.4.0"}"#
+            )
+    );
+}
+
+#[test]
+fn manifest_is_deterministic() {
+    let s = fixture_home();
+
+    let serial = manifest(&s, "--jobs 1");
+    assert!(!serial.is_empty());
+    assert_eq!(manifest(&s, "--jobs 4"), serial);
+    assert_eq!(manifest(&s, ""), serial);
+
+    let paths: Vec<&str> = serial
+        .lines()
+        .map(|line| line.split('"').nth(3).unwrap())
+        .collect();
+    let mut sorted = paths.clone();
+    sorted.sort_unstable();
+    assert_eq!(paths, sorted);
+}
+
+#[test]
+fn check_passes_when_unchanged() {
+    let s = fixture_home();
+    let file = write_manifest(&s);
+
+    assert_that!(
+        s.volta(&format!("verify-binaries --check {}", file.display())),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]All binaries match the manifest [..]binaries.jsonl")
+    );
+}
+
+#[test]
+fn check_reports_differences() {
+    let s = fixture_home();
+    let file = write_manifest(&s);
+
+    let added = volta_home_path("tools/image/node/11.10.1/bin/node");
+    fs::create_dir_all(added.parent().unwrap()).unwrap();
+    fs::write(&added, "#!/bin/sh\necho newer node\n").unwrap();
+    fs::remove_file(volta_home_path("tools/image/packages/cowsay/bin/cowthink")).unwrap();
+    fs::write(
+        volta_home_path("tools/image/yarn/1.23.483/bin/yarn"),
+        "#!/bin/sh\necho tampered\n",
+    )
+    .unwrap();
+
+    assert_that!(
+        s.volta(&format!("verify-binaries --check {}", file.display())),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stdout_contains("added    tools/image/node/11.10.1/bin/node (node@11.10.1)")
+            .with_stdout_contains(
+                "removed  tools/image/packages/cowsay/bin/cowthink (cowsay@1.4.0)"
+            )
+            .with_stdout_contains(
+                "changed  tools/image/yarn/1.23.483/bin/yarn (yarn@1.23.483): sha256-[..] -> sha256-[..]"
+            )
+            .with_stdout_does_not_contain("[..]bin/cowsay[..]")
+            .with_stderr_contains("warning:[..]Found 3 difference(s) from the manifest [..]")
+    );
+}
+
+#[test]
+fn check_reports_invalid_manifest() {
+    let s = fixture_home();
+    let file = s.root().join("binaries.jsonl");
+    fs::write(&file, "not a manifest\n").unwrap();
+
+    assert_that!(
+        s.volta(&format!("verify-binaries --check {}", file.display())),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Could not parse line 1 of binary manifest")
+    );
+}
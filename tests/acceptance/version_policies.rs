@@ -0,0 +1,144 @@
+//! Tests for the version policies in the `policies` section of the hooks and settings files
+
+use std::path::PathBuf;
+
+use crate::support::sandbox::{sandbox, Sandbox, SandboxBuilder};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const ORG_HOOKS: &str = r#"{
+    "policies": {
+        "node": { "allowed": ">=10" },
+        "yarn": { "blocked": ["1.12.99"], "overridable": false }
+    }
+}"#;
+
+const PROJECT_HOOKS: &str = r#"{
+    "policies": {
+        "node": { "allowed": ">=8" }
+    }
+}"#;
+
+const PACKAGE_JSON: &str = r#"{
+  "name": "test-package",
+  "volta": {
+    "node": "10.99.1040"
+  }
+}"#;
+
+/// A sandbox with the organization's hooks, in which the Node versions are already fetched so
+/// that nothing is downloaded
+fn policy_sandbox() -> SandboxBuilder {
+    sandbox()
+        .default_hooks(ORG_HOOKS)
+        .setup_node_binary("9.27.6", "5.6.17", "#!/bin/sh\n")
+        .setup_node_binary("10.99.1040", "6.2.26", "#!/bin/sh\n")
+        .env("VOLTA_LOGLEVEL", "info")
+}
+
+#[test]
+fn install_rejects_version_outside_allowed_range() {
+    let s = policy_sandbox().build();
+
+    assert_that!(
+        s.volta("install node@9.27.6"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "[..]node@9.27.6 is not allowed by the version policy in [..]hooks.json"
+            )
+            .with_stderr_contains("Rule: allowed: >=10")
+            .with_stderr_contains("[..]`--override-policy`[..]")
+    );
+    assert!(!Sandbox::path_exists(".volta/tools/user/platform.json"));
+
+    assert_that!(
+        s.volta("install node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed and set node@10.99.1040[..]")
+    );
+}
+
+#[test]
+fn override_policy_ignores_overridable_policies() {
+    let s = policy_sandbox().build();
+
+    assert_that!(
+        s.volta("install node@9.27.6 --override-policy"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed and set node@9.27.6[..]")
+            .with_stderr_contains(
+                "warning:[..]Ignoring the version policy in [..]hooks.json for node@9.27.6 (allowed: >=10)"
+            )
+    );
+}
+
+#[test]
+fn override_policy_is_denied_by_strict_policies() {
+    let s = policy_sandbox().build();
+
+    assert_that!(
+        s.volta("install yarn@1.12.99 --override-policy"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]yarn@1.12.99 is not allowed by the version policy in [..]")
+            .with_stderr_contains("Rule: blocked: 1.12.99")
+            .with_stderr_contains("[..]This policy can't be overridden.")
+    );
+}
+
+#[test]
+fn project_hooks_cannot_loosen_policies() {
+    let project_hooks: PathBuf = [".volta", "hooks.json"].iter().collect();
+    let s = policy_sandbox()
+        .package_json(PACKAGE_JSON)
+        .project_file(&project_hooks.to_string_lossy(), PROJECT_HOOKS)
+        .build();
+
+    assert_that!(
+        s.volta("pin node@9.27.6"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("Rule: allowed: >=10")
+    );
+    assert!(s.read_package_json().contains(r#""node": "10.99.1040""#));
+}
+
+#[test]
+fn settings_policies_apply() {
+    let s = sandbox()
+        .file(
+            ".volta/settings.json",
+            r#"{ "policies": { "npm": { "blocked": ["8.1.0"] } } }"#,
+        )
+        .build();
+
+    assert_that!(
+        s.volta("install npm@8.1.0"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "[..]npm@8.1.0 is not allowed by the version policy in [..]settings.json"
+            )
+    );
+}
+
+#[test]
+fn invalid_policies_are_reported() {
+    let s = sandbox()
+        .default_hooks(r#"{ "policies": { "node": { "allowed": "not a range" } } }"#)
+        .build();
+
+    assert_that!(
+        s.volta("install node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Invalid version policy for node")
+            .with_stderr_contains("`allowed` must be a range of versions, not 'not a range'.")
+    );
+}
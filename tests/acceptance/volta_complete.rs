@@ -0,0 +1,89 @@
+//! Tests for `volta _complete`, which the dynamic shell completions call.
+
+use crate::support::sandbox::sandbox;
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+use volta_core::error::ExitCode;
+
+const PKG_CONFIG_COWSAY: &str = r#"{
+  "name": "cowsay",
+  "version": "1.4.0",
+  "platform": {
+    "node": "11.10.1",
+    "npm": "6.7.0",
+    "yarn": null
+  },
+  "bins": [
+    "cowsay",
+    "cowthink"
+  ],
+  "manager": "Npm"
+}"#;
+
+#[test]
+fn empty_home_has_no_uninstall_targets() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("_complete uninstall-targets"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout("")
+    );
+}
+
+#[test]
+fn empty_home_offers_the_tools_to_pin() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("_complete pin-targets"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout("node\nnpm\nyarn")
+    );
+}
+
+#[test]
+fn uninstall_targets_include_packages_and_node_versions() {
+    let s = sandbox()
+        .package_config("cowsay", PKG_CONFIG_COWSAY)
+        .setup_node_binary("11.10.1", "6.7.0", "")
+        .build();
+
+    assert_that!(
+        s.volta("_complete uninstall-targets"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout("cowsay\nnode@11.10.1")
+    );
+}
+
+#[test]
+fn pin_targets_include_fetched_versions() {
+    let s = sandbox()
+        .setup_node_binary("11.10.1", "6.7.0", "")
+        .setup_yarn_binary("1.22.19", "")
+        .build();
+
+    assert_that!(
+        s.volta("_complete pin-targets"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout("node\nnpm\nyarn\nnode@11.10.1\nyarn@1.22.19")
+    );
+}
+
+#[test]
+fn completions_script_calls_the_hook() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("completions bash --dynamic"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]volta _complete ${target}[..]")
+            .with_stdout_contains("[..]complete -F _volta_dynamic [..]")
+    );
+}
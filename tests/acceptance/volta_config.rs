@@ -0,0 +1,199 @@
+//! Tests for `volta config`, which reads and changes the settings in the user's settings file
+
+use crate::support::sandbox::{sandbox, Sandbox};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const ALIAS_SETTINGS: &str = r#"{
+    "aliases": { "node": { "company-standard": "20.11.1" } },
+    "install": { "mode": "manual" }
+}"#;
+
+fn read_settings() -> String {
+    std::fs::read_to_string(Sandbox::home_path(".volta/settings.json")).unwrap()
+}
+
+#[test]
+fn set_and_get_round_trip() {
+    let s = sandbox().env("VOLTA_LOGLEVEL", "info").build();
+
+    assert_that!(
+        s.volta("config set feature.pnpm true"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]set feature.pnpm to true")
+    );
+    assert_that!(
+        s.volta("config get feature.pnpm"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("true")
+    );
+
+    assert_that!(
+        s.volta("config set shims.jobs 3"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    assert_that!(
+        s.volta("config get shims.jobs"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("3")
+    );
+
+    let settings = read_settings();
+    assert!(settings.contains(r#""pnpm": true"#));
+    assert!(settings.contains(r#""jobs": 3"#));
+}
+
+#[test]
+fn set_keeps_the_rest_of_the_file() {
+    let s = sandbox()
+        .file(".volta/settings.json", ALIAS_SETTINGS)
+        .build();
+
+    assert_that!(
+        s.volta("config set install.mode auto"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    let settings = read_settings();
+    assert!(settings.contains(r#""company-standard": "20.11.1""#));
+    assert!(settings.contains(r#""mode": "auto""#));
+}
+
+#[test]
+fn list_shows_every_setting() {
+    let s = sandbox()
+        .file(".volta/settings.json", ALIAS_SETTINGS)
+        .build();
+
+    assert_that!(
+        s.volta("config list"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("feature.pnpm = false")
+            .with_stdout_contains("install.clearQuarantine = true")
+            .with_stdout_contains("install.mode = manual")
+            .with_stdout_contains("install.unsafeGlobal = false")
+            .with_stdout_contains("log.level = auto")
+            .with_stdout_contains("network.maxConcurrentDownloads = 4")
+            .with_stdout_contains("node.glibcCheck = true")
+            .with_stdout_contains("shims.jobs = [..]")
+            .with_stdout_contains("usage.stats = false")
+    );
+}
+
+#[test]
+fn log_level_setting_applies_unless_the_environment_variable_is_set() {
+    let s = sandbox()
+        .file(".volta/settings.json", r#"{ "log": { "level": "info" } }"#)
+        .build();
+
+    assert_that!(
+        s.volta("config set install.mode auto"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]set install.mode to auto")
+    );
+    assert_that!(
+        s.volta("config set install.mode manual")
+            .env("VOLTA_LOGLEVEL", "error"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_does_not_contain("[..]set install.mode to manual")
+    );
+}
+
+#[test]
+fn unknown_settings_list_the_known_ones() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("config get feature.yarn"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Unknown setting 'feature.yarn'.")
+            .with_stderr_contains("The known settings are: feature.pnpm, install.clearQuarantine, install.mode, install.unsafeGlobal, log.level, network.maxConcurrentDownloads, node.glibcCheck, shims.jobs, usage.stats")
+    );
+}
+
+#[test]
+fn invalid_values_are_not_saved() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("config set shims.jobs 0"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Invalid value '0' for setting 'shims.jobs'.")
+            .with_stderr_contains("Please use a number greater than 0.")
+    );
+    assert!(!Sandbox::path_exists(".volta/settings.json"));
+}
+
+#[test]
+fn malformed_settings_file_is_reported() {
+    let s = sandbox()
+        .file(".volta/settings.json", r#"{ "feature": { "pnpm": "#)
+        .build();
+
+    assert_that!(
+        s.volta("config get feature.pnpm"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Could not parse settings file.")
+    );
+    assert_that!(
+        s.volta("config set feature.pnpm true"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Could not parse settings file.")
+    );
+    assert_eq!(read_settings(), r#"{ "feature": { "pnpm": "#);
+}
+
+#[test]
+fn pnpm_feature_can_be_enabled_in_settings() {
+    let s = sandbox()
+        .file(".volta/settings.json", r#"{ "feature": { "pnpm": true } }"#)
+        .build();
+
+    assert_that!(
+        s.volta("uninstall pnpm"),
+        execs()
+            .with_status(ExitCode::UnknownError as i32)
+            .with_stderr_contains("[..]Uninstalling pnpm is not supported yet.")
+    );
+}
+
+#[test]
+fn pnpm_feature_env_takes_precedence_over_settings() {
+    let s = sandbox()
+        .file(
+            ".volta/settings.json",
+            r#"{ "feature": { "pnpm": false } }"#,
+        )
+        .env("VOLTA_FEATURE_PNPM", "1")
+        .build();
+
+    assert_that!(
+        s.volta("uninstall pnpm"),
+        execs()
+            .with_status(ExitCode::UnknownError as i32)
+            .with_stderr_contains("[..]Uninstalling pnpm is not supported yet.")
+    );
+}
+
+#[test]
+fn pnpm_feature_is_disabled_by_default() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("uninstall pnpm"),
+        execs().with_stderr_does_not_contain("[..]Uninstalling pnpm is not supported yet.")
+    );
+}
@@ -0,0 +1,336 @@
+//! Tests for `volta doctor`.
+
+use crate::support::sandbox::{sandbox, Sandbox};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+use test_support::paths;
+
+use volta_core::error::ExitCode;
+
+const PKG_CONFIG_VOLTA_WRAPPER: &str = r#"{
+  "name": "volta-wrapper",
+  "version": "1.0.0",
+  "platform": {
+    "node": "11.10.1",
+    "npm": "6.7.0",
+    "yarn": null
+  },
+  "bins": [
+    "volta",
+    "volta-wrapper"
+  ],
+  "manager": "Npm"
+}"#;
+
+fn bin_config(name: &str) -> String {
+    format!(
+        r#"{{
+  "name": "{}",
+  "package": "volta-wrapper",
+  "version": "1.0.0",
+  "platform": {{
+    "node": "11.10.1",
+    "npm": "6.7.0",
+    "yarn": null
+  }},
+  "manager": "Npm"
+}}"#,
+        name
+    )
+}
+
+/// A sandbox with a `volta` shim left over from installing `volta-wrapper` with an older Volta
+fn shadowed_sandbox() -> Sandbox {
+    sandbox()
+        .package_config("volta-wrapper", PKG_CONFIG_VOLTA_WRAPPER)
+        .binary_config("volta", &bin_config("volta"))
+        .binary_config("volta-wrapper", &bin_config("volta-wrapper"))
+        .shim("volta")
+        .shim("volta-wrapper")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build()
+}
+
+#[test]
+fn doctor_passes_without_shadowing_shims() {
+    let s = sandbox().env("VOLTA_LOGLEVEL", "info").build();
+
+    assert_that!(
+        s.volta("doctor"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]No shims shadow Volta's own executables")
+    );
+}
+
+#[test]
+fn doctor_reports_shadowing_shims() {
+    let s = shadowed_sandbox();
+
+    assert_that!(
+        s.volta("doctor"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]shadow Volta's own executables: volta")
+            .with_stdout_contains("[..]Run `volta doctor --fix` to remove them")
+    );
+
+    assert!(Sandbox::shim_exists("volta"));
+}
+
+#[test]
+fn doctor_fix_removes_shadowing_shims() {
+    let s = shadowed_sandbox();
+
+    assert_that!(
+        s.volta("doctor --fix"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]Removed shims shadowing Volta's own executables: volta")
+    );
+
+    assert!(!Sandbox::shim_exists("volta"));
+    assert!(!Sandbox::bin_config_exists("volta"));
+    assert!(Sandbox::shim_exists("volta-wrapper"));
+    assert!(Sandbox::bin_config_exists("volta-wrapper"));
+
+    let config = Sandbox::read_package_config("volta-wrapper");
+    assert!(!config.contains(r#""volta","#));
+    assert!(config.contains(r#""volta-wrapper""#));
+}
+
+#[test]
+fn shadowing_shim_explains_how_to_remove_it() {
+    let s = shadowed_sandbox();
+
+    assert_that!(
+        s.exec_shim("volta", "--version"),
+        execs()
+            .with_status(ExitCode::ExecutionFailure as i32)
+            .with_stderr_contains(
+                "[..]The 'volta' shim at [..] is shadowing Volta's own executable."
+            )
+            .with_stderr_contains("[..]doctor --fix[..]")
+    );
+}
+
+const STALE_IMAGE_BIN: &str = ".volta/tools/image/node/14.0.0/bin";
+
+const BASHRC_WITH_STALE_IMAGE: &str = r#"export VOLTA_HOME="$HOME/.volta"
+export PATH="$VOLTA_HOME/bin:$PATH"
+export PATH="$HOME/.volta/tools/image/node/14.0.0/bin:$PATH"
+"#;
+
+/// A sandbox whose PATH still includes the bin directory of a Node image that was removed
+fn stale_path_sandbox(bashrc: Option<&str>) -> Sandbox {
+    let home = paths::home();
+    let path = std::env::join_paths([
+        home.join(".volta/bin"),
+        home.join(STALE_IMAGE_BIN),
+        "/usr/bin".into(),
+    ])
+    .unwrap();
+
+    let mut s = sandbox();
+    if let Some(bashrc) = bashrc {
+        s = s.file(".bashrc", bashrc);
+    }
+    s.env("HOME", &home.to_string_lossy())
+        .env("PATH", &path.to_string_lossy())
+        .env("PROFILE", "")
+        .env("ZDOTDIR", "")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build()
+}
+
+#[test]
+fn doctor_passes_without_stale_path_entries() {
+    let s = sandbox().env("VOLTA_LOGLEVEL", "info").build();
+
+    assert_that!(
+        s.volta("doctor"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(
+                "[..]No PATH entries point at tool images or missing directories in the Volta home"
+            )
+            .with_stdout_contains("[..]No Notion directory or shims were found")
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn doctor_reports_stale_path_entries_with_profile() {
+    let s = stale_path_sandbox(Some(BASHRC_WITH_STALE_IMAGE));
+
+    assert_that!(
+        s.volta("doctor"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Found stale entries in your PATH:")
+            .with_stderr_contains(
+                "    [..]/.volta/tools/image/node/14.0.0/bin (a tool image that no longer exists), likely added by [..]/.bashrc"
+            )
+            .with_stdout_contains("[..]Run `volta doctor --fix` to remove them from your profile scripts")
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn doctor_fix_removes_stale_path_entries_from_profile() {
+    let s = stale_path_sandbox(Some(BASHRC_WITH_STALE_IMAGE));
+
+    assert_that!(
+        s.volta("doctor --fix"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]Removed stale PATH entries from [..]/.bashrc[..]")
+    );
+
+    let bashrc = std::fs::read_to_string(paths::home().join(".bashrc")).unwrap();
+    assert_eq!(
+        bashrc,
+        "export VOLTA_HOME=\"$HOME/.volta\"\nexport PATH=\"$VOLTA_HOME/bin:$PATH\"\n"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn doctor_fix_leaves_stale_path_entries_without_profile() {
+    let s = stale_path_sandbox(None);
+
+    assert_that!(
+        s.volta("doctor --fix"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "    [..]/.volta/tools/image/node/14.0.0/bin (a tool image that no longer exists)"
+            )
+            .with_stdout_contains("[..]Remove them from the profile script or environment setting that changes your PATH")
+    );
+}
+
+#[test]
+fn doctor_reports_notion_home() {
+    let s = sandbox()
+        .file(".notion/bin/node", "")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("doctor"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "[..]Found [..]/.notion, the directory of Notion (the previous name of Volta)"
+            )
+            .with_stdout_contains("[..]Run `volta setup --migrate-notion`[..]")
+    );
+
+    // Even with `--fix`, the migration is left to `volta setup`
+    assert_that!(
+        s.volta("doctor --fix"),
+        execs().with_status(ExitCode::ConfigurationError as i32)
+    );
+    assert!(Sandbox::path_exists(".notion/bin/node"));
+}
+
+#[test]
+fn doctor_reports_notion_shims_on_path() {
+    let path =
+        std::env::join_paths([paths::home().join(".notion/bin"), "/usr/bin".into()]).unwrap();
+    let s = sandbox()
+        .env("PATH", &path.to_string_lossy())
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("doctor"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "[..]Your PATH includes the shims of Notion (the previous name of Volta): [..]/.notion/bin"
+            )
+    );
+}
+
+#[test]
+fn doctor_reports_orphaned_shims() {
+    let s = sandbox()
+        .package_config("volta-wrapper", PKG_CONFIG_VOLTA_WRAPPER)
+        .binary_config("volta-wrapper", &bin_config("volta-wrapper"))
+        .binary_config(
+            "cowsay",
+            &bin_config("cowsay").replace("volta-wrapper", "cowsay"),
+        )
+        .shim("volta-wrapper")
+        .shim("cowsay")
+        .shim("tsc")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("doctor"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]belong to packages that are not installed: cowsay, tsc")
+            .with_stdout_contains("[..]Run `volta doctor --fix` to remove them")
+    );
+
+    assert_that!(
+        s.volta("doctor --fix"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(
+                "[..]Removed shims of packages that are not installed: cowsay, tsc"
+            )
+    );
+
+    assert!(!Sandbox::shim_exists("cowsay"));
+    assert!(!Sandbox::bin_config_exists("cowsay"));
+    assert!(!Sandbox::shim_exists("tsc"));
+    assert!(Sandbox::shim_exists("volta-wrapper"));
+    assert!(Sandbox::bin_config_exists("volta-wrapper"));
+}
+
+#[test]
+fn doctor_reports_shim_dir_missing_from_path() {
+    let s = sandbox()
+        .env("PATH", "/usr/bin")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("doctor"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]The shim directory [..] is not on your PATH")
+            .with_stdout_contains("[..]Run `volta setup` to add it to your profile scripts[..]")
+    );
+}
+
+#[test]
+fn doctor_prints_json() {
+    let s = sandbox().shim("tsc").build();
+
+    let output = s.volta("doctor --json").build_command().output().unwrap();
+    assert_eq!(
+        output.status.code(),
+        Some(ExitCode::ConfigurationError as i32)
+    );
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["jsonVersion"], 1);
+    let checks = report["checks"].as_array().unwrap();
+    let status = |name: &str| {
+        checks
+            .iter()
+            .find(|check| check["name"] == name)
+            .map(|check| check["status"].as_str().unwrap().to_owned())
+    };
+    assert_eq!(status("shim-dir-on-path").as_deref(), Some("pass"));
+    assert_eq!(status("orphaned-shims").as_deref(), Some("warn"));
+    assert_eq!(status("volta-home").as_deref(), Some("pass"));
+    assert_eq!(status("lock").as_deref(), Some("pass"));
+}
@@ -0,0 +1,73 @@
+//! Tests for `volta env`, which prints a PATH for running the default tools without the shims
+
+use crate::support::sandbox::sandbox;
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const PLATFORM_NODE_YARN: &str = r#"{
+    "node":{
+        "runtime":"11.10.1",
+        "npm":"6.7.0"
+    },
+    "yarn": "1.22.19"
+}"#;
+
+#[test]
+fn cron_prints_a_single_path_line() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE_YARN)
+        .env("PATH", "/usr/bin:/bin")
+        .build();
+
+    assert_that!(
+        s.volta("env --cron"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout(
+                "PATH=[..]npm[..]6.7.0[..]bin:[..]yarn[..]1.22.19[..]bin:[..]node[..]11.10.1[..]bin:[..].volta[..]bin:/usr/bin:/bin"
+            )
+    );
+}
+
+#[test]
+fn ignores_project_pins() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE_YARN)
+        .package_json(r#"{ "volta": { "node": "10.99.1040" } }"#)
+        .build();
+
+    assert_that!(
+        s.volta("env --cron"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("PATH=[..]node[..]11.10.1[..]")
+            .with_stdout_does_not_contain("[..]10.99.1040[..]")
+    );
+}
+
+#[test]
+fn prints_a_shell_command_by_default() {
+    let s = sandbox().platform(PLATFORM_NODE_YARN).build();
+
+    assert_that!(
+        s.volta("env"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout("export PATH=\"[..]node[..]11.10.1[..]\"")
+    );
+}
+
+#[test]
+fn requires_a_default_platform() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("env --cron"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Node is not available.")
+    );
+}
@@ -0,0 +1,354 @@
+//! Tests for `volta fetch`, focused on recording the npm bundled with Node, on fetching the tools
+//! listed in a manifest file, and on fetching every active Node LTS line
+
+use crate::support::sandbox::{sandbox, DistroMetadata, NodeFixture, Sandbox, Yarn1Fixture};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use node_semver::Version;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const NODE_VERSION_INFO: &str = r#"[
+{"version":"v10.99.1040","npm":"6.2.26","lts": "Dubnium","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]}
+]
+"#;
+
+cfg_if::cfg_if! {
+    if #[cfg(target_os = "windows")] {
+        const NODE_VERSION_FIXTURES: [DistroMetadata; 1] = [
+            DistroMetadata {
+                version: "10.99.1040",
+                compressed_size: 1096,
+                uncompressed_size: None,
+            },
+        ];
+    } else {
+        const NODE_VERSION_FIXTURES: [DistroMetadata; 1] = [
+            DistroMetadata {
+                version: "10.99.1040",
+                compressed_size: 273,
+                uncompressed_size: Some(0x0028_0000),
+            },
+        ];
+    }
+}
+
+const LTS_FILES: &str =
+    r#""files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip","linux-arm64"]"#;
+
+/// An index with two active LTS lines (v10 and v9), each with an older release, two LTS lines that
+/// have reached end-of-life (v8 and v6), and a line without LTS releases (v7)
+fn lts_version_info() -> String {
+    let entries = [
+        ("10.99.1040", r#""Dubnium""#),
+        ("10.5.0", r#""Dubnium""#),
+        ("9.27.6", r#""Test""#),
+        ("9.1.0", r#""Test""#),
+        ("8.9.10", r#""Carbon""#),
+        ("7.10.1", "false"),
+        ("6.19.62", r#""Boron""#),
+    ];
+    let entries: Vec<String> = entries
+        .iter()
+        .map(|(version, lts)| {
+            format!(
+                r#"{{"version":"v{}","npm":"6.2.26","lts":{},{}}}"#,
+                version, lts, LTS_FILES
+            )
+        })
+        .collect();
+    format!("[\n{}\n]\n", entries.join(",\n"))
+}
+
+const LTS_SCHEDULE: &str = r#"{
+"v6": { "start": "2016-04-26", "end": "2019-04-30" },
+"v8": { "start": "2017-05-30", "end": "2019-12-31" },
+"v9": { "start": "2017-10-01", "end": "2099-06-30" },
+"v10": { "start": "2018-04-24", "end": "2099-04-30" }
+}"#;
+
+cfg_if::cfg_if! {
+    if #[cfg(target_os = "windows")] {
+        const LTS_NODE_VERSION_FIXTURES: [DistroMetadata; 2] = [
+            DistroMetadata {
+                version: "10.99.1040",
+                compressed_size: 1096,
+                uncompressed_size: None,
+            },
+            DistroMetadata {
+                version: "9.27.6",
+                compressed_size: 1068,
+                uncompressed_size: None,
+            },
+        ];
+    } else {
+        const LTS_NODE_VERSION_FIXTURES: [DistroMetadata; 2] = [
+            DistroMetadata {
+                version: "10.99.1040",
+                compressed_size: 273,
+                uncompressed_size: Some(0x0028_0000),
+            },
+            DistroMetadata {
+                version: "9.27.6",
+                compressed_size: 272,
+                uncompressed_size: Some(0x0028_0000),
+            },
+        ];
+    }
+}
+
+const YARN_1_VERSION_INFO: &str = r#"[
+{"tag_name":"v1.12.99","assets":[{"name":"yarn-v1.12.99.tar.gz"}]}
+]"#;
+
+const YARN_1_VERSION_FIXTURES: [DistroMetadata; 1] = [DistroMetadata {
+    version: "1.12.99",
+    compressed_size: 178,
+    uncompressed_size: Some(0x0028_0000),
+}];
+
+#[test]
+fn fetch_npm_bundled_for_node_records_mapping() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("fetch npm@bundled-for node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]recorded bundled npm@6.2.26 for node@10.99.1040")
+    );
+
+    assert!(s.node_inventory_archive_exists(&Version::parse("10.99.1040").unwrap()));
+    assert_eq!(
+        Sandbox::read_node_npm_version_file("10.99.1040").as_deref(),
+        Some("6.2.26")
+    );
+}
+
+#[test]
+fn fetch_npm_bundled_for_requires_node() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("fetch npm@bundled-for"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]No Node version specified for npm@bundled-for.")
+    );
+}
+
+#[test]
+fn fetch_npm_bundled_for_backfills_existing_image() {
+    let s = sandbox()
+        .layout_file("v4")
+        .node_image_npm_manifest("10.99.1040", "6.2.26")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_eq!(Sandbox::read_node_npm_version_file("10.99.1040"), None);
+
+    // The image already exists, so nothing is downloaded: the mapping comes from the image
+    assert_that!(
+        s.volta("fetch npm@bundled-for node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]recorded bundled npm@6.2.26 for node@10.99.1040")
+    );
+
+    assert_eq!(
+        Sandbox::read_node_npm_version_file("10.99.1040").as_deref(),
+        Some("6.2.26")
+    );
+}
+
+#[test]
+fn list_node_shows_recorded_mappings() {
+    let s = sandbox()
+        .layout_file("v4")
+        .node_image_npm_manifest("10.99.1040", "6.2.26")
+        .node_npm_version_file("10.99.1040", "6.2.26")
+        // An image without a bundled npm, so there is nothing to record for it
+        .file(".volta/tools/image/node/9.27.6/README.md", "")
+        .build();
+
+    assert_that!(
+        s.volta("list node --format human"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]v10.99.1040[..]")
+            .with_stdout_does_not_contain("[..](bundles[..]")
+    );
+
+    assert_that!(
+        s.volta("list node --format plain --verbose"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("runtime node@10.99.1040")
+            .with_stdout_does_not_contain("[..](bundles[..]")
+    );
+
+    assert_that!(
+        s.volta("list node --format human --verbose"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]v10.99.1040[..] (bundles npm@6.2.26)")
+            .with_stdout_does_not_contain("[..]v9.27.6 (bundles[..]")
+    );
+}
+
+#[test]
+fn fetch_manifest_reports_every_invalid_entry() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .project_file(
+            "tools.json",
+            r#"{ "tools": ["node@10.99.1040", "cowsay", "Bad Name@1", "npm@bundled-for"] }"#,
+        )
+        .build();
+
+    assert_that!(
+        s.volta("fetch --manifest tools.json"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Invalid fetch manifest [..]tools.json")
+            .with_stderr_contains(
+                "[..]tools[1] 'cowsay': packages can't be fetched, only installed"
+            )
+            .with_stderr_contains("[..]tools[2] 'Bad Name@1': [..]")
+            .with_stderr_contains("[..]tools[3] 'npm@bundled-for': [..]")
+            .with_stderr_contains("Nothing was fetched.")
+    );
+
+    // The valid entry isn't fetched either
+    assert!(!s.node_inventory_archive_exists(&Version::parse("10.99.1040").unwrap()));
+}
+
+#[test]
+fn fetch_manifest_fetches_and_reports_failures() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .project_file(
+            "tools.json",
+            r#"{ "tools": ["node@10.99.1040", "node@11.1.1", "yarn@1.12.99"] }"#,
+        )
+        .build();
+
+    assert_that!(
+        s.volta("fetch --manifest tools.json"),
+        execs()
+            .with_status(ExitCode::NetworkError as i32)
+            .with_stderr_contains("[..]Could not fetch every tool listed in [..]tools.json")
+            .with_stderr_contains("Fetched: node@10.99.1040, yarn@1.12.99")
+            .with_stderr_contains("Failed: node@11.1.1")
+    );
+
+    // The tools before and after the failure are fetched
+    assert!(s.node_inventory_archive_exists(&Version::parse("10.99.1040").unwrap()));
+    assert!(s.yarn_inventory_archive_exists("1.12.99"));
+}
+
+#[test]
+fn fetch_manifest_skips_fetched_tools() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .project_file(
+            "tools.json",
+            r#"{ "tools": ["node@10.99.1040", "yarn@1.12.99"] }"#,
+        )
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("fetch --manifest tools.json"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("node@10.99.1040  fetched")
+            .with_stdout_contains("[..]2 fetched, 0 already fetched, 0 failed")
+    );
+
+    assert_that!(
+        s.volta("fetch --manifest tools.json"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("node@10.99.1040  already fetched")
+            .with_stdout_contains("yarn@1.12.99     already fetched")
+            .with_stdout_contains("[..]0 fetched, 2 already fetched, 0 failed")
+    );
+}
+
+#[test]
+fn fetch_all_lts_fetches_newest_release_of_each_active_line() {
+    let s = sandbox()
+        .node_available_versions(&lts_version_info())
+        .distro_mocks::<NodeFixture>(&LTS_NODE_VERSION_FIXTURES)
+        .file(".volta/cache/node/schedule.json", LTS_SCHEDULE)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("fetch node --all-lts"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("Tool             Status")
+            .with_stdout_contains("node@10.99.1040  fetched")
+            .with_stdout_contains("node@9.27.6      fetched")
+            .with_stdout_contains("[..]2 fetched, 0 already fetched, 0 failed")
+    );
+
+    assert!(s.node_inventory_archive_exists(&Version::parse("10.99.1040").unwrap()));
+    assert!(s.node_inventory_archive_exists(&Version::parse("9.27.6").unwrap()));
+    for version in ["10.5.0", "9.1.0", "8.9.10", "7.10.1", "6.19.62"] {
+        assert!(
+            !s.node_inventory_archive_exists(&Version::parse(version).unwrap()),
+            "{}",
+            version
+        );
+    }
+}
+
+#[test]
+fn fetch_all_lts_skips_fetched_versions() {
+    let s = sandbox()
+        .node_available_versions(&lts_version_info())
+        .distro_mocks::<NodeFixture>(&LTS_NODE_VERSION_FIXTURES[1..])
+        .file(".volta/cache/node/schedule.json", LTS_SCHEDULE)
+        .setup_node_binary("10.99.1040", "6.2.26", "echo node")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("fetch --all-lts"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("node@10.99.1040  already fetched")
+            .with_stdout_contains("node@9.27.6      fetched")
+            .with_stdout_contains("[..]1 fetched, 1 already fetched, 0 failed")
+    );
+
+    assert!(!s.node_inventory_archive_exists(&Version::parse("10.99.1040").unwrap()));
+    assert!(s.node_inventory_archive_exists(&Version::parse("9.27.6").unwrap()));
+}
+
+#[test]
+fn fetch_all_lts_rejects_other_tools() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("fetch yarn --all-lts"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Could not fetch 'yarn' with --all-lts[..]")
+    );
+}
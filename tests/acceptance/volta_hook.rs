@@ -0,0 +1,168 @@
+//! Tests for `volta hook test`, which shows the hooks that apply to a tool without installing it
+
+use std::path::PathBuf;
+
+use crate::support::sandbox::{sandbox, Sandbox};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use mockito::mock;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+fn default_hooks_json() -> String {
+    format!(
+        r#"
+{{
+    "node": {{
+        "distro": {{
+            "template": "{0}/hook-test/default/node/{{{{version}}}}"
+        }},
+        "index": {{
+            "prefix": "{0}/hook-test/default/node-index/"
+        }}
+    }},
+    "yarn": {{
+        "distro": {{
+            "template": "{0}/hook-test/default/yarn/{{{{version}}}}"
+        }}
+    }}
+}}"#,
+        mockito::server_url()
+    )
+}
+
+fn project_hooks_json() -> String {
+    format!(
+        r#"
+{{
+    "yarn": {{
+        "distro": {{
+            "template": "{0}/hook-test/project/yarn/{{{{version}}}}"
+        }}
+    }}
+}}"#,
+        mockito::server_url()
+    )
+}
+
+#[test]
+fn shows_hooks_and_resolved_urls() {
+    let s = sandbox().default_hooks(&default_hooks_json()).build();
+
+    assert_that!(
+        s.volta("hook test node 20.11.1"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("⚡️ Hooks for node@20.11.1:")
+            .with_stdout_contains("    distro  template hook from [..]hooks.json")
+            .with_stdout_contains("            [..]/hook-test/default/node/20.11.1")
+            .with_stdout_contains("    latest  not configured")
+            .with_stdout_contains("    index   prefix hook from [..]hooks.json")
+            .with_stdout_contains("            [..]/hook-test/default/node-index/index.json")
+    );
+}
+
+#[test]
+fn project_hooks_take_precedence() {
+    let project_hooks: PathBuf = [".volta", "hooks.json"].iter().collect();
+    let s = sandbox()
+        .package_json("{}")
+        .default_hooks(&default_hooks_json())
+        .project_file(&project_hooks.to_string_lossy(), &project_hooks_json())
+        .build();
+
+    assert_that!(
+        s.volta("hook test yarn 1.22.19"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains(format!(
+                "    distro  template hook from {}",
+                s.root().join(&project_hooks).display()
+            ))
+            .with_stdout_contains("            [..]/hook-test/project/yarn/1.22.19")
+            .with_stdout_contains("    latest  not configured")
+            .with_stdout_contains("    index   not configured")
+    );
+}
+
+#[test]
+fn tools_without_hooks_are_not_configured() {
+    let s = sandbox().default_hooks(&default_hooks_json()).build();
+
+    assert_that!(
+        s.volta("hook test npm 10.2.4"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("⚡️ Hooks for npm@10.2.4:")
+            .with_stdout_contains("    distro  not configured")
+            .with_stdout_contains("    index   not configured")
+            .with_stdout_does_not_contain("[..]latest[..]")
+    );
+}
+
+#[test]
+fn requires_an_exact_version() {
+    let s = sandbox().default_hooks(&default_hooks_json()).build();
+
+    assert_that!(
+        s.volta("hook test node lts"),
+        execs()
+            .with_status(ExitCode::NoVersionMatch as i32)
+            .with_stderr_contains("[..]Could not parse version \"lts\"")
+    );
+}
+
+#[test]
+fn probe_reports_status_codes() {
+    let _found = mock("HEAD", "/hook-test/default/node/18.19.0")
+        .with_status(200)
+        .create();
+    let _missing = mock("HEAD", "/hook-test/default/node-index/index.json")
+        .with_status(404)
+        .create();
+    let s = sandbox().default_hooks(&default_hooks_json()).build();
+
+    assert_that!(
+        s.volta("hook test node 18.19.0 --probe"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stdout_contains("            [..]/hook-test/default/node/18.19.0")
+            .with_stdout_contains("            status: 200")
+            .with_stdout_contains("            [..]/hook-test/default/node-index/index.json")
+            .with_stdout_contains("            status: 404")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_bin_hooks_and_shows_their_output() {
+    const DISTRO_SCRIPT: &str = r#"#!/bin/sh
+# the version is passed after the arguments in the hook
+echo "https://mirror.example/node/v$2/node.tar.gz"
+"#;
+    const HOOKS_JSON: &str = r#"
+{
+    "node": {
+        "distro": {
+            "bin": "hook-test-distro.sh --mirror"
+        }
+    }
+}"#;
+    let s = sandbox()
+        .default_hooks(HOOKS_JSON)
+        .executable_file("hook-test-distro.sh", DISTRO_SCRIPT)
+        .build();
+
+    assert_that!(
+        s.volta("hook test node 20.11.1"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("    distro  bin hook from [..]hooks.json")
+            .with_stdout_contains("            command: hook-test-distro.sh --mirror 20.11.1")
+            .with_stdout_contains(
+                "            output: https://mirror.example/node/v20.11.1/node.tar.gz"
+            )
+    );
+    assert!(!Sandbox::path_exists(".volta/tools/image/node/20.11.1"));
+}
@@ -0,0 +1,231 @@
+//! Tests for `volta import nvm`.
+
+use crate::support::sandbox::{sandbox, DistroMetadata, NodeFixture, Sandbox};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use node_semver::Version;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const NODE_VERSION_INFO: &str = r#"[
+{"version":"v10.99.1040","npm":"6.2.26","lts": "Dubnium","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]},
+{"version":"v9.27.6","npm":"5.6.17","lts": false,"files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]}
+]
+"#;
+
+cfg_if::cfg_if! {
+    if #[cfg(target_os = "macos")] {
+        const NODE_VERSION_FIXTURES: [DistroMetadata; 2] = [
+            DistroMetadata {
+                version: "10.99.1040",
+                compressed_size: 273,
+                uncompressed_size: Some(0x0028_0000),
+            },
+            DistroMetadata {
+                version: "9.27.6",
+                compressed_size: 272,
+                uncompressed_size: Some(0x0028_0000),
+            },
+        ];
+    } else if #[cfg(target_os = "linux")] {
+        const NODE_VERSION_FIXTURES: [DistroMetadata; 2] = [
+            DistroMetadata {
+                version: "10.99.1040",
+                compressed_size: 273,
+                uncompressed_size: Some(0x0028_0000),
+            },
+            DistroMetadata {
+                version: "9.27.6",
+                compressed_size: 272,
+                uncompressed_size: Some(0x0028_0000),
+            },
+        ];
+    } else {
+        compile_error!("Unsupported target_os for tests (expected 'macos' or 'linux').");
+    }
+}
+
+fn node_bin(version: &str) -> String {
+    format!(
+        r#"#!/bin/sh
+echo "v{}"
+"#,
+        version
+    )
+}
+
+const VOLTA_LOGLEVEL: &str = "VOLTA_LOGLEVEL";
+
+#[test]
+fn import_link_existing() {
+    let s = sandbox()
+        .nvm_node_binary("10.99.1040", "6.2.26", &node_bin("10.99.1040"))
+        .nvm_node_binary("9.27.6", "5.6.17", &node_bin("9.27.6"))
+        .nvm_alias("default", "v9.27.6")
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("import nvm --link-existing --nvm-dir")
+            .arg(Sandbox::nvm_dir()),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("Imported node@10.99.1040")
+            .with_stdout_contains("Imported node@9.27.6")
+            .with_stdout_contains("[..]installed and set node@9.27.6[..]")
+            .with_stdout_contains("[..]imported 2 Node versions from nvm (0 skipped)")
+    );
+
+    assert!(Sandbox::node_image_exists("10.99.1040"));
+    assert!(Sandbox::node_image_exists("9.27.6"));
+    assert!(Sandbox::read_default_platform().contains(r#""runtime": "9.27.6""#));
+
+    // the nvm directory must be left untouched
+    assert!(Sandbox::nvm_dir()
+        .join("versions/node/v10.99.1040/bin/node")
+        .exists());
+    assert!(Sandbox::nvm_dir()
+        .join("versions/node/v9.27.6/bin/node")
+        .exists());
+}
+
+#[test]
+fn import_link_existing_version_mismatch() {
+    let s = sandbox()
+        .nvm_node_binary("10.99.1040", "6.2.26", &node_bin("1.2.3"))
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("import nvm --link-existing --nvm-dir")
+            .arg(Sandbox::nvm_dir()),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(
+                "Skipped node@10.99.1040: Expected the Node installation at [..] to be v10.99.1040, but it reported v1.2.3"
+            )
+            .with_stdout_contains("[..]imported 0 Node versions from nvm (1 skipped)")
+    );
+
+    assert!(!Sandbox::node_image_exists("10.99.1040"));
+}
+
+#[test]
+fn import_fetches_versions() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .nvm_node_binary("10.99.1040", "6.2.26", &node_bin("10.99.1040"))
+        .env("NVM_DIR", Sandbox::nvm_dir().to_str().unwrap())
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("import nvm"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("Imported node@10.99.1040")
+            .with_stdout_contains("[..]imported 1 Node version from nvm (0 skipped)")
+    );
+
+    assert!(s.node_inventory_archive_exists(&Version::parse("10.99.1040").unwrap()));
+}
+
+#[test]
+fn import_skips_existing_and_invalid_versions() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .nvm_node_binary("10.99.1040", "6.2.26", &node_bin("10.99.1040"))
+        .file(".nvm/versions/node/not-a-version/README", "")
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("fetch node@10.99.1040"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_that!(
+        s.volta("import nvm --nvm-dir").arg(Sandbox::nvm_dir()),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("Skipped node@10.99.1040: already available in Volta")
+            .with_stdout_contains("Skipped 'not-a-version': not a Node version")
+            .with_stdout_contains("[..]imported 0 Node versions from nvm (2 skipped)")
+    );
+}
+
+#[test]
+fn import_lts_alias_resolves_from_index() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .nvm_node_binary("9.27.6", "5.6.17", &node_bin("9.27.6"))
+        .nvm_alias("default", "lts/*")
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("import nvm --link-existing --nvm-dir")
+            .arg(Sandbox::nvm_dir()),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("Imported node@9.27.6")
+            .with_stdout_contains("[..]installed and set node@10.99.1040[..]")
+    );
+
+    assert!(Sandbox::read_default_platform().contains(r#""runtime": "10.99.1040""#));
+}
+
+#[test]
+fn import_follows_nvm_lts_alias_files() {
+    let s = sandbox()
+        .nvm_node_binary("9.27.6", "5.6.17", &node_bin("9.27.6"))
+        .nvm_alias("default", "lts/*")
+        .nvm_alias("lts/*", "lts/example")
+        .nvm_alias("lts/example", "v9.27.6")
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("import nvm --link-existing --nvm-dir")
+            .arg(Sandbox::nvm_dir()),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed and set node@9.27.6[..]")
+    );
+}
+
+#[test]
+fn import_unsupported_alias() {
+    let s = sandbox()
+        .nvm_node_binary("9.27.6", "5.6.17", &node_bin("9.27.6"))
+        .nvm_alias("default", "system")
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("import nvm --link-existing --nvm-dir")
+            .arg(Sandbox::nvm_dir()),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("Imported node@9.27.6")
+            .with_stderr_contains(
+                "[..]Could not import the nvm default alias 'system', as it is not supported by Volta"
+            )
+    );
+}
+
+#[test]
+fn import_missing_nvm_dir() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("import nvm --nvm-dir").arg(Sandbox::nvm_dir()),
+        execs()
+            .with_status(ExitCode::EnvironmentError as i32)
+            .with_stderr_contains("[..]Could not find any nvm-installed Node versions in [..]")
+    );
+}
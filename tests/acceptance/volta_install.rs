@@ -1,9 +1,11 @@
 use crate::support::sandbox::{
-    sandbox, DistroMetadata, NodeFixture, NpmFixture, PnpmFixture, Sandbox, Yarn1Fixture,
-    YarnBerryFixture,
+    file_reads, sandbox, DistroMetadata, NodeFixture, NpmFixture, PnpmFixture, Sandbox,
+    Yarn1Fixture, YarnBerryFixture,
 };
 use hamcrest2::assert_that;
 use hamcrest2::prelude::*;
+use node_semver::Version;
+use serde_json::Value;
 use test_support::matchers::execs;
 
 use volta_core::error::ExitCode;
@@ -262,6 +264,24 @@ fn install_node_informs_newer_npm() {
     );
 }
 
+#[test]
+fn install_node_v_prefixed_version() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("install node@v10.99.1040"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_eq!(
+        Sandbox::read_default_platform(),
+        platform_with_node("10.99.1040")
+    );
+}
+
 #[test]
 fn install_node_with_npm_hides_bundled_version() {
     let s = sandbox()
@@ -279,6 +299,45 @@ fn install_node_with_npm_hides_bundled_version() {
     );
 }
 
+#[test]
+fn install_several_tools_reads_each_configuration_once() {
+    let s = sandbox()
+        .platform(&platform_with_node("8.9.10"))
+        .package_json(
+            r#"{
+  "name": "test-package",
+  "volta": {
+    "node": "8.9.10",
+    "extends": "./base.json"
+  }
+}"#,
+        )
+        .project_file("base.json", r#"{ "name": "base" }"#)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .npm_available_versions(NPM_VERSION_INFO)
+        .distro_mocks::<NpmFixture>(&NPM_VERSION_FIXTURES)
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+
+    let output = s
+        .volta("install node@10.99.1040 npm@8.1.5 yarn@1.4.159")
+        .exec_with_output()
+        .unwrap();
+
+    let platforms = file_reads(&output.stderr, "default platform");
+    assert_eq!(platforms.values().sum::<usize>(), 1, "{:?}", platforms);
+    let manifests = file_reads(&output.stderr, "project manifest");
+    assert!(
+        manifests.values().all(|count| *count == 1),
+        "manifests read more than once: {:?}",
+        manifests
+    );
+    assert!(Sandbox::read_default_platform().contains(r#""yarn": "1.4.159""#));
+}
+
 #[test]
 fn install_npm_bundled_clears_npm() {
     let s = sandbox()
@@ -384,6 +443,109 @@ fn install_yarn_3_without_node_errors() {
     );
 }
 
+// The newest release is not an LTS release, so resolving `lts` and `current` gives different versions
+const NODE_VERSION_INFO_NEWEST_NOT_LTS: &str = r#"[
+{"version":"v10.99.1040","npm":"6.2.26","lts": false,"files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]},
+{"version":"v9.27.6","npm":"5.6.17","lts": "Erbium","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]},
+{"version":"v8.9.10","npm":"5.6.7","lts": false,"files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]}
+]
+"#;
+
+#[test]
+fn install_node_without_version_uses_lts() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO_NEWEST_NOT_LTS)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install node"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed and set node@9.27.6[..]")
+    );
+}
+
+#[test]
+fn install_node_current_uses_newest_release() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO_NEWEST_NOT_LTS)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install node@current"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed and set node@10.99.1040[..]")
+    );
+}
+
+#[test]
+fn install_first_default_node_explains_default() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]this is now your default Node version[..]")
+    );
+}
+
+#[test]
+fn install_node_porcelain() {
+    // the porcelain output is a stable format, so it is matched in full
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install --porcelain node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout(
+                "\
+image created [..]/.volta/tools/image/node/10.99.1040
+config created [..]/.volta/tools/user/platform.json"
+            )
+            .with_stderr_contains("[..]installed and set node@10.99.1040[..]")
+    );
+
+    // installing a version that is already the default changes nothing
+    assert_that!(
+        s.volta("install --porcelain node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout("")
+    );
+}
+
+#[test]
+fn install_node_over_existing_default_skips_explanation() {
+    let s = sandbox()
+        .platform(&platform_with_node("8.9.10"))
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_does_not_contain("[..]this is now your default Node version[..]")
+    );
+}
+
 #[test]
 fn install_node_with_shadowed_binary() {
     #[cfg(windows)]
@@ -406,3 +568,1093 @@ fn install_node_with_shadowed_binary() {
             .with_stdout_contains("[..]is shadowed by another binary of the same name at [..]")
     );
 }
+
+#[test]
+fn install_node_with_single_download_slot() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install node@10.99.1040 --max-concurrent-downloads 1"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed and set node@10.99.1040[..]")
+    );
+
+    assert!(s.node_inventory_archive_exists(&Version::parse("10.99.1040").unwrap()));
+}
+
+#[test]
+fn install_rejects_zero_download_slots() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("install node@10.99.1040 --max-concurrent-downloads 0"),
+        execs()
+            .with_status(2)
+            .with_stderr_contains("[..]--max-concurrent-downloads[..]")
+    );
+}
+
+#[test]
+fn install_ignores_invalid_download_limit_env() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_MAX_CONCURRENT_DOWNLOADS", "zero")
+        .env("VOLTA_LOGLEVEL", "warn")
+        .build();
+
+    assert_that!(
+        s.volta("install node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains(
+                "[..]Ignoring invalid value 'zero' for VOLTA_MAX_CONCURRENT_DOWNLOADS[..]"
+            )
+    );
+}
+
+/// Finds the control characters that draw progress in a terminal, apart from the carriage
+/// return that the terminal writes before each newline
+#[cfg(unix)]
+fn progress_control_chars(output: &[u8]) -> Vec<char> {
+    let output = String::from_utf8_lossy(output).replace("\r\n", "\n");
+    output
+        .chars()
+        .filter(|c| c.is_control() && *c != '\n')
+        .collect()
+}
+
+#[cfg(unix)]
+fn sandbox_for_terminal_install() -> Sandbox {
+    sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        // Colors are disabled so that only progress can write control characters
+        .env("CLICOLOR", "0")
+        .build()
+}
+
+#[cfg(unix)]
+#[test]
+fn install_node_in_terminal_shows_progress() {
+    let s = sandbox_for_terminal_install();
+
+    let output = s.volta("install node@10.99.1040").exec_with_pty().unwrap();
+    assert!(!progress_control_chars(&output.stdout).is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn install_node_with_no_progress_in_terminal() {
+    let s = sandbox_for_terminal_install();
+
+    let output = s
+        .volta("install node@10.99.1040 --no-progress")
+        .exec_with_pty()
+        .unwrap();
+    assert_eq!(progress_control_chars(&output.stdout), Vec::<char>::new());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("installed and set node@10.99.1040"));
+}
+
+#[cfg(unix)]
+#[test]
+fn install_node_with_no_progress_env_in_terminal() {
+    let s = sandbox_for_terminal_install();
+
+    let output = s
+        .volta("install node@10.99.1040")
+        .env("VOLTA_NO_PROGRESS", "1")
+        .exec_with_pty()
+        .unwrap();
+    assert_eq!(progress_control_chars(&output.stdout), Vec::<char>::new());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("installed and set node@10.99.1040"));
+}
+
+#[cfg(unix)]
+const LOCAL_NPM_BIN: &str = r#"#!/bin/sh
+# Link the local package into the global prefix, as `npm install --global file:<path>` does
+for arg; do src="$arg"; done
+src="${src#file:}"
+dest="$npm_config_prefix/lib/node_modules/${src##*/}"
+/bin/mkdir -p "${dest%/*}" && /bin/ln -s "$src" "$dest"
+"#;
+
+#[cfg(unix)]
+fn local_package_json(version: &str) -> String {
+    format!(
+        r#"{{
+  "name": "my-cli",
+  "version": "{}",
+  "bin": {{ "my-cli": "index.js" }}
+}}"#,
+        version
+    )
+}
+
+#[test]
+#[cfg(unix)]
+fn install_local_package_and_reinstall() {
+    let s = sandbox()
+        .platform(&platform_with_node_npm("11.10.1", "6.7.0"))
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", LOCAL_NPM_BIN)
+        .project_file("my-cli/package.json", &local_package_json("1.0.0"))
+        .project_file("my-cli/index.js", "")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install file:my-cli"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed my-cli@1.0.0 with executables: my-cli")
+    );
+    let config = Sandbox::read_package_config("my-cli");
+    assert!(config.contains(r#""version": "1.0.0""#));
+    assert!(config.contains(r#""source": ""#));
+    assert!(Sandbox::shim_exists("my-cli"));
+
+    std::fs::write(
+        s.root().join("my-cli").join("package.json"),
+        local_package_json("1.1.0"),
+    )
+    .unwrap();
+
+    assert_that!(
+        s.volta("install file:my-cli"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed my-cli@1.1.0 with executables: my-cli")
+    );
+    let config = Sandbox::read_package_config("my-cli");
+    assert!(config.contains(r#""version": "1.1.0""#));
+    assert!(Sandbox::shim_exists("my-cli"));
+}
+
+#[test]
+#[cfg(unix)]
+fn install_local_package_copies_the_checkout() {
+    let s = sandbox()
+        .platform(&platform_with_node_npm("11.10.1", "6.7.0"))
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", LOCAL_NPM_BIN)
+        .project_file("my-cli/package.json", &local_package_json("1.0.0"))
+        .project_file("my-cli/index.js", "")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install file:my-cli"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    std::fs::remove_dir_all(s.root().join("my-cli")).unwrap();
+
+    let installed = Sandbox::package_image_path("my-cli").join("lib/node_modules/my-cli");
+    assert!(!installed
+        .symlink_metadata()
+        .unwrap()
+        .file_type()
+        .is_symlink());
+    assert!(installed.join("index.js").is_file());
+
+    assert_that!(
+        s.volta("list my-cli --format human"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]my-cli@1.0.0 (from [..]my-cli) (default)")
+    );
+
+    assert_that!(
+        s.volta("list my-cli --format plain"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("package my-cli@1.0.0 / my-cli / [..] (default)")
+            .with_stdout_does_not_contain("[..](from[..]")
+    );
+
+    let output = s.volta("list --json").exec_with_output().unwrap();
+    let list: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let installed_from = list["packages"][0]["installedFrom"].as_str().unwrap();
+    assert!(installed_from.ends_with("my-cli"));
+}
+
+#[test]
+#[cfg(unix)]
+fn install_local_package_without_shims() {
+    let s = sandbox()
+        .platform(&platform_with_node_npm("11.10.1", "6.7.0"))
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", LOCAL_NPM_BIN)
+        .project_file("my-cli/package.json", &local_package_json("1.0.0"))
+        .project_file("my-cli/index.js", "")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install --no-shims file:my-cli"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(
+                "[..]installed my-cli@1.0.0 without shims for its executables: my-cli"
+            )
+    );
+    let config = Sandbox::read_package_config("my-cli");
+    assert!(config.contains(r#""shims": false"#), "{}", config);
+    assert!(config.contains(r#""my-cli""#), "{}", config);
+    assert!(Sandbox::package_image_path("my-cli").is_dir());
+    assert!(!Sandbox::shim_exists("my-cli"));
+    assert!(!Sandbox::bin_config_exists("my-cli"));
+
+    assert_that!(
+        s.volta("list my-cli --format plain"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(
+                "package my-cli@1.0.0 / my-cli / node@11.10.1 npm@built-in (default) (no shims)"
+            )
+    );
+
+    // Installing again without the flag adds the shims
+    assert_that!(
+        s.volta("install file:my-cli"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed my-cli@1.0.0 with executables: my-cli")
+    );
+    let config = Sandbox::read_package_config("my-cli");
+    assert!(!config.contains(r#""shims""#), "{}", config);
+    assert!(Sandbox::shim_exists("my-cli"));
+    assert!(Sandbox::bin_config_exists("my-cli"));
+
+    assert_that!(
+        s.volta("list my-cli --format plain"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_does_not_contain("[..](no shims)[..]")
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn install_local_package_skips_reserved_bins() {
+    let s = sandbox()
+        .platform(&platform_with_node_npm("11.10.1", "6.7.0"))
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", LOCAL_NPM_BIN)
+        .project_file(
+            "volta-wrapper/package.json",
+            r#"{ "name": "volta-wrapper", "version": "1.0.0", "bin": { "volta": "volta.js", "volta-wrapper": "wrapper.js" } }"#,
+        )
+        .project_file("volta-wrapper/volta.js", "")
+        .project_file("volta-wrapper/wrapper.js", "")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install file:volta-wrapper"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains(
+                "[..]Package 'volta-wrapper' provides executables that would shadow Volta's own commands, so they were skipped: volta"
+            )
+            .with_stderr_contains("[..]The other executables were installed: volta-wrapper")
+            .with_stdout_contains(
+                "[..]installed volta-wrapper@1.0.0 with executables: volta-wrapper"
+            )
+    );
+    assert!(!Sandbox::shim_exists("volta"));
+    assert!(!Sandbox::bin_config_exists("volta"));
+    assert!(Sandbox::shim_exists("volta-wrapper"));
+
+    let config = Sandbox::read_package_config("volta-wrapper");
+    assert!(!config.contains(r#""volta","#));
+}
+
+#[test]
+fn install_local_package_requires_manifest() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("install file:missing-cli"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Could not find a package to install at[..]missing-cli")
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn install_local_package_with_directories_bin() {
+    let s = sandbox()
+        .platform(&platform_with_node_npm("11.10.1", "6.7.0"))
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", LOCAL_NPM_BIN)
+        .project_file(
+            "dir-cli/package.json",
+            r#"{ "name": "dir-cli", "version": "2.0.0", "directories": { "bin": "scripts" } }"#,
+        )
+        .project_file("dir-cli/scripts/dir-build", "")
+        .project_file("dir-cli/scripts/dir-serve", "")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install file:dir-cli"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(
+                "[..]installed dir-cli@2.0.0 with executables: dir-build, dir-serve"
+            )
+    );
+    assert!(Sandbox::shim_exists("dir-build"));
+    assert!(Sandbox::shim_exists("dir-serve"));
+    assert!(Sandbox::bin_config_exists("dir-build"));
+}
+
+#[cfg(unix)]
+const TARBALL_NPM_BIN: &str = r#"#!/bin/sh
+# Unpack the tarball into the global prefix under the name in its manifest, as npm would
+for arg; do src="$arg"; done
+src="${src#file:}"
+unpacked="$npm_config_prefix/unpacked"
+/bin/mkdir -p "$unpacked" && /bin/gzip -dc "$src" | /bin/tar -x -C "$unpacked" || exit 1
+name=$(/bin/sed -n 's/.*"name": *"\([^"]*\)".*/\1/p' "$unpacked/package/package.json")
+dest="$npm_config_prefix/lib/node_modules/$name"
+/bin/mkdir -p "$dest" && /bin/cp -R "$unpacked/package/." "$dest" && /bin/rm -rf "$unpacked"
+"#;
+
+#[test]
+#[cfg(unix)]
+fn install_package_from_tarball() {
+    let s = sandbox()
+        .platform(&platform_with_node_npm("11.10.1", "6.7.0"))
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", TARBALL_NPM_BIN)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+    std::fs::copy(
+        "tests/fixtures/volta-test-1.0.0.tgz",
+        s.root().join("volta-test-1.0.0.tgz"),
+    )
+    .unwrap();
+
+    assert_that!(
+        s.volta("install ./volta-test-1.0.0.tgz"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed volta-test@1.0.0 with executables: volta-test")
+    );
+    let config = Sandbox::read_package_config("volta-test");
+    assert!(config.contains(r#""name": "volta-test""#));
+    assert!(config.contains(r#""source": ""#));
+    assert!(config.contains(r#"volta-test-1.0.0.tgz""#));
+    assert!(Sandbox::shim_exists("volta-test"));
+    assert!(Sandbox::bin_config_exists("volta-test"));
+}
+
+#[test]
+fn install_package_from_missing_tarball() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("install ../missing-1.0.0.tgz"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains(
+                "[..]Could not find a package to install at[..]missing-1.0.0.tgz"
+            )
+    );
+}
+
+#[cfg(unix)]
+const POSTINSTALL_NPM_BIN: &str = r#"#!/bin/sh
+# Copy the package and link its bins to the files in its manifest, then run its postinstall, as
+# `npm install --global file:<path>` would
+for arg; do src="$arg"; done
+src="${src#file:}"
+dest="$npm_config_prefix/lib/node_modules/swap-cli"
+/bin/mkdir -p "$dest" "$npm_config_prefix/bin" && /bin/cp -R "$src/." "$dest" || exit 1
+/bin/ln -s ../lib/node_modules/swap-cli/stub.js "$npm_config_prefix/bin/swap-cli"
+/bin/ln -s ../lib/node_modules/swap-cli/helper.js "$npm_config_prefix/bin/swap-helper"
+cd "$dest" && /bin/sh postinstall.sh
+"#;
+
+#[cfg(unix)]
+const SWAP_POSTINSTALL: &str = r#"# Replace the stub with the real script, as downloaders like playwright do
+echo "real" > cli.js
+/bin/rm stub.js helper.js
+/bin/cat > package.json <<EOF
+{ "name": "swap-cli", "version": "1.0.0", "bin": { "swap-cli": "cli.js", "swap-helper": "helper.js" } }
+EOF
+"#;
+
+#[test]
+#[cfg(unix)]
+fn install_package_whose_postinstall_swaps_its_bin() {
+    let s = sandbox()
+        .platform(&platform_with_node_npm("11.10.1", "6.7.0"))
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", POSTINSTALL_NPM_BIN)
+        .project_file(
+            "swap-cli/package.json",
+            r#"{ "name": "swap-cli", "version": "1.0.0", "bin": { "swap-cli": "stub.js", "swap-helper": "helper.js" } }"#,
+        )
+        .project_file("swap-cli/stub.js", "stub")
+        .project_file("swap-cli/helper.js", "helper")
+        .project_file("swap-cli/postinstall.sh", SWAP_POSTINSTALL)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install file:swap-cli"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains(
+                "[..]Skipping executable 'swap-helper' of package 'swap-cli', as the file it points to doesn't exist after the install: [..]helper.js"
+            )
+            .with_stdout_contains("[..]installed swap-cli@1.0.0 with executables: swap-cli")
+    );
+    assert!(Sandbox::shim_exists("swap-cli"));
+    assert!(!Sandbox::shim_exists("swap-helper"));
+    assert!(!Sandbox::bin_config_exists("swap-helper"));
+
+    let bin = Sandbox::home_path(".volta/tools/image/packages/swap-cli/bin/swap-cli");
+    assert_eq!(std::fs::read_to_string(bin).unwrap(), "real\n");
+}
+
+#[cfg(unix)]
+const NPM_DUPLICATE_DIR: &str = ".volta/tools/image/node/11.10.1/lib/node_modules/my-cli";
+
+#[test]
+#[cfg(unix)]
+fn install_local_package_reports_npm_duplicate() {
+    let s = sandbox()
+        .platform(&platform_with_node_npm("11.10.1", "6.7.0"))
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", LOCAL_NPM_BIN)
+        .file(
+            &format!("{}/package.json", NPM_DUPLICATE_DIR),
+            &local_package_json("0.9.0"),
+        )
+        .project_file("my-cli/package.json", &local_package_json("1.0.0"))
+        .project_file("my-cli/index.js", "")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install file:my-cli"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]my-cli@0.9.0 is also installed globally with npm, in:")
+            .with_stderr_contains("    [..]/tools/image/node/11.10.1/lib/node_modules/my-cli")
+            .with_stderr_contains(
+                "    npm uninstall --global --prefix [..]/tools/image/node/11.10.1 my-cli"
+            )
+            .with_stdout_contains("[..]installed my-cli@1.0.0 with executables: my-cli")
+    );
+    assert!(Sandbox::path_exists(NPM_DUPLICATE_DIR));
+}
+
+#[test]
+#[cfg(unix)]
+fn install_local_package_reports_duplicate_in_prefix_from_global_npmrc() {
+    let s = sandbox()
+        .platform(&platform_with_node_npm("11.10.1", "6.7.0"))
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", LOCAL_NPM_BIN)
+        .file(
+            ".volta/tools/image/node/11.10.1/etc/npmrc",
+            &format!("prefix={}\n", Sandbox::home_path("npm-global").display()),
+        )
+        .file(
+            "npm-global/lib/node_modules/my-cli/package.json",
+            &local_package_json("0.9.0"),
+        )
+        .project_file("my-cli/package.json", &local_package_json("1.0.0"))
+        .project_file("my-cli/index.js", "")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install file:my-cli"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]my-cli@0.9.0 is also installed globally with npm, in:")
+            .with_stderr_contains("    npm uninstall --global --prefix [..]/npm-global my-cli")
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn install_local_package_takes_over_npm_duplicate() {
+    let s = sandbox()
+        .platform(&platform_with_node_npm("11.10.1", "6.7.0"))
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", LOCAL_NPM_BIN)
+        .file(
+            ".volta/tools/image/node/11.10.1/etc/npmrc",
+            &format!("prefix={}\n", Sandbox::home_path("npm-global").display()),
+        )
+        .file(
+            "npm-global/lib/node_modules/my-cli/package.json",
+            &local_package_json("0.9.0"),
+        )
+        .project_file("my-cli/package.json", &local_package_json("1.0.0"))
+        .project_file("my-cli/index.js", "")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install --take-over file:my-cli"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(
+                "[..]removed my-cli@0.9.0 that was installed with npm in [..]/npm-global/lib/node_modules/my-cli"
+            )
+            .with_stderr_does_not_contain("[..]is also installed globally with npm[..]")
+    );
+    assert!(!Sandbox::path_exists("npm-global/lib/node_modules/my-cli"));
+    assert!(Sandbox::shim_exists("my-cli"));
+}
+
+#[test]
+#[cfg(unix)]
+fn install_local_package_take_over_leaves_node_image_alone() {
+    let s = sandbox()
+        .platform(&platform_with_node_npm("11.10.1", "6.7.0"))
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", LOCAL_NPM_BIN)
+        .file(
+            &format!("{}/package.json", NPM_DUPLICATE_DIR),
+            &local_package_json("0.9.0"),
+        )
+        .project_file("my-cli/package.json", &local_package_json("1.0.0"))
+        .project_file("my-cli/index.js", "")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install --take-over file:my-cli"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]my-cli@0.9.0 is also installed globally with npm, in:")
+            .with_stderr_contains(
+                "It is in a Node image managed by Volta, so it isn't removed automatically."
+            )
+            .with_stdout_does_not_contain("[..]removed my-cli@0.9.0[..]")
+    );
+    assert!(Sandbox::path_exists(NPM_DUPLICATE_DIR));
+}
+
+#[test]
+#[cfg(unix)]
+fn install_corepack_ignores_the_copy_bundled_with_node() {
+    const BUNDLED_COREPACK: &str = ".volta/tools/image/node/11.10.1/lib/node_modules/corepack";
+    let corepack_json = r#"{
+  "name": "corepack",
+  "version": "0.20.0",
+  "bin": { "corepack": "index.js" }
+}"#;
+
+    let s = sandbox()
+        .platform(&platform_with_node_npm("11.10.1", "6.7.0"))
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", LOCAL_NPM_BIN)
+        .file(&format!("{}/package.json", BUNDLED_COREPACK), corepack_json)
+        .project_file(
+            "corepack/package.json",
+            &corepack_json.replace("0.20.0", "0.29.4"),
+        )
+        .project_file("corepack/index.js", "")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install --take-over file:corepack"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed corepack@0.29.4 with executables: corepack")
+            .with_stderr_does_not_contain("[..]is also installed globally with npm[..]")
+            .with_stdout_does_not_contain("[..]removed corepack[..]")
+    );
+    assert!(Sandbox::path_exists(BUNDLED_COREPACK));
+}
+
+fn npmrc_with_private_registry(token: Option<&str>) -> String {
+    let server = mockito::server_url();
+    let mut npmrc = format!("registry={}/private/\n", server);
+    if let Some(token) = token {
+        let host = server.trim_start_matches("http:");
+        npmrc.push_str(&format!("{}/private/:_authToken={}\n", host, token));
+    }
+    npmrc
+}
+
+#[test]
+fn install_npm_resolves_from_npmrc_registry_with_auth() {
+    let s = sandbox()
+        .platform(&platform_with_node("10.99.1040"))
+        .package_json(r#"{ "name": "private-project" }"#)
+        .project_file(".npmrc", &npmrc_with_private_registry(Some("npmrc-secret")))
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .distro_mock_at::<NpmFixture>(&NPM_VERSION_FIXTURES[2], "/private/npm/-/npm-8.1.5.tgz")
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+    let _registry = mockito::mock("GET", "/private/npm")
+        .match_header("authorization", "Bearer npmrc-secret")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(NPM_VERSION_INFO)
+        .create();
+
+    assert_that!(
+        s.volta("install npm@8"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains(
+                "[..]Found npm@8.1.5 matching requirement '>=8.0.0 <9.0.0-0' from [..]/private/npm"
+            )
+            .with_stdout_contains("[..]installed and set npm@8.1.5 as default")
+            .with_stdout_does_not_contain("[..]npmrc-secret[..]")
+            .with_stderr_does_not_contain("[..]npmrc-secret[..]")
+    );
+}
+
+#[test]
+fn install_npm_from_npmrc_registry_without_auth_fails() {
+    let s = sandbox()
+        .platform(&platform_with_node("10.99.1040"))
+        .package_json(r#"{ "name": "private-project" }"#)
+        .project_file(".npmrc", &npmrc_with_private_registry(None))
+        .build();
+    let _registry = mockito::mock("GET", "/private/npm")
+        .match_header("authorization", "Bearer npmrc-secret")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(NPM_VERSION_INFO)
+        .create();
+
+    assert_that!(
+        s.volta("install npm@8"),
+        execs()
+            .with_status(ExitCode::NetworkError as i32)
+            .with_stderr_contains("[..]Could not download npm version registry")
+    );
+}
+
+const NODE_INDEX_ERROR_PAGE: &str =
+    "<!DOCTYPE html>\n<html><head><title>503 Service Unavailable</title></head></html>\n";
+
+fn node_index_cache() -> String {
+    format!(
+        "{}/node-dist/index.json\n{}",
+        mockito::server_url(),
+        NODE_VERSION_INFO
+    )
+}
+
+#[test]
+fn install_node_reads_compact_index_once_written() {
+    let s = sandbox()
+        .node_cache(&node_index_cache(), false)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+    let compact_file = Sandbox::home_path(".volta/cache/node/index.compact");
+
+    assert_that!(
+        s.volta("install node@10"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Wrote the compact Node index to [..]")
+    );
+    let compact = std::fs::read_to_string(&compact_file).unwrap();
+    assert!(compact.starts_with("volta-node-index 1\n"), "{}", compact);
+
+    assert_that!(
+        s.volta("install node@9"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Read the compact Node index from [..]")
+            .with_stdout_contains("[..]installed and set node@9.27.6[..]")
+    );
+
+    // A compact index written from another JSON cache is replaced
+    std::fs::write(
+        &compact_file,
+        compact.replacen('\n', "\nhttps://example.com\n", 1),
+    )
+    .unwrap();
+    assert_that!(
+        s.volta("install node@10"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]The compact Node index is outdated, using the JSON index")
+            .with_stderr_contains("[..]Wrote the compact Node index to [..]")
+    );
+    assert_eq!(std::fs::read_to_string(&compact_file).unwrap(), compact);
+}
+
+#[test]
+fn install_node_falls_back_to_expired_index_on_unparseable_response() {
+    let _index = mockito::mock("GET", "/node-dist/index.json")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(NODE_INDEX_ERROR_PAGE)
+        .create();
+    let s = sandbox()
+        .node_cache(&node_index_cache(), true)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install node@10"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed and set node@10.99.1040[..]")
+            .with_stderr_contains(
+                "warning:[..]Could not refresh the Node version index from [..]/node-dist/index.json, using the cached index instead.[..]"
+            )
+    );
+    assert_eq!(Sandbox::read_node_index_cache(), node_index_cache());
+}
+
+#[test]
+fn fetch_refresh_index_ignores_a_fresh_index() {
+    let _index = mockito::mock("GET", "/node-dist/index.json")
+        .with_status(503)
+        .with_body(NODE_INDEX_ERROR_PAGE)
+        .expect(1)
+        .create();
+    let s = sandbox()
+        .node_cache(&node_index_cache(), false)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("fetch node@9"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    assert_that!(
+        s.volta("fetch --refresh-index node@10"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("warning:[..]Could not refresh the Node version index[..]")
+    );
+    _index.assert();
+}
+
+#[test]
+fn install_node_treats_an_unparseable_expiry_as_expired() {
+    let s = sandbox()
+        .node_cache(&node_index_cache(), false)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+    // The format of older versions, which only held the `Expires` date
+    std::fs::write(
+        Sandbox::home_path(".volta/cache/node/index.json.expires"),
+        "Wed, 21 Oct 2099 07:28:00 GMT",
+    )
+    .unwrap();
+
+    assert_that!(
+        s.volta("install node@10"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains(
+            "[..]Could not parse the Node index expiry file [..], treating the cache as expired"
+        )
+    );
+    let expiry =
+        std::fs::read_to_string(Sandbox::home_path(".volta/cache/node/index.json.expires"))
+            .unwrap();
+    assert!(expiry.starts_with("received: "), "{}", expiry);
+}
+
+#[test]
+fn install_node_falls_back_to_expired_index_on_server_error() {
+    let _index = mockito::mock("GET", "/node-dist/index.json")
+        .with_status(503)
+        .with_body(NODE_INDEX_ERROR_PAGE)
+        .create();
+    let s = sandbox()
+        .node_cache(&node_index_cache(), true)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install node@10"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed and set node@10.99.1040[..]")
+            .with_stderr_contains("warning:[..]Could not refresh the Node version index[..]")
+    );
+    assert_eq!(Sandbox::read_node_index_cache(), node_index_cache());
+}
+
+#[test]
+fn install_node_without_cache_reports_unparseable_response() {
+    let _index = mockito::mock("GET", "/node-dist/index.json")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(NODE_INDEX_ERROR_PAGE)
+        .create();
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("install node@10"),
+        execs()
+            .with_status(ExitCode::NetworkError as i32)
+            .with_stderr_contains("[..]Could not parse Node version index")
+            .with_stderr_contains("The response began with:")
+            .with_stderr_contains("<!DOCTYPE html>")
+            .with_stderr_contains(
+                "<html><head><title>503 Service Unavailable</title></head></html>"
+            )
+    );
+}
+
+/// Parses the JSON progress records that were written to stderr
+fn progress_records(stderr: &[u8]) -> Vec<Value> {
+    String::from_utf8_lossy(stderr)
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn install_reports_json_progress() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_PROGRESS", "json")
+        .build();
+
+    let output = s
+        .volta("install node@10.99.1040")
+        .exec_with_output()
+        .unwrap();
+    let records = progress_records(&output.stderr);
+
+    // The fixture is read in well under the 100ms between records, so only the first record and
+    // the final one are reported
+    assert!(
+        (1..=2).contains(&records.len()),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    for record in &records {
+        assert_eq!(record["tool"], "node");
+        assert_eq!(record["version"], "10.99.1040");
+        assert!(record["read"].as_u64().unwrap() <= record["total"].as_u64().unwrap());
+    }
+    let last = records.last().unwrap();
+    assert_eq!(last["read"], last["total"]);
+}
+
+#[test]
+fn progress_flag_reports_json_for_each_tool() {
+    let s = sandbox()
+        .platform(&platform_with_node("10.99.1040"))
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .npm_available_versions(NPM_VERSION_INFO)
+        .distro_mocks::<NpmFixture>(&NPM_VERSION_FIXTURES)
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .build();
+
+    let output = s
+        .volta("install npm@8.1.5 yarn@1.12.99 --progress json")
+        .exec_with_output()
+        .unwrap();
+    let tools: Vec<(Value, Value)> = progress_records(&output.stderr)
+        .into_iter()
+        .map(|record| (record["tool"].clone(), record["version"].clone()))
+        .collect();
+
+    assert!(tools.contains(&("npm".into(), "8.1.5".into())));
+    assert!(tools.contains(&("yarn".into(), "1.12.99".into())));
+}
+
+#[test]
+fn no_progress_hides_json_progress() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_PROGRESS", "json")
+        .build();
+
+    assert_that!(
+        s.volta("install node@10.99.1040 --no-progress"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_does_not_contain("[..]\"tool\"[..]")
+    );
+}
+
+#[cfg(unix)]
+const MANIFEST_NPM_BIN: &str = r#"#!/bin/sh
+# Install the requested package into the global prefix, failing as the registry would for a
+# package that doesn't exist
+for arg; do spec="$arg"; done
+name="${spec%@*}"
+if [ "$name" = "missing-tool" ]; then
+  echo "npm ERR! code E404" >&2
+  exit 1
+fi
+dest="$npm_config_prefix/lib/node_modules/$name"
+/bin/mkdir -p "$dest"
+echo "{\"name\": \"$name\", \"version\": \"1.4.0\", \"bin\": {\"$name\": \"index.js\"}}" > "$dest/package.json"
+: > "$dest/index.js"
+"#;
+
+#[cfg(unix)]
+const PROJECT_PINNING_NODE_10: &str = r#"{
+  "name": "team-project",
+  "volta": {
+    "node": "10.99.1040",
+    "npm": "6.7.0"
+  }
+}"#;
+
+#[cfg(unix)]
+const TOOLS_MANIFEST: &str = r#"{
+  "packages": {
+    "cowsay": "^1.4",
+    "missing-tool": "2.0.0"
+  }
+}"#;
+
+#[test]
+#[cfg(unix)]
+fn install_from_manifest_continues_past_failures() {
+    let s = sandbox()
+        .platform(&platform_with_node_npm("11.10.1", "6.7.0"))
+        .package_json(PROJECT_PINNING_NODE_10)
+        .project_file(".volta/tools.json", TOOLS_MANIFEST)
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_node_binary("10.99.1040", "6.2.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", MANIFEST_NPM_BIN)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install --manifest"),
+        execs()
+            .with_status(ExitCode::UnknownError as i32)
+            .with_stdout_contains("[..]installed cowsay@1.4.0 with executables: cowsay")
+            .with_stdout_contains("cowsay@^1.4         installed")
+            .with_stdout_contains("missing-tool@2.0.0  failed")
+            .with_stdout_contains("[..]1 installed, 0 already installed, 1 failed")
+            .with_stderr_contains(
+                "[..]Could not find 'missing-tool@2.0.0' in the package registry."
+            )
+            .with_stderr_contains(
+                "[..]Could not install a tool from the tools manifest: missing-tool@2.0.0"
+            )
+    );
+
+    // The package is installed with the project's platform rather than the default one
+    let config = Sandbox::read_package_config("cowsay");
+    assert!(config.contains(r#""node": "10.99.1040""#), "{}", config);
+    assert!(Sandbox::shim_exists("cowsay"));
+
+    assert_that!(
+        s.volta("install --manifest"),
+        execs()
+            .with_status(ExitCode::UnknownError as i32)
+            .with_stdout_contains("cowsay@^1.4         already installed")
+            .with_stdout_contains("[..]0 installed, 1 already installed, 1 failed")
+    );
+}
+
+const PROJECT_WITHOUT_TOOLS: &str = r#"{
+  "name": "team-project"
+}"#;
+
+#[test]
+fn install_from_manifest_requires_a_tools_manifest() {
+    let s = sandbox()
+        .package_json(PROJECT_WITHOUT_TOOLS)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("install --manifest"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Could not find a `.volta/tools.json` file in this project.")
+    );
+}
+
+#[cfg(unix)]
+const PROJECT_PINNING_NPM_8: &str = r#"{
+  "name": "team-project",
+  "volta": {
+    "node": "10.99.1040",
+    "npm": "8.1.5"
+  }
+}"#;
+
+#[test]
+#[cfg(unix)]
+fn install_from_manifest_fetches_the_project_npm_once() {
+    let s = sandbox()
+        .platform(&platform_with_node("11.10.1"))
+        .package_json(PROJECT_PINNING_NPM_8)
+        .project_file(".volta/tools.json", TOOLS_MANIFEST)
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_node_binary("10.99.1040", "6.2.0", "#!/bin/sh\n")
+        .distro_mocks::<NpmFixture>(&NPM_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+
+    let output = s
+        .volta("install --manifest")
+        .build_command()
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(
+        stderr.matches("Downloading npm@8.1.5").count(),
+        1,
+        "{}",
+        stderr
+    );
+    assert!(
+        stderr.contains("npm@8.1.5 was already checked out in this invocation"),
+        "{}",
+        stderr
+    );
+
+    // The memo only lasts for one invocation, so the next one finds the fetched npm again
+    let output = s
+        .volta("install --manifest")
+        .build_command()
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("Downloading npm@8.1.5"), "{}", stderr);
+    assert_eq!(
+        stderr
+            .matches("npm@8.1.5 was already checked out in this invocation")
+            .count(),
+        1,
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,344 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::support::sandbox::{sandbox, Sandbox, SandboxBuilder};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use node_semver::Version;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+use volta_core::tool::Node;
+
+fn node_archive() -> String {
+    Node::archive_filename(&Version::parse("10.99.1040").unwrap())
+}
+
+/// Creates a directory of archives in the sandbox, with the fixture archive for Node 10.99.1040
+fn archive_dir(s: &Sandbox) -> PathBuf {
+    let dir = s.root().join("archives");
+    fs::create_dir_all(&dir).unwrap();
+    fs::copy(
+        Path::new("tests/fixtures").join(node_archive()),
+        dir.join(node_archive()),
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn installs_node_from_archive_dir() {
+    // No distro mocks are set up, so any attempt to download Node would fail
+    let s = sandbox().env("VOLTA_LOGLEVEL", "info").build();
+    let dir = archive_dir(&s);
+
+    assert_that!(
+        s.volta(&format!(
+            "install node@10.99.1040 --offline-from {}",
+            dir.display()
+        )),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed and set node@10.99.1040[..]")
+    );
+
+    assert!(Sandbox::read_default_platform().contains(r#""runtime": "10.99.1040""#));
+    assert!(Sandbox::path_exists(&format!(
+        ".volta/tools/inventory/node/{}",
+        node_archive()
+    )));
+}
+
+#[test]
+fn reports_missing_archive() {
+    let s = sandbox().build();
+    let dir = s.root().join("archives");
+    fs::create_dir_all(&dir).unwrap();
+
+    assert_that!(
+        s.volta(&format!(
+            "install node@10.99.1040 --offline-from {}",
+            dir.display()
+        )),
+        execs()
+            .with_status(ExitCode::FileSystemError as i32)
+            .with_stderr_contains("[..]Could not find the archive for node@10.99.1040 in [..]")
+            .with_stderr_contains(format!("Expected a file named: {}", node_archive()))
+    );
+}
+
+#[test]
+fn rejects_archive_with_wrong_checksum() {
+    let s = sandbox().build();
+    let dir = archive_dir(&s);
+    fs::write(
+        dir.join(format!("{}.sha256", node_archive())),
+        format!("{}  {}\n", "0".repeat(64), node_archive()),
+    )
+    .unwrap();
+
+    assert_that!(
+        s.volta(&format!(
+            "install node@10.99.1040 --offline-from {}",
+            dir.display()
+        )),
+        execs()
+            .with_status(ExitCode::FileSystemError as i32)
+            .with_stderr_contains("[..]does not match its checksum file")
+    );
+
+    assert!(!Sandbox::path_exists(".volta/tools/image/node/10.99.1040"));
+}
+
+#[test]
+fn requires_exact_versions() {
+    let s = sandbox().build();
+    let dir = archive_dir(&s);
+
+    assert_that!(
+        s.volta(&format!("install node@10 --offline-from {}", dir.display())),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Could not install node@[..] from a directory of archives.")
+    );
+}
+
+#[cfg(unix)]
+const NODE_NPM_PLATFORM: &str = r#"{
+  "node": {
+    "runtime": "11.10.1",
+    "npm": "6.7.0"
+  },
+  "pnpm": null,
+  "yarn": null
+}"#;
+
+/// An npm that records its arguments and installs an empty `cowsay` into the global prefix
+#[cfg(unix)]
+fn recording_npm_bin() -> String {
+    format!(
+        r#"#!/bin/sh
+echo "$@" > "{}"
+dest="$npm_config_prefix/lib/node_modules/cowsay"
+/bin/mkdir -p "$dest"
+echo '{{"name": "cowsay", "version": "1.4.0"}}' > "$dest/package.json"
+"#,
+        Sandbox::home_path("npm-args").display()
+    )
+}
+
+#[test]
+#[cfg(unix)]
+fn installs_package_from_archive_dir() {
+    let s = sandbox()
+        .platform(NODE_NPM_PLATFORM)
+        .setup_node_binary("11.10.1", "6.5.0", "#!/bin/sh\n")
+        .setup_npm_binary("6.7.0", &recording_npm_bin())
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+    let dir = s.root().join("archives");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("cowsay-1.4.0.tgz"), "cowsay").unwrap();
+
+    assert_that!(
+        s.volta(&format!(
+            "install cowsay@1.4.0 --offline-from {}",
+            dir.display()
+        )),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed cowsay@1.4.0[..]")
+    );
+
+    let args = fs::read_to_string(Sandbox::home_path("npm-args")).unwrap();
+    assert!(args.contains(&format!(
+        "--offline file:{}",
+        dir.join("cowsay-1.4.0.tgz").display()
+    )));
+    assert!(!args.contains("--registry"));
+}
+
+/// Hooks that send every Node and Yarn lookup to a port that nothing listens on, so that any
+/// network access fails
+const UNREACHABLE_HOOKS: &str = r#"{
+    "node": {
+        "index": { "prefix": "http://127.0.0.1:9/node/" },
+        "distro": { "prefix": "http://127.0.0.1:9/node/" }
+    },
+    "yarn": {
+        "index": { "prefix": "http://127.0.0.1:9/yarn/", "format": "npm" },
+        "distro": { "prefix": "http://127.0.0.1:9/yarn/" }
+    }
+}"#;
+
+/// A cached copy of the Node index for the unreachable hooks, in which only 9.27.6 is an LTS release
+const UNREACHABLE_NODE_INDEX_CACHE: &str = r#"http://127.0.0.1:9/node/index.json
+[
+{"version":"v10.99.1040","npm":"6.2.26","lts": false,"files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]},
+{"version":"v9.27.6","npm":"5.6.17","lts": "Erbium","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]},
+{"version":"v8.9.10","npm":"5.6.7","lts": false,"files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]}
+]
+"#;
+
+const NODE_PLATFORM: &str = r#"{
+  "node": {
+    "runtime": "10.99.1040",
+    "npm": null
+  },
+  "pnpm": null,
+  "yarn": null
+}"#;
+
+const COWSAY_CONFIG: &str = r#"{
+  "name": "cowsay",
+  "version": "1.4.0",
+  "platform": {
+    "node": "10.99.1040",
+    "npm": null,
+    "yarn": null
+  },
+  "bins": [
+    "cowsay"
+  ],
+  "manager": "Npm"
+}"#;
+
+/// A sandbox with unreachable hooks and a few fetched Node versions
+fn offline_sandbox() -> SandboxBuilder {
+    sandbox()
+        .default_hooks(UNREACHABLE_HOOKS)
+        .setup_node_binary("8.9.10", "5.6.7", "#!/bin/sh\n")
+        .setup_node_binary("9.27.6", "5.6.17", "#!/bin/sh\n")
+        .setup_node_binary("10.99.1040", "6.2.26", "#!/bin/sh\n")
+        .env("VOLTA_LOGLEVEL", "info")
+}
+
+#[test]
+fn offline_install_uses_fetched_node_versions() {
+    let s = offline_sandbox().build();
+
+    assert_that!(
+        s.volta("install node@9 --offline"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed and set node@9.27.6[..]")
+    );
+    assert!(Sandbox::read_default_platform().contains(r#""runtime": "9.27.6""#));
+}
+
+#[test]
+fn offline_install_finds_lts_in_expired_index() {
+    let s = offline_sandbox()
+        .node_cache(UNREACHABLE_NODE_INDEX_CACHE, true)
+        .build();
+
+    assert_that!(
+        s.volta("install node --offline"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed and set node@9.27.6[..]")
+    );
+}
+
+#[test]
+fn offline_install_lists_available_versions() {
+    let s = offline_sandbox().build();
+
+    assert_that!(
+        s.volta("install node@12 --offline"),
+        execs()
+            .with_status(ExitCode::NoVersionMatch as i32)
+            .with_stderr_contains(
+                "[..]Could not find a version of node matching '[..]' without network access."
+            )
+            .with_stderr_contains("Available locally: 8.9.10, 9.27.6, 10.99.1040")
+    );
+}
+
+#[test]
+fn offline_env_var_fails_fast_for_missing_exact_version() {
+    let s = offline_sandbox().env("VOLTA_OFFLINE", "1").build();
+
+    assert_that!(
+        s.volta("install node@12.0.0"),
+        execs()
+            .with_status(ExitCode::NoVersionMatch as i32)
+            .with_stderr_contains(
+                "[..]Could not find a version of node matching '12.0.0' without network access."
+            )
+            .with_stderr_contains("Available locally: 8.9.10, 9.27.6, 10.99.1040")
+            .with_stderr_does_not_contain("[..]127.0.0.1[..]")
+    );
+    assert!(!Sandbox::path_exists(".volta/tools/image/node/12.0.0"));
+}
+
+#[test]
+fn offline_install_uses_fetched_yarn_versions() {
+    let s = offline_sandbox()
+        .platform(NODE_PLATFORM)
+        .setup_yarn_binary("1.12.99", "#!/bin/sh\n")
+        .setup_yarn_binary("1.22.4", "#!/bin/sh\n")
+        .build();
+
+    assert_that!(
+        s.volta("install yarn@1 --offline"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed and set yarn@1.22.4[..]")
+    );
+    assert_that!(
+        s.volta("install yarn@3 --offline"),
+        execs()
+            .with_status(ExitCode::NoVersionMatch as i32)
+            .with_stderr_contains("[..]Could not find a version of yarn matching '[..]'[..]")
+            .with_stderr_contains("Available locally: 1.12.99, 1.22.4")
+    );
+}
+
+#[test]
+fn offline_install_accepts_installed_packages() {
+    let s = offline_sandbox()
+        .platform(NODE_PLATFORM)
+        .package_config("cowsay", COWSAY_CONFIG)
+        .build();
+
+    assert_that!(
+        s.volta("install cowsay --offline"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]cowsay@1.4.0 is already installed[..]")
+    );
+    assert_that!(
+        s.volta("install cowsay@2 --offline"),
+        execs()
+            .with_status(ExitCode::NoVersionMatch as i32)
+            .with_stderr_contains("[..]Could not find a version of cowsay matching '[..]'[..]")
+            .with_stderr_contains("Available locally: 1.4.0")
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn offline_run_uses_fetched_node_versions() {
+    let s = offline_sandbox()
+        .setup_node_binary(
+            "10.99.1040",
+            "6.2.26",
+            "#!/bin/sh\necho \"node args: $@\"\n",
+        )
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    assert_that!(
+        s.volta("run --offline --node 10 node --version"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("node args: --version")
+    );
+    assert_that!(
+        s.volta("run --offline --node 12 node --version"),
+        execs()
+            .with_status(ExitCode::NoVersionMatch as i32)
+            .with_stderr_contains("[..]Could not find a version of node matching '[..]'[..]")
+    );
+}
@@ -0,0 +1,421 @@
+use crate::support::sandbox::{sandbox, Sandbox};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use serde_json::Value;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const PLATFORM_NODE: &str = r#"{
+    "node":{
+        "runtime":"10.99.1040",
+        "npm":null
+    }
+}"#;
+
+const PACKAGE_JSON_WITH_UNFETCHED_NODE: &str = r#"{
+  "name": "test-package",
+  "volta": {
+    "node": "9.27.6"
+  }
+}"#;
+
+const PKG_CONFIG_COWSAY: &str = r#"{
+    "name": "cowsay",
+    "version": "1.4.0",
+    "platform": {
+      "node": "10.99.1040",
+      "npm": null,
+      "yarn": null
+    },
+    "bins": [
+      "cowsay"
+    ],
+    "manager": "Npm"
+  }"#;
+
+fn list_json(s: &Sandbox, args: &str) -> Value {
+    let output = s
+        .volta(&format!("list {} --format json", args))
+        .exec_with_output()
+        .unwrap();
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn json_includes_paths_of_fetched_tools() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .package_config("cowsay", PKG_CONFIG_COWSAY)
+        .package_image("cowsay", "1.4.0", None)
+        .build();
+
+    let list = list_json(&s, "");
+    let node_image = Sandbox::node_image_path("10.99.1040");
+    let cowsay_image = Sandbox::package_image_path("cowsay");
+    assert_eq!(list["jsonVersion"], 7);
+    assert_eq!(list["runtimes"][0]["version"], "10.99.1040");
+    assert_eq!(list["runtimes"][0]["path"], node_image.to_str().unwrap());
+    assert_eq!(list["packages"][0]["name"], "cowsay");
+    assert_eq!(list["packages"][0]["path"], cowsay_image.to_str().unwrap());
+}
+
+#[test]
+fn json_path_is_null_for_pinned_tool_that_is_not_fetched() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .package_json(PACKAGE_JSON_WITH_UNFETCHED_NODE)
+        .build();
+
+    let list = list_json(&s, "");
+    assert_eq!(list["runtimes"][0]["version"], "9.27.6");
+    assert_eq!(list["runtimes"][0]["source"], "project");
+    assert_eq!(list["runtimes"][0]["path"], Value::Null);
+}
+
+#[test]
+fn json_version_2_has_no_paths() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .build();
+
+    let output = s.volta("list --json=2").exec_with_output().unwrap();
+    let list: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(list["jsonVersion"], 2);
+    assert!(list["runtimes"][0].get("path").is_none());
+}
+
+const NODE_INDEX_WITH_NEWER_10: &str = r#"[
+{"version":"v11.10.1","npm":"6.7.0","lts": false,"files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]},
+{"version":"v10.100.0","npm":"6.2.26","lts": "Dubnium","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]},
+{"version":"v10.99.1040","npm":"6.2.26","lts": "Dubnium","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]}
+]
+"#;
+
+const COWSAY_METADATA: &str = r#"{
+  "name": "cowsay",
+  "dist-tags": { "latest": "1.4.0" },
+  "versions": {
+    "1.4.0": {
+      "version": "1.4.0",
+      "dist": { "shasum": "", "tarball": "" }
+    },
+    "1.3.1": {
+      "version": "1.3.1",
+      "dist": { "shasum": "", "tarball": "" }
+    }
+  }
+}"#;
+
+#[test]
+fn outdated_shows_newer_node_in_same_major() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .node_available_versions(NODE_INDEX_WITH_NEWER_10)
+        .build();
+
+    assert_that!(
+        s.volta("list --outdated --format plain"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("node@10.99.1040 (default) latest=10.100.0 outdated=true")
+    );
+}
+
+#[test]
+fn outdated_json_shows_up_to_date_package() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .package_config("cowsay", PKG_CONFIG_COWSAY)
+        .package_image("cowsay", "1.4.0", None)
+        .node_available_versions(NODE_INDEX_WITH_NEWER_10)
+        .package_metadata("cowsay", COWSAY_METADATA)
+        .build();
+
+    let output = s
+        .volta("list --outdated --json")
+        .exec_with_output()
+        .unwrap();
+    let list: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(list["jsonVersion"], 7);
+
+    let cowsay = list["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|tool| tool["name"] == "cowsay")
+        .expect("cowsay should be listed");
+    assert_eq!(cowsay["version"], "1.4.0");
+    assert_eq!(cowsay["source"], "default");
+    assert_eq!(cowsay["latest"], "1.4.0");
+    assert_eq!(cowsay["outdated"], false);
+}
+
+#[test]
+fn outdated_shows_unknown_when_offline() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .package_config("cowsay", PKG_CONFIG_COWSAY)
+        .package_image("cowsay", "1.4.0", None)
+        .mock_not_found()
+        .build();
+
+    assert_that!(
+        s.volta("list --outdated --format plain"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("node@10.99.1040 (default) latest=unknown outdated=false")
+            .with_stdout_contains("cowsay@1.4.0 (default) latest=unknown outdated=false")
+    );
+}
+
+#[test]
+fn outdated_rejects_older_json_versions() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .build();
+
+    assert_that!(
+        s.volta("list --outdated --json=3"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Please use `--json=4` or newer[..]")
+    );
+}
+
+const ALIAS_SETTINGS: &str = r#"{
+  "aliases": {
+    "node": {
+      "company-standard": "20.11.1"
+    },
+    "yarn": {
+      "company-standard": "^1.22"
+    }
+  }
+}"#;
+
+const ALIAS_PROJECT_HOOKS: &str = r#"{
+  "aliases": {
+    "node": {
+      "company-standard": "18.19.0"
+    }
+  }
+}"#;
+
+#[test]
+fn aliases_show_the_files_that_define_them() {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON_WITH_UNFETCHED_NODE)
+        .file(".volta/settings.json", ALIAS_SETTINGS)
+        .project_file(".volta/hooks.json", ALIAS_PROJECT_HOOKS)
+        .build();
+
+    let project_hooks = s.root().join(".volta").join("hooks.json");
+    let settings = Sandbox::home_path(".volta/settings.json");
+
+    assert_that!(
+        s.volta("list --aliases --format plain"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(format!(
+                "node@company-standard target=18.19.0 source={}",
+                project_hooks.display()
+            ))
+            .with_stdout_contains(format!(
+                "yarn@company-standard target=^1.22 source={}",
+                settings.display()
+            ))
+    );
+
+    assert_that!(
+        s.volta("list yarn --aliases --format plain"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_does_not_contain("node@company-standard[..]")
+    );
+}
+
+#[test]
+fn aliases_json() {
+    let s = sandbox()
+        .file(".volta/settings.json", ALIAS_SETTINGS)
+        .build();
+
+    let output = s.volta("list --aliases --json").exec_with_output().unwrap();
+    let list: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(list["jsonVersion"], 7);
+    assert_eq!(list["aliases"][0]["tool"], "node");
+    assert_eq!(list["aliases"][0]["name"], "company-standard");
+    assert_eq!(list["aliases"][0]["target"], "20.11.1");
+
+    assert_that!(
+        s.volta("list --aliases --json=4"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Please use `--json=5` or newer[..]")
+    );
+}
+
+#[test]
+fn invalid_alias_is_reported() {
+    let s = sandbox()
+        .file(
+            ".volta/settings.json",
+            r#"{ "aliases": { "node": { "broken": "not a version" } } }"#,
+        )
+        .build();
+
+    assert_that!(
+        s.volta("list --aliases"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "[..]Invalid version alias `node@broken` (set to 'not a version')"
+            )
+    );
+}
+
+const PKG_CONFIG_TYPESCRIPT: &str = r#"{
+    "name": "typescript",
+    "version": "5.3.3",
+    "platform": {
+      "node": "10.99.1040",
+      "npm": null,
+      "yarn": null
+    },
+    "bins": [
+      "tsc"
+    ],
+    "manager": "Npm"
+  }"#;
+
+// Listed in a different order, with different paths and end-of-life status than the sandbox
+const SNAPSHOT_BEFORE: &str = r#"{
+  "jsonVersion": 6,
+  "runtimes": [
+    { "name": "node", "version": "9.27.6", "source": "default", "project": null, "eol": true, "path": "/Users/ci/.volta/tools/image/node/9.27.6" },
+    { "name": "node", "version": "10.99.1040", "source": "fetched", "project": null, "eol": false, "path": "/Users/ci/.volta/tools/image/node/10.99.1040" }
+  ],
+  "packageManagers": [
+    { "name": "yarn", "version": "1.22.19", "source": "default", "project": null }
+  ],
+  "packages": [
+    { "name": "typescript", "version": "5.0.4", "tag": null, "source": "default", "project": null, "node": "10.99.1040", "binaries": ["tsc"], "path": null }
+  ]
+}"#;
+
+fn sandbox_for_diff() -> Sandbox {
+    sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .package_config("cowsay", PKG_CONFIG_COWSAY)
+        .package_config("typescript", PKG_CONFIG_TYPESCRIPT)
+        .file("before.json", SNAPSHOT_BEFORE)
+        .build()
+}
+
+#[test]
+fn diff_of_unchanged_toolchain_has_no_changes() {
+    let s = sandbox_for_diff();
+    let snapshot = Sandbox::home_path("snapshot.json");
+
+    assert_that!(
+        s.volta(&format!("list --json --snapshot-to {}", snapshot.display())),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    let saved: Value = serde_json::from_str(&std::fs::read_to_string(&snapshot).unwrap()).unwrap();
+    assert_eq!(saved["jsonVersion"], 7);
+    assert_eq!(saved["packages"][0]["name"], "cowsay");
+
+    assert_that!(
+        s.volta(&format!(
+            "list --diff {} --format human",
+            snapshot.display()
+        )),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("⚡️ No changes since the snapshot in [..]snapshot.json.")
+    );
+}
+
+#[test]
+fn diff_shows_each_kind_of_change() {
+    let s = sandbox_for_diff();
+
+    assert_that!(
+        s.volta(&format!(
+            "list --diff {} --format plain",
+            Sandbox::home_path("before.json").display()
+        )),
+        execs()
+            .with_status(ExitCode::DifferencesFound as i32)
+            .with_stdout_contains("changed node@10.99.1040 source=fetched->default")
+            .with_stdout_contains("removed node@9.27.6 source=default")
+            .with_stdout_contains("removed yarn@1.22.19 source=default")
+            .with_stdout_contains("added cowsay@1.4.0 source=default")
+            .with_stdout_contains("changed typescript version=5.0.4->5.3.3")
+    );
+}
+
+#[test]
+fn diff_json() {
+    let s = sandbox_for_diff();
+    let before = Sandbox::home_path("before.json");
+
+    let output = s
+        .volta(&format!("list --diff {} --json", before.display()))
+        .build_command()
+        .output()
+        .unwrap();
+    assert_eq!(
+        output.status.code(),
+        Some(ExitCode::DifferencesFound as i32)
+    );
+    let diff: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(diff["jsonVersion"], 7);
+    assert_eq!(diff["added"][0]["name"], "cowsay");
+    assert_eq!(diff["removed"].as_array().unwrap().len(), 2);
+    assert_eq!(diff["changed"][1]["name"], "typescript");
+    assert_eq!(diff["changed"][1]["changes"][0]["field"], "version");
+    assert_eq!(diff["changed"][1]["changes"][0]["after"], "5.3.3");
+
+    assert_that!(
+        s.volta(&format!("list --diff {} --json=5", before.display())),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Please use `--json=6` or newer[..]")
+    );
+}
+
+#[test]
+fn diff_rejects_files_that_are_not_snapshots() {
+    let s = sandbox()
+        .file("usage.json", r#"{ "jsonVersion": 6, "usage": [] }"#)
+        .build();
+
+    assert_that!(
+        s.volta(&format!(
+            "list --diff {}",
+            Sandbox::home_path("usage.json").display()
+        )),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Could not parse snapshot file")
+    );
+    assert_that!(
+        s.volta(&format!(
+            "list --diff {}",
+            Sandbox::home_path("missing.json").display()
+        )),
+        execs()
+            .with_status(ExitCode::FileSystemError as i32)
+            .with_stderr_contains("[..]Could not read snapshot file")
+    );
+}
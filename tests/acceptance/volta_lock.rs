@@ -0,0 +1,256 @@
+use std::fs;
+
+use crate::support::sandbox::{sandbox, Sandbox};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const PACKAGE_JSON: &str = r#"{
+  "name": "test-package",
+  "volta": {
+    "node": "10.99.1040",
+    "yarn": "1.23.483"
+  }
+}"#;
+
+const PACKAGE_JSON_CHANGED: &str = r#"{
+  "name": "test-package",
+  "volta": {
+    "node": "11.10.1",
+    "yarn": "1.23.483"
+  }
+}"#;
+
+const VOLTA_LOGLEVEL: &str = "VOLTA_LOGLEVEL";
+
+fn locked_sandbox() -> Sandbox {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON)
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .setup_node_binary("11.10.1", "6.7.0", "")
+        .setup_yarn_binary("1.23.483", "")
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("lock"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    s
+}
+
+fn read_lock_file(s: &Sandbox) -> String {
+    fs::read_to_string(s.root().join("volta.lock")).expect("volta.lock should exist")
+}
+
+fn change_package_json(s: &Sandbox) {
+    fs::write(s.root().join("package.json"), PACKAGE_JSON_CHANGED).unwrap();
+}
+
+#[test]
+fn lock_writes_platform() {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON)
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("lock"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]locked node@10.99.1040, yarn@1.23.483 in [..]volta.lock")
+    );
+
+    let lock = read_lock_file(&s);
+    assert!(lock.starts_with("{\n  \"version\": 1,\n  \"node\": \"10.99.1040\",\n"));
+    assert!(lock.contains("\n  \"yarn\": \"1.23.483\",\n  \"voltaHash\": \"sha256-"));
+    assert!(!lock.contains("npm"));
+}
+
+#[test]
+fn lock_is_up_to_date() {
+    let s = locked_sandbox();
+    let lock = read_lock_file(&s);
+
+    assert_that!(
+        s.volta("lock"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]volta.lock is up to date")
+    );
+    assert_eq!(read_lock_file(&s), lock);
+}
+
+#[test]
+fn lock_requires_pinned_node() {
+    let s = sandbox()
+        .package_json(r#"{ "name": "test-package" }"#)
+        .build();
+
+    assert_that!(
+        s.volta("lock"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]No Node version found in this project.")
+    );
+    assert!(!s.root().join("volta.lock").exists());
+}
+
+#[test]
+fn outdated_lock_file_warns() {
+    let s = locked_sandbox();
+    change_package_json(&s);
+
+    assert_that!(
+        s.volta("which node"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]10.99.1040[..]")
+            .with_stderr_contains(
+                "warning:[..]The Volta settings in [..]package.json have changed since [..]volta.lock was written."
+            )
+    );
+}
+
+#[test]
+fn frozen_lock_update_refreshes_outdated_lock_file() {
+    let s = locked_sandbox();
+    change_package_json(&s);
+
+    assert_that!(
+        s.volta("--frozen lock --update"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]locked node@11.10.1, yarn@1.23.483 in [..]volta.lock")
+    );
+}
+
+#[test]
+fn shims_run_locked_platform() {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON_CHANGED)
+        .setup_node_binary("10.99.1040", "6.2.26", "#!/bin/sh\necho node 10.99.1040\n")
+        .setup_node_binary("11.10.1", "6.7.0", "#!/bin/sh\necho node 11.10.1\n")
+        .project_file(
+            "volta.lock",
+            r#"{ "version": 1, "node": "10.99.1040", "voltaHash": "sha256-0" }"#,
+        )
+        .shim("node")
+        .build();
+
+    assert_that!(
+        s.exec_shim("node", "--version"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("node 10.99.1040")
+            .with_stdout_does_not_contain("node 11.10.1")
+    );
+}
+
+#[test]
+fn up_to_date_lock_file_does_not_warn() {
+    let s = locked_sandbox();
+
+    assert_that!(
+        s.volta("which node"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]10.99.1040[..]")
+            .with_stderr_does_not_contain("[..]have changed since[..]")
+    );
+}
+
+#[test]
+fn frozen_fails_when_outdated() {
+    let s = locked_sandbox();
+    change_package_json(&s);
+
+    assert_that!(
+        s.volta("--frozen which node"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]The platform lock file [..]volta.lock")
+            .with_stderr_contains("is out of date with the Volta settings in [..]package.json")
+    );
+}
+
+#[test]
+fn frozen_env_fails_when_outdated() {
+    let s = locked_sandbox();
+    change_package_json(&s);
+
+    assert_that!(
+        s.volta("which node").env("VOLTA_FROZEN", "1"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]The platform lock file [..]volta.lock")
+    );
+}
+
+#[test]
+fn frozen_succeeds_when_up_to_date() {
+    let s = locked_sandbox();
+
+    assert_that!(
+        s.volta("--frozen which node"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]10.99.1040[..]")
+    );
+}
+
+#[test]
+fn lock_fails_when_outdated() {
+    let s = locked_sandbox();
+    let lock = read_lock_file(&s);
+    change_package_json(&s);
+
+    assert_that!(
+        s.volta("lock"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Use `volta lock --update` to update the lock file.")
+    );
+    assert_eq!(read_lock_file(&s), lock);
+}
+
+#[test]
+fn lock_update_refreshes_lock_file() {
+    let s = locked_sandbox();
+    change_package_json(&s);
+
+    assert_that!(
+        s.volta("lock --update"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]locked node@11.10.1, yarn@1.23.483 in [..]volta.lock")
+    );
+    assert!(read_lock_file(&s).contains("\"node\": \"11.10.1\""));
+
+    assert_that!(
+        s.volta("--frozen which node"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]11.10.1[..]")
+            .with_stderr_does_not_contain("[..]have changed since[..]")
+    );
+}
+
+#[test]
+fn unsupported_lock_file_version() {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON)
+        .project_file("volta.lock", r#"{ "version": 2, "node": "10.99.1040" }"#)
+        .build();
+
+    assert_that!(
+        s.volta("which node"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "has version 2, which is not supported by this version of Volta."
+            )
+    );
+}
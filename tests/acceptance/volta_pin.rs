@@ -1,8 +1,10 @@
 use crate::support::sandbox::{
-    sandbox, DistroMetadata, NodeFixture, NpmFixture, PnpmFixture, Yarn1Fixture, YarnBerryFixture,
+    file_reads, sandbox, DistroMetadata, NodeFixture, NpmFixture, PnpmFixture, SandboxBuilder,
+    Yarn1Fixture, YarnBerryFixture,
 };
 use hamcrest2::assert_that;
 use hamcrest2::prelude::*;
+use mockito::mock;
 use test_support::matchers::execs;
 
 use volta_core::error::ExitCode;
@@ -341,6 +343,205 @@ fn pin_node() {
     )
 }
 
+const PACKAGE_JSON_WITH_TRAILING_COMMAS: &str = r#"{
+  // generated
+  "name": "test-package",
+  "volta": {
+    "node": "8.9.10",
+  },
+}"#;
+
+#[test]
+fn pin_node_refuses_manifest_with_trailing_commas() {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON_WITH_TRAILING_COMMAS)
+        .setup_node_binary("8.9.10", "5.6.0", "echo node")
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("list node --format plain"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("runtime node@8.9.10 (current @ [..]package.json)")
+    );
+
+    assert_that!(
+        s.volta("pin node@6"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Could not update project manifest")
+            .with_stderr_contains("The file contains comments or trailing commas[..]")
+    );
+
+    assert_eq!(s.read_package_json(), PACKAGE_JSON_WITH_TRAILING_COMMAS);
+}
+
+const NODE_ALIAS_SETTINGS: &str = r#"{
+  "aliases": {
+    "node": {
+      "company-standard": "6",
+      "legacy": "8.9.10"
+    }
+  }
+}"#;
+
+const NODE_ALIAS_PROJECT_HOOKS: &str = r#"{
+  "aliases": {
+    "node": {
+      "company-standard": "8.9.10"
+    }
+  }
+}"#;
+
+#[test]
+fn pin_node_alias() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .file(".volta/settings.json", NODE_ALIAS_SETTINGS)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("pin node@company-standard"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node("6.19.62"),
+    )
+}
+
+#[test]
+fn pin_node_alias_from_project_hooks() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .file(".volta/settings.json", NODE_ALIAS_SETTINGS)
+        .project_file(".volta/hooks.json", NODE_ALIAS_PROJECT_HOOKS)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("pin node@company-standard"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node("8.9.10"),
+    )
+}
+
+#[test]
+fn pin_node_unknown_alias() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .file(".volta/settings.json", NODE_ALIAS_SETTINGS)
+        .build();
+
+    assert_that!(
+        s.volta("pin node@company-standrad"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains(
+                "[..]Could not find a version alias named 'company-standrad' for node."
+            )
+            .with_stderr_contains("The aliases defined for node are: company-standard, legacy")
+    );
+
+    assert_eq!(s.read_package_json(), BASIC_PACKAGE_JSON);
+}
+
+#[test]
+fn pin_node_with_lock_writes_lock_file() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("pin --lock node@6"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]pinned node@6.19.62 [..] in package.json")
+            .with_stdout_contains("[..]locked node@6.19.62 in [..]volta.lock")
+    );
+
+    let lock = std::fs::read_to_string(s.root().join("volta.lock")).unwrap();
+    assert!(lock.contains(r#""node": "6.19.62""#));
+}
+
+#[test]
+fn pin_node_without_lock_warns_about_outdated_lock_file() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node("8.9.10"))
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("pin --lock"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_that!(
+        s.volta("pin node@6"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]volta.lock still records the previous platform[..]")
+    );
+
+    let lock = std::fs::read_to_string(s.root().join("volta.lock")).unwrap();
+    assert!(lock.contains(r#""node": "8.9.10""#));
+}
+
+#[test]
+fn pin_node_warns_end_of_life() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("pin node@6").env(VOLTA_LOGLEVEL, "warn"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]node@6.19.62 reached end-of-life on 2019-04-30[..]")
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node("6.19.62"),
+    )
+}
+
+#[test]
+fn pin_node_v_prefixed_version() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("pin node@v10.99.1040"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node("10.99.1040"),
+    )
+}
+
 #[test]
 fn pin_node_reports_info() {
     let s = sandbox()
@@ -358,6 +559,172 @@ fn pin_node_reports_info() {
     );
 }
 
+#[test]
+fn pin_node_from_subdirectory() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .project_file("src/index.js", "")
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("pin node@6").cwd(s.root().join("src")),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node("6.19.62"),
+    )
+}
+
+fn workspace_member(name: &str, extends: &str) -> String {
+    format!(
+        r#"{{
+  "name": "{}",
+  "volta": {{
+    "extends": "{}"
+  }}
+}}"#,
+        name, extends
+    )
+}
+
+/// A workspace with three members that extend the root, one of them only through another member,
+/// and two packages that don't extend it
+fn workspace_sandbox(root: &str) -> SandboxBuilder {
+    sandbox()
+        .package_json(root)
+        .project_file(
+            "packages/app/package.json",
+            &workspace_member("app", "../../package.json"),
+        )
+        .project_file(
+            "packages/lib/package.json",
+            &workspace_member("lib", "../../package.json"),
+        )
+        .project_file(
+            "packages/lib/plugin/package.json",
+            &workspace_member("plugin", "../package.json"),
+        )
+        .project_file("packages/standalone/package.json", BASIC_PACKAGE_JSON)
+        .project_file(
+            "node_modules/dep/package.json",
+            &workspace_member("dep", "../../package.json"),
+        )
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+}
+
+#[test]
+fn pin_node_in_workspace_requires_confirmation() {
+    let s = workspace_sandbox(BASIC_PACKAGE_JSON).build();
+
+    assert_that!(
+        s.volta("pin node@6"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains(
+                "[..]Did not pin, as the change to [..]package.json was not confirmed."
+            )
+            .with_stderr_contains("    [..]packages[..]app")
+            .with_stderr_contains("    [..]packages[..]lib")
+            .with_stderr_contains("    [..]packages[..]lib[..]plugin")
+            .with_stderr_does_not_contain("[..]standalone[..]")
+            .with_stderr_does_not_contain("[..]node_modules[..]")
+    );
+
+    assert_eq!(s.read_package_json(), BASIC_PACKAGE_JSON)
+}
+
+#[test]
+fn pin_node_in_workspace_with_yes() {
+    let s = workspace_sandbox(BASIC_PACKAGE_JSON)
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("pin --yes node@6"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(
+                "[..]pinning in [..]package.json also affects 3 workspace members:"
+            )
+            .with_stdout_contains("    [..]packages[..]app")
+            .with_stdout_contains("    [..]packages[..]lib")
+            .with_stdout_contains("    [..]packages[..]lib[..]plugin")
+            .with_stdout_does_not_contain("[..]standalone[..]")
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node("6.19.62"),
+    )
+}
+
+#[test]
+fn pin_node_in_workspace_without_verifying_members() {
+    let s = workspace_sandbox(BASIC_PACKAGE_JSON)
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("pin --no-verify-members node@6"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_does_not_contain("[..]workspace member[..]")
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node("6.19.62"),
+    )
+}
+
+#[test]
+fn pin_node_in_workspace_member_only_checks_its_subtree() {
+    let s = workspace_sandbox(&package_json_with_pinned_node("8.9.10"))
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    // `lib` is extended by `plugin`, but its sibling `app` and the root are not affected
+    assert_that!(
+        s.volta("pin node@6")
+            .cwd(s.root().join("packages").join("app")),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    assert_that!(
+        s.volta("pin node@6")
+            .cwd(s.root().join("packages").join("lib")),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("    [..]packages[..]lib[..]plugin")
+            .with_stderr_does_not_contain("    [..]packages[..]app")
+    );
+}
+
+#[test]
+fn pin_node_stops_at_project_ceiling() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .project_file("src/index.js", "")
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("pin node@6")
+            .cwd(s.root().join("src"))
+            .env("VOLTA_PROJECT_CEILING", s.root()),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Not in a node package.")
+    );
+
+    assert_eq!(s.read_package_json(), BASIC_PACKAGE_JSON)
+}
+
 #[test]
 fn pin_node_latest() {
     let s = sandbox()
@@ -432,89 +799,225 @@ fn pin_node_with_npm_hides_bundled_version() {
 }
 
 #[test]
-fn pin_yarn_no_node() {
+fn pin_yarn_no_node() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .yarn_berry_available_versions(YARN_BERRY_VERSION_INFO)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("pin yarn@1.4"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "[..]Cannot pin Yarn because the Node version is not pinned in this project."
+            )
+    );
+
+    assert_eq!(s.read_package_json(), BASIC_PACKAGE_JSON)
+}
+
+#[test]
+fn pin_yarn_1() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node("1.2.3"))
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .yarn_berry_available_versions(YARN_BERRY_VERSION_INFO)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("pin yarn@1.4"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node_yarn("1.2.3", "1.4.159"),
+    )
+}
+
+#[test]
+fn pin_yarn_2_is_error() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node("1.2.3"))
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .yarn_berry_available_versions(YARN_BERRY_VERSION_INFO)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .distro_mocks::<YarnBerryFixture>(&YARN_BERRY_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("pin yarn@2"),
+        execs()
+            .with_status(ExitCode::NoVersionMatch as i32)
+            .with_stderr_contains(
+                "[..]Yarn version 2 is not recommended for use, and not supported by Volta[..]"
+            )
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node("1.2.3"),
+    )
+}
+
+#[test]
+fn pin_yarn_3() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node("1.2.3"))
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .yarn_berry_available_versions(YARN_BERRY_VERSION_INFO)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .distro_mocks::<YarnBerryFixture>(&YARN_BERRY_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("pin yarn@3"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node_yarn("1.2.3", "3.12.99"),
+    )
+}
+
+const YARN_1_LOCKFILE: &str = "# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.
+# yarn lockfile v1
+";
+
+#[test]
+fn pin_yarn_from_lockfile() {
     let s = sandbox()
-        .package_json(BASIC_PACKAGE_JSON)
+        .package_json(&package_json_with_pinned_node("1.2.3"))
+        .project_file("yarn.lock", YARN_1_LOCKFILE)
         .yarn_1_available_versions(YARN_1_VERSION_INFO)
         .yarn_berry_available_versions(YARN_BERRY_VERSION_INFO)
         .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .env(VOLTA_LOGLEVEL, "info")
         .build();
 
     assert_that!(
-        s.volta("pin yarn@1.4"),
+        s.volta("pin --from-lockfile"),
         execs()
-            .with_status(ExitCode::ConfigurationError as i32)
-            .with_stderr_contains(
-                "[..]Cannot pin Yarn because the Node version is not pinned in this project."
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(
+                "[..]inferred Yarn@^1 from [..]yarn.lock, which has lockfile version 1"
             )
     );
 
-    assert_eq!(s.read_package_json(), BASIC_PACKAGE_JSON)
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node_yarn("1.2.3", "1.12.99"),
+    )
 }
 
+const NODE_18_VERSION_INFO: &str = r#"[
+{"version":"v18.17.0","npm":"9.6.7","lts": "Hydrogen","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]},
+{"version":"v18.16.1","npm":"9.5.1","lts": "Hydrogen","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]},
+{"version":"v18.16.0","npm":"9.5.1","lts": "Hydrogen","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]}
+]
+"#;
+
+const PACKAGE_JSON_WITH_ENGINES: &str = r#"{
+  "name": "test-package",
+  "engines": {
+    "node": ">=18.17"
+  }
+}"#;
+
 #[test]
-fn pin_yarn_1() {
+fn pin_node_from_nvmrc() {
     let s = sandbox()
-        .package_json(&package_json_with_pinned_node("1.2.3"))
-        .yarn_1_available_versions(YARN_1_VERSION_INFO)
-        .yarn_berry_available_versions(YARN_BERRY_VERSION_INFO)
-        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .package_json(BASIC_PACKAGE_JSON)
+        .project_file(".nvmrc", "18.16\n")
+        .node_available_versions(NODE_18_VERSION_INFO)
+        .setup_node_binary("18.16.1", "9.5.1", "echo node")
+        .env(VOLTA_LOGLEVEL, "info")
         .build();
 
     assert_that!(
-        s.volta("pin yarn@1.4"),
-        execs().with_status(ExitCode::Success as i32)
+        s.volta("pin --from-lockfile"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]inferred Node@18.16 from [..].nvmrc")
     );
 
     assert_eq!(
         s.read_package_json(),
-        package_json_with_pinned_node_yarn("1.2.3", "1.4.159"),
+        package_json_with_pinned_node("18.16.1"),
     )
 }
 
 #[test]
-fn pin_yarn_2_is_error() {
+fn pin_from_lockfile_with_conflicting_versions_requires_confirmation() {
     let s = sandbox()
-        .package_json(&package_json_with_pinned_node("1.2.3"))
-        .yarn_1_available_versions(YARN_1_VERSION_INFO)
-        .yarn_berry_available_versions(YARN_BERRY_VERSION_INFO)
-        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
-        .distro_mocks::<YarnBerryFixture>(&YARN_BERRY_VERSION_FIXTURES)
+        .package_json(PACKAGE_JSON_WITH_ENGINES)
+        .project_file(".nvmrc", "18.16\n")
+        .node_available_versions(NODE_18_VERSION_INFO)
+        .setup_node_binary("18.16.1", "9.5.1", "echo node")
         .build();
 
     assert_that!(
-        s.volta("pin yarn@2"),
+        s.volta("pin --from-lockfile"),
         execs()
-            .with_status(ExitCode::NoVersionMatch as i32)
-            .with_stderr_contains(
-                "[..]Yarn version 2 is not recommended for use, and not supported by Volta[..]"
-            )
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]as the project records different versions of it:")
+            .with_stderr_contains("    18.16 in [..].nvmrc")
+            .with_stderr_contains("    >=18.17 in engines.node of [..]package.json")
+            .with_stderr_contains("[..]To pin node@18.16, run the command again with `--yes`[..]")
+    );
+    assert_eq!(s.read_package_json(), PACKAGE_JSON_WITH_ENGINES);
+
+    // The version file takes precedence over `engines`
+    assert_that!(
+        s.volta("pin --from-lockfile --yes"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    assert!(s.read_package_json().contains(r#""node": "18.16.1""#));
+}
+
+#[test]
+fn pin_from_lockfile_prefers_given_versions() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .project_file(".nvmrc", "18.16\n")
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("pin node@6 --from-lockfile"),
+        execs().with_status(ExitCode::Success as i32)
     );
 
     assert_eq!(
         s.read_package_json(),
-        package_json_with_pinned_node("1.2.3"),
+        package_json_with_pinned_node("6.19.62"),
     )
 }
 
 #[test]
-fn pin_yarn_3() {
+fn pin_from_lockfile_with_multiple_lockfiles_is_error() {
     let s = sandbox()
         .package_json(&package_json_with_pinned_node("1.2.3"))
-        .yarn_1_available_versions(YARN_1_VERSION_INFO)
-        .yarn_berry_available_versions(YARN_BERRY_VERSION_INFO)
-        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
-        .distro_mocks::<YarnBerryFixture>(&YARN_BERRY_VERSION_FIXTURES)
+        .project_file("yarn.lock", YARN_1_LOCKFILE)
+        .project_file("pnpm-lock.yaml", "lockfileVersion: '9.0'\n")
         .build();
 
     assert_that!(
-        s.volta("pin yarn@3"),
-        execs().with_status(ExitCode::Success as i32)
+        s.volta("pin --from-lockfile"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]has more than one lockfile: yarn.lock, pnpm-lock.yaml")
     );
 
     assert_eq!(
         s.read_package_json(),
-        package_json_with_pinned_node_yarn("1.2.3", "3.12.99"),
+        package_json_with_pinned_node("1.2.3"),
     )
 }
 
@@ -620,6 +1123,70 @@ fn pin_yarn_no_version() {
     )
 }
 
+fn yarn_index_mocks(cache_control: &str, requests: usize) -> [mockito::Mock; 2] {
+    [
+        ("/yarn", YARN_1_VERSION_INFO),
+        ("/@yarnpkg/cli-dist", YARN_BERRY_VERSION_INFO),
+    ]
+    .map(|(path, body)| {
+        mock("GET", path)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("cache-control", cache_control)
+            .with_body(body)
+            .expect(requests)
+            .create()
+    })
+}
+
+#[test]
+fn pin_yarn_uses_cached_index() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node("1.2.3"))
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .build();
+    let indexes = yarn_index_mocks("max-age=3600", 1);
+
+    assert_that!(
+        s.volta("pin yarn@1.4"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    assert_that!(
+        s.volta("pin yarn@1.7"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    for index in indexes {
+        index.assert();
+    }
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node_yarn("1.2.3", "1.7.71"),
+    )
+}
+
+#[test]
+fn pin_yarn_refetches_expired_index() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node("1.2.3"))
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .build();
+    let indexes = yarn_index_mocks("max-age=0", 2);
+
+    assert_that!(
+        s.volta("pin yarn@1.4"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    assert_that!(
+        s.volta("pin yarn@1.7"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    for index in indexes {
+        index.assert();
+    }
+}
+
 #[test]
 fn pin_yarn_1_missing_release() {
     let s = sandbox()
@@ -835,7 +1402,7 @@ fn pin_npm_missing_release() {
 }
 
 #[test]
-fn pin_npm_bundled_removes_npm() {
+fn pin_npm_bundled_writes_bundled() {
     let s = sandbox()
         .package_json(&package_json_with_pinned_node_npm("1.2.3", "4.5.6"))
         .node_npm_version_file("1.2.3", "3.2.1")
@@ -848,7 +1415,98 @@ fn pin_npm_bundled_removes_npm() {
 
     assert_eq!(
         s.read_package_json(),
-        package_json_with_pinned_node("1.2.3"),
+        package_json_with_pinned_node_npm("1.2.3", "bundled"),
+    );
+}
+
+#[test]
+fn pin_remove_npm() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node_npm("1.2.3", "4.5.6"))
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("pin --remove npm"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]unpinned npm in package.json")
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node("1.2.3")
+    );
+}
+
+#[test]
+fn pin_remove_bundled_npm() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node_npm("1.2.3", "bundled"))
+        .build();
+
+    assert_that!(
+        s.volta("pin --remove npm"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node("1.2.3")
+    );
+}
+
+#[test]
+fn pin_remove_npm_and_yarn() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node_npm_yarn(
+            "1.2.3", "4.5.6", "1.4.0",
+        ))
+        .build();
+
+    assert_that!(
+        s.volta("pin --remove npm yarn"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node("1.2.3")
+    );
+}
+
+#[test]
+fn pin_remove_node_is_error() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node_npm("1.2.3", "4.5.6"))
+        .build();
+
+    assert_that!(
+        s.volta("pin --remove npm node"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Cannot remove the Node pin from a project.")
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node_npm("1.2.3", "4.5.6")
+    );
+}
+
+#[test]
+fn pin_yarn_with_bundled_node_is_error() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node_yarn("bundled", "1.2.0"))
+        .build();
+
+    assert_that!(
+        s.volta("pin yarn@1.4"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                r#"[..]Could not use "bundled" as the node version in package.json"#
+            )
     );
 }
 
@@ -864,7 +1522,7 @@ fn pin_npm_bundled_reports_info() {
         s.volta("pin npm@bundled"),
         execs()
             .with_status(ExitCode::Success as i32)
-            .with_stdout_contains("[..]set package.json to use bundled npm (currently 3.2.1)[..]")
+            .with_stdout_contains("[..]pinned bundled npm (currently 3.2.1) in package.json[..]")
     );
 }
 
@@ -913,6 +1571,79 @@ fn pin_node_and_yarn3() {
     )
 }
 
+#[test]
+fn pin_node_and_yarn_reports_both() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .yarn_berry_available_versions(YARN_BERRY_VERSION_INFO)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("pin node@6 yarn@1.4"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(
+                "[..]pinned node@6.19.62 (with npm@3.10.1066), yarn@1.4.159 in package.json"
+            )
+    );
+}
+
+#[test]
+fn pin_several_tools_leaves_manifest_untouched_on_failure() {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON_WITH_EMPTY_LINE)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .yarn_berry_available_versions(YARN_BERRY_VERSION_INFO)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("pin node@6 yarn@1.4 npm@8.1.5"),
+        execs()
+            .with_status(ExitCode::NetworkError as i32)
+            .with_stderr_contains("[..]Could not download npm@8.1.5")
+    );
+
+    assert_eq!(s.read_package_json(), PACKAGE_JSON_WITH_EMPTY_LINE);
+}
+
+#[test]
+fn pin_several_tools_reads_each_manifest_once() {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON_WITH_EXTENDS)
+        .project_file("basic.json", BASIC_PACKAGE_JSON)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .npm_available_versions(NPM_VERSION_INFO)
+        .distro_mocks::<NpmFixture>(&NPM_VERSION_FIXTURES)
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .yarn_berry_available_versions(YARN_BERRY_VERSION_INFO)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .env(VOLTA_LOGLEVEL, "debug")
+        .build();
+
+    let output = s
+        .volta("pin node@6 npm@4.5 yarn@1.4")
+        .exec_with_output()
+        .unwrap();
+
+    let reads = file_reads(&output.stderr, "project manifest");
+    assert_eq!(reads.len(), 2, "unexpected manifests read: {:?}", reads);
+    assert!(
+        reads.values().all(|count| *count == 1),
+        "manifests read more than once: {:?}",
+        reads
+    );
+    assert!(s.read_package_json().contains(r#""yarn": "1.4.159""#));
+}
+
 #[test]
 fn pin_node_does_not_remove_trailing_newline() {
     let s = sandbox()
@@ -1109,3 +1840,27 @@ fn pin_pnpm_leaves_npm() {
         package_json_with_pinned_node_npm_pnpm("1.2.3", "3.4.5", "6.34.0"),
     )
 }
+
+#[test]
+fn error_code_is_shown_when_requested() {
+    let s = sandbox().node_available_versions(NODE_VERSION_INFO).build();
+
+    assert_that!(
+        s.volta("pin node@6").env("VOLTA_ERROR_CODES", "1"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..][VOLTA_NOT_IN_PACKAGE] Not in a node package.")
+    );
+    assert_that!(
+        s.volta("pin node@6 --verbose"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..][VOLTA_NOT_IN_PACKAGE] Not in a node package.")
+    );
+    assert_that!(
+        s.volta("pin node@6"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_does_not_contain("[..]VOLTA_NOT_IN_PACKAGE[..]")
+    );
+}
@@ -1,11 +1,17 @@
+use std::path::PathBuf;
+
 use crate::support::sandbox::{
-    sandbox, DistroMetadata, NodeFixture, NpmFixture, PnpmFixture, Yarn1Fixture, YarnBerryFixture,
+    sandbox, DistroMetadata, NodeFixture, NpmFixture, PnpmFixture, Sandbox, Yarn1Fixture,
+    YarnBerryFixture,
 };
+use cfg_if::cfg_if;
 use hamcrest2::assert_that;
 use hamcrest2::prelude::*;
+use mockito::{mock, Matcher};
 use test_support::matchers::execs;
 
 use volta_core::error::ExitCode;
+use volta_core::tool::NODE_DISTRO_ARCH;
 
 fn package_json_with_pinned_node(node: &str) -> String {
     format!(
@@ -281,6 +287,22 @@ fn command_line_node() {
     );
 }
 
+#[test]
+fn command_line_node_v_prefixed_version() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env(VOLTA_LOGLEVEL, "debug")
+        .build();
+
+    assert_that!(
+        s.volta("run --node v10.99.1040 node --version"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Node: 10.99.1040 from command-line configuration")
+    );
+}
+
 #[test]
 fn inherited_node() {
     let s = sandbox()
@@ -515,3 +537,604 @@ fn force_no_pnpm() {
             .with_stderr_contains("[..]No pnpm version found in this project.")
     );
 }
+
+const PLATFORM_NODE: &str = r#"{
+    "node":{
+        "runtime":"11.10.1",
+        "npm":"6.7.0"
+    }
+}"#;
+
+fn node_bin_echo_args() -> String {
+    cfg_if! {
+        if #[cfg(target_os = "windows")] {
+            r#"@echo off
+echo node args: %*
+"#
+            .to_string()
+        } else {
+            r#"#!/bin/sh
+echo "node args: $@"
+"#
+            .to_string()
+        }
+    }
+}
+
+#[test]
+fn passes_colliding_flags_to_command() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin_echo_args())
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    assert_that!(
+        s.volta("run node --verbose --quiet --version"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("node args: --verbose --quiet --version")
+            .with_stderr_does_not_contain("[verbose][..]")
+    );
+}
+
+#[test]
+fn passes_inspect_flag_to_command() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin_echo_args())
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    assert_that!(
+        s.volta("run node --inspect-brk=9229 app.js"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("node args: --inspect-brk=9229 app.js")
+    );
+}
+
+#[test]
+fn leading_separator_is_optional() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin_echo_args())
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    assert_that!(
+        s.volta("run -- node --verbose --quiet --version"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("node args: --verbose --quiet --version")
+            .with_stderr_does_not_contain("[verbose][..]")
+    );
+}
+
+#[test]
+fn separator_after_command_is_passed_to_command() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin_echo_args())
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    assert_that!(
+        s.volta("run node -- --version"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("node args: -- --version")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn passes_non_utf8_arguments_to_command() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    // The script compares the raw bytes it received, since the output must be valid UTF-8
+    let node_bin = r#"#!/bin/sh
+if [ "$1" = "$(printf 'caf\351')" ]; then
+    echo "argument preserved"
+fi
+"#;
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", node_bin)
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    assert_that!(
+        s.volta("run node").arg(OsStr::from_bytes(b"caf\xe9")),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("argument preserved")
+    );
+}
+
+#[test]
+fn reports_partial_checkout_failure() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node_yarn("11.10.1", "1.2.42"))
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin_echo_args())
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    assert_that!(
+        s.volta("run node app.js"),
+        execs()
+            .with_status(ExitCode::NetworkError as i32)
+            .with_stderr_contains(
+                "[..]Could not fetch every tool in the platform for this command."
+            )
+            .with_stderr_contains("Already available: node@11.10.1")
+            .with_stderr_contains("Failed: yarn@1.2.42")
+            .with_stderr_contains("    Could not download yarn@1.2.42")
+            .with_stderr_does_not_contain("Fetched:[..]")
+            .with_stdout_does_not_contain("node args:[..]")
+    );
+}
+
+#[test]
+fn continues_without_missing_tool() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node_yarn("11.10.1", "1.2.42"))
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin_echo_args())
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .env(VOLTA_LOGLEVEL, "warn")
+        .build();
+
+    assert_that!(
+        s.volta("run --continue-on-missing yarn node app.js"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Continuing without yarn@1.2.42, as it could not be fetched: Could not download yarn@1.2.42")
+            .with_stdout_contains("node args: app.js")
+    );
+}
+
+#[cfg(unix)]
+const NODE_BIN_ECHO_PATH: &str = r#"#!/bin/sh
+echo "$0"
+"#;
+
+#[cfg(unix)]
+#[test]
+fn where_prints_node_used_by_run() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", NODE_BIN_ECHO_PATH)
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    let located = s.volta("run --where node").exec_with_output().unwrap();
+    let ran = s.volta("run node").exec_with_output().unwrap();
+
+    assert_eq!(located.stdout, ran.stdout);
+    assert!(String::from_utf8(located.stdout)
+        .unwrap()
+        .trim_end()
+        .ends_with("tools/image/node/11.10.1/bin/node"));
+}
+
+#[cfg(unix)]
+#[test]
+fn where_uses_command_line_platform() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", NODE_BIN_ECHO_PATH)
+        .setup_node_binary("10.99.1040", "6.2.26", NODE_BIN_ECHO_PATH)
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    let located = s
+        .volta("run --where --node 10.99.1040 node")
+        .exec_with_output()
+        .unwrap();
+    let ran = s
+        .volta("run --node 10.99.1040 node")
+        .exec_with_output()
+        .unwrap();
+
+    assert_eq!(located.stdout, ran.stdout);
+    assert!(String::from_utf8(located.stdout)
+        .unwrap()
+        .trim_end()
+        .ends_with("tools/image/node/10.99.1040/bin/node"));
+}
+
+#[test]
+fn where_json_includes_platform() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .package_json(&package_json_with_pinned_node("10.99.1040"))
+        .setup_node_binary("10.99.1040", "6.2.26", &node_bin_echo_args())
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    assert_that!(
+        s.volta("run --where --json node"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(r#"  "jsonVersion": 1,"#)
+            .with_stdout_contains(r#"  "path": "[..]node[..]","#)
+            .with_stdout_contains(r#"      "version": "10.99.1040","#)
+            .with_stdout_contains(r#"      "version": "6.2.26","#)
+            .with_stdout_contains(r#"      "source": "project""#)
+            .with_stdout_contains(r#"    "pnpm": null,"#)
+            .with_stdout_contains(r#"    "yarn": null"#)
+            .with_stdout_does_not_contain("node args:[..]")
+    );
+}
+
+#[test]
+fn where_without_installing_fails_for_missing_tools() {
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node("10.99.1040"))
+        .build();
+    let requests = mock("GET", Matcher::Any).expect(0).create();
+
+    assert_that!(
+        s.volta("run --where --no-install-missing node"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "[..]This command needs tools that are not installed: node@10.99.1040"
+            )
+            .with_stdout("")
+    );
+
+    requests.assert();
+}
+
+#[test]
+fn where_rejects_intercepted_global_install() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin_echo_args())
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    assert_that!(
+        s.volta("run --where npm install --global cowsay"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Could not determine the executable for `npm`.")
+    );
+}
+
+#[test]
+fn exec_fetches_node_version() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env(VOLTA_LOGLEVEL, "debug")
+        .build();
+
+    assert_that!(
+        s.volta("exec node@10.99.1040 -- --version"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Node: 10.99.1040 from command-line configuration")
+    );
+    assert!(Sandbox::node_image_exists("10.99.1040"));
+}
+
+#[test]
+fn exec_resolves_node_lts() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .package_json(&package_json_with_pinned_node("9.27.6"))
+        .env(VOLTA_LOGLEVEL, "debug")
+        .build();
+
+    assert_that!(
+        s.volta("exec node@lts --version"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Node: 10.99.1040 from command-line configuration")
+    );
+}
+
+#[test]
+fn exec_yarn_version_inherits_node() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .package_json(&package_json_with_pinned_node_yarn("10.99.1040", "1.2.42"))
+        .env(VOLTA_LOGLEVEL, "debug")
+        .build();
+
+    assert_that!(
+        s.volta("exec yarn@1.7.71 --version"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Node: 10.99.1040 from project configuration")
+            .with_stderr_contains("[..]Yarn: 1.7.71 from command-line configuration")
+    );
+}
+
+#[test]
+fn exec_passes_arguments_to_tool() {
+    let s = sandbox()
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin_echo_args())
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    assert_that!(
+        s.volta("exec node@11.10.1 --verbose app.js -- --watch"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("node args: --verbose app.js -- --watch")
+            .with_stderr_does_not_contain("[verbose][..]")
+    );
+}
+
+#[test]
+fn exec_without_installing_fails_for_missing_tool() {
+    let s = sandbox().build();
+    let requests = mock("GET", Matcher::Any).expect(0).create();
+
+    assert_that!(
+        s.volta("--install-mode manual exec node@10.99.1040 --version"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "[..]This command needs tools that are not installed: node@10.99.1040"
+            )
+    );
+
+    requests.assert();
+}
+
+#[test]
+fn exec_package_is_error() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("exec cowsay@1.4.0 hello"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains(
+                "[..]Only Node, npm, pnpm, and Yarn can be run with `volta exec`."
+            )
+            .with_stderr_contains("Use `npx cowsay` to run a package once[..]")
+    );
+}
+
+#[cfg(unix)]
+const NODE_BIN_ECHO_NODE_OPTIONS: &str = r#"#!/bin/sh
+echo "NODE_OPTIONS: $NODE_OPTIONS"
+"#;
+
+#[cfg(unix)]
+#[test]
+fn env_is_passed_to_the_command() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", NODE_BIN_ECHO_NODE_OPTIONS)
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .env("NODE_OPTIONS", "--trace-warnings")
+        .build();
+
+    assert_that!(
+        s.volta("run --node 11.10.1 --env NODE_OPTIONS=--inspect node script.js"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("NODE_OPTIONS: --inspect")
+    );
+
+    // The variable only applies to that command, so later commands see the inherited value
+    assert_that!(
+        s.volta("run node script.js"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("NODE_OPTIONS: --trace-warnings")
+    );
+}
+
+#[test]
+fn env_without_equals_is_error() {
+    let s = sandbox().platform(PLATFORM_NODE).build();
+
+    assert_that!(
+        s.volta("run --env NODE_OPTIONS node script.js"),
+        execs()
+            .with_status(2)
+            .with_stderr_contains(
+                "[..]invalid value 'NODE_OPTIONS' for '--env <NAME=value>': missing '=' in 'NODE_OPTIONS', expected NAME=value"
+            )
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_by_absolute_path_without_shims_on_path() {
+    const NODE_BIN_ECHO_PATH: &str = r#"#!/bin/sh
+echo "node path: $PATH"
+"#;
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", NODE_BIN_ECHO_PATH)
+        // A cron job only has the system directories on its PATH
+        .env("PATH", "/usr/bin:/bin")
+        .build();
+
+    assert_that!(
+        s.volta("run node"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(
+                "node path: [..]node[..]11.10.1[..]bin:/usr/bin:/bin:[..].volta[..]bin"
+            )
+    );
+}
+
+#[test]
+fn profile_round_trips_through_save_list_and_run() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin_echo_args())
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("profile save legacy"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]saved profile 'legacy' with node@11.10.1")
+    );
+    assert_that!(
+        s.volta("profile list"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("legacy: node@11.10.1")
+    );
+    assert_that!(
+        s.volta("run --profile legacy --where --json node"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(r#"      "version": "11.10.1","#)
+            .with_stdout_contains(r#"      "source": "command-line""#)
+    );
+    assert_that!(
+        s.volta("profile delete legacy"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    assert_that!(
+        s.volta("profile list"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("No profiles saved yet.[..]")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn command_line_node_takes_precedence_over_profile() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .file(
+            ".volta/profiles/legacy.json",
+            r#"{ "node": { "runtime": "11.10.1", "npm": null }, "yarn": null }"#,
+        )
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin_echo_args())
+        .setup_node_binary("10.99.1040", "6.2.26", &node_bin_echo_args())
+        .build();
+
+    let located = s
+        .volta("run --profile legacy --node 10.99.1040 --where node")
+        .exec_with_output()
+        .unwrap();
+    assert!(String::from_utf8(located.stdout)
+        .unwrap()
+        .trim_end()
+        .ends_with("tools/image/node/10.99.1040/bin/node"));
+}
+
+#[test]
+fn malformed_profile_reports_parse_error() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .file(
+            ".volta/profiles/broken.json",
+            r#"{ "node": { "runtime": 14 } }"#,
+        )
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin_echo_args())
+        .build();
+
+    assert_that!(
+        s.volta("run --profile broken node"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Could not parse platform profile")
+            .with_stderr_contains("[..]invalid type: integer `14`[..]")
+            .with_stdout_does_not_contain("node args:[..]")
+    );
+}
+
+#[test]
+fn unknown_profile_lists_available_profiles() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .file(
+            ".volta/profiles/legacy.json",
+            r#"{ "node": { "runtime": "11.10.1", "npm": null } }"#,
+        )
+        .file(
+            ".volta/profiles/modern.json",
+            r#"{ "node": { "runtime": "11.10.1", "npm": null } }"#,
+        )
+        .build();
+
+    assert_that!(
+        s.volta("run --profile legcy node"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Could not find a platform profile named 'legcy'.")
+            .with_stderr_contains("Available profiles: legacy, modern")
+    );
+}
+
+fn node_bin_for_arch(arch: &str) -> String {
+    cfg_if! {
+        if #[cfg(target_os = "windows")] {
+            format!("@echo off\necho node build: {}\n", arch)
+        } else {
+            format!("#!/bin/sh\necho \"node build: {}\"\n", arch)
+        }
+    }
+}
+
+#[test]
+fn project_arch_image_coexists_with_native_image() {
+    let s = sandbox()
+        .package_json(
+            r#"{
+    "name": "rosetta",
+    "volta": {
+        "node": "20.11.1",
+        "arch": "s390x"
+    }
+}"#,
+        )
+        .setup_node_binary("20.11.1", "10.2.4", &node_bin_for_arch("native"))
+        .setup_node_arch_binary("s390x", "20.11.1", &node_bin_for_arch("s390x"))
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    assert_that!(
+        s.volta("run node"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("node build: s390x")
+    );
+    assert_that!(
+        s.volta(&format!("run --arch {} node", NODE_DISTRO_ARCH)),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("node build: native")
+    );
+}
+
+#[test]
+fn command_line_arch_missing_from_index_is_rejected() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("run --arch ppc64le --node 10.99.1040 node --version"),
+        execs()
+            .with_status(ExitCode::NoVersionMatch as i32)
+            .with_stderr_contains("[..]There is no ppc64le build of 10.99.1040.")
+    );
+    assert!(!Sandbox::path_exists(".volta/tools/image/node/ppc64le"));
+}
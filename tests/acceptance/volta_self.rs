@@ -0,0 +1,187 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::support::sandbox::{sandbox, Sandbox, SandboxBuilder};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const LATEST_VERSION: &str = "99.1.0";
+
+const RELEASE_ARCHIVE: &str = "volta-99.1.0-linux.tar.gz";
+
+const RELEASE_CHECKSUM: &str = "7f671c2bf93f8be5efc6a56bd44dab644b876177eebd5b1578b576e9313aff52";
+
+/// Serves the fixture release of Volta 99.1.0, with the given checksum file
+fn release_sandbox(shasums: &str) -> SandboxBuilder {
+    let archive = fs::read(Path::new("tests/fixtures").join(RELEASE_ARCHIVE)).unwrap();
+
+    sandbox()
+        .layout_file("v4")
+        .env("VOLTA_LOGLEVEL", "info")
+        .volta_latest_version(LATEST_VERSION)
+        .volta_release_file(LATEST_VERSION, "SHASUMS256.txt", shasums.as_bytes())
+        .volta_release_file(LATEST_VERSION, RELEASE_ARCHIVE, &archive)
+}
+
+/// Creates an install directory in the sandbox with placeholder binaries, so that replacing them
+/// doesn't touch the binaries under test
+fn install_dir(s: &Sandbox, path: &str) -> PathBuf {
+    let dir = s.root().join(path);
+    fs::create_dir_all(&dir).unwrap();
+    for binary in ["volta", "volta-shim", "volta-migrate"] {
+        fs::write(dir.join(binary), "old").unwrap();
+    }
+    dir
+}
+
+#[test]
+fn check_reports_a_newer_release() {
+    let s = sandbox()
+        .layout_file("v4")
+        .env("VOLTA_LOGLEVEL", "info")
+        .volta_latest_version(LATEST_VERSION)
+        .build();
+
+    assert_that!(
+        s.volta("self update --check"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(format!(
+                "Volta {} is available (you have {}). Update with `volta self update`.",
+                LATEST_VERSION, CURRENT_VERSION
+            ))
+    );
+}
+
+#[test]
+fn check_reports_an_up_to_date_install() {
+    let s = sandbox()
+        .layout_file("v4")
+        .env("VOLTA_LOGLEVEL", "info")
+        .volta_latest_version("1.0.0")
+        .build();
+
+    assert_that!(
+        s.volta("self update --check"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(format!("[..]Volta {} is up to date.", CURRENT_VERSION))
+    );
+}
+
+#[test]
+fn refuses_to_update_a_homebrew_install() {
+    let s = sandbox()
+        .layout_file("v4")
+        .volta_latest_version(LATEST_VERSION)
+        .build();
+    let dir = install_dir(&s, "Cellar/volta/2.0.2/bin");
+
+    assert_that!(
+        s.volta("self update").env("VOLTA_INSTALL_DIR", &dir),
+        execs()
+            .with_status(ExitCode::EnvironmentError as i32)
+            .with_stderr_contains("[..]Volta was installed with Homebrew[..]")
+            .with_stderr_contains("Please update it with `brew upgrade volta` instead.")
+    );
+    assert_eq!(fs::read_to_string(dir.join("volta")).unwrap(), "old");
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn update_replaces_the_binaries() {
+    let s = release_sandbox(&format!("{}  {}\n", RELEASE_CHECKSUM, RELEASE_ARCHIVE)).build();
+    let dir = install_dir(&s, "volta-install");
+
+    assert_that!(
+        s.volta("self update").env("VOLTA_INSTALL_DIR", &dir),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(format!(
+                "[..]updated Volta from {} to {}",
+                CURRENT_VERSION, LATEST_VERSION
+            ))
+    );
+
+    for binary in ["volta", "volta-shim", "volta-migrate"] {
+        assert_eq!(
+            fs::read_to_string(dir.join(binary)).unwrap(),
+            format!("#!/bin/sh\necho \"{} 99.1.0\"\n", binary)
+        );
+    }
+    let leftovers: Vec<_> = fs::read_dir(&dir).unwrap().collect();
+    assert_eq!(leftovers.len(), 3, "The staging directory is removed");
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn update_rejects_an_archive_that_does_not_match_its_checksum() {
+    let s = release_sandbox(&format!("{}  {}\n", "0".repeat(64), RELEASE_ARCHIVE)).build();
+    let dir = install_dir(&s, "volta-install");
+
+    assert_that!(
+        s.volta("self update").env("VOLTA_INSTALL_DIR", &dir),
+        execs()
+            .with_status(ExitCode::NetworkError as i32)
+            .with_stderr_contains("[..]does not match its published checksum")
+    );
+    assert_eq!(fs::read_to_string(dir.join("volta")).unwrap(), "old");
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn update_checks_the_checksum_before_unpacking() {
+    // A truncated archive that couldn't be unpacked is reported by its checksum
+    let archive = fs::read(Path::new("tests/fixtures").join(RELEASE_ARCHIVE)).unwrap();
+    let s = sandbox()
+        .layout_file("v4")
+        .volta_latest_version(LATEST_VERSION)
+        .volta_release_file(
+            LATEST_VERSION,
+            "SHASUMS256.txt",
+            format!("{}  {}\n", RELEASE_CHECKSUM, RELEASE_ARCHIVE).as_bytes(),
+        )
+        .volta_release_file(
+            LATEST_VERSION,
+            RELEASE_ARCHIVE,
+            &archive[..archive.len() / 2],
+        )
+        .build();
+    let dir = install_dir(&s, "volta-install");
+
+    assert_that!(
+        s.volta("self update").env("VOLTA_INSTALL_DIR", &dir),
+        execs()
+            .with_status(ExitCode::NetworkError as i32)
+            .with_stderr_contains("[..]does not match its published checksum")
+            .with_stderr_does_not_contain("[..]unpack[..]")
+    );
+    assert_eq!(fs::read_to_string(dir.join("volta")).unwrap(), "old");
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn update_requires_the_archive_in_the_checksum_file() {
+    let s = release_sandbox(&format!(
+        "{}  volta-99.1.0-macos.tar.gz\n",
+        RELEASE_CHECKSUM
+    ))
+    .build();
+    let dir = install_dir(&s, "volta-install");
+
+    assert_that!(
+        s.volta("self update").env("VOLTA_INSTALL_DIR", &dir),
+        execs()
+            .with_status(ExitCode::NetworkError as i32)
+            .with_stderr_contains(format!(
+                "[..]Could not find the checksum for {}[..]",
+                RELEASE_ARCHIVE
+            ))
+    );
+    assert_eq!(fs::read_to_string(dir.join("volta")).unwrap(), "old");
+}
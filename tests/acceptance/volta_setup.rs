@@ -0,0 +1,497 @@
+//! Tests for `volta setup`, focused on shim regeneration, package link repair, and setting up
+//! a single shell
+
+use std::fs;
+use std::os::unix::fs::{symlink, MetadataExt};
+use std::path::{Path, PathBuf};
+
+use crate::support::sandbox::{sandbox, Sandbox};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+use test_support::paths;
+
+use volta_core::error::ExitCode;
+
+fn bin_config(name: &str, pkg: &str) -> String {
+    format!(
+        r#"{{
+  "name": "{}",
+  "package": "{}",
+  "version": "1.4.0",
+  "platform": {{
+    "node": "11.10.1",
+    "npm": "6.7.0",
+    "yarn": null
+  }},
+  "manager": "Npm"
+}}"#,
+        name, pkg
+    )
+}
+
+const PKG_CONFIG_COWSAY: &str = r#"{
+  "name": "cowsay",
+  "version": "1.4.0",
+  "platform": {
+    "node": "11.10.1",
+    "npm": "6.7.0",
+    "yarn": null
+  },
+  "bins": [
+    "cowsay"
+  ],
+  "manager": "Npm"
+}"#;
+
+const COWSAY_SOURCE: &str = ".volta/tools/image/packages/cowsay/lib/node_modules/cowsay";
+
+#[test]
+fn setup_regenerates_missing_shims() {
+    let s = sandbox()
+        .binary_config("cowsay", &bin_config("cowsay", "cowsay"))
+        .binary_config("cowthink", &bin_config("cowthink", "cowsay"))
+        .shim("cowsay")
+        .build();
+
+    assert_that!(s.volta("setup"), execs().with_status(0));
+
+    assert!(Sandbox::shim_exists("cowsay"));
+    assert!(Sandbox::shim_exists("cowthink"));
+    assert!(Sandbox::shim_exists("node"));
+}
+
+#[test]
+fn setup_leaves_current_shims_in_place() {
+    let s = sandbox()
+        .binary_config("cowsay", &bin_config("cowsay", "cowsay"))
+        .shim("cowsay")
+        .build();
+
+    let before = fs::symlink_metadata(Sandbox::shim_path("cowsay")).unwrap();
+
+    assert_that!(s.volta("setup"), execs().with_status(0));
+
+    let after = fs::symlink_metadata(Sandbox::shim_path("cowsay")).unwrap();
+    assert_eq!(before.ino(), after.ino());
+}
+
+#[test]
+fn setup_porcelain_reports_changed_shims() {
+    let s = sandbox()
+        .binary_config("cowsay", &bin_config("cowsay", "cowsay"))
+        .binary_config("cowthink", &bin_config("cowthink", "cowsay"))
+        .file(".bashrc", "# bash settings\n")
+        .env("HOME", &paths::home().to_string_lossy())
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    // The first run regenerates every shim as part of the layout migration
+    assert_that!(s.volta("--version"), execs().with_status(0));
+    fs::remove_file(Sandbox::shim_path("node")).unwrap();
+    fs::remove_file(Sandbox::shim_path("cowthink")).unwrap();
+    fs::write(Sandbox::shim_path("cowthink"), "an old shim script").unwrap();
+
+    // `cowsay` is already current, so only the other shims are reported
+    assert_that!(
+        s.volta("setup --porcelain --shell bash"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("profile updated [..]/.bashrc")
+            .with_stdout_contains("shim replaced [..]/.volta/bin/cowthink")
+            .with_stdout_contains("shim created [..]/.volta/bin/node")
+            .with_stdout_does_not_contain("[..]/.volta/bin/cowsay")
+            .with_stdout_does_not_contain("[..]Setup complete[..]")
+            .with_stderr_contains("[..]Setup complete[..]")
+    );
+}
+
+#[test]
+fn setup_continues_past_shims_that_cannot_be_replaced() {
+    // A directory in place of the shim can't be removed as a file, regardless of the privileges
+    // of the user running the tests, so it stands in for a shim the user isn't permitted to modify
+    let s = sandbox()
+        .binary_config("cowsay", &bin_config("cowsay", "cowsay"))
+        .binary_config("cowthink", &bin_config("cowthink", "cowsay"))
+        .file(".volta/bin/cowsay/blocker", "")
+        .env("VOLTA_LOGLEVEL", "warn")
+        .build();
+
+    assert_that!(
+        s.volta("setup"),
+        execs()
+            .with_status(ExitCode::FileSystemError as i32)
+            .with_stderr_contains("[..]warning:[..]Could not regenerate shim [..]cowsay: [..]")
+            .with_stderr_contains("[..]error: Could not regenerate 1 shim:")
+            .with_stderr_contains("    [..]cowsay")
+    );
+
+    // The rest of the shims are still regenerated
+    assert!(Sandbox::shim_exists("cowthink"));
+    assert!(Sandbox::shim_exists("node"));
+
+    // Once the problem is fixed, running setup again completes the set
+    fs::remove_dir_all(Sandbox::shim_path("cowsay")).unwrap();
+
+    assert_that!(s.volta("setup"), execs().with_status(0));
+    assert!(Sandbox::shim_exists("cowsay"));
+}
+
+#[test]
+fn setup_regenerates_shims_in_parallel() {
+    let names: Vec<String> = (0..24).map(|n| format!("cow{}", n)).collect();
+    let mut builder = sandbox().file(".volta/settings.json", r#"{ "shims": { "jobs": 4 } }"#);
+    for name in &names {
+        builder = builder.binary_config(name, &bin_config(name, "cowsay"));
+    }
+    let s = builder.build();
+
+    assert_that!(s.volta("setup"), execs().with_status(0));
+
+    for name in &names {
+        assert!(Sandbox::shim_exists(name), "missing shim for {}", name);
+    }
+    assert!(Sandbox::shim_exists("node"));
+}
+
+#[test]
+fn parallel_setup_continues_past_a_shim_that_cannot_be_replaced() {
+    let names: Vec<String> = (0..24).map(|n| format!("cow{}", n)).collect();
+    let mut builder = sandbox()
+        .file(".volta/settings.json", r#"{ "shims": { "jobs": 4 } }"#)
+        .file(".volta/bin/cow12/blocker", "")
+        .env("VOLTA_LOGLEVEL", "warn");
+    for name in &names {
+        builder = builder.binary_config(name, &bin_config(name, "cowsay"));
+    }
+    let s = builder.build();
+
+    assert_that!(
+        s.volta("setup"),
+        execs()
+            .with_status(ExitCode::FileSystemError as i32)
+            .with_stderr_contains("[..]error: Could not regenerate 1 shim:")
+            .with_stderr_contains("    [..]cow12")
+    );
+
+    for name in names.iter().filter(|name| *name != "cow12") {
+        assert!(Sandbox::shim_exists(name), "missing shim for {}", name);
+    }
+}
+
+#[test]
+fn relink_packages_repairs_dangling_links() {
+    // With a current layout, so that the migration doesn't repair the link first
+    let s = sandbox()
+        .layout_file("v4")
+        .package_config("cowsay", PKG_CONFIG_COWSAY)
+        .file(&format!("{}/package.json", COWSAY_SOURCE), "{}")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    // A link left behind by a Volta directory that was restored to a different location
+    let link = Sandbox::home_path(".volta/tools/shared/cowsay");
+    fs::create_dir_all(link.parent().unwrap()).unwrap();
+    symlink("/old/home/.volta/tools/image/packages/cowsay", &link).unwrap();
+
+    assert_that!(
+        s.volta("setup --relink-packages"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]cowsay: repaired broken link")
+    );
+
+    assert!(fs::read_link(&link).unwrap().is_relative());
+    assert_eq!(
+        fs::canonicalize(&link).unwrap(),
+        fs::canonicalize(Sandbox::home_path(COWSAY_SOURCE)).unwrap()
+    );
+}
+
+#[test]
+fn relink_packages_reports_missing_images() {
+    let s = sandbox()
+        .package_config("cowsay", PKG_CONFIG_COWSAY)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("setup --relink-packages"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]cowsay: the package is missing[..]")
+    );
+}
+
+const FISH_SNIPPET: &str =
+    "set -gx VOLTA_HOME \"$HOME/.volta\"\nset -gx PATH \"$VOLTA_HOME/bin\" $PATH\n";
+
+/// Lists every file and directory in the sandbox home, to check that nothing was changed
+fn list_home() -> Vec<PathBuf> {
+    fn visit(dir: &Path, entries: &mut Vec<PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() && !path.is_symlink() {
+                visit(&path, entries);
+            }
+            entries.push(path);
+        }
+    }
+
+    let mut entries = Vec::new();
+    visit(&paths::home(), &mut entries);
+    entries.sort();
+    entries
+}
+
+#[test]
+fn print_shell_snippet_changes_no_files() {
+    let s = sandbox()
+        .binary_config("cowsay", &bin_config("cowsay", "cowsay"))
+        .file(".bashrc", "# bash settings\n")
+        .env("HOME", &paths::home().to_string_lossy())
+        .build();
+    // The first command migrates the Volta directory to the current layout, so the snapshot is
+    // taken once that is done. A missing shim shows whether the shims were regenerated.
+    assert_that!(s.volta("--version"), execs().with_status(0));
+    fs::remove_file(Sandbox::shim_path("cowsay")).unwrap();
+    let before = list_home();
+
+    assert_that!(
+        s.volta("setup --print --shell fish"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout(FISH_SNIPPET)
+    );
+    assert_that!(
+        s.volta("setup --print --shell bash"),
+        execs().with_status(ExitCode::Success as i32).with_stdout(
+            "export VOLTA_HOME=\"$HOME/.volta\"\nexport PATH=\"$VOLTA_HOME/bin:$PATH\"\n"
+        )
+    );
+
+    assert_eq!(list_home(), before);
+}
+
+#[test]
+fn print_requires_shell() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("setup --print"),
+        execs()
+            .with_status(2)
+            .with_stderr_contains("[..]--shell <shell>[..]")
+    );
+}
+
+#[test]
+fn print_unsupported_shell_lists_supported_shells() {
+    let s = sandbox()
+        .env("HOME", &paths::home().to_string_lossy())
+        .build();
+    assert_that!(s.volta("--version"), execs().with_status(0));
+    let before = list_home();
+
+    assert_that!(
+        s.volta("setup --print --shell nu"),
+        execs().with_status(2).with_stderr_contains(
+            "[..]unsupported shell 'nu', the supported shells are: bash, zsh, fish[..]"
+        )
+    );
+    assert_eq!(list_home(), before);
+}
+
+#[test]
+fn shell_only_modifies_its_profile() {
+    let s = sandbox()
+        .binary_config("cowsay", &bin_config("cowsay", "cowsay"))
+        .file(".bashrc", "# bash settings\n")
+        .file(".zshenv", "# zsh settings\n")
+        .env("HOME", &paths::home().to_string_lossy())
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("setup --shell fish"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("Added Volta to the fish profile [..]config.fish")
+    );
+
+    let fish_config = Sandbox::home_path(".config/fish/config.fish");
+    assert_eq!(
+        fs::read_to_string(fish_config).unwrap(),
+        format!("\n{}", FISH_SNIPPET)
+    );
+    assert_eq!(
+        fs::read_to_string(Sandbox::home_path(".bashrc")).unwrap(),
+        "# bash settings\n"
+    );
+    assert_eq!(
+        fs::read_to_string(Sandbox::home_path(".zshenv")).unwrap(),
+        "# zsh settings\n"
+    );
+    assert!(!Sandbox::path_exists(".profile"));
+    assert!(Sandbox::shim_exists("cowsay"));
+}
+
+#[test]
+fn shell_replaces_previous_snippet() {
+    let s = sandbox()
+        .file(
+            ".config/fish/config.fish",
+            "set -gx VOLTA_HOME \"/old/volta\"\nalias ll 'ls -l'\n",
+        )
+        .env("HOME", &paths::home().to_string_lossy())
+        .build();
+
+    assert_that!(
+        s.volta("setup --shell fish"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_eq!(
+        fs::read_to_string(Sandbox::home_path(".config/fish/config.fish")).unwrap(),
+        format!("alias ll 'ls -l'\n{}", FISH_SNIPPET)
+    );
+}
+
+const NOTION_PLATFORM: &str = r#"{"node":{"runtime":"10.15.0","npm":"6.4.1"},"yarn":"1.12.3"}"#;
+
+const BASHRC_WITH_NOTION: &str = r#"# bash settings
+export NOTION_HOME="$HOME/.notion"
+[ -s "$NOTION_HOME/load.sh" ] && \. "$NOTION_HOME/load.sh"
+export PATH="$HOME/.notion/bin:$HOME/go/bin:$PATH"
+"#;
+
+/// A sandbox with the directory of Notion, the previous name of Volta, and its shims on the PATH
+fn notion_sandbox() -> Sandbox {
+    let path =
+        std::env::join_paths([paths::home().join(".notion/bin"), "/usr/bin".into()]).unwrap();
+
+    sandbox()
+        .file(".notion/tools/user/platform.json", NOTION_PLATFORM)
+        .file(".notion/tools/user/packages/cowsay.json", PKG_CONFIG_COWSAY)
+        .file(".notion/bin/node", "")
+        .file(".bashrc", BASHRC_WITH_NOTION)
+        .env("HOME", &paths::home().to_string_lossy())
+        .env("PATH", &path.to_string_lossy())
+        .env("PROFILE", "")
+        .env("ZDOTDIR", "")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build()
+}
+
+#[test]
+fn migrate_notion_requires_confirmation() {
+    let s = notion_sandbox();
+
+    assert_that!(
+        s.volta("setup --migrate-notion"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stdout_contains("    move [..]/.notion to [..]/.notion.bak")
+            .with_stdout_contains("    remove Notion from [..]/.bashrc")
+            .with_stderr_contains("[..]the migration was not confirmed.")
+    );
+
+    assert!(Sandbox::path_exists(".notion/bin/node"));
+    assert!(!Sandbox::path_exists(".notion.bak"));
+    assert!(
+        !fs::read_to_string(Sandbox::home_path(".volta/tools/user/platform.json"))
+            .unwrap_or_default()
+            .contains("10.15.0")
+    );
+    assert_eq!(
+        fs::read_to_string(Sandbox::home_path(".bashrc")).unwrap(),
+        BASHRC_WITH_NOTION
+    );
+}
+
+#[test]
+fn migrate_notion_renames_directory_and_imports_platform() {
+    let s = notion_sandbox();
+
+    assert_that!(
+        s.volta("setup --migrate-notion --yes"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(
+                "[..]Set your default platform to node@10.15.0, npm@6.4.1, yarn@1.12.3"
+            )
+            .with_stdout_contains("[..]Moved [..]/.notion to [..]/.notion.bak[..]")
+            .with_stdout_contains(
+                "[..]Removed Notion from [..]/.bashrc, the previous version is saved as [..]/.bashrc.notion.bak"
+            )
+            .with_stdout_contains("    volta install cowsay")
+    );
+
+    assert!(!Sandbox::path_exists(".notion"));
+    assert!(Sandbox::path_exists(".notion.bak/bin/node"));
+    assert!(Sandbox::path_exists(
+        ".notion.bak/tools/user/packages/cowsay.json"
+    ));
+    assert!(Sandbox::read_default_platform().contains("\"runtime\": \"10.15.0\""));
+    assert_eq!(
+        fs::read_to_string(Sandbox::home_path(".bashrc")).unwrap(),
+        "# bash settings\nexport PATH=\"$HOME/go/bin:$PATH\"\n"
+    );
+    assert_eq!(
+        fs::read_to_string(Sandbox::home_path(".bashrc.notion.bak")).unwrap(),
+        BASHRC_WITH_NOTION
+    );
+}
+
+#[test]
+fn migrate_notion_keeps_existing_backup() {
+    let s = sandbox()
+        .file(".notion/bin/node", "")
+        .file(".notion.bak/bin/node", "")
+        .env("HOME", &paths::home().to_string_lossy())
+        .env("PROFILE", "")
+        .env("ZDOTDIR", "")
+        .build();
+
+    assert_that!(
+        s.volta("setup --migrate-notion --yes"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert!(!Sandbox::path_exists(".notion"));
+    assert!(Sandbox::path_exists(".notion.bak/bin/node"));
+    assert!(Sandbox::path_exists(".notion.bak.1/bin/node"));
+}
+
+#[test]
+fn migrate_notion_without_notion_changes_nothing() {
+    let s = sandbox().env("VOLTA_LOGLEVEL", "info").build();
+
+    assert_that!(
+        s.volta("setup --migrate-notion --yes"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("No Notion directory or shims were found[..]")
+    );
+}
+
+#[test]
+fn mutating_commands_warn_about_notion_weekly() {
+    let s = notion_sandbox();
+
+    assert_that!(
+        s.volta("uninstall cowsay"),
+        execs().with_stderr_contains(
+            "[..]Found [..]/.notion, the directory of Notion (the previous name of Volta), and its shims are on your PATH: [..]/.notion/bin"
+        )
+        .with_stderr_contains("[..]volta setup --migrate-notion[..]")
+    );
+    assert_that!(
+        s.volta("uninstall cowsay"),
+        execs().with_stderr_does_not_contain("[..]Notion[..]")
+    );
+    assert_that!(
+        s.volta("which node"),
+        execs().with_stderr_does_not_contain("[..]Notion[..]")
+    );
+}
@@ -3,7 +3,11 @@
 use crate::support::sandbox::{sandbox, Sandbox};
 use hamcrest2::assert_that;
 use hamcrest2::prelude::*;
+use node_semver::Version;
 use test_support::matchers::execs;
+use test_support::paths;
+use volta_core::error::ExitCode;
+use volta_core::tool::Node;
 
 const PKG_CONFIG_BASIC: &str = r#"{
   "name": "cowsay",
@@ -32,6 +36,37 @@ const PKG_CONFIG_NO_BINS: &str = r#"{
   "manager": "Npm"
 }"#;
 
+const PKG_CONFIG_VOLTA_BIN: &str = r#"{
+  "name": "cowsay",
+  "version": "1.4.0",
+  "platform": {
+    "node": "11.10.1",
+    "npm": "6.7.0",
+    "yarn": null
+  },
+  "bins": [
+    "cowsay",
+    "volta"
+  ],
+  "manager": "Npm"
+}"#;
+
+const PKG_CONFIG_NO_SHIMS: &str = r#"{
+  "name": "cowsay",
+  "version": "1.4.0",
+  "platform": {
+    "node": "11.10.1",
+    "npm": "6.7.0",
+    "yarn": null
+  },
+  "bins": [
+    "cowsay",
+    "cowthink"
+  ],
+  "manager": "Npm",
+  "shims": false
+}"#;
+
 fn bin_config(name: &str) -> String {
     format!(
         r#"{{
@@ -84,6 +119,9 @@ fn uninstall_package_basic() {
             .with_status(0)
             .with_stdout_contains("Removed executable 'cowsay' installed by 'cowsay'")
             .with_stdout_contains("Removed executable 'cowthink' installed by 'cowsay'")
+            .with_stdout_contains("Removed shim: [..]cowthink")
+            .with_stdout_contains("Removed package image: [..]cowsay")
+            .with_stdout_contains("Removed 6 entries (approximately [..])")
             .with_stdout_contains("[..]package 'cowsay' uninstalled")
     );
 
@@ -96,6 +134,155 @@ fn uninstall_package_basic() {
     assert!(!Sandbox::package_image_exists("cowsay"));
 }
 
+#[test]
+fn uninstall_package_without_shims() {
+    // the `cowsay` shim belongs to another package, since `cowsay` was installed without shims
+    let other_bin_config =
+        bin_config("cowsay").replace(r#""package": "cowsay""#, r#""package": "other-cowsay""#);
+    let s = sandbox()
+        .package_config("cowsay", PKG_CONFIG_NO_SHIMS)
+        .binary_config("cowsay", &other_bin_config)
+        .shim("cowsay")
+        .package_image("cowsay", "1.4.0", None)
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("uninstall cowsay"),
+        execs()
+            .with_status(0)
+            .with_stdout_does_not_contain("[..]Removed executable[..]")
+            .with_stdout_does_not_contain("[..]Removed shim[..]")
+            .with_stdout_contains("Removed package image: [..]cowsay")
+            .with_stdout_contains("[..]package 'cowsay' uninstalled")
+    );
+
+    assert!(!Sandbox::package_config_exists("cowsay"));
+    assert!(!Sandbox::package_image_exists("cowsay"));
+    assert!(Sandbox::bin_config_exists("cowsay"));
+    assert!(Sandbox::shim_exists("cowsay"));
+}
+
+#[test]
+fn uninstall_package_porcelain() {
+    // the porcelain output is a stable format, so it is matched in full
+    let s = sandbox()
+        .package_config("cowsay", PKG_CONFIG_BASIC)
+        .binary_config("cowsay", &bin_config("cowsay"))
+        .binary_config("cowthink", &bin_config("cowthink"))
+        .shim("cowsay")
+        .shim("cowthink")
+        .package_image("cowsay", "1.4.0", None)
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("uninstall --porcelain cowsay"),
+        execs()
+            .with_status(0)
+            .with_stdout(
+                "\
+config removed [..]/.volta/tools/user/packages/cowsay.json
+config removed [..]/.volta/tools/user/bins/cowsay.json
+shim removed [..]/.volta/bin/cowsay[..]
+config removed [..]/.volta/tools/user/bins/cowthink.json
+shim removed [..]/.volta/bin/cowthink[..]
+image removed [..]/.volta/tools/image/packages/cowsay"
+            )
+            .with_stderr_contains("[..]package 'cowsay' uninstalled")
+    );
+
+    assert!(!Sandbox::package_config_exists("cowsay"));
+    assert!(!Sandbox::shim_exists("cowthink"));
+}
+
+#[test]
+fn uninstall_porcelain_conflicts_with_dry_run() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("uninstall --porcelain --dry-run cowsay"),
+        execs()
+            .with_status(2)
+            .with_stderr_contains("[..]cannot be used with[..]")
+    );
+}
+
+#[test]
+fn uninstall_package_keeps_volta_executable() {
+    // a package config from an older install may list a `volta` bin, but the file at that path is
+    // Volta's own executable rather than a shim
+    let s = sandbox()
+        .package_config("cowsay", PKG_CONFIG_VOLTA_BIN)
+        .binary_config("cowsay", &bin_config("cowsay"))
+        .binary_config("volta", &bin_config("volta"))
+        .shim("cowsay")
+        .file(".volta/bin/volta", "the real Volta")
+        .package_image("cowsay", "1.4.0", None)
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("uninstall cowsay"),
+        execs()
+            .with_status(0)
+            .with_stdout_does_not_contain("Removed shim: [..]volta")
+            .with_stdout_contains("[..]package 'cowsay' uninstalled")
+    );
+
+    assert!(!Sandbox::bin_config_exists("volta"));
+    assert!(!Sandbox::shim_exists("cowsay"));
+    assert!(Sandbox::path_exists(".volta/bin/volta"));
+}
+
+#[test]
+fn uninstall_package_dry_run() {
+    // a dry run reports the same entries as a real uninstall, but leaves them all in place
+    let s = sandbox()
+        .package_config("cowsay", PKG_CONFIG_BASIC)
+        .binary_config("cowsay", &bin_config("cowsay"))
+        .binary_config("cowthink", &bin_config("cowthink"))
+        .shim("cowsay")
+        .shim("cowthink")
+        .package_image("cowsay", "1.4.0", None)
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("uninstall --dry-run cowsay"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Would remove executable 'cowsay' installed by 'cowsay'")
+            .with_stdout_contains("Would remove executable 'cowthink' installed by 'cowsay'")
+            .with_stdout_contains("Would remove package config: [..]cowsay.json")
+            .with_stdout_contains("Would remove executable config: [..]cowthink.json")
+            .with_stdout_contains("Would remove shim: [..]cowthink")
+            .with_stdout_contains("Would remove package image: [..]cowsay")
+            .with_stdout_contains("Would remove 6 entries (approximately [..])")
+            .with_stdout_contains("[..]dry run, package 'cowsay' was not uninstalled")
+    );
+
+    // check that nothing is deleted
+    assert!(Sandbox::package_config_exists("cowsay"));
+    assert!(Sandbox::bin_config_exists("cowsay"));
+    assert!(Sandbox::bin_config_exists("cowthink"));
+    assert!(Sandbox::shim_exists("cowsay"));
+    assert!(Sandbox::shim_exists("cowthink"));
+    assert!(Sandbox::package_image_exists("cowsay"));
+}
+
+#[test]
+fn uninstall_nonexistent_pkg_dry_run() {
+    let s = sandbox().env(VOLTA_LOGLEVEL, "info").build();
+
+    assert_that!(
+        s.volta("uninstall --dry-run cowsay"),
+        execs()
+            .with_status(0)
+            .with_stderr_contains("[..]No package 'cowsay' found to uninstall")
+    );
+}
+
 // The setup here is the same as the above, but here we check to make sure that
 // if the user supplies a version, we error correctly.
 #[test]
@@ -205,13 +392,157 @@ fn uninstall_package_orphaned_bins() {
     assert!(!Sandbox::shim_exists("cowthink"));
 }
 
+const PLATFORM_NODE_ONLY: &str = r#"{
+    "node": {
+        "runtime": "10.99.1040",
+        "npm": null
+    }
+}"#;
+
+fn node_archive(version: &str) -> String {
+    format!(
+        ".volta/tools/inventory/node/{}",
+        Node::archive_filename(&Version::parse(version).unwrap())
+    )
+}
+
 #[test]
-fn uninstall_runtime() {
-    let s = sandbox().build();
+fn uninstall_node_requires_version() {
+    let s = sandbox()
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .setup_node_binary("6.19.62", "3.10.1066", "")
+        .build();
+
     assert_that!(
         s.volta("uninstall node"),
         execs()
-            .with_status(1)
-            .with_stderr_contains("[..]error: Uninstalling node is not supported yet.")
-    )
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Please specify the version of Node to uninstall[..]")
+            .with_stderr_contains("    6.19.62")
+            .with_stderr_contains("    10.99.1040")
+    );
+}
+
+#[test]
+fn uninstall_node_version() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE_ONLY)
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .setup_node_binary("6.19.62", "3.10.1066", "")
+        .file(&node_archive("6.19.62"), "")
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("uninstall node@6.19.62"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Removed Node image: [..]")
+            .with_stdout_contains("Removed inventory file: [..]")
+            .with_stdout_contains("[..]success: node@6.19.62 uninstalled")
+    );
+
+    assert!(!Sandbox::node_image_exists("6.19.62"));
+    assert!(!s.node_inventory_archive_exists(&Version::parse("6.19.62").unwrap()));
+    assert!(!paths::home()
+        .join(".volta/tools/inventory/node/node-v6.19.62-npm")
+        .exists());
+    assert!(Sandbox::node_image_exists("10.99.1040"));
+}
+
+#[test]
+fn uninstall_node_version_porcelain() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE_ONLY)
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .setup_node_binary("6.19.62", "3.10.1066", "")
+        .file(&node_archive("6.19.62"), "")
+        .build();
+
+    assert_that!(
+        s.volta("uninstall --porcelain node@6.19.62"),
+        execs().with_status(0).with_stdout(
+            "\
+image removed [..]/.volta/tools/image/node/6.19.62
+inventory removed [..]/.volta/tools/inventory/node/node-v6.19.62-[..]
+inventory removed [..]/.volta/tools/inventory/node/node-v6.19.62-npm"
+        )
+    );
+}
+
+#[test]
+fn uninstall_node_version_dry_run() {
+    let s = sandbox()
+        .setup_node_binary("6.19.62", "3.10.1066", "")
+        .file(&node_archive("6.19.62"), "")
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("uninstall --dry-run node@6.19.62"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Would remove Node image: [..]")
+            .with_stdout_contains("[..]dry run, node@6.19.62 was not uninstalled")
+    );
+
+    assert!(Sandbox::node_image_exists("6.19.62"));
+    assert!(s.node_inventory_archive_exists(&Version::parse("6.19.62").unwrap()));
+}
+
+#[test]
+fn uninstall_default_node_fails() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE_ONLY)
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .build();
+
+    assert_that!(
+        s.volta("uninstall node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+                "[..]Could not uninstall node@10.99.1040 because it is your default Node version."
+            )
+    );
+
+    assert!(Sandbox::node_image_exists("10.99.1040"));
+}
+
+#[test]
+fn uninstall_project_node_fails() {
+    let s = sandbox()
+        .package_json(
+            r#"{
+  "name": "project",
+  "volta": {
+    "node": "6.19.62"
+  }
+}"#,
+        )
+        .setup_node_binary("6.19.62", "3.10.1066", "")
+        .build();
+
+    assert_that!(
+        s.volta("uninstall node@6.19.62"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains(
+            "[..]Could not uninstall node@6.19.62 because it is used by the current project[..]"
+        )
+    );
+
+    assert!(Sandbox::node_image_exists("6.19.62"));
+}
+
+#[test]
+fn uninstall_node_not_installed() {
+    let s = sandbox().env(VOLTA_LOGLEVEL, "info").build();
+
+    assert_that!(
+        s.volta("uninstall node@6.19.62"),
+        execs()
+            .with_status(0)
+            .with_stderr_contains("[..]No node@6.19.62 found to uninstall")
+    );
 }
@@ -0,0 +1,144 @@
+use std::path::PathBuf;
+
+use crate::support::sandbox::{sandbox, Sandbox};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use mockito::{mock, Matcher};
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const PLATFORM_NODE: &str = r#"{
+    "node":{
+        "runtime":"11.10.1",
+        "npm":"6.7.0"
+    }
+}"#;
+
+const PACKAGE_JSON: &str = r#"{
+  "name": "test-package",
+  "volta": {
+    "node": "10.99.1040",
+    "yarn": "1.23.483"
+  }
+}"#;
+
+#[test]
+fn json_for_project_tool() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .package_json(PACKAGE_JSON)
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .setup_yarn_binary("1.23.483", "")
+        .build();
+
+    assert_that!(
+        s.volta("which --json node"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(r#"  "jsonVersion": 1,"#)
+            .with_stdout_contains(r#"  "path": "[..]10.99.1040[..]node[..]","#)
+            .with_stdout_contains(r#"  "installed": true,"#)
+            .with_stdout_contains(r#"  "source": "project","#)
+            .with_stdout_contains(r#"      "version": "10.99.1040","#)
+            .with_stdout_contains(r#"      "version": "6.2.26","#)
+            .with_stdout_contains(r#"      "version": "1.23.483","#)
+            .with_stdout_contains(r#"    "pnpm": null,"#)
+    );
+}
+
+#[test]
+fn json_for_default_tool() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", "")
+        .build();
+
+    assert_that!(
+        s.volta("which --json node"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(r#"  "path": "[..]11.10.1[..]node[..]","#)
+            .with_stdout_contains(r#"  "source": "default","#)
+            .with_stdout_contains(r#"      "version": "11.10.1","#)
+            .with_stdout_contains(r#"      "version": "6.7.0","#)
+            .with_stdout_contains(r#"    "yarn": null"#)
+    );
+}
+
+#[test]
+fn json_for_missing_tool() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", "")
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .build();
+
+    assert_that!(
+        s.volta("which --json not-a-real-tool"),
+        execs()
+            .with_status(ExitCode::UnknownError as i32)
+            .with_stdout_contains(r#"  "jsonVersion": 1,"#)
+            .with_stdout_contains(r#"  "error": "Could not find `not-a-real-tool`""#)
+            .with_stderr("")
+    );
+}
+
+#[test]
+fn json_for_error_includes_slug() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .package_json("{ not json")
+        .build();
+
+    assert_that!(
+        s.volta("which --json node"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stdout_contains(r#"  "slug": "package-parse-error""#)
+            .with_stderr("")
+    );
+}
+
+#[test]
+fn unfetched_project_node_is_not_downloaded() {
+    let requests = mock("GET", Matcher::Any).expect(0).create();
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .package_json(PACKAGE_JSON)
+        .setup_node_binary("11.10.1", "6.7.0", "")
+        .build();
+
+    assert_that!(
+        s.volta("which node"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(
+                "[..]10.99.1040[..]node[..] (not yet installed — run 'volta install'/'volta fetch' for node@10.99.1040)"
+            )
+    );
+    requests.assert();
+    assert!(!Sandbox::path_exists(".volta/tools/image/node/10.99.1040"));
+}
+
+#[test]
+fn json_for_unfetched_project_tool() {
+    let requests = mock("GET", Matcher::Any).expect(0).create();
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .package_json(PACKAGE_JSON)
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .build();
+
+    assert_that!(
+        s.volta("which --json yarn"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(r#"  "path": "[..]1.23.483[..]yarn[..]","#)
+            .with_stdout_contains(r#"  "installed": false,"#)
+            .with_stdout_contains(r#"  "source": "project","#)
+            .with_stdout_contains(r#"      "version": "1.23.483","#)
+    );
+    requests.assert();
+    assert!(!Sandbox::path_exists(".volta/tools/image/yarn/1.23.483"));
+}
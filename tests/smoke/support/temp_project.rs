@@ -112,6 +112,7 @@ impl TempProjectBuilder {
 
         // make sure these directories exist and are empty
         node_cache_dir(self.root()).ensure_empty();
+        yarn_cache_dir(self.root()).ensure_empty();
         volta_bin_dir(self.root()).ensure_empty();
         node_inventory_dir(self.root()).ensure_empty();
         yarn_inventory_dir(self.root()).ensure_empty();
@@ -235,6 +236,9 @@ fn cache_dir(root: PathBuf) -> PathBuf {
 fn node_cache_dir(root: PathBuf) -> PathBuf {
     cache_dir(root).join("node")
 }
+fn yarn_cache_dir(root: PathBuf) -> PathBuf {
+    cache_dir(root).join("yarn")
+}
 fn package_json_file(mut root: PathBuf) -> PathBuf {
     root.push("package.json");
     root
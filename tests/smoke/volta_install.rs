@@ -172,6 +172,35 @@ fn install_package() {
     );
 }
 
+#[test]
+fn install_package_porcelain() {
+    let p = temp_project().build();
+
+    assert_that!(p.volta("install node@14.11.0"), execs().with_status(0));
+
+    assert_that!(
+        p.volta("install --porcelain cowsay@1.4.0"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("image created [..]/tools/image/packages/cowsay")
+            .with_stdout_contains("link created [..]/tools/shared/cowsay")
+            .with_stdout_contains("shim created [..]/bin/cowsay[..]")
+            .with_stdout_contains("config created [..]/tools/user/bins/cowsay.json")
+            .with_stdout_contains("config created [..]/tools/user/packages/cowsay.json")
+            .with_stdout_does_not_contain("[..]installed cowsay@1.4.0[..]")
+    );
+
+    // Installing it again replaces the image and configs, but the shims are already current
+    assert_that!(
+        p.volta("install --porcelain cowsay@1.4.0"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("image replaced [..]/tools/image/packages/cowsay")
+            .with_stdout_contains("config replaced [..]/tools/user/packages/cowsay.json")
+            .with_stdout_does_not_contain("shim [..]")
+    );
+}
+
 #[test]
 fn install_package_concurrent() {
     let p = temp_project().build();